@@ -9,11 +9,14 @@
 
 use crate::client::{AnthropicClient, ClientError, AgenticEvent};
 use crate::conversation::Conversation;
-use crate::tools::{Tool, ToolExecutor, all_tools};
+use crate::plan::AgentMode;
+use crate::tools::{AutoApproveGuard, CallOptions, PreviewFirst, Tool, ToolExecutor, ToolUse, DRY_RUN_TOOLS};
 use aurora_core::plugin::{AgentDefinition, AgentInfo};
 use aurora_core::hooks::{HookExecutor, ToolCallContext, AfterToolCallContext};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
 use thiserror::Error;
 
 /// Errors that can occur during agent operations
@@ -107,34 +110,162 @@ impl AgentContext {
     }
 }
 
+/// Interactive approval of individual tool calls
+///
+/// Implementations ask a user (or an external client) whether a tool call may
+/// run. Returning `false` rejects the call without executing it.
+pub trait ToolApproval: Send + Sync {
+    /// Decide whether the tool call may run
+    fn approve<'a>(&'a self, tool_use: &'a ToolUse) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>>;
+}
+
 /// Filtered tool executor that enforces agent tool permissions
 pub struct FilteredToolExecutor {
-    /// Base tool executor
-    base_executor: ToolExecutor,
+    /// Base tool executor, shared with other conversations
+    base_executor: Arc<ToolExecutor>,
     /// Tools that are allowed (if empty, all tools except denied are allowed)
     allowed_tools: Vec<String>,
     /// Tools that are explicitly denied
     denied_tools: Vec<String>,
+    /// Optional per-call approval
+    approval: Option<Arc<dyn ToolApproval>>,
+    /// Asked only before actions that need the user's consent
+    consent: Option<Arc<dyn ToolApproval>>,
+    /// Session that tool call metrics are attributed to
+    session: Option<String>,
+    /// Plan mode restricts the agent to read and analysis tools
+    mode: AgentMode,
+    /// Runs calls without approval, within the policy's blast-radius limits
+    auto_approve: Option<AutoApproveGuard>,
+    /// Calls previewed as dry runs and not yet applied, by [`preview_key`]
+    previewed: Mutex<HashSet<String>>,
+}
+
+/// Appended to a call that ran as a dry run because the policy requires one
+const PREVIEW_REQUIRED_NOTE: &str =
+    "\n\nThis project previews changes before applying them. Call the tool again with the same input to apply it.";
+
+/// Identity of a call for matching a preview to its application: the tool
+/// name and its input without `dry_run`, with object keys sorted
+fn preview_key(tool_use: &ToolUse) -> String {
+    fn sorted(value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                serde_json::Value::Object(entries.into_iter().map(|(k, v)| (k.clone(), sorted(v))).collect())
+            }
+            serde_json::Value::Array(items) => serde_json::Value::Array(items.iter().map(sorted).collect()),
+            value => value.clone(),
+        }
+    }
+
+    let mut input = tool_use.input.clone();
+    if let Some(map) = input.as_object_mut() {
+        map.remove("dry_run");
+    }
+    format!("{}:{}", tool_use.name, sorted(&input))
 }
 
 impl FilteredToolExecutor {
     /// Create a new filtered tool executor
     pub fn new(
-        base_executor: ToolExecutor,
+        base_executor: impl Into<Arc<ToolExecutor>>,
         allowed_tools: Vec<String>,
         denied_tools: Vec<String>,
     ) -> Self {
         Self {
-            base_executor,
+            base_executor: base_executor.into(),
             allowed_tools,
             denied_tools,
+            approval: None,
+            consent: None,
+            session: None,
+            mode: AgentMode::default(),
+            auto_approve: None,
+            previewed: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Require approval for each permitted tool call
+    pub fn with_approval(mut self, approval: Arc<dyn ToolApproval>) -> Self {
+        self.approval = Some(approval);
+        self
+    }
+
+    /// Ask `consent` before actions that need the user's consent, such as
+    /// reading from a cluster or applying infrastructure changes, while
+    /// other calls run without asking
+    ///
+    /// Ignored when [`with_approval`](Self::with_approval) is set, as that
+    /// broker is asked instead.
+    pub fn with_consent(mut self, consent: Arc<dyn ToolApproval>) -> Self {
+        self.consent = Some(consent);
+        self
+    }
+
+    /// Attribute tool call metrics to `session`
+    pub fn with_session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Run calls without asking for approval, refusing those that exceed the
+    /// base executor policy's auto-approve limits
+    ///
+    /// The limits count over the life of this executor, so create one per turn.
+    /// Takes precedence over [`with_approval`](Self::with_approval).
+    pub fn with_auto_approve(mut self) -> Self {
+        self.auto_approve = Some(AutoApproveGuard::new(
+            self.base_executor.working_directory(),
+            self.base_executor.policy().auto_approve,
+        ));
+        self
+    }
+
+    /// Restrict the tools to those allowed in `mode`
+    pub fn with_mode(mut self, mode: AgentMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Whether the policy makes calls that change files run as a preview first
+    fn requires_preview(&self) -> bool {
+        match self.base_executor.policy().preview_first {
+            PreviewFirst::Never => false,
+            PreviewFirst::AutoApprove => self.auto_approve.is_some(),
+            PreviewFirst::Always => true,
+        }
+    }
+
+    /// Whether `tool_use` must run as a dry run because it was not previewed
+    ///
+    /// A dry run records the call as previewed; applying a previewed call
+    /// uses up its preview. A call run as a forced preview counts as
+    /// previewed, so repeating it applies the change.
+    fn needs_preview(&self, tool_use: &ToolUse) -> bool {
+        if !DRY_RUN_TOOLS.contains(&tool_use.name.as_str()) || !tool_use.input.is_object() || !self.requires_preview() {
+            return false;
+        }
+        let key = preview_key(tool_use);
+        let mut previewed = self.previewed.lock().unwrap_or_else(PoisonError::into_inner);
+        if tool_use.is_dry_run() {
+            previewed.insert(key);
+            false
+        } else {
+            !previewed.remove(&key) && previewed.insert(key)
         }
     }
 
     /// Check if a tool is allowed for this agent
     pub fn is_tool_allowed(&self, tool_name: &str) -> bool {
-        // If tool is explicitly denied, reject
-        if self.denied_tools.contains(&tool_name.to_string()) {
+        // If tool is explicitly denied, disabled for every agent, or not
+        // available in the current mode, reject
+        let capabilities = self.base_executor.capabilities(tool_name);
+        if self.denied_tools.contains(&tool_name.to_string())
+            || !self.base_executor.policy().permits(tool_name, capabilities)
+            || !self.mode.permits(capabilities)
+        {
             return false;
         }
 
@@ -149,7 +280,8 @@ impl FilteredToolExecutor {
 
     /// Get filtered list of tools available to this agent
     pub fn get_available_tools(&self) -> Vec<Tool> {
-        all_tools()
+        self.base_executor
+            .tools()
             .into_iter()
             .filter(|tool| self.is_tool_allowed(&tool.name))
             .collect()
@@ -157,7 +289,25 @@ impl FilteredToolExecutor {
 
     /// Execute a tool use request with permission checking
     pub async fn execute(&self, tool_use: &crate::tools::ToolUse) -> crate::tools::ToolResult {
+        self.execute_streaming(tool_use, None).await
+    }
+
+    /// Execute a tool use request, sending command output to `output` as it is produced
+    pub async fn execute_streaming(
+        &self,
+        tool_use: &crate::tools::ToolUse,
+        output: Option<&crate::tools::ToolOutputSender>,
+    ) -> crate::tools::ToolResult {
         // Check if tool is allowed
+        if !self.mode.permits(self.base_executor.capabilities(&tool_use.name)) {
+            return crate::tools::ToolResult::error(
+                tool_use.id.clone(),
+                format!(
+                    "Tool '{}' is not available in plan mode; only read and analysis tools can run until the plan is approved",
+                    tool_use.name
+                ),
+            );
+        }
         if !self.is_tool_allowed(&tool_use.name) {
             return crate::tools::ToolResult::error(
                 tool_use.id.clone(),
@@ -165,8 +315,43 @@ impl FilteredToolExecutor {
             );
         }
 
-        // Execute with base executor
-        self.base_executor.execute(tool_use).await
+        // Unpreviewed changes run as a dry run when the policy asks for it
+        if self.needs_preview(tool_use) {
+            let mut preview = tool_use.clone();
+            preview.input["dry_run"] = serde_json::Value::Bool(true);
+            let options = CallOptions::default().conversation(self.session.as_deref());
+            let mut result = self.base_executor.execute_call(&preview, options).await;
+            if result.is_error.is_none() {
+                result.content.push_str(PREVIEW_REQUIRED_NOTE);
+            }
+            return result;
+        }
+
+        // Auto-approved calls must stay within the limits; others may need approval
+        if let Some(guard) = &self.auto_approve {
+            if let Err(reason) = guard.admit(tool_use, self.base_executor.capabilities(&tool_use.name)) {
+                return crate::tools::ToolResult::error(tool_use.id.clone(), reason);
+            }
+        } else if let Some(approval) = &self.approval {
+            if !approval.approve(tool_use).await {
+                return crate::tools::ToolResult::error(
+                    tool_use.id.clone(),
+                    format!("Tool '{}' was rejected by the user", tool_use.name),
+                );
+            }
+        }
+
+        // Execute with base executor; tools ask the same broker before
+        // actions that need consent, unless calls are auto-approved
+        let approval = match &self.auto_approve {
+            Some(_) => None,
+            None => self.approval.as_deref().or(self.consent.as_deref()),
+        };
+        let options = CallOptions::default()
+            .conversation(self.session.as_deref())
+            .output(output)
+            .approval(approval);
+        self.base_executor.execute_call(tool_use, options).await
     }
 }
 
@@ -174,8 +359,8 @@ impl FilteredToolExecutor {
 pub struct AgentExecutor {
     /// Anthropic API client
     client: Arc<AnthropicClient>,
-    /// Base tool executor
-    base_tool_executor: ToolExecutor,
+    /// Base tool executor, shared by every sub-agent
+    base_tool_executor: Arc<ToolExecutor>,
     /// Available agent definitions (from plugins)
     agent_definitions: HashMap<String, AgentDefinition>,
     /// Optional hook executor for lifecycle events
@@ -186,11 +371,11 @@ impl AgentExecutor {
     /// Create a new agent executor
     pub fn new(
         client: Arc<AnthropicClient>,
-        base_tool_executor: ToolExecutor,
+        base_tool_executor: impl Into<Arc<ToolExecutor>>,
     ) -> Self {
         Self {
             client,
-            base_tool_executor,
+            base_tool_executor: base_tool_executor.into(),
             agent_definitions: HashMap::new(),
             hook_executor: None,
         }
@@ -281,7 +466,7 @@ impl AgentExecutor {
 
         // Create filtered tool executor for this agent
         let filtered_executor = FilteredToolExecutor::new(
-            self.base_tool_executor.clone(),
+            Arc::clone(&self.base_tool_executor),
             allowed_tools,
             denied_tools,
         );
@@ -376,6 +561,7 @@ impl AgentExecutor {
                                 tool_use_id: result.tool_use_id.clone(),
                                 content: result.content.clone(),
                                 is_error: result.is_error,
+                                data: result.data.clone(),
                             });
 
                             return Err(AgentError::StoppedOnError(result.content.clone()));
@@ -386,8 +572,18 @@ impl AgentExecutor {
                             tool_use_id: result.tool_use_id.clone(),
                             content: result.content.clone(),
                             is_error: result.is_error,
+                            data: result.data.clone(),
                         });
 
+                        let (result, findings) = crate::injection::sanitize_result(name, result);
+                        if !findings.is_empty() {
+                            all_events.push(AgenticEvent::InjectionDetected {
+                                tool_use_id: result.tool_use_id.clone(),
+                                tool: name.clone(),
+                                findings,
+                            });
+                        }
+
                         tool_results.push(result);
                     }
                     crate::client::ContentBlock::ToolResult { .. } => {
@@ -461,9 +657,15 @@ impl AgentExecutor {
 impl Default for FilteredToolExecutor {
     fn default() -> Self {
         Self {
-            base_executor: ToolExecutor::new(),
+            base_executor: Arc::new(ToolExecutor::new()),
             allowed_tools: Vec::new(),
             denied_tools: Vec::new(),
+            approval: None,
+            consent: None,
+            session: None,
+            mode: AgentMode::default(),
+            auto_approve: None,
+            previewed: Mutex::new(HashSet::new()),
         }
     }
 }
@@ -555,6 +757,273 @@ mod tests {
         assert!(!filtered.is_tool_allowed("glob"));
     }
 
+    struct RejectWrites;
+
+    impl ToolApproval for RejectWrites {
+        fn approve<'a>(&'a self, tool_use: &'a ToolUse) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async move { tool_use.name != "write" })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filtered_tool_executor_approval() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "hello").unwrap();
+
+        let filtered = FilteredToolExecutor::new(
+            ToolExecutor::with_working_directory(temp_dir.path()),
+            Vec::new(),
+            Vec::new(),
+        )
+        .with_approval(Arc::new(RejectWrites));
+
+        let read = ToolUse {
+            id: "1".to_string(),
+            name: "read".to_string(),
+            input: serde_json::json!({ "file_path": "a.txt" }),
+        };
+        assert_eq!(filtered.execute(&read).await.is_error, None);
+
+        let write = ToolUse {
+            id: "2".to_string(),
+            name: "write".to_string(),
+            input: serde_json::json!({ "file_path": "b.txt", "content": "x" }),
+        };
+        let result = filtered.execute(&write).await;
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.content.contains("rejected by the user"));
+        assert!(!temp_dir.path().join("b.txt").exists());
+    }
+
+    /// Answers every request with `approve` and records what it was asked
+    struct Consent {
+        approve: bool,
+        asked: Mutex<Vec<String>>,
+    }
+
+    impl ToolApproval for Consent {
+        fn approve<'a>(&'a self, tool_use: &'a ToolUse) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            self.asked.lock().unwrap().push(tool_use.name.clone());
+            Box::pin(async move { self.approve })
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_filtered_tool_executor_consent() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let bin_dir = tempfile::TempDir::new().unwrap();
+        let kubectl = bin_dir.path().join("kubectl");
+        std::fs::write(
+            &kubectl,
+            "#!/bin/sh\nif [ \"$1\" = config ]; then echo staging; else echo \"pod/web Running\"; fi\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&kubectl, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "hello").unwrap();
+        let base = Arc::new(ToolExecutor::with_working_directory(temp_dir.path()));
+        let path = bin_dir.path().to_string_lossy().to_string();
+        base.set_shell_environment(Some([("PATH".to_string(), path)].into_iter().collect()));
+
+        let read = ToolUse {
+            id: "1".to_string(),
+            name: "read".to_string(),
+            input: serde_json::json!({ "file_path": "a.txt" }),
+        };
+        let get = ToolUse {
+            id: "2".to_string(),
+            name: "k8s".to_string(),
+            input: serde_json::json!({ "action": "get", "resource": "pods" }),
+        };
+        for approve in [true, false] {
+            let consent = Arc::new(Consent {
+                approve,
+                asked: Mutex::new(Vec::new()),
+            });
+            let filtered = FilteredToolExecutor::new(Arc::clone(&base), Vec::new(), Vec::new())
+                .with_consent(Arc::clone(&consent) as Arc<dyn ToolApproval>);
+
+            assert_eq!(filtered.execute(&read).await.is_error, None);
+            let result = filtered.execute(&get).await;
+            assert_eq!(*consent.asked.lock().unwrap(), vec!["k8s"], "only the cluster read asks");
+            if approve {
+                assert_eq!(result.is_error, None, "{}", result.content);
+                assert!(result.content.contains("pod/web Running"));
+            } else {
+                assert_eq!(result.is_error, Some(true));
+                assert!(result.content.contains("Reading from cluster context 'staging' needs the user's explicit approval"));
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_filtered_tool_executor_consent_to_apply() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let bin_dir = tempfile::TempDir::new().unwrap();
+        let terraform = bin_dir.path().join("terraform");
+        std::fs::write(
+            &terraform,
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$1\" in\n",
+                "  show) echo '{\"format_version\":\"1.2\",\"resource_changes\":",
+                "[{\"address\":\"aws_s3_bucket.logs\",\"change\":{\"actions\":[\"create\"]}}]}' ;;\n",
+                "  apply) : > applied ;;\n",
+                "esac\n"
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&terraform, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let infra = temp_dir.path().join("infra");
+        std::fs::create_dir(&infra).unwrap();
+        std::fs::write(infra.join("main.tf"), "").unwrap();
+        let base = Arc::new(ToolExecutor::with_working_directory(temp_dir.path()));
+        let path = bin_dir.path().to_string_lossy().to_string();
+        base.set_shell_environment(Some([("PATH".to_string(), path)].into_iter().collect()));
+
+        let apply = ToolUse {
+            id: "1".to_string(),
+            name: "iac".to_string(),
+            input: serde_json::json!({ "action": "apply", "directory": "infra" }),
+        };
+        let plan = aurora_core::plan_file(temp_dir.path(), &infra);
+        for approve in [false, true] {
+            std::fs::create_dir_all(plan.parent().unwrap()).unwrap();
+            std::fs::write(&plan, "saved plan").unwrap();
+            let consent = Arc::new(Consent {
+                approve,
+                asked: Mutex::new(Vec::new()),
+            });
+            let filtered = FilteredToolExecutor::new(Arc::clone(&base), Vec::new(), Vec::new())
+                .with_consent(Arc::clone(&consent) as Arc<dyn ToolApproval>);
+
+            let result = filtered.execute(&apply).await;
+            assert_eq!(*consent.asked.lock().unwrap(), vec!["iac"]);
+            if approve {
+                assert_eq!(result.is_error, None, "{}", result.content);
+                assert!(result.content.contains("Applied Terraform changes in infra"), "{}", result.content);
+                assert!(infra.join("applied").exists());
+            } else {
+                assert_eq!(result.is_error, Some(true));
+                assert!(result.content.contains("needs the user's explicit approval"));
+                assert!(result.content.contains("aws_s3_bucket.logs"));
+                assert!(!infra.join("applied").exists());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filtered_tool_executor_auto_approve() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut policy = crate::tools::ToolPolicy::default();
+        policy.auto_approve.max_files_per_turn = 1;
+
+        let filtered = FilteredToolExecutor::new(
+            ToolExecutor::with_working_directory(temp_dir.path()).with_policy(policy),
+            Vec::new(),
+            Vec::new(),
+        )
+        .with_approval(Arc::new(RejectWrites))
+        .with_auto_approve();
+
+        let write = |id: &str, path: &str| ToolUse {
+            id: id.to_string(),
+            name: "write".to_string(),
+            input: serde_json::json!({ "file_path": path, "content": "x" }),
+        };
+        assert_eq!(filtered.execute(&write("1", "a.txt")).await.is_error, None, "no approval is asked");
+        let result = filtered.execute(&write("2", "b.txt")).await;
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.content.contains("at most 1 files per turn"));
+        assert!(temp_dir.path().join("a.txt").exists());
+        assert!(!temp_dir.path().join("b.txt").exists());
+
+        let push = ToolUse {
+            id: "3".to_string(),
+            name: "bash".to_string(),
+            input: serde_json::json!({ "command": "git push origin main" }),
+        };
+        assert!(filtered.execute(&push).await.content.contains("does not allow running 'git push'"));
+    }
+
+    #[tokio::test]
+    async fn test_filtered_tool_executor_preview_first() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "old\n").unwrap();
+        let policy = crate::tools::ToolPolicy {
+            preview_first: PreviewFirst::AutoApprove,
+            ..Default::default()
+        };
+        let base = Arc::new(ToolExecutor::with_working_directory(temp_dir.path()).with_policy(policy));
+
+        let edit = |input: serde_json::Value| ToolUse {
+            id: "1".to_string(),
+            name: "edit".to_string(),
+            input,
+        };
+        let change = serde_json::json!({ "file_path": "a.txt", "old_string": "old", "new_string": "new" });
+        let reordered = serde_json::json!({ "new_string": "new", "old_string": "old", "file_path": "a.txt" });
+
+        let reviewed = FilteredToolExecutor::new(Arc::clone(&base), Vec::new(), Vec::new());
+        assert_eq!(reviewed.execute(&edit(change.clone())).await.is_error, None);
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(), "new\n");
+
+        std::fs::write(temp_dir.path().join("a.txt"), "old\n").unwrap();
+        let unattended = FilteredToolExecutor::new(base, Vec::new(), Vec::new()).with_auto_approve();
+        let preview = unattended.execute(&edit(change.clone())).await;
+        assert!(preview.content.starts_with("DRY RUN"));
+        assert!(preview.content.contains("-old\n+new"));
+        assert!(preview.content.contains("Call the tool again"));
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(), "old\n");
+
+        assert_eq!(unattended.execute(&edit(reordered)).await.is_error, None);
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(), "new\n");
+
+        let revert = serde_json::json!({ "file_path": "a.txt", "old_string": "new", "new_string": "old" });
+        let mut dry_run = revert.clone();
+        dry_run["dry_run"] = serde_json::Value::Bool(true);
+        assert!(unattended.execute(&edit(dry_run)).await.content.starts_with("DRY RUN"));
+        assert_eq!(unattended.execute(&edit(revert)).await.is_error, None, "an explicit dry run counts as the preview");
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(), "old\n");
+    }
+
+    #[tokio::test]
+    async fn test_filtered_tool_executor_plan_mode() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "hello").unwrap();
+
+        let filtered = FilteredToolExecutor::new(
+            ToolExecutor::with_working_directory(temp_dir.path()),
+            Vec::new(),
+            vec!["grep".to_string()],
+        )
+        .with_mode(AgentMode::Plan);
+
+        let advertised: Vec<String> = filtered.get_available_tools().into_iter().map(|tool| tool.name).collect();
+        assert!(advertised.contains(&"read".to_string()));
+        assert!(!advertised.contains(&"grep".to_string()), "deny lists still apply");
+        assert!(advertised.iter().all(|tool| crate::plan::PLAN_MODE_TOOLS.contains(&tool.as_str())));
+
+        let write = ToolUse {
+            id: "1".to_string(),
+            name: "write".to_string(),
+            input: serde_json::json!({ "file_path": "b.txt", "content": "x" }),
+        };
+        let result = filtered.execute(&write).await;
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.content.contains("not available in plan mode"));
+        assert!(!temp_dir.path().join("b.txt").exists());
+
+        let filtered = filtered.with_mode(AgentMode::Approved("Write b.txt".to_string()));
+        assert_eq!(filtered.execute(&write).await.is_error, None);
+        assert!(temp_dir.path().join("b.txt").exists());
+    }
+
     #[test]
     fn test_filtered_tool_executor_empty_allowed_list() {
         let base_executor = ToolExecutor::new();
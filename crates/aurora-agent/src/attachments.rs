@@ -0,0 +1,525 @@
+//! Files and folders attached to a message as explicit context
+//!
+//! Attachments are read from disk with size caps, oversized files are replaced
+//! by a summary (head, tail, and an outline of the omitted definitions), and
+//! the result is wrapped as labeled context blocks in the next user message.
+
+use aurora_core::documents::{self, DocumentKind};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Directories never descended into when attaching a folder
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "dist", "build"];
+
+/// Lines kept from the start of a summarized file
+const SUMMARY_HEAD_LINES: usize = 60;
+
+/// Lines kept from the end of a summarized file
+const SUMMARY_TAIL_LINES: usize = 20;
+
+/// Maximum outline entries listed for the omitted part of a file
+const SUMMARY_OUTLINE_ENTRIES: usize = 40;
+
+/// Errors that can occur while reading attachments
+#[derive(Error, Debug)]
+pub enum AttachmentError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Path does not exist
+    #[error("Path not found: {0}")]
+    NotFound(String),
+}
+
+pub type Result<T> = std::result::Result<T, AttachmentError>;
+
+/// Size caps applied when reading attachments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttachmentLimits {
+    /// Files larger than this are summarized
+    pub max_file_bytes: usize,
+    /// Total content budget across all attachments; later files are summarized
+    pub max_total_bytes: usize,
+    /// Maximum files read from a single folder
+    pub max_folder_files: usize,
+}
+
+impl Default for AttachmentLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 32 * 1024,
+            max_total_bytes: 128 * 1024,
+            max_folder_files: 50,
+        }
+    }
+}
+
+/// Kind of attached path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    /// A single file
+    File,
+    /// A folder (listing plus file contents)
+    Folder,
+    /// Command output captured by the IDE (e.g. a failing watch run)
+    Output,
+}
+
+/// An attached file or folder, ready to send
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Display path (relative to the project root when inside it)
+    pub path: String,
+    /// File or folder
+    pub kind: AttachmentKind,
+    /// Size of the original content in bytes
+    pub bytes: usize,
+    /// True when some content was summarized or omitted to fit the caps
+    pub summarized: bool,
+    /// Content included in the message
+    pub content: String,
+}
+
+impl Attachment {
+    /// Attach captured command output under a descriptive label
+    pub fn output(label: impl Into<String>, content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self {
+            path: label.into(),
+            kind: AttachmentKind::Output,
+            bytes: content.len(),
+            summarized: false,
+            content,
+        }
+    }
+
+    /// Wrap the attachment as a labeled context block
+    pub fn to_context_block(&self) -> String {
+        let kind = match self.kind {
+            AttachmentKind::File => "file",
+            AttachmentKind::Folder => "folder",
+            AttachmentKind::Output => "output",
+        };
+        format!(
+            "<context path=\"{}\" kind=\"{}\"{}>\n{}\n</context>",
+            self.path,
+            kind,
+            if self.summarized { " summarized=\"true\"" } else { "" },
+            self.content.trim_end()
+        )
+    }
+
+    /// Metadata recorded in the conversation once the attachment is sent
+    pub fn record(&self, message_index: usize) -> AttachmentRecord {
+        AttachmentRecord {
+            path: self.path.clone(),
+            kind: self.kind,
+            bytes: self.bytes,
+            summarized: self.summarized,
+            message_index,
+        }
+    }
+}
+
+/// Record of an attachment sent with a message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentRecord {
+    /// Display path
+    pub path: String,
+    /// File or folder
+    pub kind: AttachmentKind,
+    /// Size of the original content in bytes
+    pub bytes: usize,
+    /// True when content was summarized
+    pub summarized: bool,
+    /// Index of the user message that carried the attachment
+    pub message_index: usize,
+}
+
+/// Read the given paths as attachments
+///
+/// Relative paths are resolved against `project_root`.
+pub fn load_attachments(
+    project_root: &Path,
+    paths: &[String],
+    limits: &AttachmentLimits,
+) -> Result<Vec<Attachment>> {
+    let mut remaining = limits.max_total_bytes;
+    let mut attachments = Vec::new();
+
+    for path in paths {
+        let resolved = resolve(project_root, path);
+        if !resolved.exists() {
+            return Err(AttachmentError::NotFound(path.clone()));
+        }
+
+        let attachment = if resolved.is_dir() {
+            load_folder(project_root, &resolved, limits, &mut remaining)?
+        } else {
+            let (content, bytes, summarized) = read_capped(&resolved, limits, &mut remaining)?;
+            Attachment {
+                path: display_path(project_root, &resolved),
+                kind: AttachmentKind::File,
+                bytes,
+                summarized,
+                content,
+            }
+        };
+        attachments.push(attachment);
+    }
+
+    Ok(attachments)
+}
+
+/// Build the user message text with attachment blocks ahead of the request
+pub fn wrap_message(message: &str, attachments: &[Attachment]) -> String {
+    if attachments.is_empty() {
+        return message.to_string();
+    }
+
+    let blocks = attachments
+        .iter()
+        .map(|a| a.to_context_block())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!("{}\n\n{}", blocks, message)
+}
+
+fn resolve(project_root: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        project_root.join(path)
+    }
+}
+
+fn display_path(project_root: &Path, path: &Path) -> String {
+    path.strip_prefix(project_root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn load_folder(
+    project_root: &Path,
+    folder: &Path,
+    limits: &AttachmentLimits,
+    remaining: &mut usize,
+) -> Result<Attachment> {
+    let mut files = Vec::new();
+    collect_files(folder, &mut files)?;
+    files.sort();
+
+    let mut listing = files
+        .iter()
+        .map(|f| format!("- {}", display_path(folder, f)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut summarized = files.len() > limits.max_folder_files;
+    if summarized {
+        listing.push_str(&format!(
+            "\n(contents of the first {} of {} files included)",
+            limits.max_folder_files,
+            files.len()
+        ));
+    }
+
+    let mut content = format!("Files:\n{}\n", listing);
+    let mut bytes = 0;
+    for file in files.iter().take(limits.max_folder_files) {
+        let (file_content, file_bytes, file_summarized) = read_capped(file, limits, remaining)?;
+        bytes += file_bytes;
+        summarized |= file_summarized;
+        content.push_str(&format!(
+            "\n--- {} ---\n{}\n",
+            display_path(project_root, file),
+            file_content.trim_end()
+        ));
+    }
+
+    Ok(Attachment {
+        path: display_path(project_root, folder),
+        kind: AttachmentKind::Folder,
+        bytes,
+        summarized,
+        content,
+    })
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            if !SKIPPED_DIRS.contains(&name) {
+                collect_files(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Read a file, summarizing it if it exceeds the per-file or remaining budget
+fn read_capped(path: &Path, limits: &AttachmentLimits, remaining: &mut usize) -> Result<(String, usize, bool)> {
+    let raw = std::fs::read(path)?;
+    let bytes = raw.len();
+
+    let document = DocumentKind::from_path(path).map(|kind| match kind {
+        DocumentKind::Pdf => documents::extract_pdf(&raw),
+        DocumentKind::Docx => documents::extract_docx(&raw),
+    });
+    let is_document = document.is_some();
+    let text = match document {
+        Some(Ok(document)) => document.text(),
+        Some(Err(e)) => return Ok((format!("[document could not be read: {}]", e), bytes, true)),
+        None if raw.iter().take(8192).any(|b| *b == 0) => {
+            return Ok((format!("[binary file, {} bytes omitted]", bytes), bytes, true));
+        }
+        None => String::from_utf8_lossy(&raw).into_owned(),
+    };
+
+    let budget = limits.max_file_bytes.min(*remaining);
+    let (content, summarized) = if text.len() <= budget {
+        (text, false)
+    } else if is_document {
+        let summary = summarize(&text, budget);
+        (format!("{}\n[use read_document with a page range to read omitted pages]", summary), true)
+    } else {
+        (summarize(&text, budget), true)
+    };
+
+    *remaining = remaining.saturating_sub(content.len());
+    Ok((content, bytes, summarized))
+}
+
+/// Summarize a file as its head, tail, and an outline of the omitted lines
+fn summarize(text: &str, max_bytes: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let total = lines.len();
+
+    if max_bytes == 0 {
+        return format!("[{} lines omitted: attachment size limit reached]", total);
+    }
+    if total <= SUMMARY_HEAD_LINES + SUMMARY_TAIL_LINES {
+        // Few but very long lines: keep a prefix
+        let cut = floor_char_boundary(text, max_bytes);
+        return format!("{}\n[... truncated at {} of {} bytes]", &text[..cut], cut, text.len());
+    }
+
+    let head = &lines[..SUMMARY_HEAD_LINES];
+    let omitted = &lines[SUMMARY_HEAD_LINES..total - SUMMARY_TAIL_LINES];
+    let tail = &lines[total - SUMMARY_TAIL_LINES..];
+
+    let outline: Vec<String> = omitted
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| definition_pattern().is_some_and(|re| re.is_match(line)))
+        .take(SUMMARY_OUTLINE_ENTRIES)
+        .map(|(i, line)| format!("  {}: {}", SUMMARY_HEAD_LINES + i + 1, line.trim()))
+        .collect();
+
+    let mut summary = format!(
+        "{}\n[... {} lines omitted ({} lines total)",
+        head.join("\n"),
+        omitted.len(),
+        total
+    );
+    if outline.is_empty() {
+        summary.push_str(" ...]\n");
+    } else {
+        summary.push_str("; definitions in omitted section:\n");
+        summary.push_str(&outline.join("\n"));
+        summary.push_str("\n...]\n");
+    }
+    summary.push_str(&tail.join("\n"));
+
+    if summary.len() > max_bytes {
+        let cut = floor_char_boundary(&summary, max_bytes);
+        summary.truncate(cut);
+        summary.push_str("\n[... summary truncated]");
+    }
+    summary
+}
+
+fn definition_pattern() -> Option<&'static Regex> {
+    static PATTERN: OnceLock<Option<Regex>> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| {
+            Regex::new(
+                r"^\s*(pub(\([^)]*\))?\s+)?(async\s+)?(fn|struct|enum|trait|impl|mod|class|def|interface|type|function|func|export)\b",
+            )
+            .ok()
+        })
+        .as_ref()
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_attach_small_file() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("notes.md"), "Remember the parser").unwrap();
+
+        let attachments = load_attachments(
+            temp_dir.path(),
+            &["notes.md".to_string()],
+            &AttachmentLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(attachments[0].path, "notes.md");
+        assert!(!attachments[0].summarized);
+        assert_eq!(
+            attachments[0].to_context_block(),
+            "<context path=\"notes.md\" kind=\"file\">\nRemember the parser\n</context>"
+        );
+
+        let message = wrap_message("What does this say?", &attachments);
+        assert!(message.starts_with("<context"));
+        assert!(message.ends_with("</context>\n\nWhat does this say?"));
+
+        assert!(matches!(
+            load_attachments(temp_dir.path(), &["missing.rs".to_string()], &AttachmentLimits::default()),
+            Err(AttachmentError::NotFound(_))
+        ));
+
+        assert_eq!(
+            Attachment::output("watch: test", "1 failed\n").to_context_block(),
+            "<context path=\"watch: test\" kind=\"output\">\n1 failed\n</context>"
+        );
+    }
+
+    #[test]
+    fn test_oversized_file_is_summarized() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = (0..500)
+            .map(|i| {
+                if i == 250 {
+                    "pub fn hidden_in_the_middle() {}".to_string()
+                } else {
+                    format!("// line {}", i)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(temp_dir.path().join("big.rs"), &source).unwrap();
+
+        let limits = AttachmentLimits {
+            max_file_bytes: 2048,
+            ..AttachmentLimits::default()
+        };
+        let attachment = &load_attachments(temp_dir.path(), &["big.rs".to_string()], &limits).unwrap()[0];
+
+        assert!(attachment.summarized);
+        assert_eq!(attachment.bytes, source.len());
+        assert!(attachment.content.len() <= 2048 + "\n[... summary truncated]".len());
+        assert!(attachment.content.starts_with("// line 0\n"));
+        assert!(attachment.content.contains("251: pub fn hidden_in_the_middle() {}"));
+        assert!(attachment.to_context_block().contains("summarized=\"true\""));
+    }
+
+    #[test]
+    fn test_attach_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        std::fs::create_dir_all(src.join("target")).unwrap();
+        std::fs::write(src.join("lib.rs"), "mod a;").unwrap();
+        std::fs::write(src.join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(src.join("target").join("junk"), "skip").unwrap();
+        std::fs::write(src.join("logo.png"), [0u8, 1, 2, 3]).unwrap();
+
+        let attachment =
+            &load_attachments(temp_dir.path(), &["src".to_string()], &AttachmentLimits::default()).unwrap()[0];
+
+        assert_eq!(attachment.kind, AttachmentKind::Folder);
+        assert_eq!(attachment.path, "src");
+        assert!(attachment.content.contains("- a.rs\n- lib.rs\n- logo.png"));
+        assert!(attachment.content.contains("--- src/a.rs ---\nfn a() {}"));
+        assert!(attachment.content.contains("[binary file, 4 bytes omitted]"));
+        assert!(!attachment.content.contains("skip"));
+
+        let limits = AttachmentLimits {
+            max_folder_files: 1,
+            ..AttachmentLimits::default()
+        };
+        let limited = &load_attachments(temp_dir.path(), &["src".to_string()], &limits).unwrap()[0];
+        assert!(limited.summarized);
+        assert!(limited.content.contains("first 1 of 3 files"));
+    }
+
+    #[test]
+    fn test_total_budget_is_shared() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "a".repeat(100)).unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "b".repeat(100)).unwrap();
+
+        let limits = AttachmentLimits {
+            max_total_bytes: 150,
+            ..AttachmentLimits::default()
+        };
+        let attachments = load_attachments(
+            temp_dir.path(),
+            &["a.txt".to_string(), "b.txt".to_string()],
+            &limits,
+        )
+        .unwrap();
+
+        assert!(!attachments[0].summarized);
+        assert!(attachments[1].summarized);
+        assert!(attachments[1].content.len() < 100);
+    }
+
+    #[test]
+    fn test_attach_documents_as_text() {
+        let temp_dir = TempDir::new().unwrap();
+        // The binary marker comment would otherwise flag the file as binary
+        let pdf = b"%PDF-1.4
+%\x00\xff
+1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj
+2 0 obj << /Type /Pages /Kids [3 0 R] /Count 1 >> endobj
+3 0 obj << /Type /Page /Parent 2 0 R /Contents 4 0 R >> endobj
+4 0 obj << /Length 23 >>
+stream
+BT (Design goals) Tj ET
+endstream
+endobj
+%%EOF
+";
+        std::fs::write(temp_dir.path().join("design.pdf"), pdf).unwrap();
+        std::fs::write(temp_dir.path().join("broken.docx"), b"not a zip").unwrap();
+
+        let attachments = load_attachments(
+            temp_dir.path(),
+            &["design.pdf".to_string(), "broken.docx".to_string()],
+            &AttachmentLimits::default(),
+        )
+        .unwrap();
+
+        assert!(attachments[0].content.starts_with("--- Page 1 ---\nDesign goals"));
+        assert!(!attachments[0].summarized);
+        assert!(attachments[1].content.contains("document could not be read"));
+        assert!(attachments[1].summarized);
+    }
+}
@@ -0,0 +1,77 @@
+//! Model-polished changelog wording
+//!
+//! The sections `aurora_core::Release` renders repeat commit subjects as
+//! written. `polish_changelog` asks the model to reword the entries for
+//! readers of the changelog, keeping its headings and commit hashes; a reply
+//! that drops either is discarded in favor of the original section.
+
+use crate::client::{AnthropicClient, ClientError, MessageRequest};
+use crate::conversation::Conversation;
+
+/// Model used to reword changelog entries
+const POLISH_MODEL: &str = "claude-3-5-haiku-latest";
+
+/// `section` with its entries reworded by the model, or unchanged when the
+/// reply does not keep its structure
+pub async fn polish_changelog(client: &AnthropicClient, section: &str) -> Result<String, ClientError> {
+    let mut prompt = Conversation::with_system_prompt(
+        "You edit changelogs. Reword each entry of this Markdown changelog section so it reads clearly to \
+         users of the project: fix grammar, start with a capital letter, and expand terse commit subjects. \
+         Keep every heading, bold scope, and parenthesized commit hash exactly as they are, and do not add, \
+         remove, or reorder entries. Reply with the Markdown section only.",
+    );
+    prompt.add_user_message(section.to_string());
+
+    let request = MessageRequest::from_conversation(&prompt, POLISH_MODEL).with_max_tokens(4096);
+    let response = client.send_message(request).await?;
+    let polished = response
+        .content
+        .iter()
+        .find_map(|block| block.as_text())
+        .unwrap_or_default();
+    Ok(accept_polished(section, polished))
+}
+
+/// `polished` when it keeps the headings and entry count of `original`
+fn accept_polished(original: &str, polished: &str) -> String {
+    let polished = polished.trim();
+    let polished = polished
+        .strip_prefix("```markdown")
+        .or_else(|| polished.strip_prefix("```"))
+        .and_then(|body| body.strip_suffix("```"))
+        .unwrap_or(polished)
+        .trim();
+    let outline = |text: &str| -> Vec<String> {
+        text.lines()
+            .filter(|line| line.starts_with('#') || line.starts_with("- "))
+            .map(|line| match line.rsplit_once(" (") {
+                Some((_, hash)) if line.starts_with("- ") => hash.to_string(),
+                _ => line.to_string(),
+            })
+            .collect()
+    };
+    if polished.is_empty() || outline(original) != outline(polished) {
+        return original.to_string();
+    }
+    format!("{}\n", polished)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_polished() {
+        let original = "## 1.2.0\n\n### Features\n\n- **ui:** add dark mode (a1b2c3)\n";
+        let polished = "```markdown\n## 1.2.0\n\n### Features\n\n- **ui:** Add a dark mode theme (a1b2c3)\n```";
+        assert_eq!(
+            accept_polished(original, polished),
+            "## 1.2.0\n\n### Features\n\n- **ui:** Add a dark mode theme (a1b2c3)\n"
+        );
+
+        // Dropped hashes, headings, or entries keep the original
+        assert_eq!(accept_polished(original, "## 1.2.0\n\n### Features\n\n- Add dark mode\n"), original);
+        assert_eq!(accept_polished(original, "## 1.2.0\n\n- **ui:** Add dark mode (a1b2c3)\n"), original);
+        assert_eq!(accept_polished(original, ""), original);
+    }
+}
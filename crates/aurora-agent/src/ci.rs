@@ -0,0 +1,717 @@
+//! CI status and workflow integration
+//!
+//! This module fetches GitHub Actions and GitLab CI status for the current
+//! branch (and its open pull/merge request), and retrieves the logs of failed
+//! jobs so the agent can diagnose red builds directly. The provider is detected
+//! from the `origin` remote; access tokens are read from the project's
+//! `CredentialStore`.
+
+use aurora_core::CredentialStore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::path::Path;
+use std::process::Command;
+use thiserror::Error;
+
+/// Credential store key for the GitHub access token
+pub const GITHUB_TOKEN_KEY: &str = "github_token";
+
+/// Credential store key for the GitLab access token
+pub const GITLAB_TOKEN_KEY: &str = "gitlab_token";
+
+/// Number of trailing log lines returned per failed job by default
+pub const DEFAULT_LOG_LINES: usize = 200;
+
+/// Errors that can occur while talking to a CI provider
+#[derive(Error, Debug)]
+pub enum CiError {
+    /// HTTP request error
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Provider returned an error response
+    #[error("CI API error ({status}): {message}")]
+    Api { status: u16, message: String },
+
+    /// Git command failed
+    #[error("Git error: {0}")]
+    Git(String),
+
+    /// Remote URL does not point at a supported provider
+    #[error("Unsupported remote for CI integration: {0}")]
+    UnsupportedRemote(String),
+
+    /// No run was found to inspect
+    #[error("No CI runs found for branch {0}")]
+    NoRuns(String),
+}
+
+pub type Result<T> = std::result::Result<T, CiError>;
+
+/// A supported CI provider and the repository it hosts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum CiProvider {
+    /// GitHub Actions
+    GitHub { owner: String, repo: String },
+    /// GitLab CI (gitlab.com or self-hosted)
+    GitLab { host: String, project: String },
+}
+
+impl CiProvider {
+    /// Detect the provider from a git remote URL
+    ///
+    /// Supports `https://host/owner/repo(.git)`, `ssh://git@host/owner/repo`,
+    /// and scp-style `git@host:owner/repo.git` remotes.
+    pub fn from_remote_url(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = if let Some(rest) = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .or_else(|| url.strip_prefix("ssh://"))
+        {
+            let rest = rest.rsplit_once('@').map(|(_, r)| r).unwrap_or(rest);
+            let (host, path) = rest.split_once('/')?;
+            (host.split(':').next().unwrap_or(host), path)
+        } else {
+            let rest = url.split_once('@').map(|(_, r)| r).unwrap_or(url);
+            rest.split_once(':')?
+        };
+
+        let path = path.trim_matches('/').trim_end_matches(".git");
+        if path.is_empty() || !path.contains('/') {
+            return None;
+        }
+
+        if host == "github.com" {
+            let (owner, repo) = path.split_once('/')?;
+            Some(CiProvider::GitHub {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            })
+        } else if host.contains("gitlab") {
+            Some(CiProvider::GitLab {
+                host: host.to_string(),
+                project: path.to_string(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Credential store key for this provider's token
+    pub fn token_key(&self) -> &'static str {
+        match self {
+            CiProvider::GitHub { .. } => GITHUB_TOKEN_KEY,
+            CiProvider::GitLab { .. } => GITLAB_TOKEN_KEY,
+        }
+    }
+
+    fn default_base_url(&self) -> String {
+        match self {
+            CiProvider::GitHub { .. } => "https://api.github.com".to_string(),
+            CiProvider::GitLab { host, .. } => format!("https://{}/api/v4", host),
+        }
+    }
+}
+
+/// Normalized state of a CI run or job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Waiting to start
+    Queued,
+    /// Currently running
+    InProgress,
+    /// Finished successfully
+    Success,
+    /// Finished with failures
+    Failure,
+    /// Cancelled before finishing
+    Cancelled,
+    /// Skipped or not applicable
+    Skipped,
+    /// State not recognized
+    Unknown,
+}
+
+impl RunStatus {
+    fn from_github(status: &str, conclusion: Option<&str>) -> Self {
+        match (status, conclusion) {
+            ("completed", Some("success")) => RunStatus::Success,
+            ("completed", Some("failure" | "timed_out" | "startup_failure")) => RunStatus::Failure,
+            ("completed", Some("cancelled")) => RunStatus::Cancelled,
+            ("completed", Some("skipped" | "neutral")) => RunStatus::Skipped,
+            ("queued" | "waiting" | "pending" | "requested", _) => RunStatus::Queued,
+            ("in_progress", _) => RunStatus::InProgress,
+            _ => RunStatus::Unknown,
+        }
+    }
+
+    fn from_gitlab(status: &str) -> Self {
+        match status {
+            "success" => RunStatus::Success,
+            "failed" => RunStatus::Failure,
+            "canceled" => RunStatus::Cancelled,
+            "skipped" | "manual" => RunStatus::Skipped,
+            "created" | "waiting_for_resource" | "preparing" | "pending" | "scheduled" => {
+                RunStatus::Queued
+            }
+            "running" => RunStatus::InProgress,
+            _ => RunStatus::Unknown,
+        }
+    }
+}
+
+/// A workflow run (GitHub) or pipeline (GitLab)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CiRun {
+    /// Provider-specific run ID
+    pub id: u64,
+    /// Workflow or pipeline name
+    pub name: String,
+    /// Normalized status
+    pub status: RunStatus,
+    /// Commit the run was triggered for
+    pub commit_sha: String,
+    /// Link to the run in the provider's UI
+    pub url: String,
+}
+
+/// Open pull request (GitHub) or merge request (GitLab) for the branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestInfo {
+    /// PR/MR number
+    pub number: u64,
+    /// Title
+    pub title: String,
+    /// Link to the PR/MR
+    pub url: String,
+}
+
+/// CI status for a branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CiStatus {
+    /// Provider and repository
+    pub provider: CiProvider,
+    /// Branch that was inspected
+    pub branch: String,
+    /// Open PR/MR for the branch, if any
+    pub pull_request: Option<PullRequestInfo>,
+    /// Latest run of each workflow, most recent first
+    pub runs: Vec<CiRun>,
+    /// Aggregate status across runs
+    pub overall: RunStatus,
+}
+
+impl CiStatus {
+    /// Format the status for display
+    pub fn format(&self) -> String {
+        let mut output = format!("📊 CI status for {} ({:?})\n", self.branch, self.overall);
+        if let Some(pr) = &self.pull_request {
+            output.push_str(&format!("Pull request #{}: {} ({})\n", pr.number, pr.title, pr.url));
+        }
+
+        if self.runs.is_empty() {
+            output.push_str("No CI runs found\n");
+        }
+        for run in &self.runs {
+            let marker = match run.status {
+                RunStatus::Success => "✅",
+                RunStatus::Failure => "❌",
+                RunStatus::Queued | RunStatus::InProgress => "⏳",
+                _ => "⚠️",
+            };
+            let sha: String = run.commit_sha.chars().take(8).collect();
+            output.push_str(&format!(
+                "{} {} [{}] {:?} @ {} {}\n",
+                marker, run.name, run.id, run.status, sha, run.url
+            ));
+        }
+        output
+    }
+}
+
+/// Log output of a failed job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLog {
+    /// Provider-specific job ID
+    pub job_id: u64,
+    /// Job name
+    pub name: String,
+    /// Link to the job
+    pub url: String,
+    /// Trailing lines of the job log
+    pub log: String,
+}
+
+/// Client for a CI provider's REST API
+pub struct CiClient {
+    provider: CiProvider,
+    token: Option<String>,
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl CiClient {
+    /// Create a client for a provider with an optional access token
+    pub fn new(provider: CiProvider, token: Option<String>) -> Self {
+        Self {
+            base_url: provider.default_base_url(),
+            provider,
+            token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a client for a project by inspecting its `origin` remote
+    ///
+    /// The access token is loaded from the project's credential store when present.
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Result<Self> {
+        let project_root = project_root.as_ref();
+        let url = git_output(project_root, &["remote", "get-url", "origin"])?;
+        let provider = CiProvider::from_remote_url(&url)
+            .ok_or_else(|| CiError::UnsupportedRemote(url.clone()))?;
+
+        let token = CredentialStore::for_project(project_root)
+            .retrieve(provider.token_key(), "auroraheart")
+            .ok();
+
+        Ok(Self::new(provider, token))
+    }
+
+    /// Get the provider this client talks to
+    pub fn provider(&self) -> &CiProvider {
+        &self.provider
+    }
+
+    /// Fetch CI status for a branch
+    pub async fn get_status(&self, branch: &str) -> Result<CiStatus> {
+        let (runs, pull_request) = match &self.provider {
+            CiProvider::GitHub { owner, repo } => {
+                let runs = self.github_runs(owner, repo, branch).await?;
+                let pulls = self
+                    .get_json(
+                        &format!("/repos/{}/{}/pulls", owner, repo),
+                        &[("head", format!("{}:{}", owner, branch)), ("state", "open".to_string())],
+                    )
+                    .await?;
+                let pull_request = pulls.as_array().and_then(|a| a.first()).map(|pr| PullRequestInfo {
+                    number: pr["number"].as_u64().unwrap_or_default(),
+                    title: pr["title"].as_str().unwrap_or_default().to_string(),
+                    url: pr["html_url"].as_str().unwrap_or_default().to_string(),
+                });
+                (runs, pull_request)
+            }
+            CiProvider::GitLab { project, .. } => {
+                let runs = self.gitlab_pipelines(project, branch).await?;
+                let merge_requests = self
+                    .get_json(
+                        &format!("/projects/{}/merge_requests", encode_path(project)),
+                        &[("source_branch", branch.to_string()), ("state", "opened".to_string())],
+                    )
+                    .await?;
+                let pull_request = merge_requests.as_array().and_then(|a| a.first()).map(|mr| PullRequestInfo {
+                    number: mr["iid"].as_u64().unwrap_or_default(),
+                    title: mr["title"].as_str().unwrap_or_default().to_string(),
+                    url: mr["web_url"].as_str().unwrap_or_default().to_string(),
+                });
+                (runs, pull_request)
+            }
+        };
+
+        Ok(CiStatus {
+            provider: self.provider.clone(),
+            branch: branch.to_string(),
+            pull_request,
+            overall: overall_status(&runs),
+            runs,
+        })
+    }
+
+    /// Fetch the trailing log lines of every failed job in a run
+    ///
+    /// When `run_id` is `None`, the most recent failed run on `branch` is used.
+    pub async fn get_failed_job_logs(
+        &self,
+        branch: &str,
+        run_id: Option<u64>,
+        max_lines: usize,
+    ) -> Result<Vec<JobLog>> {
+        let run_id = match run_id {
+            Some(id) => id,
+            None => self
+                .get_status(branch)
+                .await?
+                .runs
+                .iter()
+                .find(|r| r.status == RunStatus::Failure)
+                .map(|r| r.id)
+                .ok_or_else(|| CiError::NoRuns(branch.to_string()))?,
+        };
+
+        let mut logs = Vec::new();
+        match &self.provider {
+            CiProvider::GitHub { owner, repo } => {
+                let jobs = self
+                    .get_json(&format!("/repos/{}/{}/actions/runs/{}/jobs", owner, repo, run_id), &[])
+                    .await?;
+                for job in jobs["jobs"].as_array().into_iter().flatten() {
+                    let status = RunStatus::from_github(
+                        job["status"].as_str().unwrap_or_default(),
+                        job["conclusion"].as_str(),
+                    );
+                    if status != RunStatus::Failure {
+                        continue;
+                    }
+                    let job_id = job["id"].as_u64().unwrap_or_default();
+                    let log = self
+                        .get_text(&format!("/repos/{}/{}/actions/jobs/{}/logs", owner, repo, job_id))
+                        .await?;
+                    logs.push(JobLog {
+                        job_id,
+                        name: job["name"].as_str().unwrap_or_default().to_string(),
+                        url: job["html_url"].as_str().unwrap_or_default().to_string(),
+                        log: tail_lines(&log, max_lines),
+                    });
+                }
+            }
+            CiProvider::GitLab { project, .. } => {
+                let project = encode_path(project);
+                let jobs = self
+                    .get_json(
+                        &format!("/projects/{}/pipelines/{}/jobs", project, run_id),
+                        &[("scope[]", "failed".to_string())],
+                    )
+                    .await?;
+                for job in jobs.as_array().into_iter().flatten() {
+                    let job_id = job["id"].as_u64().unwrap_or_default();
+                    let log = self
+                        .get_text(&format!("/projects/{}/jobs/{}/trace", project, job_id))
+                        .await?;
+                    logs.push(JobLog {
+                        job_id,
+                        name: job["name"].as_str().unwrap_or_default().to_string(),
+                        url: job["web_url"].as_str().unwrap_or_default().to_string(),
+                        log: tail_lines(&log, max_lines),
+                    });
+                }
+            }
+        }
+
+        Ok(logs)
+    }
+
+    async fn github_runs(&self, owner: &str, repo: &str, branch: &str) -> Result<Vec<CiRun>> {
+        let body = self
+            .get_json(
+                &format!("/repos/{}/{}/actions/runs", owner, repo),
+                &[("branch", branch.to_string()), ("per_page", "20".to_string())],
+            )
+            .await?;
+
+        let mut runs: Vec<CiRun> = Vec::new();
+        for run in body["workflow_runs"].as_array().into_iter().flatten() {
+            let name = run["name"].as_str().unwrap_or("workflow").to_string();
+            // Runs are returned newest first; keep only the latest per workflow
+            if runs.iter().any(|r| r.name == name) {
+                continue;
+            }
+            runs.push(CiRun {
+                id: run["id"].as_u64().unwrap_or_default(),
+                name,
+                status: RunStatus::from_github(
+                    run["status"].as_str().unwrap_or_default(),
+                    run["conclusion"].as_str(),
+                ),
+                commit_sha: run["head_sha"].as_str().unwrap_or_default().to_string(),
+                url: run["html_url"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(runs)
+    }
+
+    async fn gitlab_pipelines(&self, project: &str, branch: &str) -> Result<Vec<CiRun>> {
+        let body = self
+            .get_json(
+                &format!("/projects/{}/pipelines", encode_path(project)),
+                &[("ref", branch.to_string()), ("per_page", "1".to_string())],
+            )
+            .await?;
+
+        Ok(body
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|pipeline| {
+                let id = pipeline["id"].as_u64().unwrap_or_default();
+                CiRun {
+                    id,
+                    name: format!("pipeline #{}", id),
+                    status: RunStatus::from_gitlab(pipeline["status"].as_str().unwrap_or_default()),
+                    commit_sha: pipeline["sha"].as_str().unwrap_or_default().to_string(),
+                    url: pipeline["web_url"].as_str().unwrap_or_default().to_string(),
+                }
+            })
+            .collect())
+    }
+
+    fn request(&self, path: &str) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("user-agent", "AuroraHeart");
+
+        match (&self.provider, &self.token) {
+            (CiProvider::GitHub { .. }, Some(token)) => {
+                request = request
+                    .header("accept", "application/vnd.github+json")
+                    .bearer_auth(token);
+            }
+            (CiProvider::GitHub { .. }, None) => {
+                request = request.header("accept", "application/vnd.github+json");
+            }
+            (CiProvider::GitLab { .. }, Some(token)) => {
+                request = request.header("private-token", token);
+            }
+            (CiProvider::GitLab { .. }, None) => {}
+        }
+        request
+    }
+
+    async fn get_json(&self, path: &str, query: &[(&str, String)]) -> Result<JsonValue> {
+        let response = self.request(path).query(query).send().await?;
+        let response = check_status(response).await?;
+        Ok(response.json().await?)
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String> {
+        let response = self.request(path).send().await?;
+        let response = check_status(response).await?;
+        Ok(response.text().await?)
+    }
+}
+
+/// Get the current branch of a repository
+pub fn current_branch<P: AsRef<Path>>(project_root: P) -> Result<String> {
+    git_output(project_root.as_ref(), &["rev-parse", "--abbrev-ref", "HEAD"])
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status().as_u16();
+    let message = response.text().await.unwrap_or_default();
+    Err(CiError::Api { status, message })
+}
+
+fn git_output(project_root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(project_root)
+        .output()
+        .map_err(|e| CiError::Git(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(CiError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn overall_status(runs: &[CiRun]) -> RunStatus {
+    let statuses: Vec<RunStatus> = runs.iter().map(|r| r.status).collect();
+    if statuses.is_empty() {
+        RunStatus::Unknown
+    } else if statuses.contains(&RunStatus::Failure) {
+        RunStatus::Failure
+    } else if statuses.contains(&RunStatus::InProgress) {
+        RunStatus::InProgress
+    } else if statuses.contains(&RunStatus::Queued) {
+        RunStatus::Queued
+    } else if statuses.contains(&RunStatus::Cancelled) {
+        RunStatus::Cancelled
+    } else if statuses.iter().all(|s| *s == RunStatus::Skipped) {
+        RunStatus::Skipped
+    } else {
+        RunStatus::Success
+    }
+}
+
+fn encode_path(path: &str) -> String {
+    path.replace('/', "%2F")
+}
+
+fn tail_lines(text: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn github() -> CiProvider {
+        CiProvider::GitHub {
+            owner: "octo".to_string(),
+            repo: "app".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_provider_from_remote_url() {
+        assert_eq!(CiProvider::from_remote_url("git@github.com:octo/app.git"), Some(github()));
+        assert_eq!(CiProvider::from_remote_url("https://github.com/octo/app"), Some(github()));
+        assert_eq!(
+            CiProvider::from_remote_url("https://token@github.com/octo/app.git"),
+            Some(github())
+        );
+        assert_eq!(
+            CiProvider::from_remote_url("ssh://git@gitlab.example.com:2222/group/sub/app.git"),
+            Some(CiProvider::GitLab {
+                host: "gitlab.example.com".to_string(),
+                project: "group/sub/app".to_string(),
+            })
+        );
+        assert_eq!(CiProvider::from_remote_url("https://bitbucket.org/octo/app"), None);
+        assert_eq!(CiProvider::from_remote_url("/local/path"), None);
+    }
+
+    #[test]
+    fn test_status_mapping_and_overall() {
+        assert_eq!(RunStatus::from_github("completed", Some("timed_out")), RunStatus::Failure);
+        assert_eq!(RunStatus::from_github("in_progress", None), RunStatus::InProgress);
+        assert_eq!(RunStatus::from_gitlab("failed"), RunStatus::Failure);
+        assert_eq!(RunStatus::from_gitlab("pending"), RunStatus::Queued);
+
+        let run = |status| CiRun {
+            id: 1,
+            name: "ci".to_string(),
+            status,
+            commit_sha: String::new(),
+            url: String::new(),
+        };
+        assert_eq!(overall_status(&[]), RunStatus::Unknown);
+        assert_eq!(overall_status(&[run(RunStatus::Success), run(RunStatus::Failure)]), RunStatus::Failure);
+        assert_eq!(overall_status(&[run(RunStatus::Success), run(RunStatus::Skipped)]), RunStatus::Success);
+    }
+
+    #[test]
+    fn test_tail_lines() {
+        assert_eq!(tail_lines("a\nb\nc", 2), "b\nc");
+        assert_eq!(tail_lines("a", 5), "a");
+    }
+
+    #[tokio::test]
+    async fn test_github_status_and_failed_logs() {
+        let mut server = mockito::Server::new_async().await;
+        let runs = server
+            .mock("GET", "/repos/octo/app/actions/runs")
+            .match_query(mockito::Matcher::UrlEncoded("branch".into(), "main".into()))
+            .match_header("authorization", "Bearer secret")
+            .with_body(
+                r#"{"workflow_runs": [
+                    {"id": 2, "name": "CI", "status": "completed", "conclusion": "failure", "head_sha": "abcdef123456", "html_url": "https://gh/run/2"},
+                    {"id": 1, "name": "CI", "status": "completed", "conclusion": "success", "head_sha": "0000", "html_url": "https://gh/run/1"},
+                    {"id": 3, "name": "Docs", "status": "completed", "conclusion": "success", "head_sha": "abcdef123456", "html_url": "https://gh/run/3"}
+                ]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let pulls = server
+            .mock("GET", "/repos/octo/app/pulls")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"[{"number": 7, "title": "Add parser", "html_url": "https://gh/pull/7"}]"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let jobs = server
+            .mock("GET", "/repos/octo/app/actions/runs/2/jobs")
+            .with_body(
+                r#"{"jobs": [
+                    {"id": 10, "name": "test", "status": "completed", "conclusion": "failure", "html_url": "https://gh/job/10"},
+                    {"id": 11, "name": "lint", "status": "completed", "conclusion": "success", "html_url": "https://gh/job/11"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let log = server
+            .mock("GET", "/repos/octo/app/actions/jobs/10/logs")
+            .with_body("setup\ncompile\nerror: test failed")
+            .create_async()
+            .await;
+
+        let mut client = CiClient::new(github(), Some("secret".to_string()));
+        client.base_url = server.url();
+
+        let status = client.get_status("main").await.unwrap();
+        assert_eq!(status.runs.len(), 2);
+        assert_eq!(status.overall, RunStatus::Failure);
+        assert_eq!(status.pull_request.as_ref().unwrap().number, 7);
+        assert!(status.format().contains("❌ CI [2]"));
+
+        let logs = client.get_failed_job_logs("main", None, 2).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].name, "test");
+        assert_eq!(logs[0].log, "compile\nerror: test failed");
+
+        runs.assert_async().await;
+        pulls.assert_async().await;
+        jobs.assert_async().await;
+        log.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_status() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/projects/group%2Fapp/pipelines")
+            .match_query(mockito::Matcher::Any)
+            .match_header("private-token", "glpat")
+            .with_body(r#"[{"id": 99, "status": "running", "sha": "feed", "web_url": "https://gl/p/99"}]"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/projects/group%2Fapp/merge_requests")
+            .match_query(mockito::Matcher::Any)
+            .with_body("[]")
+            .create_async()
+            .await;
+
+        let provider = CiProvider::GitLab {
+            host: "gitlab.com".to_string(),
+            project: "group/app".to_string(),
+        };
+        let mut client = CiClient::new(provider, Some("glpat".to_string()));
+        client.base_url = server.url();
+
+        let status = client.get_status("main").await.unwrap();
+        assert_eq!(status.overall, RunStatus::InProgress);
+        assert_eq!(status.runs[0].name, "pipeline #99");
+        assert!(status.pull_request.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_api_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/octo/app/actions/runs")
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .with_body("Not Found")
+            .create_async()
+            .await;
+
+        let mut client = CiClient::new(github(), None);
+        client.base_url = server.url();
+
+        let result = client.get_status("main").await;
+        assert!(matches!(result, Err(CiError::Api { status: 404, .. })));
+    }
+}
@@ -3,10 +3,17 @@
 //! This module provides a client for interacting with Anthropic's Claude API,
 //! including support for streaming responses and tool use.
 
+use crate::agent::FilteredToolExecutor;
 use crate::conversation::{Conversation, Message, MessageContent, Role};
-use crate::tools::{Tool, ToolResult, ToolUse};
+use crate::injection::InjectionFinding;
+use crate::outbound_filter::{OutboundFilter, OutboundFilterError};
+use crate::system_prompt::SystemBlock;
+use crate::tools::{Tool, ToolOutputEvent, ToolResult, ToolUse};
+use crate::usage::Usage;
+use aurora_core::{OutputStream, OutputStyle, SamplingParams};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Events that occur during an agentic loop execution
@@ -28,6 +35,33 @@ pub enum AgenticEvent {
         content: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
+        /// Structured data attached by the tool (e.g. build diagnostics)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<JsonValue>,
+    },
+
+    /// Output written by a tool while it is still running
+    #[serde(rename = "tool_output_chunk")]
+    ToolOutputChunk {
+        tool_use_id: String,
+        stream: OutputStream,
+        text: String,
+    },
+
+    /// A running tool's command appears to be waiting for input
+    #[serde(rename = "tool_awaiting_input")]
+    ToolAwaitingInput {
+        tool_use_id: String,
+        prompt: String,
+    },
+
+    /// Text that looked like instructions to the model was removed from a
+    /// tool result before the model saw it
+    #[serde(rename = "injection_detected")]
+    InjectionDetected {
+        tool_use_id: String,
+        tool: String,
+        findings: Vec<InjectionFinding>,
     },
 
     /// Claude sent a text response
@@ -35,6 +69,76 @@ pub enum AgenticEvent {
     TextResponse {
         text: String,
     },
+
+    /// The conversation reached its budget and the loop paused before the
+    /// next request; the user decides whether to continue
+    #[serde(rename = "budget_exceeded")]
+    BudgetExceeded {
+        /// Tokens spent by the conversation
+        tokens: u64,
+        /// Cost of the conversation in US dollars
+        cost_usd: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_tokens: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_cost_usd: Option<f64>,
+    },
+}
+
+impl AgenticEvent {
+    /// The event as the IDE shows it in `style`: tool results and output
+    /// restyled, the rest unchanged
+    pub fn styled(self, style: OutputStyle) -> Self {
+        if style == OutputStyle::Rich {
+            return self;
+        }
+        match self {
+            AgenticEvent::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+                data,
+            } => AgenticEvent::ToolResult {
+                tool_use_id,
+                content: style.apply(&content).into_owned(),
+                is_error,
+                data,
+            },
+            AgenticEvent::ToolOutputChunk {
+                tool_use_id,
+                stream,
+                text,
+            } => AgenticEvent::ToolOutputChunk {
+                tool_use_id,
+                stream,
+                text: style.apply(&text).into_owned(),
+            },
+            AgenticEvent::ToolAwaitingInput { tool_use_id, prompt } => AgenticEvent::ToolAwaitingInput {
+                tool_use_id,
+                prompt: style.apply(&prompt).into_owned(),
+            },
+            other => other,
+        }
+    }
+}
+
+impl From<ToolOutputEvent> for AgenticEvent {
+    fn from(event: ToolOutputEvent) -> Self {
+        match event {
+            ToolOutputEvent::Chunk(chunk) => AgenticEvent::ToolOutputChunk {
+                tool_use_id: chunk.tool_use_id,
+                stream: chunk.stream,
+                text: chunk.text,
+            },
+            ToolOutputEvent::AwaitingInput {
+                tool_use_id,
+                prompt,
+            } => AgenticEvent::ToolAwaitingInput {
+                tool_use_id,
+                prompt,
+            },
+        }
+    }
 }
 
 /// Errors that can occur during API client operations
@@ -71,6 +175,10 @@ pub enum ClientError {
     /// Maximum retries exceeded
     #[error("Maximum retries exceeded: {0}")]
     MaxRetriesExceeded(String),
+
+    /// The outbound filter blocked the request or failed
+    #[error(transparent)]
+    OutboundFilter(#[from] OutboundFilterError),
 }
 
 impl ClientError {
@@ -90,6 +198,7 @@ impl ClientError {
             ClientError::ApiError(_) => false,
             ClientError::JsonParse(_) => false,
             ClientError::MaxRetriesExceeded(_) => false,
+            ClientError::OutboundFilter(_) => false,
         }
     }
 
@@ -114,7 +223,13 @@ impl ClientError {
             ClientError::Http(e) if e.is_connect() => {
                 "Could not connect to the API. Please check your internet connection.".to_string()
             }
-            ClientError::Http(_) | ClientError::ApiError(_) | ClientError::JsonParse(_) => {
+            ClientError::OutboundFilter(OutboundFilterError::Blocked { filter, reason }) => {
+                format!("The message was not sent: the '{}' filter blocked it ({}).", filter, reason)
+            }
+            ClientError::Http(_)
+            | ClientError::ApiError(_)
+            | ClientError::JsonParse(_)
+            | ClientError::OutboundFilter(_) => {
                 format!("An error occurred: {}", self)
             }
         }
@@ -129,6 +244,8 @@ pub struct AnthropicClient {
     client: reqwest::Client,
     /// API base URL
     base_url: String,
+    /// Filters every request passes through before it is sent
+    outbound_filter: Option<Arc<OutboundFilter>>,
 }
 
 /// Request to send to Claude
@@ -155,6 +272,12 @@ pub struct MessageRequest {
     /// Top-k sampling (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_k: Option<usize>,
+    /// Sequences that stop generation (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+    /// System prompt as cacheable blocks, sent in place of `system` when set
+    #[serde(skip)]
+    pub system_blocks: Option<Vec<SystemBlock>>,
 }
 
 /// Content for API messages - can be either string or content blocks
@@ -209,6 +332,9 @@ pub struct MessageResponse {
     pub model: String,
     /// Stop reason
     pub stop_reason: Option<String>,
+    /// Tokens used by the request
+    #[serde(default)]
+    pub usage: Usage,
 }
 
 /// Content block in a response
@@ -305,7 +431,11 @@ pub enum StreamEvent {
     ContentBlockStop { index: usize },
 
     #[serde(rename = "message_delta")]
-    MessageDelta { delta: MessageDeltaInfo },
+    MessageDelta {
+        delta: MessageDeltaInfo,
+        #[serde(default)]
+        usage: Usage,
+    },
 
     #[serde(rename = "message_stop")]
     MessageStop,
@@ -325,6 +455,8 @@ pub struct MessageStart {
     #[serde(rename = "type")]
     pub type_: String,
     pub role: String,
+    #[serde(default)]
+    pub usage: Usage,
 }
 
 /// Content block start
@@ -333,6 +465,14 @@ pub struct MessageStart {
 pub enum ContentBlockStart {
     #[serde(rename = "text")]
     Text { text: String },
+
+    #[serde(rename = "tool_use")]
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: JsonValue,
+    },
 }
 
 /// Delta in streaming response
@@ -341,6 +481,9 @@ pub enum ContentBlockStart {
 pub enum Delta {
     #[serde(rename = "text_delta")]
     TextDelta { text: String },
+
+    #[serde(rename = "input_json_delta")]
+    InputJsonDelta { partial_json: String },
 }
 
 /// Message delta information
@@ -357,6 +500,244 @@ pub struct ApiError {
     pub message: String,
 }
 
+/// Marker appended to text salvaged from a stream that could not be completed
+pub const INTERRUPTED_MARKER: &str = "[response interrupted]";
+
+/// Maximum number of times a dropped stream is resumed before salvaging
+const MAX_STREAM_RESUMES: usize = 2;
+
+/// A content block being assembled from stream events
+#[derive(Debug, Clone)]
+enum PartialBlock {
+    Text(String),
+    ToolUse {
+        id: String,
+        name: String,
+        input_json: String,
+        complete: bool,
+    },
+}
+
+/// Assembles streaming events into a response, keeping partial content
+///
+/// If the stream drops, the content received so far is still available through
+/// [`StreamAccumulator::partial_text`], and a reconnected stream that continues
+/// the same text (see [`AnthropicClient::send_message_stream_recoverable`]) can
+/// be applied on top after calling [`StreamAccumulator::prepare_resume`].
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    blocks: Vec<PartialBlock>,
+    /// Offset added to block indices of a resumed stream
+    index_offset: usize,
+    model: Option<String>,
+    stop_reason: Option<String>,
+    complete: bool,
+    /// Tokens used by earlier attempts of a resumed stream
+    usage: Usage,
+    /// Tokens reported so far by the current attempt
+    attempt_usage: Usage,
+}
+
+impl StreamAccumulator {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a stream event
+    ///
+    /// Returns an error when the server reports an error event mid-stream.
+    pub fn apply(&mut self, event: &StreamEvent) -> Result<(), ClientError> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.model = Some(message.model.clone());
+                // Each resumed attempt is a separate, separately billed request
+                self.usage += std::mem::take(&mut self.attempt_usage);
+                self.attempt_usage = message.usage;
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                let position = self.index_offset + index;
+                match content_block {
+                    ContentBlockStart::Text { text } => match self.blocks.get_mut(position) {
+                        // A resumed stream continues the last text block
+                        Some(PartialBlock::Text(existing)) => existing.push_str(text),
+                        _ => self.blocks.push(PartialBlock::Text(text.clone())),
+                    },
+                    ContentBlockStart::ToolUse { id, name, .. } => {
+                        self.blocks.push(PartialBlock::ToolUse {
+                            id: id.clone(),
+                            name: name.clone(),
+                            input_json: String::new(),
+                            complete: false,
+                        });
+                    }
+                }
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                match (self.blocks.get_mut(self.index_offset + index), delta) {
+                    (Some(PartialBlock::Text(existing)), Delta::TextDelta { text }) => {
+                        existing.push_str(text);
+                    }
+                    (
+                        Some(PartialBlock::ToolUse { input_json, .. }),
+                        Delta::InputJsonDelta { partial_json },
+                    ) => input_json.push_str(partial_json),
+                    _ => tracing::warn!("Stream delta for unknown content block {}", index),
+                }
+            }
+            StreamEvent::ContentBlockStop { index } => {
+                if let Some(PartialBlock::ToolUse { complete, .. }) =
+                    self.blocks.get_mut(self.index_offset + index)
+                {
+                    *complete = true;
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                if delta.stop_reason.is_some() {
+                    self.stop_reason = delta.stop_reason.clone();
+                }
+                self.attempt_usage = self.attempt_usage.max(*usage);
+            }
+            StreamEvent::MessageStop => self.complete = true,
+            StreamEvent::Ping => {}
+            StreamEvent::Error { error } => {
+                return Err(match error.type_.as_str() {
+                    "overloaded_error" | "api_error" => {
+                        ClientError::ServerError(error.message.clone())
+                    }
+                    "rate_limit_error" => ClientError::RateLimitExceeded,
+                    _ => ClientError::ApiError(error.message.clone()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the message finished (a `message_stop` event was received)
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Whether any content has been received
+    pub fn has_content(&self) -> bool {
+        self.blocks.iter().any(|block| match block {
+            PartialBlock::Text(text) => !text.is_empty(),
+            PartialBlock::ToolUse { .. } => true,
+        })
+    }
+
+    /// All text received so far
+    pub fn partial_text(&self) -> String {
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                PartialBlock::Text(text) => Some(text.as_str()),
+                PartialBlock::ToolUse { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Prepare to apply a stream that continues the current text
+    ///
+    /// Returns the assistant prefill the continuation request should end
+    /// with, or `None` when the stream cannot be resumed (no text yet, or a
+    /// tool call is in progress).
+    pub fn prepare_resume(&mut self) -> Option<String> {
+        let in_progress_tool = self
+            .blocks
+            .iter()
+            .any(|block| matches!(block, PartialBlock::ToolUse { complete: false, .. }));
+        if in_progress_tool {
+            return None;
+        }
+
+        // The API rejects assistant prefills that end in whitespace
+        let last = self.blocks.len().checked_sub(1)?;
+        match &mut self.blocks[last] {
+            PartialBlock::Text(text) if !text.trim().is_empty() => {
+                text.truncate(text.trim_end().len());
+            }
+            _ => return None,
+        }
+
+        self.index_offset = last;
+        Some(self.partial_text())
+    }
+
+    /// Build the final response
+    ///
+    /// When `interrupted` is set, incomplete tool calls are dropped and
+    /// [`INTERRUPTED_MARKER`] is appended to the text.
+    pub fn into_response(mut self, interrupted: bool) -> MessageResponse {
+        self.usage += self.attempt_usage;
+        let mut content: Vec<ContentBlock> = self
+            .blocks
+            .into_iter()
+            .filter_map(|block| match block {
+                PartialBlock::Text(text) => Some(ContentBlock::Text { text }),
+                PartialBlock::ToolUse {
+                    id,
+                    name,
+                    input_json,
+                    complete,
+                } => {
+                    if interrupted && !complete {
+                        return None;
+                    }
+                    let input = if input_json.trim().is_empty() {
+                        JsonValue::Object(serde_json::Map::new())
+                    } else {
+                        match serde_json::from_str(&input_json) {
+                            Ok(input) => input,
+                            Err(e) => {
+                                tracing::warn!("Invalid tool input in stream: {}", e);
+                                return None;
+                            }
+                        }
+                    };
+                    Some(ContentBlock::ToolUse { id, name, input })
+                }
+            })
+            .collect();
+
+        let stop_reason = if interrupted {
+            match content.iter_mut().rev().find_map(|block| match block {
+                ContentBlock::Text { text } => Some(text),
+                _ => None,
+            }) {
+                Some(text) => text.push_str(&format!("\n\n{}", INTERRUPTED_MARKER)),
+                None => content.push(ContentBlock::Text {
+                    text: INTERRUPTED_MARKER.to_string(),
+                }),
+            }
+            Some("interrupted".to_string())
+        } else {
+            self.stop_reason
+        };
+
+        MessageResponse {
+            content,
+            model: self.model.unwrap_or_default(),
+            stop_reason,
+            usage: self.usage,
+        }
+    }
+}
+
+/// Result of a streamed request that survives dropped connections
+#[derive(Debug)]
+pub struct StreamedResponse {
+    /// The assembled response (salvaged content when interrupted)
+    pub response: MessageResponse,
+    /// True when the stream could not be completed and the text was salvaged
+    pub interrupted: bool,
+    /// Number of times the stream was resumed after a drop
+    pub resumed: usize,
+}
+
 impl MessageRequest {
     /// Create a new message request with default settings
     pub fn new(model: impl Into<String>, messages: Vec<ApiMessage>) -> Self {
@@ -369,15 +750,16 @@ impl MessageRequest {
             temperature: None,
             top_p: None,
             top_k: None,
+            stop_sequences: None,
+            system_blocks: None,
         }
     }
 
     /// Create a request from a Conversation
     pub fn from_conversation(conversation: &Conversation, model: impl Into<String>) -> Self {
         let messages = conversation
-            .messages()
-            .iter()
-            .map(|msg| ApiMessage::from_message(msg))
+            .included_messages()
+            .map(ApiMessage::from_message)
             .collect();
 
         Self {
@@ -389,7 +771,10 @@ impl MessageRequest {
             temperature: None,
             top_p: None,
             top_k: None,
+            stop_sequences: None,
+            system_blocks: conversation.system_blocks.clone(),
         }
+        .with_sampling(&conversation.sampling)
     }
 
     /// Set the tools available to the model
@@ -415,6 +800,47 @@ impl MessageRequest {
         self.top_k = Some(top_k);
         self
     }
+
+    /// Set the maximum number of tokens to generate
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Set the sequences that stop generation
+    pub fn with_stop_sequences(mut self, stop_sequences: Vec<String>) -> Self {
+        self.stop_sequences = Some(stop_sequences);
+        self
+    }
+
+    /// Send the system prompt as cacheable blocks
+    pub fn with_system_blocks(mut self, blocks: Vec<SystemBlock>) -> Self {
+        self.system_blocks = Some(blocks);
+        self
+    }
+
+    /// Serialize the request body, substituting system blocks when set
+    pub fn to_body(&self) -> Result<JsonValue, ClientError> {
+        let mut body = serde_json::to_value(self)?;
+        if let (Some(blocks), Some(obj)) = (&self.system_blocks, body.as_object_mut()) {
+            obj.insert("system".to_string(), serde_json::to_value(blocks)?);
+        }
+        Ok(body)
+    }
+
+    /// Apply every sampling parameter that is set
+    pub fn with_sampling(mut self, sampling: &SamplingParams) -> Self {
+        if let Some(max_tokens) = sampling.max_tokens {
+            self.max_tokens = max_tokens;
+        }
+        self.temperature = sampling.temperature.or(self.temperature);
+        self.top_p = sampling.top_p.or(self.top_p);
+        self.top_k = sampling.top_k.or(self.top_k);
+        if !sampling.stop_sequences.is_empty() {
+            self.stop_sequences = Some(sampling.stop_sequences.clone());
+        }
+        self
+    }
 }
 
 impl ApiMessage {
@@ -440,9 +866,34 @@ impl AnthropicClient {
             api_key,
             client: reqwest::Client::new(),
             base_url: "https://api.anthropic.com/v1".to_string(),
+            outbound_filter: None,
         }
     }
 
+    /// Use a different API base URL (e.g. a proxy or gateway)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Pass every request through `filter` before sending it
+    pub fn with_outbound_filter(mut self, filter: Arc<OutboundFilter>) -> Self {
+        self.outbound_filter = Some(filter);
+        self
+    }
+
+    /// The request body after the outbound filter
+    async fn outbound_body(&self, mut request: MessageRequest) -> Result<JsonValue, ClientError> {
+        if let Some(filter) = self.outbound_filter.as_ref().filter(|filter| !filter.is_empty()) {
+            // External filters run programs; keep them off the async workers
+            let filter = Arc::clone(filter);
+            request = tokio::task::spawn_blocking(move || filter.filter_request(&mut request).map(|()| request))
+                .await
+                .map_err(|e| ClientError::ApiError(format!("Outbound filter task failed: {}", e)))??;
+        }
+        request.to_body()
+    }
+
     /// Send a message to Claude and get a response
     pub async fn send_message(
         &self,
@@ -456,7 +907,7 @@ impl AnthropicClient {
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
-            .json(&request)
+            .json(&self.outbound_body(request).await?)
             .send()
             .await?;
 
@@ -497,7 +948,7 @@ impl AnthropicClient {
         let url = format!("{}/messages", self.base_url);
 
         // Create request body with stream: true
-        let mut request_json = serde_json::to_value(&request)?;
+        let mut request_json = self.outbound_body(request).await?;
         if let Some(obj) = request_json.as_object_mut() {
             obj.insert("stream".to_string(), serde_json::Value::Bool(true));
         }
@@ -524,35 +975,134 @@ impl AnthropicClient {
             });
         }
 
-        // Convert response to stream of SSE events
-        let stream = response.bytes_stream().map(move |chunk_result| {
-            let chunk = chunk_result.map_err(ClientError::Http)?;
+        // Convert response to stream of SSE events. Events can be split
+        // across chunks, so buffer bytes until a full line is available.
+        let mut buffer: Vec<u8> = Vec::new();
+        let stream = response
+            .bytes_stream()
+            .map(move |chunk_result| {
+                let chunk = match chunk_result {
+                    Ok(chunk) => chunk,
+                    Err(e) => return vec![Err(ClientError::Http(e))],
+                };
+                buffer.extend_from_slice(chunk.chunk());
+
+                let mut events = Vec::new();
+                while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=newline).collect();
+                    let line = String::from_utf8_lossy(&line);
 
-            // Parse SSE format: "data: {...}\n\n"
-            let data = String::from_utf8_lossy(chunk.chunk());
+                    // Parse SSE format: "data: {...}\n\n"
+                    if let Some(json_str) = line.trim_end().strip_prefix("data: ") {
+                        if json_str.trim() == "[DONE]" {
+                            // End of stream marker (some APIs use this)
+                            continue;
+                        }
 
-            for line in data.lines() {
-                if let Some(json_str) = line.strip_prefix("data: ") {
-                    if json_str.trim() == "[DONE]" {
-                        // End of stream marker (some APIs use this)
-                        continue;
+                        match serde_json::from_str::<StreamEvent>(json_str) {
+                            Ok(event) => events.push(Ok(event)),
+                            Err(e) => tracing::warn!("Failed to parse stream event: {}", e),
+                        }
                     }
+                }
+                events
+            })
+            .flat_map(futures::stream::iter);
 
-                    match serde_json::from_str::<StreamEvent>(json_str) {
-                        Ok(event) => return Ok(event),
-                        Err(e) => {
-                            tracing::warn!("Failed to parse stream event: {}", e);
-                            continue;
+        Ok(stream)
+    }
+
+    /// Stream a message, recovering from dropped connections
+    ///
+    /// `on_event` sees every stream event as it arrives. If the stream drops
+    /// (or the server sends an error event) after text has been received, the
+    /// request is re-sent with the partial text as an assistant prefill so the
+    /// model continues where it stopped. When that is not possible, the
+    /// partial text is returned with [`INTERRUPTED_MARKER`] instead of failing
+    /// the whole turn. Errors are only returned when nothing was received.
+    pub async fn send_message_stream_recoverable<F>(
+        &self,
+        request: MessageRequest,
+        mut on_event: F,
+    ) -> Result<StreamedResponse, ClientError>
+    where
+        F: FnMut(&StreamEvent),
+    {
+        use futures::stream::StreamExt;
+
+        let mut accumulator = StreamAccumulator::new();
+        let mut resumed = 0;
+        let mut attempt_request = request.clone();
+
+        loop {
+            let failure = match self.send_message_stream(attempt_request).await {
+                Ok(stream) => {
+                    let mut stream = Box::pin(stream);
+                    let mut failure = None;
+                    while let Some(event) = stream.next().await {
+                        let applied = event.and_then(|event| {
+                            accumulator.apply(&event)?;
+                            on_event(&event);
+                            Ok(())
+                        });
+                        if let Err(e) = applied {
+                            failure = Some(e);
+                            break;
+                        }
+                        if accumulator.is_complete() {
+                            break;
                         }
                     }
+                    failure
                 }
+                Err(e) => Some(e),
+            };
+
+            if accumulator.is_complete() {
+                return Ok(StreamedResponse {
+                    response: accumulator.into_response(false),
+                    interrupted: false,
+                    resumed,
+                });
             }
 
-            // If we couldn't parse anything, return a Ping event to keep stream alive
-            Ok(StreamEvent::Ping)
-        });
+            // Nothing to salvage: surface the original error so callers can retry
+            if !accumulator.has_content() {
+                return Err(failure.unwrap_or_else(|| {
+                    ClientError::ApiError("Stream ended before any content".to_string())
+                }));
+            }
 
-        Ok(stream)
+            let reason = failure
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| "stream ended before message_stop".to_string());
+
+            let prefill = if resumed < MAX_STREAM_RESUMES {
+                accumulator.prepare_resume()
+            } else {
+                None
+            };
+
+            match prefill {
+                Some(prefill) => {
+                    resumed += 1;
+                    tracing::warn!("Stream interrupted ({}), resuming (attempt {})", reason, resumed);
+                    attempt_request = request.clone();
+                    attempt_request.messages.push(ApiMessage {
+                        role: "assistant".to_string(),
+                        content: ApiMessageContent::Text(prefill),
+                    });
+                }
+                None => {
+                    tracing::warn!("Stream interrupted ({}), keeping partial response", reason);
+                    return Ok(StreamedResponse {
+                        response: accumulator.into_response(true),
+                        interrupted: true,
+                        resumed,
+                    });
+                }
+            }
+        }
     }
 
     /// Send a conversation to Claude and get a streaming response
@@ -587,6 +1137,8 @@ impl AnthropicClient {
     ///
     /// This method sends the conversation to Claude, executes any requested tools,
     /// and repeats until Claude responds with text (no more tool calls).
+    /// The loop pauses with [`AgenticEvent::BudgetExceeded`] before a request
+    /// once the conversation has spent its budget.
     /// Returns a vector of events that occurred during the loop for UI display.
     ///
     /// # Arguments
@@ -601,21 +1153,65 @@ impl AnthropicClient {
     pub async fn run_agentic_loop(
         &self,
         conversation: &mut Conversation,
-        executor: &crate::tools::ToolExecutor,
+        executor: &Arc<crate::tools::ToolExecutor>,
         max_iterations: Option<usize>,
     ) -> Result<Vec<AgenticEvent>, ClientError> {
-        let max_iterations = max_iterations.unwrap_or(10);
+        let executor = FilteredToolExecutor::new(Arc::clone(executor), Vec::new(), Vec::new());
         let mut events = Vec::new();
 
+        self.run_agentic_loop_with(
+            conversation,
+            &executor,
+            Self::default_model(),
+            max_iterations,
+            |event| events.push(event.clone()),
+        )
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Run an agentic loop with tool permissions, reporting events as they happen
+    ///
+    /// Only the tools permitted by `executor` are offered to the model, and
+    /// `on_event` is called for every event as soon as it occurs (used for
+    /// streaming output such as headless JSONL mode).
+    pub async fn run_agentic_loop_with<F>(
+        &self,
+        conversation: &mut Conversation,
+        executor: &FilteredToolExecutor,
+        model: &str,
+        max_iterations: Option<usize>,
+        mut on_event: F,
+    ) -> Result<(), ClientError>
+    where
+        F: FnMut(&AgenticEvent),
+    {
+        let max_iterations = max_iterations.unwrap_or(10);
+        let tools = executor.get_available_tools();
+
         for iteration in 0..max_iterations {
             tracing::debug!("Agentic loop iteration {}", iteration);
 
+            if conversation.budget_exceeded() {
+                tracing::info!("Conversation budget reached, pausing agentic loop");
+                on_event(&AgenticEvent::BudgetExceeded {
+                    tokens: conversation.usage.usage.total_tokens(),
+                    cost_usd: conversation.usage.cost_usd,
+                    max_tokens: conversation.budget.max_tokens,
+                    max_cost_usd: conversation.budget.max_cost_usd,
+                });
+                break;
+            }
+
             // Create request with tools
-            let request = MessageRequest::from_conversation(conversation, Self::default_model())
-                .with_tools(crate::tools::all_tools());
+            let request = MessageRequest::from_conversation(conversation, model)
+                .with_tools(tools.clone());
 
             // Send request
             let response = self.send_message(request).await?;
+            let billed_model = if response.model.is_empty() { model } else { &response.model };
+            conversation.usage.record(billed_model, &response.usage);
 
             // Check if response contains tool use
             let mut has_tool_use = false;
@@ -624,16 +1220,16 @@ impl AnthropicClient {
             for content_block in &response.content {
                 match content_block {
                     ContentBlock::Text { text } => {
-                        // Add text response to events
-                        events.push(AgenticEvent::TextResponse {
+                        // Report text response
+                        on_event(&AgenticEvent::TextResponse {
                             text: text.clone(),
                         });
                     }
                     ContentBlock::ToolUse { id, name, input } => {
                         has_tool_use = true;
 
-                        // Record tool call event
-                        events.push(AgenticEvent::ToolCall {
+                        // Report tool call
+                        on_event(&AgenticEvent::ToolCall {
                             id: id.clone(),
                             name: name.clone(),
                             input: input.clone(),
@@ -646,15 +1242,38 @@ impl AnthropicClient {
                             input: input.clone(),
                         };
 
-                        let result = executor.execute(&tool_use).await;
+                        // Report command output as it arrives, then the final result
+                        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+                        let execution = executor.execute_streaming(&tool_use, Some(&sender));
+                        tokio::pin!(execution);
+                        let result = loop {
+                            tokio::select! {
+                                result = &mut execution => break result,
+                                Some(output) = receiver.recv() => on_event(&output.into()),
+                            }
+                        };
+                        while let Ok(output) = receiver.try_recv() {
+                            on_event(&output.into());
+                        }
 
-                        // Record tool result event
-                        events.push(AgenticEvent::ToolResult {
+                        // Report tool result
+                        on_event(&AgenticEvent::ToolResult {
                             tool_use_id: result.tool_use_id.clone(),
                             content: result.content.clone(),
                             is_error: result.is_error,
+                            data: result.data.clone(),
                         });
 
+                        // The model gets the result as untrusted content
+                        let (result, findings) = crate::injection::sanitize_result(name, result);
+                        if !findings.is_empty() {
+                            on_event(&AgenticEvent::InjectionDetected {
+                                tool_use_id: result.tool_use_id.clone(),
+                                tool: name.clone(),
+                                findings,
+                            });
+                        }
+
                         tool_results.push(result);
                     }
                     ContentBlock::ToolResult { .. } => {
@@ -683,7 +1302,7 @@ impl AnthropicClient {
             conversation.messages.push(user_message);
         }
 
-        Ok(events)
+        Ok(())
     }
 
     /// Get the model name
@@ -754,6 +1373,29 @@ mod tests {
         assert_eq!(client.base_url, "https://api.anthropic.com/v1");
     }
 
+    #[test]
+    fn test_styled_event() {
+        let result = AgenticEvent::ToolResult {
+            tool_use_id: "call_1".to_string(),
+            content: "✅ Build succeeded".to_string(),
+            is_error: None,
+            data: None,
+        };
+        match result.clone().styled(OutputStyle::Plain) {
+            AgenticEvent::ToolResult { content, .. } => assert_eq!(content, "OK: Build succeeded"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        match result.styled(OutputStyle::Rich) {
+            AgenticEvent::ToolResult { content, .. } => assert_eq!(content, "✅ Build succeeded"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let reply = AgenticEvent::TextResponse {
+            text: "Done ✅".to_string(),
+        };
+        assert!(matches!(reply.styled(OutputStyle::Plain), AgenticEvent::TextResponse { text } if text == "Done ✅"));
+    }
+
     #[test]
     fn test_message_request_serialization() {
         let request = MessageRequest {
@@ -768,6 +1410,8 @@ mod tests {
             temperature: Some(0.7),
             top_p: None,
             top_k: None,
+            stop_sequences: None,
+            system_blocks: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -792,6 +1436,45 @@ mod tests {
         assert_eq!(request.top_k, Some(40));
     }
 
+    #[test]
+    fn test_from_conversation_applies_sampling() {
+        let mut conversation = Conversation::new();
+        conversation.add_user_message("Hello");
+        conversation.sampling = SamplingParams {
+            max_tokens: Some(512),
+            top_p: Some(0.9),
+            stop_sequences: vec!["STOP".to_string()],
+            ..SamplingParams::default()
+        };
+
+        let request = MessageRequest::from_conversation(&conversation, "claude-sonnet-4");
+        assert_eq!(request.max_tokens, 512);
+        assert_eq!(request.top_p, Some(0.9));
+        assert!(request.temperature.is_none());
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["stop_sequences"], serde_json::json!(["STOP"]));
+        assert!(json.get("top_k").is_none());
+    }
+
+    #[test]
+    fn test_sectioned_system_prompt_body() {
+        let prompt = crate::system_prompt::SystemPromptBuilder::new()
+            .identity("You are helpful")
+            .environment("Platform: linux")
+            .build();
+        let mut conversation = Conversation::with_sectioned_prompt(&prompt);
+        conversation.add_user_message("Hello");
+
+        let request = MessageRequest::from_conversation(&conversation, "claude-sonnet-4");
+        assert_eq!(request.system, Some(prompt.text()));
+
+        let body = request.to_body().unwrap();
+        assert_eq!(body["system"][0]["text"], "You are helpful");
+        assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+        assert!(body["system"][1].get("cache_control").is_none());
+    }
+
     #[test]
     fn test_from_conversation() {
         use crate::conversation::Conversation;
@@ -1172,6 +1855,202 @@ mod tests {
         mock.assert_async().await;
     }
 
+    fn sse(events: &[&str]) -> String {
+        events.iter().map(|e| format!("event: x\ndata: {}\n\n", e)).collect()
+    }
+
+    const START: &str = r#"{"type":"message_start","message":{"id":"msg_1","model":"claude-sonnet-4","type":"message","role":"assistant"}}"#;
+    const TEXT_START: &str = r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#;
+    const STOP: &str = r#"{"type":"message_stop"}"#;
+
+    fn text_delta(text: &str) -> String {
+        format!(
+            r#"{{"type":"content_block_delta","index":0,"delta":{{"type":"text_delta","text":"{}"}}}}"#,
+            text
+        )
+    }
+
+    fn stream_request() -> MessageRequest {
+        MessageRequest::new(
+            "claude-sonnet-4",
+            vec![ApiMessage {
+                role: "user".to_string(),
+                content: "Hello".to_string().into(),
+            }],
+        )
+    }
+
+    #[test]
+    fn test_stream_accumulator_tool_use() {
+        let events = [
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":"Let me look"}}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"read","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"file_path\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"a.rs\"}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"}}"#,
+        ];
+
+        let mut accumulator = StreamAccumulator::new();
+        for event in events {
+            accumulator.apply(&serde_json::from_str(event).unwrap()).unwrap();
+        }
+        assert!(!accumulator.is_complete());
+
+        let response = accumulator.into_response(false);
+        assert_eq!(response.stop_reason.as_deref(), Some("tool_use"));
+        assert_eq!(
+            response.content[1].as_tool_use().unwrap().input["file_path"],
+            "a.rs"
+        );
+    }
+
+    #[test]
+    fn test_stream_accumulator_usage() {
+        let events = [
+            r#"{"type":"message_start","message":{"id":"msg_1","model":"claude-sonnet-4","type":"message","role":"assistant","usage":{"input_tokens":120,"output_tokens":1,"cache_read_input_tokens":30}}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":null},"usage":{"output_tokens":15}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":40}}"#,
+            // A resumed attempt is billed again
+            r#"{"type":"message_start","message":{"id":"msg_2","model":"claude-sonnet-4","type":"message","role":"assistant","usage":{"input_tokens":150,"output_tokens":1}}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":10}}"#,
+        ];
+
+        let mut accumulator = StreamAccumulator::new();
+        for event in events {
+            accumulator.apply(&serde_json::from_str(event).unwrap()).unwrap();
+        }
+        let usage = accumulator.into_response(false).usage;
+        assert_eq!((usage.input_tokens, usage.output_tokens, usage.cache_read_input_tokens), (270, 50, 30));
+    }
+
+    #[tokio::test]
+    async fn test_agentic_loop_pauses_at_budget() {
+        let mut server = mockito::Server::new_async().await;
+        let request = server
+            .mock("POST", "/messages")
+            .with_body(
+                r#"{"content":[{"type":"text","text":"Done"}],"model":"claude-sonnet-4","stop_reason":"end_turn","usage":{"input_tokens":80,"output_tokens":30}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let executor = Arc::new(crate::tools::ToolExecutor::new());
+        let mut conversation = Conversation::new();
+        conversation.budget.max_tokens = Some(100);
+        conversation.add_user_message("Hello");
+
+        let events = client.run_agentic_loop(&mut conversation, &executor, None).await.unwrap();
+        assert!(matches!(&events[..], [AgenticEvent::TextResponse { text }] if text == "Done"));
+        assert_eq!(conversation.usage.usage.total_tokens(), 110);
+        assert_eq!(conversation.usage.requests, 1);
+
+        conversation.add_user_message("And then?");
+        let events = client.run_agentic_loop(&mut conversation, &executor, None).await.unwrap();
+        assert!(matches!(
+            &events[..],
+            [AgenticEvent::BudgetExceeded { tokens: 110, max_tokens: Some(100), .. }]
+        ));
+        request.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_recoverable_complete() {
+        let mut server = mockito::Server::new_async().await;
+        let delta = text_delta("Hi there");
+        server
+            .mock("POST", "/messages")
+            .with_body(sse(&[START, TEXT_START, &delta, STOP]))
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let mut seen = 0;
+        let streamed = client
+            .send_message_stream_recoverable(stream_request(), |_| seen += 1)
+            .await
+            .unwrap();
+
+        assert_eq!(seen, 4);
+        assert!(!streamed.interrupted);
+        assert_eq!(streamed.response.model, "claude-sonnet-4");
+        assert_eq!(streamed.response.content[0].as_text(), Some("Hi there"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_resumes_after_drop() {
+        let mut server = mockito::Server::new_async().await;
+        let continuation = text_delta(" world");
+        let resume = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"messages":[{"role":"user","content":"Hello"},{"role":"assistant","content":"Hello"}]}"#
+                    .to_string(),
+            ))
+            .with_body(sse(&[START, TEXT_START, &continuation, STOP]))
+            .expect(1)
+            .create_async()
+            .await;
+        let dropped = text_delta("Hello ");
+        server
+            .mock("POST", "/messages")
+            .with_body(sse(&[START, TEXT_START, &dropped]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let streamed = client
+            .send_message_stream_recoverable(stream_request(), |_| {})
+            .await
+            .unwrap();
+
+        assert!(!streamed.interrupted);
+        assert_eq!(streamed.resumed, 1);
+        assert_eq!(streamed.response.content[0].as_text(), Some("Hello world"));
+        resume.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_salvages_partial_text() {
+        let mut server = mockito::Server::new_async().await;
+        let dropped = text_delta("Partial answer");
+        server
+            .mock("POST", "/messages")
+            .with_body(sse(&[START, TEXT_START, &dropped]))
+            .expect(1 + MAX_STREAM_RESUMES)
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let streamed = client
+            .send_message_stream_recoverable(stream_request(), |_| {})
+            .await
+            .unwrap();
+
+        assert!(streamed.interrupted);
+        assert_eq!(streamed.resumed, MAX_STREAM_RESUMES);
+        assert_eq!(streamed.response.stop_reason.as_deref(), Some("interrupted"));
+        let text = streamed.response.content[0].as_text().unwrap();
+        assert!(text.starts_with("Partial answer"));
+        assert!(text.ends_with(INTERRUPTED_MARKER));
+
+        // Nothing received at all is still an error
+        let mut empty = mockito::Server::new_async().await;
+        empty
+            .mock("POST", "/messages")
+            .with_body(sse(&[START]))
+            .create_async()
+            .await;
+        let client = AnthropicClient::new("key".to_string()).with_base_url(empty.url());
+        assert!(client
+            .send_message_stream_recoverable(stream_request(), |_| {})
+            .await
+            .is_err());
+    }
+
     // Integration tests
     #[tokio::test]
     async fn test_integration_conversation_to_api_request() {
@@ -1328,7 +2207,7 @@ mod tests {
 
         // Verify request has tools
         assert!(request.tools.is_some());
-        assert_eq!(request.tools.as_ref().unwrap().len(), 19);
+        assert_eq!(request.tools.as_ref().unwrap().len(), 49);
 
         let result = client.send_message(request).await;
         assert!(result.is_ok());
@@ -1420,7 +2299,7 @@ mod tests {
         .with_tools(all_tools());
 
         assert!(request.tools.is_some());
-        assert_eq!(request.tools.as_ref().unwrap().len(), 19);
+        assert_eq!(request.tools.as_ref().unwrap().len(), 49);
 
         // Verify it serializes correctly
         let json = serde_json::to_string(&request).unwrap();
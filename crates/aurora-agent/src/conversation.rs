@@ -1,5 +1,10 @@
 //! Conversation management for the AI agent
 
+use crate::attachments::{self, Attachment, AttachmentRecord};
+use crate::persona::PromptOverride;
+use crate::system_prompt::{SystemBlock, SystemPrompt};
+use crate::usage::ConversationUsage;
+use aurora_core::{BudgetConfig, SamplingParams};
 use serde::{Deserialize, Serialize};
 
 /// Role of a message sender
@@ -75,6 +80,16 @@ pub struct Message {
     pub role: Role,
     /// Content of the message
     pub content: MessageContent,
+    /// Pinned messages are always kept when the conversation is truncated
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    /// Excluded messages stay in the history but are never sent to the API
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub excluded: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 impl Message {
@@ -83,6 +98,8 @@ impl Message {
         Self {
             role: Role::User,
             content: MessageContent::Text(content.into()),
+            pinned: false,
+            excluded: false,
         }
     }
 
@@ -91,6 +108,8 @@ impl Message {
         Self {
             role: Role::Assistant,
             content: MessageContent::Text(content.into()),
+            pinned: false,
+            excluded: false,
         }
     }
 
@@ -99,6 +118,8 @@ impl Message {
         Self {
             role: Role::User,
             content: MessageContent::Blocks(blocks),
+            pinned: false,
+            excluded: false,
         }
     }
 
@@ -107,6 +128,8 @@ impl Message {
         Self {
             role: Role::Assistant,
             content: MessageContent::Blocks(blocks),
+            pinned: false,
+            excluded: false,
         }
     }
 
@@ -126,6 +149,15 @@ impl Message {
         }
     }
 
+    /// Whether the message contains tool use or tool result blocks
+    fn has_tool_blocks(&self) -> bool {
+        self.as_blocks().is_some_and(|blocks| {
+            blocks
+                .iter()
+                .any(|b| b.is_tool_use() || b.is_tool_result())
+        })
+    }
+
     /// Get the character count of this message
     pub fn char_count(&self) -> usize {
         match &self.content {
@@ -146,6 +178,21 @@ pub struct Conversation {
     pub system_prompt: Option<String>,
     /// Messages in the conversation
     pub messages: Vec<Message>,
+    /// Sampling parameters applied to requests for this conversation
+    pub sampling: SamplingParams,
+    /// System prompt as cacheable sections (sent instead of `system_prompt` when set)
+    pub system_blocks: Option<Vec<SystemBlock>>,
+    /// Attachments waiting to be sent with the next user message
+    pub pending_attachments: Vec<Attachment>,
+    /// Attachments already sent, with the message that carried them
+    pub attachments: Vec<AttachmentRecord>,
+    /// Tokens and cost spent so far
+    pub usage: ConversationUsage,
+    /// Spending limit; the agentic loop pauses once it is reached
+    pub budget: BudgetConfig,
+    /// Persona and custom instructions for this conversation, assembled
+    /// into `system_blocks` by the prompt builder
+    pub prompt_override: PromptOverride,
 }
 
 impl Conversation {
@@ -158,7 +205,16 @@ impl Conversation {
     pub fn with_system_prompt(system_prompt: impl Into<String>) -> Self {
         Self {
             system_prompt: Some(system_prompt.into()),
-            messages: Vec::new(),
+            ..Self::default()
+        }
+    }
+
+    /// Create a conversation with a system prompt assembled from sections
+    pub fn with_sectioned_prompt(prompt: &SystemPrompt) -> Self {
+        Self {
+            system_prompt: Some(prompt.text()),
+            system_blocks: Some(prompt.to_blocks()),
+            ..Self::default()
         }
     }
 
@@ -168,10 +224,23 @@ impl Conversation {
     }
 
     /// Add a user message
+    ///
+    /// Pending attachments are wrapped into the message as context blocks and
+    /// recorded in `attachments`.
     pub fn add_user_message(&mut self, content: impl Into<String>) {
+        let pending = std::mem::take(&mut self.pending_attachments);
+        let content = attachments::wrap_message(&content.into(), &pending);
+        let index = self.messages.len();
+        self.attachments
+            .extend(pending.iter().map(|attachment| attachment.record(index)));
         self.add_message(Message::user(content));
     }
 
+    /// Attach files or folders to the next user message
+    pub fn attach(&mut self, attachments: Vec<Attachment>) {
+        self.pending_attachments.extend(attachments);
+    }
+
     /// Add an assistant message
     pub fn add_assistant_message(&mut self, content: impl Into<String>) {
         self.add_message(Message::assistant(content));
@@ -182,9 +251,91 @@ impl Conversation {
         &self.messages
     }
 
-    /// Clear all messages (keeps system prompt)
+    /// Text of the most recent assistant message, joining its text blocks
+    pub fn last_assistant_text(&self) -> Option<String> {
+        let message = self.messages.iter().rev().find(|m| m.role == Role::Assistant)?;
+        let text = match &message.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|block| match block {
+                    crate::client::ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        };
+        Some(text).filter(|text| !text.trim().is_empty())
+    }
+
+    /// Messages that are sent to the API (everything not excluded)
+    pub fn included_messages(&self) -> impl Iterator<Item = &Message> {
+        self.messages.iter().filter(|m| !m.excluded)
+    }
+
+    /// Pin or unpin a message
+    ///
+    /// Tool use and tool result messages only make sense together, so the
+    /// paired message is pinned or unpinned along with them. Returns false
+    /// if there is no message at `index`.
+    pub fn pin_message(&mut self, index: usize, pinned: bool) -> bool {
+        let Some(indices) = self.with_tool_partner(index) else {
+            return false;
+        };
+        for i in indices {
+            self.messages[i].pinned = pinned;
+        }
+        true
+    }
+
+    /// Exclude a message from (or restore it to) the context sent to the API
+    ///
+    /// Tool use and tool result messages only make sense together, so the
+    /// paired message is excluded or restored along with them. Returns false
+    /// if there is no message at `index`.
+    pub fn exclude_message(&mut self, index: usize, excluded: bool) -> bool {
+        let Some(indices) = self.with_tool_partner(index) else {
+            return false;
+        };
+        for i in indices {
+            self.messages[i].excluded = excluded;
+        }
+        true
+    }
+
+    /// The message at `index` and, for a tool use or tool result, the
+    /// message it is paired with
+    fn with_tool_partner(&self, index: usize) -> Option<Vec<usize>> {
+        let message = self.messages.get(index)?;
+
+        let mut indices = vec![index];
+        if message.has_tool_blocks() {
+            let partner = match message.role {
+                Role::Assistant => index.checked_add(1),
+                Role::User => index.checked_sub(1),
+            };
+            if let Some(partner) = partner.filter(|&i| {
+                self.messages.get(i).is_some_and(|m| m.has_tool_blocks())
+            }) {
+                indices.push(partner);
+            }
+        }
+        Some(indices)
+    }
+
+    /// Clear all messages and reset spend, budget, and prompt override
+    ///
+    /// The system prompt is kept; rebuild it if the override was set.
     pub fn clear(&mut self) {
         self.messages.clear();
+        self.usage = ConversationUsage::default();
+        self.budget = BudgetConfig::default();
+        self.prompt_override = PromptOverride::default();
+    }
+
+    /// Whether the conversation has spent its budget
+    pub fn budget_exceeded(&self) -> bool {
+        self.usage.exceeds(&self.budget)
     }
 
     /// Get the total number of characters in the conversation
@@ -201,6 +352,9 @@ impl Conversation {
     /// Truncate conversation to stay within a character limit
     /// Removes oldest messages while preserving recent context
     ///
+    /// Pinned messages are never removed, and excluded messages do not count
+    /// toward the limit since they are not sent.
+    ///
     /// # Arguments
     /// * `max_chars` - Maximum number of characters to keep (excluding system prompt)
     ///
@@ -208,7 +362,7 @@ impl Conversation {
     /// Number of messages removed
     pub fn truncate_to_limit(&mut self, max_chars: usize) -> usize {
         let system_chars = self.system_prompt.as_ref().map(|s| s.len()).unwrap_or(0);
-        let mut current_chars: usize = self.messages.iter().map(|m| m.char_count()).sum();
+        let mut current_chars: usize = self.included_messages().map(|m| m.char_count()).sum();
 
         if current_chars + system_chars <= max_chars {
             return 0; // No truncation needed
@@ -217,12 +371,17 @@ impl Conversation {
         let mut removed_count = 0;
         let target_chars = max_chars.saturating_sub(system_chars);
 
-        // Remove messages from the beginning until we're under the limit
-        while current_chars > target_chars && !self.messages.is_empty() {
-            if let Some(msg) = self.messages.first() {
+        // Remove unpinned messages from the beginning until we're under the limit
+        let mut index = 0;
+        while current_chars > target_chars && index < self.messages.len() {
+            if self.messages[index].pinned {
+                index += 1;
+                continue;
+            }
+            let msg = self.messages.remove(index);
+            if !msg.excluded {
                 current_chars = current_chars.saturating_sub(msg.char_count());
             }
-            self.messages.remove(0);
             removed_count += 1;
         }
 
@@ -280,6 +439,25 @@ mod tests {
         assert_eq!(conv.messages().len(), 0);
     }
 
+    #[test]
+    fn test_last_assistant_text() {
+        let mut conv = Conversation::new();
+        assert_eq!(conv.last_assistant_text(), None);
+
+        conv.add_assistant_message("First answer");
+        conv.add_message(Message::assistant_with_blocks(vec![
+            crate::client::ContentBlock::Text { text: "## Plan".to_string() },
+            crate::client::ContentBlock::ToolUse {
+                id: "1".to_string(),
+                name: "read".to_string(),
+                input: serde_json::json!({}),
+            },
+            crate::client::ContentBlock::Text { text: "1. Edit".to_string() },
+        ]));
+        conv.add_user_message("Looks good");
+        assert_eq!(conv.last_assistant_text().as_deref(), Some("## Plan\n\n1. Edit"));
+    }
+
     #[test]
     fn test_conversation_clear() {
         let mut conv = Conversation::with_system_prompt("System");
@@ -287,8 +465,13 @@ mod tests {
         conv.add_assistant_message("Hi");
         assert_eq!(conv.messages().len(), 2);
 
+        conv.usage.record("claude-sonnet-4", &crate::usage::Usage { input_tokens: 10, ..Default::default() });
+        conv.budget.max_tokens = Some(5);
+        assert!(conv.budget_exceeded());
+
         conv.clear();
         assert_eq!(conv.messages().len(), 0);
+        assert!(conv.usage.is_empty() && !conv.budget_exceeded());
         assert_eq!(conv.system_prompt, Some("System".to_string()));
     }
 
@@ -368,4 +551,111 @@ mod tests {
         assert_eq!(conv.message_count(), 1);
         assert_eq!(conv.messages()[0].content, "c".repeat(20));
     }
+
+    #[test]
+    fn test_truncation_keeps_pinned_and_ignores_excluded() {
+        let mut conv = Conversation::new();
+        conv.add_user_message("a".repeat(20));
+        conv.add_assistant_message("b".repeat(20));
+        conv.add_user_message("c".repeat(20));
+        conv.add_assistant_message("d".repeat(20));
+        assert!(conv.pin_message(0, true));
+        assert!(conv.exclude_message(3, true));
+        assert!(!conv.pin_message(10, true));
+
+        // Included: a, b, c = 60 chars; keeping 30 removes b and c but never a
+        let removed = conv.truncate_to_limit(30);
+        assert_eq!(removed, 2);
+        assert_eq!(conv.messages()[0].content, "a".repeat(20));
+        assert!(conv.messages()[0].pinned);
+        assert!(conv.messages()[1].excluded);
+    }
+
+    #[test]
+    fn test_exclude_tool_pair() {
+        use crate::client::ContentBlock;
+
+        let mut conv = Conversation::new();
+        conv.add_user_message("Read it");
+        conv.add_message(Message::assistant_with_blocks(vec![ContentBlock::ToolUse {
+            id: "toolu_1".to_string(),
+            name: "read".to_string(),
+            input: serde_json::json!({}),
+        }]));
+        conv.add_message(Message::user_with_blocks(vec![ContentBlock::ToolResult {
+            tool_use_id: "toolu_1".to_string(),
+            content: "lots of output".to_string(),
+            is_error: None,
+        }]));
+
+        assert!(conv.exclude_message(2, true));
+        assert!(conv.messages()[1].excluded);
+        assert!(!conv.messages()[0].excluded);
+        assert_eq!(conv.included_messages().count(), 1);
+
+        // Flags survive serialization and default to false
+        let json = serde_json::to_string(&conv.messages()[1]).unwrap();
+        assert!(json.contains("\"excluded\":true"));
+        let plain: Message = serde_json::from_str(r#"{"role":"user","content":"hi"}"#).unwrap();
+        assert!(!plain.pinned && !plain.excluded);
+    }
+
+    #[test]
+    fn test_pin_tool_result_keeps_tool_use() {
+        use crate::client::ContentBlock;
+
+        let mut conv = Conversation::new();
+        conv.add_user_message("x".repeat(100));
+        conv.add_message(Message::assistant_with_blocks(vec![ContentBlock::ToolUse {
+            id: "toolu_1".to_string(),
+            name: "read".to_string(),
+            input: serde_json::json!({}),
+        }]));
+        conv.add_message(Message::user_with_blocks(vec![ContentBlock::ToolResult {
+            tool_use_id: "toolu_1".to_string(),
+            content: "y".repeat(100),
+            is_error: None,
+        }]));
+        conv.add_assistant_message("z".repeat(100));
+
+        assert!(conv.pin_message(2, true));
+        assert!(conv.messages()[1].pinned);
+        conv.truncate_to_limit(0);
+
+        assert_eq!(conv.message_count(), 2);
+        assert!(matches!(
+            &conv.messages()[0].content,
+            MessageContent::Blocks(blocks) if matches!(blocks[0], ContentBlock::ToolUse { .. })
+        ));
+        assert!(matches!(
+            &conv.messages()[1].content,
+            MessageContent::Blocks(blocks) if matches!(blocks[0], ContentBlock::ToolResult { .. })
+        ));
+
+        assert!(conv.pin_message(0, false));
+        assert!(!conv.messages()[1].pinned);
+    }
+
+    #[test]
+    fn test_attachments_wrap_next_user_message() {
+        let mut conv = Conversation::new();
+        conv.attach(vec![Attachment {
+            path: "src/lib.rs".to_string(),
+            kind: crate::attachments::AttachmentKind::File,
+            bytes: 6,
+            summarized: false,
+            content: "mod a;".to_string(),
+        }]);
+        conv.add_user_message("Explain this");
+        conv.add_user_message("And this?");
+
+        assert!(conv.pending_attachments.is_empty());
+        assert_eq!(conv.attachments.len(), 1);
+        assert_eq!(conv.attachments[0].message_index, 0);
+        assert!(conv.messages()[0]
+            .as_text()
+            .unwrap()
+            .starts_with("<context path=\"src/lib.rs\" kind=\"file\">\nmod a;\n</context>"));
+        assert_eq!(conv.messages()[1].content, "And this?");
+    }
 }
@@ -2,21 +2,293 @@
 //!
 //! This module handles loading modular directive files from `.AuroraHeart/directives/`
 //! and assembling them into a complete system prompt.
-//! Full implementation will be done in Phase 4.
+//!
+//! Directives are templates rendered at assembly time, so one directive set
+//! can serve a polyglot monorepo:
+//! - `{{project_name}}`, `{{language}}`, and `{{os}}` are replaced with the
+//!   project's values; unknown variables are left as written
+//! - `{{#if language == rust}} ... {{else}} ... {{/if}}` keeps one branch;
+//!   conditions are `name == value`, `name != value`, or a bare `name` that
+//!   must be set. Comparisons ignore case, and blocks can nest
+//! - an optional frontmatter block between `---` lines holds `key: value`
+//!   pairs: `when: <condition>` includes the whole file only when the
+//!   condition holds, and other keys define variables for the file (project
+//!   values take precedence)
+//!
+//! A line holding nothing but a block tag is removed along with its newline.
+//!
+//! The assembled directives come in order of precedence: the project's
+//! `AURORA.md`, then the enabled directive modules, then the user's global
+//! `AURORA.md` from the per-user config directory (`~/.config/auroraheart` on
+//! Linux). The global file holds personal preferences that apply everywhere,
+//! so it goes last under its own heading, which tells the model that project
+//! directives win where the two conflict.
+
+use crate::onboarding::PROJECT_DIRECTIVES_FILE;
+use aurora_core::{detect_language, get_project_name, user_config_dir, Config};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors in a directive template
+#[derive(Error, Debug, PartialEq)]
+pub enum DirectiveError {
+    /// `{{else}}` or `{{/if}}` without a matching `{{#if}}`, or a second `{{else}}`
+    #[error("Unexpected {{{{{0}}}}} on line {1}")]
+    UnexpectedTag(String, usize),
+
+    /// A `{{#if}}` block is never closed
+    #[error("Unclosed {{{{#if}}}} block")]
+    UnclosedBlock,
+
+    /// The frontmatter block is never closed
+    #[error("Unclosed frontmatter block")]
+    UnclosedFrontmatter,
+}
+
+pub type Result<T> = std::result::Result<T, DirectiveError>;
+
+/// Values substituted into directive templates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirectiveVariables {
+    values: BTreeMap<String, String>,
+}
+
+impl DirectiveVariables {
+    /// No variables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The project's name, language, and the operating system
+    ///
+    /// Configured values are preferred over detected ones.
+    pub fn for_project(project_root: &Path, config: &Config) -> Self {
+        let mut variables = Self::new().with("os", std::env::consts::OS);
+        if let Some(name) = config.project.name.clone().or_else(|| get_project_name(project_root)) {
+            variables.set("project_name", name);
+        }
+        let language = config
+            .project
+            .language
+            .clone()
+            .or_else(|| detect_language(project_root).ok().map(|language| language.as_str().to_string()));
+        if let Some(language) = language {
+            variables.set("language", language);
+        }
+        variables
+    }
+
+    /// Set a variable, returning the updated set
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Set a variable
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// A variable's value
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Whether a condition holds
+    fn holds(&self, condition: &str) -> bool {
+        let condition = condition.trim();
+        let (name, expected, negated) = if let Some((name, value)) = condition.split_once("!=") {
+            (name, Some(value), true)
+        } else if let Some((name, value)) = condition.split_once("==") {
+            (name, Some(value), false)
+        } else {
+            (condition, None, false)
+        };
+        let actual = self.get(name.trim()).unwrap_or_default();
+
+        match expected {
+            Some(value) => actual.eq_ignore_ascii_case(unquote(value.trim())) != negated,
+            None => !actual.is_empty() && !actual.eq_ignore_ascii_case("false"),
+        }
+    }
+}
 
-use std::path::PathBuf;
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// Render a directive: apply its frontmatter, conditional blocks, and variables
+///
+/// Returns an empty string when the frontmatter's `when` condition fails.
+pub fn render_directive(content: &str, variables: &DirectiveVariables) -> Result<String> {
+    let (frontmatter, body) = split_frontmatter(content)?;
+
+    let mut variables = variables.clone();
+    let mut when = None;
+    for (key, value) in frontmatter {
+        if key == "when" {
+            when = Some(value);
+        } else if variables.get(&key).is_none() {
+            variables.set(key, value);
+        }
+    }
+    if when.is_some_and(|condition| !variables.holds(&condition)) {
+        return Ok(String::new());
+    }
+
+    render_template(body, &variables)
+}
+
+/// Split `key: value` frontmatter from the body
+fn split_frontmatter(content: &str) -> Result<(Vec<(String, String)>, &str)> {
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return Ok((Vec::new(), content));
+    };
+
+    let mut pairs = Vec::new();
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim();
+        if line == "---" {
+            return Ok((pairs, &rest[offset..]));
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            pairs.push((key.trim().to_string(), unquote(value.trim()).to_string()));
+        }
+    }
+    Err(DirectiveError::UnclosedFrontmatter)
+}
+
+/// A `{{#if}}` block being rendered
+struct Block {
+    /// Whether the enclosing text is rendered
+    parent_active: bool,
+    condition: bool,
+    in_else: bool,
+}
+
+impl Block {
+    fn active(&self) -> bool {
+        self.parent_active && (self.condition != self.in_else)
+    }
+}
+
+/// Kind of `{{...}}` tag
+enum Tag<'a> {
+    If(&'a str),
+    Else,
+    EndIf,
+    Variable(&'a str),
+}
+
+fn parse_tag(inner: &str) -> Tag<'_> {
+    let inner = inner.trim();
+    if let Some(condition) = inner.strip_prefix("#if ") {
+        Tag::If(condition)
+    } else if inner == "else" {
+        Tag::Else
+    } else if inner == "/if" {
+        Tag::EndIf
+    } else {
+        Tag::Variable(inner)
+    }
+}
+
+/// Render conditional blocks and variables
+fn render_template(body: &str, variables: &DirectiveVariables) -> Result<String> {
+    let mut output = String::new();
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for (index, line) in body.split_inclusive('\n').enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+        // A line holding only a block tag leaves no trace
+        let standalone = trimmed.starts_with("{{")
+            && trimmed.ends_with("}}")
+            && trimmed.matches("{{").count() == 1
+            && !matches!(parse_tag(&trimmed[2..trimmed.len() - 2]), Tag::Variable(_));
+
+        let mut rest = line;
+        while !rest.is_empty() {
+            let active = blocks.last().is_none_or(Block::active);
+            let Some(start) = rest.find("{{") else {
+                if active && !standalone {
+                    output.push_str(rest);
+                }
+                break;
+            };
+            let Some(length) = rest[start..].find("}}") else {
+                if active {
+                    output.push_str(rest);
+                }
+                break;
+            };
+            if active && !standalone {
+                output.push_str(&rest[..start]);
+            }
+            let inner = &rest[start + 2..start + length];
+            match parse_tag(inner) {
+                Tag::If(condition) => blocks.push(Block {
+                    parent_active: active,
+                    condition: variables.holds(condition),
+                    in_else: false,
+                }),
+                Tag::Else => match blocks.last_mut() {
+                    Some(block) if !block.in_else => block.in_else = true,
+                    _ => return Err(DirectiveError::UnexpectedTag("else".to_string(), line_number)),
+                },
+                Tag::EndIf => {
+                    if blocks.pop().is_none() {
+                        return Err(DirectiveError::UnexpectedTag("/if".to_string(), line_number));
+                    }
+                }
+                Tag::Variable(name) => {
+                    if active {
+                        match variables.get(name) {
+                            Some(value) => output.push_str(value),
+                            None => output.push_str(&rest[start..start + length + 2]),
+                        }
+                    }
+                }
+            }
+            rest = &rest[start + length + 2..];
+        }
+    }
+
+    if blocks.is_empty() {
+        Ok(output)
+    } else {
+        Err(DirectiveError::UnclosedBlock)
+    }
+}
 
 /// Manages loading and assembling directive files
 pub struct DirectiveManager {
     /// Path to the directives directory
-    #[allow(dead_code)] // Will be used in Phase 4
     directives_path: PathBuf,
+    /// Values substituted into directive templates
+    variables: DirectiveVariables,
 }
 
 impl DirectiveManager {
     /// Create a new directive manager
     pub fn new(directives_path: PathBuf) -> Self {
-        Self { directives_path }
+        Self {
+            directives_path,
+            variables: DirectiveVariables::new(),
+        }
+    }
+
+    /// Render directives with `variables`
+    pub fn with_variables(mut self, variables: DirectiveVariables) -> Self {
+        self.variables = variables;
+        self
     }
 
     /// Load and assemble the system prompt from directives
@@ -24,6 +296,82 @@ impl DirectiveManager {
     pub fn assemble_system_prompt(&self) -> String {
         "You are a helpful AI coding assistant.".to_string()
     }
+
+    /// Load and render the enabled directive files (`<name>.md`) and join them
+    ///
+    /// Missing, unreadable, or malformed directives are skipped with a warning.
+    pub fn load_enabled(&self, names: &[String]) -> String {
+        names
+            .iter()
+            .filter_map(|name| self.load(&self.directives_path.join(format!("{}.md", name))))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Load and render one directive file, or `None` if it is empty or unusable
+    fn load(&self, path: &Path) -> Option<String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::warn!("Skipping directive {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match render_directive(&content, &self.variables) {
+            Ok(rendered) => Some(rendered.trim().to_string()).filter(|rendered| !rendered.is_empty()),
+            Err(e) => {
+                tracing::warn!("Skipping directive {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Heading placed above the user's global directives
+pub const GLOBAL_DIRECTIVES_HEADING: &str = "# Personal preferences";
+
+/// Tells the model how global directives rank against the project's
+const GLOBAL_DIRECTIVES_PRECEDENCE: &str = "These are the user's own preferences from their global AURORA.md and \
+apply to every project. Where they conflict with the project directives, follow the project directives.";
+
+/// Location of the user's global `AURORA.md`
+pub fn global_directives_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(PROJECT_DIRECTIVES_FILE))
+}
+
+/// Directives for a project: its `AURORA.md`, then the enabled directive
+/// modules, then the user's global `AURORA.md` unless `agent.global_directives`
+/// is off, all rendered with the project's variables
+pub fn project_directives(project_root: &Path, config: &Config) -> String {
+    let global = if config.agent.global_directives {
+        global_directives_path()
+    } else {
+        None
+    };
+    assemble_directives(project_root, config, global.as_deref())
+}
+
+/// Assemble project directives with the global directives read from
+/// `global_directives`, if given and present
+pub fn assemble_directives(project_root: &Path, config: &Config, global_directives: Option<&Path>) -> String {
+    let directives_dir = config
+        .directives_dir()
+        .unwrap_or_else(|| project_root.join(".AuroraHeart").join("directives"));
+    let manager = DirectiveManager::new(directives_dir)
+        .with_variables(DirectiveVariables::for_project(project_root, config));
+
+    let global = global_directives
+        .filter(|path| path.is_file())
+        .and_then(|path| manager.load(path))
+        .map(|global| format!("{}\n\n{}\n\n{}", GLOBAL_DIRECTIVES_HEADING, GLOBAL_DIRECTIVES_PRECEDENCE, global));
+
+    manager
+        .load(&project_root.join(PROJECT_DIRECTIVES_FILE))
+        .into_iter()
+        .chain(Some(manager.load_enabled(&config.agent.enabled_directives)).filter(|enabled| !enabled.is_empty()))
+        .chain(global)
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
 #[cfg(test)]
@@ -43,4 +391,124 @@ mod tests {
         let prompt = manager.assemble_system_prompt();
         assert!(!prompt.is_empty());
     }
+
+    #[test]
+    fn test_load_enabled_directives() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("rust.md"), "Use clippy.\n").unwrap();
+        std::fs::write(temp_dir.path().join("testing.md"), "Write tests.").unwrap();
+
+        let manager = DirectiveManager::new(temp_dir.path().to_path_buf());
+        let names = vec!["rust".to_string(), "missing".to_string(), "testing".to_string()];
+        assert_eq!(manager.load_enabled(&names), "Use clippy.\n\nWrite tests.");
+        assert!(manager.load_enabled(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_project_directives_include_aurora_md() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let directives_dir = temp_dir.path().join(".AuroraHeart/directives");
+        std::fs::create_dir_all(&directives_dir).unwrap();
+        std::fs::write(directives_dir.join("rust.md"), "Use clippy.").unwrap();
+        let mut config = Config::default();
+        config.agent.enabled_directives = vec!["rust".to_string()];
+        config.agent.global_directives = false;
+
+        assert_eq!(project_directives(temp_dir.path(), &config), "Use clippy.");
+        std::fs::write(temp_dir.path().join("AURORA.md"), "# Demo\n\nRun cargo test.\n").unwrap();
+        assert_eq!(
+            project_directives(temp_dir.path(), &config),
+            "# Demo\n\nRun cargo test.\n\nUse clippy."
+        );
+    }
+
+    #[test]
+    fn test_global_directives_come_last() {
+        let project = tempfile::TempDir::new().unwrap();
+        let home = tempfile::TempDir::new().unwrap();
+        let global = home.path().join("AURORA.md");
+        std::fs::write(project.path().join("AURORA.md"), "Use npm.").unwrap();
+        std::fs::write(project.path().join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        let config = Config::default();
+
+        // A missing global file adds nothing
+        assert_eq!(assemble_directives(project.path(), &config, Some(&global)), "Use npm.");
+
+        std::fs::write(&global, "Prefer pnpm.\n{{#if language == rust}}Prefer nextest.{{/if}}\n").unwrap();
+        let assembled = assemble_directives(project.path(), &config, Some(&global));
+        let (project_part, global_part) = assembled.split_once(GLOBAL_DIRECTIVES_HEADING).unwrap();
+        assert_eq!(project_part.trim(), "Use npm.");
+        assert!(global_part.contains("follow the project directives"));
+        assert!(global_part.ends_with("Prefer pnpm.\nPrefer nextest."), "{}", global_part);
+
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(assemble_directives(empty.path(), &config, Some(&global)).starts_with(GLOBAL_DIRECTIVES_HEADING));
+        assert_eq!(assemble_directives(project.path(), &config, None), "Use npm.");
+    }
+
+    #[test]
+    fn test_render_variables_and_conditionals() {
+        let variables = DirectiveVariables::new()
+            .with("project_name", "demo")
+            .with("language", "rust")
+            .with("os", "linux");
+        let template = "# {{project_name}} ({{ unknown }})\n\
+                        {{#if language == Rust}}\n\
+                        Run `cargo clippy`.\n\
+                        {{#if os != windows}}\n\
+                        Use `/` paths.\n\
+                        {{/if}}\n\
+                        {{else}}\n\
+                        Run the linter.\n\
+                        {{/if}}\n\
+                        Target: {{#if language == go}}go{{else}}{{language}}{{/if}} on {{os}}\n";
+
+        assert_eq!(
+            render_directive(template, &variables).unwrap(),
+            "# demo ({{ unknown }})\nRun `cargo clippy`.\nUse `/` paths.\nTarget: rust on linux\n"
+        );
+
+        let python = variables.clone().with("language", "python");
+        assert_eq!(
+            render_directive(template, &python).unwrap(),
+            "# demo ({{ unknown }})\nRun the linter.\nTarget: python on linux\n"
+        );
+    }
+
+    #[test]
+    fn test_render_frontmatter() {
+        let variables = DirectiveVariables::new().with("language", "typescript");
+        let directive = "---\nwhen: language == typescript\nrunner: vitest\nlanguage: ignored\n---\nTest {{language}} with {{runner}}.\n";
+
+        assert_eq!(render_directive(directive, &variables).unwrap(), "Test typescript with vitest.\n");
+        assert_eq!(render_directive(directive, &DirectiveVariables::new()).unwrap(), "");
+        assert_eq!(render_directive("---\nwhen: os\n", &variables), Err(DirectiveError::UnclosedFrontmatter));
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let variables = DirectiveVariables::new();
+        assert_eq!(render_template("{{#if os}}\nx\n", &variables), Err(DirectiveError::UnclosedBlock));
+        assert_eq!(
+            render_template("a\n{{/if}}\n", &variables),
+            Err(DirectiveError::UnexpectedTag("/if".to_string(), 2))
+        );
+        assert_eq!(
+            render_template("{{#if os}}{{else}}{{else}}{{/if}}", &variables),
+            Err(DirectiveError::UnexpectedTag("else".to_string(), 1))
+        );
+    }
+
+    #[test]
+    fn test_load_enabled_renders_and_skips_malformed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("rust.md"), "---\nwhen: language == rust\n---\nUse clippy.\n").unwrap();
+        std::fs::write(temp_dir.path().join("broken.md"), "{{#if os}}\nnever closed\n").unwrap();
+        std::fs::write(temp_dir.path().join("os.md"), "Running on {{os}}.").unwrap();
+
+        let manager = DirectiveManager::new(temp_dir.path().to_path_buf())
+            .with_variables(DirectiveVariables::new().with("os", "linux").with("language", "go"));
+        let names = vec!["rust".to_string(), "broken".to_string(), "os".to_string()];
+        assert_eq!(manager.load_enabled(&names), "Running on linux.");
+    }
 }
@@ -0,0 +1,243 @@
+//! Editor state shared with the agent
+//!
+//! The UI keeps this state current (open tabs, the active tab, and the current
+//! selection) so the `get_selection` and `get_open_editors` tools can answer
+//! requests like "refactor this" with what the user is actually looking at.
+//! Unsaved buffers are exposed through [`BufferSync`] so file tools see the
+//! same content as the user and the editor learns about agent writes. The
+//! problem list is exposed through [`DiagnosticsProvider`] for the
+//! `get_diagnostics` and `apply_quick_fix` tools.
+
+use aurora_core::diagnostics::{Diagnostic, DiagnosticFilter};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Editor state shared between the UI and the tool executor
+pub type SharedEditorState = Arc<RwLock<EditorState>>;
+
+/// Bridge between file tools and the editor's open buffers
+pub trait BufferSync: Send + Sync {
+    /// Unsaved content of the file, if the editor holds a dirty buffer for it
+    fn dirty_content(&self, path: &Path) -> Option<String>;
+
+    /// Files the editor holds unsaved changes for
+    fn dirty_paths(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Called after a tool writes the file on disk
+    fn file_written(&self, path: &Path);
+}
+
+/// Bridge between the `get_diagnostics` tool and the IDE's problem list
+pub trait DiagnosticsProvider: Send + Sync {
+    /// Current diagnostics matching the filter, most severe first
+    fn diagnostics(&self, filter: &DiagnosticFilter) -> Vec<Diagnostic>;
+
+    /// Look up a diagnostic by its [`Diagnostic::id`]
+    fn diagnostic(&self, id: &str) -> Option<Diagnostic> {
+        self.diagnostics(&DiagnosticFilter::default())
+            .into_iter()
+            .find(|diagnostic| diagnostic.id() == id)
+    }
+
+    /// Called after a quick fix for the diagnostic was applied
+    fn fixed(&self, id: &str);
+}
+
+/// A text selection in an editor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextSelection {
+    /// Path of the file containing the selection
+    pub path: String,
+    /// First selected line (1-based)
+    pub start_line: usize,
+    /// Column on the first line (1-based)
+    pub start_column: usize,
+    /// Last selected line (1-based)
+    pub end_line: usize,
+    /// Column on the last line (1-based)
+    pub end_column: usize,
+    /// Selected text
+    pub text: String,
+}
+
+/// A file open in an editor tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenEditor {
+    /// Path of the open file
+    pub path: String,
+    /// Language identifier, if known
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Whether this is the focused tab
+    #[serde(default)]
+    pub is_active: bool,
+    /// Whether the tab has unsaved changes
+    #[serde(default)]
+    pub is_modified: bool,
+}
+
+/// Snapshot of the editor as seen by the user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EditorState {
+    /// Open editor tabs in display order
+    #[serde(default)]
+    pub open_editors: Vec<OpenEditor>,
+    /// Current non-empty selection, if any
+    #[serde(default)]
+    pub selection: Option<TextSelection>,
+}
+
+impl EditorState {
+    /// Create a shared, empty editor state
+    pub fn shared() -> SharedEditorState {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    /// The focused editor tab
+    pub fn active_editor(&self) -> Option<&OpenEditor> {
+        self.open_editors.iter().find(|e| e.is_active)
+    }
+
+    /// Describe the current selection for the agent
+    pub fn describe_selection(&self) -> String {
+        match &self.selection {
+            Some(selection) if !selection.text.is_empty() => {
+                let language = self
+                    .open_editors
+                    .iter()
+                    .find(|e| e.path == selection.path)
+                    .and_then(|e| e.language.as_deref())
+                    .unwrap_or("");
+                format!(
+                    "Selection in {} (line {}, column {} to line {}, column {}):\n```{}\n{}\n```",
+                    selection.path,
+                    selection.start_line,
+                    selection.start_column,
+                    selection.end_line,
+                    selection.end_column,
+                    language,
+                    selection.text
+                )
+            }
+            _ => match self.active_editor() {
+                Some(editor) => {
+                    format!("No text is selected. The active editor is {}.", editor.path)
+                }
+                None => "No text is selected and no editor is open.".to_string(),
+            },
+        }
+    }
+
+    /// Describe the open editor tabs for the agent
+    pub fn describe_open_editors(&self) -> String {
+        if self.open_editors.is_empty() {
+            return "No editors are open.".to_string();
+        }
+
+        let lines: Vec<String> = self
+            .open_editors
+            .iter()
+            .map(|editor| {
+                let mut flags = Vec::new();
+                if editor.is_active {
+                    flags.push("active");
+                }
+                if editor.is_modified {
+                    flags.push("unsaved changes");
+                }
+                if let Some(language) = &editor.language {
+                    flags.push(language);
+                }
+
+                if flags.is_empty() {
+                    format!("- {}", editor.path)
+                } else {
+                    format!("- {} ({})", editor.path, flags.join(", "))
+                }
+            })
+            .collect();
+
+        format!(
+            "{} open editors:\n{}",
+            self.open_editors.len(),
+            lines.join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> EditorState {
+        EditorState {
+            open_editors: vec![
+                OpenEditor {
+                    path: "src/lib.rs".to_string(),
+                    language: Some("rust".to_string()),
+                    is_active: true,
+                    is_modified: true,
+                },
+                OpenEditor {
+                    path: "README.md".to_string(),
+                    language: None,
+                    is_active: false,
+                    is_modified: false,
+                },
+            ],
+            selection: Some(TextSelection {
+                path: "src/lib.rs".to_string(),
+                start_line: 3,
+                start_column: 1,
+                end_line: 4,
+                end_column: 2,
+                text: "fn parse() {\n}".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_describe_selection() {
+        let mut state = state();
+        assert_eq!(
+            state.describe_selection(),
+            "Selection in src/lib.rs (line 3, column 1 to line 4, column 2):\n```rust\nfn parse() {\n}\n```"
+        );
+
+        state.selection = None;
+        assert_eq!(
+            state.describe_selection(),
+            "No text is selected. The active editor is src/lib.rs."
+        );
+
+        assert_eq!(
+            EditorState::default().describe_selection(),
+            "No text is selected and no editor is open."
+        );
+    }
+
+    #[test]
+    fn test_describe_open_editors() {
+        assert_eq!(
+            state().describe_open_editors(),
+            "2 open editors:\n- src/lib.rs (active, unsaved changes, rust)\n- README.md"
+        );
+        assert_eq!(
+            EditorState::default().describe_open_editors(),
+            "No editors are open."
+        );
+    }
+
+    #[test]
+    fn test_deserialize_from_ui_payload() {
+        let state: EditorState = serde_json::from_value(serde_json::json!({
+            "open_editors": [{ "path": "a.rs", "is_active": true }]
+        }))
+        .unwrap();
+        assert_eq!(state.active_editor().map(|e| e.path.as_str()), Some("a.rs"));
+        assert!(state.selection.is_none());
+    }
+}
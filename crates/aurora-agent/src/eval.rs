@@ -0,0 +1,596 @@
+//! Local evaluation harness for agent regressions
+//!
+//! An eval suite is a directory of scenario files, `.AuroraHeart/evals/*.toml`
+//! by default. A scenario gives a prompt, the files of a small workspace, and
+//! what the agent is expected to do with them:
+//!
+//! ```toml
+//! prompt = "Add a `double` function to src/lib.rs"
+//!
+//! [files]
+//! "src/lib.rs" = "pub fn one() -> i32 {\n    1\n}\n"
+//!
+//! [expect]
+//! tools = ["read", "edit"]
+//!
+//! [expect.files."src/lib.rs"]
+//! contains = ["fn double"]
+//! ```
+//!
+//! [`EvalRunner`] replays each scenario in a fresh temporary workspace with
+//! the project's current agent configuration: its config, tool policy, and
+//! directives are copied in before the scenario's files. Expected tools must
+//! be called in the listed order, with other calls allowed between them.
+//! Failed file checks carry a diff of the expected and actual contents.
+//!
+//! Everything runs locally. Workspaces, and the transcripts written in them,
+//! are deleted after each scenario, and reports name scenarios and tools
+//! only.
+
+use crate::client::{AgenticEvent, AnthropicClient};
+use crate::headless::{self, HeadlessEvent, HeadlessOptions};
+use crate::onboarding::PROJECT_DIRECTIVES_FILE;
+use crate::tools::POLICY_FILE;
+use aurora_core::unified_diff;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Directory of eval scenarios in the project's `.AuroraHeart` directory
+pub const EVALS_DIR: &str = "evals";
+
+/// Errors from loading eval scenarios
+#[derive(Debug, Error)]
+pub enum EvalError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A scenario file could not be parsed
+    #[error("Invalid scenario {path}: {message}")]
+    InvalidScenario { path: PathBuf, message: String },
+}
+
+/// Result type for evals
+pub type Result<T> = std::result::Result<T, EvalError>;
+
+/// A recorded task and the outcome expected of the agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalScenario {
+    /// Scenario name; the file stem when unset
+    #[serde(default)]
+    pub name: String,
+
+    /// What the scenario covers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Prompt sent to the agent
+    pub prompt: String,
+
+    /// Workspace files by relative path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+
+    /// Agentic loop iteration limit; the agent's default when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<usize>,
+
+    /// Expected outcome
+    #[serde(default)]
+    pub expect: EvalExpectation,
+}
+
+/// What a scenario checks after the agent has run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalExpectation {
+    /// Tools that must be called, in this order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+
+    /// Tools that must not be called
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden_tools: Vec<String>,
+
+    /// Text the last response must contain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_contains: Vec<String>,
+
+    /// Checks of workspace files by relative path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, FileExpectation>,
+}
+
+/// Expected state of one workspace file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileExpectation {
+    /// Whether the file exists; it must exist when other checks are set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+
+    /// Exact contents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+
+    /// Text the file must contain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contains: Vec<String>,
+
+    /// Text the file must not contain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_contains: Vec<String>,
+}
+
+impl EvalScenario {
+    /// Load a scenario file, naming it after the file when it has no name
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |message: String| EvalError::InvalidScenario {
+            path: path.to_path_buf(),
+            message,
+        };
+        let mut scenario: Self = toml::from_str(&std::fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?;
+        if scenario.name.trim().is_empty() {
+            scenario.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        if let Some(file) = scenario.files.keys().chain(scenario.expect.files.keys()).find(|file| !is_relative(file)) {
+            return Err(invalid(format!("'{}' is not a relative path inside the workspace", file)));
+        }
+        Ok(scenario)
+    }
+}
+
+/// Every scenario in `dir`, sorted by name
+pub fn load_suite(dir: &Path) -> Result<Vec<EvalScenario>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut scenarios = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "toml") {
+            scenarios.push(EvalScenario::load(&path)?);
+        }
+    }
+    scenarios.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(scenarios)
+}
+
+/// The project's eval suite directory, `.AuroraHeart/evals`
+pub fn suite_dir(project_root: &Path) -> PathBuf {
+    project_root.join(".AuroraHeart").join(EVALS_DIR)
+}
+
+fn is_relative(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// A check a scenario failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalFailure {
+    /// What was checked, such as `tools` or `file src/lib.rs`
+    pub check: String,
+    /// How the outcome differed from the expectation
+    pub message: String,
+    /// Unified diff of expected and actual contents, for exact file checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+impl EvalFailure {
+    fn new(check: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            check: check.into(),
+            message: message.into(),
+            diff: None,
+        }
+    }
+}
+
+/// What the agent did in a scenario
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvalOutcome {
+    /// Tools called, in order
+    pub tools: Vec<String>,
+    /// Text of the last response
+    pub response: String,
+}
+
+impl EvalOutcome {
+    /// Collect the tool calls and last response of agentic loop events
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a AgenticEvent>) -> Self {
+        let mut outcome = Self::default();
+        for event in events {
+            match event {
+                AgenticEvent::ToolCall { name, .. } => outcome.tools.push(name.clone()),
+                AgenticEvent::TextResponse { text } => outcome.response = text.clone(),
+                _ => {}
+            }
+        }
+        outcome
+    }
+}
+
+/// Checks of `expect` that `outcome` and the files in `workspace` fail
+pub fn check(expect: &EvalExpectation, outcome: &EvalOutcome, workspace: &Path) -> Vec<EvalFailure> {
+    let mut failures = Vec::new();
+
+    let mut called = outcome.tools.iter();
+    if !expect.tools.iter().all(|tool| called.any(|name| name == tool)) {
+        failures.push(EvalFailure::new(
+            "tools",
+            format!("expected [{}] in order, called [{}]", expect.tools.join(", "), outcome.tools.join(", ")),
+        ));
+    }
+    for tool in &expect.forbidden_tools {
+        if outcome.tools.contains(tool) {
+            failures.push(EvalFailure::new("tools", format!("called forbidden tool {}", tool)));
+        }
+    }
+    for text in &expect.response_contains {
+        if !outcome.response.contains(text.as_str()) {
+            failures.push(EvalFailure::new("response", format!("missing {:?}", text)));
+        }
+    }
+
+    for (file, expected) in &expect.files {
+        let label = format!("file {}", file);
+        let actual = std::fs::read_to_string(workspace.join(file)).ok();
+        let must_exist = expected.exists.unwrap_or(true);
+        let Some(actual) = actual else {
+            if must_exist {
+                failures.push(EvalFailure::new(label, "missing"));
+            }
+            continue;
+        };
+        if !must_exist {
+            failures.push(EvalFailure::new(label, "exists but should not"));
+            continue;
+        }
+        if let Some(equals) = &expected.equals {
+            if &actual != equals {
+                failures.push(EvalFailure {
+                    diff: Some(unified_diff(equals, &actual, "expected", "actual")),
+                    ..EvalFailure::new(label.clone(), "contents differ")
+                });
+            }
+        }
+        for text in &expected.contains {
+            if !actual.contains(text.as_str()) {
+                failures.push(EvalFailure::new(label.clone(), format!("missing {:?}", text)));
+            }
+        }
+        for text in &expected.not_contains {
+            if actual.contains(text.as_str()) {
+                failures.push(EvalFailure::new(label.clone(), format!("unexpectedly contains {:?}", text)));
+            }
+        }
+    }
+    failures
+}
+
+/// The result of one scenario
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    /// Scenario name
+    pub name: String,
+    /// Whether every check passed
+    pub passed: bool,
+    /// Tools the agent called, in order
+    pub tools: Vec<String>,
+    /// Failed checks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<EvalFailure>,
+    /// Error that stopped the run, such as an API failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Wall-clock time of the run
+    pub duration_ms: u64,
+}
+
+/// Results of a suite run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    /// One result per scenario, in run order
+    pub results: Vec<ScenarioResult>,
+}
+
+impl EvalReport {
+    /// Number of scenarios that passed
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed).count()
+    }
+
+    /// Number of scenarios that failed
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    /// Plain text summary with the failures and their diffs
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        for result in &self.results {
+            out.push_str(&format!(
+                "{} {} ({} ms)\n",
+                if result.passed { "PASS" } else { "FAIL" },
+                result.name,
+                result.duration_ms
+            ));
+            if let Some(error) = &result.error {
+                out.push_str(&format!("  error: {}\n", error));
+            }
+            for failure in &result.failures {
+                out.push_str(&format!("  {}: {}\n", failure.check, failure.message));
+                if let Some(diff) = &failure.diff {
+                    for line in diff.lines() {
+                        out.push_str(&format!("    {}\n", line));
+                    }
+                }
+            }
+        }
+        out.push_str(&format!("{} passed, {} failed\n", self.passed(), self.failed()));
+        out
+    }
+}
+
+/// A scenario's temporary workspace, removed when dropped
+struct EvalWorkspace {
+    dir: PathBuf,
+}
+
+impl EvalWorkspace {
+    /// Copy the project's agent configuration and the scenario's files into
+    /// a new directory under the system temp directory
+    fn create(project_root: &Path, scenario: &EvalScenario) -> std::io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "auroraheart-eval-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        ));
+        std::fs::create_dir_all(&dir)?;
+        // Dropping removes the directory, including on a failed copy below
+        let workspace = Self { dir };
+
+        let config_dir = project_root.join(".AuroraHeart");
+        for file in ["config.toml", POLICY_FILE] {
+            if config_dir.join(file).is_file() {
+                std::fs::create_dir_all(workspace.dir.join(".AuroraHeart"))?;
+                std::fs::copy(config_dir.join(file), workspace.dir.join(".AuroraHeart").join(file))?;
+            }
+        }
+        copy_dir(&config_dir.join("directives"), &workspace.dir.join(".AuroraHeart").join("directives"))?;
+        if project_root.join(PROJECT_DIRECTIVES_FILE).is_file() {
+            std::fs::copy(project_root.join(PROJECT_DIRECTIVES_FILE), workspace.dir.join(PROJECT_DIRECTIVES_FILE))?;
+        }
+
+        for (file, contents) in &scenario.files {
+            let path = workspace.dir.join(file);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+        Ok(workspace)
+    }
+}
+
+impl Drop for EvalWorkspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replays scenarios against the agent
+pub struct EvalRunner<'a> {
+    client: &'a AnthropicClient,
+    project_root: PathBuf,
+    model: Option<String>,
+}
+
+impl<'a> EvalRunner<'a> {
+    /// Run scenarios with `client` and the configuration of the project at
+    /// `project_root`
+    pub fn new(client: &'a AnthropicClient, project_root: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            project_root: project_root.into(),
+            model: None,
+        }
+    }
+
+    /// Use a model other than the default
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Run one scenario in its own workspace
+    pub async fn run_scenario(&self, scenario: &EvalScenario) -> ScenarioResult {
+        let started = Instant::now();
+        let mut result = ScenarioResult {
+            name: scenario.name.clone(),
+            passed: false,
+            tools: Vec::new(),
+            failures: Vec::new(),
+            error: None,
+            duration_ms: 0,
+        };
+
+        match EvalWorkspace::create(&self.project_root, scenario) {
+            Ok(workspace) => {
+                let options = HeadlessOptions {
+                    prompt: Some(scenario.prompt.clone()),
+                    project_root: workspace.dir.clone(),
+                    model: self.model.clone(),
+                    max_iterations: scenario.max_iterations,
+                    allowed_tools: Vec::new(),
+                    denied_tools: Vec::new(),
+                    plan: false,
+                };
+                let mut events = Vec::new();
+                let run = headless::run(self.client, &options, &scenario.prompt, |event| {
+                    if let HeadlessEvent::Agent(event) = event {
+                        events.push(event.clone());
+                    }
+                })
+                .await;
+
+                let outcome = EvalOutcome::from_events(&events);
+                match run {
+                    Ok(_) => result.failures = check(&scenario.expect, &outcome, &workspace.dir),
+                    Err(e) => result.error = Some(e.to_string()),
+                }
+                result.tools = outcome.tools;
+            }
+            Err(e) => result.error = Some(format!("Failed to create workspace: {}", e)),
+        }
+
+        result.passed = result.error.is_none() && result.failures.is_empty();
+        result.duration_ms = started.elapsed().as_millis() as u64;
+        result
+    }
+
+    /// Run scenarios one after another, calling `on_result` as each finishes
+    pub async fn run_suite<F>(&self, scenarios: &[EvalScenario], mut on_result: F) -> EvalReport
+    where
+        F: FnMut(&ScenarioResult),
+    {
+        let mut report = EvalReport::default();
+        for scenario in scenarios {
+            tracing::info!("Running eval scenario {}", scenario.name);
+            let result = self.run_scenario(scenario).await;
+            on_result(&result);
+            report.results.push(result);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_suite() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("add-double.toml"),
+            "prompt = \"Add double\"\n\n[files]\n\"src/lib.rs\" = \"pub fn one() {}\\n\"\n\n\
+             [expect]\ntools = [\"read\", \"edit\"]\n\n[expect.files.\"src/lib.rs\"]\ncontains = [\"fn double\"]\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.md"), "not a scenario").unwrap();
+
+        let suite = load_suite(dir.path()).unwrap();
+        assert_eq!(suite.len(), 1);
+        assert_eq!(suite[0].name, "add-double");
+        assert_eq!(suite[0].expect.tools, vec!["read", "edit"]);
+        assert_eq!(suite[0].expect.files["src/lib.rs"].contains, vec!["fn double"]);
+
+        std::fs::write(dir.path().join("escape.toml"), "prompt = \"x\"\n[files]\n\"../outside\" = \"\"\n").unwrap();
+        let err = load_suite(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("'../outside' is not a relative path"));
+        assert!(load_suite(&dir.path().join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_failures() {
+        let workspace = TempDir::new().unwrap();
+        std::fs::write(workspace.path().join("lib.rs"), "fn one() {}\nfn two() {}\n").unwrap();
+        std::fs::write(workspace.path().join("scratch.txt"), "").unwrap();
+
+        let outcome = EvalOutcome {
+            tools: vec!["glob".to_string(), "read".to_string(), "bash".to_string(), "edit".to_string()],
+            response: "Added `two`.".to_string(),
+        };
+        let mut expect = EvalExpectation {
+            tools: vec!["read".to_string(), "edit".to_string()],
+            response_contains: vec!["two".to_string()],
+            ..Default::default()
+        };
+        expect.files.insert(
+            "lib.rs".to_string(),
+            FileExpectation {
+                contains: vec!["fn two".to_string()],
+                ..Default::default()
+            },
+        );
+        assert!(check(&expect, &outcome, workspace.path()).is_empty());
+
+        expect.tools = vec!["edit".to_string(), "read".to_string()];
+        expect.forbidden_tools = vec!["bash".to_string()];
+        expect.files.insert(
+            "lib.rs".to_string(),
+            FileExpectation {
+                equals: Some("fn one() {}\n".to_string()),
+                ..Default::default()
+            },
+        );
+        expect.files.insert(
+            "scratch.txt".to_string(),
+            FileExpectation {
+                exists: Some(false),
+                ..Default::default()
+            },
+        );
+        expect.files.insert("missing.rs".to_string(), FileExpectation::default());
+
+        let failures = check(&expect, &outcome, workspace.path());
+        let messages: Vec<_> = failures
+            .iter()
+            .map(|failure| format!("{}: {}", failure.check, failure.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "tools: expected [edit, read] in order, called [glob, read, bash, edit]",
+                "tools: called forbidden tool bash",
+                "file lib.rs: contents differ",
+                "file missing.rs: missing",
+                "file scratch.txt: exists but should not",
+            ]
+        );
+        assert!(failures[2].diff.as_deref().unwrap().contains("+fn two() {}"));
+
+        let report = EvalReport {
+            results: vec![ScenarioResult {
+                name: "add-two".to_string(),
+                passed: false,
+                tools: outcome.tools.clone(),
+                failures,
+                error: None,
+                duration_ms: 12,
+            }],
+        };
+        let summary = report.summary();
+        assert!(summary.starts_with("FAIL add-two (12 ms)\n  tools: expected"));
+        assert!(summary.ends_with("0 passed, 1 failed\n"));
+    }
+}
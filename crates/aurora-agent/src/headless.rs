@@ -0,0 +1,604 @@
+//! Headless agent runner
+//!
+//! This module runs the agentic loop without the Tauri UI, using the same
+//! tools, tool permissions, and directives, and reports every event as one JSON
+//! object per line (JSONL). It backs the `aurora-agent run` command used for CI
+//! automation and scripting. With nobody to approve them, tool calls run
+//! auto-approved within the policy's blast-radius limits. Each run is also
+//! written to a transcript under `.AuroraHeart/transcripts/`, named
+//! `headless-<timestamp>.jsonl`.
+
+use crate::agent::FilteredToolExecutor;
+use crate::client::{AgenticEvent, AnthropicClient, ClientError};
+use crate::conversation::Conversation;
+use crate::directives::project_directives;
+use crate::outbound_filter::OutboundFilter;
+use crate::plan::AgentMode;
+use crate::sessions::SessionMetadata;
+use crate::system_prompt::{SystemPrompt, SystemPromptBuilder};
+use crate::tools::{PolicyError, ToolExecutor, ToolPolicy};
+use crate::transcripts::{TranscriptStore, TranscriptWriter};
+use crate::usage::{ConversationUsage, UsageRecord};
+use aurora_core::{Config, CredentialStore};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Environment variable checked for the API key before the credential store
+pub const API_KEY_ENV_VAR: &str = "ANTHROPIC_API_KEY";
+
+/// Usage text for the `aurora-agent` binary
+pub const USAGE: &str = "\
+Usage: aurora-agent run [OPTIONS]
+       aurora-agent serve [--project <DIR>]
+       aurora-agent prompt [--project <DIR>]
+       aurora-agent eval [--project <DIR>] [--model <MODEL>] [SCENARIO...]
+
+run     Run the AuroraHeart agent without the UI, printing events as JSONL.
+serve   Serve the agent over JSON-RPC 2.0 on stdin/stdout (one message per line)
+        for editor integrations.
+prompt  Print the system prompt assembled for the project, including project
+        and global directives.
+eval    Replay the project's eval scenarios (.AuroraHeart/evals/*.toml) in
+        temporary workspaces and report which pass; all of them when none are
+        named.
+
+Options:
+  -p, --prompt <TEXT>         Prompt to send (read from stdin if omitted)
+  -C, --project <DIR>         Project directory (default: current directory)
+  -m, --model <MODEL>         Model to use
+      --max-iterations <N>    Maximum agentic loop iterations (default: 10)
+      --allow-tool <NAME>     Only allow the named tool (repeatable)
+      --deny-tool <NAME>      Deny the named tool (repeatable)
+      --plan                  Plan only: allow read and analysis tools and print a plan
+  -h, --help                  Print this help
+";
+
+/// Errors that can occur in headless mode
+#[derive(Error, Debug)]
+pub enum HeadlessError {
+    /// Invalid command-line arguments
+    #[error("{0}")]
+    InvalidArgs(String),
+
+    /// No API key in the environment or credential store
+    #[error("No API key found: set {API_KEY_ENV_VAR} or save one in the project credential store")]
+    MissingApiKey,
+
+    /// Project configuration could not be loaded
+    #[error("Configuration error: {0}")]
+    Config(#[from] aurora_core::ConfigError),
+
+    /// The project's tool policy could not be loaded
+    #[error("Policy error: {0}")]
+    Policy(#[from] PolicyError),
+
+    /// API client error
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, HeadlessError>;
+
+/// Options for a headless run
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessOptions {
+    /// Prompt to send (None = read from stdin)
+    pub prompt: Option<String>,
+    /// Project directory used as the tool working directory
+    pub project_root: PathBuf,
+    /// Model override
+    pub model: Option<String>,
+    /// Maximum agentic loop iterations
+    pub max_iterations: Option<usize>,
+    /// Tools that are allowed (empty = all)
+    pub allowed_tools: Vec<String>,
+    /// Tools that are denied
+    pub denied_tools: Vec<String>,
+    /// Run in read-only plan mode
+    pub plan: bool,
+}
+
+/// Options for an eval run
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalOptions {
+    /// Project whose scenarios and agent configuration are used
+    pub project_root: PathBuf,
+    /// Model override
+    pub model: Option<String>,
+    /// Scenarios to run by name (empty = all)
+    pub scenarios: Vec<String>,
+}
+
+/// Parsed command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadlessCommand {
+    /// Run the agent
+    Run(HeadlessOptions),
+    /// Serve the agent over JSON-RPC on stdio for the given project
+    Serve(PathBuf),
+    /// Print the assembled system prompt for the given project
+    Prompt(PathBuf),
+    /// Replay the project's eval scenarios
+    Eval(EvalOptions),
+    /// Print usage
+    Help,
+}
+
+impl HeadlessCommand {
+    /// Parse command-line arguments (excluding the program name)
+    pub fn parse<I, S>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+
+        match args.next().as_deref() {
+            Some("run") => {}
+            Some(command @ ("serve" | "prompt")) => {
+                let command = command.to_string();
+                let mut project_root = std::env::current_dir()?;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "-C" | "--project" => {
+                            project_root = PathBuf::from(args.next().ok_or_else(|| {
+                                HeadlessError::InvalidArgs(format!("Missing value for {}", arg))
+                            })?);
+                        }
+                        "-h" | "--help" => return Ok(HeadlessCommand::Help),
+                        other => {
+                            return Err(HeadlessError::InvalidArgs(format!("Unknown option: {}", other)))
+                        }
+                    }
+                }
+                return Ok(if command == "serve" {
+                    HeadlessCommand::Serve(project_root)
+                } else {
+                    HeadlessCommand::Prompt(project_root)
+                });
+            }
+            Some("eval") => {
+                let mut options = EvalOptions {
+                    project_root: std::env::current_dir()?,
+                    model: None,
+                    scenarios: Vec::new(),
+                };
+                while let Some(arg) = args.next() {
+                    let mut value = |name: &str| {
+                        args.next()
+                            .ok_or_else(|| HeadlessError::InvalidArgs(format!("Missing value for {}", name)))
+                    };
+                    match arg.as_str() {
+                        "-C" | "--project" => options.project_root = PathBuf::from(value(&arg)?),
+                        "-m" | "--model" => options.model = Some(value(&arg)?),
+                        "-h" | "--help" => return Ok(HeadlessCommand::Help),
+                        other if other.starts_with('-') => {
+                            return Err(HeadlessError::InvalidArgs(format!("Unknown option: {}", other)))
+                        }
+                        _ => options.scenarios.push(arg),
+                    }
+                }
+                return Ok(HeadlessCommand::Eval(options));
+            }
+            Some("-h") | Some("--help") | Some("help") | None => return Ok(HeadlessCommand::Help),
+            Some(other) => {
+                return Err(HeadlessError::InvalidArgs(format!("Unknown command: {}", other)))
+            }
+        }
+
+        let mut options = HeadlessOptions {
+            prompt: None,
+            project_root: std::env::current_dir()?,
+            model: None,
+            max_iterations: None,
+            allowed_tools: Vec::new(),
+            denied_tools: Vec::new(),
+            plan: false,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| HeadlessError::InvalidArgs(format!("Missing value for {}", name)))
+            };
+
+            match arg.as_str() {
+                "-p" | "--prompt" => options.prompt = Some(value(&arg)?),
+                "-C" | "--project" => options.project_root = PathBuf::from(value(&arg)?),
+                "-m" | "--model" => options.model = Some(value(&arg)?),
+                "--max-iterations" => {
+                    let raw = value(&arg)?;
+                    let parsed = raw.parse::<usize>().map_err(|_| {
+                        HeadlessError::InvalidArgs(format!("Invalid --max-iterations: {}", raw))
+                    })?;
+                    options.max_iterations = Some(parsed);
+                }
+                "--allow-tool" => options.allowed_tools.push(value(&arg)?),
+                "--deny-tool" => options.denied_tools.push(value(&arg)?),
+                "--plan" => options.plan = true,
+                "-h" | "--help" => return Ok(HeadlessCommand::Help),
+                other => {
+                    return Err(HeadlessError::InvalidArgs(format!("Unknown option: {}", other)))
+                }
+            }
+        }
+
+        Ok(HeadlessCommand::Run(options))
+    }
+}
+
+/// A line of headless output
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum HeadlessEvent<'a> {
+    /// An event from the agentic loop
+    Agent(&'a AgenticEvent),
+    /// Final status line
+    Status(HeadlessStatus),
+}
+
+/// Final status of a headless run
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HeadlessStatus {
+    /// The run completed
+    Done {
+        /// Number of messages in the final conversation
+        messages: usize,
+    },
+    /// The run failed
+    Error {
+        /// Error description
+        message: String,
+    },
+}
+
+impl HeadlessEvent<'_> {
+    /// Serialize the event as a single JSON line (without trailing newline)
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            format!(r#"{{"type":"error","message":"failed to serialize event: {}"}}"#, e)
+        })
+    }
+}
+
+/// Find the API key from the environment or the project's credential store
+pub fn resolve_api_key(project_root: &Path) -> Result<String> {
+    if let Ok(key) = std::env::var(API_KEY_ENV_VAR) {
+        if !key.trim().is_empty() {
+            return Ok(key);
+        }
+    }
+
+    CredentialStore::for_project(project_root)
+        .retrieve("anthropic_api_key", "auroraheart")
+        .map_err(|_| HeadlessError::MissingApiKey)
+}
+
+/// A client for the project: its API key and its outbound filter
+pub fn project_client(project_root: &Path) -> Result<AnthropicClient> {
+    let api_key = resolve_api_key(project_root)?;
+    let config = Config::load(project_root)?;
+    let filter = OutboundFilter::for_project(project_root, &config.outbound_filter).map_err(ClientError::from)?;
+    Ok(AnthropicClient::new(api_key).with_outbound_filter(Arc::new(filter)))
+}
+
+/// Assemble the system prompt from the project's directives and environment
+pub fn system_prompt(project_root: &Path) -> Result<SystemPrompt> {
+    system_prompt_for_mode(project_root, &AgentMode::Execute)
+}
+
+/// Assemble the system prompt with the instructions for an agent mode
+pub fn system_prompt_for_mode(project_root: &Path, mode: &AgentMode) -> Result<SystemPrompt> {
+    let config = Config::load(project_root)?;
+
+    Ok(SystemPromptBuilder::for_project(project_root)
+        .response_language(&config.locale)
+        .agent_mode(mode)
+        .directives(project_directives(project_root, &config))
+        .build())
+}
+
+/// Run the agent headlessly, calling `on_event` for each event as it occurs
+///
+/// Returns the final conversation.
+pub async fn run<F>(
+    client: &AnthropicClient,
+    options: &HeadlessOptions,
+    prompt: &str,
+    mut on_event: F,
+) -> Result<Conversation>
+where
+    F: FnMut(HeadlessEvent<'_>),
+{
+    let mode = if options.plan { AgentMode::Plan } else { AgentMode::Execute };
+    let mut conversation =
+        Conversation::with_sectioned_prompt(&system_prompt_for_mode(&options.project_root, &mode)?);
+    let agent_config = Config::load(&options.project_root)?.agent;
+    conversation.sampling = agent_config.sampling;
+    conversation.budget = agent_config.budget;
+    conversation.add_user_message(prompt);
+
+    let executor = tool_executor(options, mode)?;
+    let model = options
+        .model
+        .clone()
+        .unwrap_or_else(|| AnthropicClient::default_model().to_string());
+
+    let session = format!("headless-{}", SessionMetadata::new().id);
+    let mut transcript = start_transcript(&options.project_root, &session, &model, prompt);
+
+    let result = client
+        .run_agentic_loop_with(
+            &mut conversation,
+            &executor,
+            &model,
+            options.max_iterations,
+            |event| {
+                if let Some(writer) = transcript.as_mut() {
+                    if let Err(e) = writer.agentic(event) {
+                        tracing::warn!("Failed to write transcript: {}", e);
+                    }
+                }
+                on_event(HeadlessEvent::Agent(event))
+            },
+        )
+        .await;
+
+    if let Some(mut writer) = transcript {
+        let records = UsageRecord::since(&ConversationUsage::default(), &conversation.usage, &session);
+        let written = match &result {
+            Ok(()) => writer.usage(&records).and_then(|()| writer.finish(None)),
+            Err(e) => writer.finish(Some(e.to_string())),
+        };
+        if let Err(e) = written {
+            tracing::warn!("Failed to write transcript: {}", e);
+        }
+    }
+    result?;
+
+    on_event(HeadlessEvent::Status(HeadlessStatus::Done {
+        messages: conversation.message_count(),
+    }));
+
+    Ok(conversation)
+}
+
+/// The executor for a headless run's tool calls
+///
+/// Nobody is there to approve calls, so they run auto-approved within the
+/// blast-radius limits of the project's policy.
+fn tool_executor(options: &HeadlessOptions, mode: AgentMode) -> Result<FilteredToolExecutor> {
+    Ok(FilteredToolExecutor::new(
+        ToolExecutor::with_working_directory(&options.project_root).with_policy(ToolPolicy::load(&options.project_root)?),
+        options.allowed_tools.clone(),
+        options.denied_tools.clone(),
+    )
+    .with_mode(mode)
+    .with_auto_approve())
+}
+
+/// Start the transcript of a headless run, logging rather than failing on errors
+fn start_transcript(project_root: &Path, session: &str, model: &str, prompt: &str) -> Option<TranscriptWriter> {
+    let started = TranscriptStore::for_project(project_root)
+        .start_turn(session, model)
+        .and_then(|mut writer| writer.user_message(prompt).map(|()| writer));
+    match started {
+        Ok(writer) => {
+            tracing::info!("Writing transcript for session {}", session);
+            Some(writer)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to start transcript: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_run_options() {
+        let command = HeadlessCommand::parse([
+            "run",
+            "--prompt",
+            "fix the build",
+            "-C",
+            "/tmp/project",
+            "--max-iterations",
+            "3",
+            "--allow-tool",
+            "read",
+            "--allow-tool",
+            "grep",
+            "--deny-tool",
+            "bash",
+            "--plan",
+        ])
+        .unwrap();
+
+        let HeadlessCommand::Run(options) = command else {
+            panic!("expected run command");
+        };
+        assert_eq!(options.prompt.as_deref(), Some("fix the build"));
+        assert_eq!(options.project_root, PathBuf::from("/tmp/project"));
+        assert_eq!(options.max_iterations, Some(3));
+        assert_eq!(options.allowed_tools, vec!["read", "grep"]);
+        assert_eq!(options.denied_tools, vec!["bash"]);
+        assert!(options.plan);
+    }
+
+    #[test]
+    fn test_parse_errors_and_help() {
+        assert_eq!(HeadlessCommand::parse(Vec::<String>::new()).unwrap(), HeadlessCommand::Help);
+        assert_eq!(HeadlessCommand::parse(["run", "--help"]).unwrap(), HeadlessCommand::Help);
+        assert!(matches!(
+            HeadlessCommand::parse(["launch"]),
+            Err(HeadlessError::InvalidArgs(_))
+        ));
+        assert_eq!(
+            HeadlessCommand::parse(["serve", "--project", "/tmp/p"]).unwrap(),
+            HeadlessCommand::Serve(PathBuf::from("/tmp/p"))
+        );
+        assert_eq!(
+            HeadlessCommand::parse(["prompt", "-C", "/tmp/p"]).unwrap(),
+            HeadlessCommand::Prompt(PathBuf::from("/tmp/p"))
+        );
+        assert_eq!(
+            HeadlessCommand::parse(["eval", "-C", "/tmp/p", "add-double", "--model", "claude-haiku-4"]).unwrap(),
+            HeadlessCommand::Eval(EvalOptions {
+                project_root: PathBuf::from("/tmp/p"),
+                model: Some("claude-haiku-4".to_string()),
+                scenarios: vec!["add-double".to_string()],
+            })
+        );
+        assert!(matches!(
+            HeadlessCommand::parse(["run", "--prompt"]),
+            Err(HeadlessError::InvalidArgs(msg)) if msg.contains("Missing value")
+        ));
+        assert!(matches!(
+            HeadlessCommand::parse(["run", "--max-iterations", "many"]),
+            Err(HeadlessError::InvalidArgs(_))
+        ));
+    }
+
+    #[test]
+    fn test_event_json_lines() {
+        let event = AgenticEvent::TextResponse {
+            text: "hi".to_string(),
+        };
+        assert_eq!(
+            HeadlessEvent::Agent(&event).to_json_line(),
+            r#"{"type":"text_response","text":"hi"}"#
+        );
+        assert_eq!(
+            HeadlessEvent::Status(HeadlessStatus::Done { messages: 2 }).to_json_line(),
+            r#"{"type":"done","messages":2}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_respects_tool_permissions() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut server = mockito::Server::new_async().await;
+
+        // First turn requests a denied tool, second turn answers with text
+        let tool_turn = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::Regex("delete it".to_string()))
+            .with_body(
+                r#"{"id": "msg_1", "type": "message", "role": "assistant", "model": "m",
+                    "content": [{"type": "tool_use", "id": "toolu_1", "name": "bash", "input": {"command": "rm -rf ."}}],
+                    "stop_reason": "tool_use", "usage": {"input_tokens": 1, "output_tokens": 1}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let text_turn = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::Regex("not allowed".to_string()))
+            .with_body(
+                r#"{"id": "msg_2", "type": "message", "role": "assistant", "model": "m",
+                    "content": [{"type": "text", "text": "Done"}],
+                    "stop_reason": "end_turn", "usage": {"input_tokens": 1, "output_tokens": 1}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+
+        let options = HeadlessOptions {
+            prompt: None,
+            project_root: temp_dir.path().to_path_buf(),
+            model: None,
+            max_iterations: Some(5),
+            allowed_tools: Vec::new(),
+            denied_tools: vec!["bash".to_string()],
+            plan: false,
+        };
+
+        let mut lines = Vec::new();
+        run(&client, &options, "delete it", |event| lines.push(event.to_json_line()))
+            .await
+            .unwrap();
+
+        tool_turn.assert_async().await;
+        text_turn.assert_async().await;
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains(r#""type":"tool_call""#));
+        assert!(lines[1].contains("not allowed"));
+        assert_eq!(lines[2], r#"{"type":"text_response","text":"Done"}"#);
+        assert!(lines[3].contains(r#""type":"done""#));
+
+        let transcripts: Vec<_> = std::fs::read_dir(temp_dir.path().join(".AuroraHeart").join("transcripts"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(transcripts.len(), 1);
+        let session = transcripts[0].file_stem().unwrap().to_str().unwrap();
+        assert!(session.starts_with("headless-"));
+        let entries = TranscriptStore::for_project(temp_dir.path()).read(session).unwrap();
+        assert_eq!(entries.len(), 7, "start, prompt, call, result, reply, usage, end");
+        assert!(matches!(entries[6].event, crate::transcripts::TranscriptEvent::TurnEnd { error: None }));
+    }
+
+    #[tokio::test]
+    async fn test_run_refuses_calls_beyond_auto_approve_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut server = mockito::Server::new_async().await;
+
+        let tool_turn = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::Regex("ship it".to_string()))
+            .with_body(
+                r#"{"id": "msg_1", "type": "message", "role": "assistant", "model": "m",
+                    "content": [{"type": "tool_use", "id": "toolu_1", "name": "bash", "input": {"command": "git push origin main"}}],
+                    "stop_reason": "tool_use", "usage": {"input_tokens": 1, "output_tokens": 1}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let text_turn = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::Regex("does not allow running 'git push'".to_string()))
+            .with_body(
+                r#"{"id": "msg_2", "type": "message", "role": "assistant", "model": "m",
+                    "content": [{"type": "text", "text": "Not pushed"}],
+                    "stop_reason": "end_turn", "usage": {"input_tokens": 1, "output_tokens": 1}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let options = HeadlessOptions {
+            prompt: None,
+            project_root: temp_dir.path().to_path_buf(),
+            model: None,
+            max_iterations: Some(5),
+            allowed_tools: Vec::new(),
+            denied_tools: Vec::new(),
+            plan: false,
+        };
+
+        let mut lines = Vec::new();
+        run(&client, &options, "ship it", |event| lines.push(event.to_json_line()))
+            .await
+            .unwrap();
+
+        tool_turn.assert_async().await;
+        text_turn.assert_async().await;
+        assert!(lines[1].contains(r#""is_error":true"#), "{}", lines[1]);
+        assert!(lines[1].contains("does not allow running 'git push'"), "{}", lines[1]);
+    }
+}
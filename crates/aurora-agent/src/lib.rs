@@ -3,21 +3,104 @@
 //! This crate provides the AI agent functionality for AuroraHeart, including:
 //! - Anthropic API client with streaming support
 //! - Tool system (Read, Write, Edit, Bash, Grep, Glob, Task)
+//! - Out-of-process tool execution with CPU, memory, and time limits
 //! - Conversation management
-//! - Directive loading and assembly
+//! - Editor selection and open tabs shared with the agent
+//! - File and folder attachments as explicit message context
+//! - Prompt injection defenses for tool-returned content
+//! - Outbound content filtering (DLP) with redaction, blocking, and an audit log
+//! - Directive loading and assembly with template variables and conditionals
+//! - Sectioned system prompt assembly with token budgets
+//! - Per-conversation personas and custom system prompts
 //! - Agent spawning and execution with custom prompts
+//! - CI status and failed job logs
+//! - Issue tracker context (Jira, GitHub Issues, Linear)
+//! - Headless mode for running the agent from the command line
+//! - Local evaluation harness replaying recorded scenarios for regressions
+//! - JSON-RPC server for external editor integration
+//! - Saved sessions with generated titles and search
+//! - Model-polished wording for generated changelog sections
+//! - Project onboarding that proposes AURORA.md and config from repo analysis
+//! - Read-only plan mode with plan approval
+//! - Token usage and cost tracking with per-conversation budgets
+//! - Usage store with daily, per-model, and per-conversation reports
+//! - JSONL conversation transcripts for offline analysis
+//! - Signed application updates with stable and beta channels
+//! - Crash recovery snapshots of unsaved buffers and in-flight turns
+//! - Voice input transcription with whisper.cpp or a speech-to-text API
+//! - Temporary project fixtures for tool tests (`test-support` feature)
 
 pub mod client;
 pub mod tools;
 pub mod conversation;
 pub mod directives;
+pub mod editor;
+pub mod eval;
+pub mod headless;
+pub mod injection;
 pub mod agent;
+pub mod attachments;
+pub mod changelog;
+pub mod ci;
+pub mod issues;
+pub mod onboarding;
+pub mod outbound_filter;
+pub mod persona;
+pub mod plan;
+pub mod recovery;
+pub mod rpc;
+pub mod sessions;
+pub mod system_prompt;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod transcripts;
+pub mod updates;
+pub mod usage;
+pub mod voice;
 
-pub use agent::{AgentContext, AgentError, AgentExecutor, FilteredToolExecutor};
-pub use client::{AnthropicClient, ClientError, StreamEvent, Delta, ApiError, MessageRequest, MessageResponse, ContentBlock, AgenticEvent};
+pub use agent::{AgentContext, AgentError, AgentExecutor, FilteredToolExecutor, ToolApproval};
+pub use attachments::{Attachment, AttachmentError, AttachmentKind, AttachmentLimits, AttachmentRecord};
+pub use ci::{CiClient, CiError, CiProvider, CiRun, CiStatus, JobLog, RunStatus};
+pub use client::{AnthropicClient, ClientError, StreamEvent, Delta, ApiError, MessageRequest, MessageResponse, ContentBlock, AgenticEvent, StreamAccumulator, StreamedResponse, INTERRUPTED_MARKER};
 pub use conversation::{Conversation, Message, MessageContent, Role};
-pub use directives::DirectiveManager;
-pub use tools::{Tool, ToolUse, ToolResult, ToolExecutor, ToolError, all_tools};
+pub use directives::{
+    assemble_directives, global_directives_path, project_directives, render_directive, DirectiveError,
+    DirectiveManager, DirectiveVariables, GLOBAL_DIRECTIVES_HEADING,
+};
+pub use editor::{BufferSync, DiagnosticsProvider, EditorState, OpenEditor, SharedEditorState, TextSelection};
+pub use eval::{EvalError, EvalExpectation, EvalFailure, EvalReport, EvalRunner, EvalScenario, FileExpectation, ScenarioResult};
+pub use injection::{InjectionFinding, SanitizedContent, EXTERNAL_CONTENT_TOOLS};
+pub use issues::{Issue, IssueClient, IssueComment, IssueError, IssueTracker};
+pub use onboarding::{OnboardingError, OnboardingPlan, ProjectAnalysis, ProposedFile, PROJECT_DIRECTIVES_FILE};
+pub use outbound_filter::{
+    CommandFilter, ContentFilter, FilterAuditEntry, FilterAuditLog, FilterVerdict, OutboundFilter, OutboundFilterError,
+    RegexFilter,
+};
+pub use persona::{Persona, PromptOverride};
+pub use plan::{AgentMode, PLAN_MODE_TOOLS};
+pub use recovery::{InFlightTurn, RecoveryError, RecoverySnapshot, RecoveryStore, UnsavedBuffer};
+pub use sessions::{SessionError, SessionMetadata, SessionSearchResult, SessionStore};
+pub use system_prompt::{repo_map, PromptSection, PromptSectionKind, SystemBlock, SystemPrompt, SystemPromptBuilder};
+pub use tools::{
+    AgentTool, CallOptions, CancellationToken, Capability, RunningTool, SlowCall, Tool, ToolCapabilities, ToolContext, ToolFuture, ToolRegistry,
+    ToolUse, ToolResult, ToolCache, ToolExecutor, ToolError,
+    ToolMetrics, ToolMetricsReport, ToolOutputChunk, ToolOutputEvent, ToolOutputSender, ToolPolicy,
+    ToolStats, ToolStatsEntry, all_tools, AutoApproveGuard, AutoApproveLimits, PolicyError, POLICY_FILE,
+    ReplEntry, ReplLanguage, ReplSessionInfo, ReplUpdate, CellExecution, KernelError, KernelInfo, KernelManager,
+    IsolationPolicy, IsolationWorker, ToolCategory, WorkerRequest, IN_PROCESS_TOOLS, WORKER_ARG,
+};
+pub use transcripts::{
+    TranscriptEntry, TranscriptError, TranscriptEvent, TranscriptStore, TranscriptWriter, TRANSCRIPT_VERSION,
+};
+pub use updates::{
+    platform_key, signed_payload, verify_signature, AvailableUpdate, PlatformBuild, StagedUpdate, UpdateClient, UpdateError,
+    UpdateManifest, UpdateStager, Version, CURRENT_VERSION,
+};
+pub use usage::{
+    ConversationUsage, ConversationUsageSummary, DailyUsage, ModelPricing, ModelUsage, Usage, UsageError,
+    UsageRange, UsageRecord, UsageReport, UsageStore, UsageTotals, USAGE_FILE,
+};
+pub use voice::{SttEngine, Transcriber, VoiceError};
 
 /// Result type alias for agent operations
 pub type Result<T> = std::result::Result<T, anyhow::Error>;
@@ -0,0 +1,139 @@
+//! `aurora-agent` command-line entry point
+//!
+//! Runs the agent headlessly and prints events as JSONL on stdout, or serves
+//! it over JSON-RPC on stdio for editor integrations:
+//!
+//! ```text
+//! aurora-agent run --prompt "fix the failing test" --project path/to/repo
+//! aurora-agent serve --project path/to/repo
+//! aurora-agent prompt --project path/to/repo
+//! aurora-agent eval --project path/to/repo
+//! ```
+
+use aurora_agent::eval::{self, EvalRunner};
+use aurora_agent::headless::{self, EvalOptions, HeadlessCommand, HeadlessEvent, HeadlessStatus};
+use aurora_agent::rpc::RpcServer;
+use aurora_agent::tools;
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    if tools::is_worker_invocation() {
+        return ExitCode::from(tools::run_worker().await as u8);
+    }
+    let options = match HeadlessCommand::parse(std::env::args().skip(1)) {
+        Ok(HeadlessCommand::Run(options)) => options,
+        Ok(HeadlessCommand::Serve(project_root)) => {
+            return match RpcServer::new(project_root).serve_stdio().await {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Ok(HeadlessCommand::Prompt(project_root)) => {
+            return match headless::system_prompt(&project_root) {
+                Ok(prompt) => {
+                    println!("{}", prompt.text());
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Ok(HeadlessCommand::Eval(options)) => return run_evals(options).await,
+        Ok(HeadlessCommand::Help) => {
+            print!("{}", headless::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, headless::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let prompt = match &options.prompt {
+        Some(prompt) => prompt.clone(),
+        None => {
+            let mut buffer = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
+                eprintln!("error: failed to read prompt from stdin: {}", e);
+                return ExitCode::from(2);
+            }
+            buffer
+        }
+    };
+
+    if prompt.trim().is_empty() {
+        eprintln!("error: prompt is empty\n\n{}", headless::USAGE);
+        return ExitCode::from(2);
+    }
+
+    let emit = |event: HeadlessEvent<'_>| {
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{}", event.to_json_line());
+        let _ = stdout.flush();
+    };
+
+    let result = match headless::project_client(&options.project_root) {
+        Ok(client) => headless::run(&client, &options, &prompt, emit).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            emit(HeadlessEvent::Status(HeadlessStatus::Error {
+                message: e.to_string(),
+            }));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the project's eval suite, printing a summary with the failures
+async fn run_evals(options: EvalOptions) -> ExitCode {
+    let suite_dir = eval::suite_dir(&options.project_root);
+    let scenarios: Vec<_> = match eval::load_suite(&suite_dir) {
+        Ok(scenarios) => scenarios
+            .into_iter()
+            .filter(|scenario| options.scenarios.is_empty() || options.scenarios.contains(&scenario.name))
+            .collect(),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if scenarios.is_empty() {
+        eprintln!("error: no matching eval scenarios in {}", suite_dir.display());
+        return ExitCode::from(2);
+    }
+
+    let client = match headless::project_client(&options.project_root) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut runner = EvalRunner::new(&client, &options.project_root);
+    if let Some(model) = options.model {
+        runner = runner.with_model(model);
+    }
+    let report = runner
+        .run_suite(&scenarios, |result| {
+            eprintln!("{} {}", if result.passed { "PASS" } else { "FAIL" }, result.name);
+        })
+        .await;
+
+    print!("{}", report.summary());
+    if report.failed() == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,574 @@
+//! Project onboarding
+//!
+//! `init_project_directives` analyzes a repository (build system, build and
+//! test commands, formatting and lint conventions, CI) and proposes a starter
+//! `AURORA.md` with project instructions for the agent, plus a
+//! `.AuroraHeart/config.toml` with the project's name, language, and tasks.
+//! Nothing is written until the user has reviewed each proposed file as a
+//! diff against what is on disk.
+
+use crate::client::{AnthropicClient, ClientError, MessageRequest};
+use crate::conversation::Conversation;
+use aurora_core::{detect_language, get_project_name, unified_diff, Config, ConfigError, TaskDefinition};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Project instructions file read into the system prompt
+pub const PROJECT_DIRECTIVES_FILE: &str = "AURORA.md";
+
+/// Project configuration file, relative to the project root
+pub const PROJECT_CONFIG_FILE: &str = ".AuroraHeart/config.toml";
+
+/// Model used to draft `AURORA.md` from the analysis
+const DRAFT_MODEL: &str = "claude-sonnet-4-5";
+
+/// Files whose presence documents a convention, with what they configure
+const CONVENTION_FILES: &[(&str, &str)] = &[
+    ("rustfmt.toml", "Rust formatting"),
+    (".rustfmt.toml", "Rust formatting"),
+    ("clippy.toml", "Clippy lints"),
+    (".editorconfig", "editor settings (indentation, line endings)"),
+    (".prettierrc", "Prettier formatting"),
+    (".prettierrc.json", "Prettier formatting"),
+    ("prettier.config.js", "Prettier formatting"),
+    (".eslintrc", "ESLint rules"),
+    (".eslintrc.json", "ESLint rules"),
+    (".eslintrc.js", "ESLint rules"),
+    ("eslint.config.js", "ESLint rules"),
+    ("tsconfig.json", "TypeScript compiler options"),
+    ("ruff.toml", "Ruff lints and formatting"),
+    (".flake8", "flake8 lints"),
+    ("mypy.ini", "mypy type checking"),
+    (".golangci.yml", "golangci-lint rules"),
+    (".clang-format", "clang-format formatting"),
+    (".pre-commit-config.yaml", "pre-commit hooks"),
+    ("commitlint.config.js", "commit message rules"),
+    ("CONTRIBUTING.md", "contribution guidelines"),
+    ("CLAUDE.md", "existing agent instructions"),
+    ("AGENTS.md", "existing agent instructions"),
+];
+
+/// Errors that can occur during onboarding
+#[derive(Error, Debug)]
+pub enum OnboardingError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The existing configuration could not be read or serialized
+    #[error("Configuration error: {0}")]
+    Config(#[from] ConfigError),
+
+    /// A file outside the onboarding set was submitted for saving
+    #[error("Onboarding does not write {0}")]
+    UnsupportedFile(String),
+
+    /// The model request failed
+    #[error("Drafting failed: {0}")]
+    Client(#[from] ClientError),
+}
+
+pub type Result<T> = std::result::Result<T, OnboardingError>;
+
+/// What onboarding learned about a repository
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectAnalysis {
+    /// Project name from the manifest or directory
+    pub name: String,
+    /// Primary language, if detected
+    pub language: Option<String>,
+    /// Build systems found (`cargo`, `npm`, `make`, ...)
+    pub build_systems: Vec<String>,
+    /// Commands by purpose (`build`, `test`, `lint`, `format`)
+    pub commands: BTreeMap<String, String>,
+    /// Convention files found, with what they configure
+    pub conventions: Vec<(String, String)>,
+    /// CI configuration files
+    pub ci: Vec<String>,
+    /// Top-level directories
+    pub directories: Vec<String>,
+}
+
+impl ProjectAnalysis {
+    /// Inspect the repository at `root`
+    pub fn analyze(root: &Path) -> Self {
+        let mut analysis = Self {
+            name: get_project_name(root).unwrap_or_else(|| "project".to_string()),
+            language: detect_language(root).ok().map(|language| language.as_str().to_string()),
+            ..Self::default()
+        };
+
+        analysis.detect_cargo(root);
+        analysis.detect_node(root);
+        analysis.detect_python(root);
+        analysis.detect_go(root);
+        analysis.detect_make(root);
+        analysis.detect_cmake(root);
+
+        analysis.conventions = CONVENTION_FILES
+            .iter()
+            .filter(|(file, _)| root.join(file).is_file())
+            .map(|(file, purpose)| (file.to_string(), purpose.to_string()))
+            .collect();
+
+        if let Ok(entries) = std::fs::read_dir(root.join(".github").join("workflows")) {
+            let mut workflows: Vec<String> = entries
+                .flatten()
+                .map(|entry| format!(".github/workflows/{}", entry.file_name().to_string_lossy()))
+                .filter(|name| name.ends_with(".yml") || name.ends_with(".yaml"))
+                .collect();
+            workflows.sort();
+            analysis.ci.extend(workflows);
+        }
+        for file in [".gitlab-ci.yml", "azure-pipelines.yml", ".circleci/config.yml"] {
+            if root.join(file).is_file() {
+                analysis.ci.push(file.to_string());
+            }
+        }
+
+        if let Ok(entries) = std::fs::read_dir(root) {
+            analysis.directories = entries
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| !name.starts_with('.') && !matches!(name.as_str(), "target" | "node_modules" | "dist" | "build"))
+                .collect();
+            analysis.directories.sort();
+        }
+
+        analysis
+    }
+
+    /// Record a command unless an earlier build system already provides one
+    fn add_command(&mut self, purpose: &str, command: impl Into<String>) {
+        self.commands.entry(purpose.to_string()).or_insert_with(|| command.into());
+    }
+
+    fn detect_cargo(&mut self, root: &Path) {
+        let Ok(manifest) = std::fs::read_to_string(root.join("Cargo.toml")) else {
+            return;
+        };
+        self.build_systems.push("cargo".to_string());
+        let workspace = if manifest.contains("[workspace]") { " --workspace" } else { "" };
+        self.add_command("build", format!("cargo build{}", workspace));
+        self.add_command("test", format!("cargo test{}", workspace));
+        self.add_command("lint", format!("cargo clippy{} --all-targets -- -D warnings", workspace));
+        self.add_command("format", "cargo fmt --all");
+    }
+
+    fn detect_node(&mut self, root: &Path) {
+        let Ok(manifest) = std::fs::read_to_string(root.join("package.json")) else {
+            return;
+        };
+        let manager = if root.join("pnpm-lock.yaml").exists() {
+            "pnpm"
+        } else if root.join("yarn.lock").exists() {
+            "yarn"
+        } else if root.join("bun.lockb").exists() {
+            "bun"
+        } else {
+            "npm"
+        };
+        self.build_systems.push(manager.to_string());
+
+        let scripts = serde_json::from_str::<serde_json::Value>(&manifest)
+            .ok()
+            .and_then(|json| json.get("scripts").cloned())
+            .and_then(|scripts| scripts.as_object().cloned())
+            .unwrap_or_default();
+        for script in ["build", "test", "lint", "format"] {
+            if scripts.contains_key(script) {
+                self.add_command(script, format!("{} run {}", manager, script));
+            }
+        }
+    }
+
+    fn detect_python(&mut self, root: &Path) {
+        let pyproject = std::fs::read_to_string(root.join("pyproject.toml")).ok();
+        if pyproject.is_none() && !root.join("setup.py").exists() && !root.join("requirements.txt").exists() {
+            return;
+        }
+        let pyproject = pyproject.unwrap_or_default();
+        self.build_systems.push(if pyproject.contains("[tool.poetry]") { "poetry" } else { "pip" }.to_string());
+
+        if pyproject.contains("pytest") || root.join("tests").is_dir() || root.join("pytest.ini").exists() {
+            self.add_command("test", "pytest");
+        }
+        if pyproject.contains("ruff") || root.join("ruff.toml").exists() {
+            self.add_command("lint", "ruff check .");
+            self.add_command("format", "ruff format .");
+        }
+        if pyproject.contains("black") {
+            self.add_command("format", "black .");
+        }
+    }
+
+    fn detect_go(&mut self, root: &Path) {
+        if !root.join("go.mod").exists() {
+            return;
+        }
+        self.build_systems.push("go".to_string());
+        self.add_command("build", "go build ./...");
+        self.add_command("test", "go test ./...");
+        self.add_command("lint", "go vet ./...");
+        self.add_command("format", "gofmt -w .");
+    }
+
+    fn detect_make(&mut self, root: &Path) {
+        let Ok(makefile) = std::fs::read_to_string(root.join("Makefile")) else {
+            return;
+        };
+        self.build_systems.push("make".to_string());
+        for target in ["build", "test", "lint", "format"] {
+            if makefile.lines().any(|line| line.starts_with(&format!("{}:", target))) {
+                self.add_command(target, format!("make {}", target));
+            }
+        }
+    }
+
+    fn detect_cmake(&mut self, root: &Path) {
+        if !root.join("CMakeLists.txt").exists() {
+            return;
+        }
+        self.build_systems.push("cmake".to_string());
+        self.add_command("build", "cmake -B build && cmake --build build");
+        self.add_command("test", "ctest --test-dir build");
+    }
+
+    /// Plain-text summary given to the model when drafting
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!("Project: {}", self.name)];
+        if let Some(language) = &self.language {
+            lines.push(format!("Language: {}", language));
+        }
+        if !self.build_systems.is_empty() {
+            lines.push(format!("Build systems: {}", self.build_systems.join(", ")));
+        }
+        for (purpose, command) in &self.commands {
+            lines.push(format!("{} command: {}", purpose, command));
+        }
+        for (file, purpose) in &self.conventions {
+            lines.push(format!("Convention file: {} ({})", file, purpose));
+        }
+        for file in &self.ci {
+            lines.push(format!("CI: {}", file));
+        }
+        if !self.directories.is_empty() {
+            lines.push(format!("Top-level directories: {}", self.directories.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    /// Starter `AURORA.md` built from the analysis alone
+    pub fn render_directives(&self) -> String {
+        let mut out = format!("# {}\n\n", self.name);
+        let language = self.language.as_deref().map(capitalize).unwrap_or_else(|| "A".to_string());
+        if self.build_systems.is_empty() {
+            out.push_str(&format!("{} project.\n", language));
+        } else {
+            out.push_str(&format!("{} project built with {}.\n", language, self.build_systems.join(", ")));
+        }
+
+        if !self.commands.is_empty() {
+            out.push_str("\n## Commands\n\n");
+            for purpose in ["build", "test", "lint", "format"] {
+                if let Some(command) = self.commands.get(purpose) {
+                    out.push_str(&format!("- {}: `{}`\n", capitalize(purpose), command));
+                }
+            }
+            out.push_str("\nRun the test and lint commands before considering a change done.\n");
+        }
+
+        if !self.conventions.is_empty() {
+            out.push_str("\n## Conventions\n\n");
+            for (file, purpose) in &self.conventions {
+                out.push_str(&format!("- `{}`: {}\n", file, purpose));
+            }
+            out.push_str("\nFollow these files rather than personal style preferences.\n");
+        }
+
+        if !self.ci.is_empty() {
+            out.push_str("\n## Continuous integration\n\n");
+            for file in &self.ci {
+                out.push_str(&format!("- `{}`\n", file));
+            }
+        }
+
+        if !self.directories.is_empty() {
+            out.push_str("\n## Layout\n\n");
+            for directory in &self.directories {
+                out.push_str(&format!("- `{}/`\n", directory));
+            }
+        }
+        out
+    }
+
+    /// The project configuration with the analysis filled in
+    ///
+    /// Values already set in `config` are kept; only a missing name, language,
+    /// or task is added.
+    pub fn apply_to_config(&self, config: &mut Config) {
+        config.project.name.get_or_insert_with(|| self.name.clone());
+        if let Some(language) = &self.language {
+            config.project.language.get_or_insert_with(|| language.clone());
+        }
+        for (purpose, command) in &self.commands {
+            config.tasks.entry(purpose.clone()).or_insert_with(|| {
+                let mut task = TaskDefinition::new(command.clone());
+                task.description = Some(format!("{} the project", capitalize(purpose)));
+                task
+            });
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A file onboarding proposes to write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposedFile {
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// Whether the file already exists
+    pub exists: bool,
+    /// Content to write
+    pub content: String,
+    /// Unified diff from the current content
+    pub diff: String,
+}
+
+impl ProposedFile {
+    /// Propose `content` for `path`, diffed against the file on disk
+    fn new(root: &Path, path: &str, content: String) -> Result<Self> {
+        let current = match std::fs::read_to_string(root.join(path)) {
+            Ok(current) => Some(current),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let old_label = if current.is_some() { path } else { "/dev/null" };
+        Ok(Self {
+            path: path.to_string(),
+            exists: current.is_some(),
+            diff: unified_diff(current.as_deref().unwrap_or_default(), &content, old_label, path),
+            content,
+        })
+    }
+}
+
+/// Analysis and proposed files awaiting the user's review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnboardingPlan {
+    /// What was detected
+    pub analysis: ProjectAnalysis,
+    /// Files that would change; unchanged files are left out
+    pub files: Vec<ProposedFile>,
+}
+
+impl OnboardingPlan {
+    /// Propose `directives` as `AURORA.md` and the analysis merged into the
+    /// project configuration
+    pub fn new(root: &Path, analysis: ProjectAnalysis, directives: String) -> Result<Self> {
+        let mut config = Config::load(root)?;
+        analysis.apply_to_config(&mut config);
+
+        let files = [
+            ProposedFile::new(root, PROJECT_DIRECTIVES_FILE, directives)?,
+            ProposedFile::new(root, PROJECT_CONFIG_FILE, config.to_toml()?)?,
+        ]
+        .into_iter()
+        .filter(|file| !file.diff.is_empty())
+        .collect();
+
+        Ok(Self { analysis, files })
+    }
+}
+
+/// Draft `AURORA.md` with the model from the analysis and the starter text
+pub async fn draft_directives(client: &AnthropicClient, analysis: &ProjectAnalysis) -> Result<String> {
+    let mut prompt = Conversation::with_system_prompt(format!(
+        "You write {} files: concise instructions that tell an AI coding agent how to work in a \
+         repository. Cover how to build, test, and lint, the conventions to follow, and where \
+         things live. Only state what the analysis supports. Reply with the Markdown file only.",
+        PROJECT_DIRECTIVES_FILE
+    ));
+    prompt.add_user_message(format!(
+        "Repository analysis:\n{}\n\nStarter draft:\n{}",
+        analysis.summary(),
+        analysis.render_directives()
+    ));
+
+    let request = MessageRequest::from_conversation(&prompt, DRAFT_MODEL).with_max_tokens(2048);
+    let response = client.send_message(request).await?;
+    let text = response
+        .content
+        .iter()
+        .find_map(|block| block.as_text())
+        .map(strip_code_fence)
+        .unwrap_or_default();
+
+    if text.trim().is_empty() {
+        Ok(analysis.render_directives())
+    } else {
+        Ok(format!("{}\n", text.trim_end()))
+    }
+}
+
+/// Remove a Markdown code fence wrapped around the whole reply
+fn strip_code_fence(text: &str) -> String {
+    let trimmed = text.trim();
+    match trimmed.strip_prefix("```") {
+        Some(rest) => {
+            let body = rest.split_once('\n').map_or("", |(_, body)| body);
+            body.strip_suffix("```").unwrap_or(body).trim().to_string()
+        }
+        None => trimmed.to_string(),
+    }
+}
+
+/// Write reviewed onboarding files
+///
+/// Only `AURORA.md` and the project configuration can be written; the
+/// configuration must parse before it replaces the existing one.
+pub fn save_files(root: &Path, files: &[ProposedFile]) -> Result<Vec<PathBuf>> {
+    for file in files {
+        if file.path != PROJECT_DIRECTIVES_FILE && file.path != PROJECT_CONFIG_FILE {
+            return Err(OnboardingError::UnsupportedFile(file.path.clone()));
+        }
+        if file.path == PROJECT_CONFIG_FILE {
+            Config::from_toml(&file.content)?;
+        }
+    }
+
+    let mut written = Vec::new();
+    for file in files {
+        let path = root.join(&file.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &file.content)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rust_project() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[package]\nname = \"demo\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(temp.path().join("crates/core")).unwrap();
+        std::fs::create_dir_all(temp.path().join(".github/workflows")).unwrap();
+        std::fs::write(temp.path().join(".github/workflows/ci.yml"), "on: push\n").unwrap();
+        std::fs::write(temp.path().join("rustfmt.toml"), "max_width = 100\n").unwrap();
+        std::fs::write(temp.path().join("Makefile"), "test:\n\tcargo nextest run\nrelease:\n").unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_analyze_rust_workspace() {
+        let temp = rust_project();
+        let analysis = ProjectAnalysis::analyze(temp.path());
+
+        assert_eq!(analysis.build_systems, vec!["cargo", "make"]);
+        assert_eq!(analysis.commands["test"], "cargo test --workspace", "cargo is preferred over make");
+        assert_eq!(analysis.commands["lint"], "cargo clippy --workspace --all-targets -- -D warnings");
+        assert_eq!(analysis.conventions, vec![("rustfmt.toml".to_string(), "Rust formatting".to_string())]);
+        assert_eq!(analysis.ci, vec![".github/workflows/ci.yml"]);
+        assert_eq!(analysis.directories, vec!["crates"]);
+
+        let directives = analysis.render_directives();
+        assert!(directives.contains("- Test: `cargo test --workspace`"));
+        assert!(directives.contains("- `rustfmt.toml`: Rust formatting"));
+        assert!(directives.contains("- `crates/`"));
+    }
+
+    #[test]
+    fn test_analyze_node_scripts() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("package.json"),
+            r#"{"name": "web", "scripts": {"build": "vite build", "test": "vitest"}}"#,
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("pnpm-lock.yaml"), "").unwrap();
+
+        let analysis = ProjectAnalysis::analyze(temp.path());
+        assert_eq!(analysis.build_systems, vec!["pnpm"]);
+        assert_eq!(analysis.commands["build"], "pnpm run build");
+        assert_eq!(analysis.commands["test"], "pnpm run test");
+        assert!(!analysis.commands.contains_key("lint"));
+    }
+
+    #[test]
+    fn test_plan_previews_and_saves() {
+        let temp = rust_project();
+        let mut config = Config::default();
+        config.project.name = Some("kept".to_string());
+        config.tasks.insert("test".to_string(), TaskDefinition::new("make test"));
+        config.save(temp.path()).unwrap();
+
+        let analysis = ProjectAnalysis::analyze(temp.path());
+        let directives = analysis.render_directives();
+        let plan = OnboardingPlan::new(temp.path(), analysis, directives.clone()).unwrap();
+
+        assert_eq!(plan.files.len(), 2);
+        let aurora = &plan.files[0];
+        assert!(!aurora.exists);
+        assert!(aurora.diff.starts_with("--- /dev/null\n+++ AURORA.md\n"));
+        let config_file = &plan.files[1];
+        assert!(config_file.exists);
+        assert!(config_file.diff.contains("+[tasks.build]"));
+        assert!(!config_file.diff.contains("-name = \"kept\""), "existing values are kept");
+        assert!(!temp.path().join(PROJECT_DIRECTIVES_FILE).exists(), "nothing is written before saving");
+
+        save_files(temp.path(), &plan.files).unwrap();
+        assert_eq!(std::fs::read_to_string(temp.path().join(PROJECT_DIRECTIVES_FILE)).unwrap(), directives);
+        let saved = Config::load(temp.path()).unwrap();
+        assert_eq!(saved.tasks["test"].command, "make test");
+        assert_eq!(saved.tasks["build"].command, "cargo build --workspace");
+
+        // Saving again proposes nothing
+        let analysis = ProjectAnalysis::analyze(temp.path());
+        let plan = OnboardingPlan::new(temp.path(), analysis, directives).unwrap();
+        assert!(plan.files.is_empty());
+    }
+
+    #[test]
+    fn test_save_rejects_other_files_and_invalid_config() {
+        let temp = TempDir::new().unwrap();
+        let file = |path: &str, content: &str| ProposedFile {
+            path: path.to_string(),
+            exists: false,
+            content: content.to_string(),
+            diff: String::new(),
+        };
+
+        assert!(matches!(
+            save_files(temp.path(), &[file("../escape.md", "x")]),
+            Err(OnboardingError::UnsupportedFile(_))
+        ));
+        assert!(save_files(temp.path(), &[file(PROJECT_CONFIG_FILE, "[project\n")]).is_err());
+        assert!(!temp.path().join(PROJECT_CONFIG_FILE).exists());
+    }
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(strip_code_fence("```markdown\n# Title\n\nBody\n```"), "# Title\n\nBody");
+        assert_eq!(strip_code_fence("# Title\n"), "# Title");
+    }
+}
@@ -0,0 +1,149 @@
+//! Per-conversation personas and custom system prompts
+//!
+//! A conversation can take on a persona (a reviewer, a teacher, or a pair
+//! programmer) and carry custom instructions of its own. Both go into their
+//! own section of the system prompt, after the project directives, so they
+//! shape how the agent works without replacing the directives.
+//!
+//! The override belongs to the conversation: it is saved with the session
+//! and cleared when a new conversation starts.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A way of working the agent can be asked to take on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Persona {
+    /// Reviews changes instead of making them
+    Reviewer,
+    /// Explains and lets the user do the work
+    Teacher,
+    /// Works in small steps alongside the user
+    PairProgrammer,
+}
+
+impl Persona {
+    /// Every persona, in the order they are offered
+    pub const ALL: [Persona; 3] = [Persona::Reviewer, Persona::Teacher, Persona::PairProgrammer];
+
+    /// Name shown to the user
+    pub fn title(&self) -> &'static str {
+        match self {
+            Persona::Reviewer => "Reviewer",
+            Persona::Teacher => "Teacher",
+            Persona::PairProgrammer => "Pair programmer",
+        }
+    }
+
+    /// System prompt instructions for the persona
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            Persona::Reviewer => {
+                "Act as a code reviewer. Read the code in question closely and report bugs, unhandled \
+                 edge cases, security problems, and departures from the project's conventions, most \
+                 important first, each with the file and line. Suggest fixes, but do not edit files \
+                 unless the user asks you to."
+            }
+            Persona::Teacher => {
+                "Act as a teacher. Explain the reasoning behind your answers and the concepts they rely \
+                 on, at the user's level. Prefer hints and small examples that let the user write the \
+                 code themselves, and check their understanding before moving on."
+            }
+            Persona::PairProgrammer => {
+                "Act as a pair programmer. Work in small steps, say what you are about to do before \
+                 doing it, and check in with the user at decision points instead of making large \
+                 changes on your own."
+            }
+        }
+    }
+}
+
+impl fmt::Display for Persona {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Persona::Reviewer => "reviewer",
+            Persona::Teacher => "teacher",
+            Persona::PairProgrammer => "pair_programmer",
+        })
+    }
+}
+
+impl FromStr for Persona {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(['-', ' '], "_");
+        Persona::ALL
+            .into_iter()
+            .find(|persona| persona.to_string() == name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown persona '{}'; expected one of: {}",
+                    s,
+                    Persona::ALL.map(|persona| persona.to_string()).join(", ")
+                )
+            })
+    }
+}
+
+/// A conversation's persona and custom instructions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptOverride {
+    /// Persona the agent takes on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<Persona>,
+    /// Instructions added for this conversation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_prompt: Option<String>,
+}
+
+impl PromptOverride {
+    /// Whether the override adds nothing to the system prompt
+    pub fn is_empty(&self) -> bool {
+        self.persona.is_none() && self.custom_prompt.as_deref().is_none_or(|prompt| prompt.trim().is_empty())
+    }
+
+    /// System prompt text for the override, empty when there is none
+    pub fn instructions(&self) -> String {
+        let persona = self.persona.map(|persona| persona.instructions());
+        let custom = self.custom_prompt.as_deref().map(str::trim).filter(|prompt| !prompt.is_empty());
+        persona.into_iter().chain(custom).collect::<Vec<_>>().join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persona_names() {
+        for persona in Persona::ALL {
+            assert_eq!(persona.to_string().parse::<Persona>(), Ok(persona));
+            assert_eq!(serde_json::to_value(persona).unwrap(), serde_json::json!(persona.to_string()));
+        }
+        assert_eq!("Pair Programmer".parse::<Persona>(), Ok(Persona::PairProgrammer));
+        assert!("critic".parse::<Persona>().unwrap_err().contains("reviewer, teacher, pair_programmer"));
+    }
+
+    #[test]
+    fn test_override_instructions() {
+        assert!(PromptOverride::default().is_empty());
+        let blank = PromptOverride {
+            persona: None,
+            custom_prompt: Some("  \n".to_string()),
+        };
+        assert!(blank.is_empty());
+        assert!(blank.instructions().is_empty());
+
+        let both = PromptOverride {
+            persona: Some(Persona::Teacher),
+            custom_prompt: Some("Use examples from the standard library.\n".to_string()),
+        };
+        assert!(!both.is_empty());
+        let instructions = both.instructions();
+        assert!(instructions.starts_with("Act as a teacher."));
+        assert!(instructions.ends_with("\n\nUse examples from the standard library."));
+    }
+}
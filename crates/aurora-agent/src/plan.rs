@@ -0,0 +1,139 @@
+//! Read-only plan mode
+//!
+//! In plan mode the agent may only read, search, and analyze the project,
+//! and is told to finish with a plan instead of making changes. The
+//! restriction is enforced twice: tools that are not read-only (for the
+//! built-in tools, those outside [`PLAN_MODE_TOOLS`]) are left out of the
+//! tool list sent to the model, and a
+//! [`FilteredToolExecutor`](crate::FilteredToolExecutor) in plan mode refuses
+//! to run them if they are requested anyway.
+//!
+//! Approving the plan switches back to execution with every permitted tool,
+//! carrying the plan in the system prompt so it survives truncation of the
+//! conversation that produced it.
+
+use crate::tools::{builtin_capabilities, ToolCapabilities};
+use serde::{Deserialize, Serialize};
+
+/// Built-in tools available in plan mode, the read-only ones, none of which
+/// change files or run commands
+pub const PLAN_MODE_TOOLS: &[&str] = &[
+    "read",
+    "grep",
+    "glob",
+    "symbols",
+    "list_directory",
+    "read_document",
+    "secret_scan",
+    "get_ci_status",
+    "get_failed_job_logs",
+    "get_issue",
+    "get_selection",
+    "get_open_editors",
+    "get_diagnostics",
+    "validate_config_file",
+    "regex_test",
+    "get_file_owners",
+];
+
+/// System prompt instructions while planning
+pub const PLAN_MODE_INSTRUCTIONS: &str = "You are in plan mode. You can read, search, and analyze the project, \
+but you cannot edit files, run commands, or change anything. Investigate as needed, then reply with a plan: \
+the goal, the files to change and how, the order of the steps, how to verify the result, and any open \
+questions or risks. Do not claim to have made changes. The user will review the plan and approve it before \
+anything is carried out.";
+
+/// Whether the agent is planning or carrying out work
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", content = "plan", rename_all = "snake_case")]
+pub enum AgentMode {
+    /// Every permitted tool is available
+    #[default]
+    Execute,
+    /// Only read and analysis tools are available, and the agent writes a plan
+    Plan,
+    /// Every permitted tool is available, working from an approved plan
+    Approved(String),
+}
+
+impl AgentMode {
+    /// Whether the mode lets the built-in tool `tool` run
+    pub fn allows(&self, tool: &str) -> bool {
+        self.permits(builtin_capabilities(tool))
+    }
+
+    /// Whether the mode lets a tool with `capabilities` run
+    pub fn permits(&self, capabilities: ToolCapabilities) -> bool {
+        match self {
+            AgentMode::Plan => capabilities.read_only,
+            AgentMode::Execute | AgentMode::Approved(_) => true,
+        }
+    }
+
+    /// Whether the agent is restricted to planning
+    pub fn is_planning(&self) -> bool {
+        matches!(self, AgentMode::Plan)
+    }
+
+    /// System prompt text for the mode, empty in plain execution mode
+    pub fn instructions(&self) -> String {
+        match self {
+            AgentMode::Execute => String::new(),
+            AgentMode::Plan => PLAN_MODE_INSTRUCTIONS.to_string(),
+            AgentMode::Approved(plan) => format!(
+                "The user approved the following plan. Carry it out step by step, and say so if you need \
+                 to depart from it.\n\n{}",
+                plan.trim()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::all_tools;
+
+    #[test]
+    fn test_plan_mode_allows_only_plan_tools() {
+        let tools: Vec<String> = all_tools().into_iter().map(|tool| tool.name).collect();
+        for name in PLAN_MODE_TOOLS {
+            assert!(tools.iter().any(|tool| tool == name), "unknown plan tool {}", name);
+        }
+
+        let plan = AgentMode::Plan;
+        assert!(plan.allows("read") && plan.allows("grep"));
+        for tool in [
+            "write",
+            "edit",
+            "bash",
+            "delete",
+            "move",
+            "build",
+            "code_format",
+            "scaffold",
+            "syntax_check",
+            "code_analysis",
+            "update_secret_baseline",
+            "db_schema",
+            "k8s",
+        ] {
+            assert!(!plan.allows(tool), "{} must not run while planning", tool);
+        }
+        assert!(AgentMode::Execute.allows("bash"));
+        assert!(AgentMode::Approved("1. Edit".to_string()).allows("edit"));
+    }
+
+    #[test]
+    fn test_mode_instructions_and_serialization() {
+        assert!(AgentMode::Execute.instructions().is_empty());
+        assert!(AgentMode::Plan.instructions().starts_with("You are in plan mode."));
+        assert!(AgentMode::Approved("1. Add tests\n".to_string())
+            .instructions()
+            .ends_with("\n\n1. Add tests"));
+
+        assert_eq!(serde_json::to_value(AgentMode::Plan).unwrap(), serde_json::json!({"mode": "plan"}));
+        let approved: AgentMode = serde_json::from_value(serde_json::json!({"mode": "approved", "plan": "Do it"})).unwrap();
+        assert_eq!(approved, AgentMode::Approved("Do it".to_string()));
+    }
+}
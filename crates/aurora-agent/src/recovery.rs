@@ -0,0 +1,268 @@
+//! Crash recovery snapshots
+//!
+//! While the app runs it periodically saves the conversation, the agent turn
+//! in flight (if any), and the content of editor tabs with unsaved changes to
+//! `recovery.json` in the user config directory. A clean shutdown removes the
+//! file, so finding one at startup means the previous run ended abruptly and
+//! its state can be offered for restoring.
+//!
+//! A turn that was cut off is restored as an assistant message holding the
+//! text and tool calls received so far, ending with [`INTERRUPTED_MARKER`],
+//! the same way a dropped stream is salvaged.
+
+use crate::client::{AgenticEvent, INTERRUPTED_MARKER};
+use crate::conversation::{Conversation, Message};
+use crate::sessions::{SavedSession, SessionMetadata};
+use aurora_core::user_config_dir;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Format version of the snapshot file
+pub const RECOVERY_VERSION: u32 = 1;
+
+/// File name of the snapshot in the user config directory
+const RECOVERY_FILE: &str = "recovery.json";
+
+/// Errors that can occur while saving or loading a recovery snapshot
+#[derive(Error, Debug)]
+pub enum RecoveryError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Snapshot could not be parsed or written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, RecoveryError>;
+
+/// An agent turn that had not finished when the snapshot was taken
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InFlightTurn {
+    pub started_at: DateTime<Utc>,
+    /// Assistant text and tool calls received so far
+    pub partial_text: String,
+}
+
+impl InFlightTurn {
+    /// A turn starting now
+    pub fn new() -> Self {
+        Self {
+            started_at: Utc::now(),
+            partial_text: String::new(),
+        }
+    }
+
+    /// Add the output of an event to the partial response
+    pub fn record(&mut self, event: &AgenticEvent) {
+        let text = match event {
+            AgenticEvent::TextResponse { text } => text.clone(),
+            AgenticEvent::ToolCall { name, .. } => format!("[Tool: {}]", name),
+            _ => return,
+        };
+        if !self.partial_text.is_empty() {
+            self.partial_text.push_str("\n\n");
+        }
+        self.partial_text.push_str(&text);
+    }
+}
+
+impl Default for InFlightTurn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Unsaved content of an editor tab
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsavedBuffer {
+    pub path: String,
+    pub content: String,
+}
+
+/// State to restore after a crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverySnapshot {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    /// Project that was open
+    pub project_root: PathBuf,
+    /// The conversation, as a saved session
+    pub session: SavedSession,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn: Option<InFlightTurn>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buffers: Vec<UnsavedBuffer>,
+}
+
+impl RecoverySnapshot {
+    /// Capture the current state
+    pub fn capture(
+        project_root: &Path,
+        metadata: &SessionMetadata,
+        conversation: &Conversation,
+        turn: Option<&InFlightTurn>,
+        buffers: Vec<UnsavedBuffer>,
+    ) -> Self {
+        let mut metadata = metadata.clone();
+        metadata.message_count = conversation.message_count();
+        Self {
+            version: RECOVERY_VERSION,
+            saved_at: Utc::now(),
+            project_root: project_root.to_path_buf(),
+            session: SavedSession {
+                metadata,
+                messages: conversation.messages().to_vec(),
+                attachments: conversation.attachments.clone(),
+                usage: conversation.usage.clone(),
+                budget: conversation.budget,
+                prompt_override: conversation.prompt_override.clone(),
+            },
+            turn: turn.cloned(),
+            buffers,
+        }
+    }
+
+    /// Whether there is nothing worth offering to restore
+    pub fn is_empty(&self) -> bool {
+        self.session.messages.is_empty() && self.turn.is_none() && self.buffers.is_empty()
+    }
+
+    /// Messages of the restored conversation
+    ///
+    /// An interrupted turn becomes a final assistant message with the
+    /// partial response and [`INTERRUPTED_MARKER`].
+    pub fn messages(&self) -> Vec<Message> {
+        let mut messages = self.session.messages.clone();
+        if let Some(turn) = &self.turn {
+            let text = if turn.partial_text.is_empty() {
+                INTERRUPTED_MARKER.to_string()
+            } else {
+                format!("{}\n\n{}", turn.partial_text, INTERRUPTED_MARKER)
+            };
+            messages.push(Message::assistant(text));
+        }
+        messages
+    }
+}
+
+/// Saves and loads the recovery snapshot
+pub struct RecoveryStore {
+    path: PathBuf,
+}
+
+impl RecoveryStore {
+    /// The current user's snapshot, or `None` if there is no config directory
+    pub fn for_user() -> Option<Self> {
+        user_config_dir().map(|dir| Self::at(dir.join(RECOVERY_FILE)))
+    }
+
+    /// A snapshot stored at `path`
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Save `snapshot`, or remove the file when there is nothing to restore
+    ///
+    /// The file is replaced atomically so a crash while saving leaves the
+    /// previous snapshot intact.
+    pub fn save(&self, snapshot: &RecoverySnapshot) -> Result<()> {
+        if snapshot.is_empty() {
+            return self.clear();
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string(snapshot)?)?;
+        std::fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// The snapshot left by a run that did not shut down cleanly
+    ///
+    /// Snapshots from another format version are ignored.
+    pub fn load(&self) -> Result<Option<RecoverySnapshot>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let snapshot: RecoverySnapshot = serde_json::from_str(&std::fs::read_to_string(&self.path)?)?;
+        Ok((snapshot.version == RECOVERY_VERSION).then_some(snapshot))
+    }
+
+    /// Remove the snapshot, e.g. on a clean shutdown
+    pub fn clear(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::Role;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_interrupted_turn_is_restored_with_marker() {
+        let mut conversation = Conversation::new();
+        conversation.add_user_message("Fix the build");
+        let mut turn = InFlightTurn::new();
+        turn.record(&AgenticEvent::TextResponse {
+            text: "Looking at the error".to_string(),
+        });
+        turn.record(&AgenticEvent::ToolCall {
+            id: "t1".to_string(),
+            name: "build".to_string(),
+            input: serde_json::json!({}),
+        });
+
+        let snapshot = RecoverySnapshot::capture(
+            Path::new("/project"),
+            &SessionMetadata::new(),
+            &conversation,
+            Some(&turn),
+            Vec::new(),
+        );
+        let messages = snapshot.messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].role, Role::Assistant);
+        let restored = Conversation {
+            messages,
+            ..Conversation::new()
+        };
+        let text = restored.last_assistant_text().unwrap();
+        assert!(text.starts_with("Looking at the error\n\n[Tool: build]"));
+        assert!(text.ends_with(INTERRUPTED_MARKER));
+    }
+
+    #[test]
+    fn test_store_round_trip_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RecoveryStore::at(temp_dir.path().join("config").join(RECOVERY_FILE));
+        assert!(store.load().unwrap().is_none());
+
+        let empty = RecoverySnapshot::capture(temp_dir.path(), &SessionMetadata::new(), &Conversation::new(), None, Vec::new());
+        store.save(&empty).unwrap();
+        assert!(store.load().unwrap().is_none(), "nothing to restore is not saved");
+
+        let buffers = vec![UnsavedBuffer {
+            path: "/project/src/main.rs".to_string(),
+            content: "fn main() {}".to_string(),
+        }];
+        let snapshot = RecoverySnapshot::capture(temp_dir.path(), &SessionMetadata::new(), &Conversation::new(), None, buffers);
+        store.save(&snapshot).unwrap();
+        let loaded = store.load().unwrap().unwrap();
+        assert_eq!(loaded.buffers, snapshot.buffers);
+        assert_eq!(loaded.project_root, temp_dir.path());
+
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_none());
+        store.clear().unwrap();
+    }
+}
@@ -0,0 +1,663 @@
+//! JSON-RPC server for external editor integration
+//!
+//! This module exposes the agent core over JSON-RPC 2.0 with one message per
+//! line, so editor plugins (Neovim, Emacs, ...) can drive the same engine the
+//! Tauri UI uses. `aurora-agent serve` runs it on stdin/stdout.
+//!
+//! Client → server methods:
+//! - `initialize` `{project_root?, model?, approve_tools?, auto_approve?, allowed_tools?, denied_tools?, sampling?}`;
+//!   `auto_approve` runs every tool call without asking, within the limits of
+//!   the project's `policy.toml`
+//! - `send_message` `{message, continue?}` → `{text, messages, usage}`; the
+//!   loop pauses with a `budget_exceeded` event once the conversation reaches
+//!   the project's `[agent.budget]`, and `continue: true` grants the same
+//!   allowance again
+//! - `list_tools` → `[{name, description}]`
+//! - `clear_conversation`
+//! - `shutdown`
+//!
+//! Server → client messages:
+//! - `event` notifications carrying each agentic loop event as it happens
+//! - `approve_tool` requests `{id, name, input}` when tool approval is enabled;
+//!   the client answers with `{approved: bool}`
+
+use crate::agent::{FilteredToolExecutor, ToolApproval};
+use crate::client::{AgenticEvent, AnthropicClient};
+use crate::conversation::Conversation;
+use crate::headless;
+use crate::tools::{ToolExecutor, ToolPolicy, ToolUse};
+use aurora_core::{Config, SamplingParams, ANTHROPIC_LIMITS};
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+
+/// JSON-RPC error code: invalid JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code: not a valid request object
+pub const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code: unknown method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code: invalid method parameters
+pub const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code: internal error while handling the request
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Errors that stop the server
+#[derive(Error, Debug)]
+pub enum RpcError {
+    /// IO error on the transport
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, RpcError>;
+
+/// Error returned to the client for a single request
+struct MethodError {
+    code: i64,
+    message: String,
+}
+
+impl MethodError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Per-connection settings chosen by `initialize`
+#[derive(Default)]
+struct SessionSettings {
+    model: Option<String>,
+    approve_tools: bool,
+    auto_approve: bool,
+    allowed_tools: Vec<String>,
+    denied_tools: Vec<String>,
+    sampling: SamplingParams,
+}
+
+/// State shared between the reader loop and request handlers
+struct Session {
+    project_root: Mutex<PathBuf>,
+    client: Mutex<Option<Arc<AnthropicClient>>>,
+    settings: Mutex<SessionSettings>,
+    conversation: tokio::sync::Mutex<Option<Conversation>>,
+    outgoing: mpsc::UnboundedSender<JsonValue>,
+    /// Outstanding server → client requests; `None` once the client has gone
+    pending: Mutex<Option<HashMap<String, oneshot::Sender<JsonValue>>>>,
+    next_id: AtomicU64,
+}
+
+impl Session {
+    fn send(&self, message: JsonValue) {
+        // The writer only stops when the connection is closing
+        let _ = self.outgoing.send(message);
+    }
+
+    fn notify(&self, method: &str, params: JsonValue) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Send a request to the client and wait for its result
+    async fn request(&self, method: &str, params: JsonValue) -> Option<JsonValue> {
+        let id = format!("server-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().ok()?.as_mut()?.insert(id.clone(), tx);
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        rx.await.ok()
+    }
+
+    fn resolve_response(&self, id: &JsonValue, message: JsonValue) {
+        let key = match id {
+            JsonValue::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let sender = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut p| p.as_mut()?.remove(&key));
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(message);
+            }
+            None => tracing::warn!("Response for unknown request id {}", key),
+        }
+    }
+}
+
+/// Tool approval that asks the RPC client
+struct RpcApproval {
+    session: Arc<Session>,
+}
+
+impl ToolApproval for RpcApproval {
+    fn approve<'a>(
+        &'a self,
+        tool_use: &'a ToolUse,
+    ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+        Box::pin(async move {
+            let params =
+                json!({ "id": tool_use.id, "name": tool_use.name, "input": tool_use.input });
+            match self.session.request("approve_tool", params).await {
+                Some(response) => {
+                    let result = &response["result"];
+                    result["approved"]
+                        .as_bool()
+                        .or_else(|| result.as_bool())
+                        .unwrap_or(false)
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+/// JSON-RPC server driving the agent
+pub struct RpcServer {
+    project_root: PathBuf,
+    client: Option<AnthropicClient>,
+}
+
+impl RpcServer {
+    /// Create a server for a project
+    pub fn new(project_root: impl Into<PathBuf>) -> Self {
+        Self {
+            project_root: project_root.into(),
+            client: None,
+        }
+    }
+
+    /// Use a specific API client instead of resolving the API key on first use
+    pub fn with_client(mut self, client: AnthropicClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Serve on stdin/stdout until `shutdown` or end of input
+    pub async fn serve_stdio(self) -> Result<()> {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        self.serve(stdin, tokio::io::stdout()).await
+    }
+
+    /// Serve on an arbitrary line-oriented transport
+    pub async fn serve<R, W>(self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<JsonValue>();
+        let writer_task = tokio::spawn(async move {
+            while let Some(message) = outgoing_rx.recv().await {
+                let line = format!("{}\n", message);
+                writer.write_all(line.as_bytes()).await?;
+                writer.flush().await?;
+            }
+            Ok::<(), std::io::Error>(())
+        });
+
+        let session = Arc::new(Session {
+            project_root: Mutex::new(self.project_root),
+            client: Mutex::new(self.client.map(Arc::new)),
+            settings: Mutex::new(SessionSettings::default()),
+            conversation: tokio::sync::Mutex::new(None),
+            outgoing,
+            pending: Mutex::new(Some(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        });
+
+        let mut handlers = Vec::new();
+        let mut shutdown_id = None;
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message: JsonValue = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    session.send(error_response(JsonValue::Null, PARSE_ERROR, &e.to_string()));
+                    continue;
+                }
+            };
+
+            let id = message.get("id").cloned();
+            let method = message
+                .get("method")
+                .and_then(|m| m.as_str())
+                .map(|m| m.to_string());
+
+            match (method, id) {
+                // Response to one of our requests
+                (None, Some(id)) => session.resolve_response(&id, message),
+                (Some(method), id) if method == "shutdown" => {
+                    shutdown_id = id;
+                    break;
+                }
+                (Some(method), id) => {
+                    let session = Arc::clone(&session);
+                    let params = message.get("params").cloned().unwrap_or(JsonValue::Null);
+                    handlers.push(tokio::spawn(async move {
+                        let result = handle(&session, &method, params).await;
+                        if let Some(id) = id {
+                            session.send(match result {
+                                Ok(result) => {
+                                    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+                                }
+                                Err(e) => error_response(id, e.code, &e.message),
+                            });
+                        }
+                    }));
+                }
+                (None, None) => {
+                    session.send(error_response(
+                        JsonValue::Null,
+                        INVALID_REQUEST,
+                        "Missing method",
+                    ));
+                }
+            }
+        }
+
+        // Nobody is left to answer approval requests, so deny them and let
+        // in-flight requests finish before replying to shutdown
+        if let Ok(mut pending) = session.pending.lock() {
+            *pending = None;
+        }
+        for handler in handlers {
+            if let Err(e) = handler.await {
+                tracing::warn!("RPC request handler failed: {}", e);
+            }
+        }
+        if let Some(id) = shutdown_id {
+            session.send(json!({ "jsonrpc": "2.0", "id": id, "result": null }));
+        }
+        drop(session);
+
+        match writer_task.await {
+            Ok(result) => result?,
+            Err(e) => tracing::warn!("RPC writer task ended abnormally: {}", e),
+        }
+        Ok(())
+    }
+}
+
+fn poisoned<T>(_: std::sync::PoisonError<T>) -> MethodError {
+    MethodError::new(INTERNAL_ERROR, "Session state poisoned")
+}
+
+fn error_response(id: JsonValue, code: i64, message: &str) -> JsonValue {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+async fn handle(
+    session: &Arc<Session>,
+    method: &str,
+    params: JsonValue,
+) -> std::result::Result<JsonValue, MethodError> {
+    match method {
+        "initialize" => initialize(session, &params),
+        "send_message" => send_message(session, &params).await,
+        "list_tools" => Ok(JsonValue::Array(
+            filtered_executor(session)?
+                .get_available_tools()
+                .into_iter()
+                .map(|t| json!({ "name": t.name, "description": t.description }))
+                .collect(),
+        )),
+        "clear_conversation" => {
+            *session.conversation.lock().await = None;
+            Ok(JsonValue::Null)
+        }
+        other => Err(MethodError::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {}", other),
+        )),
+    }
+}
+
+fn initialize(
+    session: &Arc<Session>,
+    params: &JsonValue,
+) -> std::result::Result<JsonValue, MethodError> {
+    if let Some(root) = params["project_root"].as_str() {
+        let root = PathBuf::from(root);
+        if !root.is_dir() {
+            return Err(MethodError::new(
+                INVALID_PARAMS,
+                format!("Project root is not a directory: {}", root.display()),
+            ));
+        }
+        *session.project_root.lock().map_err(poisoned)? = root;
+    }
+
+    let strings = |value: &JsonValue| -> Vec<String> {
+        value
+            .as_array()
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let sampling: SamplingParams = match params.get("sampling") {
+        Some(sampling) if !sampling.is_null() => serde_json::from_value(sampling.clone())
+            .map_err(|e| MethodError::new(INVALID_PARAMS, format!("Invalid sampling: {}", e)))?,
+        _ => SamplingParams::default(),
+    };
+    sampling
+        .validate(&ANTHROPIC_LIMITS)
+        .map_err(|e| MethodError::new(INVALID_PARAMS, e.to_string()))?;
+
+    {
+        let mut settings = session.settings.lock().map_err(poisoned)?;
+        settings.model = params["model"].as_str().map(|s| s.to_string());
+        settings.approve_tools = params["approve_tools"].as_bool().unwrap_or(false);
+        settings.auto_approve = params["auto_approve"].as_bool().unwrap_or(false);
+        settings.allowed_tools = strings(&params["allowed_tools"]);
+        settings.denied_tools = strings(&params["denied_tools"]);
+        settings.sampling = sampling;
+    }
+
+    let project_root = session.project_root.lock().map_err(poisoned)?.clone();
+    Ok(json!({
+        "name": "aurora-agent",
+        "version": env!("CARGO_PKG_VERSION"),
+        "project_root": project_root,
+    }))
+}
+
+fn filtered_executor(
+    session: &Arc<Session>,
+) -> std::result::Result<FilteredToolExecutor, MethodError> {
+    let project_root = session.project_root.lock().map_err(poisoned)?.clone();
+    let settings = session.settings.lock().map_err(poisoned)?;
+
+    let policy = ToolPolicy::load(&project_root).map_err(|e| MethodError::new(INTERNAL_ERROR, e.to_string()))?;
+    let executor = FilteredToolExecutor::new(
+        ToolExecutor::with_working_directory(project_root).with_policy(policy),
+        settings.allowed_tools.clone(),
+        settings.denied_tools.clone(),
+    );
+
+    Ok(if settings.auto_approve {
+        executor.with_auto_approve()
+    } else if settings.approve_tools {
+        executor.with_approval(Arc::new(RpcApproval {
+            session: Arc::clone(session),
+        }))
+    } else {
+        executor
+    })
+}
+
+async fn send_message(
+    session: &Arc<Session>,
+    params: &JsonValue,
+) -> std::result::Result<JsonValue, MethodError> {
+    let message = params["message"]
+        .as_str()
+        .ok_or_else(|| MethodError::new(INVALID_PARAMS, "Missing message"))?;
+
+    let project_root = session.project_root.lock().map_err(poisoned)?.clone();
+    let (model, sampling_overrides) = {
+        let settings = session.settings.lock().map_err(poisoned)?;
+        let model = settings
+            .model
+            .clone()
+            .unwrap_or_else(|| AnthropicClient::default_model().to_string());
+        (model, settings.sampling.clone())
+    };
+    let agent_config = Config::load(&project_root)
+        .map_err(|e| MethodError::new(INTERNAL_ERROR, e.to_string()))?
+        .agent;
+    let sampling = agent_config.sampling.merge(&sampling_overrides);
+
+    let client = {
+        let mut client = session.client.lock().map_err(poisoned)?;
+        match client.as_ref() {
+            Some(client) => Arc::clone(client),
+            None => {
+                let project_client = headless::project_client(&project_root)
+                    .map_err(|e| MethodError::new(INTERNAL_ERROR, e.to_string()))?;
+                Arc::clone(client.insert(Arc::new(project_client)))
+            }
+        }
+    };
+
+    let executor = filtered_executor(session)?;
+
+    // Hold the conversation for the whole turn so concurrent messages are serialized
+    let mut guard = session.conversation.lock().await;
+    let conversation = match guard.as_mut() {
+        Some(conversation) => conversation,
+        None => {
+            let system_prompt = headless::system_prompt(&project_root)
+                .map_err(|e| MethodError::new(INTERNAL_ERROR, e.to_string()))?;
+            let mut conversation = Conversation::with_sectioned_prompt(&system_prompt);
+            conversation.budget = agent_config.budget;
+            guard.insert(conversation)
+        }
+    };
+    conversation.sampling = sampling;
+    if params["continue"].as_bool().unwrap_or(false) {
+        conversation.budget = conversation.usage.extend(&conversation.budget);
+    }
+    conversation.add_user_message(message);
+
+    let mut text = String::new();
+    client
+        .run_agentic_loop_with(conversation, &executor, &model, None, |event| {
+            if let AgenticEvent::TextResponse { text: chunk } = event {
+                text.push_str(chunk);
+            }
+            session.notify(
+                "event",
+                serde_json::to_value(event).unwrap_or(JsonValue::Null),
+            );
+        })
+        .await
+        .map_err(|e| MethodError::new(INTERNAL_ERROR, e.to_string()))?;
+
+    if !text.is_empty() {
+        conversation.add_assistant_message(text.clone());
+    }
+
+    Ok(json!({
+        "text": text,
+        "messages": conversation.message_count(),
+        "usage": conversation.usage,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    /// Drive a server over an in-memory duplex pipe
+    struct TestClient {
+        writer: tokio::io::WriteHalf<tokio::io::DuplexStream>,
+        lines: tokio::io::Lines<BufReader<tokio::io::ReadHalf<tokio::io::DuplexStream>>>,
+        server: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    impl TestClient {
+        fn start(server: RpcServer) -> Self {
+            let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+            let (server_read, server_write) = tokio::io::split(server_side);
+            let server = tokio::spawn(server.serve(BufReader::new(server_read), server_write));
+
+            let (client_read, client_write) = tokio::io::split(client_side);
+            Self {
+                writer: client_write,
+                lines: BufReader::new(client_read).lines(),
+                server,
+            }
+        }
+
+        async fn send(&mut self, message: JsonValue) {
+            self.writer
+                .write_all(format!("{}\n", message).as_bytes())
+                .await
+                .unwrap();
+        }
+
+        async fn recv(&mut self) -> JsonValue {
+            let line = self.lines.next_line().await.unwrap().unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+    }
+
+    fn text_response(text: &str) -> String {
+        json!({
+            "id": "msg", "type": "message", "role": "assistant", "model": "m",
+            "content": [{ "type": "text", "text": text }],
+            "stop_reason": "end_turn", "usage": { "input_tokens": 1, "output_tokens": 1 }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_errors_and_shutdown() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut client = TestClient::start(RpcServer::new(temp_dir.path()));
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }))
+            .await;
+        let response = client.recv().await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["name"], "aurora-agent");
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 5, "method": "initialize",
+                          "params": { "sampling": { "temperature": 2.0 } } }))
+            .await;
+        let response = client.recv().await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("temperature"));
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 2, "method": "bogus" }))
+            .await;
+        assert_eq!(client.recv().await["error"]["code"], METHOD_NOT_FOUND);
+
+        client.writer.write_all(b"{not json\n").await.unwrap();
+        assert_eq!(client.recv().await["error"]["code"], PARSE_ERROR);
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 3, "method": "send_message", "params": {} }))
+            .await;
+        assert_eq!(client.recv().await["error"]["code"], INVALID_PARAMS);
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }))
+            .await;
+        assert_eq!(client.recv().await["id"], 4);
+        client.server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_message_streams_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = mockito::Server::new_async().await;
+        api.mock("POST", "/messages")
+            .with_body(text_response("Hello from the agent"))
+            .create_async()
+            .await;
+
+        let server = RpcServer::new(temp_dir.path())
+            .with_client(AnthropicClient::new("key".to_string()).with_base_url(api.url()));
+        let mut client = TestClient::start(server);
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "send_message", "params": { "message": "hi" } }))
+            .await;
+
+        let event = client.recv().await;
+        assert_eq!(event["method"], "event");
+        assert_eq!(event["params"]["type"], "text_response");
+
+        let response = client.recv().await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["text"], "Hello from the agent");
+        assert_eq!(response["result"]["messages"], 2);
+        assert_eq!(response["result"]["usage"]["requests"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_tool_approval_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = mockito::Server::new_async().await;
+        api.mock("POST", "/messages")
+            .match_body(mockito::Matcher::Regex("rejected by the user".to_string()))
+            .with_body(text_response("Understood"))
+            .create_async()
+            .await;
+        api.mock("POST", "/messages")
+            .with_body(
+                json!({
+                    "id": "msg", "type": "message", "role": "assistant", "model": "m",
+                    "content": [{ "type": "tool_use", "id": "toolu_1", "name": "write",
+                                  "input": { "file_path": "x.txt", "content": "x" } }],
+                    "stop_reason": "tool_use", "usage": { "input_tokens": 1, "output_tokens": 1 }
+                })
+                .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let server = RpcServer::new(temp_dir.path())
+            .with_client(AnthropicClient::new("key".to_string()).with_base_url(api.url()));
+        let mut client = TestClient::start(server);
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "approve_tools": true } }))
+            .await;
+        client.recv().await;
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "id": 2, "method": "send_message", "params": { "message": "write x" } }))
+            .await;
+
+        let tool_call = client.recv().await;
+        assert_eq!(tool_call["params"]["type"], "tool_call");
+
+        let approval = client.recv().await;
+        assert_eq!(approval["method"], "approve_tool");
+        assert_eq!(approval["params"]["name"], "write");
+        client
+            .send(
+                json!({ "jsonrpc": "2.0", "id": approval["id"], "result": { "approved": false } }),
+            )
+            .await;
+
+        let tool_result = client.recv().await;
+        assert_eq!(tool_result["params"]["is_error"], true);
+
+        let text = client.recv().await;
+        assert_eq!(text["params"]["text"], "Understood");
+
+        let response = client.recv().await;
+        assert_eq!(response["id"], 2);
+        assert!(!temp_dir.path().join("x.txt").exists());
+    }
+}
@@ -0,0 +1,502 @@
+//! Saved chat sessions
+//!
+//! Conversations are stored as JSON files under `.AuroraHeart/sessions/`,
+//! together with metadata such as a short title generated after the first
+//! exchange. Saved sessions can be listed, reloaded, and searched by title and
+//! message content.
+
+use crate::attachments::AttachmentRecord;
+use crate::client::{AnthropicClient, ClientError, ContentBlock, MessageRequest};
+use crate::conversation::{Conversation, Message, MessageContent, Role};
+use crate::persona::PromptOverride;
+use crate::usage::ConversationUsage;
+use aurora_core::BudgetConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Maximum length of a session title in characters
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Characters of context shown around a search match
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Errors that can occur while working with saved sessions
+#[derive(Error, Debug)]
+pub enum SessionError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Session file could not be parsed or written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// No session with the given ID
+    #[error("Session not found: {0}")]
+    NotFound(String),
+
+    /// Title generation failed
+    #[error("Title generation failed: {0}")]
+    Client(#[from] ClientError),
+}
+
+pub type Result<T> = std::result::Result<T, SessionError>;
+
+/// Metadata stored with each session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionMetadata {
+    /// Unique session ID (also the file name)
+    pub id: String,
+    /// Short generated title
+    #[serde(default)]
+    pub title: Option<String>,
+    /// When the session started
+    pub created_at: DateTime<Utc>,
+    /// When the session was last saved
+    pub updated_at: DateTime<Utc>,
+    /// Number of messages in the session
+    pub message_count: usize,
+}
+
+impl SessionMetadata {
+    /// Metadata for a new session
+    pub fn new() -> Self {
+        let now = Utc::now();
+        Self {
+            id: now.format("%Y%m%d-%H%M%S-%3f").to_string(),
+            title: None,
+            created_at: now,
+            updated_at: now,
+            message_count: 0,
+        }
+    }
+}
+
+impl Default for SessionMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A session as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Session metadata
+    #[serde(flatten)]
+    pub metadata: SessionMetadata,
+    /// Conversation messages
+    pub messages: Vec<Message>,
+    /// Attachments sent during the session
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AttachmentRecord>,
+    /// Tokens and cost spent by the session
+    #[serde(default, skip_serializing_if = "ConversationUsage::is_empty")]
+    pub usage: ConversationUsage,
+    /// Budget set for the session
+    #[serde(default, skip_serializing_if = "BudgetConfig::is_unlimited")]
+    pub budget: BudgetConfig,
+    /// Persona and custom instructions of the session
+    #[serde(default, skip_serializing_if = "PromptOverride::is_empty")]
+    pub prompt_override: PromptOverride,
+}
+
+/// A message that matched a search query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageMatch {
+    /// Index of the message in the session
+    pub message_index: usize,
+    /// Who sent the message
+    pub role: Role,
+    /// Text surrounding the match
+    pub snippet: String,
+}
+
+/// A session that matched a search query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSearchResult {
+    /// Session metadata
+    pub metadata: SessionMetadata,
+    /// True when the title matched
+    pub title_match: bool,
+    /// Matching messages
+    pub matches: Vec<MessageMatch>,
+}
+
+/// Stores sessions for a project
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    /// Create a store for a sessions directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Create a store for a project's `.AuroraHeart/sessions` directory
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Self {
+        Self::new(project_root.as_ref().join(".AuroraHeart").join("sessions"))
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        // IDs become file names, so refuse anything that could escape the directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(SessionError::NotFound(id.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    /// Save a conversation, updating the metadata's timestamp and message count
+    pub fn save(&self, metadata: &mut SessionMetadata, conversation: &Conversation) -> Result<()> {
+        metadata.updated_at = Utc::now();
+        metadata.message_count = conversation.message_count();
+
+        let session = SavedSession {
+            metadata: metadata.clone(),
+            messages: conversation.messages().to_vec(),
+            attachments: conversation.attachments.clone(),
+            usage: conversation.usage.clone(),
+            budget: conversation.budget,
+            prompt_override: conversation.prompt_override.clone(),
+        };
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(&metadata.id)?, serde_json::to_string_pretty(&session)?)?;
+        Ok(())
+    }
+
+    /// Load a saved session
+    pub fn load(&self, id: &str) -> Result<SavedSession> {
+        let path = self.path(id)?;
+        if !path.exists() {
+            return Err(SessionError::NotFound(id.to_string()));
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Load every saved session, newest first
+    ///
+    /// Files that cannot be parsed are skipped with a warning.
+    fn load_all(&self) -> Result<Vec<SavedSession>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(SessionError::from)
+                .and_then(|content| Ok(serde_json::from_str::<SavedSession>(&content)?));
+            match parsed {
+                Ok(session) => sessions.push(session),
+                Err(e) => tracing::warn!("Skipping session file {}: {}", path.display(), e),
+            }
+        }
+
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.updated_at));
+        Ok(sessions)
+    }
+
+    /// List saved sessions, newest first
+    pub fn list(&self) -> Result<Vec<SessionMetadata>> {
+        Ok(self.load_all()?.into_iter().map(|s| s.metadata).collect())
+    }
+
+    /// Search titles and message text across saved sessions (case-insensitive)
+    pub fn search(&self, query: &str) -> Result<Vec<SessionSearchResult>> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(self
+            .load_all()?
+            .into_iter()
+            .filter_map(|session| {
+                let title_match = session
+                    .metadata
+                    .title
+                    .as_ref()
+                    .is_some_and(|t| t.to_lowercase().contains(&query));
+
+                let matches: Vec<MessageMatch> = session
+                    .messages
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(message_index, message)| {
+                        let text = message_text(message);
+                        snippet(&text, &query).map(|snippet| MessageMatch {
+                            message_index,
+                            role: message.role,
+                            snippet,
+                        })
+                    })
+                    .collect();
+
+                (title_match || !matches.is_empty()).then_some(SessionSearchResult {
+                    metadata: session.metadata,
+                    title_match,
+                    matches,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Whether a conversation has completed its first exchange and can be titled
+pub fn ready_for_title(conversation: &Conversation) -> bool {
+    let messages = conversation.messages();
+    messages.iter().any(|m| m.role == Role::User)
+        && messages.iter().any(|m| m.role == Role::Assistant)
+}
+
+/// Generate a short title for a conversation with a short call to `model`
+pub async fn generate_title(client: &AnthropicClient, conversation: &Conversation, model: &str) -> Result<String> {
+    let excerpt: String = conversation
+        .messages()
+        .iter()
+        .take(4)
+        .map(|m| {
+            let role = match m.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+            };
+            format!("{}: {}\n", role, truncate_chars(&message_text(m), 500))
+        })
+        .collect();
+
+    let mut prompt = Conversation::with_system_prompt(
+        "Write a short title (at most 6 words) for this conversation. \
+         Reply with the title only, without quotes or punctuation at the end.",
+    );
+    prompt.add_user_message(excerpt);
+
+    let request = MessageRequest::from_conversation(&prompt, model).with_max_tokens(30);
+    let response = client.send_message(request).await?;
+    let title = response
+        .content
+        .iter()
+        .find_map(|block| block.as_text())
+        .map(clean_title)
+        .unwrap_or_default();
+
+    if title.is_empty() {
+        Ok(fallback_title(conversation))
+    } else {
+        Ok(title)
+    }
+}
+
+/// A generated title, or the [`fallback_title`] when generation fails
+pub async fn title_or_fallback(client: &AnthropicClient, conversation: &Conversation, model: &str) -> String {
+    match generate_title(client, conversation, model).await {
+        Ok(title) => title,
+        Err(e) => {
+            tracing::warn!("Failed to generate session title with {}: {}", model, e);
+            fallback_title(conversation)
+        }
+    }
+}
+
+/// Title derived from the first user message, used when generation fails
+pub fn fallback_title(conversation: &Conversation) -> String {
+    conversation
+        .messages()
+        .iter()
+        .find(|m| m.role == Role::User)
+        .map(|m| clean_title(&message_text(m)))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Untitled session".to_string())
+}
+
+/// Searchable text of a message
+fn message_text(message: &Message) -> String {
+    match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.clone()),
+                ContentBlock::ToolResult { content, .. } => Some(content.clone()),
+                ContentBlock::ToolUse { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn clean_title(raw: &str) -> String {
+    let line = raw.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let title = line
+        .trim()
+        .trim_start_matches(['#', ' '])
+        .trim_matches(['"', '\'', '`', '*'])
+        .trim_end_matches(['.', '!', ':'])
+        .trim();
+    truncate_chars(title, MAX_TITLE_CHARS)
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max).collect();
+    match truncated.rfind(' ') {
+        Some(space) if space > 0 => format!("{}…", &truncated[..space]),
+        _ => format!("{}…", truncated),
+    }
+}
+
+/// Text around the first occurrence of `query` (already lowercased)
+fn snippet(text: &str, query: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    let start = lower.find(query)?;
+
+    // Lowercasing can change byte lengths, so map back through char positions
+    let match_char = lower[..start].chars().count();
+    let chars: Vec<char> = text.chars().collect();
+    let from = match_char.saturating_sub(SNIPPET_CONTEXT);
+    let to = (match_char + query.chars().count() + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut snippet: String = chars[from..to].iter().collect();
+    snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    if from > 0 {
+        snippet.insert(0, '…');
+    }
+    if to < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn conversation(user: &str, assistant: &str) -> Conversation {
+        let mut conversation = Conversation::new();
+        conversation.add_user_message(user);
+        conversation.add_assistant_message(assistant);
+        conversation
+    }
+
+    #[test]
+    fn test_save_load_and_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::for_project(temp_dir.path());
+        assert!(store.list().unwrap().is_empty());
+
+        let mut metadata = SessionMetadata::new();
+        metadata.title = Some("Parser bug".to_string());
+        let mut fixed = conversation("Fix the parser", "Done");
+        fixed.usage.record("claude-sonnet-4", &crate::usage::Usage { input_tokens: 40, ..Default::default() });
+        fixed.budget.max_cost_usd = Some(2.5);
+        fixed.prompt_override.persona = Some(crate::persona::Persona::Reviewer);
+        store.save(&mut metadata, &fixed).unwrap();
+        assert_eq!(metadata.message_count, 2);
+
+        let loaded = store.load(&metadata.id).unwrap();
+        assert_eq!(loaded.metadata, metadata);
+        assert_eq!(loaded.messages[0].content, "Fix the parser");
+        assert_eq!((loaded.usage, loaded.budget), (fixed.usage, fixed.budget));
+        assert_eq!(loaded.prompt_override, fixed.prompt_override);
+
+        assert_eq!(store.list().unwrap(), vec![metadata]);
+        assert!(matches!(store.load("missing"), Err(SessionError::NotFound(_))));
+        assert!(matches!(store.load("../escape"), Err(SessionError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_search_titles_and_messages() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::for_project(temp_dir.path());
+
+        let mut first = SessionMetadata::new();
+        first.id = "first".to_string();
+        first.title = Some("Tokenizer refactor".to_string());
+        store
+            .save(&mut first, &conversation("Split the lexer", "Sure"))
+            .unwrap();
+
+        let mut second = SessionMetadata::new();
+        second.id = "second".to_string();
+        store
+            .save(
+                &mut second,
+                &conversation("Why does the TOKENIZER panic on empty input?", "It indexes [0]"),
+            )
+            .unwrap();
+
+        let results = store.search("tokenizer").unwrap();
+        assert_eq!(results.len(), 2);
+
+        let first_hit = results.iter().find(|r| r.metadata.id == "first").unwrap();
+        assert!(first_hit.title_match);
+        assert!(first_hit.matches.is_empty());
+
+        let second_hit = results.iter().find(|r| r.metadata.id == "second").unwrap();
+        assert!(!second_hit.title_match);
+        assert_eq!(second_hit.matches[0].message_index, 0);
+        assert!(second_hit.matches[0].snippet.contains("TOKENIZER panic"));
+
+        assert!(store.search("  ").unwrap().is_empty());
+        assert!(store.search("nothing like this").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_titles() {
+        assert_eq!(clean_title("\"Fixing the parser.\"\nextra"), "Fixing the parser");
+        let long = fallback_title(&conversation(&"word ".repeat(30), "ok"));
+        assert!(long.chars().count() <= MAX_TITLE_CHARS + 1);
+        assert!(long.ends_with("word…"));
+        assert_eq!(fallback_title(&Conversation::new()), "Untitled session");
+
+        assert!(!ready_for_title(&Conversation::new()));
+        assert!(ready_for_title(&conversation("hi", "hello")));
+    }
+
+    #[tokio::test]
+    async fn test_generate_title() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"model":"claude-haiku-4-5","max_tokens":30}"#.to_string(),
+            ))
+            .with_body(
+                r#"{"content":[{"type":"text","text":"Parser Panic Investigation."}],"model":"m","stop_reason":"end_turn"}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let title = generate_title(&client, &conversation("Why does it panic?", "Indexing"), "claude-haiku-4-5")
+            .await
+            .unwrap();
+        assert_eq!(title, "Parser Panic Investigation");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_title_falls_back_to_first_message() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/messages")
+            .with_status(404)
+            .with_body(r#"{"type":"error","error":{"type":"not_found_error","message":"model: retired-model"}}"#)
+            .create_async()
+            .await;
+
+        let client = AnthropicClient::new("key".to_string()).with_base_url(server.url());
+        let title = title_or_fallback(&client, &conversation("Why does the parser panic?", "Indexing"), "retired-model").await;
+        assert_eq!(title, "Why does the parser panic?");
+        mock.assert_async().await;
+    }
+}
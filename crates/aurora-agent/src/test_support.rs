@@ -0,0 +1,300 @@
+//! Temporary project fixtures for tool tests
+//!
+//! Enabled with the `test-support` feature, for plugin and tool authors
+//! writing executor tests against a real directory:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! aurora-agent = { path = "...", features = ["test-support"] }
+//! ```
+//!
+//! [`ProjectBuilder`] lays out a Rust crate, a Node package, or loose files
+//! in a temporary directory, optionally as a git repository with a history of
+//! commits. Fixtures are deterministic: git runs without the user's or the
+//! system's configuration, with a fixed author and fixed commit dates, so
+//! the same builder always produces the same commit hashes.
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use aurora_agent::test_support::ProjectBuilder;
+//!
+//! let project = ProjectBuilder::rust_crate("demo")
+//!     .commit("Add greeting", [("src/greet.rs", "pub fn hi() {}\n")])
+//!     .build()?;
+//! let result = project.run_tool("read", serde_json::json!({"file_path": "src/greet.rs"})).await;
+//! assert_eq!(result.content, "pub fn hi() {}\n");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::tools::{ToolExecutor, ToolResult, ToolUse};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+
+/// Author and committer of fixture commits
+pub const FIXTURE_AUTHOR: (&str, &str) = ("AuroraHeart Fixtures", "fixtures@auroraheart.invalid");
+
+/// Date of the first fixture commit; each later commit is a minute after
+/// the one before
+pub const FIXTURE_EPOCH: i64 = 1_704_067_200;
+
+/// Branch fixture repositories are created on
+pub const FIXTURE_BRANCH: &str = "main";
+
+/// A commit of a fixture repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FixtureCommit {
+    message: String,
+    files: Vec<(String, String)>,
+}
+
+/// Builds a temporary project
+#[derive(Debug, Clone, Default)]
+pub struct ProjectBuilder {
+    files: BTreeMap<String, String>,
+    git: bool,
+    commits: Vec<FixtureCommit>,
+}
+
+impl ProjectBuilder {
+    /// An empty project
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A library crate named `name` with a `Cargo.toml` and a `src/lib.rs`
+    /// holding one tested function
+    pub fn rust_crate(name: &str) -> Self {
+        Self::new()
+            .file(
+                "Cargo.toml",
+                format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n", name),
+            )
+            .file(
+                "src/lib.rs",
+                "pub fn add(left: u64, right: u64) -> u64 {\n    left + right\n}\n\n\
+                 #[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn it_works() {\n        \
+                 assert_eq!(add(2, 2), 4);\n    }\n}\n",
+            )
+    }
+
+    /// A Node package named `name` with a `package.json` and an `index.js`
+    pub fn node_package(name: &str) -> Self {
+        Self::new()
+            .file(
+                "package.json",
+                format!(
+                    "{{\n  \"name\": \"{}\",\n  \"version\": \"1.0.0\",\n  \"main\": \"index.js\",\n  \
+                     \"scripts\": {{\n    \"test\": \"node --test\"\n  }}\n}}\n",
+                    name
+                ),
+            )
+            .file("index.js", "function add(left, right) {\n  return left + right;\n}\n\nmodule.exports = { add };\n")
+    }
+
+    /// Add or replace a file at `path`, relative to the project root
+    pub fn file(mut self, path: impl Into<String>, contents: impl Into<String>) -> Self {
+        self.files.insert(path.into(), contents.into());
+        self
+    }
+
+    /// Make the project a git repository, committing its files as
+    /// "Initial commit"
+    pub fn git(mut self) -> Self {
+        self.git = true;
+        self
+    }
+
+    /// Write `files` and commit them after the initial commit and any
+    /// earlier ones; implies [`ProjectBuilder::git`]
+    pub fn commit<P, C>(mut self, message: impl Into<String>, files: impl IntoIterator<Item = (P, C)>) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+    {
+        self.git = true;
+        self.commits.push(FixtureCommit {
+            message: message.into(),
+            files: files.into_iter().map(|(path, contents)| (path.into(), contents.into())).collect(),
+        });
+        self
+    }
+
+    /// Create the project in a new temporary directory
+    pub fn build(self) -> std::io::Result<TempProject> {
+        let project = TempProject {
+            dir: TempDir::new()?,
+            next_id: AtomicUsize::new(1),
+        };
+        for (path, contents) in &self.files {
+            project.write(path, contents)?;
+        }
+        if !self.git {
+            return Ok(project);
+        }
+
+        project.git(&["init", "--quiet"], 0)?;
+        project.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", FIXTURE_BRANCH)], 0)?;
+        project.git(&["add", "--all"], 0)?;
+        project.git(&["commit", "--quiet", "--allow-empty", "-m", "Initial commit"], 0)?;
+        for (index, commit) in self.commits.iter().enumerate() {
+            for (path, contents) in &commit.files {
+                project.write(path, contents)?;
+            }
+            project.git(&["add", "--all"], index + 1)?;
+            project.git(&["commit", "--quiet", "--allow-empty", "-m", &commit.message], index + 1)?;
+        }
+        Ok(project)
+    }
+}
+
+/// A project in a temporary directory, removed when dropped
+#[derive(Debug)]
+pub struct TempProject {
+    dir: TempDir,
+    next_id: AtomicUsize,
+}
+
+impl TempProject {
+    /// The project root
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Absolute path of `relative` in the project
+    pub fn join(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.dir.path().join(relative)
+    }
+
+    /// Write a file, creating its parent directories
+    pub fn write(&self, relative: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+        let path = self.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)
+    }
+
+    /// Contents of a file, or an empty string when it cannot be read
+    pub fn read(&self, relative: impl AsRef<Path>) -> String {
+        std::fs::read_to_string(self.join(relative)).unwrap_or_default()
+    }
+
+    /// A tool executor working in the project
+    pub fn executor(&self) -> ToolExecutor {
+        ToolExecutor::with_working_directory(self.path())
+    }
+
+    /// A tool call with the next id in sequence (`test_1`, `test_2`, ...)
+    pub fn tool_use(&self, name: &str, input: JsonValue) -> ToolUse {
+        ToolUse {
+            id: format!("test_{}", self.next_id.fetch_add(1, Ordering::Relaxed)),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    /// Run a tool in the project with a fresh executor
+    pub async fn run_tool(&self, name: &str, input: JsonValue) -> ToolResult {
+        self.executor().execute(&self.tool_use(name, input)).await
+    }
+
+    /// Commit subjects, newest first; empty outside a git repository
+    pub fn git_log(&self) -> Vec<String> {
+        self.git_output(&["log", "--format=%s"]).map(|log| log.lines().map(str::to_string).collect()).unwrap_or_default()
+    }
+
+    /// Hash of the commit `HEAD` points at
+    pub fn head(&self) -> Option<String> {
+        self.git_output(&["rev-parse", "HEAD"]).map(|head| head.trim().to_string())
+    }
+
+    fn git_output(&self, args: &[&str]) -> Option<String> {
+        let output = self.git_command(args, 0).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn git(&self, args: &[&str], commit_index: usize) -> std::io::Result<()> {
+        let output = self.git_command(args, commit_index).output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    /// git isolated from the user's and the system's configuration, dated
+    /// `commit_index` minutes after [`FIXTURE_EPOCH`]
+    fn git_command(&self, args: &[&str], commit_index: usize) -> Command {
+        let date = format!("{} +0000", FIXTURE_EPOCH + 60 * commit_index as i64);
+        let mut command = Command::new("git");
+        command
+            .args(["-c", "commit.gpgsign=false", "-c", "core.autocrlf=false"])
+            .args(args)
+            .current_dir(self.path())
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_CONFIG_GLOBAL", null_device())
+            .env("GIT_AUTHOR_NAME", FIXTURE_AUTHOR.0)
+            .env("GIT_AUTHOR_EMAIL", FIXTURE_AUTHOR.1)
+            .env("GIT_COMMITTER_NAME", FIXTURE_AUTHOR.0)
+            .env("GIT_COMMITTER_EMAIL", FIXTURE_AUTHOR.1)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date);
+        command
+    }
+}
+
+fn null_device() -> &'static str {
+    if cfg!(windows) {
+        "NUL"
+    } else {
+        "/dev/null"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rust_crate_with_tools() {
+        let project = ProjectBuilder::rust_crate("demo").file("README.md", "# Demo\n").build().unwrap();
+        assert!(project.read("Cargo.toml").contains("name = \"demo\""));
+        assert!(project.head().is_none());
+
+        let result = project.run_tool("read", serde_json::json!({"file_path": "README.md"})).await;
+        assert_eq!(result.tool_use_id, "test_1");
+        assert_eq!(result.content, "# Demo\n");
+        let result = project.run_tool("glob", serde_json::json!({"pattern": "src/*.rs"})).await;
+        assert_eq!(result.tool_use_id, "test_2");
+        assert!(result.content.contains("lib.rs"));
+    }
+
+    #[test]
+    fn test_git_history_is_deterministic() {
+        let build = || {
+            ProjectBuilder::node_package("demo")
+                .commit("Add subtract", [("index.js", "module.exports = { sub: (a, b) => a - b };\n")])
+                .commit("Add notes", [("docs/notes.md", "notes\n")])
+                .build()
+                .unwrap()
+        };
+        let first = build();
+        assert_eq!(first.git_log(), vec!["Add notes", "Add subtract", "Initial commit"]);
+        assert!(first.read("index.js").contains("sub:"));
+        assert!(first.read("package.json").contains("\"name\": \"demo\""));
+
+        let second = build();
+        assert!(first.head().is_some());
+        assert_eq!(first.head(), second.head());
+        assert_ne!(first.path(), second.path());
+    }
+}
@@ -0,0 +1,535 @@
+//! Safety rails for auto-approved tool calls
+//!
+//! In auto-approve mode tool calls run without asking the user. An
+//! [`AutoApproveGuard`] lives for one turn and refuses calls that would
+//! exceed the blast radius allowed by the policy's [`AutoApproveLimits`]:
+//! - the file tools may modify at most `max_files_per_turn` distinct files;
+//!   a `multi_replace` that is not a dry run reserves its `max_files`;
+//!   dry runs change nothing and are always admitted
+//! - `delete`, `move`, and `rm`, `mv`, or `find -delete` in bash may not
+//!   remove paths outside the workspace. Paths are resolved against every
+//!   directory an earlier `cd` may have moved to, and removals whose paths
+//!   cannot be checked (`~`, `$VAR`, or after `cd -`) are refused
+//! - commands matching a blocked command, such as `git push`, are refused
+//!   wherever they appear in a command list or pipeline: the program's name
+//!   and the subcommands after it are compared, skipping options, so
+//!   `git -c user.name=x push` is refused too
+//! - commands the guard cannot see into are refused: `eval`, `sh -c`,
+//!   `bash -c`, `xargs`, and command substitution with `$(...)` or backticks
+//! - other tools that run commands have the commands in their input checked
+//!   the same way; [`UNCHECKED_COMMAND_TOOLS`], which run code or commands
+//!   not in their input, and command tools the guard does not know are
+//!   refused
+//!
+//! Paths are compared lexically. Files changed by other shell commands are
+//! not counted.
+
+use super::policy::AutoApproveLimits;
+use super::{ToolCapabilities, ToolUse};
+use aurora_core::{normalize_path, resolve_path};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+/// Files `multi_replace` may modify when its input sets no `max_files`
+const MULTI_REPLACE_DEFAULT_MAX_FILES: usize = 50;
+
+/// Shell commands that remove files, or move them away, from the paths
+/// they are given
+const REMOVE_COMMANDS: &[&str] = &["rm", "rmdir", "unlink", "mv"];
+
+/// Shell commands that change the working directory
+const CD_COMMANDS: &[&str] = &["cd", "pushd", "popd"];
+
+/// `find` actions that remove what is found, or run a command on it
+const FIND_REMOVE_ACTIONS: &[&str] = &["-delete", "-exec", "-execdir", "-ok", "-okdir"];
+
+/// Tools running arbitrary code, or commands from the project's
+/// configuration or framework rather than their input, which auto-approve
+/// cannot check
+const UNCHECKED_COMMAND_TOOLS: &[&str] = &[
+    "run_project_task",
+    "migrate",
+    "container",
+    "run_snippet",
+    "repl",
+    "execute_cell",
+    "code_analysis",
+];
+
+/// Programs running a command given to them as an argument or on stdin
+const INDIRECT_COMMANDS: &[&str] = &["eval", "xargs"];
+
+/// Shells, which run the command given with `-c`
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash"];
+
+/// Built-in tools running commands from their input, checked for blocked
+/// commands; `bash` is checked on its own
+const CHECKED_COMMAND_TOOLS: &[&str] = &[
+    "task",
+    "build",
+    "test_runner",
+    "lint",
+    "run_quality_gate",
+    "benchmark",
+    "profile",
+    "api_spec",
+    "iac",
+    "syntax_check",
+    "db_schema",
+    "k8s",
+];
+
+/// Files counted against the per-turn limit
+#[derive(Debug, Default)]
+struct Modified {
+    /// Distinct files named by file tool calls
+    files: HashSet<PathBuf>,
+    /// Slots taken by calls whose files are not known in advance
+    reserved: usize,
+}
+
+/// Enforces [`AutoApproveLimits`] over the tool calls of one turn
+#[derive(Debug)]
+pub struct AutoApproveGuard {
+    limits: AutoApproveLimits,
+    workspace: PathBuf,
+    modified: Mutex<Modified>,
+}
+
+impl AutoApproveGuard {
+    /// Create a guard for a turn in `workspace`
+    pub fn new(workspace: impl AsRef<Path>, limits: AutoApproveLimits) -> Self {
+        Self {
+            limits,
+            workspace: normalize_path(workspace),
+            modified: Mutex::new(Modified::default()),
+        }
+    }
+
+    /// Number of files counted as modified so far this turn
+    pub fn files_modified(&self) -> usize {
+        let modified = self.modified.lock().unwrap_or_else(PoisonError::into_inner);
+        modified.files.len() + modified.reserved
+    }
+
+    /// Admit a call of a tool with `capabilities`, counting the files it may
+    /// modify, or say why it is refused
+    pub fn admit(&self, tool_use: &ToolUse, capabilities: ToolCapabilities) -> Result<(), String> {
+        if tool_use.is_dry_run() {
+            return Ok(());
+        }
+        let input = &tool_use.input;
+        match tool_use.name.as_str() {
+            "delete" => self.check_removal(input["path"].as_str())?,
+            "move" => self.check_removal(input["source"].as_str())?,
+            "bash" => self.check_command(input["command"].as_str().unwrap_or_default())?,
+            name if CHECKED_COMMAND_TOOLS.contains(&name) => {
+                for command in input_commands(input) {
+                    self.check_command(&command)?;
+                }
+            }
+            name if UNCHECKED_COMMAND_TOOLS.contains(&name) || capabilities.destructive => {
+                return Err(format!(
+                    "Auto-approve cannot check the commands '{}' runs; run it with approval instead",
+                    name
+                ));
+            }
+            _ => {}
+        }
+
+        let (files, reserve) = self.files_touched(tool_use);
+        let mut modified = self.modified.lock().unwrap_or_else(PoisonError::into_inner);
+        let new_files = files.iter().filter(|file| !modified.files.contains(*file)).count();
+        let total = modified.files.len() + modified.reserved + new_files + reserve;
+        if total > self.limits.max_files_per_turn {
+            return Err(format!(
+                "Auto-approve allows modifying at most {} files per turn; '{}' would bring this turn to {}",
+                self.limits.max_files_per_turn, tool_use.name, total
+            ));
+        }
+        modified.files.extend(files);
+        modified.reserved += reserve;
+        Ok(())
+    }
+
+    /// Files a call may modify, and extra slots for files not known in advance
+    fn files_touched(&self, tool_use: &ToolUse) -> (Vec<PathBuf>, usize) {
+        let input = &tool_use.input;
+        let path = |key: &str| input[key].as_str().map(|path| self.resolve(path));
+        match tool_use.name.as_str() {
+            "write" | "edit" | "code_format" => (path("file_path").into_iter().collect(), 0),
+            "multi_edit" => (
+                input["edits"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|edit| edit["file_path"].as_str())
+                    .map(|path| self.resolve(path))
+                    .collect(),
+                0,
+            ),
+            "delete" => (path("path").into_iter().collect(), 0),
+            "move" => (path("source").into_iter().chain(path("destination")).collect(), 0),
+            "copy" => (path("destination").into_iter().collect(), 0),
+            "multi_replace" => {
+                let max_files = input["max_files"]
+                    .as_u64()
+                    .map_or(MULTI_REPLACE_DEFAULT_MAX_FILES, |max| max as usize);
+                (Vec::new(), max_files)
+            }
+            "apply_quick_fix" | "scaffold" | "update_secret_baseline" => (Vec::new(), 1),
+            _ => (Vec::new(), 0),
+        }
+    }
+
+    /// Refuse removing a path outside the workspace
+    fn check_removal(&self, path: Option<&str>) -> Result<(), String> {
+        match path {
+            Some(path) if !self.resolve(path).starts_with(&self.workspace) => Err(format!(
+                "Auto-approve does not allow removing paths outside the workspace: {}",
+                path
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuse blocked commands, commands that cannot be checked, and
+    /// removals outside the workspace
+    fn check_command(&self, command: &str) -> Result<(), String> {
+        if command.contains("$(") || command.contains('`') {
+            return Err(
+                "Auto-approve cannot check commands run through command substitution; run it with approval instead"
+                    .to_string(),
+            );
+        }
+        // Every directory the command may be in by now: a `cd` may fail or
+        // run in a subshell, so earlier directories stay possible
+        let mut directories = Some(vec![self.workspace.clone()]);
+        for segment in command_segments(command) {
+            let words = command_words(&segment);
+            if let Some(program) = runs_indirectly(&words) {
+                return Err(format!(
+                    "Auto-approve cannot check commands run through '{}'; run it with approval instead",
+                    program
+                ));
+            }
+            if let Some(blocked) = self
+                .limits
+                .blocked_commands
+                .iter()
+                .find(|blocked| runs_blocked(&words, blocked))
+            {
+                return Err(format!("Auto-approve does not allow running '{}'", blocked));
+            }
+
+            let program = words.first().map(|word| word.rsplit('/').next().unwrap_or(word));
+            match program {
+                Some(program) if CD_COMMANDS.contains(&program) => {
+                    directories = directories.and_then(|dirs| changed_directories(dirs, &words));
+                }
+                Some(program) if REMOVE_COMMANDS.contains(&program) => {
+                    for arg in words.iter().skip(1).filter(|arg| !arg.starts_with('-')) {
+                        self.check_shell_removal(directories.as_deref(), arg, false)?;
+                    }
+                }
+                Some("find") if words.iter().any(|word| FIND_REMOVE_ACTIONS.contains(word)) => {
+                    let starts: Vec<&str> = words[1..]
+                        .iter()
+                        .copied()
+                        .take_while(|word| !word.starts_with('-') && *word != "(" && *word != "!")
+                        .collect();
+                    for arg in if starts.is_empty() { vec!["."] } else { starts } {
+                        self.check_shell_removal(directories.as_deref(), arg, true)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse a removal in a shell command of `arg`, resolved against each
+    /// directory the command may be in (`None` when that is unknown)
+    ///
+    /// The workspace itself may only be named by `find`, which removes
+    /// what it matches inside it rather than the directory.
+    fn check_shell_removal(&self, directories: Option<&[PathBuf]>, arg: &str, root_allowed: bool) -> Result<(), String> {
+        let Some(directories) = directories else {
+            return Err(format!(
+                "Auto-approve cannot check where '{}' is after the directory change before it",
+                arg
+            ));
+        };
+        if arg.starts_with('~') || arg.contains('$') || arg.contains('`') {
+            return Err(format!("Auto-approve cannot check that '{}' is inside the workspace", arg));
+        }
+        for directory in directories {
+            let path = resolve_path(directory, arg);
+            if !path.starts_with(&self.workspace) || (path == self.workspace && !root_allowed) {
+                return Err(format!(
+                    "Auto-approve does not allow removing paths outside the workspace: {}",
+                    arg
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, path: &str) -> PathBuf {
+        resolve_path(&self.workspace, path)
+    }
+}
+
+/// The directories a shell may be in after running `words`, a `cd`,
+/// `pushd`, or `popd`, from any of `directories`; `None` when the target
+/// cannot be known (`cd`, `cd -`, `cd ~`, `cd $DIR`, `popd`)
+fn changed_directories(mut directories: Vec<PathBuf>, words: &[&str]) -> Option<Vec<PathBuf>> {
+    let target = words[1..].iter().find(|word| !word.starts_with('-') || **word == "-")?;
+    if words[0].ends_with("popd") || *target == "-" || target.starts_with('~') || target.contains('$') {
+        return None;
+    }
+    let moved: Vec<PathBuf> = directories.iter().map(|directory| resolve_path(directory, target)).collect();
+    for directory in moved {
+        if !directories.contains(&directory) {
+            directories.push(directory);
+        }
+    }
+    Some(directories)
+}
+
+/// Commands and code in a tool's input: each string in it, and a custom
+/// command with its arguments
+fn input_commands(input: &serde_json::Value) -> Vec<String> {
+    fn strings(value: &serde_json::Value, out: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(text) => out.push(text.clone()),
+            serde_json::Value::Array(items) => items.iter().for_each(|item| strings(item, out)),
+            serde_json::Value::Object(fields) => fields.values().for_each(|field| strings(field, out)),
+            _ => {}
+        }
+    }
+    let mut commands = Vec::new();
+    strings(input, &mut commands);
+    if let Some(command) = input["custom_command"].as_str() {
+        let args = input["args"].as_array().into_iter().flatten().filter_map(|arg| arg.as_str());
+        commands.push(std::iter::once(command).chain(args).collect::<Vec<_>>().join(" "));
+    }
+    commands
+}
+
+/// Whether `words` run the `blocked` command: the same program, followed by
+/// its subcommands in order
+///
+/// Options are skipped, and a word after an option that could be the
+/// option's value (`-c name=value`, `-C dir`) is skipped when it is not the
+/// next subcommand.
+fn runs_blocked(words: &[&str], blocked: &str) -> bool {
+    let mut blocked = blocked.split_whitespace();
+    let (Some(program), Some(first)) = (blocked.next(), words.first()) else {
+        return false;
+    };
+    if first.rsplit('/').next() != Some(program) {
+        return false;
+    }
+    let mut expected = blocked.peekable();
+    let mut after_option = false;
+    for word in &words[1..] {
+        let Some(next) = expected.peek() else {
+            break;
+        };
+        if word.starts_with('-') {
+            after_option = !word.contains('=');
+        } else if word == next {
+            expected.next();
+            after_option = false;
+        } else if after_option {
+            after_option = false;
+        } else {
+            return false;
+        }
+    }
+    expected.peek().is_none()
+}
+
+/// The program running a command that is not among `words`, as in
+/// `eval ...`, `xargs ...`, or `sh -c ...`
+fn runs_indirectly<'a>(words: &[&'a str]) -> Option<&'a str> {
+    let program = words.first()?.rsplit('/').next()?;
+    let shell_command = SHELLS.contains(&program)
+        && words[1..]
+            .iter()
+            .any(|word| word.starts_with('-') && !word.starts_with("--") && word.contains('c'));
+    (INDIRECT_COMMANDS.contains(&program) || shell_command).then_some(program)
+}
+
+/// Split a shell command into the simple commands of its lists and pipelines
+fn command_segments(command: &str) -> Vec<String> {
+    command
+        .replace("&&", ";")
+        .replace("||", ";")
+        .split([';', '|', '&', '\n', '(', ')'])
+        .map(str::to_string)
+        .collect()
+}
+
+/// Words of a simple command without quotes, a leading `sudo`, `env`, or
+/// variable assignments
+fn command_words(segment: &str) -> Vec<&str> {
+    let mut words: Vec<&str> = segment
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
+        .collect();
+    while words
+        .first()
+        .is_some_and(|word| *word == "sudo" || *word == "env" || *word == "command" || is_assignment(word))
+    {
+        words.remove(0);
+    }
+    words
+}
+
+/// Whether a word is a `NAME=value` assignment
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::builtin_capabilities;
+
+    fn call(name: &str, input: serde_json::Value) -> ToolUse {
+        ToolUse {
+            id: "1".to_string(),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    fn admit(guard: &AutoApproveGuard, tool_use: ToolUse) -> Result<(), String> {
+        guard.admit(&tool_use, builtin_capabilities(&tool_use.name))
+    }
+
+    fn guard(max_files: usize) -> AutoApproveGuard {
+        AutoApproveGuard::new(
+            "/work/project",
+            AutoApproveLimits {
+                max_files_per_turn: max_files,
+                ..AutoApproveLimits::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_file_limit_counts_distinct_files() {
+        let guard = guard(3);
+        let write = |path: &str| call("write", serde_json::json!({ "file_path": path, "content": "" }));
+
+        assert!(admit(&guard, write("a.rs")).is_ok());
+        assert!(admit(&guard, write("/work/project/a.rs")).is_ok(), "the same file counts once");
+        assert!(admit(&guard, call("multi_edit", serde_json::json!({ "edits": [{ "file_path": "b.rs" }, { "file_path": "c.rs" }] })))
+            .is_ok());
+        assert_eq!(guard.files_modified(), 3);
+
+        let refused = admit(&guard, write("d.rs")).unwrap_err();
+        assert!(refused.contains("at most 3 files"), "{}", refused);
+        assert!(admit(&guard, call("read", serde_json::json!({ "file_path": "e.rs" }))).is_ok());
+
+        let guard = self::guard(10);
+        assert!(admit(&guard, call("multi_replace", serde_json::json!({ "pattern": "a", "replacement": "b" })))
+            .is_ok(), "dry runs change nothing");
+        assert!(admit(&guard, call("multi_replace", serde_json::json!({ "pattern": "a", "replacement": "b", "dry_run": false })))
+            .is_err(), "the default of 50 files exceeds the limit");
+    }
+
+    #[test]
+    fn test_removals_stay_in_workspace() {
+        let guard = guard(20);
+        assert!(admit(&guard, call("delete", serde_json::json!({ "path": "target/tmp" }))).is_ok());
+        assert!(admit(&guard, call("delete", serde_json::json!({ "path": "../other" }))).is_err());
+        assert!(admit(&guard, call("move", serde_json::json!({ "source": "/etc/hosts", "destination": "hosts" })))
+            .is_err());
+
+        let bash = |command: &str| call("bash", serde_json::json!({ "command": command }));
+        assert!(admit(&guard, bash("rm -rf target/debug && cargo build")).is_ok());
+        assert!(admit(&guard, bash("cargo clean; sudo rm -rf /usr/local/lib")).is_err());
+        assert!(admit(&guard, bash("rm -rf ~/cache")).is_err());
+        assert!(admit(&guard, bash("rm \"$TMPDIR/file\"")).is_err());
+
+        assert!(admit(&guard, bash("cd .. && rm -rf x")).is_err());
+        assert!(admit(&guard, bash("cd /tmp; rm -r y")).is_err());
+        assert!(admit(&guard, bash("cd .. && rm -rf project")).is_err());
+        assert!(admit(&guard, bash("(cd src) && rm -rf ../x")).is_err(), "the cd may not have stuck");
+        assert!(admit(&guard, bash("cd src && rm -rf generated")).is_ok());
+        assert!(admit(&guard, bash("cd && rm -rf x")).is_err());
+        assert!(admit(&guard, bash("pushd /tmp && popd && rm x")).is_err());
+        assert!(admit(&guard, bash("find .. -name '*.o' -delete")).is_err());
+        assert!(admit(&guard, bash("find / -exec rm {} +")).is_err());
+        assert!(admit(&guard, bash("find target -name '*.o' -delete")).is_ok());
+        assert!(admit(&guard, bash("find . -name '*.rs' | wc -l")).is_ok());
+        assert!(admit(&guard, bash("mv notes.txt /tmp/")).is_err());
+        assert!(admit(&guard, bash("mv old.rs new.rs")).is_ok());
+    }
+
+    #[test]
+    fn test_blocked_commands() {
+        let guard = guard(20);
+        let bash = |command: &str| call("bash", serde_json::json!({ "command": command }));
+
+        assert!(admit(&guard, bash("git status && git diff | head")).is_ok());
+        assert!(admit(&guard, bash("git pushd")).is_ok());
+        let refused = admit(&guard, bash("cargo test && git   push origin main")).unwrap_err();
+        assert_eq!(refused, "Auto-approve does not allow running 'git push'");
+        assert!(admit(&guard, bash("NPM_TOKEN=x npm publish --access public")).is_err());
+        assert!(admit(&guard, bash("(cd pkg && cargo publish)")).is_err());
+        assert!(admit(&guard, bash("git -c user.name=x push origin")).is_err());
+        assert!(admit(&guard, bash("/usr/bin/git --no-pager -C repo push")).is_err());
+        assert!(admit(&guard, bash("git commit -m 'push the fix'")).is_ok());
+    }
+
+    #[test]
+    fn test_command_tools_are_checked() {
+        let guard = guard(20);
+        let steps = serde_json::json!({ "steps": [{ "name": "ship", "command": "cargo test && git push" }] });
+        assert!(admit(&guard, call("task", steps)).is_err());
+        let custom = serde_json::json!({ "project_type": "custom", "custom_command": "git", "args": ["push"] });
+        assert!(admit(&guard, call("build", custom)).is_err());
+        assert!(admit(&guard, call("build", serde_json::json!({ "build_type": "release" }))).is_ok());
+
+        let refused = admit(&guard, call("run_project_task", serde_json::json!({ "name": "release" }))).unwrap_err();
+        assert!(refused.contains("cannot check the commands 'run_project_task' runs"), "{}", refused);
+        assert!(admit(&guard, call("migrate", serde_json::json!({ "action": "up" }))).is_err());
+    }
+
+    #[test]
+    fn test_code_tools_are_not_auto_approved() {
+        let guard = guard(20);
+        let code = "import subprocess\nsubprocess.run([\"git\", \"push\"])";
+        let snippet = serde_json::json!({ "language": "python", "code": code });
+        let refused = admit(&guard, call("run_snippet", snippet)).unwrap_err();
+        assert!(refused.contains("cannot check the commands 'run_snippet' runs"), "{}", refused);
+        assert!(admit(&guard, call("repl", serde_json::json!({ "language": "python", "code": code }))).is_err());
+        assert!(admit(&guard, call("execute_cell", serde_json::json!({ "path": "a.ipynb", "cell": 0 }))).is_err());
+        assert!(admit(&guard, call("code_analysis", serde_json::json!({ "path": "." }))).is_err());
+    }
+
+    #[test]
+    fn test_indirect_commands_are_refused() {
+        let guard = guard(20);
+        let bash = |command: &str| admit(&guard, call("bash", serde_json::json!({ "command": command })));
+
+        assert_eq!(
+            bash("eval \"git push\"").unwrap_err(),
+            "Auto-approve cannot check commands run through 'eval'; run it with approval instead"
+        );
+        assert!(bash("sh -c 'git push'").unwrap_err().contains("through 'sh'"));
+        assert!(bash("cargo build && /bin/bash -lc 'git push'").unwrap_err().contains("through 'bash'"));
+        assert!(bash("echo origin | xargs git push").unwrap_err().contains("through 'xargs'"));
+        assert!(bash("echo $(git push)").unwrap_err().contains("command substitution"));
+        assert!(bash("echo `git push`").unwrap_err().contains("command substitution"));
+        assert!(bash("bash scripts/check.sh && sh --version").is_ok());
+    }
+}
@@ -0,0 +1,161 @@
+//! Cache of file reads
+//!
+//! Within one conversation the model often re-reads the same file.
+//! [`ToolCache`] keeps the results of `read` and `read_document` keyed by
+//! tool name and input, and serves a repeat only while the file keeps its
+//! modification time and no unsaved editor buffer has changed. Calling any
+//! tool that may write clears the whole cache.
+//!
+//! Searches and directory listings are not cached: they depend on every
+//! file below their root, and editing a nested file does not change the
+//! root's modification time.
+
+use super::builtin_capabilities;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Tools whose results are cached, each reading the one file named in its input
+const CACHEABLE_TOOLS: &[&str] = &["read", "read_document"];
+
+/// State of the files a cached result was computed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Fingerprint {
+    /// Modification time of the file named in the tool input
+    pub(crate) mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Hash of every unsaved editor buffer
+    pub(crate) buffers: u64,
+}
+
+/// A cached tool result
+struct CacheEntry {
+    fingerprint: Fingerprint,
+    content: String,
+    data: Option<JsonValue>,
+}
+
+/// Per-conversation cache of file reads
+///
+/// Share one cache between the executors created for a conversation with
+/// [`ToolExecutor::with_tool_cache`](super::ToolExecutor::with_tool_cache),
+/// and clear it when the conversation is reset.
+#[derive(Default)]
+pub struct ToolCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl ToolCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether results of `tool` are cached
+    pub fn is_cacheable(tool: &str) -> bool {
+        CACHEABLE_TOOLS.contains(&tool)
+    }
+
+    /// Number of cached results
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or(0)
+    }
+
+    /// Whether the cache holds no results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every cached result
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Clear the cache unless `tool` is known not to write
+    pub(crate) fn invalidate_after(&self, tool: &str) {
+        if !Self::is_cacheable(tool) && !builtin_capabilities(tool).read_only {
+            self.clear();
+        }
+    }
+
+    /// The cached content and data for a call, if the files are unchanged
+    pub(crate) fn get(
+        &self,
+        tool: &str,
+        input: &JsonValue,
+        fingerprint: &Fingerprint,
+    ) -> Option<(String, Option<JsonValue>)> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(&cache_key(tool, input))
+            .filter(|entry| entry.fingerprint == *fingerprint)
+            .map(|entry| (entry.content.clone(), entry.data.clone()))
+    }
+
+    /// Remember the result of a successful call
+    pub(crate) fn insert(
+        &self,
+        tool: &str,
+        input: &JsonValue,
+        fingerprint: Fingerprint,
+        content: String,
+        data: Option<JsonValue>,
+    ) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                cache_key(tool, input),
+                CacheEntry {
+                    fingerprint,
+                    content,
+                    data,
+                },
+            );
+        }
+    }
+}
+
+fn cache_key(tool: &str, input: &JsonValue) -> String {
+    format!("{}:{}", tool, input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(buffers: u64) -> Fingerprint {
+        Fingerprint {
+            mtimes: vec![(PathBuf::from("src/lib.rs"), Some(SystemTime::UNIX_EPOCH))],
+            buffers,
+        }
+    }
+
+    #[test]
+    fn test_cache_hits_and_invalidation() {
+        let cache = ToolCache::new();
+        let input = serde_json::json!({ "file_path": "src/lib.rs" });
+        cache.insert("read", &input, fingerprint(1), "fn main() {}".to_string(), None);
+
+        assert_eq!(
+            cache.get("read", &input, &fingerprint(1)),
+            Some(("fn main() {}".to_string(), None))
+        );
+        assert_eq!(cache.get("read", &input, &fingerprint(2)), None);
+        assert_eq!(cache.get("grep", &input, &fingerprint(1)), None);
+
+        cache.invalidate_after("get_diagnostics");
+        cache.invalidate_after("grep");
+        assert_eq!(cache.len(), 1);
+
+        cache.invalidate_after("update_secret_baseline");
+        assert!(cache.is_empty(), "updating the baseline writes a file");
+        cache.insert("read", &input, fingerprint(1), "fn main() {}".to_string(), None);
+        cache.invalidate_after("edit");
+        assert!(cache.is_empty());
+        assert!(ToolCache::is_cacheable("read_document"));
+        assert!(!ToolCache::is_cacheable("grep"));
+        assert!(!ToolCache::is_cacheable("bash"));
+    }
+}
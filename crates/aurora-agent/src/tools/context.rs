@@ -0,0 +1,350 @@
+//! Context of a single tool call
+//!
+//! A [`ToolContext`] is handed to every [`AgentTool`](super::AgentTool) when
+//! it runs. It carries what belongs to the call rather than to the executor:
+//! the conversation it was made in, the project root it runs against, the
+//! approval broker of the agent that made it, where its output streams to,
+//! and the token that cancels it.
+//!
+//! Callers describe a call with [`CallOptions`] and pass them to
+//! [`ToolExecutor::execute_call`](super::ToolExecutor::execute_call).
+//! Cancelling the token (or calling
+//! [`ToolExecutor::cancel`](super::ToolExecutor::cancel) with the call's ID)
+//! stops the tool at its next await point and fails the call.
+
+use super::executor::OutputSink;
+use super::{ToolExecutor, ToolOutputSender, ToolUse};
+use crate::agent::ToolApproval;
+use aurora_core::OutputStream;
+use serde_json::Value as JsonValue;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Cancels tool calls sharing it
+///
+/// Clones share their state: cancelling one cancels all of them.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<watch::Sender<bool>>,
+}
+
+impl CancellationToken {
+    /// A token that has not been cancelled
+    pub fn new() -> Self {
+        Self {
+            cancelled: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Cancel every call using this token
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    /// Whether the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        let mut receiver = self.cancelled.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a tool call is made, for [`ToolExecutor::execute_call`](super::ToolExecutor::execute_call)
+#[derive(Clone, Default)]
+pub struct CallOptions<'a> {
+    /// Conversation the call belongs to; metrics are attributed to it
+    pub conversation: Option<&'a str>,
+    /// Receives command output as it is produced
+    pub output: Option<&'a ToolOutputSender>,
+    /// Asked before actions that need the user's consent; `None` approves them
+    pub approval: Option<&'a dyn ToolApproval>,
+    /// Cancels the call; every call can also be cancelled by its ID
+    pub cancel: Option<CancellationToken>,
+}
+
+impl<'a> CallOptions<'a> {
+    /// Attribute the call to `conversation`
+    pub fn conversation(mut self, conversation: Option<&'a str>) -> Self {
+        self.conversation = conversation;
+        self
+    }
+
+    /// Stream command output to `output`
+    pub fn output(mut self, output: Option<&'a ToolOutputSender>) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Ask `approval` before actions that need consent
+    pub fn approval(mut self, approval: Option<&'a dyn ToolApproval>) -> Self {
+        self.approval = approval;
+        self
+    }
+
+    /// Cancel the call with `cancel`
+    pub fn cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+}
+
+/// What a running tool can use of the call and the executor running it
+pub struct ToolContext<'a> {
+    pub(super) executor: &'a ToolExecutor,
+    pub(super) conversation: Option<&'a str>,
+    pub(super) project_root: PathBuf,
+    pub(super) approval: Option<&'a dyn ToolApproval>,
+    pub(super) cancel: CancellationToken,
+    pub(super) sink: Option<&'a OutputSink<'a>>,
+    pub(super) data: &'a mut Option<JsonValue>,
+}
+
+impl ToolContext<'_> {
+    /// The executor running the tool
+    pub fn executor(&self) -> &ToolExecutor {
+        self.executor
+    }
+
+    /// Conversation the call belongs to, if any
+    pub fn conversation(&self) -> Option<&str> {
+        self.conversation
+    }
+
+    /// Project root the call runs against
+    ///
+    /// Fixed when the call starts, so pointing the executor at another
+    /// project does not move paths resolved through the context.
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    /// Resolve a path from the tool input against the project root
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        aurora_core::resolve_path(&self.project_root, path)
+    }
+
+    /// Ask the user whether `action` may go ahead
+    ///
+    /// Approved without asking when the call has no approval broker, e.g.
+    /// while calls are auto-approved or in headless runs.
+    pub async fn request_approval(&self, action: &ToolUse) -> bool {
+        match self.approval {
+            Some(approval) => approval.approve(action).await,
+            None => true,
+        }
+    }
+
+    /// Ask the user whether `action` may go ahead, refusing it when there is
+    /// nobody to ask
+    ///
+    /// For actions too consequential to auto-approve, such as changing
+    /// deployed infrastructure.
+    pub async fn require_approval(&self, action: &ToolUse) -> bool {
+        match self.approval {
+            Some(approval) => approval.approve(action).await,
+            None => false,
+        }
+    }
+
+    /// Whether the call has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// The call's cancellation token, to hand to work it spawns
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Stream output to the IDE while the tool runs
+    ///
+    /// Output is dropped when nobody is watching; the tool's result should
+    /// still contain everything the model needs.
+    pub fn output(&self, stream: OutputStream, text: impl Into<String>) {
+        if let Some(sink) = self.sink {
+            sink.send(stream, text.into());
+        }
+    }
+
+    /// Attach structured data for the IDE to the result
+    pub fn set_data(&mut self, data: JsonValue) {
+        *self.data = Some(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{AgentTool, ToolCapabilities, ToolFuture};
+    use std::future::Future;
+    use std::pin::Pin;
+    use tempfile::TempDir;
+
+    /// Reports what it sees of its context, asking before a pretend write
+    struct Inspect;
+
+    impl AgentTool for Inspect {
+        fn name(&self) -> &str {
+            "inspect"
+        }
+
+        fn description(&self) -> String {
+            "Describe the call context".to_string()
+        }
+
+        fn input_schema(&self) -> JsonValue {
+            serde_json::json!({ "type": "object", "properties": {} })
+        }
+
+        fn capabilities(&self) -> ToolCapabilities {
+            ToolCapabilities::READ_ONLY
+        }
+
+        fn execute<'a>(&'a self, _input: &'a JsonValue, ctx: ToolContext<'a>) -> ToolFuture<'a> {
+            Box::pin(async move {
+                let write = ToolUse {
+                    id: "inspect_write".to_string(),
+                    name: "write".to_string(),
+                    input: serde_json::json!({ "file_path": "notes.txt", "content": "" }),
+                };
+                ctx.output(OutputStream::Stdout, "inspecting\n");
+                Ok(format!(
+                    "{} {} {} {} {}",
+                    ctx.conversation().unwrap_or("none"),
+                    ctx.resolve("notes.txt").starts_with(ctx.project_root()),
+                    ctx.request_approval(&write).await,
+                    ctx.require_approval(&write).await,
+                    ctx.is_cancelled(),
+                ))
+            })
+        }
+    }
+
+    struct RejectAll;
+
+    impl ToolApproval for RejectAll {
+        fn approve<'a>(&'a self, _tool_use: &'a ToolUse) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+            Box::pin(async { false })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_context_describes_call() {
+        let temp_dir = TempDir::new().unwrap();
+        let executor = ToolExecutor::with_working_directory(temp_dir.path()).with_tool(Inspect);
+        let call = ToolUse {
+            id: "inspect_1".to_string(),
+            name: "inspect".to_string(),
+            input: serde_json::json!({}),
+        };
+
+        let result = executor.execute(&call).await;
+        assert_eq!(result.content, "none true true false false");
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let options = CallOptions::default()
+            .conversation(Some("session-1"))
+            .output(Some(&sender))
+            .approval(Some(&RejectAll));
+        let result = executor.execute_call(&call, options).await;
+        assert_eq!(result.content, "session-1 true false false false");
+        assert!(receiver.try_recv().is_ok(), "output reaches the sender");
+    }
+
+    /// Resolves a path before and after the test switches projects
+    struct SwitchDuringCall {
+        started: Arc<tokio::sync::Notify>,
+        switched: Arc<tokio::sync::Notify>,
+    }
+
+    impl AgentTool for SwitchDuringCall {
+        fn name(&self) -> &str {
+            "switch_during_call"
+        }
+
+        fn description(&self) -> String {
+            "Resolve a path across a project switch".to_string()
+        }
+
+        fn input_schema(&self) -> JsonValue {
+            serde_json::json!({ "type": "object", "properties": {} })
+        }
+
+        fn capabilities(&self) -> ToolCapabilities {
+            ToolCapabilities::READ_ONLY
+        }
+
+        fn execute<'a>(&'a self, _input: &'a JsonValue, ctx: ToolContext<'a>) -> ToolFuture<'a> {
+            Box::pin(async move {
+                let before = ctx.executor().resolve("a.txt");
+                self.started.notify_one();
+                self.switched.notified().await;
+                let after = ctx.executor().resolve("a.txt");
+                Ok(format!("{}\n{}", before.display(), after.display()))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_project_switch_during_call() {
+        let old_project = TempDir::new().unwrap();
+        let new_project = TempDir::new().unwrap();
+        let started = Arc::new(tokio::sync::Notify::new());
+        let switched = Arc::new(tokio::sync::Notify::new());
+        let executor = ToolExecutor::with_working_directory(old_project.path()).with_tool(SwitchDuringCall {
+            started: Arc::clone(&started),
+            switched: Arc::clone(&switched),
+        });
+        let call = ToolUse {
+            id: "switch_1".to_string(),
+            name: "switch_during_call".to_string(),
+            input: serde_json::json!({}),
+        };
+
+        let switch = async {
+            started.notified().await;
+            executor.set_working_directory(new_project.path());
+            switched.notify_one();
+        };
+        let (result, ()) = tokio::join!(executor.execute(&call), switch);
+
+        let expected = aurora_core::resolve_path(old_project.path(), "a.txt");
+        assert_eq!(result.is_error, None, "{}", result.content);
+        assert_eq!(result.content, format!("{}\n{}", expected.display(), expected.display()));
+        assert_eq!(executor.working_directory(), aurora_core::normalize_path(new_project.path()));
+        let after = executor.execute(&call);
+        let proceed = async {
+            started.notified().await;
+            switched.notify_one();
+        };
+        let (result, ()) = tokio::join!(after, proceed);
+        assert!(result.content.starts_with(&new_project.path().display().to_string()), "{}", result.content);
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+
+        let waiter = tokio::spawn(async move { clone.cancelled().await });
+        token.cancel();
+        waiter.await.unwrap();
+        assert!(token.is_cancelled());
+
+        // Waiting on an already cancelled token returns at once
+        token.cancelled().await;
+    }
+}
@@ -0,0 +1,448 @@
+//! Out-of-process tool execution with resource limits
+//!
+//! A tool that hangs or runs away with memory (a pathological regex, a huge
+//! file) takes the whole IDE with it when it runs in-process. The policy's
+//! `[isolation]` table picks tools, by name or by category, to run in a
+//! separate worker process instead:
+//!
+//! ```toml
+//! [isolation]
+//! # "read_only", "write", "destructive", or "network"
+//! categories = ["read_only"]
+//! tools = ["multi_replace"]
+//! cpu_seconds = 60
+//! memory_mb = 2048
+//! timeout_secs = 120
+//! ```
+//!
+//! The worker is the running program itself, started with [`WORKER_ARG`];
+//! binaries that run tools call [`run_worker`] when they see it. The call
+//! goes to the worker as JSON on stdin and its [`ToolResult`] comes back as
+//! JSON on stdout. On Unix and Windows the worker's CPU time and memory are
+//! capped with [`ProcessLimits`], and the limits carry over to commands it
+//! starts. Other platforms have no CPU or memory limits: `cpu_seconds` and
+//! `memory_mb` are ignored there and only the timeout applies. A worker over
+//! its timeout, or whose call is cancelled, is killed.
+//!
+//! The worker runs the call under the same [`ToolPolicy`], login shell
+//! environment, and project config as the executor that sent it. Isolated
+//! calls work on the files on disk: they do not see unsaved editor buffers
+//! or stream output while running. Tools that need the IDE's state
+//! ([`IN_PROCESS_TOOLS`]) and tools registered by embedders always run
+//! in-process.
+
+use super::policy::ToolPolicy;
+use super::registry::ToolCapabilities;
+use super::{CallOptions, ToolError, ToolExecutor, ToolResult, ToolUse};
+use aurora_core::{Config, FileHistory, ProcessLimits};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Argument that starts a program as a tool worker
+pub const WORKER_ARG: &str = "--aurora-tool-worker";
+
+/// Tools that use the IDE's in-process state and are never isolated
+pub const IN_PROCESS_TOOLS: &[&str] = &[
+    "task",
+    "get_selection",
+    "get_open_editors",
+    "get_diagnostics",
+    "apply_quick_fix",
+    "repl",
+    "execute_cell",
+];
+
+/// A group of tools that can be isolated together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCategory {
+    /// Tools that only read and analyze
+    ReadOnly,
+    /// Tools that change files or run commands
+    Write,
+    /// Tools that delete data or run arbitrary commands
+    Destructive,
+    /// Tools that use the network
+    Network,
+}
+
+impl ToolCategory {
+    /// Whether a tool with `capabilities` is in the category
+    pub fn includes(&self, capabilities: ToolCapabilities) -> bool {
+        match self {
+            ToolCategory::ReadOnly => capabilities.read_only,
+            ToolCategory::Write => !capabilities.read_only,
+            ToolCategory::Destructive => capabilities.destructive,
+            ToolCategory::Network => capabilities.network,
+        }
+    }
+}
+
+/// Which tools run in a worker process, and its limits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IsolationPolicy {
+    /// Categories whose tools are isolated
+    #[serde(default)]
+    pub categories: BTreeSet<ToolCategory>,
+
+    /// Tools isolated by name
+    #[serde(default)]
+    pub tools: BTreeSet<String>,
+
+    /// CPU time the worker may use, in seconds; enforced only on Unix and Windows
+    #[serde(default = "default_cpu_seconds")]
+    pub cpu_seconds: u64,
+
+    /// Heap and other private data the worker may allocate, in megabytes;
+    /// enforced only on Unix and Windows
+    #[serde(default = "default_memory_mb")]
+    pub memory_mb: u64,
+
+    /// Wall-clock time before the worker is killed, in seconds
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_cpu_seconds() -> u64 {
+    60
+}
+
+fn default_memory_mb() -> u64 {
+    2048
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for IsolationPolicy {
+    fn default() -> Self {
+        Self {
+            categories: BTreeSet::new(),
+            tools: BTreeSet::new(),
+            cpu_seconds: default_cpu_seconds(),
+            memory_mb: default_memory_mb(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+impl IsolationPolicy {
+    /// Whether calls to `tool` with `capabilities` run in a worker
+    pub fn isolates(&self, tool: &str, capabilities: ToolCapabilities) -> bool {
+        !IN_PROCESS_TOOLS.contains(&tool)
+            && (self.tools.contains(tool) || self.categories.iter().any(|category| category.includes(capabilities)))
+    }
+
+    /// The CPU and memory limits of a worker
+    fn process_limits(&self) -> ProcessLimits {
+        ProcessLimits {
+            cpu_seconds: Some(self.cpu_seconds),
+            memory_bytes: Some(self.memory_mb.saturating_mul(1024 * 1024)),
+        }
+    }
+}
+
+/// The program started as a tool worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsolationWorker {
+    /// Program to run
+    pub program: PathBuf,
+    /// Arguments that make it a worker
+    pub args: Vec<String>,
+}
+
+impl IsolationWorker {
+    /// The running program, started with [`WORKER_ARG`]
+    pub fn current_exe() -> std::io::Result<Self> {
+        Ok(Self {
+            program: std::env::current_exe()?,
+            args: vec![WORKER_ARG.to_string()],
+        })
+    }
+}
+
+/// A tool call sent to a worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerRequest {
+    /// Directory the tool works in
+    pub working_directory: PathBuf,
+    /// Record file changes in the project's local file history
+    pub file_history: bool,
+    /// Project configuration the file history is kept under
+    pub config: Config,
+    /// Policy of the executor that sent the call
+    pub policy: ToolPolicy,
+    /// Environment captured from the user's login shell, if any
+    pub shell_environment: Option<BTreeMap<String, String>>,
+    /// The call
+    pub tool_use: ToolUse,
+}
+
+/// Whether the program was started as a tool worker
+pub fn is_worker_invocation() -> bool {
+    std::env::args().nth(1).as_deref() == Some(WORKER_ARG)
+}
+
+/// Serve one tool call as a worker: read a [`WorkerRequest`] on stdin and
+/// print the [`ToolResult`] on stdout, returning the exit code
+pub async fn run_worker() -> i32 {
+    let mut input = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut input) {
+        eprintln!("failed to read tool request: {}", e);
+        return 2;
+    }
+    let request: WorkerRequest = match serde_json::from_str(&input) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("invalid tool request: {}", e);
+            return 2;
+        }
+    };
+    let result = execute_request(&request).await;
+    match serde_json::to_string(&result) {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            eprintln!("failed to serialize tool result: {}", e);
+            1
+        }
+    }
+}
+
+/// Run a worker request in this process
+async fn execute_request(request: &WorkerRequest) -> ToolResult {
+    // The worker runs the call itself rather than isolating it again
+    let policy = ToolPolicy {
+        isolation: IsolationPolicy::default(),
+        ..request.policy.clone()
+    };
+    let mut executor = ToolExecutor::with_working_directory(&request.working_directory).with_policy(policy);
+    executor.set_shell_environment(
+        request
+            .shell_environment
+            .clone()
+            .map(|vars| vars.into_iter().collect()),
+    );
+    if request.file_history {
+        executor = executor.with_file_history(FileHistory::for_project(&request.working_directory, &request.config));
+    }
+    if let Some(refusal) = executor.policy_refusal(&request.tool_use) {
+        return refusal;
+    }
+    let mut data = None;
+    let result = executor
+        .dispatch(&request.tool_use, &CallOptions::default(), None, &mut data)
+        .await;
+    let result = match result {
+        Ok(content) => ToolResult::success(request.tool_use.id.clone(), content),
+        Err(e) => ToolResult::error(request.tool_use.id.clone(), e.to_string()),
+    };
+    match data {
+        Some(data) => result.with_data(data),
+        None => result,
+    }
+}
+
+/// Run `request` in a worker process within the limits of `policy`
+///
+/// Dropping the returned future kills the worker.
+pub async fn run_isolated(
+    worker: &IsolationWorker,
+    policy: &IsolationPolicy,
+    request: &WorkerRequest,
+) -> Result<ToolResult, ToolError> {
+    let tool = &request.tool_use.name;
+    let mut command = tokio::process::Command::new(&worker.program);
+    command
+        .args(&worker.args)
+        .current_dir(&request.working_directory)
+        // Keep glibc from reserving an arena per thread against the memory limit
+        .env("MALLOC_ARENA_MAX", "2")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if !ProcessLimits::ENFORCED {
+        tracing::warn!(
+            "Tool '{}' runs in a worker without CPU or memory limits, which only apply on Unix and Windows; only the {} second timeout applies",
+            tool,
+            policy.timeout_secs
+        );
+    }
+    policy.process_limits().apply(&mut command);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| ToolError::CommandFailed(format!("could not start tool worker: {}", e)))?;
+    // Dropping the child kills a worker that would run unlimited
+    policy
+        .process_limits()
+        .attach(&child)
+        .map_err(|e| ToolError::CommandFailed(format!("could not limit tool worker: {}", e)))?;
+    let input = serde_json::to_vec(request)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A worker that dies early closes its stdin; its exit status says why
+        let _ = stdin.write_all(&input).await;
+    }
+
+    let output = match tokio::time::timeout(Duration::from_secs(policy.timeout_secs), child.wait_with_output()).await {
+        Ok(output) => output?,
+        Err(_) => {
+            return Err(ToolError::Isolated(format!(
+                "Tool '{}' did not finish within {} seconds and was stopped",
+                tool, policy.timeout_secs
+            )))
+        }
+    };
+
+    if !output.status.success() {
+        return Err(ToolError::Isolated(format!(
+            "Tool '{}' stopped in its worker process ({}){}",
+            tool,
+            describe_exit(&output.status, policy),
+            match String::from_utf8_lossy(&output.stderr).trim() {
+                "" => String::new(),
+                stderr => format!(": {}", stderr),
+            }
+        )));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| ToolError::Isolated(format!("Tool '{}' worker returned an invalid result: {}", tool, e)))
+}
+
+fn describe_exit(status: &std::process::ExitStatus, policy: &IsolationPolicy) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        match status.signal() {
+            Some(libc::SIGXCPU) => return format!("over its CPU limit of {} seconds", policy.cpu_seconds),
+            Some(libc::SIGKILL) => return "killed".to_string(),
+            Some(libc::SIGABRT) | Some(libc::SIGSEGV) => {
+                return format!("crashed, possibly over its memory limit of {} MB", policy.memory_mb)
+            }
+            Some(signal) => return format!("signal {}", signal),
+            None => {}
+        }
+    }
+    #[cfg(windows)]
+    {
+        // STATUS_NO_MEMORY, or the fail-fast abort of a failed allocation
+        if let Some(0xC000_0017 | 0xC000_0409) = status.code().map(|code| code as u32) {
+            return format!("crashed, possibly over its memory limit of {} MB", policy.memory_mb);
+        }
+    }
+    let _ = policy;
+    status.to_string()
+}
+
+/// The worker request for running `tool_use` as `executor` would
+pub(super) fn request_for(executor: &ToolExecutor, tool_use: &ToolUse) -> WorkerRequest {
+    let working_directory = executor.working_directory();
+    WorkerRequest {
+        config: Config::load(&working_directory).unwrap_or_default(),
+        file_history: executor.history().is_some(),
+        policy: executor.policy(),
+        shell_environment: executor
+            .shell_environment()
+            .map(|environment| environment.vars().clone()),
+        working_directory,
+        tool_use: tool_use.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tool_use(name: &str, input: serde_json::Value) -> ToolUse {
+        ToolUse {
+            id: "t1".to_string(),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    #[test]
+    fn test_policy_selects_tools() {
+        let policy: IsolationPolicy =
+            toml::from_str("categories = [\"read_only\"]\ntools = [\"multi_replace\"]\ntimeout_secs = 5\n").unwrap();
+        assert_eq!(policy.timeout_secs, 5);
+        assert_eq!(policy.memory_mb, 2048);
+        assert!(policy.isolates("grep", ToolCapabilities::READ_ONLY));
+        assert!(policy.isolates("multi_replace", ToolCapabilities::WRITE));
+        assert!(!policy.isolates("edit", ToolCapabilities::WRITE));
+        assert!(!policy.isolates("get_selection", ToolCapabilities::READ_ONLY));
+        assert!(!IsolationPolicy::default().isolates("grep", ToolCapabilities::READ_ONLY));
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_in_process() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "alpha\nbeta\n").unwrap();
+        let executor = ToolExecutor::with_working_directory(dir.path());
+        let request = request_for(&executor, &tool_use("read", serde_json::json!({"file_path": "notes.txt"})));
+        let result = execute_request(&request).await;
+        assert_eq!(result.tool_use_id, "t1");
+        assert_eq!(result.content, "alpha\nbeta\n");
+
+        // The request survives the trip through JSON
+        let json = serde_json::to_string(&request).unwrap();
+        let parsed: WorkerRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.tool_use.input["file_path"], "notes.txt");
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_under_sender_policy() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "alpha\n").unwrap();
+        let mut policy: ToolPolicy = toml::from_str("disabled_tools = [\"read\"]").unwrap();
+        policy.isolation.tools.insert("read".to_string());
+        let executor = ToolExecutor::with_working_directory(dir.path()).with_policy(policy);
+        executor.set_shell_environment(Some([("PATH".to_string(), "/opt/tools".to_string())].into_iter().collect()));
+
+        let request = request_for(&executor, &tool_use("read", serde_json::json!({"file_path": "notes.txt"})));
+        let parsed: WorkerRequest = serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(parsed.shell_environment.as_ref().unwrap()["PATH"], "/opt/tools");
+        let result = execute_request(&parsed).await;
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(result.content, "Tool 'read' is disabled by policy");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_isolated_results_and_limits() {
+        let dir = TempDir::new().unwrap();
+        let executor = ToolExecutor::with_working_directory(dir.path());
+        let request = request_for(&executor, &tool_use("grep", serde_json::json!({"pattern": "x"})));
+        let shell = |script: &str| IsolationWorker {
+            program: PathBuf::from("sh"),
+            args: vec!["-c".to_string(), script.to_string()],
+        };
+        let policy = IsolationPolicy {
+            timeout_secs: 1,
+            ..Default::default()
+        };
+
+        let worker = shell(r#"cat >/dev/null; echo '{"tool_use_id":"t1","content":"found"}'"#);
+        let result = run_isolated(&worker, &policy, &request).await.unwrap();
+        assert_eq!(result.content, "found");
+
+        let err = run_isolated(&shell("sleep 5"), &policy, &request).await.unwrap_err();
+        assert_eq!(err.to_string(), "Tool 'grep' did not finish within 1 seconds and was stopped");
+
+        let err = run_isolated(&shell("kill -XCPU $$"), &policy, &request).await.unwrap_err();
+        assert!(err.to_string().contains("over its CPU limit of 60 seconds"), "{}", err);
+
+        let err = run_isolated(&shell("echo broken >&2; exit 3"), &policy, &request).await.unwrap_err();
+        assert!(err.to_string().ends_with(": broken"), "{}", err);
+    }
+}
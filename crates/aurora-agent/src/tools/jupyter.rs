@@ -0,0 +1,566 @@
+//! Jupyter kernels for notebook cells
+//!
+//! Kernels speak the Jupyter messaging protocol over ZeroMQ. Rather than
+//! implement that here, each kernel is run by a small Python bridge built
+//! on `jupyter_client`, the library Jupyter's own front ends use. The
+//! bridge starts the kernel, and exchanges JSON lines with
+//! [`KernelManager`]: execute and interrupt requests in, nbformat outputs
+//! and a completion message per execution out. Every request carries an ID
+//! that its messages echo, so output of an execution whose call was
+//! abandoned is never mistaken for the next one's.
+//!
+//! Kernels are keyed by an ID chosen by the caller (the notebook's path, or
+//! a scratch kernel per conversation), so the agent's `execute_cell` tool
+//! and the IDE's notebook panel share a notebook's kernel and its state.
+
+use aurora_core::CellOutput;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+
+/// How long a kernel may take to start
+const KERNEL_START_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// How long an interrupted execution may take to stop before the kernel
+/// is shut down
+const INTERRUPT_GRACE: Duration = Duration::from_secs(5);
+
+/// Python program that runs one kernel and relays between it and the IDE
+const BRIDGE: &str = r#"
+import json, queue, sys, threading
+
+def send(message):
+    sys.stdout.write(json.dumps(message) + "\n")
+    sys.stdout.flush()
+
+try:
+    from jupyter_client.manager import KernelManager
+except ImportError:
+    send({"type": "fatal", "message": "jupyter_client is not installed for this Python; install it with: pip install jupyter_client ipykernel"})
+    sys.exit(1)
+
+kernel_name, cwd = sys.argv[1], sys.argv[2]
+try:
+    manager = KernelManager(kernel_name=kernel_name)
+    manager.start_kernel(cwd=cwd)
+    client = manager.client()
+    client.start_channels()
+    client.wait_for_ready(timeout=60)
+except Exception as e:
+    send({"type": "fatal", "message": "Could not start kernel '%s': %s" % (kernel_name, e)})
+    sys.exit(1)
+send({"type": "ready", "language": manager.kernel_spec.language})
+
+executions, control = queue.Queue(), queue.Queue()
+def read():
+    for line in sys.stdin:
+        request = json.loads(line)
+        (executions if request["type"] == "execute" else control).put(request)
+    control.put({"type": "shutdown"})
+threading.Thread(target=read, daemon=True).start()
+
+def stop():
+    manager.shutdown_kernel(now=True)
+    sys.exit(0)
+
+OUTPUTS = ("stream", "execute_result", "display_data", "error")
+while True:
+    try:
+        request = executions.get(timeout=0.2)
+    except queue.Empty:
+        try:
+            if control.get_nowait()["type"] == "shutdown":
+                stop()
+        except queue.Empty:
+            pass
+        continue
+    id = request["id"]
+    msg_id = client.execute(request["code"], allow_stdin=False)
+    while True:
+        try:
+            message = client.get_iopub_msg(timeout=0.1)
+        except queue.Empty:
+            if not manager.is_alive():
+                send({"type": "fatal", "message": "The kernel died"})
+                sys.exit(1)
+            try:
+                pending = control.get_nowait()
+            except queue.Empty:
+                continue
+            if pending["type"] == "interrupt":
+                manager.interrupt_kernel()
+            elif pending["type"] == "shutdown":
+                stop()
+            continue
+        if message["parent_header"].get("msg_id") != msg_id:
+            continue
+        kind, content = message["msg_type"], message["content"]
+        if kind in OUTPUTS:
+            output = {key: value for key, value in content.items() if key != "transient"}
+            output["output_type"] = kind
+            send({"type": "output", "id": id, "output": output})
+        elif kind == "clear_output":
+            send({"type": "clear", "id": id})
+        elif kind == "status" and content["execution_state"] == "idle":
+            break
+    status, count = "ok", None
+    while True:
+        try:
+            reply = client.get_shell_msg(timeout=10)
+        except queue.Empty:
+            break
+        if reply["parent_header"].get("msg_id") == msg_id:
+            status, count = reply["content"]["status"], reply["content"].get("execution_count")
+            break
+    send({"type": "done", "id": id, "status": status, "execution_count": count})
+"#;
+
+/// Errors in running kernels
+#[derive(Error, Debug)]
+pub enum KernelError {
+    /// IO error talking to the bridge
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The kernel could not be started
+    #[error("{0}")]
+    Start(String),
+
+    /// The kernel or its bridge stopped
+    #[error("The kernel stopped: {0}")]
+    Died(String),
+
+    /// No kernel has the ID
+    #[error("No kernel '{0}' is running")]
+    Unknown(String),
+
+    /// The execution did not finish in time and could not be interrupted,
+    /// so the kernel was shut down
+    #[error("Execution did not finish within {0}s and could not be interrupted; the kernel was shut down")]
+    Timeout(u64),
+}
+
+pub type Result<T> = std::result::Result<T, KernelError>;
+
+/// How to start a kernel's bridge
+#[derive(Debug, Clone)]
+pub struct KernelLaunch {
+    /// Python with `jupyter_client` installed
+    pub python: PathBuf,
+    /// Directory the kernel runs in
+    pub cwd: PathBuf,
+    /// Variables set on top of the inherited environment
+    pub env: Vec<(String, String)>,
+}
+
+/// A running kernel, as listed for the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KernelInfo {
+    pub id: String,
+    /// Kernelspec name, e.g. `python3`
+    pub kernel_name: String,
+    /// Language the kernel reports
+    pub language: String,
+    pub started: SystemTime,
+    /// Executions run so far
+    pub executions: usize,
+}
+
+/// Result of running one cell
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CellExecution {
+    pub outputs: Vec<CellOutput>,
+    pub execution_count: Option<u32>,
+    /// `ok`, `error`, or `aborted`
+    pub status: String,
+    /// Whether it was interrupted after running too long
+    pub interrupted: bool,
+}
+
+/// A message from the bridge
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum BridgeMessage {
+    Ready { language: String },
+    Output { id: u64, output: CellOutput },
+    Clear { id: u64 },
+    Done { id: u64, status: String, execution_count: Option<u32> },
+    Fatal { message: String },
+}
+
+/// A request to the bridge
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum BridgeRequest<'a> {
+    Execute { id: u64, code: &'a str },
+    Interrupt,
+}
+
+struct Kernel {
+    info: Mutex<KernelInfo>,
+    /// Written to outside executions too, to interrupt one
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    /// Held for the whole of an execution, so executions take turns
+    stdout: tokio::sync::Mutex<Lines<BufReader<ChildStdout>>>,
+    /// The bridge, which shuts the kernel down when its stdin closes
+    _child: Child,
+}
+
+impl Kernel {
+    async fn send(&self, request: &BridgeRequest<'_>) -> Result<()> {
+        let mut line = serde_json::to_string(request).map_err(std::io::Error::other)?;
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+}
+
+/// Read the bridge's next message, skipping lines that are not protocol
+/// messages (e.g. warnings a library prints)
+async fn next_message(stdout: &mut Lines<BufReader<ChildStdout>>) -> Result<BridgeMessage> {
+    loop {
+        let line = stdout
+            .next_line()
+            .await?
+            .ok_or_else(|| KernelError::Died("the bridge process exited".to_string()))?;
+        match serde_json::from_str::<BridgeMessage>(&line) {
+            Ok(message) => return Ok(message),
+            Err(_) => tracing::debug!("Ignoring kernel bridge output: {}", line),
+        }
+    }
+}
+
+/// Kernels running for notebooks and scratch cells
+pub struct KernelManager {
+    kernels: Mutex<HashMap<String, Arc<Kernel>>>,
+    next_execution: AtomicU64,
+    /// Python program the bridge runs; replaceable for tests
+    bridge: String,
+}
+
+impl KernelManager {
+    pub fn new() -> Self {
+        Self {
+            kernels: Mutex::new(HashMap::new()),
+            next_execution: AtomicU64::new(0),
+            bridge: BRIDGE.to_string(),
+        }
+    }
+
+    /// Start kernel `kernel_name` as `id`, unless one with that ID is running
+    pub async fn start(&self, id: &str, kernel_name: &str, launch: &KernelLaunch) -> Result<KernelInfo> {
+        if let Some(kernel) = self.get(id) {
+            return Ok(kernel.info.lock().unwrap_or_else(PoisonError::into_inner).clone());
+        }
+        let mut child = tokio::process::Command::new(&launch.python)
+            .arg("-u")
+            .arg("-c")
+            .arg(&self.bridge)
+            .arg(kernel_name)
+            .arg(&launch.cwd)
+            .current_dir(&launch.cwd)
+            .envs(launch.env.iter().cloned())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| std::io::Error::other("bridge has no stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| std::io::Error::other("bridge has no stdout"))?;
+        let mut stdout = BufReader::new(stdout).lines();
+
+        let ready = tokio::time::timeout(KERNEL_START_TIMEOUT, next_message(&mut stdout))
+            .await
+            .map_err(|_| {
+                KernelError::Start(format!(
+                    "Kernel '{}' did not start within {}s",
+                    kernel_name,
+                    KERNEL_START_TIMEOUT.as_secs()
+                ))
+            })?;
+        let language = match ready {
+            Ok(BridgeMessage::Ready { language }) => language,
+            Ok(BridgeMessage::Fatal { message }) => return Err(KernelError::Start(message)),
+            Ok(_) => return Err(KernelError::Start("Unexpected message from the kernel bridge".to_string())),
+            Err(KernelError::Died(_)) => {
+                return Err(KernelError::Start(format!(
+                    "The kernel bridge exited before kernel '{}' started",
+                    kernel_name
+                )))
+            }
+            Err(e) => return Err(e),
+        };
+
+        let info = KernelInfo {
+            id: id.to_string(),
+            kernel_name: kernel_name.to_string(),
+            language,
+            started: SystemTime::now(),
+            executions: 0,
+        };
+        let kernel = Arc::new(Kernel {
+            info: Mutex::new(info.clone()),
+            stdin: tokio::sync::Mutex::new(stdin),
+            stdout: tokio::sync::Mutex::new(stdout),
+            _child: child,
+        });
+        // Two callers starting the same kernel at once keep the first
+        let mut kernels = self.kernels.lock().unwrap_or_else(PoisonError::into_inner);
+        let kernel = kernels.entry(id.to_string()).or_insert(kernel);
+        let info = kernel.info.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        Ok(info)
+    }
+
+    /// Run `code` in kernel `id`, passing each output to `on_output` as it
+    /// arrives
+    ///
+    /// An execution still running after `timeout` is interrupted; if it
+    /// does not stop shortly after, the kernel is shut down.
+    pub async fn execute(
+        &self,
+        id: &str,
+        code: &str,
+        timeout: Duration,
+        mut on_output: impl FnMut(&CellOutput),
+    ) -> Result<CellExecution> {
+        let kernel = self.get(id).ok_or_else(|| KernelError::Unknown(id.to_string()))?;
+        let mut stdout = kernel.stdout.lock().await;
+        let execution = self.next_execution.fetch_add(1, Ordering::Relaxed);
+        kernel.send(&BridgeRequest::Execute { id: execution, code }).await?;
+
+        let mut outputs = Vec::new();
+        let finish = self.finish(&mut stdout, execution, &mut outputs, &mut on_output);
+        let finished = match tokio::time::timeout(timeout, finish).await {
+            Ok(finished) => finished.map(|done| (done, false)),
+            Err(_) => match kernel.send(&BridgeRequest::Interrupt).await {
+                Ok(()) => {
+                    let finish = self.finish(&mut stdout, execution, &mut outputs, &mut on_output);
+                    match tokio::time::timeout(INTERRUPT_GRACE, finish).await {
+                        Ok(finished) => finished.map(|done| (done, true)),
+                        Err(_) => Err(KernelError::Timeout(timeout.as_secs())),
+                    }
+                }
+                Err(e) => Err(e),
+            },
+        };
+        drop(stdout);
+        match finished {
+            Ok((done, interrupted)) => Ok(self.record(&kernel, done, outputs, interrupted)),
+            Err(e) => {
+                self.remove(id);
+                Err(e)
+            }
+        }
+    }
+
+    /// Read messages until execution `execution` is done
+    async fn finish(
+        &self,
+        stdout: &mut Lines<BufReader<ChildStdout>>,
+        execution: u64,
+        outputs: &mut Vec<CellOutput>,
+        on_output: &mut impl FnMut(&CellOutput),
+    ) -> Result<(String, Option<u32>)> {
+        loop {
+            let message = next_message(stdout).await?;
+            if let Some(done) = Self::handle(message, execution, outputs, on_output)? {
+                return Ok(done);
+            }
+        }
+    }
+
+    /// Apply one bridge message to execution `execution`, returning its
+    /// status and count once it is done
+    fn handle(
+        message: BridgeMessage,
+        execution: u64,
+        outputs: &mut Vec<CellOutput>,
+        on_output: &mut impl FnMut(&CellOutput),
+    ) -> Result<Option<(String, Option<u32>)>> {
+        match message {
+            BridgeMessage::Output { id, output } if id == execution => {
+                on_output(&output);
+                outputs.push(output);
+            }
+            BridgeMessage::Clear { id } if id == execution => outputs.clear(),
+            BridgeMessage::Done {
+                id,
+                status,
+                execution_count,
+            } if id == execution => return Ok(Some((status, execution_count))),
+            BridgeMessage::Fatal { message } => return Err(KernelError::Died(message)),
+            // Left over from an execution whose caller gave up on it
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn record(
+        &self,
+        kernel: &Kernel,
+        (status, execution_count): (String, Option<u32>),
+        outputs: Vec<CellOutput>,
+        interrupted: bool,
+    ) -> CellExecution {
+        kernel.info.lock().unwrap_or_else(PoisonError::into_inner).executions += 1;
+        CellExecution {
+            outputs,
+            execution_count,
+            status,
+            interrupted,
+        }
+    }
+
+    /// Interrupt what kernel `id` is running, as the notebook panel's stop
+    /// button does
+    pub async fn interrupt(&self, id: &str) -> Result<()> {
+        let kernel = self.get(id).ok_or_else(|| KernelError::Unknown(id.to_string()))?;
+        kernel.send(&BridgeRequest::Interrupt).await
+    }
+
+    /// Shut kernel `id` down, losing its state; false if it was not running
+    pub fn shutdown(&self, id: &str) -> bool {
+        self.remove(id)
+    }
+
+    /// Shut every kernel down, returning how many there were
+    pub fn shutdown_all(&self) -> usize {
+        let mut kernels = self.kernels.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = kernels.len();
+        kernels.clear();
+        count
+    }
+
+    /// Kernels running, oldest first
+    pub fn list(&self) -> Vec<KernelInfo> {
+        let kernels = self.kernels.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut list: Vec<KernelInfo> = kernels
+            .values()
+            .map(|kernel| kernel.info.lock().unwrap_or_else(PoisonError::into_inner).clone())
+            .collect();
+        list.sort_by_key(|info| info.started);
+        list
+    }
+
+    fn get(&self, id: &str) -> Option<Arc<Kernel>> {
+        self.kernels.lock().unwrap_or_else(PoisonError::into_inner).get(id).cloned()
+    }
+
+    /// Drop kernel `id`; closing the bridge's stdin makes it shut the
+    /// kernel down, and dropping the last handle kills the bridge
+    fn remove(&self, id: &str) -> bool {
+        self.kernels.lock().unwrap_or_else(PoisonError::into_inner).remove(id).is_some()
+    }
+}
+
+impl Default for KernelManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stands in for the bridge: answers `x` with a stream output, `sleep`
+    /// by waiting for an interrupt, and `die` by exiting
+    const FAKE_BRIDGE: &str = r#"
+import json, sys
+print("a warning the library printed", flush=True)
+def send(message):
+    print(json.dumps(message), flush=True)
+send({"type": "ready", "language": "python"})
+count = 0
+for line in sys.stdin:
+    request = json.loads(line)
+    if request["type"] == "interrupt":
+        send({"type": "output", "id": sleeping, "output": {"output_type": "error", "ename": "KeyboardInterrupt", "evalue": "", "traceback": []}})
+        send({"type": "done", "id": sleeping, "status": "error", "execution_count": count})
+        continue
+    count += 1
+    if request["code"] == "sleep":
+        sleeping = request["id"]
+        continue
+    if request["code"] == "die":
+        sys.exit(1)
+    send({"type": "clear", "id": request["id"]})
+    send({"type": "output", "id": request["id"], "output": {"output_type": "stream", "name": "stdout", "text": request["code"] + "\n"}})
+    send({"type": "done", "id": request["id"], "status": "ok", "execution_count": count})
+"#;
+
+    fn fake_manager() -> Option<(KernelManager, KernelLaunch, tempfile::TempDir)> {
+        let python = aurora_core::shell::find_on_path("python")?;
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = KernelManager {
+            bridge: FAKE_BRIDGE.to_string(),
+            ..KernelManager::new()
+        };
+        let launch = KernelLaunch {
+            python,
+            cwd: dir.path().to_path_buf(),
+            env: Vec::new(),
+        };
+        Some((manager, launch, dir))
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let Some((manager, launch, _dir)) = fake_manager() else {
+            return;
+        };
+        let info = manager.start("nb", "python3", &launch).await.unwrap();
+        assert_eq!(info.language, "python");
+
+        let mut streamed = Vec::new();
+        let execution = manager
+            .execute("nb", "x", Duration::from_secs(10), |output| streamed.push(output.clone()))
+            .await
+            .unwrap();
+        assert_eq!(execution.status, "ok");
+        assert_eq!(execution.execution_count, Some(1));
+        assert_eq!(execution.outputs, streamed);
+        assert_eq!(execution.outputs[0].text().unwrap(), "x\n");
+        assert_eq!(manager.list()[0].executions, 1);
+
+        assert!(matches!(
+            manager.execute("other", "x", Duration::from_secs(1), |_| {}).await,
+            Err(KernelError::Unknown(_))
+        ));
+        assert!(manager.shutdown("nb"));
+        assert!(manager.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_interrupts() {
+        let Some((manager, launch, _dir)) = fake_manager() else {
+            return;
+        };
+        manager.start("nb", "python3", &launch).await.unwrap();
+        let execution = manager
+            .execute("nb", "sleep", Duration::from_millis(300), |_| {})
+            .await
+            .unwrap();
+        assert!(execution.interrupted);
+        assert_eq!(execution.status, "error");
+
+        // The kernel keeps running after an interrupt
+        let next = manager.execute("nb", "y", Duration::from_secs(10), |_| {}).await.unwrap();
+        assert_eq!(next.outputs[0].text().unwrap(), "y\n");
+
+        assert!(matches!(
+            manager.execute("nb", "die", Duration::from_secs(10), |_| {}).await,
+            Err(KernelError::Died(_))
+        ));
+        assert!(manager.list().is_empty(), "a dead kernel is dropped");
+    }
+}
@@ -0,0 +1,312 @@
+//! Per-tool execution metrics
+//!
+//! Every call through a [`ToolExecutor`](super::ToolExecutor) is recorded with
+//! its duration, outcome, and output size, aggregated globally and per
+//! session. Calls slower than a threshold are logged and kept in a short list
+//! so users can see which tools dominate latency.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// Calls slower than this are reported by default
+pub const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// Number of slow calls remembered
+const MAX_SLOW_CALLS: usize = 50;
+
+/// Aggregated statistics for one tool (or all tools)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ToolStats {
+    /// Number of calls
+    pub calls: u64,
+    /// Calls that returned an error
+    pub failures: u64,
+    /// Total time spent in the tool
+    pub total_ms: u64,
+    /// Slowest single call
+    pub max_ms: u64,
+    /// Total size of the output returned to the model
+    pub output_bytes: u64,
+    /// Calls slower than the threshold
+    pub slow_calls: u64,
+}
+
+impl ToolStats {
+    /// Average call duration
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.calls).unwrap_or(0)
+    }
+
+    fn add(&mut self, duration_ms: u64, success: bool, output_bytes: u64, slow: bool) {
+        self.calls += 1;
+        self.failures += u64::from(!success);
+        self.total_ms += duration_ms;
+        self.max_ms = self.max_ms.max(duration_ms);
+        self.output_bytes += output_bytes;
+        self.slow_calls += u64::from(slow);
+    }
+
+    fn merge(&mut self, other: &ToolStats) {
+        self.calls += other.calls;
+        self.failures += other.failures;
+        self.total_ms += other.total_ms;
+        self.max_ms = self.max_ms.max(other.max_ms);
+        self.output_bytes += other.output_bytes;
+        self.slow_calls += other.slow_calls;
+    }
+}
+
+/// Statistics for one tool in a report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolStatsEntry {
+    /// Tool name
+    pub name: String,
+    /// Statistics for the tool
+    #[serde(flatten)]
+    pub stats: ToolStats,
+}
+
+/// A call that took longer than the slow threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlowCall {
+    /// Tool name
+    pub tool: String,
+    /// Session the call belonged to, if known
+    pub session: Option<String>,
+    /// How long the call took
+    pub duration_ms: u64,
+    /// Whether the call succeeded
+    pub success: bool,
+    /// When the call finished
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Tool metrics for one session or for all sessions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolMetricsReport {
+    /// Per-tool statistics, most total time first
+    pub tools: Vec<ToolStatsEntry>,
+    /// All tools combined
+    pub total: ToolStats,
+    /// Recent slow calls, newest last
+    pub slow_calls: Vec<SlowCall>,
+    /// Duration above which a call counts as slow
+    pub slow_threshold_ms: u64,
+}
+
+impl ToolMetricsReport {
+    /// Format the report as a table for display
+    pub fn format(&self) -> String {
+        if self.tools.is_empty() {
+            return "No tool calls recorded".to_string();
+        }
+
+        let mut output = format!(
+            "{} tool calls, {} failed, {} ms total\n\n",
+            self.total.calls, self.total.failures, self.total.total_ms
+        );
+        output.push_str(&format!(
+            "{:<20} {:>6} {:>6} {:>10} {:>8} {:>8} {:>6} {:>10}\n",
+            "tool", "calls", "failed", "total ms", "avg ms", "max ms", "time", "output"
+        ));
+        for entry in &self.tools {
+            let share = entry.stats.total_ms as f64 * 100.0 / self.total.total_ms.max(1) as f64;
+            output.push_str(&format!(
+                "{:<20} {:>6} {:>6} {:>10} {:>8} {:>8} {:>5.1}% {:>10}\n",
+                entry.name,
+                entry.stats.calls,
+                entry.stats.failures,
+                entry.stats.total_ms,
+                entry.stats.average_ms(),
+                entry.stats.max_ms,
+                share,
+                entry.stats.output_bytes
+            ));
+        }
+
+        if !self.slow_calls.is_empty() {
+            output.push_str(&format!("\nCalls over {} ms:\n", self.slow_threshold_ms));
+            for call in &self.slow_calls {
+                output.push_str(&format!(
+                    "  {} {} {} ms{}\n",
+                    call.finished_at.format("%Y-%m-%d %H:%M:%S"),
+                    call.tool,
+                    call.duration_ms,
+                    if call.success { "" } else { " (failed)" }
+                ));
+            }
+        }
+        output
+    }
+}
+
+#[derive(Default)]
+struct MetricsData {
+    global: HashMap<String, ToolStats>,
+    sessions: HashMap<String, HashMap<String, ToolStats>>,
+    slow_calls: VecDeque<SlowCall>,
+}
+
+/// Collects tool call metrics, globally and per session
+pub struct ToolMetrics {
+    data: Mutex<MetricsData>,
+    slow_threshold: Duration,
+}
+
+impl Default for ToolMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolMetrics {
+    /// Create an empty collector with the default slow threshold
+    pub fn new() -> Self {
+        Self {
+            data: Mutex::new(MetricsData::default()),
+            slow_threshold: DEFAULT_SLOW_THRESHOLD,
+        }
+    }
+
+    /// Report calls slower than `threshold`
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = threshold;
+        self
+    }
+
+    /// Record one finished call
+    pub fn record(
+        &self,
+        session: Option<&str>,
+        tool: &str,
+        duration: Duration,
+        success: bool,
+        output_bytes: usize,
+    ) {
+        let duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        let output_bytes = output_bytes as u64;
+        let slow = duration >= self.slow_threshold;
+        if slow {
+            tracing::warn!("Slow tool call: {} took {} ms", tool, duration_ms);
+        }
+
+        let mut data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        data.global
+            .entry(tool.to_string())
+            .or_default()
+            .add(duration_ms, success, output_bytes, slow);
+        if let Some(session) = session {
+            data.sessions
+                .entry(session.to_string())
+                .or_default()
+                .entry(tool.to_string())
+                .or_default()
+                .add(duration_ms, success, output_bytes, slow);
+        }
+        if slow {
+            if data.slow_calls.len() == MAX_SLOW_CALLS {
+                data.slow_calls.pop_front();
+            }
+            data.slow_calls.push_back(SlowCall {
+                tool: tool.to_string(),
+                session: session.map(str::to_string),
+                duration_ms,
+                success,
+                finished_at: Utc::now(),
+            });
+        }
+    }
+
+    /// Metrics for one session, or for every call when `session` is `None`
+    pub fn report(&self, session: Option<&str>) -> ToolMetricsReport {
+        let data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        let empty = HashMap::new();
+        let stats = match session {
+            Some(session) => data.sessions.get(session).unwrap_or(&empty),
+            None => &data.global,
+        };
+
+        let mut tools: Vec<ToolStatsEntry> = stats
+            .iter()
+            .map(|(name, stats)| ToolStatsEntry {
+                name: name.clone(),
+                stats: stats.clone(),
+            })
+            .collect();
+        tools.sort_by(|a, b| {
+            b.stats
+                .total_ms
+                .cmp(&a.stats.total_ms)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let mut total = ToolStats::default();
+        for entry in &tools {
+            total.merge(&entry.stats);
+        }
+
+        ToolMetricsReport {
+            tools,
+            total,
+            slow_calls: data
+                .slow_calls
+                .iter()
+                .filter(|call| session.is_none() || call.session.as_deref() == session)
+                .cloned()
+                .collect(),
+            slow_threshold_ms: u64::try_from(self.slow_threshold.as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Forget a session's metrics; its calls stay in the global totals
+    pub fn clear_session(&self, session: &str) {
+        let mut data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        data.sessions.remove(session);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_aggregation() {
+        let metrics = ToolMetrics::new().with_slow_threshold(Duration::from_millis(100));
+        metrics.record(Some("a"), "grep", Duration::from_millis(300), true, 2000);
+        metrics.record(Some("a"), "grep", Duration::from_millis(50), false, 10);
+        metrics.record(Some("b"), "read", Duration::from_millis(20), true, 500);
+        metrics.record(None, "read", Duration::from_millis(10), true, 100);
+
+        let report = metrics.report(None);
+        assert_eq!(report.tools[0].name, "grep");
+        assert_eq!(
+            report.tools[0].stats,
+            ToolStats {
+                calls: 2,
+                failures: 1,
+                total_ms: 350,
+                max_ms: 300,
+                output_bytes: 2010,
+                slow_calls: 1,
+            }
+        );
+        assert_eq!(report.tools[0].stats.average_ms(), 175);
+        assert_eq!(report.total.calls, 4);
+        assert_eq!(report.total.total_ms, 380);
+        assert_eq!(report.slow_calls.len(), 1);
+        assert_eq!(report.slow_calls[0].session.as_deref(), Some("a"));
+        assert!(report.format().contains("grep"));
+
+        let session = metrics.report(Some("b"));
+        assert_eq!(session.tools.len(), 1);
+        assert_eq!(session.total.output_bytes, 500);
+        assert!(session.slow_calls.is_empty());
+
+        metrics.clear_session("a");
+        assert!(metrics.report(Some("a")).tools.is_empty());
+        assert_eq!(metrics.report(Some("a")).format(), "No tool calls recorded");
+        assert_eq!(metrics.report(None).total.calls, 4);
+    }
+}
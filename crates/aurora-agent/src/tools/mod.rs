@@ -3,9 +3,37 @@
 //! This module defines the tools that Claude can use to interact with the IDE,
 //! including their schemas and execution logic.
 
+mod auto_approve;
+mod cache;
+mod context;
 mod executor;
+mod isolation;
+mod jupyter;
+mod metrics;
+mod policy;
+mod registry;
+mod repl;
+mod retry;
+mod ripgrep;
+mod schema;
 
-pub use executor::{ToolExecutor, ToolError};
+pub use auto_approve::AutoApproveGuard;
+pub use cache::ToolCache;
+pub use context::{CallOptions, CancellationToken, ToolContext};
+pub use executor::{
+    RunningTool, ToolExecutor, ToolError, ToolOutputChunk, ToolOutputEvent, ToolOutputSender,
+};
+pub use isolation::{
+    is_worker_invocation, run_worker, IsolationPolicy, IsolationWorker, ToolCategory, WorkerRequest,
+    IN_PROCESS_TOOLS, WORKER_ARG,
+};
+pub use jupyter::{CellExecution, KernelError, KernelInfo, KernelManager};
+pub use metrics::{SlowCall, ToolMetrics, ToolMetricsReport, ToolStats, ToolStatsEntry};
+pub use policy::{AutoApproveLimits, PolicyError, PreviewFirst, ToolPolicy, POLICY_FILE};
+pub use registry::{AgentTool, Capability, ToolCapabilities, ToolFuture, ToolRegistry};
+pub(crate) use registry::builtin_capabilities;
+pub use repl::{ReplEntry, ReplLanguage, ReplSessionInfo, ReplUpdate};
+pub use schema::{tool_schema, validate, SchemaViolation};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -32,6 +60,29 @@ pub struct ToolUse {
     pub input: JsonValue,
 }
 
+/// Tools that take a `dry_run` flag to preview their changes
+pub const DRY_RUN_TOOLS: &[&str] = &[
+    "write",
+    "edit",
+    "multi_edit",
+    "copy",
+    "delete",
+    "move",
+    "multi_replace",
+    "apply_quick_fix",
+];
+
+impl ToolUse {
+    /// Whether the call only previews its changes
+    ///
+    /// `multi_replace` previews unless told otherwise; the other tools in
+    /// [`DRY_RUN_TOOLS`] change files unless `dry_run` is set.
+    pub fn is_dry_run(&self) -> bool {
+        let default = self.name == "multi_replace";
+        DRY_RUN_TOOLS.contains(&self.name.as_str()) && self.input["dry_run"].as_bool().unwrap_or(default)
+    }
+}
+
 /// Result of executing a tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
@@ -42,6 +93,9 @@ pub struct ToolResult {
     /// Whether the tool execution failed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// Structured data for the IDE (e.g. build diagnostics); not sent to the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<JsonValue>,
 }
 
 impl ToolResult {
@@ -51,6 +105,7 @@ impl ToolResult {
             tool_use_id,
             content,
             is_error: None,
+            data: None,
         }
     }
 
@@ -60,8 +115,15 @@ impl ToolResult {
             tool_use_id,
             content: error_message,
             is_error: Some(true),
+            data: None,
         }
     }
+
+    /// Attach structured data for the IDE
+    pub fn with_data(mut self, data: JsonValue) -> Self {
+        self.data = Some(data);
+        self
+    }
 }
 
 /// Create the Read tool definition
@@ -86,7 +148,7 @@ pub fn read_tool() -> Tool {
 pub fn write_tool() -> Tool {
     Tool {
         name: "write".to_string(),
-        description: "Write content to a file, creating it if it doesn't exist or overwriting if it does.".to_string(),
+        description: "Write content to a file, creating it if it doesn't exist or overwriting if it does. Indentation, line endings, trailing whitespace, and the final newline follow the project's .editorconfig.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -97,6 +159,14 @@ pub fn write_tool() -> Tool {
                 "content": {
                     "type": "string",
                     "description": "The content to write to the file"
+                },
+                "template": {
+                    "type": "string",
+                    "description": "Name of a project file template from .AuroraHeart/file_templates/ (e.g. a license header, module doc comment, or test scaffold) to wrap the content in"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, return the diff against the current file without writing it (default: false)"
                 }
             },
             "required": ["file_path", "content"]
@@ -123,6 +193,10 @@ pub fn edit_tool() -> Tool {
                 "new_string": {
                     "type": "string",
                     "description": "The string to replace it with"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, return the diff without changing the file (default: false)"
                 }
             },
             "required": ["file_path", "old_string", "new_string"]
@@ -159,6 +233,10 @@ pub fn multi_edit_tool() -> Tool {
                         },
                         "required": ["file_path", "old_string", "new_string"]
                     }
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, return the diffs without changing any file (default: false)"
                 }
             },
             "required": ["edits"]
@@ -184,11 +262,35 @@ pub fn bash_tool() -> Tool {
     }
 }
 
+/// Create the Bash tool definition for commands run in `shell`
+///
+/// The description tells the model which shell is active, so it writes
+/// PowerShell rather than POSIX syntax when needed.
+pub fn bash_tool_for(shell: &aurora_core::Shell) -> Tool {
+    let kind = shell.kind();
+    let note = if kind == aurora_core::ShellKind::Powershell {
+        " Use PowerShell syntax (e.g. Get-ChildItem, $env:NAME). `&&`, `||`, `export NAME=value`, and /dev/null are translated automatically."
+    } else if kind.is_powershell() {
+        " Use PowerShell syntax (e.g. Get-ChildItem, $env:NAME); `&&` and `||` work as in POSIX shells."
+    } else if kind == aurora_core::ShellKind::Cmd {
+        " Use cmd.exe syntax (e.g. dir, set NAME=value)."
+    } else {
+        ""
+    };
+    let mut tool = bash_tool();
+    tool.description = format!(
+        "Execute a shell command and return its output. Commands run in {}.{}",
+        kind.display_name(),
+        note
+    );
+    tool
+}
+
 /// Create the Grep tool definition
 pub fn grep_tool() -> Tool {
     Tool {
         name: "grep".to_string(),
-        description: "Search for a pattern in files using regular expressions. Returns matching lines with file names and line numbers.".to_string(),
+        description: "Search for a pattern in files using regular expressions. Returns matching lines with line numbers, grouped by file, with the files with the densest and most recent matches first.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -211,6 +313,27 @@ pub fn grep_tool() -> Tool {
                 "max_results": {
                     "type": "integer",
                     "description": "Maximum number of results to return (default: 100)"
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "Lines of context before and after each match, like grep -C (default: 0)"
+                },
+                "before_context": {
+                    "type": "integer",
+                    "description": "Lines of context before each match, like grep -B (overrides context)"
+                },
+                "after_context": {
+                    "type": "integer",
+                    "description": "Lines of context after each match, like grep -A (overrides context)"
+                },
+                "output_mode": {
+                    "type": "string",
+                    "enum": ["content", "files_with_matches"],
+                    "description": "'content' shows matching lines (default); 'files_with_matches' lists only the matching files and their match counts"
+                },
+                "follow_symlinks": {
+                    "type": "boolean",
+                    "description": "Follow symbolic links that stay inside the workspace; links are skipped otherwise (default: false)"
                 }
             },
             "required": ["pattern"]
@@ -222,7 +345,7 @@ pub fn grep_tool() -> Tool {
 pub fn glob_tool() -> Tool {
     Tool {
         name: "glob".to_string(),
-        description: "Find files matching a glob pattern. Useful for discovering files by name or extension.".to_string(),
+        description: "Find files matching a glob pattern. Useful for discovering files by name or extension. Each result includes the file size and modification time.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -237,6 +360,15 @@ pub fn glob_tool() -> Tool {
                 "max_results": {
                     "type": "integer",
                     "description": "Maximum number of results to return (default: 100)"
+                },
+                "sort": {
+                    "type": "string",
+                    "enum": ["name", "mtime", "size"],
+                    "description": "Result order: 'name' (default), 'mtime' (most recently modified first), or 'size' (largest first)"
+                },
+                "follow_symlinks": {
+                    "type": "boolean",
+                    "description": "Follow symbolic links that stay inside the workspace; links are skipped otherwise (default: false)"
                 }
             },
             "required": ["pattern"]
@@ -244,11 +376,43 @@ pub fn glob_tool() -> Tool {
     }
 }
 
+/// Create the Symbols tool definition
+pub fn symbols_tool() -> Tool {
+    Tool {
+        name: "symbols".to_string(),
+        description: "Find where functions, methods, types, traits, classes, and constants are defined anywhere in the project by fuzzy name (e.g. 'parsecfg' finds parse_config), or list the definitions in one file. Backed by a symbol index stored in .AuroraHeart/index/ that only re-reads changed files, so it is faster and more precise than grep for locating definitions.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Symbol name to search for; characters must appear in order but need not be adjacent"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "List the definitions in this file instead of searching"
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["function", "method", "struct", "enum", "trait", "interface", "class", "module", "type", "constant", "macro"],
+                    "description": "Only return symbols of this kind"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Maximum number of search results (default: 20)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
 /// Create the List Directory tool definition
 pub fn list_directory_tool() -> Tool {
     Tool {
         name: "list_directory".to_string(),
-        description: "List directory contents with file metadata including size, modified time, and type.".to_string(),
+        description: "List directory contents as a tree with file metadata including size and modified time. Directories beyond the depth limit show how many entries they contain.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -263,6 +427,14 @@ pub fn list_directory_tool() -> Tool {
                 "recursive": {
                     "type": "boolean",
                     "description": "Whether to list subdirectories recursively (default: false)"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "description": "How many directory levels to list; 1 lists only the directory itself (default: 1, or unlimited when recursive)"
+                },
+                "follow_symlinks": {
+                    "type": "boolean",
+                    "description": "Follow symbolic links that stay inside the workspace; links are skipped otherwise (default: false)"
                 }
             },
             "required": []
@@ -274,7 +446,7 @@ pub fn list_directory_tool() -> Tool {
 pub fn multi_replace_tool() -> Tool {
     Tool {
         name: "multi_replace".to_string(),
-        description: "Search and replace a pattern across multiple files with preview support.".to_string(),
+        description: "Search and replace a pattern across multiple files with preview support. Lockfiles, minified bundles, vendored directories, generated files, and files with unresolved merge conflicts are skipped and listed in the result.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -298,6 +470,14 @@ pub fn multi_replace_tool() -> Tool {
                     "type": "boolean",
                     "description": "Whether to perform case-insensitive search (default: false)"
                 },
+                "match_whole_word": {
+                    "type": "boolean",
+                    "description": "Only match whole words, so 'foo' leaves 'food' and 'foo_bar' alone (default: false)"
+                },
+                "preserve_case": {
+                    "type": "boolean",
+                    "description": "Replace each match in its own case style. An identifier pattern like 'fooBar' also matches 'FooBar', 'foo_bar', and 'FOO_BAR'; replacing with 'bazQux' gives 'BazQux', 'baz_qux', and 'BAZ_QUX' (default: false)"
+                },
                 "dry_run": {
                     "type": "boolean",
                     "description": "If true, preview changes without modifying files (default: true)"
@@ -305,6 +485,10 @@ pub fn multi_replace_tool() -> Tool {
                 "max_files": {
                     "type": "integer",
                     "description": "Maximum number of files to process (default: 50)"
+                },
+                "follow_symlinks": {
+                    "type": "boolean",
+                    "description": "Follow symbolic links that stay inside the workspace; links are skipped otherwise (default: false)"
                 }
             },
             "required": ["pattern", "replacement"]
@@ -316,7 +500,7 @@ pub fn multi_replace_tool() -> Tool {
 pub fn syntax_check_tool() -> Tool {
     Tool {
         name: "syntax_check".to_string(),
-        description: "Check syntax validity of code files using language-specific validators. Supports Rust, JavaScript, TypeScript, Python, and more.".to_string(),
+        description: "Check syntax validity of code files using language-specific validators. Supports Rust, JavaScript, TypeScript, Python, Go, and C/C++, plus checkers configured under [syntax_checkers.<language>]. When a language's compiler is not installed, or for Java, Ruby, and JSON, the file is parsed with a bundled tree-sitter grammar to find syntax errors; for other languages such as Kotlin or Lua only brackets, strings, and comments are checked.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -326,7 +510,7 @@ pub fn syntax_check_tool() -> Tool {
                 },
                 "language": {
                     "type": "string",
-                    "description": "Optional language override (rust, javascript, typescript, python, etc.). If not specified, detected from file extension."
+                    "description": "Optional language override (rust, javascript, typescript, python, etc., or a configured checker's name). If not specified, detected from file extension."
                 },
                 "strict": {
                     "type": "boolean",
@@ -342,7 +526,7 @@ pub fn syntax_check_tool() -> Tool {
 pub fn code_format_tool() -> Tool {
     Tool {
         name: "code_format".to_string(),
-        description: "Format code files according to language-specific style guidelines. Supports Rust (rustfmt), JavaScript/TypeScript (prettier), Python (black), Go (gofmt), and C/C++ (clang-format).".to_string(),
+        description: "Format code files according to language-specific style guidelines. Supports Rust (rustfmt), JavaScript/TypeScript (prettier), Python (black), Go (gofmt), and C/C++ (clang-format). Formatters configured under [formatters.<language>] (e.g. zig fmt, stylua) are used for their extensions ahead of the built-ins. Other files are formatted with the project's .editorconfig (indentation, line endings, trailing whitespace, final newline).".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -352,7 +536,7 @@ pub fn code_format_tool() -> Tool {
                 },
                 "language": {
                     "type": "string",
-                    "description": "Optional language override (rust, javascript, typescript, python, go, c, cpp). If not specified, detected from file extension."
+                    "description": "Optional language override (rust, javascript, typescript, python, go, c, cpp, or a configured formatter's name). If not specified, detected from file extension."
                 },
                 "check_only": {
                     "type": "boolean",
@@ -368,7 +552,7 @@ pub fn code_format_tool() -> Tool {
 pub fn code_analysis_tool() -> Tool {
     Tool {
         name: "code_analysis".to_string(),
-        description: "Analyze code quality, complexity, and security vulnerabilities. Supports Rust (clippy, cargo-audit), JavaScript/TypeScript (eslint, npm audit), Python (pylint, bandit), and more.".to_string(),
+        description: "Analyze code quality, complexity, and security vulnerabilities. Supports Rust (clippy, cargo-audit), JavaScript/TypeScript (eslint, npm audit), Python (pylint, bandit), and more. The 'license' analysis checks source files for the required license header and dependencies (Cargo, npm) for disallowed licenses, as configured under [license].".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -382,8 +566,8 @@ pub fn code_analysis_tool() -> Tool {
                 },
                 "analysis_type": {
                     "type": "string",
-                    "description": "Type of analysis: 'quality' (linting/code smells), 'security' (vulnerability scanning), or 'all' (default: 'all')",
-                    "enum": ["quality", "security", "all"]
+                    "description": "Type of analysis: 'quality' (linting/code smells), 'security' (vulnerability scanning), 'license' (license headers and dependency licenses), or 'all' (default: 'all'; includes 'license' when a license policy is configured)",
+                    "enum": ["quality", "security", "license", "all"]
                 },
                 "strict": {
                     "type": "boolean",
@@ -418,6 +602,14 @@ pub fn copy_tool() -> Tool {
                 "recursive": {
                     "type": "boolean",
                     "description": "If true, copy directories recursively (default: true)"
+                },
+                "follow_symlinks": {
+                    "type": "boolean",
+                    "description": "Follow symbolic links that stay inside the workspace; links are skipped otherwise (default: false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, describe what would be copied without copying (default: false)"
                 }
             },
             "required": ["source", "destination"]
@@ -429,7 +621,7 @@ pub fn copy_tool() -> Tool {
 pub fn delete_tool() -> Tool {
     Tool {
         name: "delete".to_string(),
-        description: "Delete files or directories. Includes safety checks and requires confirmation for directory deletion.".to_string(),
+        description: "Delete files or directories. Includes safety checks and requires confirmation for directory deletion. Symbolic links are removed themselves, never their targets.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -440,6 +632,10 @@ pub fn delete_tool() -> Tool {
                 "recursive": {
                     "type": "boolean",
                     "description": "If true, delete directories and their contents recursively (default: false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, describe what would be deleted without deleting (default: false)"
                 }
             },
             "required": ["path"]
@@ -466,6 +662,10 @@ pub fn move_tool() -> Tool {
                 "overwrite": {
                     "type": "boolean",
                     "description": "Whether to overwrite existing files at the destination (default: false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, describe the move without performing it (default: false)"
                 }
             },
             "required": ["source", "destination"]
@@ -559,7 +759,7 @@ pub fn test_runner_tool() -> Tool {
 pub fn lint_tool() -> Tool {
     Tool {
         name: "lint".to_string(),
-        description: "Run code linters for various project types. Supports Rust (cargo clippy), JavaScript/TypeScript (eslint), Python (pylint/flake8), Go (go vet), and custom lint commands.".to_string(),
+        description: "Run code linters for various project types. Supports Rust (cargo clippy), JavaScript/TypeScript (eslint), Python (pylint/flake8), Go (go vet), and custom lint commands. Findings are added to the problem list; with JSON output (cargo --message-format=json, eslint --format json) machine-applicable fixes can be applied with apply_quick_fix.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -651,129 +851,1008 @@ pub fn task_tool() -> Tool {
     }
 }
 
-/// Get all available tools
-pub fn all_tools() -> Vec<Tool> {
-    vec![
-        read_tool(),
-        write_tool(),
-        edit_tool(),
-        multi_edit_tool(),
-        bash_tool(),
-        grep_tool(),
-        glob_tool(),
-        list_directory_tool(),
-        multi_replace_tool(),
-        syntax_check_tool(),
-        code_format_tool(),
-        code_analysis_tool(),
-        copy_tool(),
-        delete_tool(),
-        move_tool(),
-        build_tool(),
-        test_runner_tool(),
-        lint_tool(),
-        task_tool(),
-    ]
+/// Create the SecretScan tool definition
+pub fn secret_scan_tool() -> Tool {
+    Tool {
+        name: "secret_scan".to_string(),
+        description: "Scan project files for committed secrets such as API keys, private keys, and access tokens using pattern and entropy rules. Known false positives listed in .AuroraHeart/secrets-baseline.json are suppressed; add new ones with update_secret_baseline.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Optional file or directory in the project to scan (defaults to all tracked files in the project)"
+                },
+                "staged_only": {
+                    "type": "boolean",
+                    "description": "Only scan files staged for commit, as they are staged (default: false)"
+                }
+            },
+            "required": []
+        }),
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_tool_serialization() {
-        let tool = read_tool();
-        let json = serde_json::to_string(&tool).unwrap();
-        assert!(json.contains("read"));
-        assert!(json.contains("file_path"));
+/// Create the UpdateSecretBaseline tool definition
+pub fn update_secret_baseline_tool() -> Tool {
+    Tool {
+        name: "update_secret_baseline".to_string(),
+        description: "Scan like secret_scan and add every finding to .AuroraHeart/secrets-baseline.json, so known false positives are suppressed in future scans. Only use it for findings confirmed not to be real secrets.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Optional file or directory in the project whose findings to add (defaults to all tracked files in the project)"
+                },
+                "staged_only": {
+                    "type": "boolean",
+                    "description": "Only add findings in files staged for commit (default: false)"
+                }
+            },
+            "required": []
+        }),
     }
+}
 
-    #[test]
-    fn test_tool_use_serialization() {
-        let tool_use = ToolUse {
-            id: "toolu_123".to_string(),
-            name: "read".to_string(),
-            input: serde_json::json!({"file_path": "/tmp/test.txt"}),
-        };
-
-        let json = serde_json::to_string(&tool_use).unwrap();
-        assert!(json.contains("toolu_123"));
-        assert!(json.contains("read"));
-        assert!(json.contains("/tmp/test.txt"));
+/// Create the RunQualityGate tool definition
+pub fn run_quality_gate_tool() -> Tool {
+    Tool {
+        name: "run_quality_gate".to_string(),
+        description: "Run the project's pre-commit quality gate (format check, lint, test subset, secret scan, and custom steps configured in .AuroraHeart/config.toml) and report structured reasons for any failures.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": "string"
+                    },
+                    "description": "Optional names of steps to run (runs all enabled steps if omitted; named steps run even if disabled)"
+                },
+                "staged_only": {
+                    "type": "boolean",
+                    "description": "Restrict file-based checks such as the secret scan to staged files (default: false)"
+                }
+            },
+            "required": []
+        }),
     }
+}
 
-    #[test]
-    fn test_tool_result_success() {
-        let result = ToolResult::success(
-            "toolu_123".to_string(),
-            "File contents here".to_string(),
-        );
-
-        assert_eq!(result.tool_use_id, "toolu_123");
-        assert_eq!(result.content, "File contents here");
-        assert_eq!(result.is_error, None);
+/// Create the GetCiStatus tool definition
+pub fn get_ci_status_tool() -> Tool {
+    Tool {
+        name: "get_ci_status".to_string(),
+        description: "Get GitHub Actions or GitLab CI status for a branch, including the latest run of each workflow and the open pull/merge request. Defaults to the current branch.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "branch": {
+                    "type": "string",
+                    "description": "Branch to inspect (defaults to the current branch)"
+                }
+            },
+            "required": []
+        }),
     }
+}
 
-    #[test]
-    fn test_tool_result_error() {
-        let result = ToolResult::error(
-            "toolu_123".to_string(),
-            "File not found".to_string(),
-        );
-
-        assert_eq!(result.tool_use_id, "toolu_123");
-        assert_eq!(result.content, "File not found");
-        assert_eq!(result.is_error, Some(true));
+/// Create the GetFailedJobLogs tool definition
+pub fn get_failed_job_logs_tool() -> Tool {
+    Tool {
+        name: "get_failed_job_logs".to_string(),
+        description: "Fetch the trailing log output of failed CI jobs so build failures can be diagnosed. Defaults to the most recent failed run on the current branch.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "run_id": {
+                    "type": "integer",
+                    "description": "Workflow run or pipeline ID (defaults to the latest failed run)"
+                },
+                "branch": {
+                    "type": "string",
+                    "description": "Branch to inspect when run_id is not given (defaults to the current branch)"
+                },
+                "max_lines": {
+                    "type": "integer",
+                    "description": "Maximum number of log lines to return per job (default: 200)"
+                }
+            },
+            "required": []
+        }),
     }
+}
 
-    #[test]
-    fn test_all_tools() {
-        let tools = all_tools();
-        assert_eq!(tools.len(), 19);
-
-        let tool_names: Vec<String> = tools.iter().map(|t| t.name.clone()).collect();
-        assert!(tool_names.contains(&"read".to_string()));
-        assert!(tool_names.contains(&"write".to_string()));
-        assert!(tool_names.contains(&"edit".to_string()));
-        assert!(tool_names.contains(&"bash".to_string()));
-        assert!(tool_names.contains(&"grep".to_string()));
-        assert!(tool_names.contains(&"glob".to_string()));
-        assert!(tool_names.contains(&"list_directory".to_string()));
-        assert!(tool_names.contains(&"multi_replace".to_string()));
-        assert!(tool_names.contains(&"syntax_check".to_string()));
-        assert!(tool_names.contains(&"code_format".to_string()));
-        assert!(tool_names.contains(&"code_analysis".to_string()));
-        assert!(tool_names.contains(&"copy".to_string()));
-        assert!(tool_names.contains(&"delete".to_string()));
-        assert!(tool_names.contains(&"move".to_string()));
-        assert!(tool_names.contains(&"build".to_string()));
-        assert!(tool_names.contains(&"test_runner".to_string()));
-        assert!(tool_names.contains(&"lint".to_string()));
-        assert!(tool_names.contains(&"task".to_string()));
+/// Create the GetIssue tool definition
+pub fn get_issue_tool() -> Tool {
+    Tool {
+        name: "get_issue".to_string(),
+        description: "Fetch an issue's title, description, status, labels, and comments from the configured issue tracker (Jira, GitHub Issues, or Linear). Use this to pull real requirements when asked to work on a ticket such as ABC-123 or #42.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Issue key or number (e.g. 'ABC-123', '#42')"
+                },
+                "tracker": {
+                    "type": "string",
+                    "description": "Optional tracker override (defaults to the tracker configured in .AuroraHeart/config.toml)",
+                    "enum": ["jira", "github", "linear"]
+                }
+            },
+            "required": ["id"]
+        }),
     }
+}
 
-    #[test]
-    fn test_read_tool_schema() {
-        let tool = read_tool();
-        assert_eq!(tool.name, "read");
-        assert!(tool.description.contains("Read"));
-
-        let schema = tool.input_schema;
-        assert_eq!(schema["type"], "object");
-        assert!(schema["properties"]["file_path"].is_object());
-        assert_eq!(schema["required"][0], "file_path");
+/// Create the ReadDocument tool definition
+pub fn read_document_tool() -> Tool {
+    Tool {
+        name: "read_document".to_string(),
+        description: "Extract the text of a PDF or DOCX document, with '--- Page N ---' markers. Use the pages parameter to read specific pages of long documents.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "Path to the .pdf or .docx file"
+                },
+                "pages": {
+                    "type": "string",
+                    "description": "Optional pages to read, e.g. '3', '1-4', '2,5-7' or '10-' (defaults to all pages)"
+                }
+            },
+            "required": ["file_path"]
+        }),
     }
+}
 
-    #[test]
-    fn test_write_tool_schema() {
-        let tool = write_tool();
-        assert_eq!(tool.name, "write");
-        assert!(tool.description.contains("Write"));
-
-        let schema = tool.input_schema;
-        assert!(schema["properties"]["file_path"].is_object());
-        assert!(schema["properties"]["content"].is_object());
-        assert_eq!(schema["required"].as_array().unwrap().len(), 2);
+/// Create the GetSelection tool definition
+pub fn get_selection_tool() -> Tool {
+    Tool {
+        name: "get_selection".to_string(),
+        description: "Get the text currently selected in the user's editor, with its file path and line/column range. Use this when the user refers to 'this', 'the selection', or 'this code'.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Create the GetOpenEditors tool definition
+pub fn get_open_editors_tool() -> Tool {
+    Tool {
+        name: "get_open_editors".to_string(),
+        description: "List the files open in the user's editor tabs, marking the active tab and tabs with unsaved changes.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Create the GetDiagnostics tool definition
+pub fn get_diagnostics_tool() -> Tool {
+    Tool {
+        name: "get_diagnostics".to_string(),
+        description: "Get the IDE's current problem list: language server diagnostics, build errors, lint findings, and analysis results with file, line, severity, and message. Check this before and after editing to see what is broken.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "severity": {
+                    "type": "string",
+                    "enum": ["error", "warning", "info", "hint"],
+                    "description": "Only include problems at least this severe"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Only include problems in this file"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Create the ApplyQuickFix tool definition
+pub fn apply_quick_fix_tool() -> Tool {
+    Tool {
+        name: "apply_quick_fix".to_string(),
+        description: "Apply a machine-applicable quick fix (clippy suggestion, ESLint fix, or language server code action) to a diagnostic from get_diagnostics. Prefer this over editing by hand when a fix is listed; the edits are applied exactly as the tool proposed them.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "diagnostic_id": {
+                    "type": "string",
+                    "description": "The diagnostic's id as listed by get_diagnostics"
+                },
+                "fix": {
+                    "type": "integer",
+                    "description": "Index of the quick fix to apply (default: 0)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, return the diff of the fix without applying it (default: false)"
+                }
+            },
+            "required": ["diagnostic_id"]
+        }),
+    }
+}
+
+/// Create the Scaffold tool definition
+pub fn scaffold_tool() -> Tool {
+    Tool {
+        name: "scaffold".to_string(),
+        description: "Create a new project from a template: built-in generators (rust-bin, rust-lib, vite-react, vite-vanilla, poetry) or user-defined template directories with {{variable}} substitution. Omit template to list the available templates.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "template": {
+                    "type": "string",
+                    "description": "Template name (omit to list templates)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Project name, used as the new directory name"
+                },
+                "directory": {
+                    "type": "string",
+                    "description": "Parent directory for the new project (defaults to the working directory)"
+                },
+                "variables": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Values for template placeholders; project_name and crate_name are set automatically"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Create the RunProjectTask tool definition
+pub fn run_project_task_tool() -> Tool {
+    Tool {
+        name: "run_project_task".to_string(),
+        description: "Run a named project task defined in the [tasks] section of .AuroraHeart/config.toml. Dependencies run first, independent tasks in parallel; the result includes each task's status and output. Omit name to list the defined tasks.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Task name (omit to list tasks)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Create the Benchmark tool definition
+pub fn benchmark_tool() -> Tool {
+    Tool {
+        name: "benchmark".to_string(),
+        description: "Run benchmarks with cargo bench (libtest or Criterion) or hyperfine, store the timings per commit in .AuroraHeart/bench/, and compare them with a stored baseline to report regressions and improvements. Use this to verify performance claims before and after an optimization.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "runner": {
+                    "type": "string",
+                    "enum": ["cargo", "hyperfine"],
+                    "description": "Benchmark runner (default: cargo)"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Extra arguments for cargo bench, e.g. a benchmark filter"
+                },
+                "commands": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Commands to time with hyperfine (required for the hyperfine runner)"
+                },
+                "runs": {
+                    "type": "integer",
+                    "description": "Number of hyperfine runs per command"
+                },
+                "warmup": {
+                    "type": "integer",
+                    "description": "Number of hyperfine warmup runs per command"
+                },
+                "baseline": {
+                    "type": "string",
+                    "description": "Stored commit to compare against (default: the most recent other stored run)"
+                },
+                "threshold_percent": {
+                    "type": "number",
+                    "description": "Change in percent beyond which a benchmark is reported as regressed or improved (default: 5)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Create the Profile tool definition
+pub fn profile_tool() -> Tool {
+    Tool {
+        name: "profile".to_string(),
+        description: "Run a command under a sampling profiler (perf, dtrace, or py-spy; chosen per language in .AuroraHeart/config.toml) and summarize the top hotspots by self and total time. Folded stacks are saved under .AuroraHeart/profiles/ and the path is returned so the IDE can render a flamegraph.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Shell command to profile, run from the project root (build with optimizations and debug symbols first)"
+                },
+                "profiler": {
+                    "type": "string",
+                    "enum": ["perf", "dtrace", "py_spy"],
+                    "description": "Profiler override (default: configured or detected from the project language)"
+                },
+                "top_n": {
+                    "type": "integer",
+                    "description": "Number of hotspots to report (default: 20)"
+                }
+            },
+            "required": ["command"]
+        }),
+    }
+}
+
+/// Create the Generate SBOM tool definition
+pub fn generate_sbom_tool() -> Tool {
+    Tool {
+        name: "generate_sbom".to_string(),
+        description: "Generate a software bill of materials (CycloneDX 1.5 or SPDX 2.3 JSON) listing the third-party packages in the project's Cargo.lock, package-lock.json, and requirements.txt, with versions, package URLs, checksums, and declared licenses where the lockfile records them.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "format": {
+                    "type": "string",
+                    "enum": ["cyclonedx", "spdx"],
+                    "description": "Document format (default: cyclonedx)"
+                },
+                "output_path": {
+                    "type": "string",
+                    "description": "File to write the document to, e.g. sbom.cdx.json; if omitted the document is returned"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Create the Container tool definition
+pub fn container_tool() -> Tool {
+    Tool {
+        name: "container".to_string(),
+        description: "Build, list, and run container images with Docker or Podman (from [container] in .AuroraHeart/config.toml, otherwise whichever is installed). 'dockerfile' suggests a Dockerfile and .dockerignore for the project's language to adapt and save with the write tool; 'build' builds an image and streams the build log; 'images' lists local images; 'run' starts a container with port mappings.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["dockerfile", "build", "images", "run"],
+                    "description": "What to do"
+                },
+                "engine": {
+                    "type": "string",
+                    "enum": ["docker", "podman"],
+                    "description": "Engine override"
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "Image tag to build, e.g. myapp:dev (required for build)"
+                },
+                "dockerfile": {
+                    "type": "string",
+                    "description": "Dockerfile to build (default: Dockerfile)"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Build context directory (default: the project root)"
+                },
+                "build_args": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Build arguments (--build-arg NAME=VALUE)"
+                },
+                "image": {
+                    "type": "string",
+                    "description": "Image to run (required for run)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Container name"
+                },
+                "ports": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Published ports as HOST:CONTAINER, e.g. [\"8080:80\", \"5353:53/udp\"]"
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Environment variables for the container"
+                },
+                "command": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Command overriding the image's default"
+                },
+                "detach": {
+                    "type": "boolean",
+                    "description": "Run in the background and return the container ID (default: true)"
+                }
+            },
+            "required": ["action"]
+        }),
+    }
+}
+
+/// Create the Markdown tool definition
+pub fn markdown_tool() -> Tool {
+    Tool {
+        name: "markdown".to_string(),
+        description: "Work with Markdown documentation. 'toc' generates a file's table of contents from its headings, and with write=true refreshes it between <!-- toc --> and <!-- tocstop --> markers (inserting them after the title if missing); 'check_links' reports links to missing files and anchors within the repository; 'lint' reports skipped heading levels, repeated top-level headings, and '#Heading' lines missing their space; 'check' does both. Checks cover docs/ and the root's Markdown files unless paths are given.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["toc", "check_links", "lint", "check"],
+                    "description": "What to do"
+                },
+                "file_path": {
+                    "type": "string",
+                    "description": "Markdown file for toc"
+                },
+                "write": {
+                    "type": "boolean",
+                    "description": "For toc, update the file instead of returning the table (default: false)"
+                },
+                "min_level": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 6,
+                    "description": "Shallowest heading level in the table (default: 2)"
+                },
+                "max_level": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 6,
+                    "description": "Deepest heading level in the table (default: 3)"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files or directories to check (default: docs/ and the root's Markdown files)"
+                }
+            },
+            "required": ["action"]
+        }),
+    }
+}
+
+/// Create the Validate Config File tool definition
+pub fn validate_config_file_tool() -> Tool {
+    Tool {
+        name: "validate_config_file".to_string(),
+        description: "Check a JSON, YAML, or TOML file's syntax and validate it against a JSON Schema, reporting the line of every error. The schema is the one given, else the file's $schema, else the published schema for well-known files (GitHub Actions workflows, Dependabot, Compose files, package.json, tsconfig.json, Cargo.toml, pyproject.toml); without one only the syntax is checked.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The file to validate"
+                },
+                "schema": {
+                    "type": "string",
+                    "description": "JSON Schema to validate against: a project file or an http(s) URL"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["json", "yaml", "toml"],
+                    "description": "Format override (default: from the file extension)"
+                }
+            },
+            "required": ["file_path"]
+        }),
+    }
+}
+
+/// Create the API Spec tool definition
+pub fn api_spec_tool() -> Tool {
+    Tool {
+        name: "api_spec".to_string(),
+        description: "Work with the project's API contracts without reading the whole spec. 'list' finds OpenAPI, Swagger, and GraphQL schema files; 'summary' condenses one into its endpoints (method, path, parameters, request body, response types) and models (fields and types, with $refs by name), optionally filtered to names containing a word; 'generate' runs a typed-client generator configured under [api_generators.<name>] in .AuroraHeart/config.toml.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "summary", "generate"],
+                    "description": "What to do"
+                },
+                "spec": {
+                    "type": "string",
+                    "description": "Spec file (default: the generator's spec, or the project's only spec)"
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "For summary, keep only endpoints and models whose path, operationId, or name contains this text"
+                },
+                "generator": {
+                    "type": "string",
+                    "description": "Configured generator to run (default: the only one configured)"
+                },
+                "output": {
+                    "type": "string",
+                    "description": "Output directory override for generate"
+                }
+            },
+            "required": ["action"]
+        }),
+    }
+}
+
+/// Create the DB Schema tool definition
+pub fn db_schema_tool() -> Tool {
+    Tool {
+        name: "db_schema".to_string(),
+        description: "Get the database schema: tables, columns (type, nullability, default), primary keys, and foreign key relationships in both directions. Reads the live database at DATABASE_URL (or [database] in .AuroraHeart/config.toml) through psql, mysql, or sqlite3, querying only the catalog; without a database, replays the project's SQL migrations (skipping down migrations). Use it before writing queries or migrations.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "source": {
+                    "type": "string",
+                    "enum": ["auto", "database", "migrations"],
+                    "description": "Where to read the schema (default: auto, the database when one is configured, otherwise the migrations)"
+                },
+                "tables": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only these tables and the tables they reference (default: all)"
+                },
+                "migrations": {
+                    "type": "string",
+                    "description": "Directory of the SQL migrations (default: every migrations, migration, or migrate directory)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Create the Migrate tool definition
+pub fn migrate_tool() -> Tool {
+    Tool {
+        name: "migrate".to_string(),
+        description: "Run database migrations with the project's framework: SQLx, Diesel, Alembic, Prisma Migrate, or Knex, detected from prisma/schema.prisma, a knexfile, alembic.ini, diesel.toml, or sqlx in Cargo.toml with a migrations directory. 'status' lists applied and pending migrations; 'up' applies all pending ones; 'down' reverts the last `steps` (Prisma has no down migrations). With dry_run=true, 'up' and 'down' print the SQL they would run where the framework can (SQLx, and Alembic for up), and otherwise the status. Migrations change the database, so check status first.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["status", "up", "down"],
+                    "description": "What to do"
+                },
+                "tool": {
+                    "type": "string",
+                    "enum": ["sqlx", "diesel", "alembic", "prisma", "knex"],
+                    "description": "Framework override (default: detected)"
+                },
+                "directory": {
+                    "type": "string",
+                    "description": "Directory of the project whose migrations to run (default: the project root)"
+                },
+                "steps": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "How many migrations down reverts (default: 1)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Show what up or down would do without changing the database (default: false)"
+                }
+            },
+            "required": ["action"]
+        }),
+    }
+}
+
+/// Create the RegexTest tool definition
+pub fn regex_test_tool() -> Tool {
+    Tool {
+        name: "regex_test".to_string(),
+        description: "Try a regular expression on sample text before using it in grep or multi_replace. Uses the same regex engine and flags, and lists each match with its line, column, and capture groups, plus what `replacement` would turn it into. Warns about patterns that match empty text, replacements that name groups the pattern lacks, and `$1x`, which is read as the group named '1x' (write `${1}x`). The text comes from `text` or from a file.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "The regular expression to try"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "Sample text to match against"
+                },
+                "file_path": {
+                    "type": "string",
+                    "description": "File to match against instead of text"
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "Replacement to preview for each match, with $1 or ${name} for groups"
+                },
+                "case_insensitive": {
+                    "type": "boolean",
+                    "description": "Ignore case (default: false)"
+                },
+                "multiline": {
+                    "type": "boolean",
+                    "description": "Make ^ and $ match at every line (default: false)"
+                },
+                "dot_matches_newline": {
+                    "type": "boolean",
+                    "description": "Make . match newlines too (default: false)"
+                },
+                "max_matches": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Most matches to list in detail (default: 20)"
+                }
+            },
+            "required": ["pattern"]
+        }),
+    }
+}
+
+/// Create the RunSnippet tool definition
+pub fn run_snippet_tool() -> Tool {
+    Tool {
+        name: "run_snippet".to_string(),
+        description: "Run a short Rust, Python, or JavaScript snippet in a scratch directory outside the project, to check an algorithm or how an API behaves without touching project files. Rust snippets become a throwaway crate with no dependencies; code without `fn main` is wrapped in one. Returns the output and exit code. The run is killed after timeout_secs, and on Unix its memory is capped at memory_mb. The snippet still runs as the user with network and file system access, so it is not a security sandbox.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "language": {
+                    "type": "string",
+                    "enum": ["rust", "python", "javascript"],
+                    "description": "Language of the snippet"
+                },
+                "code": {
+                    "type": "string",
+                    "description": "The code to run"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 60,
+                    "description": "Seconds the snippet may run, not counting a Rust build (default: 10)"
+                },
+                "memory_mb": {
+                    "type": "integer",
+                    "minimum": 16,
+                    "maximum": 4096,
+                    "description": "Memory the snippet may use, in MB (default: 512)"
+                }
+            },
+            "required": ["language", "code"]
+        }),
+    }
+}
+
+/// Create the Repl tool definition
+pub fn repl_tool() -> Tool {
+    Tool {
+        name: "repl".to_string(),
+        description: "Evaluate code in a persistent Python, Node, or Ruby session that lives for the rest of the conversation, so variables, imports, and functions defined in one call are there in the next. Runs in the project root, so project modules can be imported. The value of a trailing expression is printed, like an interactive prompt. An evaluation that runs past timeout_secs stops the session and its state is lost; 'reset' closes the session on purpose. Node evaluates scripts, not modules: use require() and wrap top-level await in an async function whose promise is the last expression.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "language": {
+                    "type": "string",
+                    "enum": ["python", "node", "ruby"],
+                    "description": "Interpreter of the session"
+                },
+                "code": {
+                    "type": "string",
+                    "description": "Code to evaluate (required for eval)"
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["eval", "reset"],
+                    "description": "Evaluate code, or close the session (default: eval)"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 600,
+                    "description": "Seconds the evaluation may run (default: 30)"
+                }
+            },
+            "required": ["language"]
+        }),
+    }
+}
+
+/// Create the Execute Cell tool definition
+pub fn execute_cell_tool() -> Tool {
+    Tool {
+        name: "execute_cell".to_string(),
+        description: "Run a Jupyter notebook code cell in a Jupyter kernel and return its outputs: printed text, results, errors with tracebacks, and images (plots), which are saved under .AuroraHeart/jupyter/ and listed by path. With notebook_path and cell, runs that cell in the notebook's kernel (from its kernelspec), which keeps its state between calls and is shared with the IDE's notebook panel, and writes the outputs into the notebook. Without notebook_path, runs code in a scratch kernel for the conversation. A cell running past timeout_secs is interrupted, keeping the kernel. Needs Python with jupyter_client and the kernel installed.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "notebook_path": {
+                    "type": "string",
+                    "description": "Path to the .ipynb notebook"
+                },
+                "cell": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Index of the code cell to run, counting every cell from 0 (required with notebook_path)"
+                },
+                "code": {
+                    "type": "string",
+                    "description": "Code to run in the scratch kernel (required without notebook_path)"
+                },
+                "kernel": {
+                    "type": "string",
+                    "description": "Kernelspec name of the scratch kernel (default: python3)"
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["execute", "shutdown"],
+                    "description": "Run the cell, or shut the kernel down and lose its state (default: execute)"
+                },
+                "save": {
+                    "type": "boolean",
+                    "description": "Write the outputs into the notebook (default: true)"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 600,
+                    "description": "Seconds the cell may run before it is interrupted (default: 60)"
+                }
+            }
+        }),
+    }
+}
+
+/// Create the K8s tool definition
+pub fn k8s_tool() -> Tool {
+    Tool {
+        name: "k8s".to_string(),
+        description: "Work with Kubernetes manifests and clusters. 'validate' checks manifest files for missing or mistyped fields, apiVersions the API server no longer serves, selectors that miss their pod labels, and unquoted ConfigMap values; 'render' runs helm template or kubectl kustomize and validates the output; 'get' and 'describe' read resources from the current kubeconfig context with kubectl after the user explicitly approves. Nothing is ever applied to the cluster.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["validate", "render", "get", "describe"],
+                    "description": "What to do"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Manifest files or directories to validate (default: the project's YAML manifests)"
+                },
+                "source": {
+                    "type": "string",
+                    "description": "Helm chart or kustomization directory to render (required for render)"
+                },
+                "renderer": {
+                    "type": "string",
+                    "enum": ["helm", "kustomize"],
+                    "description": "How to render (default: helm for a directory with Chart.yaml, otherwise kustomize)"
+                },
+                "release": {
+                    "type": "string",
+                    "description": "Helm release name (default: the chart directory's name)"
+                },
+                "values": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Helm values files"
+                },
+                "resource": {
+                    "type": "string",
+                    "description": "Resource type for get and describe, e.g. pods or deployment"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Resource name (default: all resources of the type)"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace to read or render into"
+                },
+                "all_namespaces": {
+                    "type": "boolean",
+                    "description": "Read from every namespace (default: false)"
+                },
+                "output": {
+                    "type": "string",
+                    "enum": ["wide", "yaml", "json"],
+                    "description": "Output format for get (default: wide)"
+                }
+            },
+            "required": ["action"]
+        }),
+    }
+}
+
+/// Create the IaC tool definition
+pub fn iac_tool() -> Tool {
+    Tool {
+        name: "iac".to_string(),
+        description: "Check and plan infrastructure-as-code with Terraform or Pulumi (detected from the directory's .tf files or Pulumi.yaml). 'fmt' checks Terraform formatting, or fixes it with write=true; 'validate' runs terraform validate; 'plan' runs terraform plan (saving the plan) or pulumi preview and summarizes what would be added, changed, and destroyed; 'apply' applies the saved plan (pulumi up for Pulumi) and only runs after the user explicitly approves the summarized changes.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["fmt", "validate", "plan", "apply"],
+                    "description": "What to do"
+                },
+                "directory": {
+                    "type": "string",
+                    "description": "Directory of the configuration or Pulumi project (default: the project root)"
+                },
+                "engine": {
+                    "type": "string",
+                    "enum": ["terraform", "pulumi"],
+                    "description": "Engine override"
+                },
+                "write": {
+                    "type": "boolean",
+                    "description": "For fmt, rewrite files instead of reporting the differences (default: false)"
+                },
+                "stack": {
+                    "type": "string",
+                    "description": "Pulumi stack (default: the selected stack)"
+                }
+            },
+            "required": ["action"]
+        }),
+    }
+}
+
+/// Create the GetFileOwners tool definition
+pub fn get_file_owners_tool() -> Tool {
+    Tool {
+        name: "get_file_owners".to_string(),
+        description: "Find who owns a file: the teams or people its CODEOWNERS entry names, and its most frequent recent committers from git history. Use this before changing code, to note which team owns it and who should review the change.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to the file or directory"
+                },
+                "history": {
+                    "type": "boolean",
+                    "description": "Also list frequent contributors from git history (default: true)"
+                }
+            },
+            "required": ["path"]
+        }),
+    }
+}
+
+/// Get all available tools
+pub fn all_tools() -> Vec<Tool> {
+    registry::builtin_registry().definitions()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_serialization() {
+        let tool = read_tool();
+        let json = serde_json::to_string(&tool).unwrap();
+        assert!(json.contains("read"));
+        assert!(json.contains("file_path"));
+    }
+
+    #[test]
+    fn test_tool_use_serialization() {
+        let tool_use = ToolUse {
+            id: "toolu_123".to_string(),
+            name: "read".to_string(),
+            input: serde_json::json!({"file_path": "/tmp/test.txt"}),
+        };
+
+        let json = serde_json::to_string(&tool_use).unwrap();
+        assert!(json.contains("toolu_123"));
+        assert!(json.contains("read"));
+        assert!(json.contains("/tmp/test.txt"));
+    }
+
+    #[test]
+    fn test_tool_result_success() {
+        let result = ToolResult::success(
+            "toolu_123".to_string(),
+            "File contents here".to_string(),
+        );
+
+        assert_eq!(result.tool_use_id, "toolu_123");
+        assert_eq!(result.content, "File contents here");
+        assert_eq!(result.is_error, None);
+    }
+
+    #[test]
+    fn test_tool_result_error() {
+        let result = ToolResult::error(
+            "toolu_123".to_string(),
+            "File not found".to_string(),
+        );
+
+        assert_eq!(result.tool_use_id, "toolu_123");
+        assert_eq!(result.content, "File not found");
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[test]
+    fn test_all_tools() {
+        let tools = all_tools();
+        assert_eq!(tools.len(), 49);
+
+        let tool_names: Vec<String> = tools.iter().map(|t| t.name.clone()).collect();
+        assert!(tool_names.contains(&"read".to_string()));
+        assert!(tool_names.contains(&"write".to_string()));
+        assert!(tool_names.contains(&"edit".to_string()));
+        assert!(tool_names.contains(&"bash".to_string()));
+        assert!(tool_names.contains(&"grep".to_string()));
+        assert!(tool_names.contains(&"glob".to_string()));
+        assert!(tool_names.contains(&"symbols".to_string()));
+        assert!(tool_names.contains(&"list_directory".to_string()));
+        assert!(tool_names.contains(&"multi_replace".to_string()));
+        assert!(tool_names.contains(&"syntax_check".to_string()));
+        assert!(tool_names.contains(&"code_format".to_string()));
+        assert!(tool_names.contains(&"code_analysis".to_string()));
+        assert!(tool_names.contains(&"copy".to_string()));
+        assert!(tool_names.contains(&"delete".to_string()));
+        assert!(tool_names.contains(&"move".to_string()));
+        assert!(tool_names.contains(&"build".to_string()));
+        assert!(tool_names.contains(&"test_runner".to_string()));
+        assert!(tool_names.contains(&"lint".to_string()));
+        assert!(tool_names.contains(&"task".to_string()));
+    }
+
+    #[test]
+    fn test_read_tool_schema() {
+        let tool = read_tool();
+        assert_eq!(tool.name, "read");
+        assert!(tool.description.contains("Read"));
+
+        let schema = tool.input_schema;
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["file_path"].is_object());
+        assert_eq!(schema["required"][0], "file_path");
+    }
+
+    #[test]
+    fn test_write_tool_schema() {
+        let tool = write_tool();
+        assert_eq!(tool.name, "write");
+        assert!(tool.description.contains("Write"));
+
+        let schema = tool.input_schema;
+        assert!(schema["properties"]["file_path"].is_object());
+        assert!(schema["properties"]["content"].is_object());
+        assert_eq!(schema["required"].as_array().unwrap().len(), 2);
     }
 
     #[test]
@@ -799,4 +1878,17 @@ mod tests {
         assert!(schema["properties"]["command"].is_object());
         assert_eq!(schema["required"][0], "command");
     }
+
+    #[test]
+    fn test_bash_tool_describes_shell() {
+        use aurora_core::{Shell, ShellKind};
+
+        let tool = bash_tool_for(&Shell::new(ShellKind::Zsh));
+        assert_eq!(tool.name, "bash");
+        assert!(tool.description.contains("run in zsh"));
+
+        let tool = bash_tool_for(&Shell::new(ShellKind::Powershell));
+        assert!(tool.description.contains("Windows PowerShell 5"));
+        assert!(tool.description.contains("translated"));
+    }
 }
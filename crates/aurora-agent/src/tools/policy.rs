@@ -0,0 +1,198 @@
+//! Executor-wide tool policy
+//!
+//! A [`ToolPolicy`] is held by a shared [`ToolExecutor`](super::ToolExecutor)
+//! and applies to every conversation using it, on top of the per-agent
+//! allow and deny lists of a `FilteredToolExecutor`.
+//!
+//! The policy is read from `.AuroraHeart/policy.toml`:
+//!
+//! ```toml
+//! disabled_tools = ["profile"]
+//!
+//! # Disable every tool with a capability: "write", "destructive", or "network"
+//! disabled_capabilities = ["network"]
+//!
+//! # Run file changes as a dry run first: "never", "auto_approve", or "always"
+//! preview_first = "auto_approve"
+//!
+//! # Limits for auto-approve mode
+//! [auto_approve]
+//! max_files_per_turn = 20
+//! blocked_commands = ["git push", "cargo publish"]
+//!
+//! # Tools run in a separate process with resource limits
+//! [isolation]
+//! categories = ["read_only"]
+//! memory_mb = 1024
+//! ```
+
+use super::isolation::IsolationPolicy;
+use super::registry::{builtin_capabilities, Capability, ToolCapabilities};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use thiserror::Error;
+
+/// Name of the policy file in the project's `.AuroraHeart` directory
+pub const POLICY_FILE: &str = "policy.toml";
+
+/// Errors that can occur while loading the policy file
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    /// IO error while reading the policy file
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The policy file is not valid TOML or has unknown values
+    #[error("Invalid {POLICY_FILE}: {0}")]
+    Parse(#[from] toml::de::Error),
+}
+
+/// Result type for policy operations
+pub type Result<T> = std::result::Result<T, PolicyError>;
+
+/// Restrictions applied to every tool call of an executor
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    /// Tools that may not run at all
+    #[serde(default)]
+    pub disabled_tools: BTreeSet<String>,
+
+    /// Capabilities whose tools may not run
+    #[serde(default)]
+    pub disabled_capabilities: BTreeSet<Capability>,
+
+    /// Safety limits enforced while tool calls are auto-approved
+    #[serde(default)]
+    pub auto_approve: AutoApproveLimits,
+
+    /// When calls that change files must be previewed before they apply
+    #[serde(default)]
+    pub preview_first: PreviewFirst,
+
+    /// Tools that run in a separate process, and its limits
+    #[serde(default)]
+    pub isolation: IsolationPolicy,
+}
+
+/// When a call to a tool with a `dry_run` flag first runs as a preview
+///
+/// A call that must be previewed runs as a dry run and returns its diff or
+/// plan; repeating the same call then applies it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewFirst {
+    /// Changes apply directly
+    #[default]
+    Never,
+    /// Only while calls are auto-approved, with nobody reviewing them
+    AutoApprove,
+    /// For every conversation
+    Always,
+}
+
+impl ToolPolicy {
+    /// Whether the policy lets the built-in tool `tool` run
+    pub fn allows(&self, tool: &str) -> bool {
+        self.permits(tool, builtin_capabilities(tool))
+    }
+
+    /// Whether the policy lets `tool` with `capabilities` run
+    pub fn permits(&self, tool: &str, capabilities: ToolCapabilities) -> bool {
+        !self.disabled_tools.contains(tool)
+            && !self
+                .disabled_capabilities
+                .iter()
+                .any(|capability| capabilities.has(*capability))
+    }
+
+    /// Load the project's `policy.toml`, or the default policy if there is none
+    pub fn load(project_root: impl AsRef<Path>) -> Result<Self> {
+        let path = project_root.as_ref().join(".AuroraHeart").join(POLICY_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// Limits on what auto-approved tool calls may do in one turn
+///
+/// Deleting or moving paths outside the workspace is always refused while
+/// calls are auto-approved and cannot be configured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoApproveLimits {
+    /// Most distinct files the file tools may modify in one turn
+    #[serde(default = "default_max_files_per_turn")]
+    pub max_files_per_turn: usize,
+
+    /// Commands auto-approved calls may not run, matched against the program
+    /// and subcommands of each command in a pipeline or command list
+    #[serde(default = "default_blocked_commands")]
+    pub blocked_commands: Vec<String>,
+}
+
+fn default_max_files_per_turn() -> usize {
+    20
+}
+
+fn default_blocked_commands() -> Vec<String> {
+    [
+        "git push",
+        "cargo publish",
+        "npm publish",
+        "pnpm publish",
+        "yarn publish",
+        "twine upload",
+        "gem push",
+        "docker push",
+        "gh release create",
+        "gh pr merge",
+    ]
+    .iter()
+    .map(|command| command.to_string())
+    .collect()
+}
+
+impl Default for AutoApproveLimits {
+    fn default() -> Self {
+        Self {
+            max_files_per_turn: default_max_files_per_turn(),
+            blocked_commands: default_blocked_commands(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_policy_file() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(ToolPolicy::load(temp_dir.path()).unwrap(), ToolPolicy::default());
+
+        std::fs::create_dir(temp_dir.path().join(".AuroraHeart")).unwrap();
+        let path = temp_dir.path().join(".AuroraHeart").join(POLICY_FILE);
+        std::fs::write(&path, "disabled_tools = [\"bash\"]\n\n[auto_approve]\nmax_files_per_turn = 5\n").unwrap();
+
+        let policy = ToolPolicy::load(temp_dir.path()).unwrap();
+        assert!(!policy.allows("bash"));
+        assert_eq!(policy.auto_approve.max_files_per_turn, 5);
+        assert!(policy.auto_approve.blocked_commands.contains(&"git push".to_string()));
+
+        assert_eq!(policy.preview_first, PreviewFirst::Never);
+
+        std::fs::write(&path, "preview_first = \"auto_approve\"\n").unwrap();
+        assert_eq!(ToolPolicy::load(temp_dir.path()).unwrap().preview_first, PreviewFirst::AutoApprove);
+
+        std::fs::write(&path, "disabled_capabilities = [\"network\"]\n").unwrap();
+        let policy = ToolPolicy::load(temp_dir.path()).unwrap();
+        assert!(!policy.allows("get_issue") && !policy.allows("bash"));
+        assert!(policy.allows("read") && policy.allows("edit"));
+
+        std::fs::write(&path, "disabled_tools = \"bash\"\n").unwrap();
+        assert!(matches!(ToolPolicy::load(temp_dir.path()), Err(PolicyError::Parse(_))));
+    }
+}
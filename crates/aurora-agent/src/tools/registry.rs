@@ -0,0 +1,355 @@
+//! Registry of the tools an executor can run
+//!
+//! Every tool implements [`AgentTool`]: it describes itself to the model with
+//! a name, description, and input schema, declares its
+//! [`ToolCapabilities`], and runs with a [`ToolContext`] describing the
+//! call. A [`ToolRegistry`] holds the tools of one executor; the built-in
+//! tools are registered by [`ToolRegistry::builtin`], and embedders can add
+//! their own with [`ToolExecutor::with_tool`](super::ToolExecutor::with_tool).
+//!
+//! The capability flags are what plan mode, the executor policy, and the
+//! tool cache go by, so a new tool only has to declare them correctly to be
+//! handled like the built-in tools with the same capabilities.
+
+use super::context::ToolContext;
+use super::executor::builtin_tools;
+use super::{Tool, ToolError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+
+/// Future returned by [`AgentTool::execute`]
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<String, ToolError>> + Send + 'a>>;
+
+/// What a tool may do when it runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCapabilities {
+    /// Never changes files or runs commands; allowed in plan mode
+    pub read_only: bool,
+    /// May delete data or run arbitrary commands
+    pub destructive: bool,
+    /// Talks to services over the network
+    pub network: bool,
+}
+
+impl ToolCapabilities {
+    /// Reads and analyzes local files
+    pub const READ_ONLY: Self = Self {
+        read_only: true,
+        destructive: false,
+        network: false,
+    };
+
+    /// Changes files in ways the file history can undo
+    pub const WRITE: Self = Self {
+        read_only: false,
+        destructive: false,
+        network: false,
+    };
+
+    /// Deletes files or runs arbitrary commands
+    pub const DESTRUCTIVE: Self = Self {
+        read_only: false,
+        destructive: true,
+        network: false,
+    };
+
+    /// The same capabilities, also using the network
+    pub const fn with_network(self) -> Self {
+        Self {
+            network: true,
+            ..self
+        }
+    }
+
+    /// Whether the tool has `capability`
+    pub fn has(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Write => !self.read_only,
+            Capability::Destructive => self.destructive,
+            Capability::Network => self.network,
+        }
+    }
+}
+
+/// A capability that policy can disable for every tool having it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Tools that are not read-only
+    Write,
+    /// Tools that delete data or run arbitrary commands
+    Destructive,
+    /// Tools that use the network
+    Network,
+}
+
+/// A tool the agent can call
+pub trait AgentTool: Send + Sync {
+    /// Name the model calls the tool by
+    fn name(&self) -> &str;
+
+    /// Description telling the model what the tool does
+    fn description(&self) -> String;
+
+    /// JSON schema of the tool's input; calls are validated against it
+    fn input_schema(&self) -> JsonValue;
+
+    /// What the tool may do when it runs
+    fn capabilities(&self) -> ToolCapabilities;
+
+    /// Run the tool, returning the output for the model
+    fn execute<'a>(&'a self, input: &'a JsonValue, ctx: ToolContext<'a>) -> ToolFuture<'a>;
+
+    /// The definition sent to the model
+    fn definition(&self) -> Tool {
+        Tool {
+            name: self.name().to_string(),
+            description: self.description(),
+            input_schema: self.input_schema(),
+        }
+    }
+}
+
+/// Signature of a built-in tool's entry point
+pub(super) type BuiltinRun = for<'a> fn(&'a JsonValue, ToolContext<'a>) -> ToolFuture<'a>;
+
+/// A built-in tool implemented by a [`ToolExecutor`] method
+pub(super) struct BuiltinTool {
+    definition: Tool,
+    capabilities: ToolCapabilities,
+    run: BuiltinRun,
+}
+
+impl BuiltinTool {
+    pub(super) fn new(definition: Tool, capabilities: ToolCapabilities, run: BuiltinRun) -> Self {
+        Self {
+            definition,
+            capabilities,
+            run,
+        }
+    }
+}
+
+impl AgentTool for BuiltinTool {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn description(&self) -> String {
+        self.definition.description.clone()
+    }
+
+    fn input_schema(&self) -> JsonValue {
+        self.definition.input_schema.clone()
+    }
+
+    fn capabilities(&self) -> ToolCapabilities {
+        self.capabilities
+    }
+
+    fn execute<'a>(&'a self, input: &'a JsonValue, ctx: ToolContext<'a>) -> ToolFuture<'a> {
+        (self.run)(input, ctx)
+    }
+
+    fn definition(&self) -> Tool {
+        self.definition.clone()
+    }
+}
+
+/// A registered tool with its definition and capabilities computed once
+#[derive(Clone)]
+struct RegisteredTool {
+    definition: Tool,
+    capabilities: ToolCapabilities,
+    tool: Arc<dyn AgentTool>,
+}
+
+/// The tools of an executor, in the order they are offered to the model
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<RegisteredTool>,
+}
+
+impl ToolRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with every built-in tool
+    pub fn builtin() -> Self {
+        builtin_registry().clone()
+    }
+
+    /// Add `tool`, replacing a registered tool of the same name
+    pub fn register(&mut self, tool: impl AgentTool + 'static) {
+        self.register_shared(Arc::new(tool));
+    }
+
+    /// Add a shared `tool`, replacing a registered tool of the same name
+    pub fn register_shared(&mut self, tool: Arc<dyn AgentTool>) {
+        let registered = RegisteredTool {
+            definition: tool.definition(),
+            capabilities: tool.capabilities(),
+            tool,
+        };
+        match self
+            .tools
+            .iter_mut()
+            .find(|existing| existing.definition.name == registered.definition.name)
+        {
+            Some(existing) => *existing = registered,
+            None => self.tools.push(registered),
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<&RegisteredTool> {
+        self.tools.iter().find(|tool| tool.definition.name == name)
+    }
+
+    /// The tool called `name`
+    pub fn get(&self, name: &str) -> Option<Arc<dyn AgentTool>> {
+        self.find(name).map(|tool| Arc::clone(&tool.tool))
+    }
+
+    /// Whether the tool called `name` is the built-in tool, not a replacement
+    pub(crate) fn is_builtin(&self, name: &str) -> bool {
+        match (self.find(name), builtin_registry().find(name)) {
+            (Some(tool), Some(builtin)) => Arc::ptr_eq(&tool.tool, &builtin.tool),
+            _ => false,
+        }
+    }
+
+    /// The definition of the tool called `name`
+    pub fn definition(&self, name: &str) -> Option<&Tool> {
+        self.find(name).map(|tool| &tool.definition)
+    }
+
+    /// The capabilities of the tool called `name`
+    pub fn capabilities(&self, name: &str) -> Option<ToolCapabilities> {
+        self.find(name).map(|tool| tool.capabilities)
+    }
+
+    /// Definitions of every registered tool
+    pub fn definitions(&self) -> Vec<Tool> {
+        self.tools.iter().map(|tool| tool.definition.clone()).collect()
+    }
+
+    /// Number of registered tools
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Whether no tool is registered
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+/// The built-in tools, built once
+pub(crate) fn builtin_registry() -> &'static ToolRegistry {
+    static BUILTIN: OnceLock<ToolRegistry> = OnceLock::new();
+    BUILTIN.get_or_init(|| {
+        let mut registry = ToolRegistry::new();
+        for tool in builtin_tools() {
+            registry.register_shared(tool);
+        }
+        registry
+    })
+}
+
+/// Capabilities of the built-in tool `name`, or [`ToolCapabilities::WRITE`]
+/// for a tool that is not built in
+pub(crate) fn builtin_capabilities(name: &str) -> ToolCapabilities {
+    builtin_registry()
+        .capabilities(name)
+        .unwrap_or(ToolCapabilities::WRITE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::PLAN_MODE_TOOLS;
+    use crate::tools::{ToolExecutor, ToolUse};
+    use tempfile::TempDir;
+
+    struct Shout;
+
+    impl AgentTool for Shout {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn description(&self) -> String {
+            "Repeat a message in capitals".to_string()
+        }
+
+        fn input_schema(&self) -> JsonValue {
+            serde_json::json!({
+                "type": "object",
+                "properties": { "message": { "type": "string" } },
+                "required": ["message"]
+            })
+        }
+
+        fn capabilities(&self) -> ToolCapabilities {
+            ToolCapabilities::READ_ONLY
+        }
+
+        fn execute<'a>(&'a self, input: &'a JsonValue, mut ctx: ToolContext<'a>) -> ToolFuture<'a> {
+            Box::pin(async move {
+                let message = input["message"].as_str().unwrap_or_default().to_uppercase();
+                ctx.set_data(serde_json::json!({ "length": message.len() }));
+                Ok(message)
+            })
+        }
+    }
+
+    #[test]
+    fn test_builtin_capabilities() {
+        let registry = ToolRegistry::builtin();
+        let read_only: Vec<String> = registry
+            .definitions()
+            .into_iter()
+            .map(|tool| tool.name)
+            .filter(|name| registry.capabilities(name).is_some_and(|c| c.read_only))
+            .collect();
+        let mut plan_tools: Vec<&str> = PLAN_MODE_TOOLS.to_vec();
+        let mut read_only: Vec<&str> = read_only.iter().map(String::as_str).collect();
+        plan_tools.sort_unstable();
+        read_only.sort_unstable();
+        assert_eq!(read_only, plan_tools);
+
+        assert!(builtin_capabilities("delete").destructive);
+        assert!(builtin_capabilities("bash").has(Capability::Network));
+        assert!(builtin_capabilities("get_issue").has(Capability::Network));
+        assert!(!builtin_capabilities("get_issue").has(Capability::Write));
+        assert_eq!(builtin_capabilities("edit"), ToolCapabilities::WRITE);
+        assert_eq!(builtin_capabilities("mcp__unknown"), ToolCapabilities::WRITE);
+    }
+
+    #[tokio::test]
+    async fn test_executor_runs_registered_tool() {
+        let temp_dir = TempDir::new().unwrap();
+        let executor = ToolExecutor::with_working_directory(temp_dir.path()).with_tool(Shout);
+        assert_eq!(executor.tools().last().map(|tool| tool.name.as_str()), Some("shout"));
+        assert!(executor.capabilities("shout").read_only);
+
+        let call = |input| ToolUse {
+            id: "shout_1".to_string(),
+            name: "shout".to_string(),
+            input,
+        };
+        let result = executor.execute(&call(serde_json::json!({ "message": "hi" }))).await;
+        assert_eq!(result.is_error, None);
+        assert_eq!(result.content, "HI");
+        assert_eq!(result.data, Some(serde_json::json!({ "length": 2 })));
+
+        let result = executor.execute(&call(serde_json::json!({}))).await;
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.content.contains("message: required property is missing"));
+    }
+}
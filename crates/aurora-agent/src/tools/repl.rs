@@ -0,0 +1,501 @@
+//! Persistent interpreter sessions for the repl tool
+//!
+//! Each conversation gets at most one Python, Node, and Ruby process, kept
+//! running between calls so variables, imports, and definitions carry over.
+//! The interpreter runs a small driver rather than its interactive prompt:
+//! the driver reads one base64-encoded snippet per line, evaluates it in a
+//! namespace that persists, prints the value of a trailing expression, and
+//! ends the output with a sentinel line carrying the outcome. Output is
+//! read up to that line, so a call knows exactly where its output ends.
+//!
+//! Every evaluation is kept in the session's transcript, which the UI reads
+//! through [`ToolExecutor::repl_transcript`](super::ToolExecutor::repl_transcript)
+//! to show the session, and finished evaluations are broadcast to
+//! [`ToolExecutor::subscribe_repl`](super::ToolExecutor::subscribe_repl).
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::broadcast;
+
+/// Evaluations kept in a session's transcript
+const MAX_TRANSCRIPT: usize = 200;
+
+/// Finished evaluations buffered for slow subscribers
+const UPDATE_CAPACITY: usize = 64;
+
+/// Interpreter of a REPL session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplLanguage {
+    Python,
+    Node,
+    Ruby,
+}
+
+impl ReplLanguage {
+    /// The interpreter's executable
+    pub fn program(&self) -> &'static str {
+        match self {
+            ReplLanguage::Python => "python",
+            ReplLanguage::Node => "node",
+            ReplLanguage::Ruby => "ruby",
+        }
+    }
+
+    /// Arguments that start the driver, which ends each evaluation's output
+    /// with `sentinel` followed by ` ok` or ` error`
+    fn args(&self, sentinel: &str) -> Vec<String> {
+        let (flag, driver) = match self {
+            ReplLanguage::Python => ("-c", PYTHON_DRIVER),
+            ReplLanguage::Node => ("-e", NODE_DRIVER),
+            ReplLanguage::Ruby => ("-e", RUBY_DRIVER),
+        };
+        let mut args = Vec::new();
+        if *self == ReplLanguage::Python {
+            args.push("-u".to_string());
+        }
+        args.extend([flag.to_string(), driver.to_string()]);
+        // Node and Ruby take script arguments after a separator
+        if *self != ReplLanguage::Python {
+            args.push("--".to_string());
+        }
+        args.push(sentinel.to_string());
+        args
+    }
+}
+
+impl FromStr for ReplLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "python" | "py" => Ok(ReplLanguage::Python),
+            "node" | "javascript" | "js" => Ok(ReplLanguage::Node),
+            "ruby" | "rb" | "irb" => Ok(ReplLanguage::Ruby),
+            _ => Err(format!("Unknown REPL language '{}'; expected 'python', 'node', or 'ruby'", s)),
+        }
+    }
+}
+
+const PYTHON_DRIVER: &str = r#"
+import ast, base64, sys, traceback
+sentinel = sys.argv[1]
+sys.stderr = sys.stdout
+namespace = {"__name__": "__main__"}
+for line in sys.stdin:
+    ok = True
+    try:
+        tree = ast.parse(base64.b64decode(line).decode(), "<repl>")
+        last = tree.body.pop() if tree.body and isinstance(tree.body[-1], ast.Expr) else None
+        exec(compile(tree, "<repl>", "exec"), namespace)
+        if last is not None:
+            value = eval(compile(ast.Expression(last.value), "<repl>", "eval"), namespace)
+            if value is not None:
+                print(repr(value))
+    except SystemExit:
+        raise
+    except BaseException:
+        ok = False
+        traceback.print_exc()
+    print(sentinel, "ok" if ok else "error", flush=True)
+"#;
+
+const NODE_DRIVER: &str = r#"
+const vm = require("vm"), util = require("util"), readline = require("readline");
+const sentinel = process.argv[process.argv.length - 1];
+globalThis.require = require;
+process.stderr.write = process.stdout.write.bind(process.stdout);
+const queue = [];
+let busy = false;
+async function next() {
+  if (busy || queue.length === 0) return;
+  busy = true;
+  let ok = true;
+  try {
+    let value = vm.runInThisContext(Buffer.from(queue.shift(), "base64").toString("utf8"), { filename: "repl" });
+    if (value instanceof Promise) value = await value;
+    if (value !== undefined) console.log(util.inspect(value, { depth: 4 }));
+  } catch (e) {
+    ok = false;
+    console.log(e && e.stack ? e.stack : String(e));
+  }
+  process.stdout.write(sentinel + (ok ? " ok\n" : " error\n"));
+  busy = false;
+  next();
+}
+readline.createInterface({ input: process.stdin }).on("line", (line) => { queue.push(line); next(); });
+"#;
+
+const RUBY_DRIVER: &str = r##"
+sentinel = ARGV[0]
+$stdout.sync = true
+$stderr = $stdout
+context = binding
+while (line = $stdin.gets)
+  ok = true
+  begin
+    value = context.eval(line.unpack1("m").force_encoding("UTF-8"), "(repl)")
+    puts value.inspect unless value.nil?
+  rescue StandardError, ScriptError => e
+    ok = false
+    puts "#{e.class}: #{e.message}"
+    puts e.backtrace.reject { |frame| frame.include?("-e:") }.map { |frame| "\tfrom #{frame}" }
+  end
+  puts "#{sentinel} #{ok ? "ok" : "error"}"
+end
+"##;
+
+/// One evaluation in a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplEntry {
+    pub code: String,
+    /// Everything the evaluation printed, errors included
+    pub output: String,
+    /// Whether it finished without raising
+    pub ok: bool,
+    pub finished: SystemTime,
+}
+
+/// A session, as listed for the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplSessionInfo {
+    /// Conversation the session belongs to
+    pub conversation: Option<String>,
+    pub language: ReplLanguage,
+    pub started: SystemTime,
+    /// Evaluations run so far
+    pub evaluations: usize,
+}
+
+/// A finished evaluation, broadcast to subscribers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplUpdate {
+    pub conversation: Option<String>,
+    pub language: ReplLanguage,
+    pub entry: ReplEntry,
+}
+
+/// Why an evaluation did not finish
+#[derive(Debug)]
+pub(super) enum ReplError {
+    /// The interpreter could not be started or written to
+    Io(std::io::Error),
+    /// The interpreter exited; its last output is attached
+    Exited(String),
+}
+
+/// The running interpreter of a session
+struct ReplProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    sentinel: String,
+}
+
+impl ReplProcess {
+    async fn spawn(
+        program: &Path,
+        language: ReplLanguage,
+        dir: &Path,
+        env: &[(String, String)],
+    ) -> std::io::Result<Self> {
+        let sentinel = format!(
+            "__auroraheart_repl_{}__",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        );
+        let mut child = tokio::process::Command::new(program)
+            .args(language.args(&sentinel))
+            .current_dir(dir)
+            .envs(env.iter().cloned())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| std::io::Error::other("interpreter has no stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| std::io::Error::other("interpreter has no stdout"))?;
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            sentinel,
+        })
+    }
+
+    /// Evaluate `code`, passing each line of output to `on_line` as it arrives
+    async fn eval(&mut self, code: &str, mut on_line: impl FnMut(&str)) -> Result<(String, bool), ReplError> {
+        let mut line = BASE64.encode(code);
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes()).await.map_err(ReplError::Io)?;
+        self.stdin.flush().await.map_err(ReplError::Io)?;
+
+        let mut output = String::new();
+        loop {
+            let Some(line) = self.stdout.next_line().await.map_err(ReplError::Io)? else {
+                let _ = self.child.wait().await;
+                return Err(ReplError::Exited(output));
+            };
+            if let Some(outcome) = line.strip_prefix(&self.sentinel) {
+                return Ok((output, outcome.trim() == "ok"));
+            }
+            on_line(&line);
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+}
+
+/// A session and its transcript
+struct ReplSession {
+    started: SystemTime,
+    /// The interpreter, taken out while it evaluates: a call dropped partway
+    /// drops it too, so a half-finished evaluation never leaks into the next
+    process: tokio::sync::Mutex<Option<ReplProcess>>,
+    transcript: Mutex<Vec<ReplEntry>>,
+}
+
+/// Key of a session: its conversation and interpreter
+type SessionKey = (Option<String>, ReplLanguage);
+
+/// Every conversation's REPL sessions
+pub(super) struct ReplSessions {
+    sessions: Mutex<HashMap<SessionKey, Arc<ReplSession>>>,
+    updates: broadcast::Sender<ReplUpdate>,
+}
+
+/// How to start a session's interpreter
+pub(super) struct ReplLaunch<'a> {
+    pub program: &'a Path,
+    /// Directory it runs in
+    pub dir: &'a Path,
+    /// Variables set on top of the inherited environment
+    pub env: &'a [(String, String)],
+}
+
+/// Outcome of one evaluation
+pub(super) struct Evaluation {
+    pub output: String,
+    pub ok: bool,
+    /// Whether it replaced one that exited, timed out, or was cancelled,
+    /// losing the state built up before
+    pub restarted: bool,
+}
+
+impl ReplSessions {
+    pub(super) fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            updates: broadcast::channel(UPDATE_CAPACITY).0,
+        }
+    }
+
+    /// Evaluate `code` in the conversation's session, starting the
+    /// interpreter as `launch` says when the session has none running
+    pub(super) async fn eval(
+        &self,
+        conversation: Option<&str>,
+        language: ReplLanguage,
+        code: &str,
+        launch: ReplLaunch<'_>,
+        on_line: impl FnMut(&str),
+    ) -> Result<Evaluation, ReplError> {
+        let key = (conversation.map(str::to_string), language);
+        let session = self
+            .sessions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(ReplSession {
+                    started: SystemTime::now(),
+                    process: tokio::sync::Mutex::new(None),
+                    transcript: Mutex::new(Vec::new()),
+                })
+            })
+            .clone();
+
+        let mut process = session.process.lock().await;
+        let restarted = process.is_none() && !session.transcript.lock().unwrap_or_else(PoisonError::into_inner).is_empty();
+        let mut running = match process.take() {
+            Some(running) => running,
+            None => ReplProcess::spawn(launch.program, language, launch.dir, launch.env)
+                .await
+                .map_err(ReplError::Io)?,
+        };
+        let (output, ok) = running.eval(code, on_line).await?;
+        *process = Some(running);
+
+        let entry = ReplEntry {
+            code: code.to_string(),
+            output: output.clone(),
+            ok,
+            finished: SystemTime::now(),
+        };
+        {
+            let mut transcript = session.transcript.lock().unwrap_or_else(PoisonError::into_inner);
+            transcript.push(entry.clone());
+            let excess = transcript.len().saturating_sub(MAX_TRANSCRIPT);
+            transcript.drain(..excess);
+        }
+        // Nobody subscribing is fine
+        let _ = self.updates.send(ReplUpdate {
+            conversation: key.0,
+            language,
+            entry,
+        });
+        Ok(Evaluation {
+            output,
+            ok,
+            restarted,
+        })
+    }
+
+    /// Sessions open, oldest first
+    pub(super) fn list(&self) -> Vec<ReplSessionInfo> {
+        let sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut list: Vec<ReplSessionInfo> = sessions
+            .iter()
+            .map(|((conversation, language), session)| ReplSessionInfo {
+                conversation: conversation.clone(),
+                language: *language,
+                started: session.started,
+                evaluations: session.transcript.lock().unwrap_or_else(PoisonError::into_inner).len(),
+            })
+            .collect();
+        list.sort_by_key(|info| info.started);
+        list
+    }
+
+    /// Evaluations of a session, oldest first
+    pub(super) fn transcript(&self, conversation: Option<&str>, language: ReplLanguage) -> Option<Vec<ReplEntry>> {
+        let key = (conversation.map(str::to_string), language);
+        self.sessions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .map(|session| session.transcript.lock().unwrap_or_else(PoisonError::into_inner).clone())
+    }
+
+    /// End a session, stopping its interpreter; false if there was none
+    pub(super) fn close(&self, conversation: Option<&str>, language: ReplLanguage) -> bool {
+        let key = (conversation.map(str::to_string), language);
+        // Dropping the last handle kills the interpreter
+        self.sessions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&key)
+            .is_some()
+    }
+
+    /// End every session, returning how many there were
+    pub(super) fn close_all(&self) -> usize {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = sessions.len();
+        sessions.clear();
+        count
+    }
+
+    pub(super) fn subscribe(&self) -> broadcast::Receiver<ReplUpdate> {
+        self.updates.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn python() -> Option<std::path::PathBuf> {
+        aurora_core::shell::find_on_path("python")
+    }
+
+    #[tokio::test]
+    async fn test_state_persists_between_evaluations() {
+        let Some(program) = python() else {
+            return;
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let sessions = ReplSessions::new();
+        let mut updates = sessions.subscribe();
+        let launch = || ReplLaunch {
+            program: &program,
+            dir: dir.path(),
+            env: &[],
+        };
+        let eval = |code: &'static str| sessions.eval(Some("c1"), ReplLanguage::Python, code, launch(), |_| {});
+
+        let first = eval("import math\nx = 6\nprint('set')").await.unwrap();
+        assert!(first.ok && !first.restarted);
+        assert_eq!(first.output, "set\n");
+
+        let second = eval("math.factorial(x)").await.unwrap();
+        assert_eq!(second.output, "720\n");
+
+        let failed = eval("1 / 0").await.unwrap();
+        assert!(!failed.ok);
+        assert!(failed.output.contains("ZeroDivisionError"));
+
+        assert_eq!(updates.recv().await.unwrap().entry.output, "set\n");
+        assert_eq!(sessions.transcript(Some("c1"), ReplLanguage::Python).unwrap().len(), 3);
+        assert_eq!(sessions.transcript(Some("c2"), ReplLanguage::Python), None);
+        assert_eq!(sessions.list()[0].evaluations, 3);
+
+        assert!(sessions.close(Some("c1"), ReplLanguage::Python));
+        assert!(sessions.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_node_session() {
+        let Some(program) = aurora_core::shell::find_on_path("node") else {
+            return;
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let sessions = ReplSessions::new();
+        let launch = || ReplLaunch {
+            program: &program,
+            dir: dir.path(),
+            env: &[],
+        };
+        let eval = |code: &'static str| sessions.eval(None, ReplLanguage::Node, code, launch(), |_| {});
+
+        let first = eval("const path = require('path');\nlet n = 21;\nconsole.error('to stderr');").await.unwrap();
+        assert_eq!(first.output, "to stderr\n");
+        assert_eq!(eval("n * 2").await.unwrap().output, "42\n");
+        assert_eq!(eval("Promise.resolve(path.basename('/a/b.txt'))").await.unwrap().output, "'b.txt'\n");
+        let failed = eval("throw new Error('boom')").await.unwrap();
+        assert!(!failed.ok && failed.output.contains("Error: boom"));
+    }
+
+    #[tokio::test]
+    async fn test_exit_ends_the_process() {
+        let Some(program) = python() else {
+            return;
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let sessions = ReplSessions::new();
+        let launch = || ReplLaunch {
+            program: &program,
+            dir: dir.path(),
+            env: &[],
+        };
+        sessions.eval(None, ReplLanguage::Python, "x = 1", launch(), |_| {}).await.unwrap();
+        let result = sessions
+            .eval(None, ReplLanguage::Python, "print('bye')\nraise SystemExit", launch(), |_| {})
+            .await;
+        assert!(matches!(result, Err(ReplError::Exited(ref output)) if output == "bye\n"));
+
+        let next = sessions.eval(None, ReplLanguage::Python, "1 + 1", launch(), |_| {}).await.unwrap();
+        assert!(next.restarted);
+        assert_eq!(next.output, "2\n");
+    }
+}
@@ -0,0 +1,140 @@
+//! Retrying tool calls whose path input points to the wrong place
+//!
+//! Models often name files relative to the wrong directory: an absolute path
+//! from another checkout (`/home/me/project/src/main.rs`), a leading slash on
+//! a workspace-relative path (`/src/main.rs`), or a path starting with the
+//! project's own folder name (`project/src/main.rs`). When such a call fails
+//! and its path does not exist, the executor retries it once with the
+//! longest trailing part of the path that does exist in the workspace,
+//! instead of spending a model turn on the error.
+//!
+//! Only read-only tools are retried, so a guessed path can never change or
+//! remove the wrong file.
+
+use super::ToolUse;
+use aurora_core::resolve_path;
+use std::path::{Component, Path, PathBuf};
+
+/// Read-only tools retried with a corrected path, with the input field holding it
+const PATH_RETRY_TOOLS: &[(&str, &str)] = &[
+    ("read", "file_path"),
+    ("read_document", "file_path"),
+    ("list_directory", "path"),
+    ("grep", "path"),
+    ("glob", "path"),
+    ("secret_scan", "path"),
+];
+
+/// A tool call rewritten to use a corrected path
+#[derive(Debug, Clone)]
+pub(super) struct PathRetry {
+    /// The call with the corrected path
+    pub tool_use: ToolUse,
+    /// Note for the model explaining the substitution
+    pub note: String,
+}
+
+/// The call to retry after `tool_use` failed, if its path can be corrected
+pub(super) fn path_retry(workspace: &Path, tool_use: &ToolUse) -> Option<PathRetry> {
+    let (_, key) = PATH_RETRY_TOOLS.iter().find(|(name, _)| *name == tool_use.name)?;
+    let requested = tool_use.input[*key].as_str()?;
+    let corrected = corrected_path(workspace, requested)?;
+    let corrected = corrected.to_string_lossy().to_string();
+
+    let mut retry = tool_use.clone();
+    retry.input[*key] = serde_json::Value::String(corrected.clone());
+    Some(PathRetry {
+        tool_use: retry,
+        note: format!(
+            "[Note: '{}' does not exist; used '{}' in the workspace instead]",
+            requested, corrected
+        ),
+    })
+}
+
+/// The workspace-relative path `requested` most likely meant, if it does not
+/// exist as given
+///
+/// Leading components are dropped one at a time and the first remaining
+/// path that exists in the workspace wins, so the most specific match is
+/// used.
+pub(super) fn corrected_path(workspace: &Path, requested: &str) -> Option<PathBuf> {
+    let resolved = resolve_path(workspace, requested);
+    if resolved.exists() {
+        return None;
+    }
+
+    // Components after the last `..`; anything before it cannot be trusted
+    let mut components: Vec<&std::ffi::OsStr> = Vec::new();
+    for component in Path::new(requested).components() {
+        match component {
+            Component::Normal(part) => components.push(part),
+            Component::ParentDir => components.clear(),
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+
+    (0..components.len()).find_map(|start| {
+        let relative: PathBuf = components[start..].iter().collect();
+        let candidate = workspace.join(&relative);
+        (candidate != resolved && candidate.exists()).then_some(relative)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{builtin_capabilities, ToolCapabilities};
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src/parser")).unwrap();
+        std::fs::write(temp_dir.path().join("src/parser/mod.rs"), "").unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_corrected_path() {
+        let temp_dir = workspace();
+        let root = temp_dir.path();
+
+        assert_eq!(corrected_path(root, "src/parser/mod.rs"), None, "the path exists");
+        assert_eq!(
+            corrected_path(root, "/home/someone/checkout/src/parser/mod.rs"),
+            Some(PathBuf::from("src/parser/mod.rs"))
+        );
+        assert_eq!(corrected_path(root, "/src/parser"), Some(PathBuf::from("src/parser")));
+        assert_eq!(corrected_path(root, "project/README.md"), Some(PathBuf::from("README.md")));
+        assert_eq!(corrected_path(root, "../../README.md"), Some(PathBuf::from("README.md")));
+        assert_eq!(corrected_path(root, "src/../../docs/README.md"), Some(PathBuf::from("README.md")));
+        assert_eq!(corrected_path(root, "src/lexer.rs"), None);
+    }
+
+    #[test]
+    fn test_only_read_only_tools_are_retried() {
+        let temp_dir = workspace();
+        let call = |name: &str, key: &str| ToolUse {
+            id: "1".to_string(),
+            name: name.to_string(),
+            input: serde_json::json!({ key: "/elsewhere/src/parser/mod.rs", "limit": 5 }),
+        };
+
+        let retry = path_retry(temp_dir.path(), &call("read", "file_path")).unwrap();
+        assert_eq!(retry.tool_use.input["file_path"], "src/parser/mod.rs");
+        assert_eq!(retry.tool_use.input["limit"], 5);
+        assert!(retry.note.contains("'/elsewhere/src/parser/mod.rs' does not exist"));
+
+        assert!(path_retry(temp_dir.path(), &call("grep", "path")).is_some());
+        assert!(path_retry(temp_dir.path(), &call("delete", "path")).is_none());
+        assert!(path_retry(temp_dir.path(), &call("edit", "file_path")).is_none());
+    }
+
+    #[test]
+    fn test_retried_tools_are_read_only() {
+        for (name, _) in PATH_RETRY_TOOLS {
+            assert_eq!(builtin_capabilities(name), ToolCapabilities::READ_ONLY, "{} is retried", name);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Running the grep tool's search with ripgrep
+//!
+//! When `rg` is on PATH the grep tool hands the directory walk and matching
+//! to it: it is much faster on large trees and skips what `.gitignore` files
+//! exclude. Its `--json` output is parsed into the same per-file matches the
+//! built-in search produces, so results read the same whichever engine
+//! found them. The executor falls back to the built-in search when ripgrep
+//! is missing or fails.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// What ripgrep is asked to search for
+pub(super) struct RipgrepQuery<'a> {
+    /// Regex in the syntax of the `regex` crate, which ripgrep shares
+    pub pattern: &'a str,
+    /// File or directory to search
+    pub path: &'a Path,
+    /// Context lines before each match
+    pub before: usize,
+    /// Context lines after each match
+    pub after: usize,
+    /// Follow symbolic links while walking
+    pub follow_symlinks: bool,
+    /// Stop once this many matching lines were found
+    pub max_results: usize,
+}
+
+/// Matches ripgrep found in one file
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct RipgrepFile {
+    /// Matching lines in the file
+    pub matches: usize,
+    /// Matching and context lines in file order as (line number, text, is match)
+    pub lines: Vec<(usize, String, bool)>,
+}
+
+/// One line of `rg --json` output
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum Event {
+    Match(LineEvent),
+    Context(LineEvent),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct LineEvent {
+    path: Text,
+    lines: Text,
+    line_number: Option<usize>,
+}
+
+/// A string in ripgrep's output; names and lines that are not UTF-8 come as
+/// base64 `bytes` instead and are skipped
+#[derive(Deserialize)]
+struct Text {
+    text: Option<String>,
+}
+
+/// Collects files and lines from `rg --json` output fed line by line
+#[derive(Debug, Default)]
+pub(super) struct RipgrepResults {
+    /// Files with matches, by path
+    pub files: BTreeMap<PathBuf, RipgrepFile>,
+    /// Matching lines across every file
+    pub matches: usize,
+}
+
+impl RipgrepResults {
+    /// Add one line of output
+    pub fn push(&mut self, line: &str) {
+        let (event, is_match) = match serde_json::from_str(line) {
+            Ok(Event::Match(event)) => (event, true),
+            Ok(Event::Context(event)) => (event, false),
+            Ok(Event::Other) => return,
+            Err(e) => {
+                tracing::debug!("Skipping unreadable ripgrep output: {}", e);
+                return;
+            }
+        };
+        let (Some(path), Some(text), Some(number)) = (event.path.text, event.lines.text, event.line_number) else {
+            return;
+        };
+
+        let file = self.files.entry(PathBuf::from(path)).or_default();
+        // A match spanning several lines arrives as one event
+        for (offset, text) in text.lines().enumerate() {
+            file.lines.push((number + offset, text.to_string(), is_match));
+            if is_match {
+                file.matches += 1;
+                self.matches += 1;
+            }
+        }
+    }
+}
+
+/// Search with the ripgrep binary at `program`
+///
+/// Reading stops, and ripgrep is killed, once `max_results` matching lines
+/// arrived. Fails when ripgrep cannot run or exits with an error before
+/// reporting anything.
+pub(super) async fn search(program: &Path, query: &RipgrepQuery<'_>) -> std::io::Result<RipgrepResults> {
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(["--json", "--no-config", "--hidden", "--glob", "!.git"]);
+    if query.before > 0 {
+        cmd.arg("--before-context").arg(query.before.to_string());
+    }
+    if query.after > 0 {
+        cmd.arg("--after-context").arg(query.after.to_string());
+    }
+    if query.follow_symlinks {
+        cmd.arg("--follow");
+    }
+    cmd.arg("--regexp")
+        .arg(query.pattern)
+        .arg("--")
+        .arg(query.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = cmd.spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| std::io::Error::other("ripgrep stdout is not captured"))?;
+    let mut lines = BufReader::new(stdout).lines();
+    let mut results = RipgrepResults::default();
+    while let Some(line) = lines.next_line().await? {
+        results.push(&line);
+        if results.matches >= query.max_results {
+            // Dropping the child kills it
+            return Ok(results);
+        }
+    }
+
+    let output = child.wait_with_output().await?;
+    // Exit code 1 means nothing matched; 2 means an error, which only counts
+    // when it left nothing to show
+    if output.status.code() == Some(2) && results.files.is_empty() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_output() {
+        let output = [
+            r#"{"type":"begin","data":{"path":{"text":"src/lib.rs"}}}"#,
+            r#"{"type":"context","data":{"path":{"text":"src/lib.rs"},"lines":{"text":"// lib\n"},"line_number":1,"absolute_offset":0,"submatches":[]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"src/lib.rs"},"lines":{"text":"fn main() {\n"},"line_number":2,"absolute_offset":7,"submatches":[{"match":{"text":"main"},"start":3,"end":7}]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"src/lib.rs"},"binary_offset":null,"stats":{"matched_lines":1}}}"#,
+            r#"{"type":"match","data":{"path":{"bytes":"/w=="},"lines":{"text":"main\n"},"line_number":1,"absolute_offset":0,"submatches":[]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"b.txt"},"lines":{"text":"main one\nmain two\n"},"line_number":4,"absolute_offset":0,"submatches":[]}}"#,
+            r#"{"type":"summary","data":{"elapsed_total":{"secs":0,"nanos":1},"stats":{}}}"#,
+            "not json",
+        ];
+        let mut results = RipgrepResults::default();
+        for line in output {
+            results.push(line);
+        }
+
+        assert_eq!(results.matches, 3);
+        assert_eq!(results.files.len(), 2);
+        assert_eq!(
+            results.files[Path::new("src/lib.rs")],
+            RipgrepFile {
+                matches: 1,
+                lines: vec![(1, "// lib".to_string(), false), (2, "fn main() {".to_string(), true)],
+            }
+        );
+        assert_eq!(
+            results.files[Path::new("b.txt")].lines,
+            vec![(4, "main one".to_string(), true), (5, "main two".to_string(), true)]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Validating tool input against the tool's declared JSON schema
+//!
+//! The executor checks every call against the `input_schema` of its tool
+//! before running it, so a malformed call fails with a list of the offending
+//! fields instead of whichever lookup happens to fail first.
+//!
+//! The checking itself is [`aurora_core::json_schema`]'s; this module turns
+//! its JSON pointers into the dotted field paths the model sees, such as
+//! `edits[1].old_string`.
+
+use super::registry::builtin_registry;
+use aurora_core::json_schema::{self, SchemaError, SchemaErrorKind};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+/// One field of a tool input that does not match the tool's schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// Path to the field, e.g. `edits[1].old_string`; empty for the input itself
+    pub field: String,
+    /// How the field breaks the schema
+    pub kind: SchemaErrorKind,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl From<SchemaError> for SchemaViolation {
+    fn from(error: SchemaError) -> Self {
+        Self {
+            field: field_path(&error.pointer),
+            kind: error.kind,
+            message: error.message,
+        }
+    }
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "input: {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+/// The input schema of the built-in tool `name`
+pub fn tool_schema(name: &str) -> Option<&'static JsonValue> {
+    builtin_registry()
+        .definition(name)
+        .map(|tool| &tool.input_schema)
+}
+
+/// Check `input` against `schema`, returning every violation found
+pub fn validate(schema: &JsonValue, input: &JsonValue) -> Vec<SchemaViolation> {
+    json_schema::validate(input, schema)
+        .into_iter()
+        .map(SchemaViolation::from)
+        .collect()
+}
+
+/// Turn a JSON pointer such as `/edits/1/old` into `edits[1].old`
+fn field_path(pointer: &str) -> String {
+    let mut field = String::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        if segment.parse::<usize>().is_ok() {
+            field.push_str(&format!("[{}]", segment));
+        } else {
+            if !field.is_empty() {
+                field.push('.');
+            }
+            field.push_str(&segment);
+        }
+    }
+    field
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_reports_each_offending_field() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "mode": { "type": "string", "enum": ["fast", "full"] },
+                "limit": { "type": "integer", "minimum": 1 },
+                "edits": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "old": { "type": "string" } },
+                        "required": ["old"]
+                    }
+                },
+                "vars": { "type": "object", "additionalProperties": { "type": "string" } }
+            },
+            "required": ["path", "mode"]
+        });
+
+        assert!(validate(&schema, &json!({ "path": "a", "mode": "fast", "extra": 1 })).is_empty());
+
+        let violations = validate(
+            &schema,
+            &json!({
+                "mode": "slow",
+                "limit": 0,
+                "edits": [{ "old": "x" }, { "old": 3 }, {}],
+                "vars": { "name": true }
+            }),
+        );
+        let fields: Vec<(&str, SchemaErrorKind)> =
+            violations.iter().map(|v| (v.field.as_str(), v.kind)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("edits[1].old", SchemaErrorKind::Type),
+                ("edits[2].old", SchemaErrorKind::Missing),
+                ("limit", SchemaErrorKind::Range),
+                ("mode", SchemaErrorKind::Enum),
+                ("path", SchemaErrorKind::Missing),
+                ("vars.name", SchemaErrorKind::Type),
+            ]
+        );
+        assert_eq!(violations[0].to_string(), "edits[1].old: expected string, found integer");
+
+        let violations = validate(&schema, &json!("path"));
+        assert_eq!(violations[0].to_string(), "input: expected object, found string");
+    }
+
+    #[test]
+    fn test_tool_schemas_cover_builtin_tools() {
+        assert!(tool_schema("read").is_some());
+        assert!(tool_schema("mcp__server__tool").is_none());
+
+        let violations = validate(tool_schema("read").unwrap(), &json!({ "file_path": 42 }));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field, "file_path");
+    }
+}
@@ -0,0 +1,394 @@
+//! Conversation transcripts for offline analysis
+//!
+//! Every agent turn is appended to `.AuroraHeart/transcripts/<session>.jsonl`
+//! as one JSON object per line. Unlike saved sessions, which hold the current
+//! state of a conversation, transcripts are an append-only record of what
+//! happened and when, including tool calls, results, and spend.
+//!
+//! Each line is a [`TranscriptEntry`]: `version`, `timestamp`, `session`, and
+//! `turn` fields, flattened together with a [`TranscriptEvent`] tagged by
+//! `type`. A turn starts with `turn_start` and ends with `turn_end`. The
+//! format is documented in `docs/transcripts.md`; fields are only ever added,
+//! and a breaking change bumps [`TRANSCRIPT_VERSION`].
+
+use crate::client::{AgenticEvent, ContentBlock};
+use crate::conversation::Role;
+use crate::injection::InjectionFinding;
+use crate::usage::{UsageRecord, UsageTotals};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the transcript line format
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// Errors that can occur while writing or reading transcripts
+#[derive(Error, Debug)]
+pub enum TranscriptError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A line could not be written or parsed
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The session ID cannot be used as a file name
+    #[error("Invalid session ID: {0}")]
+    InvalidSession(String),
+}
+
+/// Result type for transcript operations
+pub type Result<T> = std::result::Result<T, TranscriptError>;
+
+/// What happened, as recorded in a transcript line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptEvent {
+    /// A turn began
+    TurnStart {
+        /// Model the turn's requests are sent to
+        model: String,
+    },
+    /// A message from the user or a text reply from the agent
+    Message { role: Role, content: Vec<ContentBlock> },
+    /// The agent called a tool
+    ToolCall { id: String, name: String, input: JsonValue },
+    /// A tool call finished
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        is_error: bool,
+        /// Structured data attached by the tool (e.g. build diagnostics)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<JsonValue>,
+    },
+    /// Possible prompt injections were removed from a tool result
+    InjectionDetected {
+        tool_use_id: String,
+        findings: Vec<InjectionFinding>,
+    },
+    /// The turn paused because the conversation reached its budget
+    BudgetExceeded { tokens: u64, cost_usd: f64 },
+    /// Spend of one model during the turn
+    Usage {
+        model: String,
+        #[serde(flatten)]
+        totals: UsageTotals,
+    },
+    /// The turn finished
+    TurnEnd {
+        /// Error that ended the turn, if it failed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+impl TranscriptEvent {
+    /// The transcript event for an agentic loop event
+    ///
+    /// Live command output is left out; the tool result repeats it.
+    pub fn from_agentic(event: &AgenticEvent) -> Option<Self> {
+        match event {
+            AgenticEvent::ToolCall { id, name, input } => Some(TranscriptEvent::ToolCall {
+                id: id.clone(),
+                name: name.clone(),
+                input: input.clone(),
+            }),
+            AgenticEvent::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+                data,
+            } => Some(TranscriptEvent::ToolResult {
+                tool_use_id: tool_use_id.clone(),
+                content: content.clone(),
+                is_error: is_error.unwrap_or(false),
+                data: data.clone(),
+            }),
+            AgenticEvent::TextResponse { text } => Some(TranscriptEvent::Message {
+                role: Role::Assistant,
+                content: vec![ContentBlock::Text { text: text.clone() }],
+            }),
+            AgenticEvent::InjectionDetected {
+                tool_use_id, findings, ..
+            } => Some(TranscriptEvent::InjectionDetected {
+                tool_use_id: tool_use_id.clone(),
+                findings: findings.clone(),
+            }),
+            AgenticEvent::BudgetExceeded { tokens, cost_usd, .. } => Some(TranscriptEvent::BudgetExceeded {
+                tokens: *tokens,
+                cost_usd: *cost_usd,
+            }),
+            AgenticEvent::ToolOutputChunk { .. } | AgenticEvent::ToolAwaitingInput { .. } => None,
+        }
+    }
+}
+
+/// One line of a transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// Format version, [`TRANSCRIPT_VERSION`] when written
+    pub version: u32,
+    /// When the event happened
+    pub timestamp: DateTime<Utc>,
+    /// Session ID of the conversation
+    pub session: String,
+    /// Turn number within the session, starting at 1
+    pub turn: u64,
+    #[serde(flatten)]
+    pub event: TranscriptEvent,
+}
+
+/// Stores a project's transcripts
+pub struct TranscriptStore {
+    dir: PathBuf,
+}
+
+impl TranscriptStore {
+    /// Create a store for a transcripts directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Create a store for a project's `.AuroraHeart/transcripts` directory
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Self {
+        Self::new(project_root.as_ref().join(".AuroraHeart").join("transcripts"))
+    }
+
+    fn path(&self, session: &str) -> Result<PathBuf> {
+        // Session IDs become file names, so refuse anything that could escape the directory
+        if session.is_empty() || !session.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(TranscriptError::InvalidSession(session.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.jsonl", session)))
+    }
+
+    /// Start a new turn of `session`, sent to `model`
+    pub fn start_turn(&self, session: &str, model: &str) -> Result<TranscriptWriter> {
+        let path = self.path(session)?;
+        // Count leniently so a line cut short by a crash does not stop new turns
+        let turns = match std::fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<TranscriptEntry>(line).ok())
+                .filter(|entry| matches!(entry.event, TranscriptEvent::TurnStart { .. }))
+                .count(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        std::fs::create_dir_all(&self.dir)?;
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let mut writer = TranscriptWriter {
+            file,
+            session: session.to_string(),
+            turn: turns as u64 + 1,
+        };
+        writer.write(TranscriptEvent::TurnStart {
+            model: model.to_string(),
+        })?;
+        Ok(writer)
+    }
+
+    /// All entries of a session's transcript, oldest first
+    pub fn read(&self, session: &str) -> Result<Vec<TranscriptEntry>> {
+        let path = self.path(session)?;
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+        std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+/// Appends the events of one turn to a transcript
+pub struct TranscriptWriter {
+    file: File,
+    session: String,
+    turn: u64,
+}
+
+impl TranscriptWriter {
+    /// Turn number being written
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+
+    /// Append an event, stamped now
+    pub fn write(&mut self, event: TranscriptEvent) -> Result<()> {
+        let entry = TranscriptEntry {
+            version: TRANSCRIPT_VERSION,
+            timestamp: Utc::now(),
+            session: self.session.clone(),
+            turn: self.turn,
+            event,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Append the user's message
+    pub fn user_message(&mut self, text: &str) -> Result<()> {
+        self.write(TranscriptEvent::Message {
+            role: Role::User,
+            content: vec![ContentBlock::Text { text: text.to_string() }],
+        })
+    }
+
+    /// Append an agentic loop event, if it belongs in the transcript
+    pub fn agentic(&mut self, event: &AgenticEvent) -> Result<()> {
+        match TranscriptEvent::from_agentic(event) {
+            Some(event) => self.write(event),
+            None => Ok(()),
+        }
+    }
+
+    /// Append the turn's spend, one line per model
+    pub fn usage(&mut self, records: &[UsageRecord]) -> Result<()> {
+        for record in records {
+            self.write(TranscriptEvent::Usage {
+                model: record.model.clone(),
+                totals: record.totals,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// End the turn, with the error that ended it if it failed
+    pub fn finish(mut self, error: Option<String>) -> Result<()> {
+        self.write(TranscriptEvent::TurnEnd { error })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::{ConversationUsage, Usage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_and_read_turns() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = TranscriptStore::for_project(temp_dir.path());
+
+        let mut writer = store.start_turn("20260301-100000-000", "claude-sonnet-4").unwrap();
+        assert_eq!(writer.turn(), 1);
+        writer.user_message("Read main.rs").unwrap();
+        writer
+            .agentic(&AgenticEvent::ToolCall {
+                id: "toolu_1".to_string(),
+                name: "read".to_string(),
+                input: serde_json::json!({ "file_path": "main.rs" }),
+            })
+            .unwrap();
+        writer
+            .agentic(&AgenticEvent::ToolOutputChunk {
+                tool_use_id: "toolu_1".to_string(),
+                stream: aurora_core::OutputStream::Stdout,
+                text: "fn main".to_string(),
+            })
+            .unwrap();
+        writer
+            .agentic(&AgenticEvent::ToolResult {
+                tool_use_id: "toolu_1".to_string(),
+                content: "fn main() {}".to_string(),
+                is_error: None,
+                data: None,
+            })
+            .unwrap();
+        writer.agentic(&AgenticEvent::TextResponse { text: "It is empty.".to_string() }).unwrap();
+        let mut spent = ConversationUsage::default();
+        spent.record("claude-sonnet-4", &Usage { input_tokens: 900, output_tokens: 40, ..Usage::default() });
+        writer
+            .usage(&UsageRecord::since(&ConversationUsage::default(), &spent, "20260301-100000-000"))
+            .unwrap();
+        writer.finish(None).unwrap();
+
+        let writer = store.start_turn("20260301-100000-000", "claude-sonnet-4").unwrap();
+        assert_eq!(writer.turn(), 2);
+        writer.finish(Some("Rate limit exceeded".to_string())).unwrap();
+
+        let entries = store.read("20260301-100000-000").unwrap();
+        let types: Vec<&str> = entries
+            .iter()
+            .map(|entry| match &entry.event {
+                TranscriptEvent::TurnStart { .. } => "turn_start",
+                TranscriptEvent::Message { .. } => "message",
+                TranscriptEvent::ToolCall { .. } => "tool_call",
+                TranscriptEvent::ToolResult { .. } => "tool_result",
+                TranscriptEvent::InjectionDetected { .. } => "injection_detected",
+                TranscriptEvent::BudgetExceeded { .. } => "budget_exceeded",
+                TranscriptEvent::Usage { .. } => "usage",
+                TranscriptEvent::TurnEnd { .. } => "turn_end",
+            })
+            .collect();
+        assert_eq!(
+            types,
+            ["turn_start", "message", "tool_call", "tool_result", "message", "usage", "turn_end", "turn_start", "turn_end"]
+        );
+        assert!(entries.iter().all(|entry| entry.version == TRANSCRIPT_VERSION));
+        assert_eq!(entries[8].turn, 2);
+
+        assert!(matches!(store.read("../escape"), Err(TranscriptError::InvalidSession(_))));
+    }
+
+    #[test]
+    fn test_line_format_is_stable() {
+        let entry = TranscriptEntry {
+            version: TRANSCRIPT_VERSION,
+            timestamp: "2026-03-01T10:00:00Z".parse().unwrap(),
+            session: "s".to_string(),
+            turn: 3,
+            event: TranscriptEvent::Usage {
+                model: "claude-sonnet-4".to_string(),
+                totals: UsageTotals {
+                    usage: Usage { input_tokens: 10, output_tokens: 2, ..Usage::default() },
+                    cost_usd: 0.5,
+                    requests: 1,
+                },
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_value(&entry).unwrap(),
+            serde_json::json!({
+                "version": 1,
+                "timestamp": "2026-03-01T10:00:00Z",
+                "session": "s",
+                "turn": 3,
+                "type": "usage",
+                "model": "claude-sonnet-4",
+                "usage": {
+                    "input_tokens": 10,
+                    "output_tokens": 2,
+                    "cache_creation_input_tokens": 0,
+                    "cache_read_input_tokens": 0
+                },
+                "cost_usd": 0.5,
+                "requests": 1
+            })
+        );
+
+        let user: TranscriptEntry = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "timestamp": "2026-03-01T10:00:00Z",
+            "session": "s",
+            "turn": 1,
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "text", "text": "Hi" }]
+        }))
+        .unwrap();
+        assert!(matches!(user.event, TranscriptEvent::Message { role: Role::User, .. }));
+    }
+}
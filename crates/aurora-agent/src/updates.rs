@@ -0,0 +1,692 @@
+//! Application updates
+//!
+//! Releases are described by a JSON manifest per channel, read from
+//! `<endpoint>/<channel>/latest.json`:
+//!
+//! ```json
+//! {
+//!   "version": "0.2.0",
+//!   "notes": "Faster indexing",
+//!   "pub_date": "2026-10-01T12:00:00Z",
+//!   "platforms": {
+//!     "linux-x86_64": { "url": "https://…/auroraheart", "signature": "<base64>" }
+//!   }
+//! }
+//! ```
+//!
+//! Each platform's binary is signed with the release Ed25519 key, whose
+//! public half is compiled in from `AURORA_UPDATE_PUBLIC_KEY` at build time;
+//! builds without it never offer updates. The signature covers the version
+//! and platform as well as the binary (see [`signed_payload`]), so an older
+//! signed build cannot be passed off as a newer release. A download whose
+//! signature does not verify is rejected before it touches the disk, and the
+//! staged binary is verified again before it replaces the running
+//! executable. Verified binaries are staged in the user config directory and
+//! swapped in when the app restarts, so nothing has to be reinstalled by hand.
+//!
+//! The release endpoint and channel come from the user's `update.toml`,
+//! never from a project's configuration.
+
+use aurora_core::{user_config_dir, UpdateChannel, UpdateConfig};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Version of the running build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Base64 Ed25519 public key release binaries are signed with
+pub const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("AURORA_UPDATE_PUBLIC_KEY");
+
+/// Largest release binary that is downloaded
+pub const MAX_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+
+/// File describing the staged update inside the staging directory
+const STAGED_FILE: &str = "staged.json";
+
+/// Errors that can occur while checking for, downloading, or applying updates
+#[derive(Error, Debug)]
+pub enum UpdateError {
+    /// HTTP request error
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Release server returned an error response
+    #[error("Update server error ({status}): {message}")]
+    Api { status: u16, message: String },
+
+    /// IO error while staging or applying an update
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Manifest or staging record could not be parsed or written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// No endpoint is configured, or the build has no release public key
+    #[error("Updates are not configured: {0}")]
+    NotConfigured(&'static str),
+
+    /// The release public key is not a base64 Ed25519 key
+    #[error("Invalid update public key")]
+    InvalidPublicKey,
+
+    /// The download is larger than [`MAX_DOWNLOAD_BYTES`]
+    #[error("Update download exceeds {0} bytes")]
+    TooLarge(u64),
+
+    /// The release is not newer than the running build
+    #[error("Refusing to install {version}, which is not newer than {current}")]
+    NotNewer { version: String, current: String },
+
+    /// A downloaded binary's signature did not verify
+    #[error("Signature verification failed for version {0}")]
+    InvalidSignature(String),
+
+    /// A version string could not be parsed
+    #[error("Invalid version: {0}")]
+    InvalidVersion(String),
+
+    /// The download was cancelled
+    #[error("Update download cancelled")]
+    Cancelled,
+
+    /// The release has no binary for this platform
+    #[error("Release {version} has no build for {platform}")]
+    UnsupportedPlatform { version: String, platform: String },
+}
+
+pub type Result<T> = std::result::Result<T, UpdateError>;
+
+/// A semantic version (`1.2.3` or `1.2.3-beta.1`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release identifiers (`beta.1` → `["beta", "1"]`), empty for releases
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    /// Parse a version, ignoring a leading `v` and any `+build` metadata
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = || UpdateError::InvalidVersion(text.to_string());
+        let trimmed = text.trim();
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+        let trimmed = trimmed.split('+').next().unwrap_or(trimmed);
+        let (core, pre) = match trimmed.split_once('-') {
+            Some((core, pre)) => (core, pre.split('.').map(str::to_string).collect()),
+            None => (trimmed, Vec::new()),
+        };
+
+        let mut numbers = core.split('.').map(|part| part.parse::<u64>().map_err(|_| invalid()));
+        let version = Self {
+            major: numbers.next().ok_or_else(invalid)??,
+            minor: numbers.next().ok_or_else(invalid)??,
+            patch: numbers.next().ok_or_else(invalid)??,
+            pre,
+        };
+        let valid_identifier =
+            |id: &String| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if numbers.next().is_some() || !version.pre.iter().all(valid_identifier) {
+            return Err(invalid());
+        }
+        Ok(version)
+    }
+
+    /// Whether this is a pre-release
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                // A release ranks above its pre-releases
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    for (a, b) in self.pre.iter().zip(&other.pre) {
+                        let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+                            (Ok(a), Ok(b)) => a.cmp(&b),
+                            (Ok(_), Err(_)) => Ordering::Less,
+                            (Err(_), Ok(_)) => Ordering::Greater,
+                            (Err(_), Err(_)) => a.cmp(b),
+                        };
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+                    }
+                    self.pre.len().cmp(&other.pre.len())
+                }
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.is_prerelease() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// Release manifest for one channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateManifest {
+    pub version: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub pub_date: Option<String>,
+    /// Builds keyed by platform (see [`platform_key`])
+    #[serde(default)]
+    pub platforms: BTreeMap<String, PlatformBuild>,
+}
+
+/// One platform's build in a release manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformBuild {
+    /// Download URL of the executable
+    pub url: String,
+    /// Base64 Ed25519 signature of the [`signed_payload`] for the executable
+    pub signature: String,
+}
+
+/// A newer release available for this platform
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    pub url: String,
+    pub signature: String,
+}
+
+/// An update downloaded, verified, and waiting for a restart
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedUpdate {
+    pub version: String,
+    /// The verified executable
+    pub path: PathBuf,
+    /// Signature it was verified with, checked again before it is applied
+    #[serde(default)]
+    pub signature: String,
+    /// When the update was staged, in milliseconds since the Unix epoch
+    pub staged_at: u64,
+}
+
+/// Manifest key of the running platform, e.g. `linux-x86_64`
+pub fn platform_key() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// The bytes a release signature covers: the version and platform the
+/// binary was published as, then the binary itself
+pub fn signed_payload(version: &str, platform: &str, executable: &[u8]) -> Vec<u8> {
+    let mut payload = format!("auroraheart-update\0{}\0{}\0", version, platform).into_bytes();
+    payload.extend_from_slice(executable);
+    payload
+}
+
+/// Decode a base64 Ed25519 public key
+fn decode_public_key(public_key: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(public_key.trim())
+        .ok()
+        .filter(|key| key.len() == 32)
+        .ok_or(UpdateError::InvalidPublicKey)
+}
+
+/// The compiled-in [`RELEASE_PUBLIC_KEY`]
+fn release_public_key() -> Result<Vec<u8>> {
+    decode_public_key(RELEASE_PUBLIC_KEY.ok_or(UpdateError::NotConfigured("this build has no release public key"))?)
+}
+
+/// Check a base64 Ed25519 `signature` of `data` against `public_key`
+pub fn verify_signature(public_key: &[u8], data: &[u8], signature: &str) -> bool {
+    BASE64
+        .decode(signature.trim())
+        .is_ok_and(|signature| UnparsedPublicKey::new(&ED25519, public_key).verify(data, &signature).is_ok())
+}
+
+/// Checks for and downloads releases of one channel
+pub struct UpdateClient {
+    client: reqwest::Client,
+    endpoint: String,
+    channel: UpdateChannel,
+    public_key: Vec<u8>,
+}
+
+impl UpdateClient {
+    /// Create a client from the user's update settings, verifying releases
+    /// with the compiled-in [`RELEASE_PUBLIC_KEY`]
+    pub fn new(config: &UpdateConfig) -> Result<Self> {
+        if config.endpoint.is_none() {
+            return Err(UpdateError::NotConfigured("set `endpoint` in update.toml"));
+        }
+        Self::with_public_key(config, release_public_key()?)
+    }
+
+    /// Create a client that verifies releases with `public_key` instead
+    fn with_public_key(config: &UpdateConfig, public_key: Vec<u8>) -> Result<Self> {
+        let endpoint = config
+            .endpoint
+            .as_deref()
+            .ok_or(UpdateError::NotConfigured("set `endpoint` in update.toml"))?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            channel: config.channel,
+            public_key,
+        })
+    }
+
+    /// Fetch the channel's manifest
+    pub async fn manifest(&self) -> Result<UpdateManifest> {
+        let url = format!("{}/{}/latest.json", self.endpoint, self.channel.as_str());
+        let response = check_status(self.client.get(url).send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    /// The channel's latest release, if it is newer than `current_version`
+    ///
+    /// The stable channel never offers pre-releases.
+    pub async fn check(&self, current_version: &str) -> Result<Option<AvailableUpdate>> {
+        let manifest = self.manifest().await?;
+        let latest = Version::parse(&manifest.version)?;
+        let current = Version::parse(current_version)?;
+        if latest <= current || (self.channel == UpdateChannel::Stable && latest.is_prerelease()) {
+            return Ok(None);
+        }
+
+        let platform = platform_key();
+        let build = manifest
+            .platforms
+            .get(&platform)
+            .ok_or_else(|| UpdateError::UnsupportedPlatform {
+                version: manifest.version.clone(),
+                platform,
+            })?;
+        Ok(Some(AvailableUpdate {
+            version: manifest.version.clone(),
+            current_version: current_version.to_string(),
+            channel: self.channel,
+            notes: manifest.notes,
+            pub_date: manifest.pub_date,
+            url: build.url.clone(),
+            signature: build.signature.clone(),
+        }))
+    }
+
+    /// Download `update`, verify its signature, and stage it for the next restart
+    ///
+    /// Only releases newer than this build are downloaded, and downloads
+    /// stop at [`MAX_DOWNLOAD_BYTES`]. `on_progress` is called with the bytes
+    /// received so far and the total size when the server reports it;
+    /// returning false cancels the download.
+    pub async fn download(
+        &self,
+        update: &AvailableUpdate,
+        stager: &UpdateStager,
+        mut on_progress: impl FnMut(u64, Option<u64>) -> bool,
+    ) -> Result<StagedUpdate> {
+        if Version::parse(&update.version)? <= Version::parse(CURRENT_VERSION)? {
+            return Err(UpdateError::NotNewer {
+                version: update.version.clone(),
+                current: CURRENT_VERSION.to_string(),
+            });
+        }
+
+        let mut response = check_status(self.client.get(&update.url).send().await?).await?;
+        let total = response.content_length();
+        if total.is_some_and(|total| total > MAX_DOWNLOAD_BYTES) {
+            return Err(UpdateError::TooLarge(MAX_DOWNLOAD_BYTES));
+        }
+        let mut bytes = Vec::with_capacity(total.unwrap_or(0) as usize);
+        while let Some(chunk) = response.chunk().await? {
+            if (bytes.len() + chunk.len()) as u64 > MAX_DOWNLOAD_BYTES {
+                return Err(UpdateError::TooLarge(MAX_DOWNLOAD_BYTES));
+            }
+            bytes.extend_from_slice(&chunk);
+            if !on_progress(bytes.len() as u64, total) {
+                return Err(UpdateError::Cancelled);
+            }
+        }
+        let payload = signed_payload(&update.version, &platform_key(), &bytes);
+        if !verify_signature(&self.public_key, &payload, &update.signature) {
+            return Err(UpdateError::InvalidSignature(update.version.clone()));
+        }
+        stager.stage(&update.version, &bytes, &update.signature)
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status().as_u16();
+    let message = response.text().await.unwrap_or_default();
+    Err(UpdateError::Api { status, message })
+}
+
+/// Holds a verified update until it is applied
+pub struct UpdateStager {
+    dir: PathBuf,
+    /// Key staged updates are verified with before they are applied;
+    /// [`RELEASE_PUBLIC_KEY`] unless replaced
+    public_key: Option<Vec<u8>>,
+}
+
+impl UpdateStager {
+    /// The current user's staging directory, or `None` if there is no config directory
+    pub fn for_user() -> Option<Self> {
+        user_config_dir().map(|dir| Self::at(dir.join("updates")))
+    }
+
+    /// A staging directory at `dir`
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            public_key: None,
+        }
+    }
+
+    /// Verify staged updates with `public_key` instead of the release key
+    #[cfg(test)]
+    fn with_public_key(mut self, public_key: Vec<u8>) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// Write a verified executable, replacing any update staged before
+    pub fn stage(&self, version: &str, executable: &[u8], signature: &str) -> Result<StagedUpdate> {
+        let parsed = Version::parse(version)?;
+        self.discard()?;
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("auroraheart-{}{}", parsed, std::env::consts::EXE_SUFFIX));
+        std::fs::write(&path, executable)?;
+
+        let staged = StagedUpdate {
+            version: version.to_string(),
+            path,
+            signature: signature.to_string(),
+            staged_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        };
+        std::fs::write(self.dir.join(STAGED_FILE), serde_json::to_string_pretty(&staged)?)?;
+        Ok(staged)
+    }
+
+    /// The staged update, if one is waiting and its executable still exists
+    pub fn pending(&self) -> Result<Option<StagedUpdate>> {
+        let record = self.dir.join(STAGED_FILE);
+        if !record.exists() {
+            return Ok(None);
+        }
+        let staged: StagedUpdate = serde_json::from_str(&std::fs::read_to_string(record)?)?;
+        Ok(staged.path.is_file().then_some(staged))
+    }
+
+    /// Remove the staged update
+    pub fn discard(&self) -> Result<()> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replace `executable` with the staged update
+    ///
+    /// The staged binary's signature is checked again first, and an update
+    /// that no longer verifies is discarded. The running executable is moved
+    /// aside to `<name>.old` rather than overwritten, which works while it is
+    /// running on every platform, and is moved back if the new one cannot be
+    /// put in place. Returns the version applied, or `None` if nothing was
+    /// staged.
+    pub fn apply(&self, executable: &Path) -> Result<Option<String>> {
+        let Some(staged) = self.pending()? else {
+            return Ok(None);
+        };
+        let public_key = match &self.public_key {
+            Some(public_key) => public_key.clone(),
+            None => release_public_key()?,
+        };
+        let payload = signed_payload(&staged.version, &platform_key(), &std::fs::read(&staged.path)?);
+        if !verify_signature(&public_key, &payload, &staged.signature) {
+            self.discard()?;
+            return Err(UpdateError::InvalidSignature(staged.version));
+        }
+
+        let mut backup = executable.as_os_str().to_owned();
+        backup.push(".old");
+        let backup = PathBuf::from(backup);
+        if backup.exists() {
+            std::fs::remove_file(&backup)?;
+        }
+        std::fs::rename(executable, &backup)?;
+        if let Err(e) = install(&staged.path, executable) {
+            std::fs::rename(&backup, executable)?;
+            return Err(e.into());
+        }
+
+        self.discard()?;
+        Ok(Some(staged.version))
+    }
+}
+
+/// Copy `source` to `target` as an executable
+fn install(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::fs::copy(source, target)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(target, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use tempfile::TempDir;
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn client(server: &mockito::Server, channel: UpdateChannel, keys: &Ed25519KeyPair) -> UpdateClient {
+        let config = UpdateConfig {
+            channel,
+            endpoint: Some(server.url()),
+            ..UpdateConfig::default()
+        };
+        UpdateClient::with_public_key(&config, keys.public_key().as_ref().to_vec()).unwrap()
+    }
+
+    fn manifest(version: &str, url: &str, signature: &str) -> String {
+        serde_json::json!({
+            "version": version,
+            "notes": "Notes",
+            "platforms": { (platform_key()): { "url": url, "signature": signature } }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let v = |text| Version::parse(text).unwrap();
+        assert!(v("0.2.0") > v("0.1.9"));
+        assert!(v("1.0.0") > v("1.0.0-beta.2"));
+        assert!(v("1.0.0-beta.10") > v("1.0.0-beta.2"));
+        assert!(v("1.0.0-beta") > v("1.0.0-alpha.5"));
+        assert!(v("1.0.0-beta.1") > v("1.0.0-beta"));
+        assert_eq!(v("v1.2.3+build.7"), v("1.2.3"));
+        assert_eq!(v("1.2.3-rc.1").to_string(), "1.2.3-rc.1");
+        assert!(Version::parse("1.2").is_err());
+        assert!(Version::parse("1.2.3-").is_err());
+        assert!(Version::parse("1.2.3-../../bin").is_err());
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let keys = key_pair();
+        let signature = BASE64.encode(keys.sign(b"binary").as_ref());
+        let public_key = keys.public_key().as_ref();
+        assert!(verify_signature(public_key, b"binary", &signature));
+        assert!(!verify_signature(public_key, b"tampered", &signature));
+        assert!(!verify_signature(public_key, b"binary", "not base64!"));
+        assert!(!verify_signature(key_pair().public_key().as_ref(), b"binary", &signature));
+    }
+
+    #[test]
+    fn test_client_requires_configuration() {
+        let missing = UpdateClient::new(&UpdateConfig::default());
+        assert!(matches!(missing, Err(UpdateError::NotConfigured(_))));
+        assert!(matches!(decode_public_key("c2hvcnQ="), Err(UpdateError::InvalidPublicKey)));
+        if RELEASE_PUBLIC_KEY.is_none() {
+            let unsigned_build = UpdateClient::new(&UpdateConfig {
+                endpoint: Some("https://releases.example.com".to_string()),
+                ..UpdateConfig::default()
+            });
+            assert!(matches!(unsigned_build, Err(UpdateError::NotConfigured(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_respects_channel() {
+        let keys = key_pair();
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/stable/latest.json")
+            .with_body(manifest("0.3.0-beta.1", "https://example.com/bin", "sig"))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/beta/latest.json")
+            .with_body(manifest("0.3.0-beta.1", "https://example.com/bin", "sig"))
+            .create_async()
+            .await;
+
+        let stable = client(&server, UpdateChannel::Stable, &keys);
+        assert_eq!(stable.check("0.2.0").await.unwrap(), None, "stable skips pre-releases");
+
+        let beta = client(&server, UpdateChannel::Beta, &keys);
+        let update = beta.check("0.2.0").await.unwrap().unwrap();
+        assert_eq!(update.version, "0.3.0-beta.1");
+        assert_eq!(update.channel, UpdateChannel::Beta);
+        assert_eq!(beta.check("0.3.0").await.unwrap(), None, "not newer");
+    }
+
+    #[tokio::test]
+    async fn test_download_stage_and_apply() {
+        let keys = key_pair();
+        let temp_dir = TempDir::new().unwrap();
+        let stager = UpdateStager::at(temp_dir.path().join("updates")).with_public_key(keys.public_key().as_ref().to_vec());
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/bin/good")
+            .with_body("new binary")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/bin/tampered")
+            .with_body("evil binary")
+            .create_async()
+            .await;
+
+        let client = client(&server, UpdateChannel::Stable, &keys);
+        let payload = signed_payload("9.2.0", &platform_key(), b"new binary");
+        let signature = BASE64.encode(keys.sign(&payload).as_ref());
+        let update = |path: &str| AvailableUpdate {
+            version: "9.2.0".to_string(),
+            current_version: "0.1.0".to_string(),
+            channel: UpdateChannel::Stable,
+            notes: None,
+            pub_date: None,
+            url: format!("{}{}", server.url(), path),
+            signature: signature.clone(),
+        };
+
+        let rejected = client.download(&update("/bin/tampered"), &stager, |_, _| true).await;
+        assert!(matches!(rejected, Err(UpdateError::InvalidSignature(_))));
+
+        // The signature is bound to the version it was published as
+        let relabeled = AvailableUpdate {
+            version: "9.3.0".to_string(),
+            ..update("/bin/good")
+        };
+        let rejected = client.download(&relabeled, &stager, |_, _| true).await;
+        assert!(matches!(rejected, Err(UpdateError::InvalidSignature(_))));
+        let rollback = AvailableUpdate {
+            version: "0.0.1".to_string(),
+            ..update("/bin/good")
+        };
+        let rejected = client.download(&rollback, &stager, |_, _| true).await;
+        assert!(matches!(rejected, Err(UpdateError::NotNewer { .. })));
+
+        let cancelled = client.download(&update("/bin/good"), &stager, |_, _| false).await;
+        assert!(matches!(cancelled, Err(UpdateError::Cancelled)));
+        assert_eq!(stager.pending().unwrap(), None);
+
+        let mut received = 0;
+        let staged = client
+            .download(&update("/bin/good"), &stager, |done, total| {
+                received = done;
+                assert_eq!(total, Some(10));
+                true
+            })
+            .await
+            .unwrap();
+        assert_eq!(received, 10);
+        assert_eq!(stager.pending().unwrap().as_ref(), Some(&staged));
+
+        let executable = temp_dir.path().join("auroraheart");
+        std::fs::write(&executable, "old binary").unwrap();
+
+        // A staged binary changed after verification is not applied
+        std::fs::write(&staged.path, "swapped binary").unwrap();
+        assert!(matches!(stager.apply(&executable), Err(UpdateError::InvalidSignature(_))));
+        assert_eq!(std::fs::read_to_string(&executable).unwrap(), "old binary");
+        assert_eq!(stager.pending().unwrap(), None);
+        assert!(stager.stage("1.0.0-../x", b"new binary", &signature).is_err());
+
+        let staged = client.download(&update("/bin/good"), &stager, |_, _| true).await.unwrap();
+        assert_eq!(staged.path.file_name().unwrap(), format!("auroraheart-9.2.0{}", std::env::consts::EXE_SUFFIX).as_str());
+        assert_eq!(stager.apply(&executable).unwrap().as_deref(), Some("9.2.0"));
+        assert_eq!(std::fs::read_to_string(&executable).unwrap(), "new binary");
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("auroraheart.old")).unwrap(),
+            "old binary"
+        );
+        assert_eq!(stager.pending().unwrap(), None);
+        assert_eq!(stager.apply(&executable).unwrap(), None);
+    }
+}
@@ -0,0 +1,577 @@
+//! Token usage and cost tracking
+//!
+//! Every API response reports the tokens it used. A [`ConversationUsage`]
+//! adds them up for one conversation and prices them with [`ModelPricing`],
+//! so the agentic loop can pause once the conversation reaches its
+//! [`BudgetConfig`] limits.
+//!
+//! Prices are the published list prices per million tokens for each model
+//! family; models that are not recognized are priced as Sonnet.
+//!
+//! Spend is also appended to the project's usage store,
+//! `.AuroraHeart/usage.jsonl`, one [`UsageRecord`] per model per turn. A
+//! [`UsageReport`] aggregates the store by day, model, and conversation for
+//! the analytics dashboard. Days are UTC dates.
+
+use aurora_core::BudgetConfig;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the usage store in the project's `.AuroraHeart` directory
+pub const USAGE_FILE: &str = "usage.jsonl";
+
+/// Errors that can occur while reading or writing the usage store
+#[derive(Error, Debug)]
+pub enum UsageError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A record could not be written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Result type for usage store operations
+pub type Result<T> = std::result::Result<T, UsageError>;
+
+/// Tokens used by one or more requests, as reported by the API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Uncached input tokens
+    #[serde(default, deserialize_with = "zero_if_null")]
+    pub input_tokens: u64,
+    /// Generated tokens
+    #[serde(default, deserialize_with = "zero_if_null")]
+    pub output_tokens: u64,
+    /// Input tokens written to the prompt cache
+    #[serde(default, deserialize_with = "zero_if_null")]
+    pub cache_creation_input_tokens: u64,
+    /// Input tokens read from the prompt cache
+    #[serde(default, deserialize_with = "zero_if_null")]
+    pub cache_read_input_tokens: u64,
+}
+
+fn zero_if_null<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    Ok(Option::<u64>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl Usage {
+    /// All input and output tokens
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+
+    /// Whether no tokens were reported
+    pub fn is_empty(&self) -> bool {
+        self.total_tokens() == 0
+    }
+
+    /// Take the larger count of each kind
+    ///
+    /// Streaming events report running totals for the same request, so later
+    /// counts replace earlier ones rather than adding to them.
+    pub fn max(self, other: Usage) -> Usage {
+        Usage {
+            input_tokens: self.input_tokens.max(other.input_tokens),
+            output_tokens: self.output_tokens.max(other.output_tokens),
+            cache_creation_input_tokens: self.cache_creation_input_tokens.max(other.cache_creation_input_tokens),
+            cache_read_input_tokens: self.cache_read_input_tokens.max(other.cache_read_input_tokens),
+        }
+    }
+
+    /// Tokens in `self` beyond those in `earlier`
+    pub fn saturating_sub(self, earlier: Usage) -> Usage {
+        Usage {
+            input_tokens: self.input_tokens.saturating_sub(earlier.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(earlier.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .saturating_sub(earlier.cache_creation_input_tokens),
+            cache_read_input_tokens: self.cache_read_input_tokens.saturating_sub(earlier.cache_read_input_tokens),
+        }
+    }
+
+    /// Cost of these tokens in US dollars on `model`
+    pub fn cost_usd(&self, model: &str) -> f64 {
+        let pricing = ModelPricing::for_model(model);
+        (self.input_tokens as f64 * pricing.input
+            + self.output_tokens as f64 * pricing.output
+            + self.cache_creation_input_tokens as f64 * pricing.cache_write
+            + self.cache_read_input_tokens as f64 * pricing.cache_read)
+            / 1_000_000.0
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, other: Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+}
+
+/// Prices in US dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    /// Uncached input tokens
+    pub input: f64,
+    /// Generated tokens
+    pub output: f64,
+    /// Input tokens written to the prompt cache
+    pub cache_write: f64,
+    /// Input tokens read from the prompt cache
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    /// Pricing for a model, by family
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_lowercase();
+        if model.contains("opus") {
+            Self { input: 15.0, output: 75.0, cache_write: 18.75, cache_read: 1.5 }
+        } else if model.contains("haiku") {
+            Self { input: 0.8, output: 4.0, cache_write: 1.0, cache_read: 0.08 }
+        } else {
+            Self { input: 3.0, output: 15.0, cache_write: 3.75, cache_read: 0.3 }
+        }
+    }
+}
+
+/// Tokens, cost, and request count added up over some requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Tokens used
+    pub usage: Usage,
+    /// Cost in US dollars
+    pub cost_usd: f64,
+    /// Number of requests
+    pub requests: u64,
+}
+
+impl AddAssign for UsageTotals {
+    fn add_assign(&mut self, other: UsageTotals) {
+        self.usage += other.usage;
+        self.cost_usd += other.cost_usd;
+        self.requests += other.requests;
+    }
+}
+
+/// Tokens and cost spent by a conversation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationUsage {
+    /// Tokens used by all requests
+    pub usage: Usage,
+    /// Cost of all requests in US dollars
+    pub cost_usd: f64,
+    /// Number of requests made
+    pub requests: u64,
+    /// Spend of each model used
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_model: BTreeMap<String, UsageTotals>,
+}
+
+impl ConversationUsage {
+    /// Add the usage of one request to `model`
+    pub fn record(&mut self, model: &str, usage: &Usage) {
+        let cost_usd = usage.cost_usd(model);
+        self.usage += *usage;
+        self.cost_usd += cost_usd;
+        self.requests += 1;
+        *self.by_model.entry(model.to_string()).or_default() += UsageTotals {
+            usage: *usage,
+            cost_usd,
+            requests: 1,
+        };
+    }
+
+    /// Spend of each model since `earlier`, a previous copy of this usage
+    pub fn since(&self, earlier: &ConversationUsage) -> Vec<(String, UsageTotals)> {
+        self.by_model
+            .iter()
+            .filter_map(|(model, totals)| {
+                let before = earlier.by_model.get(model).copied().unwrap_or_default();
+                let requests = totals.requests.saturating_sub(before.requests);
+                (requests > 0).then(|| {
+                    let totals = UsageTotals {
+                        usage: totals.usage.saturating_sub(before.usage),
+                        cost_usd: (totals.cost_usd - before.cost_usd).max(0.0),
+                        requests,
+                    };
+                    (model.clone(), totals)
+                })
+            })
+            .collect()
+    }
+
+    /// Whether nothing has been spent
+    pub fn is_empty(&self) -> bool {
+        self.requests == 0
+    }
+
+    /// Whether the spend has reached either limit of `budget`
+    pub fn exceeds(&self, budget: &BudgetConfig) -> bool {
+        budget.max_tokens.is_some_and(|max| self.usage.total_tokens() >= max)
+            || budget.max_cost_usd.is_some_and(|max| self.cost_usd >= max)
+    }
+
+    /// `budget` with each limit raised by its own amount beyond the current
+    /// spend, giving a paused conversation the same allowance again
+    pub fn extend(&self, budget: &BudgetConfig) -> BudgetConfig {
+        BudgetConfig {
+            max_tokens: budget.max_tokens.map(|max| self.usage.total_tokens().max(max) + max),
+            max_cost_usd: budget.max_cost_usd.map(|max| self.cost_usd.max(max) + max),
+        }
+    }
+}
+
+/// Spend of one model in one conversation, as stored in the usage store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// When the spend was recorded
+    pub timestamp: DateTime<Utc>,
+    /// Session ID of the conversation
+    pub conversation: String,
+    /// Model the requests were sent to
+    pub model: String,
+    /// Tokens, cost, and request count
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+impl UsageRecord {
+    /// Records, stamped now, for what `conversation` spent between `earlier`
+    /// and `current`
+    pub fn since(earlier: &ConversationUsage, current: &ConversationUsage, conversation: &str) -> Vec<Self> {
+        let timestamp = Utc::now();
+        current
+            .since(earlier)
+            .into_iter()
+            .map(|(model, totals)| Self {
+                timestamp,
+                conversation: conversation.to_string(),
+                model,
+                totals,
+            })
+            .collect()
+    }
+}
+
+/// Dates covered by a usage report, both inclusive
+///
+/// A missing `from` starts at the first recorded day and a missing `to`
+/// ends today.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageRange {
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+}
+
+impl UsageRange {
+    /// The last `days` days, ending with `today`
+    pub fn last_days(days: u64, today: NaiveDate) -> Self {
+        Self {
+            from: today.checked_sub_days(Days::new(days.saturating_sub(1))),
+            to: Some(today),
+        }
+    }
+
+    /// Whether `date` is in the range
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+}
+
+/// Spend on one day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Spend of one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Spend of one conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationUsageSummary {
+    /// Session ID
+    pub conversation: String,
+    /// Session title, when known
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Most recent spend in the range
+    pub last_used: DateTime<Utc>,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Usage aggregated for the analytics dashboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Dates the report covers, with open ends filled in
+    pub range: UsageRange,
+    /// Spend over the whole range
+    pub total: UsageTotals,
+    /// Every day of the range in order, including days without spend
+    pub days: Vec<DailyUsage>,
+    /// Models, most expensive first
+    pub models: Vec<ModelUsage>,
+    /// Conversations, most recently used first
+    pub conversations: Vec<ConversationUsageSummary>,
+}
+
+impl UsageReport {
+    /// Aggregate the records that fall in `range`, ending an open range on `today`
+    pub fn build(records: &[UsageRecord], range: UsageRange, today: NaiveDate) -> Self {
+        let records: Vec<&UsageRecord> = records
+            .iter()
+            .filter(|record| range.contains(record.timestamp.date_naive()))
+            .collect();
+
+        let first_day = records.iter().map(|record| record.timestamp.date_naive()).min();
+        let range = UsageRange {
+            from: range.from.or(first_day),
+            to: range.to.or(Some(today)),
+        };
+
+        let mut total = UsageTotals::default();
+        let mut days: BTreeMap<NaiveDate, UsageTotals> = BTreeMap::new();
+        let mut models: HashMap<&str, UsageTotals> = HashMap::new();
+        let mut conversations: HashMap<&str, (DateTime<Utc>, UsageTotals)> = HashMap::new();
+        for record in &records {
+            total += record.totals;
+            *days.entry(record.timestamp.date_naive()).or_default() += record.totals;
+            *models.entry(&record.model).or_default() += record.totals;
+            let (last_used, totals) = conversations
+                .entry(&record.conversation)
+                .or_insert((record.timestamp, UsageTotals::default()));
+            *last_used = (*last_used).max(record.timestamp);
+            *totals += record.totals;
+        }
+
+        // Fill in days without spend so the series can be charted directly
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            for date in from.iter_days().take_while(|date| *date <= to) {
+                days.entry(date).or_default();
+            }
+        }
+
+        let mut models: Vec<ModelUsage> = models
+            .into_iter()
+            .map(|(model, totals)| ModelUsage {
+                model: model.to_string(),
+                totals,
+            })
+            .collect();
+        models.sort_by(|a, b| b.totals.cost_usd.total_cmp(&a.totals.cost_usd).then_with(|| a.model.cmp(&b.model)));
+
+        let mut conversations: Vec<ConversationUsageSummary> = conversations
+            .into_iter()
+            .map(|(conversation, (last_used, totals))| ConversationUsageSummary {
+                conversation: conversation.to_string(),
+                title: None,
+                last_used,
+                totals,
+            })
+            .collect();
+        conversations.sort_by(|a, b| b.last_used.cmp(&a.last_used).then_with(|| a.conversation.cmp(&b.conversation)));
+
+        Self {
+            range,
+            total,
+            days: days
+                .into_iter()
+                .map(|(date, totals)| DailyUsage { date, totals })
+                .collect(),
+            models,
+            conversations,
+        }
+    }
+}
+
+/// Appends and reads a project's usage records
+pub struct UsageStore {
+    path: PathBuf,
+}
+
+impl UsageStore {
+    /// Create a store for a usage file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Create a store for a project's `.AuroraHeart/usage.jsonl`
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Self {
+        Self::new(project_root.as_ref().join(".AuroraHeart").join(USAGE_FILE))
+    }
+
+    /// Append records to the store
+    pub fn append(&self, records: &[UsageRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /// All stored records, skipping lines that cannot be parsed
+    pub fn records(&self) -> Result<Vec<UsageRecord>> {
+        if !self.path.is_file() {
+            return Ok(Vec::new());
+        }
+        Ok(std::fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    tracing::warn!("Skipping invalid usage record: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Aggregate the stored records in `range`
+    pub fn report(&self, range: UsageRange) -> Result<UsageReport> {
+        Ok(UsageReport::build(&self.records()?, range, Utc::now().date_naive()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(timestamp: &str, conversation: &str, model: &str, input_tokens: u64) -> UsageRecord {
+        let usage = Usage { input_tokens, ..Usage::default() };
+        UsageRecord {
+            timestamp: timestamp.parse().unwrap(),
+            conversation: conversation.to_string(),
+            model: model.to_string(),
+            totals: UsageTotals { usage, cost_usd: usage.cost_usd(model), requests: 1 },
+        }
+    }
+
+    #[test]
+    fn test_parse_and_price_usage() {
+        let usage: Usage = serde_json::from_value(serde_json::json!({
+            "input_tokens": 1_000_000,
+            "output_tokens": 100_000,
+            "cache_creation_input_tokens": null,
+            "cache_read_input_tokens": 1_000_000
+        }))
+        .unwrap();
+        assert_eq!(usage.total_tokens(), 2_100_000);
+
+        assert!((usage.cost_usd("claude-sonnet-4-20250514") - 4.8).abs() < 1e-9);
+        assert!((usage.cost_usd("claude-opus-4-1") - 24.0).abs() < 1e-9);
+        assert!((usage.cost_usd("claude-3-5-haiku-latest") - 1.28).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_budget_limits_and_extension() {
+        let mut spent = ConversationUsage::default();
+        let budget = BudgetConfig {
+            max_tokens: Some(10_000),
+            max_cost_usd: None,
+        };
+        assert!(!spent.exceeds(&budget));
+        assert!(!spent.exceeds(&BudgetConfig::default()));
+
+        spent.record("claude-sonnet-4", &Usage { input_tokens: 9_000, output_tokens: 1_500, ..Usage::default() });
+        assert_eq!(spent.requests, 1);
+        assert!(spent.exceeds(&budget));
+        assert!(!spent.exceeds(&BudgetConfig::default()), "no limit, no pause");
+
+        let extended = spent.extend(&budget);
+        assert_eq!(extended.max_tokens, Some(20_500));
+        assert!(!spent.exceeds(&extended));
+
+        let by_cost = BudgetConfig { max_tokens: None, max_cost_usd: Some(0.01) };
+        assert!(spent.exceeds(&by_cost));
+        assert_eq!(spent.extend(&by_cost).max_tokens, None);
+    }
+
+    #[test]
+    fn test_spend_since_earlier_usage() {
+        let mut usage = ConversationUsage::default();
+        usage.record("claude-sonnet-4", &Usage { input_tokens: 100, ..Usage::default() });
+        let before = usage.clone();
+        usage.record("claude-sonnet-4", &Usage { input_tokens: 50, ..Usage::default() });
+        usage.record("claude-3-5-haiku-latest", &Usage { output_tokens: 20, ..Usage::default() });
+
+        let spent = usage.since(&before);
+        assert_eq!(spent.len(), 2);
+        assert_eq!(spent[0].0, "claude-3-5-haiku-latest");
+        assert_eq!((spent[1].1.usage.input_tokens, spent[1].1.requests), (50, 1));
+        assert!(usage.since(&usage).is_empty());
+
+        let records = UsageRecord::since(&before, &usage, "session-1");
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|record| record.conversation == "session-1"));
+    }
+
+    #[test]
+    fn test_store_and_report() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = UsageStore::for_project(temp_dir.path());
+        assert!(store.records().unwrap().is_empty());
+
+        store
+            .append(&[
+                record("2026-03-01T10:00:00Z", "a", "claude-sonnet-4", 1_000_000),
+                record("2026-03-03T09:00:00Z", "b", "claude-opus-4", 1_000_000),
+            ])
+            .unwrap();
+        store.append(&[record("2026-03-03T23:00:00Z", "a", "claude-sonnet-4", 500_000)]).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(temp_dir.path().join(".AuroraHeart").join(USAGE_FILE))
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+        assert_eq!(store.records().unwrap().len(), 3);
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let report = UsageReport::build(&store.records().unwrap(), UsageRange::default(), today);
+        assert_eq!(report.range.from, NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert_eq!(report.days.len(), 4, "every day through today");
+        assert_eq!(report.days[1].totals.requests, 0);
+        assert_eq!(report.days[2].totals.usage.input_tokens, 1_500_000);
+        assert_eq!(report.total.requests, 3);
+        assert!((report.total.cost_usd - 19.5).abs() < 1e-9);
+        assert_eq!(report.models[0].model, "claude-opus-4");
+        assert_eq!(report.conversations[0].conversation, "a", "most recently used first");
+        assert_eq!(report.conversations[0].totals.requests, 2);
+
+        let report = UsageReport::build(&store.records().unwrap(), UsageRange::last_days(2, today), today);
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.total.requests, 2);
+        assert_eq!(report.models.len(), 2);
+    }
+}
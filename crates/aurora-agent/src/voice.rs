@@ -0,0 +1,449 @@
+//! Voice input transcription
+//!
+//! Audio recorded in the IDE is turned into text for the prompt box by the
+//! backend chosen in the `[voice]` section of `.AuroraHeart/config.toml`:
+//! a local whisper.cpp build, which keeps audio on the machine, or an
+//! OpenAI-compatible `/audio/transcriptions` API. The API key, when the API
+//! needs one, is read from the project's `CredentialStore`.
+//!
+//! whisper.cpp reads 16 kHz WAV, so other recordings (browsers record WebM
+//! or Ogg) are converted with ffmpeg first.
+
+use aurora_core::{Config, CredentialStore, SttBackend};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Credential store key for the transcription API key
+pub const STT_API_KEY: &str = "stt_api_key";
+
+/// Transcription API used when `[voice] api_url` is unset
+const DEFAULT_API_URL: &str = "https://api.openai.com/v1";
+
+/// Model the API transcribes with when `[voice] api_model` is unset
+const DEFAULT_API_MODEL: &str = "whisper-1";
+
+/// whisper.cpp program used when `[voice] whisper_command` is unset
+const DEFAULT_WHISPER_COMMAND: &str = "whisper-cli";
+
+/// How long converting and transcribing one recording may take
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Markers whisper prints in place of speech
+const NON_SPEECH: &[&str] = &["[BLANK_AUDIO]", "[ Silence ]", "[silence]", "(silence)"];
+
+/// Errors that can occur while transcribing
+#[derive(Error, Debug)]
+pub enum VoiceError {
+    /// HTTP request error
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The API returned an error response
+    #[error("Transcription API error ({status}): {message}")]
+    Api { status: u16, message: String },
+
+    /// The backend is not configured well enough to run
+    #[error("Voice input not configured: {0}")]
+    NotConfigured(String),
+
+    /// A local program failed
+    #[error("{program} failed: {message}")]
+    Program { program: String, message: String },
+
+    /// Nothing was said, or nothing was recognized
+    #[error("No speech was recognized in the recording")]
+    NoSpeech,
+}
+
+pub type Result<T> = std::result::Result<T, VoiceError>;
+
+/// A configured speech-to-text backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SttEngine {
+    /// A local whisper.cpp build and model
+    WhisperCpp {
+        program: PathBuf,
+        model: PathBuf,
+        /// ffmpeg, for recordings that are not WAV
+        ffmpeg: Option<PathBuf>,
+    },
+    /// An OpenAI-compatible transcription API
+    Api {
+        base_url: String,
+        model: String,
+        api_key: Option<String>,
+    },
+}
+
+/// Turns recordings into text
+pub struct Transcriber {
+    engine: SttEngine,
+    /// ISO 639-1 code of the spoken language; detected when `None`
+    language: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Transcriber {
+    /// Create a transcriber for a backend
+    pub fn new(engine: SttEngine, language: Option<String>) -> Self {
+        Self {
+            engine,
+            language,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Create the transcriber a project's `[voice]` configuration describes
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Result<Self> {
+        let project_root = project_root.as_ref();
+        let config = Config::load(project_root)
+            .map_err(|e| VoiceError::NotConfigured(e.to_string()))?
+            .voice;
+
+        let engine = match config.backend {
+            SttBackend::WhisperCpp => {
+                let model = config.whisper_model.ok_or_else(|| {
+                    VoiceError::NotConfigured(
+                        "set [voice] whisper_model to a whisper.cpp model file, or backend = \"api\"".to_string(),
+                    )
+                })?;
+                let model = project_root.join(model);
+                if !model.is_file() {
+                    return Err(VoiceError::NotConfigured(format!(
+                        "whisper model not found: {}",
+                        model.display()
+                    )));
+                }
+                let command = config.whisper_command.as_deref().unwrap_or(DEFAULT_WHISPER_COMMAND);
+                let program = find_program(command, project_root).ok_or_else(|| {
+                    VoiceError::NotConfigured(format!(
+                        "{} is not installed or not on PATH; build whisper.cpp from https://github.com/ggml-org/whisper.cpp or set [voice] whisper_command",
+                        command
+                    ))
+                })?;
+                SttEngine::WhisperCpp {
+                    program,
+                    model,
+                    ffmpeg: aurora_core::shell::find_on_path("ffmpeg"),
+                }
+            }
+            SttBackend::Api => {
+                let credentials = CredentialStore::for_project(project_root);
+                SttEngine::Api {
+                    base_url: config.api_url.unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+                    model: config.api_model.unwrap_or_else(|| DEFAULT_API_MODEL.to_string()),
+                    api_key: credentials.retrieve(STT_API_KEY, "auroraheart").ok(),
+                }
+            }
+        };
+
+        Ok(Self::new(engine, config.language))
+    }
+
+    /// Transcribe a recording of MIME type `mime_type` (e.g. `audio/webm`)
+    pub async fn transcribe(&self, audio: &[u8], mime_type: &str) -> Result<String> {
+        if audio.is_empty() {
+            return Err(VoiceError::NoSpeech);
+        }
+        let text = match &self.engine {
+            SttEngine::WhisperCpp { program, model, ffmpeg } => {
+                let run = self.transcribe_locally(program, model, ffmpeg.as_deref(), audio, mime_type);
+                tokio::time::timeout(TRANSCRIBE_TIMEOUT, run).await.map_err(|_| VoiceError::Program {
+                    program: program.display().to_string(),
+                    message: format!("did not finish within {}s", TRANSCRIBE_TIMEOUT.as_secs()),
+                })??
+            }
+            SttEngine::Api {
+                base_url,
+                model,
+                api_key,
+            } => self.transcribe_with_api(base_url, model, api_key.as_deref(), audio, mime_type).await?,
+        };
+        let text = clean_transcript(&text);
+        if text.is_empty() {
+            return Err(VoiceError::NoSpeech);
+        }
+        Ok(text)
+    }
+
+    async fn transcribe_locally(
+        &self,
+        program: &Path,
+        model: &Path,
+        ffmpeg: Option<&Path>,
+        audio: &[u8],
+        mime_type: &str,
+    ) -> Result<String> {
+        let dir = TempDir::create()?;
+        let recording = dir.0.join(format!("recording.{}", extension(mime_type)));
+        tokio::fs::write(&recording, audio).await?;
+
+        let wav = if extension(mime_type) == "wav" {
+            recording
+        } else {
+            let ffmpeg = ffmpeg.ok_or_else(|| {
+                VoiceError::NotConfigured(format!(
+                    "ffmpeg is needed to convert {} recordings for whisper.cpp; install it from https://ffmpeg.org/download.html",
+                    mime_type
+                ))
+            })?;
+            let wav = dir.0.join("recording.wav");
+            let mut command = tokio::process::Command::new(ffmpeg);
+            command
+                .args(["-nostdin", "-loglevel", "error", "-i"])
+                .arg(&recording)
+                .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+                .arg(&wav);
+            run(command, "ffmpeg").await?;
+            wav
+        };
+
+        let mut command = tokio::process::Command::new(program);
+        command
+            .arg("--model")
+            .arg(model)
+            .arg("--file")
+            .arg(&wav)
+            .args(["--no-timestamps", "--no-prints", "--language"])
+            .arg(self.language.as_deref().unwrap_or("auto"));
+        run(command, &program.display().to_string()).await
+    }
+
+    async fn transcribe_with_api(
+        &self,
+        base_url: &str,
+        model: &str,
+        api_key: Option<&str>,
+        audio: &[u8],
+        mime_type: &str,
+    ) -> Result<String> {
+        let boundary = format!(
+            "auroraheart-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        );
+        let mut fields = vec![("model", model), ("response_format", "json")];
+        if let Some(language) = &self.language {
+            fields.push(("language", language));
+        }
+        let body = multipart_body(&boundary, &fields, audio, mime_type);
+
+        let mut request = self
+            .client
+            .post(format!("{}/audio/transcriptions", base_url.trim_end_matches('/')))
+            .header("content-type", format!("multipart/form-data; boundary={}", boundary))
+            .body(body);
+        if let Some(key) = api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(VoiceError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+        let body: serde_json::Value = response.json().await?;
+        Ok(body["text"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+/// A directory for one transcription's files, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn create() -> std::io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "auroraheart-voice-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        ));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            tracing::warn!("Could not remove {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+/// Run `command`, returning its stdout, or its stderr as the error
+async fn run(mut command: tokio::process::Command, program: &str) -> Result<String> {
+    let output = command
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VoiceError::Program {
+            program: program.to_string(),
+            message: e.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(VoiceError::Program {
+            program: program.to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `command` as a path, or looked up in the project's `node_modules/.bin`
+/// and on PATH
+fn find_program(command: &str, project_root: &Path) -> Option<PathBuf> {
+    let path = project_root.join(command);
+    if command.contains(['/', '\\']) {
+        return path.is_file().then_some(path);
+    }
+    aurora_core::external::locate(command, project_root, None)
+}
+
+/// File extension for a recording's MIME type, ignoring parameters such as
+/// `;codecs=opus`
+fn extension(mime_type: &str) -> &'static str {
+    match mime_type.split(';').next().unwrap_or_default().trim() {
+        "audio/wav" | "audio/wave" | "audio/x-wav" => "wav",
+        "audio/ogg" => "ogg",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+        "audio/flac" => "flac",
+        _ => "webm",
+    }
+}
+
+/// A `multipart/form-data` body with text `fields` and the recording
+fn multipart_body(boundary: &str, fields: &[(&str, &str)], audio: &[u8], mime_type: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"recording.{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            extension(mime_type),
+            mime_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(audio);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// The transcript as one line of prompt text, without the markers whisper
+/// prints for silence
+fn clean_transcript(text: &str) -> String {
+    let mut text = text.to_string();
+    for marker in NON_SPEECH {
+        text = text.replace(marker, " ");
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_transcript_and_extension() {
+        assert_eq!(
+            clean_transcript(" Add a test\n for the parser.\n[BLANK_AUDIO]\n"),
+            "Add a test for the parser."
+        );
+        assert_eq!(clean_transcript("[BLANK_AUDIO]"), "");
+        assert_eq!(extension("audio/webm;codecs=opus"), "webm");
+        assert_eq!(extension("audio/x-wav"), "wav");
+    }
+
+    #[tokio::test]
+    async fn test_api_transcription() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_header("authorization", "Bearer key")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"model\"\r\n\r\nwhisper-1".to_string()),
+                mockito::Matcher::Regex("name=\"language\"\r\n\r\nen".to_string()),
+                mockito::Matcher::Regex("filename=\"recording.ogg\"\r\nContent-Type: audio/ogg".to_string()),
+            ]))
+            .with_body(r#"{"text": " Rename the config loader. "}"#)
+            .create_async()
+            .await;
+
+        let transcriber = Transcriber::new(
+            SttEngine::Api {
+                base_url: format!("{}/", server.url()),
+                model: "whisper-1".to_string(),
+                api_key: Some("key".to_string()),
+            },
+            Some("en".to_string()),
+        );
+        let text = transcriber.transcribe(b"OggS", "audio/ogg").await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(text, "Rename the config loader.");
+        assert!(matches!(transcriber.transcribe(b"", "audio/ogg").await, Err(VoiceError::NoSpeech)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_whisper_cpp_transcription() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-base.en.bin");
+        std::fs::write(&model, "").unwrap();
+        // Stands in for whisper-cli: checks its arguments and prints a transcript
+        let program = dir.path().join("whisper-cli");
+        std::fs::write(
+            &program,
+            "#!/bin/sh\ncase \"$*\" in *--language\\ auto*) ;; *) exit 1 ;; esac\necho ' Open the settings file.'\necho '[BLANK_AUDIO]'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let transcriber = Transcriber::new(
+            SttEngine::WhisperCpp {
+                program,
+                model,
+                ffmpeg: None,
+            },
+            None,
+        );
+        let text = transcriber.transcribe(b"RIFF", "audio/wav").await.unwrap();
+        assert_eq!(text, "Open the settings file.");
+        assert!(matches!(
+            transcriber.transcribe(b"webm", "audio/webm").await,
+            Err(VoiceError::NotConfigured(_))
+        ));
+    }
+
+    #[test]
+    fn test_for_project_requires_a_model() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(matches!(Transcriber::for_project(dir.path()), Err(VoiceError::NotConfigured(_))));
+    }
+}
@@ -0,0 +1,266 @@
+//! Registry of user-invokable actions
+//!
+//! Everything the user can trigger (backend commands, project tasks, agent
+//! tools, and slash commands, including actions contributed by plugins) is
+//! listed here with a stable id, a title, and a category, so the command
+//! palette and key bindings can be driven from data instead of being
+//! hard-coded in the frontend.
+//!
+//! Ids are namespaced by kind: `command.<name>`, `task.<name>`,
+//! `tool.<name>`, `slash.<name>`, and `plugin.<plugin>.<id>`.
+
+use crate::config::TaskDefinition;
+use crate::plugin::PluginManager;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Errors that can occur while registering actions
+#[derive(Error, Debug)]
+pub enum ActionError {
+    /// Another action already uses the id
+    #[error("Duplicate action id: {0}")]
+    DuplicateId(String),
+}
+
+pub type Result<T> = std::result::Result<T, ActionError>;
+
+/// What running an action does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    /// Invoke the backend command `target`
+    Command,
+    /// Run the project task `target`
+    Task,
+    /// Ask the agent to use the tool `target`
+    Tool,
+    /// Send the slash command `/target`
+    SlashCommand,
+}
+
+/// An action shown in the command palette
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Action {
+    /// Stable id, used by key bindings
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    pub kind: ActionKind,
+    /// Command, task, tool, or slash command name
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Suggested key binding, e.g. `ctrl+shift+p`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keybinding: Option<String>,
+    /// Plugin that contributed the action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+}
+
+impl Action {
+    /// Create an action with the id `<kind>.<target>`
+    pub fn new(kind: ActionKind, target: &str, title: impl Into<String>, category: impl Into<String>) -> Self {
+        let prefix = match kind {
+            ActionKind::Command => "command",
+            ActionKind::Task => "task",
+            ActionKind::Tool => "tool",
+            ActionKind::SlashCommand => "slash",
+        };
+        Self {
+            id: format!("{}.{}", prefix, target),
+            title: title.into(),
+            category: category.into(),
+            kind,
+            target: target.to_string(),
+            description: None,
+            keybinding: None,
+            plugin: None,
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the suggested key binding
+    pub fn with_keybinding(mut self, keybinding: impl Into<String>) -> Self {
+        self.keybinding = Some(keybinding.into());
+        self
+    }
+}
+
+/// All registered actions, keyed by id
+#[derive(Debug, Clone, Default)]
+pub struct ActionRegistry {
+    actions: BTreeMap<String, Action>,
+}
+
+impl ActionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an action, rejecting an id that is already taken
+    pub fn register(&mut self, action: Action) -> Result<()> {
+        if self.actions.contains_key(&action.id) {
+            return Err(ActionError::DuplicateId(action.id));
+        }
+        self.actions.insert(action.id.clone(), action);
+        Ok(())
+    }
+
+    /// Add an action for every project task
+    pub fn add_tasks(&mut self, tasks: &BTreeMap<String, TaskDefinition>) -> Result<()> {
+        for (name, task) in tasks {
+            let mut action = Action::new(ActionKind::Task, name, format!("Run Task: {}", name), "Tasks");
+            action.description = task.description.clone();
+            self.register(action)?;
+        }
+        Ok(())
+    }
+
+    /// Add the slash commands and actions of the enabled plugins
+    ///
+    /// A plugin action whose id is taken is skipped with a warning rather
+    /// than failing the whole registry, since plugins are third-party.
+    pub fn add_plugins(&mut self, plugins: &PluginManager) {
+        let mut enabled = plugins.enabled_plugins();
+        enabled.sort_by(|a, b| a.metadata.plugin.name.cmp(&b.metadata.plugin.name));
+
+        for plugin in enabled {
+            let plugin_name = &plugin.metadata.plugin.name;
+            let mut commands: Vec<_> = plugin.commands.values().collect();
+            commands.sort_by(|a, b| a.name.cmp(&b.name));
+            let commands = commands.into_iter().map(|command| {
+                let mut action = Action::new(
+                    ActionKind::SlashCommand,
+                    &command.name,
+                    format!("/{}", command.name),
+                    "Slash Commands",
+                );
+                action.description = command_summary(&command.content);
+                action.plugin = Some(plugin_name.clone());
+                action
+            });
+            let actions = plugin.metadata.actions.iter().map(|definition| Action {
+                id: format!("plugin.{}.{}", plugin_name, definition.id),
+                title: definition.title.clone(),
+                category: definition.category.clone().unwrap_or_else(|| plugin_name.clone()),
+                kind: ActionKind::SlashCommand,
+                target: definition.command.clone(),
+                description: None,
+                keybinding: definition.keybinding.clone(),
+                plugin: Some(plugin_name.clone()),
+            });
+
+            for action in commands.chain(actions) {
+                if let Err(e) = self.register(action) {
+                    tracing::warn!("Skipping action from plugin {}: {}", plugin_name, e);
+                }
+            }
+        }
+    }
+
+    /// The action with `id`
+    pub fn get(&self, id: &str) -> Option<&Action> {
+        self.actions.get(id)
+    }
+
+    /// Number of registered actions
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Whether no actions are registered
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// All actions, ordered by category and then title
+    pub fn list(&self) -> Vec<Action> {
+        let mut actions: Vec<Action> = self.actions.values().cloned().collect();
+        actions.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.title.cmp(&b.title)));
+        actions
+    }
+}
+
+/// First non-empty line of a slash command's markdown, without heading marks
+fn command_summary(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(|line| line.trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_register_and_list() {
+        let mut registry = ActionRegistry::new();
+        registry
+            .register(Action::new(ActionKind::Command, "open_folder", "Open Folder", "File").with_keybinding("ctrl+o"))
+            .unwrap();
+        registry
+            .register(Action::new(ActionKind::Tool, "grep", "Search with grep", "Agent Tools"))
+            .unwrap();
+        let mut tasks = BTreeMap::new();
+        tasks.insert("build".to_string(), TaskDefinition::new("cargo build"));
+        registry.add_tasks(&tasks).unwrap();
+
+        assert!(matches!(
+            registry.register(Action::new(ActionKind::Command, "open_folder", "Again", "File")),
+            Err(ActionError::DuplicateId(id)) if id == "command.open_folder"
+        ));
+        assert_eq!(registry.get("task.build").unwrap().title, "Run Task: build");
+
+        let categories: Vec<String> = registry.list().into_iter().map(|a| a.category).collect();
+        assert_eq!(categories, vec!["Agent Tools", "File", "Tasks"]);
+    }
+
+    #[test]
+    fn test_plugin_actions() {
+        let temp_dir = TempDir::new().unwrap();
+        let plugin_dir = temp_dir.path().join(".AuroraHeart").join("plugins").join("review");
+        std::fs::create_dir_all(plugin_dir.join("commands")).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            r#"
+[plugin]
+name = "review"
+version = "1.0.0"
+description = "Code review"
+author = "Test"
+
+[[actions]]
+id = "review-diff"
+title = "Review Current Diff"
+command = "review"
+keybinding = "ctrl+alt+r"
+"#,
+        )
+        .unwrap();
+        std::fs::write(plugin_dir.join("commands").join("review.md"), "# Review the diff\nLook closely").unwrap();
+
+        let mut plugins = PluginManager::new(temp_dir.path());
+        plugins.discover_plugins().unwrap();
+        let mut registry = ActionRegistry::new();
+        registry.add_plugins(&plugins);
+
+        let slash = registry.get("slash.review").unwrap();
+        assert_eq!(slash.description.as_deref(), Some("Review the diff"));
+        let action = registry.get("plugin.review.review-diff").unwrap();
+        assert_eq!(action.kind, ActionKind::SlashCommand);
+        assert_eq!(action.target, "review");
+        assert_eq!(action.category, "review");
+        assert_eq!(action.keybinding.as_deref(), Some("ctrl+alt+r"));
+    }
+}
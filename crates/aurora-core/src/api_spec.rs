@@ -0,0 +1,1125 @@
+//! Compact summaries of API contracts
+//!
+//! OpenAPI (and Swagger 2) documents and GraphQL schemas run to thousands
+//! of lines, most of them descriptions and examples. [`ApiSummary`] keeps
+//! what code against the API needs: each endpoint's method, path,
+//! parameters, request body, and response types, and each model's fields,
+//! with `$ref`s shown by the name of the schema they point to.
+//!
+//! OpenAPI documents are read with the [`config_file`](crate::config_file)
+//! reader, so YAML and JSON specs work alike. GraphQL schemas are read by a
+//! small SDL parser that understands type definitions and skips directives
+//! and default values.
+
+use crate::config_file::{parse_config_file, ConfigFormat};
+use crate::project_files::{project_files, FileListError};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors in reading or finding API specs
+#[derive(Error, Debug)]
+pub enum ApiSpecError {
+    /// The YAML or JSON could not be read
+    #[error("syntax error at {0}")]
+    Syntax(String),
+
+    /// The document is YAML or JSON but not an API description
+    #[error("not an OpenAPI or Swagger document: it has no openapi or swagger key")]
+    NotOpenApi,
+
+    /// The GraphQL schema could not be read
+    #[error("GraphQL error on line {line}: {message}")]
+    GraphQl { line: usize, message: String },
+
+    /// The project's files could not be listed
+    #[error("Could not list project files: {0}")]
+    Files(#[from] FileListError),
+}
+
+pub type Result<T> = std::result::Result<T, ApiSpecError>;
+
+/// Kind of API description
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiSpecKind {
+    OpenApi,
+    Swagger,
+    GraphQl,
+}
+
+impl fmt::Display for ApiSpecKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ApiSpecKind::OpenApi => "OpenAPI",
+            ApiSpecKind::Swagger => "Swagger",
+            ApiSpecKind::GraphQl => "GraphQL",
+        })
+    }
+}
+
+/// A parameter, property, or argument
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Field {
+    pub name: String,
+    /// Type, with referenced schemas by name
+    pub type_name: String,
+    pub required: bool,
+    /// Where an OpenAPI parameter goes: path, query, header, or cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = if self.required { "" } else { "?" };
+        write!(f, "{}{}: {}", self.name, optional, self.type_name)?;
+        if let Some(location) = &self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// One response of an endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Response {
+    /// HTTP status or `default`; empty for GraphQL fields
+    pub status: String,
+    /// Type of the body, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+}
+
+/// An HTTP operation, or a GraphQL query, mutation, or subscription field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Endpoint {
+    /// Upper-case HTTP method, or `query`, `mutation`, or `subscription`
+    pub method: String,
+    /// URL path template, or the GraphQL field name
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub parameters: Vec<Field>,
+    /// Type of the request body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<String>,
+    pub responses: Vec<Response>,
+}
+
+impl Endpoint {
+    fn is_graphql(&self) -> bool {
+        matches!(self.method.as_str(), "query" | "mutation" | "subscription")
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_graphql() {
+            write!(f, "{} {}", self.method, self.path)?;
+            if !self.parameters.is_empty() {
+                let arguments: Vec<String> = self.parameters.iter().map(ToString::to_string).collect();
+                write!(f, "({})", arguments.join(", "))?;
+            }
+            if let Some(type_name) = self.responses.first().and_then(|response| response.type_name.as_ref()) {
+                write!(f, ": {}", type_name)?;
+            }
+            if let Some(summary) = &self.summary {
+                write!(f, " — {}", summary)?;
+            }
+            return Ok(());
+        }
+
+        write!(f, "{} {}", self.method, self.path)?;
+        if let Some(operation_id) = &self.operation_id {
+            write!(f, " {}", operation_id)?;
+        }
+        if let Some(summary) = &self.summary {
+            write!(f, " — {}", summary)?;
+        }
+        if !self.parameters.is_empty() {
+            let parameters: Vec<String> = self.parameters.iter().map(ToString::to_string).collect();
+            write!(f, "\n    params: {}", parameters.join(", "))?;
+        }
+        if let Some(request) = &self.request {
+            write!(f, "\n    body: {}", request)?;
+        }
+        if !self.responses.is_empty() {
+            let responses: Vec<String> = self
+                .responses
+                .iter()
+                .map(|response| match &response.type_name {
+                    Some(type_name) => format!("{} {}", response.status, type_name),
+                    None => response.status.clone(),
+                })
+                .collect();
+            write!(f, "\n    returns: {}", responses.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A named schema or GraphQL type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Model {
+    pub name: String,
+    /// `object` for OpenAPI schemas; `type`, `input`, `interface`, `enum`,
+    /// `union`, or `scalar` for GraphQL
+    pub kind: String,
+    pub fields: Vec<Field>,
+    /// What a model without fields stands for, such as its enum values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !matches!(self.kind.as_str(), "object" | "type") {
+            write!(f, "{} ", self.kind)?;
+        }
+        f.write_str(&self.name)?;
+        if let Some(alias) = &self.alias {
+            write!(f, " = {}", alias)?;
+        }
+        if !self.fields.is_empty() || (self.alias.is_none() && self.kind != "scalar") {
+            let fields: Vec<String> = self.fields.iter().map(ToString::to_string).collect();
+            write!(f, " {{{}}}", fields.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The endpoints and models of an API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiSummary {
+    pub kind: ApiSpecKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Version of the API, from `info.version`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub endpoints: Vec<Endpoint>,
+    pub models: Vec<Model>,
+}
+
+impl ApiSummary {
+    /// Keep the endpoints and models whose path, operation, or name contains
+    /// `query`, ignoring case
+    pub fn filter(&mut self, query: &str) {
+        let query = query.to_lowercase();
+        let matches = |text: &str| text.to_lowercase().contains(&query);
+        self.endpoints.retain(|endpoint| {
+            matches(&endpoint.path) || endpoint.operation_id.as_deref().is_some_and(matches)
+        });
+        self.models.retain(|model| matches(&model.name));
+    }
+}
+
+impl fmt::Display for ApiSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.title, &self.version) {
+            (Some(title), Some(version)) => writeln!(f, "{} {} ({})", title, version, self.kind)?,
+            (Some(title), None) => writeln!(f, "{} ({})", title, self.kind)?,
+            _ => writeln!(f, "{} API", self.kind)?,
+        }
+        writeln!(f, "\nEndpoints ({}):", self.endpoints.len())?;
+        for endpoint in &self.endpoints {
+            writeln!(f, "  {}", endpoint.to_string().replace('\n', "\n  "))?;
+        }
+        writeln!(f, "\nModels ({}):", self.models.len())?;
+        for model in &self.models {
+            writeln!(f, "  {}", model)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a file's name marks it as a GraphQL schema
+pub fn is_graphql(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("graphql" | "graphqls" | "gql")
+    )
+}
+
+/// Read an API description, choosing the reader from the file's extension
+pub fn load_api_spec(path: &Path, text: &str) -> Result<ApiSummary> {
+    if is_graphql(path) {
+        return parse_graphql(text);
+    }
+    let format = ConfigFormat::from_path(path).unwrap_or(ConfigFormat::Yaml);
+    let documents = parse_config_file(text, format).map_err(|error| ApiSpecError::Syntax(error.to_string()))?;
+    let document = documents.into_iter().next().ok_or(ApiSpecError::NotOpenApi)?;
+    parse_openapi(&document.value)
+}
+
+/// Tracked GraphQL schemas, and YAML and JSON files that declare an
+/// `openapi` or `swagger` version near their top, less those the project
+/// ignores
+pub fn find_api_specs(root: &Path) -> Result<Vec<PathBuf>> {
+    Ok(project_files(root)?
+        .into_iter()
+        .filter(|file| {
+            if is_graphql(file) {
+                return true;
+            }
+            if !matches!(file.extension().and_then(|ext| ext.to_str()), Some("yaml" | "yml" | "json")) {
+                return false;
+            }
+            std::fs::read_to_string(root.join(file)).is_ok_and(|text| declares_openapi(&text))
+        })
+        .collect())
+}
+
+/// Whether one of the first lines is a top-level `openapi` or `swagger` key
+fn declares_openapi(text: &str) -> bool {
+    text.lines().take(20).any(|line| {
+        let line = line.trim_start_matches(['{', ' ', '\t']);
+        ["openapi", "swagger", "\"openapi\"", "\"swagger\""]
+            .iter()
+            .any(|key| line.strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with(':')))
+    })
+}
+
+const METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// Summarize an OpenAPI 3 or Swagger 2 document
+pub fn parse_openapi(document: &Value) -> Result<ApiSummary> {
+    let kind = if document["openapi"].is_string() {
+        ApiSpecKind::OpenApi
+    } else if document["swagger"].is_string() {
+        ApiSpecKind::Swagger
+    } else {
+        return Err(ApiSpecError::NotOpenApi);
+    };
+    let text = |value: &Value| value.as_str().map(str::to_string);
+
+    let mut endpoints = Vec::new();
+    for (path, item) in document["paths"].as_object().into_iter().flatten() {
+        let item = resolve(document, item);
+        let shared = item["parameters"].as_array().map(Vec::as_slice).unwrap_or_default();
+        for method in METHODS {
+            let Some(operation) = item.get(*method) else {
+                continue;
+            };
+            let own = operation["parameters"].as_array().map(Vec::as_slice).unwrap_or_default();
+            let mut parameters: Vec<Field> = Vec::new();
+            let mut request = None;
+            for parameter in shared.iter().chain(own).map(|parameter| resolve(document, parameter)) {
+                let location = parameter["in"].as_str().unwrap_or("query");
+                if location == "body" {
+                    request = Some(schema_type(&parameter["schema"]));
+                    continue;
+                }
+                let field = Field {
+                    name: parameter["name"].as_str().unwrap_or_default().to_string(),
+                    // Swagger 2 puts the type on the parameter itself
+                    type_name: match parameter.get("schema") {
+                        Some(schema) => schema_type(schema),
+                        None => schema_type(parameter),
+                    },
+                    required: parameter["required"] == true,
+                    location: Some(location.to_string()),
+                };
+                // An operation's parameter replaces the path's of the same name
+                parameters.retain(|existing| {
+                    existing.name != field.name || existing.location != field.location
+                });
+                parameters.push(field);
+            }
+            if let Some(body) = operation.get("requestBody") {
+                request = content_type(&resolve(document, body)["content"]);
+            }
+            let responses = operation["responses"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(status, response)| {
+                    let response = resolve(document, response);
+                    Response {
+                        status: status.clone(),
+                        type_name: match response.get("schema") {
+                            Some(schema) => Some(schema_type(schema)),
+                            None => content_type(&response["content"]),
+                        },
+                    }
+                })
+                .collect();
+            endpoints.push(Endpoint {
+                method: method.to_uppercase(),
+                path: path.clone(),
+                operation_id: text(&operation["operationId"]),
+                summary: text(&operation["summary"]),
+                parameters,
+                request,
+                responses,
+            });
+        }
+    }
+
+    let schemas = match kind {
+        ApiSpecKind::Swagger => &document["definitions"],
+        _ => &document["components"]["schemas"],
+    };
+    let models = schemas
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, schema)| {
+            let required: Vec<&str> = schema["required"]
+                .as_array()
+                .map(|required| required.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let fields: Vec<Field> = schema["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(property, property_schema)| Field {
+                    name: property.clone(),
+                    type_name: schema_type(property_schema),
+                    required: required.contains(&property.as_str()),
+                    location: None,
+                })
+                .collect();
+            let alias = (fields.is_empty() && schema.get("properties").is_none()).then(|| schema_type(schema));
+            Model {
+                name: name.clone(),
+                kind: "object".to_string(),
+                fields,
+                alias: alias.filter(|alias| alias != "object"),
+            }
+        })
+        .collect();
+
+    Ok(ApiSummary {
+        kind,
+        title: text(&document["info"]["title"]),
+        version: text(&document["info"]["version"]),
+        endpoints,
+        models,
+    })
+}
+
+/// Follow a local `$ref`, leaving the value as it is when it has none or
+/// the target is missing
+fn resolve<'a>(document: &'a Value, value: &'a Value) -> &'a Value {
+    let mut value = value;
+    // A bound on the chain guards against reference cycles
+    for _ in 0..8 {
+        let Some(target) = value["$ref"].as_str().and_then(|reference| reference.strip_prefix('#')) else {
+            break;
+        };
+        match document.pointer(target) {
+            Some(target) => value = target,
+            None => break,
+        }
+    }
+    value
+}
+
+/// Type of the preferred media type in an OpenAPI 3 `content` map
+fn content_type(content: &Value) -> Option<String> {
+    let content = content.as_object()?;
+    let schema = content
+        .get("application/json")
+        .or_else(|| content.iter().find(|(media, _)| media.ends_with("+json")).map(|(_, value)| value))
+        .or_else(|| content.values().next())?
+        .get("schema")?;
+    Some(schema_type(schema))
+}
+
+/// Last segment of a `$ref`, the referenced schema's name
+fn ref_name(reference: &str) -> String {
+    reference.rsplit('/').next().unwrap_or(reference).to_string()
+}
+
+/// A JSON Schema as a short type expression, such as `Pet[]` or
+/// `map<string, integer>`
+fn schema_type(schema: &Value) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        return ref_name(reference);
+    }
+    for (key, separator) in [("oneOf", " | "), ("anyOf", " | "), ("allOf", " & ")] {
+        if let Some(members) = schema[key].as_array() {
+            let members: Vec<String> = members.iter().map(schema_type).collect();
+            return members.join(separator);
+        }
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        let values: Vec<String> = values.iter().map(Value::to_string).collect();
+        return values.join(" | ");
+    }
+
+    let (type_name, mut nullable) = match &schema["type"] {
+        Value::String(type_name) => (type_name.as_str(), false),
+        // OpenAPI 3.1 spells nullable types as ["string", "null"]
+        Value::Array(types) => (
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|type_name| *type_name != "null")
+                .unwrap_or("null"),
+            types.iter().any(|type_name| type_name == "null"),
+        ),
+        _ if schema.get("properties").is_some() => ("object", false),
+        _ => ("", false),
+    };
+    nullable |= schema["nullable"] == true;
+
+    let base = match type_name {
+        "array" => {
+            let items = schema_type(&schema["items"]);
+            if items.contains(' ') {
+                format!("({})[]", items)
+            } else {
+                format!("{}[]", items)
+            }
+        }
+        "object" | "" if schema["additionalProperties"].is_object() => {
+            format!("map<string, {}>", schema_type(&schema["additionalProperties"]))
+        }
+        "" => "any".to_string(),
+        _ => match schema["format"].as_str() {
+            Some(format) => format!("{}({})", type_name, format),
+            None => type_name.to_string(),
+        },
+    };
+    if nullable && type_name != "null" {
+        format!("{} | null", base)
+    } else {
+        base
+    }
+}
+
+/// A GraphQL SDL token
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Punct(char),
+    /// A string, block string, or number
+    Literal,
+}
+
+fn tokenize(sdl: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = sdl.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let error = |line, message: String| ApiSpecError::GraphQl { line, message };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' if chars[i..].starts_with(&['"', '"', '"']) => {
+                let start = line;
+                i += 3;
+                loop {
+                    if i >= chars.len() {
+                        return Err(error(start, "unterminated block string".to_string()));
+                    }
+                    if chars[i..].starts_with(&['"', '"', '"']) {
+                        i += 3;
+                        break;
+                    }
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    // An escaped \""" does not end the string
+                    i += if chars[i..].starts_with(&['\\', '"', '"', '"']) { 4 } else { 1 };
+                }
+                tokens.push((Token::Literal, start));
+            }
+            '"' => {
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None | Some('\n') => return Err(error(line, "unterminated string".to_string())),
+                        Some('\\') => i += 2,
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(_) => i += 1,
+                    }
+                }
+                tokens.push((Token::Literal, line));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push((Token::Name(chars[start..i].iter().collect()), line));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '+' | '-')) {
+                    i += 1;
+                }
+                tokens.push((Token::Literal, line));
+            }
+            '.' if chars[i..].starts_with(&['.', '.', '.']) => {
+                tokens.push((Token::Punct('.'), line));
+                i += 3;
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ':' | '=' | '@' | '|' | '!' | '&' | '$' => {
+                tokens.push((Token::Punct(c), line));
+                i += 1;
+            }
+            other => return Err(error(line, format!("unexpected character '{}'", other))),
+        }
+    }
+    Ok(tokens)
+}
+
+struct GraphQlParser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl GraphQlParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ApiSpecError {
+        ApiSpecError::GraphQl {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn at(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.at(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", c)))
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn at_name(&self, expected: &str) -> bool {
+        matches!(self.peek(), Some(Token::Name(name)) if name == expected)
+    }
+
+    fn skip_description(&mut self) {
+        if self.peek() == Some(&Token::Literal) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip a bracketed group whose opening bracket is next
+    fn skip_group(&mut self) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unclosed bracket")),
+                Some(Token::Punct('(' | '[' | '{')) => depth += 1,
+                Some(Token::Punct(')' | ']' | '}')) => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_directives(&mut self) -> Result<()> {
+        while self.eat('@') {
+            self.name()?;
+            if self.at('(') {
+                self.skip_group()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A default value: one token, or a bracketed list or object
+    fn skip_value(&mut self) -> Result<()> {
+        if self.at('[') || self.at('{') {
+            return self.skip_group();
+        }
+        self.eat('$');
+        match self.peek() {
+            Some(Token::Name(_) | Token::Literal) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// A type reference such as `[Pet!]!`
+    fn type_reference(&mut self) -> Result<String> {
+        let mut type_name = if self.eat('[') {
+            let inner = self.type_reference()?;
+            self.expect(']')?;
+            format!("[{}]", inner)
+        } else {
+            self.name()?
+        };
+        if self.eat('!') {
+            type_name.push('!');
+        }
+        Ok(type_name)
+    }
+
+    /// `name: Type = default @directive`, for arguments and input fields
+    fn input_value(&mut self) -> Result<Field> {
+        self.skip_description();
+        let name = self.name()?;
+        self.expect(':')?;
+        let type_name = self.type_reference()?;
+        if self.eat('=') {
+            self.skip_value()?;
+        }
+        self.skip_directives()?;
+        Ok(Field {
+            name,
+            type_name,
+            required: true,
+            location: None,
+        })
+    }
+
+    /// Fields between braces, each with its arguments
+    fn fields(&mut self) -> Result<Vec<(Field, Vec<Field>)>> {
+        let mut fields = Vec::new();
+        if !self.eat('{') {
+            return Ok(fields);
+        }
+        while !self.eat('}') {
+            if self.peek().is_none() {
+                return Err(self.error("expected '}'"));
+            }
+            self.skip_description();
+            let name = self.name()?;
+            let mut arguments = Vec::new();
+            if self.eat('(') {
+                while !self.eat(')') {
+                    arguments.push(self.input_value()?);
+                }
+            }
+            self.expect(':')?;
+            let type_name = self.type_reference()?;
+            if self.eat('=') {
+                self.skip_value()?;
+            }
+            self.skip_directives()?;
+            let field = Field {
+                name,
+                type_name,
+                required: true,
+                location: None,
+            };
+            fields.push((field, arguments));
+        }
+        Ok(fields)
+    }
+}
+
+/// Summarize a GraphQL schema: the root operation types' fields become
+/// endpoints, every other named type a model
+pub fn parse_graphql(sdl: &str) -> Result<ApiSummary> {
+    let mut parser = GraphQlParser {
+        tokens: tokenize(sdl)?,
+        pos: 0,
+    };
+    let mut roots: BTreeMap<String, String> = [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")]
+        .into_iter()
+        .map(|(operation, type_name)| (type_name.to_string(), operation.to_string()))
+        .collect();
+    let mut models: Vec<Model> = Vec::new();
+    let mut operations: BTreeMap<String, Vec<(Field, Vec<Field>)>> = BTreeMap::new();
+
+    while parser.peek().is_some() {
+        parser.skip_description();
+        let keyword = parser.name()?;
+        let keyword = if keyword == "extend" { parser.name()? } else { keyword };
+        match keyword.as_str() {
+            "schema" => {
+                parser.skip_directives()?;
+                parser.expect('{')?;
+                let mut declared = BTreeMap::new();
+                while !parser.eat('}') {
+                    let operation = parser.name()?;
+                    parser.expect(':')?;
+                    declared.insert(parser.name()?, operation);
+                }
+                roots = declared;
+            }
+            "type" | "interface" | "input" => {
+                let name = parser.name()?;
+                if parser.at_name("implements") {
+                    parser.pos += 1;
+                    parser.eat('&');
+                    parser.name()?;
+                    while parser.eat('&') {
+                        parser.name()?;
+                    }
+                }
+                parser.skip_directives()?;
+                let fields = if keyword == "input" {
+                    let mut fields = Vec::new();
+                    if parser.eat('{') {
+                        while !parser.eat('}') {
+                            fields.push((parser.input_value()?, Vec::new()));
+                        }
+                    }
+                    fields
+                } else {
+                    parser.fields()?
+                };
+                if keyword == "type" && roots.contains_key(&name) {
+                    operations.entry(name).or_default().extend(fields);
+                } else {
+                    let fields = fields.into_iter().map(|(field, _)| field);
+                    model_entry(&mut models, &name, &keyword).fields.extend(fields);
+                }
+            }
+            "enum" => {
+                let name = parser.name()?;
+                parser.skip_directives()?;
+                let mut values = Vec::new();
+                if parser.eat('{') {
+                    while !parser.eat('}') {
+                        parser.skip_description();
+                        values.push(parser.name()?);
+                        parser.skip_directives()?;
+                    }
+                }
+                add_alias(model_entry(&mut models, &name, "enum"), values);
+            }
+            "union" => {
+                let name = parser.name()?;
+                parser.skip_directives()?;
+                let mut members = Vec::new();
+                if parser.eat('=') {
+                    parser.eat('|');
+                    members.push(parser.name()?);
+                    while parser.eat('|') {
+                        members.push(parser.name()?);
+                    }
+                }
+                add_alias(model_entry(&mut models, &name, "union"), members);
+            }
+            "scalar" => {
+                let name = parser.name()?;
+                parser.skip_directives()?;
+                model_entry(&mut models, &name, "scalar");
+            }
+            "directive" => {
+                parser.expect('@')?;
+                parser.name()?;
+                if parser.at('(') {
+                    parser.skip_group()?;
+                }
+                if parser.at_name("repeatable") {
+                    parser.pos += 1;
+                }
+                if !parser.at_name("on") {
+                    return Err(parser.error("expected 'on'"));
+                }
+                parser.pos += 1;
+                parser.eat('|');
+                parser.name()?;
+                while parser.eat('|') {
+                    parser.name()?;
+                }
+            }
+            other => return Err(parser.error(format!("unexpected '{}'; expected a type definition", other))),
+        }
+    }
+
+    // Queries first, then mutations and subscriptions
+    let mut roots: Vec<(String, String)> = roots.into_iter().collect();
+    roots.sort_by_key(|(_, operation)| ["query", "mutation", "subscription"].iter().position(|o| o == operation));
+    let endpoints = roots
+        .into_iter()
+        .flat_map(|(type_name, operation)| {
+            operations
+                .remove(&type_name)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |(field, arguments)| Endpoint {
+                    method: operation.clone(),
+                    path: field.name,
+                    operation_id: None,
+                    summary: None,
+                    parameters: arguments,
+                    request: None,
+                    responses: vec![Response {
+                        status: String::new(),
+                        type_name: Some(field.type_name),
+                    }],
+                })
+        })
+        .collect();
+
+    Ok(ApiSummary {
+        kind: ApiSpecKind::GraphQl,
+        title: None,
+        version: None,
+        endpoints,
+        models,
+    })
+}
+
+/// The model named `name`, added if this is its first definition
+fn model_entry<'a>(models: &'a mut Vec<Model>, name: &str, kind: &str) -> &'a mut Model {
+    let index = match models.iter().position(|model| model.name == name) {
+        Some(index) => index,
+        None => {
+            models.push(Model {
+                name: name.to_string(),
+                kind: kind.to_string(),
+                fields: Vec::new(),
+                alias: None,
+            });
+            models.len() - 1
+        }
+    };
+    &mut models[index]
+}
+
+/// Add enum values or union members, which `extend` may do more than once
+fn add_alias(model: &mut Model, members: Vec<String>) {
+    let members = members.join(" | ");
+    model.alias = match model.alias.take() {
+        Some(existing) if !members.is_empty() => Some(format!("{} | {}", existing, members)),
+        Some(existing) => Some(existing),
+        None => Some(members),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_openapi_summary() {
+        let spec = "openapi: 3.0.3
+info:
+  title: Pet Store
+  version: 1.0.0
+paths:
+  /pets/{petId}:
+    parameters:
+      - $ref: '#/components/parameters/PetId'
+    get:
+      operationId: showPet
+      summary: Info for a specific pet
+      parameters:
+        - name: fields
+          in: query
+          schema:
+            type: array
+            items:
+              type: string
+      responses:
+        '200':
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+        '404':
+          description: Not found
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+      responses:
+        '201':
+          description: Created
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      required: true
+      schema:
+        type: integer
+        format: int64
+  schemas:
+    Pet:
+      type: object
+      required: [id]
+      properties:
+        id:
+          type: integer
+        tag:
+          type: string
+          nullable: true
+        owners:
+          type: object
+          additionalProperties:
+            $ref: '#/components/schemas/Owner'
+    Status:
+      type: string
+      enum: [available, sold]
+";
+        let summary = load_api_spec(Path::new("openapi.yaml"), spec).unwrap();
+        assert_eq!(
+            summary.to_string(),
+            "Pet Store 1.0.0 (OpenAPI)
+
+Endpoints (2):
+  GET /pets/{petId} showPet — Info for a specific pet
+      params: petId: integer(int64) (path), fields?: string[] (query)
+      returns: 200 Pet, 404
+  POST /pets/{petId}
+      params: petId: integer(int64) (path)
+      body: Pet
+      returns: 201
+
+Models (2):
+  Pet {id: integer, owners?: map<string, Owner>, tag?: string | null}
+  Status = \"available\" | \"sold\"
+"
+        );
+
+        let mut filtered = summary.clone();
+        filtered.filter("STATUS");
+        assert!(filtered.endpoints.is_empty());
+        assert_eq!(filtered.models.len(), 1);
+    }
+
+    #[test]
+    fn test_swagger_summary() {
+        let spec = json!({
+            "swagger": "2.0",
+            "paths": {
+                "/users": {
+                    "post": {
+                        "parameters": [
+                            {"name": "user", "in": "body", "schema": {"$ref": "#/definitions/User"}},
+                            {"name": "dryRun", "in": "query", "type": "boolean"}
+                        ],
+                        "responses": {"200": {"schema": {"type": "array", "items": {"$ref": "#/definitions/User"}}}}
+                    }
+                }
+            },
+            "definitions": {"User": {"properties": {"name": {"type": "string"}}}}
+        });
+        let summary = parse_openapi(&spec).unwrap();
+        assert_eq!(summary.kind, ApiSpecKind::Swagger);
+        let endpoint = &summary.endpoints[0];
+        assert_eq!(endpoint.request.as_deref(), Some("User"));
+        assert_eq!(endpoint.parameters[0].to_string(), "dryRun?: boolean (query)");
+        assert_eq!(endpoint.responses[0].type_name.as_deref(), Some("User[]"));
+        assert_eq!(summary.models[0].to_string(), "User {name?: string}");
+
+        assert!(matches!(parse_openapi(&json!({"name": "x"})), Err(ApiSpecError::NotOpenApi)));
+    }
+
+    #[test]
+    fn test_graphql_summary() {
+        let sdl = r#"
+"""The root query"""
+type Query {
+  "Look up a pet"
+  pet(id: ID!, include: [String!] = ["owner"] @deprecated): Pet
+  pets(first: Int = 10): [Pet!]!
+}
+
+type Mutation {
+  addPet(input: NewPet!): Pet! @auth(requires: ADMIN)
+}
+
+type Pet implements Node & Named @key(fields: "id") {
+  id: ID!
+  name: String
+}
+
+input NewPet { name: String! }
+enum Status { AVAILABLE SOLD }
+extend enum Status { ADOPTED }
+union SearchResult = | Pet | Owner
+scalar DateTime
+directive @auth(requires: Role = ADMIN) repeatable on OBJECT | FIELD_DEFINITION
+"#;
+        let summary = parse_graphql(sdl).unwrap();
+        assert_eq!(
+            summary.to_string(),
+            "GraphQL API
+
+Endpoints (3):
+  query pet(id: ID!, include: [String!]): Pet
+  query pets(first: Int): [Pet!]!
+  mutation addPet(input: NewPet!): Pet!
+
+Models (5):
+  Pet {id: ID!, name: String}
+  input NewPet {name: String!}
+  enum Status = AVAILABLE | SOLD | ADOPTED
+  union SearchResult = Pet | Owner
+  scalar DateTime
+"
+        );
+
+        let custom_root = parse_graphql("schema { query: Root }\ntype Root { ping: String }").unwrap();
+        assert_eq!(custom_root.endpoints[0].to_string(), "query ping: String");
+
+        assert_eq!(
+            parse_graphql("type Query {\n  broken(: Int\n}").unwrap_err().to_string(),
+            "GraphQL error on line 2: expected a name"
+        );
+    }
+
+    #[test]
+    fn test_declares_openapi() {
+        assert!(declares_openapi("# spec\nopenapi: 3.1.0\n"));
+        assert!(declares_openapi("{\n  \"swagger\": \"2.0\",\n"));
+        assert!(!declares_openapi("name: build\non: push\n"));
+    }
+}
@@ -0,0 +1,702 @@
+//! Benchmark runs and regression tracking
+//!
+//! Benchmarks are run with `cargo bench` (libtest and Criterion output are
+//! both understood) or with `hyperfine` for arbitrary commands. Results are
+//! stored per commit in `.AuroraHeart/bench/<commit>.json`; runs made with
+//! uncommitted changes are stored as `<commit>-dirty` so they can be compared
+//! against the clean commit they started from.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Default change (in percent) beyond which a benchmark counts as regressed or improved
+pub const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// Key used for runs outside a git repository
+const NO_COMMIT: &str = "uncommitted";
+
+/// Number of output lines kept when a benchmark command fails
+const MAX_FAILURE_LINES: usize = 40;
+
+/// Errors that can occur while running or storing benchmarks
+#[derive(Error, Debug)]
+pub enum BenchError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Stored results or hyperfine output could not be parsed
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The benchmark command could not be started or exited with an error
+    #[error("Benchmark command failed: {command}\n{output}")]
+    CommandFailed {
+        /// Command that was run
+        command: String,
+        /// Tail of the command output
+        output: String,
+    },
+
+    /// The command ran but reported no benchmark timings
+    #[error("No benchmark results found in the output of: {0}")]
+    NoResults(String),
+
+    /// The requested baseline has no stored results
+    #[error("No stored benchmark results for baseline '{0}'")]
+    UnknownBaseline(String),
+}
+
+pub type Result<T> = std::result::Result<T, BenchError>;
+
+/// How benchmarks are run
+#[derive(Debug, Clone, PartialEq)]
+pub enum BenchCommand {
+    /// `cargo bench` with extra arguments (e.g. a benchmark filter)
+    Cargo {
+        /// Arguments appended to `cargo bench`
+        args: Vec<String>,
+    },
+    /// `hyperfine` timing one or more shell commands
+    Hyperfine {
+        /// Commands to time
+        commands: Vec<String>,
+        /// Number of timed runs (hyperfine chooses when unset)
+        runs: Option<u32>,
+        /// Number of warmup runs
+        warmup: Option<u32>,
+    },
+}
+
+/// Timing of a single benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    /// Benchmark name (the command for hyperfine)
+    pub name: String,
+    /// Mean (or Criterion's point estimate) in nanoseconds
+    pub mean_ns: f64,
+    /// Spread reported by the tool in nanoseconds, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deviation_ns: Option<f64>,
+}
+
+/// Stored results for one commit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchRun {
+    /// Storage key: the short commit hash, with `-dirty` for uncommitted changes
+    pub commit: String,
+    /// Seconds since the Unix epoch of the latest run
+    pub recorded_at: u64,
+    /// Results by benchmark name
+    pub results: BTreeMap<String, BenchResult>,
+}
+
+/// How a benchmark changed relative to the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchVerdict {
+    /// Slower by more than the threshold
+    Regressed,
+    /// Faster by more than the threshold
+    Improved,
+    /// Within the threshold
+    Unchanged,
+    /// Not present in the baseline
+    New,
+}
+
+/// Comparison of one benchmark against the baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchComparison {
+    /// Benchmark name
+    pub name: String,
+    /// Baseline timing in nanoseconds
+    pub baseline_ns: Option<f64>,
+    /// Current timing in nanoseconds
+    pub current_ns: f64,
+    /// Relative change in percent (positive is slower)
+    pub change_percent: Option<f64>,
+    /// Classification of the change
+    pub verdict: BenchVerdict,
+}
+
+/// Outcome of a benchmark run compared against the stored baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// Storage key of this run
+    pub commit: String,
+    /// Storage key of the baseline, if one was found
+    pub baseline: Option<String>,
+    /// Threshold used to classify changes, in percent
+    pub threshold_percent: f64,
+    /// One entry per benchmark in this run
+    pub comparisons: Vec<BenchComparison>,
+    /// Path of the stored results
+    pub stored_at: PathBuf,
+}
+
+impl BenchReport {
+    /// Benchmarks that got slower than the threshold allows
+    pub fn regressions(&self) -> impl Iterator<Item = &BenchComparison> {
+        self.comparisons
+            .iter()
+            .filter(|c| c.verdict == BenchVerdict::Regressed)
+    }
+
+    /// Format the report for display
+    pub fn format(&self) -> String {
+        let mut output = match &self.baseline {
+            Some(baseline) => format!(
+                "Benchmarks for {} compared with {} (threshold ±{}%)\n",
+                self.commit, baseline, self.threshold_percent
+            ),
+            None => format!(
+                "Benchmarks for {} (no stored baseline to compare with)\n",
+                self.commit
+            ),
+        };
+
+        for comparison in &self.comparisons {
+            let current = format_duration(comparison.current_ns);
+            let line = match (comparison.baseline_ns, comparison.change_percent) {
+                (Some(baseline), Some(change)) => {
+                    let marker = match comparison.verdict {
+                        BenchVerdict::Regressed => "❌",
+                        BenchVerdict::Improved => "✅",
+                        _ => "➖",
+                    };
+                    format!(
+                        "{} {}: {} -> {} ({:+.1}%)",
+                        marker,
+                        comparison.name,
+                        format_duration(baseline),
+                        current,
+                        change
+                    )
+                }
+                _ => format!("🆕 {}: {}", comparison.name, current),
+            };
+            output.push_str(&line);
+            output.push('\n');
+        }
+
+        let regressions = self.regressions().count();
+        if regressions > 0 {
+            output.push_str(&format!("\n{} benchmark(s) regressed\n", regressions));
+        }
+        output.push_str(&format!(
+            "\nResults stored in {}\n",
+            self.stored_at.display()
+        ));
+        output
+    }
+}
+
+/// Format nanoseconds with a readable unit
+pub fn format_duration(ns: f64) -> String {
+    if ns >= 1e9 {
+        format!("{:.3} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.3} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.3} µs", ns / 1e3)
+    } else {
+        format!("{:.1} ns", ns)
+    }
+}
+
+/// Compare results against a baseline run
+///
+/// Without a baseline every benchmark is reported as new.
+pub fn compare(
+    baseline: Option<&BenchRun>,
+    current: &[BenchResult],
+    threshold_percent: f64,
+) -> Vec<BenchComparison> {
+    current
+        .iter()
+        .map(|result| {
+            let baseline_ns = baseline
+                .and_then(|run| run.results.get(&result.name))
+                .map(|b| b.mean_ns)
+                .filter(|ns| *ns > 0.0);
+            let change_percent = baseline_ns.map(|b| (result.mean_ns - b) / b * 100.0);
+            let verdict = match change_percent {
+                None => BenchVerdict::New,
+                Some(change) if change > threshold_percent => BenchVerdict::Regressed,
+                Some(change) if change < -threshold_percent => BenchVerdict::Improved,
+                Some(_) => BenchVerdict::Unchanged,
+            };
+            BenchComparison {
+                name: result.name.clone(),
+                baseline_ns,
+                current_ns: result.mean_ns,
+                change_percent,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+/// Nanoseconds per unit as printed by Criterion
+fn unit_ns(unit: &str) -> Option<f64> {
+    match unit {
+        "ps" => Some(1e-3),
+        "ns" => Some(1.0),
+        "us" | "µs" | "μs" => Some(1e3),
+        "ms" => Some(1e6),
+        "s" => Some(1e9),
+        _ => None,
+    }
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.replace(',', "").parse().ok()
+}
+
+/// Parse `cargo bench` output (libtest `bench:` lines and Criterion `time:` lines)
+pub fn parse_cargo_bench(output: &str) -> Vec<BenchResult> {
+    let (Ok(libtest), Ok(criterion)) = (
+        Regex::new(r"^test (\S+)\s+\.\.\. bench:\s+([\d,.]+) ns/iter \(\+/- ([\d,.]+)\)"),
+        Regex::new(r"^(.*?)\s*time:\s+\[\S+ \S+ (\S+) (\S+) (\S+) (\S+)\]"),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut results = Vec::new();
+    let mut previous_line = "";
+    for line in output.lines() {
+        if let Some(caps) = libtest.captures(line) {
+            if let Some(mean_ns) = parse_number(&caps[2]) {
+                results.push(BenchResult {
+                    name: caps[1].to_string(),
+                    mean_ns,
+                    deviation_ns: parse_number(&caps[3]),
+                });
+            }
+        } else if let Some(caps) = criterion.captures(line) {
+            // Long names are printed on their own line above the timings
+            let name = match caps[1].trim() {
+                "" => previous_line.trim(),
+                name => name,
+            };
+            let estimate = parse_number(&caps[2])
+                .zip(unit_ns(&caps[3]))
+                .map(|(value, unit)| value * unit);
+            let high = parse_number(&caps[4])
+                .zip(unit_ns(&caps[5]))
+                .map(|(value, unit)| value * unit);
+            if let (Some(mean_ns), false) = (estimate, name.is_empty()) {
+                results.push(BenchResult {
+                    name: name.to_string(),
+                    mean_ns,
+                    deviation_ns: high.map(|high| high - mean_ns),
+                });
+            }
+        }
+        if !line.trim().is_empty() {
+            previous_line = line;
+        }
+    }
+    results
+}
+
+/// Parse a hyperfine `--export-json` report
+pub fn parse_hyperfine_json(json: &str) -> Result<Vec<BenchResult>> {
+    #[derive(Deserialize)]
+    struct Export {
+        results: Vec<Entry>,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        command: String,
+        mean: f64,
+        stddev: Option<f64>,
+    }
+
+    let export: Export = serde_json::from_str(json)?;
+    Ok(export
+        .results
+        .into_iter()
+        .map(|entry| BenchResult {
+            name: entry.command,
+            mean_ns: entry.mean * 1e9,
+            deviation_ns: entry.stddev.map(|s| s * 1e9),
+        })
+        .collect())
+}
+
+/// Stored benchmark results for a project
+pub struct BenchStore {
+    dir: PathBuf,
+}
+
+impl BenchStore {
+    /// Create a store for the project at `project_root`
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Self {
+        Self {
+            dir: project_root.as_ref().join(".AuroraHeart").join("bench"),
+        }
+    }
+
+    fn path(&self, commit: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", commit))
+    }
+
+    /// Load the stored run for a commit key
+    pub fn load(&self, commit: &str) -> Result<Option<BenchRun>> {
+        let path = self.path(commit);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// All stored runs, most recent first
+    pub fn list(&self) -> Result<Vec<BenchRun>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut runs = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                runs.push(serde_json::from_str::<BenchRun>(&std::fs::read_to_string(
+                    path,
+                )?)?);
+            }
+        }
+        runs.sort_by_key(|run| std::cmp::Reverse(run.recorded_at));
+        Ok(runs)
+    }
+
+    /// Merge results into the stored run for `commit` and return its path
+    pub fn record(&self, commit: &str, results: &[BenchResult]) -> Result<PathBuf> {
+        let mut run = self.load(commit)?.unwrap_or_else(|| BenchRun {
+            commit: commit.to_string(),
+            recorded_at: 0,
+            results: BTreeMap::new(),
+        });
+        run.recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+            .max(run.recorded_at);
+        for result in results {
+            run.results.insert(result.name.clone(), result.clone());
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(commit);
+        std::fs::write(&path, serde_json::to_string_pretty(&run)?)?;
+        Ok(path)
+    }
+
+    /// The most recent stored run other than `commit`
+    pub fn latest_baseline(&self, commit: &str) -> Result<Option<BenchRun>> {
+        Ok(self.list()?.into_iter().find(|run| run.commit != commit))
+    }
+}
+
+/// Runs benchmarks for a project and compares them with stored results
+pub struct Benchmark {
+    project_root: PathBuf,
+    command: BenchCommand,
+    threshold_percent: f64,
+}
+
+impl Benchmark {
+    /// Create a benchmark run for the project at `project_root`
+    pub fn new<P: AsRef<Path>>(project_root: P, command: BenchCommand) -> Self {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            command,
+            threshold_percent: DEFAULT_THRESHOLD_PERCENT,
+        }
+    }
+
+    /// Set the change (in percent) beyond which a benchmark is flagged
+    pub fn threshold_percent(mut self, threshold_percent: f64) -> Self {
+        self.threshold_percent = threshold_percent.abs();
+        self
+    }
+
+    /// Storage key for the current state of the working tree
+    pub fn commit_key(&self) -> String {
+        let Some(commit) = self.git(&["rev-parse", "--short", "HEAD"]) else {
+            return NO_COMMIT.to_string();
+        };
+        let dirty = self
+            .git(&["status", "--porcelain", "--untracked-files=no"])
+            .is_some_and(|status| !status.is_empty());
+        if dirty {
+            format!("{}-dirty", commit)
+        } else {
+            commit
+        }
+    }
+
+    fn git(&self, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.project_root)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Run the benchmarks, store the results, and compare with a baseline
+    ///
+    /// `baseline` names a stored commit key; when omitted the most recent
+    /// stored run for a different key is used.
+    pub fn run(&self, baseline: Option<&str>) -> Result<BenchReport> {
+        let store = BenchStore::for_project(&self.project_root);
+        let commit = self.commit_key();
+
+        let baseline_run = match baseline {
+            Some(key) => Some(
+                store
+                    .load(key)?
+                    .ok_or_else(|| BenchError::UnknownBaseline(key.to_string()))?,
+            ),
+            None => store.latest_baseline(&commit)?,
+        };
+
+        let results = self.execute()?;
+        let stored_at = store.record(&commit, &results)?;
+
+        let comparisons = compare(baseline_run.as_ref(), &results, self.threshold_percent);
+
+        Ok(BenchReport {
+            commit,
+            baseline: baseline_run.map(|run| run.commit),
+            threshold_percent: self.threshold_percent,
+            comparisons,
+            stored_at,
+        })
+    }
+
+    /// Run the benchmark command and parse its timings
+    fn execute(&self) -> Result<Vec<BenchResult>> {
+        match &self.command {
+            BenchCommand::Cargo { args } => {
+                let display = format!("cargo bench {}", args.join(" ")).trim().to_string();
+                let output =
+                    self.run_command(Command::new("cargo").arg("bench").args(args), &display)?;
+                let results = parse_cargo_bench(&output);
+                if results.is_empty() {
+                    return Err(BenchError::NoResults(display));
+                }
+                Ok(results)
+            }
+            BenchCommand::Hyperfine {
+                commands,
+                runs,
+                warmup,
+            } => {
+                let export = std::env::temp_dir().join(format!(
+                    "auroraheart-hyperfine-{}-{}.json",
+                    std::process::id(),
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_nanos())
+                        .unwrap_or(0)
+                ));
+
+                let mut command = Command::new("hyperfine");
+                command.arg("--export-json").arg(&export);
+                if let Some(runs) = runs {
+                    command.arg("--runs").arg(runs.to_string());
+                }
+                if let Some(warmup) = warmup {
+                    command.arg("--warmup").arg(warmup.to_string());
+                }
+                command.args(commands);
+
+                let display = format!("hyperfine {}", commands.join(" "));
+                let result = self
+                    .run_command(&mut command, &display)
+                    .and_then(|_| Ok(std::fs::read_to_string(&export)?))
+                    .and_then(|json| parse_hyperfine_json(&json));
+                // The export is only needed to read the timings
+                let _ = std::fs::remove_file(&export);
+
+                let results = result?;
+                if results.is_empty() {
+                    return Err(BenchError::NoResults(display));
+                }
+                Ok(results)
+            }
+        }
+    }
+
+    /// Run a command in the project root and return its stdout
+    fn run_command(&self, command: &mut Command, display: &str) -> Result<String> {
+        let output = command
+            .current_dir(&self.project_root)
+            .output()
+            .map_err(|e| BenchError::CommandFailed {
+                command: display.to_string(),
+                output: e.to_string(),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let lines: Vec<&str> = stderr.lines().collect();
+            let tail = lines[lines.len().saturating_sub(MAX_FAILURE_LINES)..].join("\n");
+            return Err(BenchError::CommandFailed {
+                command: display.to_string(),
+                output: tail,
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn result(name: &str, mean_ns: f64) -> BenchResult {
+        BenchResult {
+            name: name.to_string(),
+            mean_ns,
+            deviation_ns: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_cargo_bench_output() {
+        let output = "\
+running 2 tests
+test bench_add   ... bench:       1,234 ns/iter (+/- 56)
+test bench_parse ... bench:         987.50 ns/iter (+/- 12.25)
+
+fib 20                  time:   [26.029 us 26.251 us 26.505 us]
+                        change: [-1.2% +0.4% +2.1%] (p = 0.62 > 0.05)
+a_very_long_benchmark_name_that_wraps
+                        time:   [1.0000 ms 1.5000 ms 2.0000 ms]
+";
+        let results = parse_cargo_bench(output);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].name, "bench_add");
+        assert_eq!(results[0].mean_ns, 1234.0);
+        assert_eq!(results[0].deviation_ns, Some(56.0));
+        assert_eq!(results[1].mean_ns, 987.5);
+        assert_eq!(results[2].name, "fib 20");
+        assert!((results[2].mean_ns - 26_251.0).abs() < 1e-6);
+        assert_eq!(results[3].name, "a_very_long_benchmark_name_that_wraps");
+        assert_eq!(results[3].mean_ns, 1_500_000.0);
+        assert_eq!(results[3].deviation_ns, Some(500_000.0));
+    }
+
+    #[test]
+    fn test_parse_hyperfine_json() {
+        let json = r#"{"results":[{"command":"sleep 0.1","mean":0.105,"stddev":0.002,"median":0.104,"times":[0.1]}]}"#;
+        let results = parse_hyperfine_json(json).unwrap();
+        assert_eq!(results[0].name, "sleep 0.1");
+        assert!((results[0].mean_ns - 105_000_000.0).abs() < 1.0);
+        assert!((results[0].deviation_ns.unwrap() - 2_000_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_compare_classifies_changes() {
+        let baseline = BenchRun {
+            commit: "abc1234".to_string(),
+            recorded_at: 1,
+            results: [
+                ("slower".to_string(), result("slower", 100.0)),
+                ("faster".to_string(), result("faster", 100.0)),
+                ("same".to_string(), result("same", 100.0)),
+            ]
+            .into(),
+        };
+        let current = vec![
+            result("slower", 120.0),
+            result("faster", 50.0),
+            result("same", 103.0),
+            result("added", 10.0),
+        ];
+
+        let verdicts: Vec<_> = compare(Some(&baseline), &current, 5.0)
+            .into_iter()
+            .map(|c| c.verdict)
+            .collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                BenchVerdict::Regressed,
+                BenchVerdict::Improved,
+                BenchVerdict::Unchanged,
+                BenchVerdict::New,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_store_merges_and_finds_baseline() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BenchStore::for_project(temp_dir.path());
+        assert!(store.latest_baseline("abc1234").unwrap().is_none());
+
+        store.record("abc1234", &[result("a", 1.0)]).unwrap();
+        let path = store.record("abc1234", &[result("b", 2.0)]).unwrap();
+        assert!(path.ends_with(".AuroraHeart/bench/abc1234.json"));
+
+        let run = store.load("abc1234").unwrap().unwrap();
+        assert_eq!(run.results.len(), 2);
+
+        assert!(store.latest_baseline("abc1234").unwrap().is_none());
+        assert_eq!(
+            store
+                .latest_baseline("abc1234-dirty")
+                .unwrap()
+                .unwrap()
+                .commit,
+            "abc1234"
+        );
+    }
+
+    #[test]
+    fn test_report_format() {
+        let report = BenchReport {
+            commit: "abc1234-dirty".to_string(),
+            baseline: Some("abc1234".to_string()),
+            threshold_percent: 5.0,
+            comparisons: vec![
+                BenchComparison {
+                    name: "parse".to_string(),
+                    baseline_ns: Some(2_000_000.0),
+                    current_ns: 2_500_000.0,
+                    change_percent: Some(25.0),
+                    verdict: BenchVerdict::Regressed,
+                },
+                BenchComparison {
+                    name: "lex".to_string(),
+                    baseline_ns: None,
+                    current_ns: 750.0,
+                    change_percent: None,
+                    verdict: BenchVerdict::New,
+                },
+            ],
+            stored_at: PathBuf::from(".AuroraHeart/bench/abc1234-dirty.json"),
+        };
+
+        let text = report.format();
+        assert!(text.starts_with("Benchmarks for abc1234-dirty compared with abc1234"));
+        assert!(text.contains("❌ parse: 2.000 ms -> 2.500 ms (+25.0%)"));
+        assert!(text.contains("🆕 lex: 750.0 ns"));
+        assert!(text.contains("1 benchmark(s) regressed"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Changelog sections from conventional commits
+//!
+//! [`commits_in_range`] reads the commits between two refs, and a
+//! [`Release`] groups them by conventional commit type into a Markdown
+//! section: breaking changes first, then features, bug fixes, and the other
+//! types, with commits that do not follow the convention under "Other
+//! Changes". [`ChangelogPreview`] merges the section into `CHANGELOG.md`,
+//! replacing a section with the same title, and diffs the result against the
+//! file on disk so it can be reviewed before [`save_changelog`] writes it.
+
+use crate::diff::unified_diff;
+use crate::git_conventions::parse_conventional;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Changelog file, relative to the project root
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Section headings in order, with the commit types listed under them
+const SECTIONS: &[(&str, &[&str])] = &[
+    ("Features", &["feat"]),
+    ("Bug Fixes", &["fix"]),
+    ("Performance", &["perf"]),
+    ("Reverts", &["revert"]),
+    ("Refactoring", &["refactor"]),
+    ("Documentation", &["docs"]),
+    ("Maintenance", &["build", "ci", "chore", "style", "test"]),
+];
+
+/// Errors that can occur while generating a changelog
+#[derive(Error, Debug)]
+pub enum ChangelogError {
+    /// IO error while reading or writing the changelog
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// git could not list the commits
+    #[error("git log failed: {0}")]
+    Git(String),
+}
+
+/// Result type for changelog operations
+pub type Result<T> = std::result::Result<T, ChangelogError>;
+
+/// A commit as listed in a changelog
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogCommit {
+    /// Abbreviated commit hash
+    pub hash: String,
+    /// Conventional commit type; `None` when the subject does not follow the convention
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    /// The subject's description, or the whole subject
+    pub description: String,
+    /// Marked with `!` or a `BREAKING CHANGE:` footer
+    pub breaking: bool,
+}
+
+impl ChangelogCommit {
+    /// Parse a commit from its hash, subject line, and body
+    pub fn parse(hash: &str, subject: &str, body: &str) -> Self {
+        let footer_breaking = body
+            .lines()
+            .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+        match parse_conventional(subject.trim()) {
+            Some(parsed) => Self {
+                hash: hash.to_string(),
+                commit_type: Some(parsed.commit_type.to_string()),
+                scope: parsed.scope.map(str::to_string),
+                description: parsed.description.to_string(),
+                breaking: parsed.breaking || footer_breaking,
+            },
+            None => Self {
+                hash: hash.to_string(),
+                commit_type: None,
+                scope: None,
+                description: subject.trim().to_string(),
+                breaking: footer_breaking,
+            },
+        }
+    }
+
+    /// The commit as a Markdown list item
+    fn entry(&self) -> String {
+        match &self.scope {
+            Some(scope) => format!("- **{}:** {} ({})", scope, self.description, self.hash),
+            None => format!("- {} ({})", self.description, self.hash),
+        }
+    }
+}
+
+/// The commits in `range`, newest first, skipping merges
+///
+/// `range` is a git revision range such as `v1.2.0..v1.3.0`; a single ref
+/// means everything since it (`v1.2.0..HEAD`).
+pub fn commits_in_range(project_root: &Path, range: &str) -> Result<Vec<ChangelogCommit>> {
+    let range = if range.contains("..") {
+        range.to_string()
+    } else {
+        format!("{}..HEAD", range)
+    };
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--format=%h%x1f%s%x1f%b%x1e", &range, "--"])
+        .current_dir(project_root)
+        .output()?;
+    if !output.status.success() {
+        return Err(ChangelogError::Git(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+            let hash = fields.next().filter(|hash| !hash.is_empty())?;
+            let subject = fields.next().unwrap_or_default();
+            Some(ChangelogCommit::parse(hash, subject, fields.next().unwrap_or_default()))
+        })
+        .collect())
+}
+
+/// A release's entry in the changelog
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    /// Version or `Unreleased`
+    pub title: String,
+    /// Release date, e.g. `2024-05-01`
+    pub date: Option<String>,
+    pub commits: Vec<ChangelogCommit>,
+}
+
+impl Release {
+    /// The release as a Markdown section headed `## title - date`
+    pub fn render(&self) -> String {
+        let mut section = match &self.date {
+            Some(date) => format!("## {} - {}\n", self.title, date),
+            None => format!("## {}\n", self.title),
+        };
+
+        let mut groups: Vec<(&str, Vec<&ChangelogCommit>)> = Vec::new();
+        groups.push(("Breaking Changes", self.commits.iter().filter(|c| c.breaking).collect()));
+        for (heading, types) in SECTIONS {
+            let commits = self
+                .commits
+                .iter()
+                .filter(|c| !c.breaking && c.commit_type.as_deref().is_some_and(|t| types.contains(&t)))
+                .collect();
+            groups.push((heading, commits));
+        }
+        let other = self.commits.iter().filter(|c| {
+            !c.breaking
+                && !c
+                    .commit_type
+                    .as_deref()
+                    .is_some_and(|t| SECTIONS.iter().any(|(_, types)| types.contains(&t)))
+        });
+        groups.push(("Other Changes", other.collect()));
+
+        for (heading, commits) in groups.into_iter().filter(|(_, commits)| !commits.is_empty()) {
+            section.push_str(&format!("\n### {}\n\n", heading));
+            for commit in commits {
+                section.push_str(&commit.entry());
+                section.push('\n');
+            }
+        }
+        if self.commits.is_empty() {
+            section.push_str("\nNo changes.\n");
+        }
+        section
+    }
+}
+
+/// `existing` changelog text with `section` in place of the section with the
+/// same title, or above the newest release when there is none
+pub fn merge_section(existing: &str, section: &str) -> String {
+    let section = format!("{}\n", section.trim_end());
+    if existing.trim().is_empty() {
+        return format!("# Changelog\n\n{}", section);
+    }
+
+    let title = section.lines().next().and_then(heading_title);
+    let lines: Vec<&str> = existing.split_inclusive('\n').collect();
+    let headings: Vec<usize> = (0..lines.len()).filter(|&i| heading_title(lines[i]).is_some()).collect();
+    let replaced = headings
+        .iter()
+        .position(|&i| title.is_some() && heading_title(lines[i]) == title);
+
+    let (start, end) = match replaced {
+        Some(index) => (headings[index], headings.get(index + 1).copied().unwrap_or(lines.len())),
+        None => {
+            let at = headings.first().copied().unwrap_or(lines.len());
+            (at, at)
+        }
+    };
+    let mut merged: String = lines[..start].concat();
+    if !merged.is_empty() && !merged.ends_with("\n\n") {
+        merged.push_str(if merged.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    merged.push_str(&section);
+    if end < lines.len() {
+        merged.push('\n');
+        merged.push_str(&lines[end..].concat());
+    }
+    merged
+}
+
+/// The release title of a `## ` heading: its first word without brackets
+fn heading_title(line: &str) -> Option<&str> {
+    let title = line.strip_prefix("## ")?.split_whitespace().next()?;
+    Some(title.trim_start_matches('[').trim_end_matches(']'))
+}
+
+/// A proposed `CHANGELOG.md` awaiting the user's review
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogPreview {
+    /// Path relative to the project root
+    pub path: String,
+    /// Whether the file already exists
+    pub exists: bool,
+    /// Content to write
+    pub content: String,
+    /// Unified diff from the current content
+    pub diff: String,
+}
+
+impl ChangelogPreview {
+    /// Propose the project's changelog with `section` merged in
+    pub fn new(project_root: &Path, section: &str) -> Result<Self> {
+        let current = match std::fs::read_to_string(project_root.join(CHANGELOG_FILE)) {
+            Ok(current) => Some(current),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let content = merge_section(current.as_deref().unwrap_or_default(), section);
+        let old_label = if current.is_some() { CHANGELOG_FILE } else { "/dev/null" };
+        Ok(Self {
+            path: CHANGELOG_FILE.to_string(),
+            exists: current.is_some(),
+            diff: unified_diff(current.as_deref().unwrap_or_default(), &content, old_label, CHANGELOG_FILE),
+            content,
+        })
+    }
+}
+
+/// Write reviewed changelog `content` to the project's `CHANGELOG.md`
+pub fn save_changelog(project_root: &Path, content: &str) -> Result<PathBuf> {
+    let path = project_root.join(CHANGELOG_FILE);
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_release() {
+        let release = Release {
+            title: "1.2.0".to_string(),
+            date: Some("2024-05-01".to_string()),
+            commits: vec![
+                ChangelogCommit::parse("a1", "feat(ui): add dark mode", ""),
+                ChangelogCommit::parse("b2", "fix: handle empty files", ""),
+                ChangelogCommit::parse("c3", "refactor!: drop the v1 API", ""),
+                ChangelogCommit::parse("d4", "chore: bump deps", ""),
+                ChangelogCommit::parse("e5", "Update README", ""),
+                ChangelogCommit::parse("f6", "feat: new config format", "Details\n\nBREAKING CHANGE: old files are ignored"),
+            ],
+        };
+        assert_eq!(
+            release.render(),
+            "## 1.2.0 - 2024-05-01\n\
+             \n### Breaking Changes\n\n- drop the v1 API (c3)\n- new config format (f6)\n\
+             \n### Features\n\n- **ui:** add dark mode (a1)\n\
+             \n### Bug Fixes\n\n- handle empty files (b2)\n\
+             \n### Maintenance\n\n- bump deps (d4)\n\
+             \n### Other Changes\n\n- Update README (e5)\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_section() {
+        let section = "## Unreleased\n\n### Features\n\n- new (a1)\n";
+        assert_eq!(merge_section("", section), format!("# Changelog\n\n{}", section));
+
+        let existing = "# Changelog\n\nIntro.\n\n## 1.0.0 - 2024-01-01\n\n- first (z9)\n";
+        let merged = merge_section(existing, section);
+        assert_eq!(
+            merged,
+            "# Changelog\n\nIntro.\n\n## Unreleased\n\n### Features\n\n- new (a1)\n\n## 1.0.0 - 2024-01-01\n\n- first (z9)\n"
+        );
+
+        // Regenerating a release replaces its section
+        let updated = merge_section(&merged, "## Unreleased\n\n- newer (b2)\n");
+        assert_eq!(
+            updated,
+            "# Changelog\n\nIntro.\n\n## Unreleased\n\n- newer (b2)\n\n## 1.0.0 - 2024-01-01\n\n- first (z9)\n"
+        );
+        let release = merge_section(&updated, "## [1.0.0] - 2024-01-02\n\n- first (z9)\n- fix (y8)\n");
+        assert!(release.ends_with("## [1.0.0] - 2024-01-02\n\n- first (z9)\n- fix (y8)\n"));
+        assert!(release.contains("- newer (b2)\n\n## [1.0.0]"));
+    }
+}
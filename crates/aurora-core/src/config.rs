@@ -3,7 +3,9 @@
 //! This module handles loading, saving, and managing project-specific configuration
 //! stored in `.AuroraHeart/config.toml` files.
 
+use crate::sampling::SamplingParams;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -45,6 +47,94 @@ pub struct Config {
     /// Editor settings
     #[serde(default)]
     pub editor: EditorConfig,
+
+    /// Pre-commit quality gate settings
+    #[serde(default)]
+    pub quality_gate: QualityGateConfig,
+
+    /// Commit message and branch naming rules
+    #[serde(default)]
+    pub git: GitConfig,
+
+    /// Issue tracker integration
+    #[serde(default)]
+    pub issue_tracker: IssueTrackerConfig,
+
+    /// Watch mode settings
+    #[serde(default)]
+    pub watch: WatchConfig,
+
+    /// Profiler selection and sampling settings
+    #[serde(default)]
+    pub profile: ProfileConfig,
+
+    /// Local file history limits
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Answers for commands run by tools that prompt for input
+    #[serde(default)]
+    pub tool_input: ToolInputConfig,
+
+    /// Shell used by the bash tool
+    #[serde(default)]
+    pub shell: ShellConfig,
+
+    /// Reply language and locale formatting
+    #[serde(default)]
+    pub locale: LocaleConfig,
+
+    /// Integrated terminal settings
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+
+    /// Files bulk replacement leaves alone
+    #[serde(default)]
+    pub replace: ReplaceConfig,
+
+    /// Required license header and dependency license policy
+    #[serde(default)]
+    pub license: LicenseConfig,
+
+    /// Container engine used by the `container` tool
+    #[serde(default)]
+    pub container: ContainerConfig,
+
+    /// Database the `db_schema` tool introspects
+    #[serde(default)]
+    pub database: DatabaseConfig,
+
+    /// Speech-to-text for voice input
+    #[serde(default)]
+    pub voice: VoiceConfig,
+
+    /// Filters messages pass through before they are sent to the provider
+    #[serde(default)]
+    pub outbound_filter: OutboundFilterConfig,
+
+    /// Formatters for `code_format` by language (`[formatters.<language>]`
+    /// tables), used ahead of the built-in ones
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub formatters: BTreeMap<String, FormatterDefinition>,
+
+    /// Checkers for `syntax_check` by language (`[syntax_checkers.<language>]`
+    /// tables), used ahead of the built-in ones
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub syntax_checkers: BTreeMap<String, SyntaxCheckerDefinition>,
+
+    /// Client generators for the `api_spec` tool (`[api_generators.<name>]`
+    /// tables)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub api_generators: BTreeMap<String, ApiGeneratorDefinition>,
+
+    /// Named project tasks (`[tasks.<name>]` tables)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, TaskDefinition>,
+
+    /// File tree and tab icons by icon name (`[file_icons.<icon>]` tables),
+    /// used ahead of the built-in ones and those of plugins
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_icons: BTreeMap<String, FileIconDefinition>,
 }
 
 /// Project-specific configuration
@@ -59,6 +149,11 @@ pub struct ProjectConfig {
     /// Project root directory
     #[serde(skip)]
     pub root: Option<PathBuf>,
+
+    /// Ignore patterns hiding paths from the agent and the file tree, in
+    /// `.auroraignore` syntax (read before the `.auroraignore` file itself)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
 }
 
 impl Default for ProjectConfig {
@@ -67,6 +162,7 @@ impl Default for ProjectConfig {
             name: None,
             language: None,
             root: None,
+            ignore: Vec::new(),
         }
     }
 }
@@ -85,6 +181,22 @@ pub struct AgentConfig {
     /// Enabled directive modules
     #[serde(default)]
     pub enabled_directives: Vec<String>,
+
+    /// Default sampling parameters for requests
+    #[serde(default, skip_serializing_if = "SamplingParams::is_empty")]
+    pub sampling: SamplingParams,
+
+    /// Include the user's global `AURORA.md` below the project directives
+    #[serde(default = "default_global_directives")]
+    pub global_directives: bool,
+
+    /// Default spending limit for each conversation
+    #[serde(default, skip_serializing_if = "BudgetConfig::is_unlimited")]
+    pub budget: BudgetConfig,
+
+    /// Model that titles saved sessions; the session's own model when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_model: Option<String>,
 }
 
 fn default_model() -> String {
@@ -95,12 +207,20 @@ fn default_max_tokens() -> usize {
     200000
 }
 
+fn default_global_directives() -> bool {
+    true
+}
+
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
             model: default_model(),
             max_tokens: default_max_tokens(),
             enabled_directives: Vec::new(),
+            sampling: SamplingParams::default(),
+            global_directives: default_global_directives(),
+            budget: BudgetConfig::default(),
+            title_model: None,
         }
     }
 }
@@ -119,6 +239,10 @@ pub struct EditorConfig {
     /// Show line numbers
     #[serde(default = "default_show_line_numbers")]
     pub show_line_numbers: bool,
+
+    /// Files larger than this many bytes open as a truncated, read-only preview
+    #[serde(default = "default_max_open_file_size")]
+    pub max_open_file_size: u64,
 }
 
 fn default_tab_size() -> usize {
@@ -133,12 +257,1002 @@ fn default_show_line_numbers() -> bool {
     true
 }
 
+fn default_max_open_file_size() -> u64 {
+    64 * 1024 * 1024
+}
+
 impl Default for EditorConfig {
     fn default() -> Self {
         Self {
             tab_size: default_tab_size(),
             use_spaces: default_use_spaces(),
             show_line_numbers: default_show_line_numbers(),
+            max_open_file_size: default_max_open_file_size(),
+        }
+    }
+}
+
+/// Pre-commit quality gate configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QualityGateConfig {
+    /// Run the gate before every commit made through the IDE
+    #[serde(default = "default_run_on_commit")]
+    pub run_on_commit: bool,
+
+    /// Steps to run, in order
+    #[serde(default = "default_quality_gate_steps")]
+    pub steps: Vec<QualityGateStep>,
+}
+
+/// Kind of check performed by a quality gate step
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityGateStepKind {
+    /// Check formatting without modifying files
+    FormatCheck,
+    /// Run the project linter
+    Lint,
+    /// Run tests (optionally a subset via `args`)
+    Test,
+    /// Scan files for committed secrets
+    SecretScan,
+    /// Run a custom command
+    Custom,
+}
+
+/// A single quality gate step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QualityGateStep {
+    /// Display name of the step
+    pub name: String,
+
+    /// Kind of check
+    pub kind: QualityGateStepKind,
+
+    /// Whether the step runs
+    #[serde(default = "default_step_enabled")]
+    pub enabled: bool,
+
+    /// Command override (required for custom steps; otherwise detected from the project language)
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Extra arguments appended to the command (e.g. a test filter)
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Seconds the command may run before it is stopped and the step fails
+    #[serde(default = "default_step_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl QualityGateStep {
+    /// Create an enabled step using the detected command for its kind
+    pub fn new(name: impl Into<String>, kind: QualityGateStepKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            enabled: true,
+            command: None,
+            args: Vec::new(),
+            timeout_secs: default_step_timeout_secs(),
+        }
+    }
+}
+
+fn default_run_on_commit() -> bool {
+    true
+}
+
+fn default_step_enabled() -> bool {
+    true
+}
+
+fn default_step_timeout_secs() -> u64 {
+    600
+}
+
+fn default_quality_gate_steps() -> Vec<QualityGateStep> {
+    let disabled = |name: &str, kind| QualityGateStep {
+        enabled: false,
+        ..QualityGateStep::new(name, kind)
+    };
+
+    vec![
+        disabled("format", QualityGateStepKind::FormatCheck),
+        disabled("lint", QualityGateStepKind::Lint),
+        disabled("test", QualityGateStepKind::Test),
+        QualityGateStep::new("secrets", QualityGateStepKind::SecretScan),
+    ]
+}
+
+impl Default for QualityGateConfig {
+    fn default() -> Self {
+        Self {
+            run_on_commit: default_run_on_commit(),
+            steps: default_quality_gate_steps(),
+        }
+    }
+}
+
+/// Commit message and branch naming configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GitConfig {
+    /// Require commit subjects in conventional commit form (`type(scope): description`)
+    #[serde(default)]
+    pub conventional_commits: bool,
+
+    /// Commit types accepted when conventional commits are required
+    #[serde(default = "default_commit_types")]
+    pub commit_types: Vec<String>,
+
+    /// Maximum length of the commit subject line
+    #[serde(default = "default_max_subject_length")]
+    pub max_subject_length: usize,
+
+    /// Require an issue reference somewhere in the commit message
+    #[serde(default)]
+    pub require_issue_reference: bool,
+
+    /// Regex that matches an issue reference (e.g. `#123` or `PROJ-123`)
+    #[serde(default = "default_issue_reference_pattern")]
+    pub issue_reference_pattern: String,
+
+    /// Regex that new branch names must match (any valid git ref name if unset)
+    #[serde(default)]
+    pub branch_pattern: Option<String>,
+}
+
+fn default_commit_types() -> Vec<String> {
+    [
+        "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore",
+        "revert",
+    ]
+    .iter()
+    .map(|t| t.to_string())
+    .collect()
+}
+
+fn default_max_subject_length() -> usize {
+    72
+}
+
+fn default_issue_reference_pattern() -> String {
+    r"(#\d+|\b[A-Z][A-Z0-9]+-\d+\b)".to_string()
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            conventional_commits: false,
+            commit_types: default_commit_types(),
+            max_subject_length: default_max_subject_length(),
+            require_issue_reference: false,
+            issue_reference_pattern: default_issue_reference_pattern(),
+            branch_pattern: None,
+        }
+    }
+}
+
+/// Supported issue trackers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueTrackerKind {
+    /// Atlassian Jira
+    Jira,
+    /// GitHub Issues for the `origin` repository
+    #[serde(rename = "github")]
+    GitHub,
+    /// Linear
+    Linear,
+}
+
+/// Issue tracker configuration
+///
+/// Access tokens are kept in the credential store, not in this file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IssueTrackerConfig {
+    /// Tracker to query (inferred from the issue ID if unset)
+    #[serde(default)]
+    pub provider: Option<IssueTrackerKind>,
+
+    /// Jira site URL (e.g. `https://example.atlassian.net`)
+    #[serde(default)]
+    pub jira_url: Option<String>,
+
+    /// Jira account email used with the API token
+    #[serde(default)]
+    pub jira_email: Option<String>,
+}
+
+/// Watch mode configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchConfig {
+    /// How often watched files are checked for changes, in milliseconds
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+
+    /// Attach the output of failing watch runs to the next message sent to the agent
+    #[serde(default)]
+    pub inject_failures: bool,
+}
+
+fn default_poll_interval_ms() -> u64 {
+    500
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: default_poll_interval_ms(),
+            inject_failures: false,
+        }
+    }
+}
+
+/// Sampling profiler used by the `profile` tool
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfilerKind {
+    /// Linux `perf record`
+    Perf,
+    /// DTrace `profile` provider (macOS, BSD)
+    Dtrace,
+    /// `py-spy` for Python programs
+    PySpy,
+}
+
+/// Profiling configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProfileConfig {
+    /// Profiler per language name (e.g. `python = "py_spy"`), overriding the defaults
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profilers: BTreeMap<String, ProfilerKind>,
+
+    /// Sampling frequency in Hz
+    #[serde(default = "default_sample_frequency")]
+    pub frequency: u32,
+
+    /// Number of hotspots included in the summary
+    #[serde(default = "default_top_hotspots")]
+    pub top_n: usize,
+}
+
+fn default_sample_frequency() -> u32 {
+    997
+}
+
+fn default_top_hotspots() -> usize {
+    20
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            profilers: BTreeMap::new(),
+            frequency: default_sample_frequency(),
+            top_n: default_top_hotspots(),
+        }
+    }
+}
+
+/// Local file history configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryConfig {
+    /// Versions kept per file; older versions are pruned
+    #[serde(default = "default_max_versions")]
+    pub max_versions: usize,
+
+    /// Files larger than this many bytes are not recorded
+    #[serde(default = "default_max_history_file_size")]
+    pub max_file_size: u64,
+}
+
+fn default_max_versions() -> usize {
+    50
+}
+
+fn default_max_history_file_size() -> u64 {
+    1024 * 1024
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_versions: default_max_versions(),
+            max_file_size: default_max_history_file_size(),
+        }
+    }
+}
+
+/// Handling of commands run by tools that wait for input
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolInputConfig {
+    /// Milliseconds without output after which a command is assumed to be prompting
+    #[serde(default = "default_input_idle_timeout_ms")]
+    pub idle_timeout_ms: u64,
+
+    /// Answers given automatically to matching prompts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<PromptAnswer>,
+}
+
+fn default_input_idle_timeout_ms() -> u64 {
+    2000
+}
+
+impl Default for ToolInputConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_ms: default_input_idle_timeout_ms(),
+            answers: Vec::new(),
+        }
+    }
+}
+
+/// An automatic answer to a command prompt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PromptAnswer {
+    /// Regular expression matched against the last line of output
+    pub prompt: String,
+    /// Line written to the command's stdin
+    pub answer: String,
+}
+
+/// Shell that runs commands for the bash tool
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShellKind {
+    /// POSIX `sh`
+    Sh,
+    /// GNU Bash
+    Bash,
+    /// Z shell
+    Zsh,
+    /// fish
+    Fish,
+    /// PowerShell 7+ (`pwsh`)
+    Pwsh,
+    /// Windows PowerShell 5 (`powershell`)
+    Powershell,
+    /// Windows `cmd.exe`
+    Cmd,
+}
+
+/// Shell configuration for the bash tool
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ShellConfig {
+    /// Shell to use; `sh` on Unix and PowerShell on Windows when unset
+    #[serde(default)]
+    pub kind: Option<ShellKind>,
+
+    /// Shell executable, when it is not the shell's usual name on PATH
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// Run commands in a login shell
+    #[serde(default)]
+    pub login: bool,
+
+    /// Run commands in an interactive shell, which reads `.bashrc`/`.zshrc`
+    #[serde(default)]
+    pub interactive: bool,
+
+    /// Skip capturing the login shell's environment at startup
+    #[serde(default)]
+    pub skip_environment_capture: bool,
+}
+
+/// Reply language and locale formatting
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LocaleConfig {
+    /// Language the agent replies in and UI messages are shown in, as a tag
+    /// (`de`, `pt-BR`) or an English name (`German`)
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Locale for dates and sizes in tool output (e.g. `de-DE`); defaults to `language`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Release channel followed by the updater
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    /// Releases only
+    #[default]
+    Stable,
+    /// Releases and pre-releases
+    Beta,
+}
+
+impl UpdateChannel {
+    /// Channel name as used in configuration and release URLs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// Updater settings
+///
+/// Read only from the user's `update.toml` (see [`UpdateConfig::user_path`]),
+/// never from a project, so a repository cannot point the updater at its own
+/// release server. Updates are only offered once `endpoint` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpdateConfig {
+    /// Channel to follow
+    #[serde(default)]
+    pub channel: UpdateChannel,
+
+    /// Check for updates when the app starts
+    #[serde(default = "default_check_on_startup")]
+    pub check_on_startup: bool,
+
+    /// Base URL of the release manifests; the manifest for a channel is
+    /// read from `<endpoint>/<channel>/latest.json`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+fn default_check_on_startup() -> bool {
+    true
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::default(),
+            check_on_startup: default_check_on_startup(),
+            endpoint: None,
+        }
+    }
+}
+
+/// Updater settings file in [`user_config_dir`]
+pub const UPDATE_CONFIG_FILE: &str = "update.toml";
+
+impl UpdateConfig {
+    /// Where the user's updater settings are kept
+    pub fn user_path() -> Option<PathBuf> {
+        user_config_dir().map(|dir| dir.join(UPDATE_CONFIG_FILE))
+    }
+
+    /// The settings saved at `path`; defaults when there is no file
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(toml::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The user's updater settings
+    pub fn load_user() -> Result<Self, ConfigError> {
+        match Self::user_path() {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Integrated terminal settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TerminalConfig {
+    /// Shut down a terminal's shell after this many seconds without input
+    /// or output while nothing runs in the foreground; it is respawned on
+    /// next use in the directory it started in, without the old shell's
+    /// working directory, variables, or background jobs. `0`, the default,
+    /// keeps terminals running.
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+}
+
+impl TerminalConfig {
+    /// How long a terminal may stay idle, or `None` if it is never shut down
+    pub fn idle_timeout(&self) -> Option<std::time::Duration> {
+        (self.idle_timeout_secs > 0).then(|| std::time::Duration::from_secs(self.idle_timeout_secs))
+    }
+}
+
+
+/// Files the `multi_replace` tool leaves alone
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplaceConfig {
+    /// Skip lockfiles, minified bundles, and vendored directories
+    #[serde(default = "default_replace_exclusions")]
+    pub default_exclusions: bool,
+
+    /// More protected paths in `.auroraignore` syntax; `!pattern`
+    /// re-includes a default
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// Skip files with a generated marker (e.g. `@generated`) near the top
+    #[serde(default = "default_skip_generated")]
+    pub skip_generated: bool,
+
+    /// More generated markers, matched case-insensitively
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generated_markers: Vec<String>,
+}
+
+fn default_replace_exclusions() -> bool {
+    true
+}
+
+fn default_skip_generated() -> bool {
+    true
+}
+
+impl Default for ReplaceConfig {
+    fn default() -> Self {
+        Self {
+            default_exclusions: default_replace_exclusions(),
+            exclude: Vec::new(),
+            skip_generated: default_skip_generated(),
+            generated_markers: Vec::new(),
+        }
+    }
+}
+
+/// License policy checked by the `license` analysis
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LicenseConfig {
+    /// Text every source file must carry near the top, e.g.
+    /// `SPDX-License-Identifier: MIT`; `{{year}}` matches any year or range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+
+    /// Extensions of the source files that need the header
+    #[serde(default = "default_license_extensions")]
+    pub extensions: Vec<String>,
+
+    /// Files exempt from the header in `.auroraignore` syntax
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// SPDX identifiers dependencies may use; empty allows any not denied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<String>,
+
+    /// SPDX identifiers dependencies must not use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied: Vec<String>,
+}
+
+fn default_license_extensions() -> Vec<String> {
+    ["rs", "js", "jsx", "ts", "tsx", "py", "go", "c", "h", "cpp", "hpp", "java", "kt", "swift", "rb", "cs"]
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect()
+}
+
+impl Default for LicenseConfig {
+    fn default() -> Self {
+        Self {
+            header: None,
+            extensions: default_license_extensions(),
+            exclude: Vec::new(),
+            allowed: Vec::new(),
+            denied: Vec::new(),
+        }
+    }
+}
+
+impl LicenseConfig {
+    /// Whether there is anything to check
+    pub fn is_configured(&self) -> bool {
+        self.header.is_some() || !self.allowed.is_empty() || !self.denied.is_empty()
+    }
+}
+
+/// Container engine CLI
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerEngine {
+    Docker,
+    Podman,
+}
+
+/// Container tooling configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContainerConfig {
+    /// Engine to use; by default Docker, or Podman when only it is installed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<ContainerEngine>,
+}
+
+/// Database introspection configuration
+///
+/// The connection URL is best left out of the config file: by default it is
+/// read from the `DATABASE_URL` environment variable or the project's `.env`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DatabaseConfig {
+    /// Connection URL (`postgres://`, `mysql://`, or `sqlite:`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Environment variable holding the URL; `DATABASE_URL` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_env: Option<String>,
+
+    /// Directory of SQL migrations, relative to the project root; by default
+    /// every `migrations`, `migration`, or `migrate` directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrations: Option<String>,
+}
+
+/// Speech-to-text backend for voice input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SttBackend {
+    /// A local whisper.cpp build
+    #[default]
+    WhisperCpp,
+    /// An OpenAI-compatible `/audio/transcriptions` endpoint
+    Api,
+}
+
+/// Voice input configuration
+///
+/// The API key is kept in the credential store, not in this file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VoiceConfig {
+    /// Backend that transcribes recordings
+    #[serde(default)]
+    pub backend: SttBackend,
+
+    /// whisper.cpp program; `whisper-cli` on PATH when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whisper_command: Option<String>,
+
+    /// whisper.cpp model file (e.g. `ggml-base.en.bin`), relative to the
+    /// project root or absolute; required for whisper.cpp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whisper_model: Option<PathBuf>,
+
+    /// Base URL of the transcription API; `https://api.openai.com/v1` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+
+    /// Model the API transcribes with; `whisper-1` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_model: Option<String>,
+
+    /// Spoken language as an ISO 639-1 code (e.g. `en`); detected when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+/// What an outbound filter does with text it matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// Replace the matched text and send the rest
+    #[default]
+    Redact,
+    /// Refuse to send the message
+    Block,
+}
+
+/// Outbound content filtering (data loss prevention)
+///
+/// Every message is run through the regex rules, then the commands, then
+/// the WASM filters, in the order they are listed, before it is sent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutboundFilterConfig {
+    /// Record triggered filters in `.AuroraHeart/outbound_audit.jsonl`
+    #[serde(default = "default_filter_audit")]
+    pub audit: bool,
+
+    /// Regular expression rules (`[[outbound_filter.rules]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<FilterRuleDefinition>,
+
+    /// External filter programs (`[[outbound_filter.commands]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<FilterCommandDefinition>,
+
+    /// WebAssembly filter plugins (`[[outbound_filter.wasm]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wasm: Vec<WasmFilterDefinition>,
+}
+
+impl Default for OutboundFilterConfig {
+    fn default() -> Self {
+        Self {
+            audit: true,
+            rules: Vec::new(),
+            commands: Vec::new(),
+            wasm: Vec::new(),
+        }
+    }
+}
+
+fn default_filter_audit() -> bool {
+    true
+}
+
+impl OutboundFilterConfig {
+    /// Whether no filter is configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.commands.is_empty() && self.wasm.is_empty()
+    }
+}
+
+/// A regular expression outbound filter rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilterRuleDefinition {
+    /// Name recorded in the audit log
+    pub name: String,
+
+    /// Pattern, in Rust `regex` syntax
+    pub pattern: String,
+
+    /// What to do with matches
+    #[serde(default)]
+    pub action: FilterAction,
+
+    /// Text matches are redacted to; `[REDACTED:<name>]` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+/// An external program that filters outbound text
+///
+/// The program reads the text on stdin and prints a JSON verdict such as
+/// `{"action": "redact", "text": "...", "reason": "..."}`, where `action`
+/// is `allow`, `redact`, or `block`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilterCommandDefinition {
+    /// Name recorded in the audit log
+    pub name: String,
+
+    /// Program to run
+    pub command: String,
+
+    /// Arguments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Seconds the program may take before it is killed and the request blocked
+    #[serde(default = "default_filter_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_filter_timeout_secs() -> u64 {
+    10
+}
+
+/// A WebAssembly (WASI) filter plugin
+///
+/// The module is run with a WASI runtime and speaks the same stdin/stdout
+/// protocol as a [`FilterCommandDefinition`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WasmFilterDefinition {
+    /// Name recorded in the audit log
+    pub name: String,
+
+    /// Module file, relative to the project root or absolute
+    pub module: PathBuf,
+
+    /// WASI runtime; `wasmtime` on PATH when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+
+    /// Seconds the module may take before it is killed and the request blocked
+    #[serde(default = "default_filter_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// A formatter command for one language
+///
+/// `{file}` in the arguments is replaced with the file's path; when no
+/// argument mentions it, the path is passed last.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FormatterDefinition {
+    /// File extensions it formats, without the dot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+
+    /// Program to run
+    pub command: String,
+
+    /// Arguments that format the file in place
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Arguments that only check the file, failing when it needs formatting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_args: Option<Vec<String>>,
+}
+
+impl FormatterDefinition {
+    /// Arguments for formatting `file`, or for checking it when `check_only`;
+    /// `None` when the formatter has no check mode
+    pub fn arguments(&self, file: &Path, check_only: bool) -> Option<Vec<String>> {
+        let template = if check_only { self.check_args.as_ref()? } else { &self.args };
+        Some(expand_file_arguments(template, file))
+    }
+}
+
+/// A syntax checker command for one language
+///
+/// The command succeeds for valid files and prints its errors otherwise.
+/// Arguments are templated as for [`FormatterDefinition`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyntaxCheckerDefinition {
+    /// File extensions it checks, without the dot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+
+    /// Program to run
+    pub command: String,
+
+    /// Arguments for a normal check
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Arguments for a strict check; `args` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict_args: Option<Vec<String>>,
+}
+
+impl SyntaxCheckerDefinition {
+    /// Arguments for checking `file`
+    pub fn arguments(&self, file: &Path, strict: bool) -> Vec<String> {
+        let template = match &self.strict_args {
+            Some(strict_args) if strict => strict_args,
+            _ => &self.args,
+        };
+        expand_file_arguments(template, file)
+    }
+}
+
+/// The files shown with one icon in the file tree and tabs
+///
+/// Keyed by the icon name the frontend draws, such as `rust` or `markdown`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FileIconDefinition {
+    /// File extensions, without the dot; compound ones such as `d.ts` match
+    /// ahead of their last part
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+
+    /// Exact file names, such as `Dockerfile`, matched ahead of extensions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_names: Vec<String>,
+
+    /// Color as `#rgb` or `#rrggbb`; the built-in color of the icon when
+    /// unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// A command that generates a typed client from an API spec
+///
+/// `{spec}` and `{output}` in the arguments are replaced with the spec's
+/// path and the output directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiGeneratorDefinition {
+    /// Program to run
+    pub command: String,
+
+    /// Arguments, such as `["generate", "-i", "{spec}", "-g", "typescript-fetch", "-o", "{output}"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Spec to generate from, relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+
+    /// Directory the client is written to, relative to the project root
+    pub output: String,
+}
+
+impl ApiGeneratorDefinition {
+    /// Arguments for generating from `spec` into `output`
+    pub fn arguments(&self, spec: &Path, output: &Path) -> Vec<String> {
+        let spec = spec.to_string_lossy();
+        let output = output.to_string_lossy();
+        self.args
+            .iter()
+            .map(|arg| arg.replace("{spec}", &spec).replace("{output}", &output))
+            .collect()
+    }
+}
+
+/// Replace `{file}` in `template` with `file`, or pass it last if unmentioned
+fn expand_file_arguments(template: &[String], file: &Path) -> Vec<String> {
+    let file = file.to_string_lossy();
+    let mut args: Vec<String> = template.iter().map(|arg| arg.replace("{file}", &file)).collect();
+    if !template.iter().any(|arg| arg.contains("{file}")) {
+        args.push(file.to_string());
+    }
+    args
+}
+
+/// The entry of `commands` named `language`, or else the one whose
+/// `extensions` include `extension`
+fn command_for<'a, T>(
+    commands: &'a BTreeMap<String, T>,
+    extensions: impl Fn(&T) -> &[String],
+    language: Option<&str>,
+    extension: Option<&str>,
+) -> Option<(&'a str, &'a T)> {
+    if let Some((name, command)) = language.and_then(|language| commands.get_key_value(language)) {
+        return Some((name, command));
+    }
+    let extension = extension?;
+    commands
+        .iter()
+        .find(|(_, command)| extensions(command).iter().any(|ext| ext.trim_start_matches('.') == extension))
+        .map(|(name, command)| (name.as_str(), command))
+}
+
+/// Spending limit for a conversation, from `[agent.budget]`
+///
+/// When a conversation's spend reaches either limit the agent pauses until
+/// the user chooses to continue. With neither set, spend is unlimited.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct BudgetConfig {
+    /// Most input and output tokens the conversation may use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    /// Most the conversation may cost, in US dollars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+}
+
+impl BudgetConfig {
+    /// Whether no limit is set
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost_usd.is_none()
+    }
+}
+
+/// A named project task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskDefinition {
+    /// Shell command to run
+    pub command: String,
+
+    /// Short description shown in task lists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Tasks that must succeed before this one runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+
+    /// Extra environment variables
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Working directory relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Glob patterns that re-run the task in watch mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watch: Vec<String>,
+}
+
+impl TaskDefinition {
+    /// Create a task that runs `command` with no dependencies
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            description: None,
+            depends_on: Vec::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+            watch: Vec::new(),
         }
     }
 }
@@ -149,11 +1263,46 @@ impl Default for Config {
             project: ProjectConfig::default(),
             agent: AgentConfig::default(),
             editor: EditorConfig::default(),
+            quality_gate: QualityGateConfig::default(),
+            git: GitConfig::default(),
+            issue_tracker: IssueTrackerConfig::default(),
+            watch: WatchConfig::default(),
+            profile: ProfileConfig::default(),
+            history: HistoryConfig::default(),
+            tool_input: ToolInputConfig::default(),
+            shell: ShellConfig::default(),
+            locale: LocaleConfig::default(),
+            terminal: TerminalConfig::default(),
+            replace: ReplaceConfig::default(),
+            license: LicenseConfig::default(),
+            container: ContainerConfig::default(),
+            database: DatabaseConfig::default(),
+            voice: VoiceConfig::default(),
+            outbound_filter: OutboundFilterConfig::default(),
+            formatters: BTreeMap::new(),
+            syntax_checkers: BTreeMap::new(),
+            api_generators: BTreeMap::new(),
+            tasks: BTreeMap::new(),
+            file_icons: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
+    /// The configured formatter for a language name or file extension
+    pub fn formatter(&self, language: Option<&str>, extension: Option<&str>) -> Option<(&str, &FormatterDefinition)> {
+        command_for(&self.formatters, |formatter| &formatter.extensions, language, extension)
+    }
+
+    /// The configured syntax checker for a language name or file extension
+    pub fn syntax_checker(
+        &self,
+        language: Option<&str>,
+        extension: Option<&str>,
+    ) -> Option<(&str, &SyntaxCheckerDefinition)> {
+        command_for(&self.syntax_checkers, |checker| &checker.extensions, language, extension)
+    }
+
     /// Load configuration from a .AuroraHeart directory
     pub fn load<P: AsRef<Path>>(project_root: P) -> Result<Self, ConfigError> {
         let config_path = project_root.as_ref().join(".AuroraHeart").join("config.toml");
@@ -182,12 +1331,21 @@ impl Config {
             std::fs::create_dir_all(&config_dir)?;
         }
 
-        let contents = toml::to_string_pretty(self)?;
-        std::fs::write(&config_path, contents)?;
+        std::fs::write(&config_path, self.to_toml()?)?;
 
         Ok(())
     }
 
+    /// Parse configuration text in `config.toml` format
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Serialize the configuration as it is written to `config.toml`
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
     /// Get the config directory path
     pub fn config_dir(&self) -> Option<PathBuf> {
         self.project.root.as_ref().map(|root| root.join(".AuroraHeart"))
@@ -199,6 +1357,13 @@ impl Config {
     }
 }
 
+/// Per-user configuration directory (`~/.config/auroraheart` on Linux)
+///
+/// Holds settings that apply to every project, such as the global `AURORA.md`.
+pub fn user_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("auroraheart"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,8 +1374,228 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.agent.model, "claude-sonnet-4");
         assert_eq!(config.agent.max_tokens, 200000);
+        assert_eq!(config.agent.title_model, None);
         assert_eq!(config.editor.tab_size, 4);
         assert!(config.editor.use_spaces);
+        assert!(config.quality_gate.run_on_commit);
+        assert_eq!(config.quality_gate.steps.len(), 4);
+    }
+
+    #[test]
+    fn test_quality_gate_config_parsing() {
+        let toml_str = r#"
+            [quality_gate]
+            run_on_commit = false
+
+            [[quality_gate.steps]]
+            name = "unit tests"
+            kind = "test"
+            args = ["--lib"]
+            timeout_secs = 120
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(!config.quality_gate.run_on_commit);
+        assert_eq!(config.quality_gate.steps.len(), 1);
+        assert_eq!(config.quality_gate.steps[0].kind, QualityGateStepKind::Test);
+        assert!(config.quality_gate.steps[0].enabled);
+        assert_eq!(config.quality_gate.steps[0].args, vec!["--lib".to_string()]);
+        assert_eq!(config.quality_gate.steps[0].timeout_secs, 120);
+        assert_eq!(Config::default().quality_gate.steps[0].timeout_secs, 600);
+    }
+
+    #[test]
+    fn test_sampling_config_parsing() {
+        let toml_str = r#"
+            [agent]
+            title_model = "claude-haiku-4-5"
+
+            [agent.sampling]
+            temperature = 0.3
+            stop_sequences = ["</answer>"]
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.agent.model, "claude-sonnet-4");
+        assert_eq!(config.agent.sampling.temperature, Some(0.3));
+        assert_eq!(config.agent.sampling.stop_sequences, vec!["</answer>".to_string()]);
+        assert!(config.agent.sampling.max_tokens.is_none());
+        assert_eq!(config.agent.title_model.as_deref(), Some("claude-haiku-4-5"));
+    }
+
+    #[test]
+    fn test_task_config_parsing() {
+        let toml_str = r#"
+            [tasks.build]
+            command = "cargo build"
+
+            [tasks.test]
+            command = "cargo test"
+            depends_on = ["build"]
+            watch = ["src/**/*.rs"]
+            env = { RUST_LOG = "debug" }
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.tasks.len(), 2);
+        assert_eq!(config.tasks["build"], TaskDefinition::new("cargo build"));
+        let test = &config.tasks["test"];
+        assert_eq!(test.depends_on, vec!["build".to_string()]);
+        assert_eq!(test.watch, vec!["src/**/*.rs".to_string()]);
+        assert_eq!(test.env.get("RUST_LOG").map(String::as_str), Some("debug"));
+
+        assert_eq!(config.watch, WatchConfig::default());
+
+        let round_trip: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(round_trip, config);
+    }
+
+    #[test]
+    fn test_profile_config_parsing() {
+        let toml_str = r#"
+            [profile]
+            top_n = 5
+
+            [profile.profilers]
+            python = "py_spy"
+            rust = "dtrace"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.profile.top_n, 5);
+        assert_eq!(config.profile.frequency, 997);
+        assert_eq!(config.profile.profilers.get("python"), Some(&ProfilerKind::PySpy));
+        assert_eq!(config.profile.profilers.get("rust"), Some(&ProfilerKind::Dtrace));
+    }
+
+    #[test]
+    fn test_formatter_config_parsing() {
+        let toml_str = r#"
+            [formatters.zig]
+            extensions = ["zig"]
+            command = "zig"
+            args = ["fmt", "{file}"]
+            check_args = ["fmt", "--check", "{file}"]
+
+            [formatters.lua]
+            extensions = [".lua"]
+            command = "stylua"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let (name, zig) = config.formatter(None, Some("zig")).unwrap();
+        assert_eq!(name, "zig");
+        assert_eq!(
+            zig.arguments(Path::new("/src/main.zig"), true),
+            Some(vec!["fmt".to_string(), "--check".to_string(), "/src/main.zig".to_string()])
+        );
+        let (name, lua) = config.formatter(Some("lua"), None).unwrap();
+        assert_eq!(name, "lua");
+        assert_eq!(lua.arguments(Path::new("init.lua"), false), Some(vec!["init.lua".to_string()]));
+        assert_eq!(lua.arguments(Path::new("init.lua"), true), None);
+        assert_eq!(config.formatter(None, Some("lua")).unwrap().0, "lua");
+        assert!(config.formatter(Some("rust"), Some("rs")).is_none());
+    }
+
+    #[test]
+    fn test_syntax_checker_config_parsing() {
+        let toml_str = r#"
+            [syntax_checkers.ruby]
+            extensions = ["rb"]
+            command = "ruby"
+            args = ["-c"]
+            strict_args = ["-wc", "{file}"]
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let (name, ruby) = config.syntax_checker(None, Some("rb")).unwrap();
+        assert_eq!(name, "ruby");
+        assert_eq!(ruby.arguments(Path::new("app.rb"), false), vec!["-c".to_string(), "app.rb".to_string()]);
+        assert_eq!(ruby.arguments(Path::new("app.rb"), true), vec!["-wc".to_string(), "app.rb".to_string()]);
+        assert!(config.syntax_checker(Some("python"), Some("py")).is_none());
+    }
+
+    #[test]
+    fn test_api_generator_config_parsing() {
+        let toml_str = r#"
+            [api_generators.web]
+            command = "openapi-generator-cli"
+            args = ["generate", "-i", "{spec}", "-g", "typescript-fetch", "-o", "{output}"]
+            spec = "api/openapi.yaml"
+            output = "web/src/api"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let web = &config.api_generators["web"];
+        assert_eq!(web.spec.as_deref(), Some("api/openapi.yaml"));
+        assert_eq!(
+            web.arguments(Path::new("api/openapi.yaml"), Path::new("web/src/api")),
+            ["generate", "-i", "api/openapi.yaml", "-g", "typescript-fetch", "-o", "web/src/api"]
+        );
+    }
+
+    #[test]
+    fn test_tool_input_config_parsing() {
+        let toml_str = r#"
+            [tool_input]
+            idle_timeout_ms = 500
+
+            [[tool_input.answers]]
+            prompt = "Is this OK\\?"
+            answer = "yes"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.tool_input.idle_timeout_ms, 500);
+        assert_eq!(
+            config.tool_input.answers,
+            vec![PromptAnswer {
+                prompt: "Is this OK\\?".to_string(),
+                answer: "yes".to_string(),
+            }]
+        );
+        assert_eq!(Config::default().tool_input.idle_timeout_ms, 2000);
+    }
+
+    #[test]
+    fn test_shell_config_parsing() {
+        let toml_str = r#"
+            [shell]
+            kind = "zsh"
+            login = true
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.shell.kind, Some(ShellKind::Zsh));
+        assert!(config.shell.login);
+        assert!(!config.shell.interactive);
+        assert!(!config.shell.skip_environment_capture);
+        assert_eq!(Config::default().shell.kind, None);
+    }
+
+    #[test]
+    fn test_update_config_parsing() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(UPDATE_CONFIG_FILE);
+        assert_eq!(UpdateConfig::load(&path).unwrap(), UpdateConfig::default());
+
+        std::fs::write(&path, "channel = \"beta\"\nendpoint = \"https://releases.example.com\"\n").unwrap();
+        let config = UpdateConfig::load(&path).unwrap();
+        assert_eq!(config.channel, UpdateChannel::Beta);
+        assert!(config.check_on_startup);
+        assert_eq!(config.endpoint.as_deref(), Some("https://releases.example.com"));
+        assert_eq!(UpdateConfig::default().channel, UpdateChannel::Stable);
+
+        // Project configuration has no say over updates
+        let project: Config = toml::from_str("[update]\nendpoint = \"https://evil.example.com\"\n").unwrap();
+        assert!(!project.to_toml().unwrap().contains("evil"));
+    }
+
+    #[test]
+    fn test_terminal_idle_timeout() {
+        let config: Config = toml::from_str("[terminal]\nidle_timeout_secs = 1800\n").unwrap();
+        assert_eq!(config.terminal.idle_timeout(), Some(std::time::Duration::from_secs(1800)));
+        assert_eq!(Config::default().terminal.idle_timeout(), None);
     }
 
     #[test]
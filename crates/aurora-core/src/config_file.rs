@@ -0,0 +1,440 @@
+//! Reading JSON, YAML, and TOML configuration files for validation
+//!
+//! Each format is read into JSON values so one [`json_schema`] validator
+//! serves all three. Syntax errors keep the parser's line and column, and
+//! every document records the line each key and item starts on, so schema
+//! errors reported by JSON pointer can be placed in the file.
+//!
+//! [`json_schema`]: crate::json_schema
+
+use crate::yaml::{self, escape_pointer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Format of a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// The format a file's extension indicates
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" | "jsonc" | "json5" => Some(ConfigFormat::Json),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "toml" => Some(ConfigFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Toml => "TOML",
+        }
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ConfigFormat::Json),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "toml" => Ok(ConfigFormat::Toml),
+            _ => Err(format!("Unknown format '{}'; expected json, yaml, or toml", s)),
+        }
+    }
+}
+
+/// A syntax error in a configuration file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSyntaxError {
+    /// Line of the error, from 1
+    pub line: usize,
+    /// Column of the error, from 1, when the parser reports one
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "line {}, column {}: {}", self.line, column, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+/// One document of a configuration file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDocument {
+    /// Line the document starts on, from 1
+    pub line: usize,
+    pub value: Value,
+    /// Line each key and item starts on, by JSON pointer
+    pub positions: BTreeMap<String, usize>,
+}
+
+impl ConfigDocument {
+    /// Line of the value at a JSON pointer, or of its nearest ancestor
+    /// that starts a line of its own
+    pub fn line_of(&self, pointer: &str) -> usize {
+        let mut pointer = pointer;
+        loop {
+            if let Some(line) = self.positions.get(pointer) {
+                return *line;
+            }
+            match pointer.rfind('/') {
+                Some(end) => pointer = &pointer[..end],
+                None => return self.line,
+            }
+        }
+    }
+
+    /// The schema the document names with a top-level `$schema` key
+    pub fn declared_schema(&self) -> Option<&str> {
+        self.value["$schema"].as_str()
+    }
+}
+
+/// Read a configuration file; YAML files may hold several documents
+pub fn parse_config_file(text: &str, format: ConfigFormat) -> Result<Vec<ConfigDocument>, ConfigSyntaxError> {
+    match format {
+        ConfigFormat::Json => {
+            let value: Value = serde_json::from_str(text).map_err(|error| ConfigSyntaxError {
+                line: error.line(),
+                column: Some(error.column()),
+                message: strip_position(&error.to_string()),
+            })?;
+            Ok(vec![ConfigDocument {
+                line: 1,
+                value,
+                positions: json_positions(text),
+            }])
+        }
+        ConfigFormat::Yaml => {
+            let documents = yaml::parse_documents(text).map_err(|error| ConfigSyntaxError {
+                line: error.line,
+                column: None,
+                message: error.message,
+            })?;
+            Ok(documents
+                .into_iter()
+                .map(|document| ConfigDocument {
+                    line: document.line,
+                    value: document.value,
+                    positions: document.positions,
+                })
+                .collect())
+        }
+        ConfigFormat::Toml => {
+            let value: Value = toml::from_str(text).map_err(|error| {
+                let (line, column) = error
+                    .span()
+                    .map(|span| line_and_column(text, span.start))
+                    .unwrap_or((1, 1));
+                ConfigSyntaxError {
+                    line,
+                    column: Some(column),
+                    message: error.message().trim().to_string(),
+                }
+            })?;
+            Ok(vec![ConfigDocument {
+                line: 1,
+                value,
+                positions: toml_positions(text),
+            }])
+        }
+    }
+}
+
+/// Schemas published for well-known configuration files, by file path
+pub fn known_schema(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    let parent = path.parent().and_then(Path::file_name).and_then(|name| name.to_str());
+    let grandparent = path
+        .parent()
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str());
+    let yaml = name.ends_with(".yml") || name.ends_with(".yaml");
+
+    if yaml && parent == Some("workflows") && grandparent == Some(".github") {
+        return Some("https://json.schemastore.org/github-workflow.json");
+    }
+    if yaml && (name.starts_with("docker-compose") || name.starts_with("compose.") || name.starts_with("compose-")) {
+        return Some("https://raw.githubusercontent.com/compose-spec/compose-spec/master/schema/compose-spec.json");
+    }
+    match name.as_str() {
+        "dependabot.yml" | "dependabot.yaml" if parent == Some(".github") => {
+            Some("https://json.schemastore.org/dependabot-2.0.json")
+        }
+        "package.json" => Some("https://json.schemastore.org/package.json"),
+        "cargo.toml" => Some("https://json.schemastore.org/cargo.json"),
+        "pyproject.toml" => Some("https://json.schemastore.org/pyproject.json"),
+        _ if name.starts_with("tsconfig") && name.ends_with(".json") => Some("https://json.schemastore.org/tsconfig.json"),
+        _ => None,
+    }
+}
+
+/// serde_json's message without its trailing "at line L column C"
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message.to_string(),
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
+
+/// Line of every value in a well-formed JSON text, by JSON pointer
+fn json_positions(text: &str) -> BTreeMap<String, usize> {
+    struct Scanner<'a> {
+        chars: std::iter::Peekable<std::str::Chars<'a>>,
+        line: usize,
+        positions: BTreeMap<String, usize>,
+    }
+
+    impl Scanner<'_> {
+        fn skip_whitespace(&mut self) {
+            while let Some(&c) = self.chars.peek() {
+                if !c.is_whitespace() {
+                    break;
+                }
+                if c == '\n' {
+                    self.line += 1;
+                }
+                self.chars.next();
+            }
+        }
+
+        fn string(&mut self) -> String {
+            let mut text = String::new();
+            self.chars.next();
+            while let Some(c) = self.chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => {
+                        if let Some(escaped) = self.chars.next() {
+                            text.push(escaped);
+                        }
+                    }
+                    c => text.push(c),
+                }
+            }
+            text
+        }
+
+        fn value(&mut self, pointer: &str) {
+            self.skip_whitespace();
+            self.positions.insert(pointer.to_string(), self.line);
+            match self.chars.peek() {
+                Some('{') => {
+                    self.chars.next();
+                    loop {
+                        self.skip_whitespace();
+                        match self.chars.peek() {
+                            Some('"') => {
+                                let line = self.line;
+                                let key = format!("{}/{}", pointer, escape_pointer(&self.string()));
+                                self.skip_whitespace();
+                                self.chars.next(); // ':'
+                                self.value(&key);
+                                // A key's line, not its value's, locates it
+                                self.positions.insert(key, line);
+                            }
+                            Some(',') => {
+                                self.chars.next();
+                            }
+                            _ => {
+                                self.chars.next();
+                                break;
+                            }
+                        }
+                    }
+                }
+                Some('[') => {
+                    self.chars.next();
+                    let mut index = 0;
+                    loop {
+                        self.skip_whitespace();
+                        match self.chars.peek() {
+                            Some(']') | None => {
+                                self.chars.next();
+                                break;
+                            }
+                            Some(',') => {
+                                self.chars.next();
+                            }
+                            _ => {
+                                self.value(&format!("{}/{}", pointer, index));
+                                index += 1;
+                            }
+                        }
+                    }
+                }
+                Some('"') => {
+                    self.string();
+                }
+                _ => {
+                    while self.chars.peek().is_some_and(|c| !matches!(c, ',' | '}' | ']') && !c.is_whitespace()) {
+                        self.chars.next();
+                    }
+                }
+            }
+        }
+    }
+
+    let mut scanner = Scanner {
+        chars: text.chars().peekable(),
+        line: 1,
+        positions: BTreeMap::new(),
+    };
+    scanner.value("");
+    scanner.positions
+}
+
+/// Split a TOML dotted key into its parts
+fn toml_key_parts(key: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in key.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '.') => parts.push(std::mem::take(&mut current).trim().to_string()),
+            (None, c) => current.push(c),
+        }
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+/// Line of every table and key in a TOML text, by JSON pointer
+///
+/// Keys inside inline tables and multi-line arrays are located by the key
+/// that holds them.
+fn toml_positions(text: &str) -> BTreeMap<String, usize> {
+    let mut positions = BTreeMap::new();
+    // Current element of each array of tables, by its pointer
+    let mut arrays: BTreeMap<String, usize> = BTreeMap::new();
+    let mut table = String::new();
+    let mut in_string: Option<&str> = None;
+
+    // Pointer of a dotted header, through the current element of any array of tables
+    let resolve = |parts: &[String], arrays: &BTreeMap<String, usize>| {
+        let mut pointer = String::new();
+        for part in parts {
+            pointer = format!("{}/{}", pointer, escape_pointer(part));
+            if let Some(index) = arrays.get(&pointer) {
+                pointer = format!("{}/{}", pointer, index);
+            }
+        }
+        pointer
+    };
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let trimmed = line.trim();
+        if let Some(delimiter) = in_string {
+            if trimmed.contains(delimiter) {
+                in_string = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("[[") {
+            let parts = toml_key_parts(trimmed.trim_start_matches('[').split(']').next().unwrap_or(""));
+            let (last, parents) = parts.split_last().expect("split yields a part");
+            let array = format!("{}/{}", resolve(parents, &arrays), escape_pointer(last));
+            let index = arrays.get(&array).map_or(0, |index| index + 1);
+            arrays.insert(array.clone(), index);
+            positions.entry(array.clone()).or_insert(number);
+            table = format!("{}/{}", array, index);
+            positions.insert(table.clone(), number);
+        } else if trimmed.starts_with('[') {
+            let parts = toml_key_parts(trimmed.trim_start_matches('[').split(']').next().unwrap_or(""));
+            table = resolve(&parts, &arrays);
+            positions.insert(table.clone(), number);
+        } else if let Some((key, value)) = trimmed.split_once('=').filter(|_| !trimmed.starts_with('#')) {
+            let mut pointer = table.clone();
+            for part in toml_key_parts(key) {
+                pointer = format!("{}/{}", pointer, escape_pointer(&part));
+                positions.entry(pointer.clone()).or_insert(number);
+            }
+            let value = value.trim();
+            for delimiter in ["\"\"\"", "'''"] {
+                if value.starts_with(delimiter) && !value[3..].contains(delimiter) {
+                    in_string = Some(delimiter);
+                }
+            }
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_syntax_errors() {
+        let error = parse_config_file("{\n  \"a\": 1,\n  \"b\" 2\n}\n", ConfigFormat::Json).unwrap_err();
+        assert_eq!((error.line, error.column), (3, Some(7)));
+        assert_eq!(error.to_string(), "line 3, column 7: expected `:`");
+
+        let error = parse_config_file("[package]\nname = \"x\"\nversion = \n", ConfigFormat::Toml).unwrap_err();
+        assert_eq!(error.line, 3);
+
+        let error = parse_config_file("a: 1\n  b: 2\n", ConfigFormat::Yaml).unwrap_err();
+        assert_eq!(error.to_string(), "line 2: mapping values are not allowed in this context");
+    }
+
+    #[test]
+    fn test_positions() {
+        let json = parse_config_file("{\n  \"name\": \"x\",\n  \"scripts\": {\n    \"build\": \"tsc\"\n  },\n  \"files\": [\n    \"dist\"\n  ]\n}\n", ConfigFormat::Json).unwrap();
+        assert_eq!(json[0].line_of("/scripts/build"), 4);
+        assert_eq!(json[0].line_of("/files/0"), 7);
+        assert_eq!(json[0].line_of("/files/0/missing"), 7);
+
+        let toml = parse_config_file(
+            "[package]\nname = \"x\"\n\n[[bin]]\nname = \"a\"\n\n[[bin]]\nname = \"b\"\n\n[dependencies]\nserde = { version = \"1\" }\n",
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(toml[0].value["bin"][1], json!({ "name": "b" }));
+        assert_eq!(toml[0].line_of("/package/name"), 2);
+        assert_eq!(toml[0].line_of("/bin/1/name"), 8);
+        assert_eq!(toml[0].line_of("/dependencies/serde/version"), 11);
+    }
+
+    #[test]
+    fn test_known_schema() {
+        assert_eq!(
+            known_schema(Path::new("/repo/.github/workflows/ci.yml")),
+            Some("https://json.schemastore.org/github-workflow.json")
+        );
+        assert!(known_schema(Path::new("docker-compose.prod.yaml")).unwrap().contains("compose-spec"));
+        assert_eq!(known_schema(Path::new("Cargo.toml")), Some("https://json.schemastore.org/cargo.json"));
+        assert_eq!(known_schema(Path::new("config.yml")), None);
+        assert_eq!(ConfigFormat::from_path(Path::new("a/b.YML")), Some(ConfigFormat::Yaml));
+    }
+}
@@ -0,0 +1,325 @@
+//! Container image builds and runs
+//!
+//! Command lines for Docker and Podman, which accept the same arguments for
+//! building, listing, and running images, and starting-point Dockerfiles for
+//! the detected project language. The engine comes from the `[container]`
+//! config or, by default, is Docker, falling back to Podman when only Podman
+//! is on `PATH`.
+
+use crate::config::{ContainerConfig, ContainerEngine};
+use crate::project::Language;
+use crate::shell::find_on_path;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors in container tool input
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ContainerError {
+    /// A port mapping is not `host:container` with an optional protocol
+    #[error("Invalid port mapping '{0}'; expected HOST:CONTAINER, e.g. 8080:80 or 5353:53/udp")]
+    InvalidPort(String),
+
+    /// An unknown engine name
+    #[error("Unknown container engine '{0}'; expected 'docker' or 'podman'")]
+    UnknownEngine(String),
+}
+
+pub type Result<T> = std::result::Result<T, ContainerError>;
+
+impl ContainerEngine {
+    /// The engine's executable
+    pub fn program(&self) -> &'static str {
+        match self {
+            ContainerEngine::Docker => "docker",
+            ContainerEngine::Podman => "podman",
+        }
+    }
+
+    /// The configured engine, or Docker unless only Podman is installed
+    pub fn resolve(config: &ContainerConfig) -> Self {
+        config.engine.unwrap_or_else(|| {
+            if find_on_path("docker").is_none() && find_on_path("podman").is_some() {
+                ContainerEngine::Podman
+            } else {
+                ContainerEngine::Docker
+            }
+        })
+    }
+}
+
+impl FromStr for ContainerEngine {
+    type Err = ContainerError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "docker" => Ok(ContainerEngine::Docker),
+            "podman" => Ok(ContainerEngine::Podman),
+            _ => Err(ContainerError::UnknownEngine(s.to_string())),
+        }
+    }
+}
+
+/// A published container port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortMapping {
+    pub host: u16,
+    pub container: u16,
+    /// `tcp` or `udp`
+    pub protocol: String,
+}
+
+impl FromStr for PortMapping {
+    type Err = ContainerError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || ContainerError::InvalidPort(s.to_string());
+        let (ports, protocol) = match s.split_once('/') {
+            Some((ports, protocol)) if matches!(protocol, "tcp" | "udp") => (ports, protocol),
+            Some(_) => return Err(invalid()),
+            None => (s, "tcp"),
+        };
+        let (host, container) = match ports.split_once(':') {
+            Some((host, container)) => (host, container),
+            None => (ports, ports),
+        };
+        Ok(PortMapping {
+            host: host.trim().parse().map_err(|_| invalid())?,
+            container: container.trim().parse().map_err(|_| invalid())?,
+            protocol: protocol.to_string(),
+        })
+    }
+}
+
+impl PortMapping {
+    /// The `--publish` value
+    pub fn to_arg(&self) -> String {
+        if self.protocol == "tcp" {
+            format!("{}:{}", self.host, self.container)
+        } else {
+            format!("{}:{}/{}", self.host, self.container, self.protocol)
+        }
+    }
+}
+
+/// Arguments to build `context` with `dockerfile` into an image tagged `tag`
+pub fn build_args(tag: &str, dockerfile: &Path, context: &Path, build_args: &BTreeMap<String, String>) -> Vec<String> {
+    let mut args = vec![
+        "build".to_string(),
+        "--tag".to_string(),
+        tag.to_string(),
+        "--file".to_string(),
+        dockerfile.to_string_lossy().to_string(),
+    ];
+    for (name, value) in build_args {
+        args.push("--build-arg".to_string());
+        args.push(format!("{}={}", name, value));
+    }
+    args.push(context.to_string_lossy().to_string());
+    args
+}
+
+/// What to run with [`run_args`]
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub image: String,
+    pub name: Option<String>,
+    pub ports: Vec<PortMapping>,
+    pub env: BTreeMap<String, String>,
+    /// Run in the background and return the container ID
+    pub detach: bool,
+    /// Remove the container when it exits
+    pub remove: bool,
+    /// Command overriding the image's default
+    pub command: Vec<String>,
+}
+
+/// Arguments to start a container
+pub fn run_args(options: &RunOptions) -> Vec<String> {
+    let mut args = vec!["run".to_string()];
+    if options.detach {
+        args.push("--detach".to_string());
+    }
+    if options.remove {
+        args.push("--rm".to_string());
+    }
+    if let Some(name) = &options.name {
+        args.push("--name".to_string());
+        args.push(name.clone());
+    }
+    for port in &options.ports {
+        args.push("--publish".to_string());
+        args.push(port.to_arg());
+    }
+    for (name, value) in &options.env {
+        args.push("--env".to_string());
+        args.push(format!("{}={}", name, value));
+    }
+    args.push(options.image.clone());
+    args.extend(options.command.iter().cloned());
+    args
+}
+
+/// A starting-point Dockerfile for a project in `language`, if there is one
+///
+/// The images build in a separate stage where the toolchain needs it, run
+/// as a non-root user, and leave the port and entry point for the agent to
+/// adapt to the project.
+pub fn dockerfile_for(language: Language, project_name: &str) -> Option<String> {
+    let binary = project_name.to_lowercase().replace(' ', "-");
+    let dockerfile = match language {
+        Language::Rust => format!(
+            r#"FROM rust:1-slim AS build
+WORKDIR /src
+COPY . .
+RUN cargo build --release --locked
+
+FROM debian:bookworm-slim
+RUN useradd --system --uid 10001 app
+COPY --from=build /src/target/release/{binary} /usr/local/bin/{binary}
+USER app
+EXPOSE 8080
+ENTRYPOINT ["/usr/local/bin/{binary}"]
+"#
+        ),
+        Language::Python => r#"FROM python:3.12-slim
+ENV PYTHONDONTWRITEBYTECODE=1 PYTHONUNBUFFERED=1
+WORKDIR /app
+COPY requirements.txt .
+RUN pip install --no-cache-dir -r requirements.txt
+COPY . .
+RUN useradd --system --uid 10001 app
+USER app
+EXPOSE 8000
+CMD ["python", "main.py"]
+"#
+        .to_string(),
+        Language::JavaScript | Language::TypeScript => {
+            let build = if language == Language::TypeScript { "RUN npm run build\n" } else { "" };
+            format!(
+                r#"FROM node:20-slim AS build
+WORKDIR /app
+COPY package*.json ./
+RUN npm ci
+COPY . .
+{build}RUN npm prune --omit=dev
+
+FROM node:20-slim
+ENV NODE_ENV=production
+WORKDIR /app
+COPY --from=build --chown=node:node /app .
+USER node
+EXPOSE 3000
+CMD ["npm", "start"]
+"#
+            )
+        }
+        Language::Go => format!(
+            r#"FROM golang:1.22 AS build
+WORKDIR /src
+COPY go.mod go.sum* ./
+RUN go mod download
+COPY . .
+RUN CGO_ENABLED=0 go build -o /out/{binary} .
+
+FROM gcr.io/distroless/static-debian12:nonroot
+COPY --from=build /out/{binary} /{binary}
+EXPOSE 8080
+ENTRYPOINT ["/{binary}"]
+"#
+        ),
+        Language::Java => r#"FROM maven:3-eclipse-temurin-21 AS build
+WORKDIR /src
+COPY . .
+RUN mvn --batch-mode package -DskipTests
+
+FROM eclipse-temurin:21-jre
+RUN useradd --system --uid 10001 app
+COPY --from=build /src/target/*.jar /app/app.jar
+USER app
+EXPOSE 8080
+ENTRYPOINT ["java", "-jar", "/app/app.jar"]
+"#
+        .to_string(),
+        Language::CSharp | Language::Cpp | Language::C => return None,
+    };
+    Some(dockerfile)
+}
+
+/// `.dockerignore` entries keeping build output, dependencies, and local
+/// state out of the build context
+pub fn dockerignore_for(language: Language) -> String {
+    let specific: &[&str] = match language {
+        Language::Rust => &["target/"],
+        Language::Python => &["__pycache__/", "*.pyc", ".venv/", "venv/"],
+        Language::JavaScript | Language::TypeScript => &["node_modules/", "dist/", "npm-debug.log"],
+        Language::Go => &["bin/"],
+        Language::Java => &["target/", "build/", ".gradle/"],
+        Language::CSharp => &["bin/", "obj/"],
+        Language::Cpp | Language::C => &["build/"],
+    };
+    [".git/", ".AuroraHeart/", ".env", "Dockerfile", ".dockerignore"]
+        .iter()
+        .chain(specific)
+        .map(|entry| format!("{}\n", entry))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_mappings() {
+        assert_eq!(
+            "8080:80".parse::<PortMapping>().unwrap(),
+            PortMapping {
+                host: 8080,
+                container: 80,
+                protocol: "tcp".to_string()
+            }
+        );
+        assert_eq!("5353:53/udp".parse::<PortMapping>().unwrap().to_arg(), "5353:53/udp");
+        assert_eq!("3000".parse::<PortMapping>().unwrap().to_arg(), "3000:3000");
+        assert!("http:80".parse::<PortMapping>().is_err());
+        assert!("80:80/sctp".parse::<PortMapping>().is_err());
+    }
+
+    #[test]
+    fn test_command_lines() {
+        let build = build_args(
+            "web:dev",
+            Path::new("docker/Dockerfile"),
+            Path::new("."),
+            &BTreeMap::from([("VERSION".to_string(), "1.2".to_string())]),
+        );
+        assert_eq!(
+            build.join(" "),
+            "build --tag web:dev --file docker/Dockerfile --build-arg VERSION=1.2 ."
+        );
+
+        let run = run_args(&RunOptions {
+            image: "web:dev".to_string(),
+            name: Some("web".to_string()),
+            ports: vec!["8080:80".parse().unwrap()],
+            env: BTreeMap::from([("MODE".to_string(), "dev".to_string())]),
+            detach: true,
+            remove: false,
+            command: vec!["serve".to_string()],
+        });
+        assert_eq!(run.join(" "), "run --detach --name web --publish 8080:80 --env MODE=dev web:dev serve");
+        assert_eq!("Podman".parse::<ContainerEngine>().unwrap().program(), "podman");
+    }
+
+    #[test]
+    fn test_dockerfiles() {
+        let rust = dockerfile_for(Language::Rust, "My Service").unwrap();
+        assert!(rust.contains("COPY --from=build /src/target/release/my-service"));
+        let ts = dockerfile_for(Language::TypeScript, "web").unwrap();
+        assert!(ts.contains("RUN npm run build\nRUN npm prune"));
+        assert!(!dockerfile_for(Language::JavaScript, "web").unwrap().contains("npm run build"));
+        assert!(dockerfile_for(Language::Cpp, "app").is_none());
+        assert!(dockerignore_for(Language::Python).contains(".venv/\n"));
+    }
+}
@@ -0,0 +1,746 @@
+//! Structured compiler diagnostics
+//!
+//! Build output from rustc/cargo, tsc, gcc/clang, and the Go toolchain is
+//! parsed into [`Diagnostic`] values (file, position, severity, message, and
+//! error code) so the IDE can list problems and jump to their source.
+//! Machine-applicable suggestions from cargo's JSON messages and ESLint's JSON
+//! formatter become [`QuickFix`]es that [`apply_edits`] applies exactly.
+
+use regex::Regex;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors that can occur when applying a quick fix
+#[derive(Debug, Error)]
+pub enum FixError {
+    /// An edit points outside the file
+    #[error("Edit range is outside {file}: {range}")]
+    InvalidRange {
+        /// File the edit targets
+        file: String,
+        /// The offending range
+        range: String,
+    },
+
+    /// Two edits touch the same text
+    #[error("Overlapping edits in {0}")]
+    OverlappingEdits(String),
+}
+
+/// Result type for quick fix operations
+pub type Result<T> = std::result::Result<T, FixError>;
+
+/// Severity of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Compilation failed
+    Error,
+    /// Compiled, but something is likely wrong
+    Warning,
+    /// Informational note
+    Info,
+    /// Style or improvement suggestion
+    Hint,
+}
+
+impl Severity {
+    /// Parse a severity label as printed by compilers
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().as_str() {
+            "error" | "fatal error" => Some(Severity::Error),
+            "warning" => Some(Severity::Warning),
+            "note" | "info" | "information" => Some(Severity::Info),
+            "help" | "hint" => Some(Severity::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem reported at a source location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// File path as printed by the tool
+    pub file: String,
+    /// Line (1-based)
+    pub line: usize,
+    /// Column (1-based), if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Severity
+    pub severity: Severity,
+    /// Message text
+    pub message: String,
+    /// Error code (e.g. `E0308`, `TS2322`, `-Wunused-variable`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Tool that reported the diagnostic (e.g. `rustc`, `tsc`)
+    pub source: String,
+    /// Machine-applicable fixes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<QuickFix>,
+}
+
+impl Diagnostic {
+    /// Stable identifier derived from the diagnostic's location and content
+    pub fn id(&self) -> String {
+        let input = format!(
+            "{}:{}:{}:{:?}:{}:{}",
+            self.source,
+            self.file.replace('\\', "/"),
+            self.line,
+            self.column,
+            self.code.as_deref().unwrap_or(""),
+            self.message
+        );
+        digest(&SHA256, input.as_bytes())
+            .as_ref()
+            .iter()
+            .take(6)
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// A span of text to replace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextRange {
+    /// Line and column positions (1-based, columns counted in characters, end exclusive)
+    Position {
+        start_line: usize,
+        start_column: usize,
+        end_line: usize,
+        end_column: usize,
+    },
+    /// Character offsets from the start of the file (end exclusive)
+    Offset { start: usize, end: usize },
+}
+
+/// Replacement of a range of text in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    /// File to edit
+    pub file: String,
+    /// Text to replace
+    pub range: TextRange,
+    /// Replacement text
+    pub new_text: String,
+}
+
+/// A machine-applicable fix for a diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickFix {
+    /// Description of the fix
+    pub title: String,
+    /// Edits to apply, possibly across several files
+    pub edits: Vec<TextEdit>,
+}
+
+impl QuickFix {
+    /// Files touched by the fix, in first-edit order
+    pub fn files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = Vec::new();
+        for edit in &self.edits {
+            if !files.contains(&edit.file.as_str()) {
+                files.push(&edit.file);
+            }
+        }
+        files
+    }
+}
+
+/// Byte offset of the given character offset, allowing the end of `text`
+fn char_to_byte(text: &str, chars: usize) -> Option<usize> {
+    text.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .nth(chars)
+}
+
+/// Byte range of `range` within `text`
+fn byte_range(text: &str, range: &TextRange) -> Option<(usize, usize)> {
+    let (start, end) = match range {
+        TextRange::Position {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        } => {
+            let line_starts: Vec<usize> = std::iter::once(0)
+                .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+                .collect();
+            let position = |line: usize, column: usize| {
+                let line_start = *line_starts.get(line.checked_sub(1)?)?;
+                let line_end = text[line_start..]
+                    .find('\n')
+                    .map_or(text.len(), |i| line_start + i);
+                char_to_byte(&text[line_start..line_end], column.checked_sub(1)?)
+                    .map(|offset| line_start + offset)
+            };
+            (
+                position(*start_line, *start_column)?,
+                position(*end_line, *end_column)?,
+            )
+        }
+        TextRange::Offset { start, end } => {
+            (char_to_byte(text, *start)?, char_to_byte(text, *end)?)
+        }
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// Apply edits to the content of one file
+///
+/// Every edit is resolved against the original text, so ranges need not
+/// account for earlier edits. Edits must not overlap.
+pub fn apply_edits(file: &str, text: &str, edits: &[&TextEdit]) -> Result<String> {
+    let mut ranges = Vec::with_capacity(edits.len());
+    for edit in edits {
+        let (start, end) = byte_range(text, &edit.range).ok_or_else(|| FixError::InvalidRange {
+            file: file.to_string(),
+            range: format!("{:?}", edit.range),
+        })?;
+        ranges.push((start, end, edit.new_text.as_str()));
+    }
+
+    ranges.sort_by_key(|(start, end, _)| (*start, *end));
+    if ranges.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        return Err(FixError::OverlappingEdits(file.to_string()));
+    }
+
+    let mut result = text.to_string();
+    for (start, end, new_text) in ranges.into_iter().rev() {
+        result.replace_range(start..end, new_text);
+    }
+    Ok(result)
+}
+
+impl fmt::Display for Diagnostic {
+    /// Formats as `file:line:column: severity[code]: message (source)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        };
+        write!(f, ": {}", severity)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        write!(f, ": {} ({})", self.message, self.source)
+    }
+}
+
+/// Criteria for selecting diagnostics
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticFilter {
+    /// Only include diagnostics at least this severe
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+    /// Only include diagnostics in this file (absolute, or a trailing path such as `src/main.rs`)
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+impl DiagnosticFilter {
+    /// Whether the diagnostic passes the filter
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        let severe_enough = self
+            .min_severity
+            .is_none_or(|min| diagnostic.severity <= min);
+        let in_file = self
+            .file
+            .as_deref()
+            .is_none_or(|file| Path::new(&diagnostic.file).ends_with(file));
+        severe_enough && in_file
+    }
+}
+
+/// Line patterns for the supported tools
+struct Patterns {
+    rustc_header: Regex,
+    rustc_location: Regex,
+    tsc: Regex,
+    gcc: Regex,
+    go: Regex,
+}
+
+impl Patterns {
+    fn new() -> Option<Self> {
+        Some(Self {
+            rustc_header: Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").ok()?,
+            rustc_location: Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").ok()?,
+            tsc: Regex::new(
+                r"^(.+?\.[cm]?[jt]sx?)(?:\((\d+),(\d+)\)|:(\d+):(\d+)) ?[:-] (error|warning) (TS\d+): (.+)$",
+            )
+            .ok()?,
+            gcc: Regex::new(
+                r"^(.+?):(\d+):(?:(\d+):)? (fatal error|error|warning|note): (.+?)(?: \[(-W[\w=-]+)\])?$",
+            )
+            .ok()?,
+            go: Regex::new(r"^(\S+\.go):(\d+)(?::(\d+))?: (.+)$").ok()?,
+        })
+    }
+}
+
+fn number(text: Option<regex::Match<'_>>) -> Option<usize> {
+    text.and_then(|m| m.as_str().parse().ok())
+}
+
+fn json_usize(value: &JsonValue) -> Option<usize> {
+    value.as_u64().and_then(|n| usize::try_from(n).ok())
+}
+
+/// Diagnostics from one JSON document: a cargo message, a rustc diagnostic,
+/// or an ESLint report
+fn parse_json_diagnostics(value: &JsonValue) -> Vec<Diagnostic> {
+    if let Some(files) = value.as_array() {
+        return files.iter().flat_map(parse_eslint_file).collect();
+    }
+
+    let message = match value["reason"].as_str() {
+        Some("compiler-message") => &value["message"],
+        Some(_) => return Vec::new(),
+        None => value,
+    };
+    parse_rustc_json(message).into_iter().collect()
+}
+
+fn parse_rustc_json(message: &JsonValue) -> Option<Diagnostic> {
+    let severity = Severity::from_label(message["level"].as_str()?)?;
+    let span = message["spans"]
+        .as_array()?
+        .iter()
+        .find(|span| span["is_primary"].as_bool() == Some(true))?;
+    let code = message["code"]["code"].as_str().map(str::to_string);
+    let source = match &code {
+        Some(code) if code.starts_with("clippy::") => "clippy",
+        _ => "rustc",
+    };
+
+    // Suggestions are attached to the message itself or to its `help` children
+    let fixes = std::iter::once(message)
+        .chain(message["children"].as_array().into_iter().flatten())
+        .filter_map(rustc_fix)
+        .collect();
+
+    Some(Diagnostic {
+        file: span["file_name"].as_str()?.to_string(),
+        line: json_usize(&span["line_start"])?,
+        column: json_usize(&span["column_start"]),
+        severity,
+        message: message["message"].as_str()?.to_string(),
+        code,
+        source: source.to_string(),
+        fixes,
+    })
+}
+
+/// The machine-applicable suggestion spans of a rustc message as one fix
+fn rustc_fix(message: &JsonValue) -> Option<QuickFix> {
+    let edits: Vec<TextEdit> = message["spans"]
+        .as_array()?
+        .iter()
+        .filter(|span| span["suggestion_applicability"].as_str() == Some("MachineApplicable"))
+        .filter_map(|span| {
+            Some(TextEdit {
+                file: span["file_name"].as_str()?.to_string(),
+                range: TextRange::Position {
+                    start_line: json_usize(&span["line_start"])?,
+                    start_column: json_usize(&span["column_start"])?,
+                    end_line: json_usize(&span["line_end"])?,
+                    end_column: json_usize(&span["column_end"])?,
+                },
+                new_text: span["suggested_replacement"].as_str()?.to_string(),
+            })
+        })
+        .collect();
+
+    (!edits.is_empty()).then(|| QuickFix {
+        title: message["message"]
+            .as_str()
+            .unwrap_or("Apply suggestion")
+            .to_string(),
+        edits,
+    })
+}
+
+fn parse_eslint_file(file: &JsonValue) -> Vec<Diagnostic> {
+    let Some(path) = file["filePath"].as_str() else {
+        return Vec::new();
+    };
+
+    file["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|message| {
+            let code = message["ruleId"].as_str().map(str::to_string);
+            let title = format!("Fix {}", code.as_deref().unwrap_or("problem"));
+            let mut fixes: Vec<QuickFix> = eslint_fix(path, &message["fix"], title)
+                .into_iter()
+                .collect();
+            fixes.extend(
+                message["suggestions"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|suggestion| {
+                        let title = suggestion["desc"].as_str().unwrap_or("Apply suggestion");
+                        eslint_fix(path, &suggestion["fix"], title.to_string())
+                    }),
+            );
+
+            Some(Diagnostic {
+                file: path.to_string(),
+                line: json_usize(&message["line"]).unwrap_or(1),
+                column: json_usize(&message["column"]),
+                severity: if message["severity"].as_u64() == Some(2) {
+                    Severity::Error
+                } else {
+                    Severity::Warning
+                },
+                message: message["message"].as_str()?.to_string(),
+                code,
+                source: "eslint".to_string(),
+                fixes,
+            })
+        })
+        .collect()
+}
+
+/// An ESLint `{range: [start, end], text}` fix
+fn eslint_fix(path: &str, fix: &JsonValue, title: String) -> Option<QuickFix> {
+    let range = fix["range"].as_array()?;
+    Some(QuickFix {
+        title,
+        edits: vec![TextEdit {
+            file: path.to_string(),
+            range: TextRange::Offset {
+                start: json_usize(range.first()?)?,
+                end: json_usize(range.get(1)?)?,
+            },
+            new_text: fix["text"].as_str()?.to_string(),
+        }],
+    })
+}
+
+/// Parse diagnostics from build output
+///
+/// Output from several tools may be mixed; each line is matched against all
+/// supported formats, including cargo's `--message-format=json` messages and
+/// ESLint's `--format json` report. Duplicate diagnostics are reported once.
+pub fn parse_build_output(output: &str) -> Vec<Diagnostic> {
+    let Some(patterns) = Patterns::new() else {
+        return Vec::new();
+    };
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    // rustc prints the message on one line and the location on the next
+    let mut pending_rustc: Option<(Severity, Option<String>, String)> = None;
+
+    for line in output.lines() {
+        let line = line.trim_end();
+
+        let json = line.trim_start();
+        if json.starts_with('{') || json.starts_with('[') {
+            if let Ok(value) = serde_json::from_str::<JsonValue>(json) {
+                for diagnostic in parse_json_diagnostics(&value) {
+                    if !diagnostics.contains(&diagnostic) {
+                        diagnostics.push(diagnostic);
+                    }
+                }
+                continue;
+            }
+        }
+
+        if let Some(caps) = patterns.rustc_header.captures(line) {
+            pending_rustc = Severity::from_label(&caps[1]).map(|severity| {
+                (
+                    severity,
+                    caps.get(2).map(|m| m.as_str().to_string()),
+                    caps[3].to_string(),
+                )
+            });
+            continue;
+        }
+
+        if let Some(caps) = patterns.rustc_location.captures(line) {
+            if let Some((severity, code, message)) = pending_rustc.take() {
+                diagnostics.push(Diagnostic {
+                    file: caps[1].to_string(),
+                    line: number(caps.get(2)).unwrap_or(1),
+                    column: number(caps.get(3)),
+                    severity,
+                    message,
+                    code,
+                    source: "rustc".to_string(),
+                    fixes: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        let diagnostic = if let Some(caps) = patterns.tsc.captures(line) {
+            Some(Diagnostic {
+                file: caps[1].to_string(),
+                line: number(caps.get(2).or(caps.get(4))).unwrap_or(1),
+                column: number(caps.get(3).or(caps.get(5))),
+                severity: Severity::from_label(&caps[6]).unwrap_or(Severity::Error),
+                message: caps[8].to_string(),
+                code: Some(caps[7].to_string()),
+                source: "tsc".to_string(),
+                fixes: Vec::new(),
+            })
+        } else if let Some(caps) = patterns.gcc.captures(line) {
+            Some(Diagnostic {
+                file: caps[1].to_string(),
+                line: number(caps.get(2)).unwrap_or(1),
+                column: number(caps.get(3)),
+                severity: Severity::from_label(&caps[4]).unwrap_or(Severity::Error),
+                message: caps[5].to_string(),
+                code: caps.get(6).map(|m| m.as_str().to_string()),
+                source: "gcc".to_string(),
+                fixes: Vec::new(),
+            })
+        } else {
+            patterns.go.captures(line).map(|caps| Diagnostic {
+                file: caps[1].to_string(),
+                line: number(caps.get(2)).unwrap_or(1),
+                column: number(caps.get(3)),
+                severity: Severity::Error,
+                message: caps[4].to_string(),
+                code: None,
+                source: "go".to_string(),
+                fixes: Vec::new(),
+            })
+        };
+
+        if let Some(diagnostic) = diagnostic {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rustc_output() {
+        let output = "\
+   Compiling demo v0.1.0 (/tmp/demo)
+warning: unused variable: `x`
+ --> src/lib.rs:2:9
+  |
+2 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+
+error[E0308]: mismatched types
+  --> src/main.rs:4:18
+   |
+4  |     let n: u32 = \"four\";
+   |            ---   ^^^^^^ expected `u32`, found `&str`
+
+error: could not compile `demo` (bin \"demo\") due to 1 previous error
+";
+        let diagnostics = parse_build_output(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].file, "src/lib.rs");
+        assert_eq!(diagnostics[0].message, "unused variable: `x`");
+        assert_eq!(
+            diagnostics[1],
+            Diagnostic {
+                file: "src/main.rs".to_string(),
+                line: 4,
+                column: Some(18),
+                severity: Severity::Error,
+                message: "mismatched types".to_string(),
+                code: Some("E0308".to_string()),
+                source: "rustc".to_string(),
+                fixes: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_tsc_output() {
+        let output = "\
+src/app.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
+src/view.tsx:3:10 - error TS2304: Cannot find name 'React'.
+";
+        let diagnostics = parse_build_output(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].file, "src/app.ts");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (12, Some(5)));
+        assert_eq!(diagnostics[0].code.as_deref(), Some("TS2322"));
+        assert_eq!(diagnostics[1].file, "src/view.tsx");
+        assert_eq!(diagnostics[1].message, "Cannot find name 'React'.");
+        assert!(diagnostics.iter().all(|d| d.source == "tsc"));
+    }
+
+    #[test]
+    fn test_parse_gcc_and_go_output() {
+        let output = "\
+main.c: In function 'main':
+main.c:5:9: warning: unused variable 'y' [-Wunused-variable]
+main.c:6:5: error: 'z' undeclared (first use in this function)
+# example.com/demo
+./main.go:10:2: undefined: foo
+";
+        let diagnostics = parse_build_output(output);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].message, "unused variable 'y'");
+        assert_eq!(diagnostics[0].code.as_deref(), Some("-Wunused-variable"));
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].code, None);
+        assert_eq!(diagnostics[2].file, "./main.go");
+        assert_eq!(diagnostics[2].source, "go");
+        assert_eq!(diagnostics[2].message, "undefined: foo");
+    }
+
+    #[test]
+    fn test_parse_json_fixes_and_apply() {
+        let clippy = serde_json::json!({
+            "reason": "compiler-message",
+            "message": {
+                "message": "redundant clone",
+                "level": "warning",
+                "code": {"code": "clippy::redundant_clone"},
+                "spans": [{
+                    "file_name": "src/lib.rs", "is_primary": true,
+                    "line_start": 2, "line_end": 2, "column_start": 14, "column_end": 22,
+                    "suggested_replacement": null, "suggestion_applicability": null
+                }],
+                "children": [{
+                    "message": "remove this",
+                    "level": "help",
+                    "spans": [{
+                        "file_name": "src/lib.rs", "is_primary": true,
+                        "line_start": 2, "line_end": 2, "column_start": 14, "column_end": 22,
+                        "suggested_replacement": "", "suggestion_applicability": "MachineApplicable"
+                    }],
+                    "children": []
+                }]
+            }
+        });
+        let eslint = serde_json::json!([{
+            "filePath": "/repo/src/app.js",
+            "messages": [{
+                "ruleId": "prefer-const", "severity": 2, "message": "'x' is never reassigned.",
+                "line": 1, "column": 1, "fix": {"range": [0, 3], "text": "const"}
+            }]
+        }]);
+        let output = format!(
+            "{}\n{{\"reason\":\"build-finished\",\"success\":false}}\n{}\n",
+            clippy, eslint
+        );
+
+        let diagnostics = parse_build_output(&output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].source, "clippy");
+        assert_eq!(
+            diagnostics[0].code.as_deref(),
+            Some("clippy::redundant_clone")
+        );
+        assert_eq!(diagnostics[0].fixes[0].title, "remove this");
+        assert_eq!(diagnostics[1].source, "eslint");
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].fixes[0].files(), vec!["/repo/src/app.js"]);
+        assert_ne!(diagnostics[0].id(), diagnostics[1].id());
+        assert_eq!(diagnostics[0].id(), diagnostics[0].clone().id());
+
+        let fix = &diagnostics[0].fixes[0];
+        let edits: Vec<&TextEdit> = fix.edits.iter().collect();
+        let text = "fn f(s: &String) -> String {\n    let t = s.clone();\n    t\n}\n";
+        let fixed = apply_edits("src/lib.rs", text, &edits).unwrap();
+        assert_eq!(
+            fixed,
+            "fn f(s: &String) -> String {\n    let t = s;\n    t\n}\n"
+        );
+
+        let fix = &diagnostics[1].fixes[0];
+        let edits: Vec<&TextEdit> = fix.edits.iter().collect();
+        assert_eq!(
+            apply_edits("app.js", "let x = 1;", &edits).unwrap(),
+            "const x = 1;"
+        );
+    }
+
+    #[test]
+    fn test_apply_edits_rejects_bad_ranges() {
+        let edit = |start, end| TextEdit {
+            file: "a.txt".to_string(),
+            range: TextRange::Offset { start, end },
+            new_text: "é".to_string(),
+        };
+        let (first, second) = (edit(0, 2), edit(4, 5));
+        assert_eq!(
+            apply_edits("a.txt", "héllo", &[&second, &first]).unwrap(),
+            "éllé"
+        );
+
+        let overlapping = edit(1, 3);
+        assert!(matches!(
+            apply_edits("a.txt", "héllo", &[&first, &overlapping]),
+            Err(FixError::OverlappingEdits(_))
+        ));
+        let outside = edit(3, 9);
+        assert!(matches!(
+            apply_edits("a.txt", "héllo", &[&outside]),
+            Err(FixError::InvalidRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_filter_and_display() {
+        let diagnostic = |file: &str, severity| Diagnostic {
+            file: file.to_string(),
+            line: 3,
+            column: Some(7),
+            severity,
+            message: "unused import".to_string(),
+            code: Some("W0611".to_string()),
+            source: "pylint".to_string(),
+            fixes: Vec::new(),
+        };
+        let warning = diagnostic("/repo/src/app.py", Severity::Warning);
+        let hint = diagnostic("/repo/src/util.py", Severity::Hint);
+
+        let filter = DiagnosticFilter {
+            min_severity: Some(Severity::Warning),
+            file: None,
+        };
+        assert!(filter.matches(&warning));
+        assert!(!filter.matches(&hint));
+
+        let filter = DiagnosticFilter {
+            min_severity: None,
+            file: Some("src/util.py".to_string()),
+        };
+        assert!(!filter.matches(&warning));
+        assert!(filter.matches(&hint));
+        assert!(DiagnosticFilter::default().matches(&warning));
+
+        assert_eq!(
+            warning.to_string(),
+            "/repo/src/app.py:3:7: warning[W0611]: unused import (pylint)"
+        );
+    }
+}
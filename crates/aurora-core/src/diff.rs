@@ -0,0 +1,159 @@
+//! Line diffs for previewing file changes
+//!
+//! Generated files are shown to the user as a unified diff against what is on
+//! disk before they are saved. The diff is computed from the longest common
+//! subsequence of lines, which is quadratic in the size of the changed region
+//! and meant for configuration and documentation files, not large sources.
+
+/// Lines of unchanged context shown around each change
+pub const DIFF_CONTEXT_LINES: usize = 3;
+
+/// One line of a diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Render a unified diff turning `old` into `new`
+///
+/// Returns an empty string when the texts have the same lines.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+    if edits.iter().all(|edit| matches!(edit, Edit::Same(_))) {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunks(&edits) {
+        // Line numbers where the hunk starts in each file
+        let (mut old_line, mut new_line) = (1, 1);
+        for edit in &edits[..start] {
+            match edit {
+                Edit::Same(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                Edit::Removed(_) => old_line += 1,
+                Edit::Added(_) => new_line += 1,
+            }
+        }
+        let hunk = &edits[start..end];
+        let old_count = hunk.iter().filter(|e| !matches!(e, Edit::Added(_))).count();
+        let new_count = hunk.iter().filter(|e| !matches!(e, Edit::Removed(_))).count();
+        // An empty range is numbered after the line it follows
+        let old_start = if old_count == 0 { old_line - 1 } else { old_line };
+        let new_start = if new_count == 0 { new_line - 1 } else { new_line };
+
+        output.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+        for edit in hunk {
+            let (prefix, line) = match edit {
+                Edit::Same(line) => (' ', line),
+                Edit::Removed(line) => ('-', line),
+                Edit::Added(line) => ('+', line),
+            };
+            output.push(prefix);
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Edit script from the longest common subsequence of the two line lists
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    // Trim the common prefix and suffix so the table covers only the change
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // lengths[i][j] is the LCS length of old_mid[i..] and new_mid[j..]
+    let mut lengths = vec![vec![0usize; new_mid.len() + 1]; old_mid.len() + 1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lengths[i][j] = if old_mid[i] == new_mid[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits: Vec<Edit> = old[..prefix].iter().map(|line| Edit::Same(line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+            edits.push(Edit::Same(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_mid.len() && (j == new_mid.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            // Removals come before additions, as in `diff -u`
+            edits.push(Edit::Removed(old_mid[i]));
+            i += 1;
+        } else {
+            edits.push(Edit::Added(new_mid[j]));
+            j += 1;
+        }
+    }
+    edits.extend(old[old.len() - suffix..].iter().map(|line| Edit::Same(line)));
+    edits
+}
+
+/// Ranges of the edit script to print, each change padded with context and
+/// overlapping ranges merged
+fn hunks(edits: &[Edit]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (index, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Same(_)) {
+            continue;
+        }
+        let start = index.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (index + 1 + DIFF_CONTEXT_LINES).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_texts_have_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb", "old", "new"), "");
+    }
+
+    #[test]
+    fn test_new_file_diff() {
+        assert_eq!(
+            unified_diff("", "one\ntwo\n", "/dev/null", "AURORA.md"),
+            "--- /dev/null\n+++ AURORA.md\n@@ -0,0 +1,2 @@\n+one\n+two\n"
+        );
+    }
+
+    #[test]
+    fn test_hunks_with_context() {
+        let old: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        let new = old.replace("line 3\n", "line three\n").replace("line 18\n", "");
+
+        let diff = unified_diff(&old, &new, "a", "b");
+        assert_eq!(
+            diff,
+            "--- a\n+++ b\n\
+             @@ -1,6 +1,6 @@\n line 1\n line 2\n-line 3\n+line three\n line 4\n line 5\n line 6\n\
+             @@ -15,6 +15,5 @@\n line 15\n line 16\n line 17\n-line 18\n line 19\n line 20\n"
+        );
+    }
+}
@@ -0,0 +1,1398 @@
+//! Text extraction from PDF and DOCX documents
+//!
+//! This module turns documents dropped into the chat (specs, design docs)
+//! into plain text with page markers so they can be used as context. PDF
+//! support covers the common cases: uncompressed and Flate-compressed content
+//! streams, object streams, inherited page resources, and fonts with
+//! `ToUnicode` maps. DOCX pages are split at explicit and last-rendered page
+//! breaks, since Word does not store page layout.
+
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::bytes::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+use thiserror::Error;
+
+/// Upper bound on decompressed stream or archive entry size
+const MAX_DECODED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Maximum nesting followed when walking page trees and references
+const MAX_DEPTH: usize = 32;
+
+/// Errors that can occur while extracting document text
+#[derive(Error, Debug)]
+pub enum DocumentError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The file is not a supported document type
+    #[error("Unsupported document format: {0}")]
+    Unsupported(String),
+
+    /// The document structure could not be read
+    #[error("Malformed document: {0}")]
+    Malformed(String),
+
+    /// The document is encrypted
+    #[error("Document is encrypted")]
+    Encrypted,
+
+    /// A page range could not be parsed or is out of bounds
+    #[error("Invalid page range '{0}'")]
+    InvalidPageRange(String),
+}
+
+pub type Result<T> = std::result::Result<T, DocumentError>;
+
+/// Supported document formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    /// Portable Document Format
+    Pdf,
+    /// Word Open XML document
+    Docx,
+}
+
+impl DocumentKind {
+    /// Detect the format from a file extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "pdf" => Some(DocumentKind::Pdf),
+            "docx" => Some(DocumentKind::Docx),
+            _ => None,
+        }
+    }
+}
+
+/// Extracted document text, one entry per page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    /// Document format
+    pub kind: DocumentKind,
+    /// Text of each page
+    pub pages: Vec<String>,
+}
+
+impl Document {
+    /// Number of pages
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Full text with a marker before each page
+    pub fn text(&self) -> String {
+        let all: Vec<usize> = (1..=self.pages.len()).collect();
+        self.pages_text(&all)
+    }
+
+    /// Text of the given 1-based pages with page markers
+    ///
+    /// Pages outside the document are skipped.
+    pub fn pages_text(&self, pages: &[usize]) -> String {
+        pages
+            .iter()
+            .filter_map(|&page| {
+                let text = self.pages.get(page.checked_sub(1)?)?;
+                Some(format!("--- Page {} ---\n{}", page, text.trim()))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Extract the text of a PDF or DOCX file
+pub fn extract_document<P: AsRef<Path>>(path: P) -> Result<Document> {
+    let path = path.as_ref();
+    let data = std::fs::read(path)?;
+
+    let kind = if data.starts_with(b"%PDF-") {
+        DocumentKind::Pdf
+    } else {
+        DocumentKind::from_path(path)
+            .ok_or_else(|| DocumentError::Unsupported(path.display().to_string()))?
+    };
+
+    match kind {
+        DocumentKind::Pdf => extract_pdf(&data),
+        DocumentKind::Docx => extract_docx(&data),
+    }
+}
+
+/// Parse a page selection like `"1-3,5"` into sorted, unique 1-based pages
+pub fn parse_page_range(spec: &str, page_count: usize) -> Result<Vec<usize>> {
+    let invalid = || DocumentError::InvalidPageRange(spec.to_string());
+    let mut pages = Vec::new();
+
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => {
+                let start = start.trim().parse::<usize>().map_err(|_| invalid())?;
+                let end = match end.trim() {
+                    "" => page_count,
+                    end => end.parse::<usize>().map_err(|_| invalid())?,
+                };
+                (start, end)
+            }
+            None => {
+                let page = part.parse::<usize>().map_err(|_| invalid())?;
+                (page, page)
+            }
+        };
+
+        if start == 0 || start > end || end > page_count {
+            return Err(invalid());
+        }
+        pages.extend(start..=end);
+    }
+
+    if pages.is_empty() {
+        return Err(invalid());
+    }
+    pages.sort_unstable();
+    pages.dedup();
+    Ok(pages)
+}
+
+// ============================================================================
+// DOCX
+// ============================================================================
+
+/// Extract text from a DOCX archive
+pub fn extract_docx(data: &[u8]) -> Result<Document> {
+    let xml = zip_entry(data, "word/document.xml")?;
+    let xml = String::from_utf8_lossy(&xml);
+
+    let mut reader = Reader::from_str(&xml);
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut in_text = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| DocumentError::Malformed(format!("document.xml: {}", e)))?;
+        match event {
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => page.push('\n'),
+                b"tc" => page.push('\t'),
+                _ => {}
+            },
+            Event::Empty(e) | Event::Start(e) => match e.local_name().as_ref() {
+                b"tab" => page.push('\t'),
+                b"cr" => page.push('\n'),
+                b"br" => {
+                    let is_page_break = e.attributes().flatten().any(|a| {
+                        a.key.local_name().as_ref() == b"type" && a.value.as_ref() == b"page"
+                    });
+                    if is_page_break {
+                        pages.push(std::mem::take(&mut page));
+                    } else {
+                        page.push('\n');
+                    }
+                }
+                b"lastRenderedPageBreak" if !page.trim().is_empty() => {
+                    pages.push(std::mem::take(&mut page));
+                }
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| DocumentError::Malformed(format!("document.xml: {}", e)))?;
+                page.push_str(&text);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !page.trim().is_empty() || pages.is_empty() {
+        pages.push(page);
+    }
+
+    Ok(Document {
+        kind: DocumentKind::Docx,
+        pages: pages.iter().map(|p| tidy(p)).collect(),
+    })
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<usize> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .ok_or_else(|| DocumentError::Malformed("truncated zip archive".to_string()))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<usize> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(|| DocumentError::Malformed("truncated zip archive".to_string()))
+}
+
+/// Read one entry from a zip archive
+fn zip_entry(data: &[u8], name: &str) -> Result<Vec<u8>> {
+    const EOCD: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const CENTRAL: usize = 0x0201_4b50;
+    const LOCAL: usize = 0x0403_4b50;
+
+    if !data.starts_with(b"PK") {
+        return Err(DocumentError::Unsupported(
+            "not a zip-based document".to_string(),
+        ));
+    }
+
+    let search_start = data.len().saturating_sub(65_557);
+    let eocd = data[search_start..]
+        .windows(4)
+        .rposition(|w| w == EOCD)
+        .map(|i| search_start + i)
+        .ok_or_else(|| {
+            DocumentError::Malformed("zip end of central directory not found".to_string())
+        })?;
+
+    let entries = u16_at(data, eocd + 10)?;
+    let mut pos = u32_at(data, eocd + 16)?;
+
+    for _ in 0..entries {
+        if u32_at(data, pos)? != CENTRAL {
+            return Err(DocumentError::Malformed(
+                "bad zip central directory".to_string(),
+            ));
+        }
+        let method = u16_at(data, pos + 10)?;
+        let compressed_size = u32_at(data, pos + 20)?;
+        let name_len = u16_at(data, pos + 28)?;
+        let extra_len = u16_at(data, pos + 30)?;
+        let comment_len = u16_at(data, pos + 32)?;
+        let local_offset = u32_at(data, pos + 42)?;
+        let entry_name = data
+            .get(pos + 46..pos + 46 + name_len)
+            .ok_or_else(|| DocumentError::Malformed("truncated zip archive".to_string()))?;
+
+        if entry_name == name.as_bytes() {
+            if u32_at(data, local_offset)? != LOCAL {
+                return Err(DocumentError::Malformed("bad zip local header".to_string()));
+            }
+            let start = local_offset
+                + 30
+                + u16_at(data, local_offset + 26)?
+                + u16_at(data, local_offset + 28)?;
+            let raw = data
+                .get(start..start + compressed_size)
+                .ok_or_else(|| DocumentError::Malformed("truncated zip entry".to_string()))?;
+
+            return match method {
+                0 => Ok(raw.to_vec()),
+                8 => read_limited(DeflateDecoder::new(raw)),
+                other => Err(DocumentError::Unsupported(format!(
+                    "zip compression method {}",
+                    other
+                ))),
+            };
+        }
+
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+
+    Err(DocumentError::Malformed(format!(
+        "{} not found in archive",
+        name
+    )))
+}
+
+fn read_limited<R: Read>(reader: R) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    reader.take(MAX_DECODED_BYTES).read_to_end(&mut output)?;
+    Ok(output)
+}
+
+// ============================================================================
+// PDF
+// ============================================================================
+
+/// A PDF object value
+#[derive(Debug, Clone, PartialEq)]
+enum PdfValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Name(String),
+    Str(Vec<u8>),
+    Array(Vec<PdfValue>),
+    Dict(HashMap<String, PdfValue>),
+    Ref(u32),
+    Operator(String),
+}
+
+impl PdfValue {
+    fn as_dict(&self) -> Option<&HashMap<String, PdfValue>> {
+        match self {
+            PdfValue::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            PdfValue::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&PdfValue> {
+        self.as_dict()?.get(key)
+    }
+}
+
+/// Tokenizer and parser for PDF object syntax and content streams
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | 0x0c | 0)
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while let Some(b) = self.peek() {
+                    if b == b'\n' || b == b'\r' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read_regular(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) || is_delimiter(b) {
+                break;
+            }
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    /// Parse the next value; `None` at end of input or at a closing delimiter
+    fn parse(&mut self) -> Option<PdfValue> {
+        self.skip_whitespace();
+        let b = self.peek()?;
+
+        match b {
+            b'/' => {
+                self.pos += 1;
+                Some(PdfValue::Name(decode_name(self.read_regular())))
+            }
+            b'(' => Some(PdfValue::Str(self.literal_string())),
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                let mut dict = HashMap::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b'>') => {
+                            self.pos += 2;
+                            break;
+                        }
+                        None => break,
+                        _ => {}
+                    }
+                    let key = match self.parse() {
+                        Some(PdfValue::Name(key)) => key,
+                        Some(_) => continue,
+                        None => {
+                            self.pos += 1;
+                            continue;
+                        }
+                    };
+                    if let Some(value) = self.parse() {
+                        dict.insert(key, value);
+                    }
+                }
+                Some(PdfValue::Dict(dict))
+            }
+            b'<' => Some(PdfValue::Str(self.hex_string())),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b']') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        None => break,
+                        _ => {}
+                    }
+                    match self.parse() {
+                        Some(value) => items.push(value),
+                        None => self.pos += 1,
+                    }
+                }
+                Some(PdfValue::Array(items))
+            }
+            b']' | b'>' | b')' => None,
+            b'{' | b'}' => {
+                self.pos += 1;
+                Some(PdfValue::Operator((b as char).to_string()))
+            }
+            _ => {
+                let token = self.read_regular();
+                if token.is_empty() {
+                    self.pos += 1;
+                    return Some(PdfValue::Null);
+                }
+                let text = String::from_utf8_lossy(token);
+                if let Ok(number) = text.parse::<f64>() {
+                    if token.iter().all(u8::is_ascii_digit) {
+                        if let Some(reference) = self.try_reference(&text) {
+                            return Some(reference);
+                        }
+                    }
+                    return Some(PdfValue::Number(number));
+                }
+                Some(match text.as_ref() {
+                    "true" => PdfValue::Bool(true),
+                    "false" => PdfValue::Bool(false),
+                    "null" => PdfValue::Null,
+                    _ => PdfValue::Operator(text.into_owned()),
+                })
+            }
+        }
+    }
+
+    /// Recognize `<num> <gen> R` after an integer
+    fn try_reference(&mut self, number: &str) -> Option<PdfValue> {
+        let saved = self.pos;
+        self.skip_whitespace();
+        let generation = self.read_regular();
+        if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) {
+            self.skip_whitespace();
+            if self.peek() == Some(b'R')
+                && self
+                    .data
+                    .get(self.pos + 1)
+                    .is_none_or(|b| is_whitespace(*b) || is_delimiter(*b))
+            {
+                self.pos += 1;
+                return number.parse().ok().map(PdfValue::Ref);
+            }
+        }
+        self.pos = saved;
+        None
+    }
+
+    fn literal_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut output = Vec::new();
+        let mut nesting = 0;
+
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    nesting += 1;
+                    output.push(b);
+                }
+                b')' if nesting == 0 => break,
+                b')' => {
+                    nesting -= 1;
+                    output.push(b);
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => output.push(b'\n'),
+                        b'r' => output.push(b'\r'),
+                        b't' => output.push(b'\t'),
+                        b'b' => output.push(0x08),
+                        b'f' => output.push(0x0c),
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            output.push((value & 0xff) as u8);
+                        }
+                        other => output.push(other),
+                    }
+                }
+                other => output.push(other),
+            }
+        }
+        output
+    }
+
+    fn hex_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut digits = Vec::new();
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            if b == b'>' {
+                break;
+            }
+            if let Some(d) = (b as char).to_digit(16) {
+                digits.push(d as u8);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect()
+    }
+
+    /// Skip inline image data following an `ID` operator
+    fn skip_inline_image(&mut self) {
+        let rest = &self.data[self.pos..];
+        let end = rest
+            .windows(3)
+            .position(|w| is_whitespace(w[0]) && w[1] == b'E' && w[2] == b'I')
+            .map(|i| i + 3)
+            .unwrap_or(rest.len());
+        self.pos += end;
+    }
+}
+
+fn decode_name(raw: &[u8]) -> String {
+    let mut output = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'#' && i + 2 < raw.len() {
+            if let Some(byte) = std::str::from_utf8(&raw[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                output.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        output.push(raw[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// An indirect object
+struct PdfObject {
+    value: PdfValue,
+    stream: Option<Vec<u8>>,
+}
+
+/// Decoded font information needed to turn string bytes into text
+struct Font {
+    to_unicode: HashMap<u32, String>,
+    code_bytes: usize,
+    /// Composite fonts without a ToUnicode map cannot be decoded
+    undecodable: bool,
+}
+
+impl Font {
+    fn decode(&self, bytes: &[u8]) -> String {
+        if self.undecodable {
+            return String::new();
+        }
+        bytes
+            .chunks(self.code_bytes)
+            .map(|chunk| {
+                let code = chunk.iter().fold(0u32, |acc, b| acc << 8 | *b as u32);
+                match self.to_unicode.get(&code) {
+                    Some(text) => text.clone(),
+                    None if self.code_bytes == 1 => latin1(chunk),
+                    None => String::new(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+struct PdfFile {
+    objects: HashMap<u32, PdfObject>,
+}
+
+/// Extract text from PDF bytes
+pub fn extract_pdf(data: &[u8]) -> Result<Document> {
+    if !data.starts_with(b"%PDF-") {
+        return Err(DocumentError::Unsupported(
+            "missing %PDF header".to_string(),
+        ));
+    }
+
+    let file = PdfFile::parse(data)?;
+    if file.is_encrypted(data) {
+        return Err(DocumentError::Encrypted);
+    }
+
+    let pages = file.pages();
+    if pages.is_empty() {
+        return Err(DocumentError::Malformed("no pages found".to_string()));
+    }
+
+    Ok(Document {
+        kind: DocumentKind::Pdf,
+        pages: pages
+            .iter()
+            .map(|(page, resources)| tidy(&file.page_text(page, *resources)))
+            .collect(),
+    })
+}
+
+impl PdfFile {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let header = Regex::new(r"(\d+)\s+\d+\s+obj\b")
+            .map_err(|e| DocumentError::Malformed(e.to_string()))?;
+
+        let mut objects = HashMap::new();
+        let mut skip_until = 0;
+        for captures in header.captures_iter(data) {
+            let (Some(whole), Some(number)) = (captures.get(0), captures.get(1)) else {
+                continue;
+            };
+            if whole.start() < skip_until {
+                continue;
+            }
+            let Some(number) = std::str::from_utf8(number.as_bytes())
+                .ok()
+                .and_then(|n| n.parse::<u32>().ok())
+            else {
+                continue;
+            };
+
+            let mut lexer = Lexer::new(data, whole.end());
+            let Some(value) = lexer.parse() else {
+                continue;
+            };
+            lexer.skip_whitespace();
+
+            let mut stream = None;
+            if data[lexer.pos..].starts_with(b"stream") {
+                let mut start = lexer.pos + b"stream".len();
+                if data.get(start) == Some(&b'\r') {
+                    start += 1;
+                }
+                if data.get(start) == Some(&b'\n') {
+                    start += 1;
+                }
+                let end = stream_end(data, start, value.get("Length"));
+                stream = Some(data[start..end].to_vec());
+                skip_until = end;
+            }
+
+            // Later definitions (incremental updates) replace earlier ones
+            objects.insert(number, PdfObject { value, stream });
+        }
+
+        let mut file = Self { objects };
+        file.expand_object_streams();
+        Ok(file)
+    }
+
+    /// Unpack objects stored inside compressed object streams
+    fn expand_object_streams(&mut self) {
+        let mut contained = Vec::new();
+        for object in self.objects.values() {
+            if object.value.get("Type").and_then(PdfValue::as_name) != Some("ObjStm") {
+                continue;
+            }
+            let Some(data) = self.decode_stream(object) else {
+                continue;
+            };
+            let count = number(object.value.get("N")).unwrap_or(0.0) as usize;
+            let first = number(object.value.get("First")).unwrap_or(0.0) as usize;
+
+            let mut header = Lexer::new(&data, 0);
+            let mut offsets = Vec::new();
+            for _ in 0..count {
+                match (header.parse(), header.parse()) {
+                    (Some(PdfValue::Number(n)), Some(PdfValue::Number(offset))) => {
+                        offsets.push((n as u32, offset as usize))
+                    }
+                    _ => break,
+                }
+            }
+
+            for (n, offset) in offsets {
+                if first + offset < data.len() {
+                    if let Some(value) = Lexer::new(&data, first + offset).parse() {
+                        contained.push((n, value));
+                    }
+                }
+            }
+        }
+
+        for (n, value) in contained {
+            self.objects.entry(n).or_insert(PdfObject {
+                value,
+                stream: None,
+            });
+        }
+    }
+
+    fn is_encrypted(&self, data: &[u8]) -> bool {
+        let trailer_has_encrypt = data
+            .windows(b"trailer".len())
+            .rposition(|w| w == b"trailer")
+            .and_then(|pos| {
+                Lexer::new(data, pos + b"trailer".len())
+                    .parse()
+                    .map(|t| t.get("Encrypt").is_some())
+            })
+            .unwrap_or(false);
+        trailer_has_encrypt
+            || self.objects.values().any(|o| {
+                o.value.get("Type").and_then(PdfValue::as_name) == Some("XRef")
+                    && o.value.get("Encrypt").is_some()
+            })
+    }
+
+    fn resolve<'a>(&'a self, mut value: &'a PdfValue) -> &'a PdfValue {
+        for _ in 0..MAX_DEPTH {
+            match value {
+                PdfValue::Ref(n) => match self.objects.get(n) {
+                    Some(object) => value = &object.value,
+                    None => return &PdfValue::Null,
+                },
+                _ => return value,
+            }
+        }
+        &PdfValue::Null
+    }
+
+    fn decode_stream(&self, object: &PdfObject) -> Option<Vec<u8>> {
+        let raw = object.stream.as_ref()?;
+        let filters: Vec<&str> = match object.value.get("Filter").map(|f| self.resolve(f)) {
+            None | Some(PdfValue::Null) => Vec::new(),
+            Some(PdfValue::Name(name)) => vec![name.as_str()],
+            Some(PdfValue::Array(items)) => items.iter().filter_map(PdfValue::as_name).collect(),
+            Some(_) => return None,
+        };
+
+        let mut data = raw.clone();
+        for filter in filters {
+            data = match filter {
+                "FlateDecode" | "Fl" => read_limited(ZlibDecoder::new(data.as_slice()))
+                    .ok()
+                    .filter(|d| !d.is_empty())
+                    .or_else(|| read_limited(DeflateDecoder::new(data.as_slice())).ok())?,
+                _ => return None,
+            };
+        }
+        Some(data)
+    }
+
+    /// Pages in document order, each with its (possibly inherited) resources
+    fn pages(&self) -> Vec<(&PdfValue, Option<&PdfValue>)> {
+        let mut pages = Vec::new();
+        let root = self
+            .objects
+            .values()
+            .find(|o| o.value.get("Type").and_then(PdfValue::as_name) == Some("Catalog"))
+            .and_then(|catalog| catalog.value.get("Pages"));
+
+        if let Some(root) = root {
+            let mut visited = HashSet::new();
+            self.walk_pages(root, None, 0, &mut visited, &mut pages);
+        }
+
+        if pages.is_empty() {
+            let mut numbers: Vec<_> = self
+                .objects
+                .iter()
+                .filter(|(_, o)| o.value.get("Type").and_then(PdfValue::as_name) == Some("Page"))
+                .map(|(n, _)| *n)
+                .collect();
+            numbers.sort_unstable();
+            for n in numbers {
+                if let Some(object) = self.objects.get(&n) {
+                    pages.push((&object.value, object.value.get("Resources")));
+                }
+            }
+        }
+        pages
+    }
+
+    fn walk_pages<'a>(
+        &'a self,
+        node: &'a PdfValue,
+        inherited: Option<&'a PdfValue>,
+        depth: usize,
+        visited: &mut HashSet<u32>,
+        pages: &mut Vec<(&'a PdfValue, Option<&'a PdfValue>)>,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        if let PdfValue::Ref(n) = node {
+            if !visited.insert(*n) {
+                return;
+            }
+        }
+
+        let dict = self.resolve(node);
+        let resources = dict.get("Resources").or(inherited);
+        match dict.get("Kids").map(|k| self.resolve(k)) {
+            Some(PdfValue::Array(kids)) => {
+                for kid in kids {
+                    self.walk_pages(kid, resources, depth + 1, visited, pages);
+                }
+            }
+            _ if dict.as_dict().is_some() => pages.push((dict, resources)),
+            _ => {}
+        }
+    }
+
+    fn fonts(&self, resources: Option<&PdfValue>) -> HashMap<String, Font> {
+        let mut fonts = HashMap::new();
+        let Some(font_dict) = resources
+            .map(|r| self.resolve(r))
+            .and_then(|r| r.get("Font"))
+            .map(|f| self.resolve(f))
+            .and_then(PdfValue::as_dict)
+        else {
+            return fonts;
+        };
+
+        for (name, font) in font_dict {
+            let font = self.resolve(font);
+            let composite = font.get("Subtype").and_then(PdfValue::as_name) == Some("Type0");
+            let cmap = font
+                .get("ToUnicode")
+                .and_then(|r| match r {
+                    PdfValue::Ref(n) => self.objects.get(n),
+                    _ => None,
+                })
+                .and_then(|o| self.decode_stream(o))
+                .map(|data| parse_cmap(&data));
+
+            let (to_unicode, code_bytes) = match cmap {
+                Some((map, bytes)) => (map, bytes.unwrap_or(if composite { 2 } else { 1 })),
+                None => (HashMap::new(), if composite { 2 } else { 1 }),
+            };
+            fonts.insert(
+                name.clone(),
+                Font {
+                    undecodable: composite && to_unicode.is_empty(),
+                    to_unicode,
+                    code_bytes: code_bytes.max(1),
+                },
+            );
+        }
+        fonts
+    }
+
+    fn page_text(&self, page: &PdfValue, resources: Option<&PdfValue>) -> String {
+        let contents: Vec<&PdfValue> = match page.get("Contents") {
+            Some(PdfValue::Array(items)) => items.iter().collect(),
+            Some(PdfValue::Ref(n)) => match self.objects.get(n).map(|o| &o.value) {
+                // A reference can point at an array of content streams
+                Some(PdfValue::Array(items)) => items.iter().collect(),
+                _ => vec![page.get("Contents").unwrap_or(&PdfValue::Null)],
+            },
+            _ => Vec::new(),
+        };
+
+        let mut data = Vec::new();
+        for content in contents {
+            if let PdfValue::Ref(n) = content {
+                if let Some(decoded) = self.objects.get(n).and_then(|o| self.decode_stream(o)) {
+                    data.extend_from_slice(&decoded);
+                    data.push(b'\n');
+                }
+            }
+        }
+
+        content_text(&data, &self.fonts(resources))
+    }
+}
+
+fn number(value: Option<&PdfValue>) -> Option<f64> {
+    match value {
+        Some(PdfValue::Number(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Find where a stream's data ends
+fn stream_end(data: &[u8], start: usize, length: Option<&PdfValue>) -> usize {
+    if let Some(PdfValue::Number(length)) = length {
+        let end = start + *length as usize;
+        if end <= data.len() {
+            let mut after = Lexer::new(data, end);
+            after.skip_whitespace();
+            if data[after.pos..].starts_with(b"endstream") {
+                return end;
+            }
+        }
+    }
+
+    let end = data[start..]
+        .windows(b"endstream".len())
+        .position(|w| w == b"endstream")
+        .map(|i| start + i)
+        .unwrap_or(data.len());
+    // Drop the end-of-line marker before `endstream`
+    let mut trimmed = end;
+    if trimmed > start && data[trimmed - 1] == b'\n' {
+        trimmed -= 1;
+    }
+    if trimmed > start && data[trimmed - 1] == b'\r' {
+        trimmed -= 1;
+    }
+    trimmed
+}
+
+/// Parse a ToUnicode CMap into a code → text map and the code width in bytes
+fn parse_cmap(data: &[u8]) -> (HashMap<u32, String>, Option<usize>) {
+    let mut map = HashMap::new();
+    let mut code_bytes = None;
+    let mut lexer = Lexer::new(data, 0);
+    let mut section: Option<String> = None;
+    let mut operands: Vec<PdfValue> = Vec::new();
+
+    let code = |bytes: &[u8]| bytes.iter().fold(0u32, |acc, b| acc << 8 | *b as u32);
+    let utf16 = |bytes: &[u8]| {
+        let units: Vec<u16> = bytes
+            .chunks(2)
+            .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+
+    while lexer.pos < data.len() {
+        let Some(value) = lexer.parse() else {
+            lexer.pos += 1;
+            continue;
+        };
+        match value {
+            PdfValue::Operator(op) => {
+                match op.as_str() {
+                    "begincodespacerange" | "beginbfchar" | "beginbfrange" => section = Some(op),
+                    "endcodespacerange" => {
+                        if let Some(PdfValue::Str(low)) = operands.first() {
+                            code_bytes = Some(low.len());
+                        }
+                        section = None;
+                    }
+                    "endbfchar" => {
+                        for pair in operands.chunks(2) {
+                            if let [PdfValue::Str(src), PdfValue::Str(dst)] = pair {
+                                code_bytes.get_or_insert(src.len());
+                                map.insert(code(src), utf16(dst));
+                            }
+                        }
+                        section = None;
+                    }
+                    "endbfrange" => {
+                        for triple in operands.chunks(3) {
+                            let [PdfValue::Str(low), PdfValue::Str(high), dst] = triple else {
+                                continue;
+                            };
+                            code_bytes.get_or_insert(low.len());
+                            let (low, high) = (code(low), code(high));
+                            if high < low || high - low > 0xffff {
+                                continue;
+                            }
+                            for (i, c) in (low..=high).enumerate() {
+                                let text = match dst {
+                                    PdfValue::Str(start) if !start.is_empty() => {
+                                        let mut bytes = start.clone();
+                                        let last = bytes.len() - 1;
+                                        let base = if bytes.len() >= 2 {
+                                            u16::from_be_bytes([bytes[last - 1], bytes[last]])
+                                                as usize
+                                        } else {
+                                            bytes[last] as usize
+                                        };
+                                        let value = (base + i) as u16;
+                                        if bytes.len() >= 2 {
+                                            bytes[last - 1..].copy_from_slice(&value.to_be_bytes());
+                                        } else {
+                                            bytes[last] = value as u8;
+                                        }
+                                        utf16(&bytes)
+                                    }
+                                    PdfValue::Array(items) => match items.get(i) {
+                                        Some(PdfValue::Str(s)) => utf16(s),
+                                        _ => continue,
+                                    },
+                                    _ => continue,
+                                };
+                                map.insert(c, text);
+                            }
+                        }
+                        section = None;
+                    }
+                    _ => {}
+                }
+                operands.clear();
+            }
+            other if section.is_some() => operands.push(other),
+            _ => operands.clear(),
+        }
+    }
+
+    (map, code_bytes)
+}
+
+/// Extract text from a content stream
+fn content_text(data: &[u8], fonts: &HashMap<String, Font>) -> String {
+    let fallback = Font {
+        to_unicode: HashMap::new(),
+        code_bytes: 1,
+        undecodable: false,
+    };
+    let mut font = &fallback;
+    let mut output = String::new();
+    let mut operands: Vec<PdfValue> = Vec::new();
+    let mut lexer = Lexer::new(data, 0);
+
+    let newline = |output: &mut String| {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+    };
+
+    while lexer.pos < data.len() {
+        let Some(value) = lexer.parse() else {
+            lexer.pos += 1;
+            continue;
+        };
+        let PdfValue::Operator(op) = value else {
+            operands.push(value);
+            continue;
+        };
+
+        match op.as_str() {
+            "Tf" => {
+                if let Some(name) = operands.iter().rev().find_map(PdfValue::as_name) {
+                    font = fonts.get(name).unwrap_or(&fallback);
+                }
+            }
+            "Tj" | "'" | "\"" => {
+                if op != "Tj" {
+                    newline(&mut output);
+                }
+                if let Some(PdfValue::Str(bytes)) = operands.last() {
+                    output.push_str(&font.decode(bytes));
+                }
+            }
+            "TJ" => {
+                if let Some(PdfValue::Array(items)) = operands.last() {
+                    for item in items {
+                        match item {
+                            PdfValue::Str(bytes) => output.push_str(&font.decode(bytes)),
+                            // Large negative kerning is a word gap
+                            PdfValue::Number(n) if *n < -200.0 && !output.ends_with(' ') => {
+                                output.push(' ')
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            "Td" | "TD" => {
+                if number(operands.last()).is_some_and(|ty| ty.abs() > 0.01) {
+                    newline(&mut output);
+                } else if !output.ends_with([' ', '\n']) && !output.is_empty() {
+                    output.push(' ');
+                }
+            }
+            "T*" | "Tm" | "ET" => newline(&mut output),
+            "ID" => lexer.skip_inline_image(),
+            _ => {}
+        }
+        operands.clear();
+    }
+
+    output
+}
+
+/// Normalize whitespace: trim line ends and collapse runs of blank lines
+fn tidy(text: &str) -> String {
+    let mut output = String::new();
+    let mut blank_lines = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !output.is_empty() {
+            output.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        output.push_str(line);
+        blank_lines = 0;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::{DeflateEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Build a PDF from `(object number, dictionary, optional stream)` entries
+    fn build_pdf(objects: &[(u32, &str, Option<Vec<u8>>)]) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.7\n".to_vec();
+        for (number, dict, stream) in objects {
+            pdf.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+            match stream {
+                Some(data) => {
+                    pdf.extend_from_slice(dict.replace("LEN", &data.len().to_string()).as_bytes());
+                    pdf.extend_from_slice(b"\nstream\n");
+                    pdf.extend_from_slice(data);
+                    pdf.extend_from_slice(b"\nendstream");
+                }
+                None => pdf.extend_from_slice(dict.as_bytes()),
+            }
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+        pdf.extend_from_slice(b"trailer\n<< /Root 1 0 R >>\n%%EOF\n");
+        pdf
+    }
+
+    fn two_page_pdf() -> Vec<u8> {
+        build_pdf(&[
+            (1, "<< /Type /Catalog /Pages 2 0 R >>", None),
+            (
+                2,
+                "<< /Type /Pages /Kids [3 0 R 5 0 R] /Count 2 /Resources << /Font << /F1 7 0 R >> >> >>",
+                None,
+            ),
+            (3, "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>", None),
+            (
+                4,
+                "<< /Length LEN >>",
+                Some(b"BT /F1 12 Tf 72 700 Td (Requirements \\(draft\\)) Tj 0 -14 Td [(Must) -250 (scale)] TJ ET".to_vec()),
+            ),
+            (5, "<< /Type /Page /Parent 2 0 R /Contents [6 0 R] >>", None),
+            (
+                6,
+                "<< /Length LEN /Filter /FlateDecode >>",
+                Some(zlib(b"BT /F1 12 Tf (Second page) Tj ET")),
+            ),
+            (7, "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>", None),
+        ])
+    }
+
+    #[test]
+    fn test_extract_pdf_pages() {
+        let document = extract_pdf(&two_page_pdf()).unwrap();
+        assert_eq!(document.page_count(), 2);
+        assert_eq!(document.pages[0], "Requirements (draft)\nMust scale");
+        assert_eq!(document.pages[1], "Second page");
+        assert_eq!(
+            document.text(),
+            "--- Page 1 ---\nRequirements (draft)\nMust scale\n\n--- Page 2 ---\nSecond page"
+        );
+        assert_eq!(document.pages_text(&[2, 9]), "--- Page 2 ---\nSecond page");
+    }
+
+    #[test]
+    fn test_extract_pdf_with_to_unicode_cmap() {
+        let cmap = b"/CIDInit /ProcSet findresource begin
+begincmap
+1 begincodespacerange <0000> <FFFF> endcodespacerange
+1 beginbfchar <0001> <0048> endbfchar
+1 beginbfrange <0002> <0003> <0069> endbfrange
+endcmap";
+        let pdf = build_pdf(&[
+            (1, "<< /Type /Catalog /Pages 2 0 R >>", None),
+            (2, "<< /Type /Pages /Kids [3 0 R] /Count 1 >>", None),
+            (
+                3,
+                "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Resources << /Font << /F2 5 0 R >> >> >>",
+                None,
+            ),
+            (4, "<< /Length LEN >>", Some(b"BT /F2 10 Tf <000100020003> Tj ET".to_vec())),
+            (5, "<< /Type /Font /Subtype /Type0 /ToUnicode 6 0 R >>", None),
+            (6, "<< /Length LEN /Filter [/FlateDecode] >>", Some(zlib(cmap))),
+        ]);
+
+        let document = extract_pdf(&pdf).unwrap();
+        assert_eq!(document.pages, vec!["Hij".to_string()]);
+    }
+
+    #[test]
+    fn test_pdf_errors() {
+        assert!(matches!(
+            extract_pdf(b"not a pdf"),
+            Err(DocumentError::Unsupported(_))
+        ));
+
+        let mut encrypted = two_page_pdf();
+        encrypted.extend_from_slice(b"trailer\n<< /Root 1 0 R /Encrypt 9 0 R >>\n");
+        assert!(matches!(
+            extract_pdf(&encrypted),
+            Err(DocumentError::Encrypted)
+        ));
+    }
+
+    /// Build a zip archive with one entry
+    fn build_zip(name: &str, content: &[u8], deflate: bool) -> Vec<u8> {
+        let (method, data) = if deflate {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(content).unwrap();
+            (8u16, encoder.finish().unwrap())
+        } else {
+            (0u16, content.to_vec())
+        };
+
+        let mut zip = Vec::new();
+        zip.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        zip.extend_from_slice(&[20, 0, 0, 0]);
+        zip.extend_from_slice(&method.to_le_bytes());
+        zip.extend_from_slice(&[0; 8]);
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(&data);
+
+        let central_offset = zip.len();
+        zip.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        zip.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+        zip.extend_from_slice(&method.to_le_bytes());
+        zip.extend_from_slice(&[0; 8]);
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0; 12]);
+        zip.extend_from_slice(&0u32.to_le_bytes());
+        zip.extend_from_slice(name.as_bytes());
+        let central_size = zip.len() - central_offset;
+
+        zip.extend_from_slice(&[0x50, 0x4b, 0x05, 0x06, 0, 0, 0, 0, 1, 0, 1, 0]);
+        zip.extend_from_slice(&(central_size as u32).to_le_bytes());
+        zip.extend_from_slice(&(central_offset as u32).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip
+    }
+
+    const DOCUMENT_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:body>
+<w:p><w:r><w:t>Spec &amp; overview</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Name</w:t><w:tab/><w:t>Value</w:t></w:r></w:p>
+<w:p><w:r><w:br w:type="page"/><w:t>Appendix</w:t></w:r></w:p>
+</w:body>
+</w:document>"#;
+
+    #[test]
+    fn test_extract_docx() {
+        for deflate in [false, true] {
+            let docx = build_zip("word/document.xml", DOCUMENT_XML.as_bytes(), deflate);
+            let document = extract_docx(&docx).unwrap();
+            assert_eq!(document.kind, DocumentKind::Docx);
+            assert_eq!(
+                document.pages,
+                vec![
+                    "Spec & overview\nName\tValue".to_string(),
+                    "Appendix".to_string()
+                ]
+            );
+        }
+
+        let other = build_zip("content.xml", b"<x/>", false);
+        assert!(matches!(
+            extract_docx(&other),
+            Err(DocumentError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_document_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let pdf_path = temp_dir.path().join("spec.pdf");
+        std::fs::write(&pdf_path, two_page_pdf()).unwrap();
+        assert_eq!(extract_document(&pdf_path).unwrap().page_count(), 2);
+
+        let docx_path = temp_dir.path().join("Spec.DOCX");
+        std::fs::write(
+            &docx_path,
+            build_zip("word/document.xml", DOCUMENT_XML.as_bytes(), true),
+        )
+        .unwrap();
+        assert_eq!(extract_document(&docx_path).unwrap().page_count(), 2);
+
+        let txt_path = temp_dir.path().join("notes.txt");
+        std::fs::write(&txt_path, "plain").unwrap();
+        assert!(matches!(
+            extract_document(&txt_path),
+            Err(DocumentError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_page_range() {
+        assert_eq!(parse_page_range("1-3,5", 10).unwrap(), vec![1, 2, 3, 5]);
+        assert_eq!(parse_page_range("3, 1, 3", 5).unwrap(), vec![1, 3]);
+        assert_eq!(parse_page_range("8-", 10).unwrap(), vec![8, 9, 10]);
+        assert!(parse_page_range("0", 5).is_err());
+        assert!(parse_page_range("4-2", 5).is_err());
+        assert!(parse_page_range("6", 5).is_err());
+        assert!(parse_page_range("x", 5).is_err());
+        assert!(parse_page_range("", 5).is_err());
+    }
+}
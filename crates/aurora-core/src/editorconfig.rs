@@ -0,0 +1,567 @@
+//! EditorConfig support
+//!
+//! Files the agent writes follow the project's `.editorconfig`, so they do
+//! not fight its conventions and produce noisy diffs. The `.editorconfig`
+//! files in the file's directory and its ancestors are read, stopping at one
+//! declaring `root = true`; closer files and later sections take precedence.
+//!
+//! Supported properties are `indent_style`, `indent_size`, `tab_width`,
+//! `end_of_line`, `charset`, `trim_trailing_whitespace`, and
+//! `insert_final_newline`; `unset` clears a property set by an earlier
+//! section. Of the charsets, only `utf-8` and `utf-8-bom` are applied.
+//!
+//! Indentation is applied only to lines an edit adds, so lines the file
+//! already had, such as the body of a multi-line string literal or a code
+//! block in Markdown, keep theirs. Line endings, trailing whitespace, and
+//! the final newline apply to the whole file.
+//!
+//! Section globs follow the EditorConfig rules: a glob without `/` matches
+//! the file name at any depth, otherwise it is relative to the directory of
+//! the `.editorconfig` file. `*`, `?`, and `[...]` do not match `/`, `**`
+//! matches across directories, and `{a,b}` and `{1..3}` expand to
+//! alternatives.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the EditorConfig file
+pub const EDITORCONFIG_FILE: &str = ".editorconfig";
+
+/// Largest numeric range `{n1..n2}` expanded in a section glob
+const MAX_RANGE: i64 = 1000;
+
+/// Errors that can occur while reading EditorConfig files
+#[derive(Error, Debug)]
+pub enum EditorConfigError {
+    /// IO error while reading an `.editorconfig` file
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, EditorConfigError>;
+
+/// Character used for indentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+}
+
+/// Line terminator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfLine {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl EndOfLine {
+    fn as_str(self) -> &'static str {
+        match self {
+            EndOfLine::Lf => "\n",
+            EndOfLine::CrLf => "\r\n",
+            EndOfLine::Cr => "\r",
+        }
+    }
+}
+
+/// Width of one indentation level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentSize {
+    /// A number of columns
+    Columns(usize),
+    /// The tab width (`indent_size = tab`)
+    Tab,
+}
+
+/// The style a file should have; unset properties leave text as it is
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileStyle {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<IndentSize>,
+    pub tab_width: Option<usize>,
+    pub end_of_line: Option<EndOfLine>,
+    /// Lowercase charset name, e.g. `utf-8-bom`
+    pub charset: Option<String>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+}
+
+impl FileStyle {
+    /// The style `.editorconfig` files give the file at `path`
+    pub fn for_file(path: &Path) -> Result<Self> {
+        let mut configs = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            let file = dir.join(EDITORCONFIG_FILE);
+            if !file.is_file() {
+                continue;
+            }
+            let config = ConfigFile::parse(dir, &std::fs::read_to_string(&file)?);
+            let root = config.root;
+            configs.push(config);
+            if root {
+                break;
+            }
+        }
+
+        let mut style = FileStyle::default();
+        for config in configs.iter().rev() {
+            for section in &config.sections {
+                if section.matches(&config.dir, path) {
+                    for (key, value) in &section.properties {
+                        style.set(key, value);
+                    }
+                }
+            }
+        }
+        Ok(style)
+    }
+
+    /// Whether no property is set
+    pub fn is_empty(&self) -> bool {
+        *self == FileStyle::default()
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        let unset = value == "unset";
+        match key {
+            "indent_style" => {
+                self.indent_style = match value {
+                    "tab" => Some(IndentStyle::Tab),
+                    "space" => Some(IndentStyle::Space),
+                    _ if unset => None,
+                    _ => self.indent_style,
+                }
+            }
+            "indent_size" => {
+                self.indent_size = match value {
+                    "tab" => Some(IndentSize::Tab),
+                    _ if unset => None,
+                    _ => value.parse().ok().map(IndentSize::Columns).or(self.indent_size),
+                }
+            }
+            "tab_width" => self.tab_width = if unset { None } else { value.parse().ok().or(self.tab_width) },
+            "end_of_line" => {
+                self.end_of_line = match value {
+                    "lf" => Some(EndOfLine::Lf),
+                    "crlf" => Some(EndOfLine::CrLf),
+                    "cr" => Some(EndOfLine::Cr),
+                    _ if unset => None,
+                    _ => self.end_of_line,
+                }
+            }
+            "charset" => self.charset = (!unset).then(|| value.to_string()),
+            "trim_trailing_whitespace" => self.trim_trailing_whitespace = parse_bool(value, unset, self.trim_trailing_whitespace),
+            "insert_final_newline" => self.insert_final_newline = parse_bool(value, unset, self.insert_final_newline),
+            _ => {}
+        }
+    }
+
+    /// Columns of one indentation level, if known
+    fn indent_columns(&self) -> Option<usize> {
+        match self.indent_size {
+            Some(IndentSize::Columns(columns)) => Some(columns),
+            Some(IndentSize::Tab) => Some(self.tab_width.unwrap_or(DEFAULT_TAB_WIDTH)),
+            None => self.tab_width,
+        }
+    }
+
+    /// Columns a tab advances to
+    fn tab_columns(&self) -> usize {
+        self.tab_width
+            .or(match self.indent_size {
+                Some(IndentSize::Columns(columns)) => Some(columns),
+                _ => None,
+            })
+            .unwrap_or(DEFAULT_TAB_WIDTH)
+    }
+
+    /// `content` of a whole file in this style
+    pub fn apply(&self, content: &str) -> String {
+        self.apply_edit("", content)
+    }
+
+    /// `content` replacing the file's `previous` content, in this style
+    ///
+    /// Lines already in `previous` keep their indentation.
+    pub fn apply_edit(&self, previous: &str, content: &str) -> String {
+        let mut styled = match self.charset.as_deref() {
+            Some("utf-8") => content.strip_prefix('\u{feff}').unwrap_or(content).to_string(),
+            Some("utf-8-bom") if !content.starts_with('\u{feff}') => format!("\u{feff}{}", content),
+            _ => content.to_string(),
+        };
+        styled = self.style_lines(&styled, true, previous);
+        match self.insert_final_newline {
+            Some(true) if !styled.is_empty() && !styled.ends_with(['\n', '\r']) => {
+                let eol = self.end_of_line.map(EndOfLine::as_str).unwrap_or_else(|| detect_eol(&styled));
+                styled.push_str(eol);
+            }
+            Some(false) => styled.truncate(styled.trim_end_matches(['\n', '\r']).len()),
+            _ => {}
+        }
+        styled
+    }
+
+    /// Text inserted into an existing file in place of `replaced`, in this style
+    ///
+    /// The fragment may start or end mid-line, so the indentation of its
+    /// first line and trailing whitespace of its last line are left alone,
+    /// as are the final newline and charset, which belong to the file.
+    /// Lines already in `replaced` keep their indentation.
+    pub fn apply_fragment(&self, replaced: &str, fragment: &str) -> String {
+        self.style_lines(fragment, false, replaced)
+    }
+
+    /// Restyle the lines of `text`, reindenting those not in `previous`;
+    /// `whole` when it is a complete file
+    fn style_lines(&self, text: &str, whole: bool, previous: &str) -> String {
+        let mut kept: HashMap<&str, usize> = HashMap::new();
+        for (line, _) in split_lines(previous) {
+            *kept.entry(line).or_default() += 1;
+        }
+        let lines = split_lines(text);
+        let last = lines.len() - 1;
+        let mut styled = String::with_capacity(text.len());
+        for (i, (line, terminator)) in lines.into_iter().enumerate() {
+            let added = match kept.get_mut(line) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            };
+            styled.push_str(&self.style_line(line, added && (whole || i > 0), whole || i < last));
+            if !terminator.is_empty() {
+                styled.push_str(self.end_of_line.map(EndOfLine::as_str).unwrap_or(terminator));
+            }
+        }
+        styled
+    }
+
+    fn style_line(&self, line: &str, reindent: bool, trim: bool) -> String {
+        let line = if trim && self.trim_trailing_whitespace == Some(true) {
+            line.trim_end_matches([' ', '\t'])
+        } else {
+            line
+        };
+        let (Some(style), true) = (self.indent_style, reindent) else {
+            return line.to_string();
+        };
+        let body = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - body.len()];
+        let tab = self.tab_columns();
+        let columns = indent.chars().fold(0, |col, c| if c == '\t' { col - col % tab + tab } else { col + 1 });
+        let indent = match style {
+            IndentStyle::Space => " ".repeat(columns),
+            IndentStyle::Tab => {
+                let level = self.indent_columns().unwrap_or(tab).max(1);
+                // Leave alignment that is not a whole number of levels as spaces
+                format!("{}{}", "\t".repeat(columns / level), " ".repeat(columns % level))
+            }
+        };
+        format!("{}{}", indent, body)
+    }
+}
+
+/// Tab width when neither `tab_width` nor `indent_size` is set
+const DEFAULT_TAB_WIDTH: usize = 4;
+
+fn parse_bool(value: &str, unset: bool, current: Option<bool>) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ if unset => None,
+        _ => current,
+    }
+}
+
+/// Lines of `text` with their terminators; the last line has none
+fn split_lines(text: &str) -> Vec<(&str, &str)> {
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while let Some(end) = rest.find(['\n', '\r']) {
+        let terminator_len = if rest[end..].starts_with("\r\n") { 2 } else { 1 };
+        lines.push((&rest[..end], &rest[end..end + terminator_len]));
+        rest = &rest[end + terminator_len..];
+    }
+    lines.push((rest, ""));
+    lines
+}
+
+/// The first line terminator in `text`, or `\n` if it has none
+fn detect_eol(text: &str) -> &'static str {
+    match text.find(['\n', '\r']) {
+        Some(i) if text[i..].starts_with("\r\n") => "\r\n",
+        Some(i) if text[i..].starts_with('\r') => "\r",
+        _ => "\n",
+    }
+}
+
+/// One parsed `.editorconfig` file
+struct ConfigFile {
+    dir: PathBuf,
+    root: bool,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    /// The glob as a regex, `None` if it could not be compiled
+    pattern: Option<Regex>,
+    /// Match the path relative to the config directory rather than the name
+    anchored: bool,
+    /// Lowercase keys and values
+    properties: Vec<(String, String)>,
+}
+
+impl ConfigFile {
+    fn parse(dir: &Path, text: &str) -> Self {
+        let mut config = ConfigFile {
+            dir: dir.to_path_buf(),
+            root: false,
+            sections: Vec::new(),
+        };
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                config.sections.push(Section::new(glob));
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            match config.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => config.root = value == "true",
+                None => {}
+            }
+        }
+        config
+    }
+}
+
+impl Section {
+    fn new(glob: &str) -> Self {
+        let anchored = glob.contains('/');
+        let glob = glob.strip_prefix('/').unwrap_or(glob);
+        let alternatives: Vec<String> = expand_braces(glob).iter().map(|glob| glob_to_regex(glob)).collect();
+        Section {
+            pattern: Regex::new(&format!("^(?:{})$", alternatives.join("|"))).ok(),
+            anchored,
+            properties: Vec::new(),
+        }
+    }
+
+    fn matches(&self, dir: &Path, path: &Path) -> bool {
+        let candidate = if self.anchored {
+            match path.strip_prefix(dir) {
+                Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+                Err(_) => return false,
+            }
+        } else {
+            match path.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => return false,
+            }
+        };
+        self.pattern.as_ref().is_some_and(|pattern| pattern.is_match(&candidate))
+    }
+}
+
+/// Regex source matching the same paths as a brace-free glob
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let class: String = chars.clone().take_while(|&c| c != ']').collect();
+                if class.is_empty() || chars.clone().nth(class.chars().count()) != Some(']') || class.contains('/') {
+                    regex.push_str(r"\[");
+                    continue;
+                }
+                chars.nth(class.chars().count());
+                let (negated, members) = match class.strip_prefix('!') {
+                    Some(members) => (true, members),
+                    None => (false, class.as_str()),
+                };
+                regex.push('[');
+                if negated {
+                    regex.push('^');
+                }
+                for member in members.chars() {
+                    match member {
+                        '-' => regex.push('-'),
+                        member => regex.push_str(&regex::escape(&member.to_string())),
+                    }
+                }
+                regex.push(']');
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    regex.push_str(&regex::escape(&escaped.to_string()));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+/// Alternatives of a glob with `{a,b}` and `{n1..n2}` expanded
+fn expand_braces(glob: &str) -> Vec<String> {
+    let Some(open) = glob.find('{') else {
+        return vec![glob.to_string()];
+    };
+    let mut depth = 0;
+    let mut close = None;
+    let mut commas = Vec::new();
+    for (i, c) in glob[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(open + i),
+            _ => {}
+        }
+    }
+    let Some(close) = close else {
+        return vec![glob.to_string()];
+    };
+
+    let (prefix, inner, suffix) = (&glob[..open], &glob[open + 1..close], &glob[close + 1..]);
+    let alternatives: Vec<String> = if commas.is_empty() {
+        match numeric_range(inner) {
+            Some(range) => range,
+            // A brace without alternatives is literal
+            None => {
+                return expand_braces(suffix)
+                    .into_iter()
+                    .map(|rest| format!("{}\\{{{}\\}}{}", prefix, inner, rest))
+                    .collect()
+            }
+        }
+    } else {
+        let mut bounds = vec![open];
+        bounds.extend(&commas);
+        bounds.push(close);
+        bounds.windows(2).map(|w| glob[w[0] + 1..w[1]].to_string()).collect()
+    };
+
+    alternatives
+        .iter()
+        .flat_map(|alternative| expand_braces(&format!("{}{}{}", prefix, alternative, suffix)))
+        .collect()
+}
+
+fn numeric_range(inner: &str) -> Option<Vec<String>> {
+    let (start, end) = inner.split_once("..")?;
+    let (start, end): (i64, i64) = (start.parse().ok()?, end.parse().ok()?);
+    let (low, high) = (start.min(end), start.max(end));
+    (high - low <= MAX_RANGE).then(|| (low..=high).map(|n| n.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_style_for_file_merges_sections_and_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("web/src")).unwrap();
+        std::fs::write(
+            root.join(EDITORCONFIG_FILE),
+            "root = true\n\n[*]\nindent_style = space\nindent_size = 4\nend_of_line = lf\ninsert_final_newline = true\n\n[Makefile]\nindent_style = tab\n\n[*.{js,ts}]\nindent_size = 2\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("web").join(EDITORCONFIG_FILE), "[src/**.ts]\nend_of_line = CRLF\ninsert_final_newline = unset\n").unwrap();
+
+        let rust = FileStyle::for_file(&root.join("src/main.rs")).unwrap();
+        assert_eq!(rust.indent_style, Some(IndentStyle::Space));
+        assert_eq!(rust.indent_size, Some(IndentSize::Columns(4)));
+        assert_eq!(FileStyle::for_file(&root.join("Makefile")).unwrap().indent_style, Some(IndentStyle::Tab));
+
+        let ts = FileStyle::for_file(&root.join("web/src/app/main.ts")).unwrap();
+        assert_eq!(ts.indent_size, Some(IndentSize::Columns(2)));
+        assert_eq!(ts.end_of_line, Some(EndOfLine::CrLf));
+        assert_eq!(ts.insert_final_newline, None);
+        assert_eq!(FileStyle::for_file(&root.join("web/main.ts")).unwrap().end_of_line, Some(EndOfLine::Lf));
+    }
+
+    #[test]
+    fn test_apply_style() {
+        let style = FileStyle {
+            indent_style: Some(IndentStyle::Tab),
+            indent_size: Some(IndentSize::Columns(4)),
+            end_of_line: Some(EndOfLine::CrLf),
+            trim_trailing_whitespace: Some(true),
+            insert_final_newline: Some(true),
+            ..FileStyle::default()
+        };
+        assert_eq!(style.apply("fn f() {  \n    if x {\n        y();\n      }\n}"), "fn f() {\r\n\tif x {\r\n\t\ty();\r\n\t  }\r\n}\r\n");
+        assert_eq!(style.apply_fragment("", "  x = 1;  \n        y = 2;  "), "  x = 1;\r\n\t\ty = 2;  ");
+
+        let style = FileStyle {
+            indent_style: Some(IndentStyle::Space),
+            indent_size: Some(IndentSize::Columns(2)),
+            insert_final_newline: Some(false),
+            charset: Some("utf-8-bom".to_string()),
+            ..FileStyle::default()
+        };
+        assert_eq!(style.apply("a\r\n\tb\r\n\r\n"), "\u{feff}a\r\n  b");
+        assert!(FileStyle::default().is_empty());
+        assert_eq!(FileStyle::default().apply("a\r\n\tb  \n"), "a\r\n\tb  \n");
+    }
+
+    #[test]
+    fn test_apply_edit_keeps_existing_indentation() {
+        let style = FileStyle {
+            indent_style: Some(IndentStyle::Tab),
+            indent_size: Some(IndentSize::Columns(4)),
+            trim_trailing_whitespace: Some(true),
+            ..FileStyle::default()
+        };
+        let previous = "fn usage() -> &'static str {\n    \"\n    usage: tool <file>\n        --verbose  \n    \"\n}\n";
+        let content = "fn usage() -> &'static str {\n    \"\n    usage: tool <file>\n        --verbose  \n    \"\n}\n\nfn main() {\n    run();\n}\n";
+        assert_eq!(
+            style.apply_edit(previous, content),
+            "fn usage() -> &'static str {\n    \"\n    usage: tool <file>\n        --verbose\n    \"\n}\n\nfn main() {\n\trun();\n}\n"
+        );
+        assert_eq!(style.apply_fragment("    a\n    b", "    a\n    b\n    c"), "    a\n    b\n\tc");
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("*.{js,ts}"), vec!["*.js", "*.ts"]);
+        assert_eq!(expand_braces("{a,b{c,d}}.txt"), vec!["a.txt", "bc.txt", "bd.txt"]);
+        assert_eq!(expand_braces("v{1..3}"), vec!["v1", "v2", "v3"]);
+        assert_eq!(expand_braces("{single}"), vec!["\\{single\\}"]);
+
+        let section = Section::new("src/**.{ts,tsx}");
+        let dir = Path::new("/project");
+        assert!(section.matches(dir, Path::new("/project/src/a/b.tsx")));
+        assert!(!section.matches(dir, Path::new("/project/lib/b.ts")));
+        let section = Section::new("[!.]*.[ch]");
+        assert!(section.matches(dir, Path::new("/project/deep/main.c")));
+        assert!(!section.matches(dir, Path::new("/project/.hidden.c")));
+    }
+}
@@ -0,0 +1,187 @@
+//! External programs the agent's tools run
+//!
+//! Formatters, linters, container engines, and infrastructure CLIs are
+//! installed per machine. [`ExternalTools`] records which of the
+//! [`PROGRAMS`] are available, so tool descriptions can tell the model what
+//! will not work before it tries, and a missing program fails with how to
+//! install it rather than a bare "not found".
+//!
+//! Discovery only looks for executables, on `PATH` and in the project's
+//! `node_modules/.bin`, without running them. It is cheap enough to repeat
+//! whenever the environment changes, such as after the login shell's `PATH`
+//! has been captured.
+
+use crate::shell::find_in_path;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// An external program some tool runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExternalProgram {
+    /// Executable name
+    pub name: &'static str,
+    /// What the tools use it for
+    pub purpose: &'static str,
+    /// How to install it
+    pub install: &'static str,
+}
+
+/// Programs the built-in tools run
+pub const PROGRAMS: &[ExternalProgram] = &[
+    ExternalProgram { name: "rustfmt", purpose: "Rust formatting", install: "rustup component add rustfmt" },
+    ExternalProgram { name: "prettier", purpose: "JavaScript and TypeScript formatting", install: "npm install --save-dev prettier" },
+    ExternalProgram { name: "black", purpose: "Python formatting", install: "pip install black" },
+    ExternalProgram { name: "gofmt", purpose: "Go formatting", install: "install Go from https://go.dev/dl/" },
+    ExternalProgram { name: "clang-format", purpose: "C and C++ formatting", install: "install clang-format from your package manager (e.g. apt install clang-format, brew install clang-format)" },
+    ExternalProgram { name: "rustc", purpose: "Rust syntax checks", install: "install Rust from https://rustup.rs" },
+    ExternalProgram { name: "cargo", purpose: "Rust snippets", install: "install Rust from https://rustup.rs" },
+    ExternalProgram { name: "tsc", purpose: "TypeScript syntax checks", install: "npm install --save-dev typescript" },
+    ExternalProgram { name: "node", purpose: "JavaScript syntax checks, snippets, and REPL sessions", install: "install Node.js from https://nodejs.org" },
+    ExternalProgram { name: "python", purpose: "Python syntax checks, snippets, REPL sessions, and Jupyter kernels", install: "install Python from https://www.python.org/downloads/" },
+    ExternalProgram { name: "ruby", purpose: "Ruby REPL sessions", install: "install Ruby from https://www.ruby-lang.org/en/documentation/installation/" },
+    ExternalProgram { name: "go", purpose: "Go syntax checks", install: "install Go from https://go.dev/dl/" },
+    ExternalProgram { name: "docker", purpose: "container builds and runs", install: "install Docker from https://docs.docker.com/get-docker/" },
+    ExternalProgram { name: "podman", purpose: "container builds and runs", install: "install Podman from https://podman.io/docs/installation" },
+    ExternalProgram { name: "kubectl", purpose: "Kubernetes cluster reads and kustomize rendering", install: "install kubectl from https://kubernetes.io/docs/tasks/tools/" },
+    ExternalProgram { name: "helm", purpose: "Helm chart rendering", install: "install Helm from https://helm.sh/docs/intro/install/" },
+    ExternalProgram { name: "terraform", purpose: "Terraform plans", install: "install Terraform from https://developer.hashicorp.com/terraform/install" },
+    ExternalProgram { name: "pulumi", purpose: "Pulumi previews", install: "install Pulumi from https://www.pulumi.com/docs/install/" },
+    ExternalProgram { name: "psql", purpose: "PostgreSQL schema introspection", install: "install the PostgreSQL client from your package manager (e.g. apt install postgresql-client, brew install libpq)" },
+    ExternalProgram { name: "mysql", purpose: "MySQL schema introspection", install: "install the MySQL client from your package manager (e.g. apt install mysql-client, brew install mysql-client)" },
+    ExternalProgram { name: "sqlite3", purpose: "SQLite schema introspection", install: "install SQLite from https://sqlite.org/download.html or your package manager" },
+    ExternalProgram { name: "sqlx", purpose: "SQLx migrations", install: "cargo install sqlx-cli" },
+    ExternalProgram { name: "diesel", purpose: "Diesel migrations", install: "cargo install diesel_cli" },
+    ExternalProgram { name: "alembic", purpose: "Alembic migrations", install: "pip install alembic" },
+    ExternalProgram { name: "prisma", purpose: "Prisma migrations", install: "npm install --save-dev prisma" },
+    ExternalProgram { name: "knex", purpose: "Knex migrations", install: "npm install --save-dev knex" },
+];
+
+/// Which [`PROGRAMS`] are installed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExternalTools {
+    found: BTreeMap<&'static str, PathBuf>,
+}
+
+/// Availability of one program, for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgramStatus {
+    pub name: String,
+    pub purpose: String,
+    /// Where it was found, if it was
+    pub path: Option<PathBuf>,
+    pub install: String,
+}
+
+impl ExternalTools {
+    /// Look for every program in the project's `node_modules/.bin` and then
+    /// on `path` (the process's `PATH` when `None`)
+    pub fn discover(project_root: &Path, path: Option<&OsStr>) -> Self {
+        let found = PROGRAMS
+            .iter()
+            .filter_map(|program| Some((program.name, locate(program.name, project_root, path)?)))
+            .collect();
+        Self { found }
+    }
+
+    /// Where `name` was found
+    pub fn path(&self, name: &str) -> Option<&Path> {
+        self.found.get(name).map(PathBuf::as_path)
+    }
+
+    /// Whether `name` was found
+    pub fn is_available(&self, name: &str) -> bool {
+        self.found.contains_key(name)
+    }
+
+    /// Record that `name` was found at `path` after discovery
+    pub fn insert(&mut self, name: &str, path: PathBuf) {
+        if let Some(program) = PROGRAMS.iter().find(|program| program.name == name) {
+            self.found.insert(program.name, path);
+        }
+    }
+
+    /// Availability of every known program
+    pub fn statuses(&self) -> Vec<ProgramStatus> {
+        PROGRAMS
+            .iter()
+            .map(|program| ProgramStatus {
+                name: program.name.to_string(),
+                purpose: program.purpose.to_string(),
+                path: self.found.get(program.name).cloned(),
+                install: program.install.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Look for `name` in the project's `node_modules/.bin`, then on `path`
+pub fn locate(name: &str, project_root: &Path, path: Option<&OsStr>) -> Option<PathBuf> {
+    let local = std::env::join_paths([project_root.join("node_modules").join(".bin")]).ok()?;
+    find_in_path(name, &local).or_else(|| match path {
+        Some(path) => find_in_path(name, path),
+        None => crate::shell::find_on_path(name),
+    })
+}
+
+/// How to install `name`, if it is a known program
+pub fn install_hint(name: &str) -> Option<&'static str> {
+    PROGRAMS.iter().find(|program| program.name == name).map(|program| program.install)
+}
+
+/// What to tell the model when `name` cannot be run
+pub fn missing_program_message(name: &str) -> String {
+    match install_hint(name) {
+        Some(install) => format!("{} is not installed or not on PATH. To install it: {}", name, install),
+        None => format!("{} is not installed or not on PATH", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn executable(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_discover() {
+        let project = TempDir::new().unwrap();
+        let bin = TempDir::new().unwrap();
+        let name = |program: &str| if cfg!(windows) { format!("{}.exe", program) } else { program.to_string() };
+        executable(&project.path().join("node_modules/.bin").join(name("prettier")));
+        executable(&bin.path().join(name("black")));
+        executable(&bin.path().join(name("prettier")));
+
+        let path = std::env::join_paths([bin.path()]).unwrap();
+        let mut tools = ExternalTools::discover(project.path(), Some(&path));
+        assert!(tools.is_available("black"));
+        assert!(!tools.is_available("rustfmt"));
+        assert!(tools.path("prettier").unwrap().starts_with(project.path()), "project installs win");
+
+        tools.insert("rustfmt", bin.path().join("rustfmt"));
+        tools.insert("unknown", bin.path().join("unknown"));
+        let statuses = tools.statuses();
+        assert_eq!(statuses.len(), PROGRAMS.len());
+        assert!(statuses.iter().any(|status| status.name == "rustfmt" && status.path.is_some()));
+        assert!(!tools.is_available("unknown"));
+    }
+
+    #[test]
+    fn test_missing_program_message() {
+        assert_eq!(
+            missing_program_message("black"),
+            "black is not installed or not on PATH. To install it: pip install black"
+        );
+        assert_eq!(missing_program_message("frobnicate"), "frobnicate is not installed or not on PATH");
+    }
+}
@@ -0,0 +1,342 @@
+//! Icons and colors of files in the file tree and editor tabs
+//!
+//! The tree and the tabs draw the same icon for a file, so the mapping from
+//! file names to icons is kept here instead of in the frontend. Built-in
+//! icons cover common languages and project files. Plugins and the project
+//! config add `[file_icons.<icon>]` tables that map more files to an icon or
+//! recolor an existing one; the project config is applied last, so it wins.
+//!
+//! A file is matched by its exact name first, then by its longest
+//! extension (`d.ts` before `ts`), and gets the generic `file` icon
+//! otherwise. Directories always get the `folder` icon.
+
+use crate::config::{Config, FileIconDefinition};
+use crate::plugin::PluginManager;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors in icon definitions
+#[derive(Error, Debug)]
+pub enum FileIconError {
+    /// The color is not `#rgb` or `#rrggbb`
+    #[error("Invalid color '{color}' for icon '{icon}'; expected #rgb or #rrggbb")]
+    InvalidColor { icon: String, color: String },
+}
+
+pub type Result<T> = std::result::Result<T, FileIconError>;
+
+/// Icon of files no definition matches
+pub const FILE_ICON: &str = "file";
+
+/// Icon of directories
+pub const FOLDER_ICON: &str = "folder";
+
+const FILE_COLOR: &str = "#8a8a8a";
+const FOLDER_COLOR: &str = "#dcb67a";
+
+/// Built-in icons: name, color, extensions, and file names
+const BUILTIN_ICONS: &[(&str, &str, &[&str], &[&str])] = &[
+    ("rust", "#dea584", &["rs"], &[]),
+    ("python", "#3572a5", &["py", "pyi", "pyw"], &["requirements.txt", "pipfile"]),
+    ("javascript", "#f1e05a", &["js", "mjs", "cjs"], &[]),
+    ("typescript", "#3178c6", &["ts", "mts", "cts"], &[]),
+    ("typescript-def", "#3178c6", &["d.ts"], &[]),
+    ("react", "#61dafb", &["jsx", "tsx"], &[]),
+    ("vue", "#41b883", &["vue"], &[]),
+    ("svelte", "#ff3e00", &["svelte"], &[]),
+    ("html", "#e34c26", &["html", "htm"], &[]),
+    ("css", "#563d7c", &["css", "scss", "sass", "less"], &[]),
+    ("json", "#cbcb41", &["json", "jsonc", "json5"], &[]),
+    ("toml", "#9c4221", &["toml"], &[]),
+    ("yaml", "#cb171e", &["yaml", "yml"], &[]),
+    ("xml", "#0060ac", &["xml", "xsd", "xsl"], &[]),
+    ("markdown", "#519aba", &["md", "markdown", "mdx"], &[]),
+    ("text", "#8a8a8a", &["txt", "log"], &[]),
+    ("go", "#00add8", &["go"], &["go.mod", "go.sum"]),
+    ("java", "#b07219", &["java", "jar"], &[]),
+    ("kotlin", "#a97bff", &["kt", "kts"], &[]),
+    ("scala", "#c22d40", &["scala", "sc"], &[]),
+    ("c", "#555555", &["c", "h"], &[]),
+    ("cpp", "#f34b7d", &["cpp", "cc", "cxx", "hpp", "hh", "hxx"], &[]),
+    ("csharp", "#178600", &["cs", "csx"], &[]),
+    ("fsharp", "#b845fc", &["fs", "fsi", "fsx"], &[]),
+    ("ruby", "#701516", &["rb", "gemspec"], &["gemfile", "rakefile"]),
+    ("php", "#4f5d95", &["php"], &[]),
+    ("swift", "#f05138", &["swift"], &[]),
+    ("dart", "#00b4ab", &["dart"], &[]),
+    ("lua", "#000080", &["lua"], &[]),
+    ("zig", "#ec915c", &["zig"], &[]),
+    ("haskell", "#5e5086", &["hs", "lhs"], &[]),
+    ("elixir", "#6e4a7e", &["ex", "exs"], &[]),
+    ("erlang", "#b83998", &["erl", "hrl"], &[]),
+    ("r", "#198ce7", &["r", "rmd"], &[]),
+    ("julia", "#a270ba", &["jl"], &[]),
+    ("nix", "#7e7eff", &["nix"], &[]),
+    ("shell", "#89e051", &["sh", "bash", "zsh", "fish"], &[".bashrc", ".zshrc", ".profile"]),
+    ("powershell", "#012456", &["ps1", "psm1", "psd1"], &[]),
+    ("sql", "#e38c00", &["sql"], &[]),
+    ("graphql", "#e10098", &["graphql", "gql"], &[]),
+    ("protobuf", "#6a6a6a", &["proto"], &[]),
+    ("terraform", "#7b42bc", &["tf", "tfvars", "hcl"], &[]),
+    (
+        "docker",
+        "#384d54",
+        &["dockerfile"],
+        &["dockerfile", "containerfile", ".dockerignore", "docker-compose.yml", "docker-compose.yaml", "compose.yml", "compose.yaml"],
+    ),
+    ("git", "#f14e32", &[], &[".gitignore", ".gitattributes", ".gitmodules"]),
+    ("lock", "#8a8a8a", &["lock"], &["package-lock.json", "pnpm-lock.yaml"]),
+    ("env", "#faf743", &["env"], &[".env"]),
+    ("npm", "#cb3837", &[], &["package.json", ".npmrc"]),
+    ("cargo", "#dea584", &[], &["cargo.toml"]),
+    ("make", "#427819", &["mk"], &["makefile", "gnumakefile", "justfile"]),
+    ("license", "#d0bf41", &[], &["license", "license.md", "license.txt", "copying"]),
+    ("aurora", "#e25d8a", &[], &["aurora.md", ".auroraignore"]),
+    ("notebook", "#da5b0b", &["ipynb"], &[]),
+    ("image", "#a074c4", &["png", "jpg", "jpeg", "gif", "bmp", "ico", "webp", "svg"], &[]),
+    ("pdf", "#b30b00", &["pdf"], &[]),
+    ("archive", "#8a8a8a", &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z"], &[]),
+];
+
+/// The icon drawn for a file or directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIcon {
+    /// Icon name, such as `rust`, `markdown`, `file`, or `folder`
+    pub name: String,
+    /// Color as `#rgb` or `#rrggbb`
+    pub color: String,
+}
+
+/// Icons by file name and extension
+#[derive(Debug, Clone)]
+pub struct FileIcons {
+    /// Color of each icon
+    colors: BTreeMap<String, String>,
+    /// Icon of each lowercase file name
+    file_names: HashMap<String, String>,
+    /// Icon of each lowercase extension
+    extensions: HashMap<String, String>,
+}
+
+impl Default for FileIcons {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl FileIcons {
+    /// The built-in icons
+    pub fn builtin() -> Self {
+        let mut icons = Self {
+            colors: BTreeMap::new(),
+            file_names: HashMap::new(),
+            extensions: HashMap::new(),
+        };
+        icons.colors.insert(FILE_ICON.to_string(), FILE_COLOR.to_string());
+        icons.colors.insert(FOLDER_ICON.to_string(), FOLDER_COLOR.to_string());
+        for (name, color, extensions, file_names) in BUILTIN_ICONS {
+            icons.colors.insert(name.to_string(), color.to_string());
+            icons.map(name, extensions.iter().copied(), file_names.iter().copied());
+        }
+        icons
+    }
+
+    /// The built-in icons with those of the project's enabled plugins and
+    /// config added
+    ///
+    /// Invalid definitions are skipped with a warning, so one bad color
+    /// does not take every icon away.
+    pub fn for_project(project_root: &Path, config: &Config) -> Self {
+        let mut icons = Self::builtin();
+
+        // Discovery creates the plugins directory when it is missing, which
+        // looking up icons should not do
+        let mut plugins = PluginManager::new(project_root);
+        if plugins.plugins_dir.is_dir() {
+            if let Err(e) = plugins.discover_plugins() {
+                tracing::warn!("Failed to load plugins: {}", e);
+            }
+            icons.add_plugins(&plugins);
+        }
+
+        for (name, definition) in &config.file_icons {
+            if let Err(e) = icons.add(name, definition) {
+                tracing::warn!("Skipping file icon from config: {}", e);
+            }
+        }
+        icons
+    }
+
+    /// Map the files of `definition` to the icon `name`, and recolor it
+    /// when the definition has a color
+    ///
+    /// A new icon without a color gets the color of plain files.
+    pub fn add(&mut self, name: &str, definition: &FileIconDefinition) -> Result<()> {
+        if let Some(color) = &definition.color {
+            if !is_color(color) {
+                return Err(FileIconError::InvalidColor {
+                    icon: name.to_string(),
+                    color: color.clone(),
+                });
+            }
+        }
+
+        let color = definition
+            .color
+            .clone()
+            .or_else(|| self.colors.get(name).cloned())
+            .unwrap_or_else(|| FILE_COLOR.to_string());
+        self.colors.insert(name.to_string(), color);
+        self.map(
+            name,
+            definition.extensions.iter().map(String::as_str),
+            definition.file_names.iter().map(String::as_str),
+        );
+        Ok(())
+    }
+
+    /// Add the icons of the enabled plugins, in plugin name order
+    pub fn add_plugins(&mut self, plugins: &PluginManager) {
+        let mut enabled = plugins.enabled_plugins();
+        enabled.sort_by(|a, b| a.metadata.plugin.name.cmp(&b.metadata.plugin.name));
+
+        for plugin in enabled {
+            for (name, definition) in &plugin.metadata.file_icons {
+                if let Err(e) = self.add(name, definition) {
+                    tracing::warn!("Skipping file icon from plugin {}: {}", plugin.metadata.plugin.name, e);
+                }
+            }
+        }
+    }
+
+    /// The icon of the file or directory at `path`
+    pub fn icon(&self, path: &Path, is_directory: bool) -> FileIcon {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        self.icon_for_name(&name, is_directory)
+    }
+
+    /// The icon of a file or directory named `name`
+    pub fn icon_for_name(&self, name: &str, is_directory: bool) -> FileIcon {
+        let icon = if is_directory {
+            FOLDER_ICON
+        } else {
+            let name = name.to_lowercase();
+            self.file_names
+                .get(&name)
+                .or_else(|| {
+                    // Longest extension first; a leading dot starts a hidden
+                    // file's name, not an extension
+                    name.char_indices()
+                        .skip(1)
+                        .filter(|(_, c)| *c == '.')
+                        .find_map(|(i, _)| self.extensions.get(&name[i + 1..]))
+                })
+                .map_or(FILE_ICON, String::as_str)
+        };
+        FileIcon {
+            name: icon.to_string(),
+            color: self.colors.get(icon).cloned().unwrap_or_else(|| FILE_COLOR.to_string()),
+        }
+    }
+
+    fn map<'a>(
+        &mut self,
+        name: &str,
+        extensions: impl Iterator<Item = &'a str>,
+        file_names: impl Iterator<Item = &'a str>,
+    ) {
+        for extension in extensions {
+            let extension = extension.trim_start_matches('.').to_lowercase();
+            self.extensions.insert(extension, name.to_string());
+        }
+        for file_name in file_names {
+            self.file_names.insert(file_name.to_lowercase(), name.to_string());
+        }
+    }
+}
+
+/// Whether `color` is `#rgb` or `#rrggbb`
+fn is_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_icons() {
+        let icons = FileIcons::builtin();
+        let name = |file: &str| icons.icon(Path::new(file), false).name;
+
+        assert_eq!(name("src/main.rs"), "rust");
+        assert_eq!(name("README.MD"), "markdown");
+        assert_eq!(name("types/index.d.ts"), "typescript-def");
+        assert_eq!(name("app.ts"), "typescript");
+        assert_eq!(name("Cargo.toml"), "cargo");
+        assert_eq!(name("Dockerfile"), "docker");
+        assert_eq!(name(".gitignore"), "git");
+        assert_eq!(name(".hidden"), FILE_ICON);
+        assert_eq!(name("no_extension"), FILE_ICON);
+        assert_eq!(icons.icon(Path::new("src.rs"), true).name, FOLDER_ICON);
+        assert_eq!(icons.icon(Path::new("lib.rs"), false).color, "#dea584");
+    }
+
+    #[test]
+    fn test_added_icons() {
+        let mut icons = FileIcons::builtin();
+        let definition = |extensions: &[&str], color: Option<&str>| FileIconDefinition {
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            file_names: Vec::new(),
+            color: color.map(str::to_string),
+        };
+
+        icons.add("rust", &definition(&["ron"], Some("#f00"))).unwrap();
+        assert_eq!(
+            icons.icon_for_name("scene.ron", false),
+            FileIcon {
+                name: "rust".to_string(),
+                color: "#f00".to_string()
+            }
+        );
+        assert_eq!(icons.icon_for_name("main.rs", false).color, "#f00");
+
+        icons.add("astro", &definition(&[".astro"], None)).unwrap();
+        assert_eq!(icons.icon_for_name("index.astro", false).name, "astro");
+        assert_eq!(icons.icon_for_name("index.astro", false).color, FILE_COLOR);
+
+        assert!(icons.add("bad", &definition(&["bad"], Some("red"))).is_err());
+        assert_eq!(icons.icon_for_name("x.bad", false).name, FILE_ICON);
+    }
+
+    #[test]
+    fn test_project_icons() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plugin_dir = dir.path().join(".AuroraHeart").join("plugins").join("astro");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            "[plugin]\nname = \"astro\"\nversion = \"1.0.0\"\ndescription = \"Astro\"\nauthor = \"a\"\n\n\
+             [file_icons.astro]\nextensions = [\"astro\"]\ncolor = \"#ff5d01\"\n",
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.file_icons.insert(
+            "astro".to_string(),
+            FileIconDefinition {
+                color: Some("#000000".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let icons = FileIcons::for_project(dir.path(), &config);
+        let icon = icons.icon_for_name("page.astro", false);
+        assert_eq!(icon.name, "astro");
+        assert_eq!(icon.color, "#000000");
+    }
+}
@@ -0,0 +1,554 @@
+//! In-memory index of project file paths for quick-open
+//!
+//! The index walks the project once and then answers fuzzy queries (see
+//! [`crate::fuzzy`]) from memory. It is kept fresh by polling: a
+//! directory's modification time changes whenever an entry is added to it,
+//! removed from it, or renamed within it, so [`FileIndex::refresh`] only
+//! stats the indexed directories and re-reads the few that changed. Edits to
+//! existing files do not change the path list and cost nothing.
+//!
+//! The index also holds each directory's entries, so a file tree can be
+//! served from it, and a refresh reports the directories added and removed
+//! as well as the files.
+//!
+//! Hidden entries, the directories the project search skips (or another
+//! list given to [`FileIndex::build_skipping`]), and paths hidden by the
+//! project's ignore rules are left out. Symbolic links to files are indexed;
+//! links to directories are listed as entries but not followed.
+
+use crate::fuzzy::FuzzyQuery;
+use crate::ignore::IgnoreRules;
+use crate::search::SKIPPED_DIRS;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+
+/// Errors that can occur while building or refreshing the index
+#[derive(Error, Debug)]
+pub enum FileIndexError {
+    /// The project root is not a directory
+    #[error("Not a directory: {0}")]
+    NotADirectory(PathBuf),
+
+    /// IO error while walking the project
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result type for file index operations
+pub type Result<T> = std::result::Result<T, FileIndexError>;
+
+/// A file matched by a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMatch {
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// Higher is better
+    pub score: i64,
+    /// Character indices of the matched characters in `path`
+    pub positions: Vec<usize>,
+}
+
+impl Ord for FileMatch {
+    /// Better matches sort first: higher score, then shorter path
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .cmp(&self.score)
+            .then_with(|| self.path.len().cmp(&other.path.len()))
+            .then_with(|| self.path.cmp(&other.path))
+    }
+}
+
+impl PartialOrd for FileMatch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Paths added to and removed from the index by a refresh
+///
+/// All paths are relative to the project root. Everything under an added or
+/// removed directory is listed too.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexChanges {
+    /// Files now in the index
+    pub added: Vec<String>,
+    /// Files no longer in the index
+    pub removed: Vec<String>,
+    /// Directories now in the index
+    pub added_dirs: Vec<String>,
+    /// Directories no longer in the index
+    pub removed_dirs: Vec<String>,
+}
+
+impl IndexChanges {
+    /// Whether the refresh changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.added_dirs.is_empty()
+            && self.removed_dirs.is_empty()
+    }
+}
+
+/// An entry of an indexed directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// File or directory name
+    pub name: String,
+    /// Whether the entry is a directory (or a link to one)
+    pub is_directory: bool,
+}
+
+/// What the index knows about one directory
+#[derive(Debug, Default)]
+struct IndexedDir {
+    modified: Option<SystemTime>,
+    /// Names of the files directly inside it
+    files: BTreeSet<String>,
+    /// Names of the directories directly inside it
+    dirs: BTreeSet<String>,
+    /// Names of the links to directories directly inside it, which are not
+    /// walked
+    links: BTreeSet<String>,
+}
+
+/// Entries read from one directory
+struct Listing {
+    modified: Option<SystemTime>,
+    files: BTreeSet<String>,
+    dirs: BTreeSet<String>,
+    links: BTreeSet<String>,
+}
+
+/// File paths of a project, searchable by fuzzy query
+#[derive(Debug)]
+pub struct FileIndex {
+    root: PathBuf,
+    ignore: IgnoreRules,
+    /// Directory names that are never walked
+    skipped_dirs: Vec<String>,
+    /// Indexed directories by path relative to the root ("" for the root)
+    dirs: BTreeMap<String, IndexedDir>,
+    /// Every indexed file, relative to the root
+    files: BTreeSet<String>,
+}
+
+/// Bonus for a query matched entirely within the file name, so `main` ranks
+/// `src/main.rs` above `main/src/lib.rs`
+const FILE_NAME_BONUS: i64 = 16;
+
+impl FileIndex {
+    /// Walk `root` and index its files, skipping the directories the
+    /// project search skips
+    pub fn build(root: impl Into<PathBuf>, ignore: IgnoreRules) -> Result<Self> {
+        Self::build_skipping(root, ignore, SKIPPED_DIRS)
+    }
+
+    /// Walk `root` and index its files, skipping directories named in
+    /// `skipped_dirs`
+    pub fn build_skipping(root: impl Into<PathBuf>, ignore: IgnoreRules, skipped_dirs: &[&str]) -> Result<Self> {
+        let root = root.into();
+        if !root.is_dir() {
+            return Err(FileIndexError::NotADirectory(root));
+        }
+        let mut index = Self {
+            root,
+            ignore,
+            skipped_dirs: skipped_dirs.iter().map(|name| name.to_string()).collect(),
+            dirs: BTreeMap::new(),
+            files: BTreeSet::new(),
+        };
+        index.add_dir(String::new(), &mut IndexChanges::default())?;
+        Ok(index)
+    }
+
+    /// The project root
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of indexed files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether no file is indexed
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Every indexed file in path order
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(String::as_str)
+    }
+
+    /// Whether `path` (relative to the root) is indexed
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains(path)
+    }
+
+    /// Entries of the directory `relative` ("" for the root), directories
+    /// first, or `None` if the directory is not indexed
+    pub fn entries(&self, relative: &str) -> Option<Vec<IndexEntry>> {
+        let dir = self.dirs.get(relative)?;
+        let mut entries: Vec<IndexEntry> = dir
+            .dirs
+            .union(&dir.links)
+            .map(|name| IndexEntry {
+                name: name.clone(),
+                is_directory: true,
+            })
+            .collect();
+        entries.extend(dir.files.iter().map(|name| IndexEntry {
+            name: name.clone(),
+            is_directory: false,
+        }));
+        Some(entries)
+    }
+
+    /// The `limit` best matches for `query`, best first
+    ///
+    /// An empty query matches every file; the shortest paths come first.
+    pub fn find(&self, query: &str, limit: usize) -> Vec<FileMatch> {
+        if limit == 0 {
+            return Vec::new();
+        }
+        let query = FuzzyQuery::new(query);
+        // Keep the best `limit` in a heap whose top is the worst kept match
+        let mut best: BinaryHeap<FileMatch> = BinaryHeap::with_capacity(limit + 1);
+
+        for path in &self.files {
+            let candidate = if query.is_empty() {
+                FileMatch {
+                    path: path.clone(),
+                    score: 0,
+                    positions: Vec::new(),
+                }
+            } else {
+                let Some(matched) = query.matches(path) else {
+                    continue;
+                };
+                let name_start = path.rfind('/').map_or(0, |slash| path[..=slash].chars().count());
+                let in_name = matched.positions.first().is_some_and(|&first| first >= name_start);
+                FileMatch {
+                    path: path.clone(),
+                    score: matched.score + if in_name { FILE_NAME_BONUS } else { 0 },
+                    positions: matched.positions,
+                }
+            };
+
+            if best.len() < limit {
+                best.push(candidate);
+            } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                best.pop();
+                best.push(candidate);
+            }
+        }
+
+        best.into_sorted_vec()
+    }
+
+    /// Pick up files added or removed since the last refresh
+    ///
+    /// Directories that disappeared are dropped with everything under them;
+    /// directories whose modification time changed are re-read.
+    pub fn refresh(&mut self) -> Result<IndexChanges> {
+        let mut changes = IndexChanges::default();
+        let stale: Vec<String> = self
+            .dirs
+            .iter()
+            .filter(|(relative, dir)| modified(&self.absolute(relative)) != dir.modified)
+            .map(|(relative, _)| relative.clone())
+            .collect();
+
+        for relative in stale {
+            // Already dropped along with a parent that disappeared
+            if !self.dirs.contains_key(&relative) {
+                continue;
+            }
+            match self.list(&relative) {
+                Ok(listing) => self.apply(&relative, listing, &mut changes)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && !relative.is_empty() => {
+                    self.remove_dir(&relative, &mut changes);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        changes.added.sort();
+        changes.removed.sort();
+        changes.added_dirs.sort();
+        changes.removed_dirs.sort();
+        Ok(changes)
+    }
+
+    /// Bring the entry for `relative` in line with `listing`
+    fn apply(&mut self, relative: &str, listing: Listing, changes: &mut IndexChanges) -> Result<()> {
+        let indexed = self.dirs.entry(relative.to_string()).or_default();
+        indexed.modified = listing.modified;
+        let gone_files: Vec<String> = indexed.files.difference(&listing.files).cloned().collect();
+        let new_files: Vec<String> = listing.files.difference(&indexed.files).cloned().collect();
+        let gone_dirs: Vec<String> = indexed.dirs.difference(&listing.dirs).cloned().collect();
+        let new_dirs: Vec<String> = listing.dirs.difference(&indexed.dirs).cloned().collect();
+        let gone_links: Vec<String> = indexed.links.difference(&listing.links).cloned().collect();
+        let new_links: Vec<String> = listing.links.difference(&indexed.links).cloned().collect();
+        indexed.files = listing.files;
+        indexed.dirs = listing.dirs;
+        indexed.links = listing.links;
+
+        for name in gone_files {
+            let path = join(relative, &name);
+            self.files.remove(&path);
+            changes.removed.push(path);
+        }
+        for name in new_files {
+            let path = join(relative, &name);
+            self.files.insert(path.clone());
+            changes.added.push(path);
+        }
+        for name in gone_dirs {
+            self.remove_dir(&join(relative, &name), changes);
+        }
+        for name in new_dirs {
+            self.add_dir(join(relative, &name), changes)?;
+        }
+        changes.removed_dirs.extend(gone_links.iter().map(|name| join(relative, name)));
+        changes.added_dirs.extend(new_links.iter().map(|name| join(relative, name)));
+        Ok(())
+    }
+
+    /// Index the directory `relative` and everything under it
+    fn add_dir(&mut self, relative: String, changes: &mut IndexChanges) -> Result<()> {
+        let mut pending = vec![relative];
+        while let Some(relative) = pending.pop() {
+            let listing = match self.list(&relative) {
+                Ok(listing) => listing,
+                // Removed while being walked; the parent's next refresh
+                // notices
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && !relative.is_empty() => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for name in &listing.files {
+                let path = join(&relative, name);
+                self.files.insert(path.clone());
+                changes.added.push(path);
+            }
+            pending.extend(listing.dirs.iter().map(|name| join(&relative, name)));
+            changes
+                .added_dirs
+                .extend(listing.links.iter().map(|name| join(&relative, name)));
+            if !relative.is_empty() {
+                changes.added_dirs.push(relative.clone());
+            }
+            self.dirs.insert(
+                relative,
+                IndexedDir {
+                    modified: listing.modified,
+                    files: listing.files,
+                    dirs: listing.dirs,
+                    links: listing.links,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Drop the directory `relative` and everything under it
+    fn remove_dir(&mut self, relative: &str, changes: &mut IndexChanges) {
+        let prefix = format!("{}/", relative);
+        let nested: Vec<String> = self
+            .dirs
+            .range(relative.to_string()..)
+            .map(|(path, _)| path)
+            .take_while(|path| path.starts_with(relative))
+            .filter(|path| *path == relative || path.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for path in nested {
+            if let Some(dir) = self.dirs.remove(&path) {
+                changes
+                    .removed_dirs
+                    .extend(dir.links.iter().map(|name| join(&path, name)));
+            }
+            changes.removed_dirs.push(path);
+        }
+
+        let files: Vec<String> = self
+            .files
+            .range(prefix.clone()..)
+            .take_while(|path| path.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for path in files {
+            self.files.remove(&path);
+            changes.removed.push(path);
+        }
+    }
+
+    /// Read the entries of the directory `relative` that belong in the index
+    fn list(&self, relative: &str) -> std::io::Result<Listing> {
+        let dir = self.absolute(relative);
+        let modified = std::fs::metadata(&dir)?.modified().ok();
+        let mut listing = Listing {
+            modified,
+            files: BTreeSet::new(),
+            dirs: BTreeSet::new(),
+            links: BTreeSet::new(),
+        };
+
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // Links count as what they point to; dangling links are left out
+            let is_link = file_type.is_symlink();
+            let (is_dir, is_file) = if is_link {
+                let target = entry.path();
+                (target.is_dir(), target.is_file())
+            } else {
+                (file_type.is_dir(), file_type.is_file())
+            };
+            if is_dir && self.skipped_dirs.contains(&name) {
+                continue;
+            }
+            if self.ignore.is_ignored(Path::new(&join(relative, &name)), is_dir) {
+                continue;
+            }
+
+            if is_dir && is_link {
+                listing.links.insert(name);
+            } else if is_dir {
+                listing.dirs.insert(name);
+            } else if is_file {
+                listing.files.insert(name);
+            }
+        }
+        Ok(listing)
+    }
+
+    fn absolute(&self, relative: &str) -> PathBuf {
+        if relative.is_empty() {
+            self.root.clone()
+        } else {
+            self.root.join(relative)
+        }
+    }
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/parser")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("fixtures")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/parser/mod.rs"), "").unwrap();
+        fs::write(root.join("src/my_animation.rs"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        fs::write(root.join("target/debug/main"), "").unwrap();
+        fs::write(root.join(".git/HEAD"), "").unwrap();
+        fs::write(root.join("fixtures/main.rs"), "").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_build_and_find() {
+        let temp_dir = project();
+        let ignore = IgnoreRules::new(temp_dir.path(), ["fixtures/"]).unwrap();
+        let index = FileIndex::build(temp_dir.path(), ignore).unwrap();
+
+        let paths: Vec<&str> = index.paths().collect();
+        assert_eq!(paths, vec!["README.md", "src/main.rs", "src/my_animation.rs", "src/parser/mod.rs"]);
+
+        let found = index.find("main", 10);
+        assert_eq!(found[0].path, "src/main.rs");
+        assert_eq!(found[0].positions, vec![4, 5, 6, 7]);
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(index.find("", 2).len(), 2);
+        assert!(index.find("zzz", 10).is_empty());
+        assert!(index.find("main", 0).is_empty());
+        assert!(FileIndex::build(temp_dir.path().join("README.md"), IgnoreRules::default()).is_err());
+    }
+
+    #[test]
+    fn test_refresh_tracks_added_and_removed_paths() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+        let mut index = FileIndex::build(root, IgnoreRules::default()).unwrap();
+        assert!(index.refresh().unwrap().is_empty());
+
+        // Make sure the directories' modification times move on filesystems
+        // with coarse timestamps
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::create_dir_all(root.join("src/lexer")).unwrap();
+        fs::write(root.join("src/lexer/token.rs"), "").unwrap();
+        fs::write(root.join("build.rs"), "").unwrap();
+        fs::remove_dir_all(root.join("src/parser")).unwrap();
+        fs::remove_file(root.join("README.md")).unwrap();
+
+        let changes = index.refresh().unwrap();
+        assert_eq!(changes.added, vec!["build.rs", "src/lexer/token.rs"]);
+        assert_eq!(changes.removed, vec!["README.md", "src/parser/mod.rs"]);
+        assert_eq!(changes.added_dirs, vec!["src/lexer"]);
+        assert_eq!(changes.removed_dirs, vec!["src/parser"]);
+        assert!(index.contains("src/lexer/token.rs"));
+        assert!(!index.contains("src/parser/mod.rs"));
+        assert_eq!(index.find("tok", 5)[0].path, "src/lexer/token.rs");
+    }
+
+    #[test]
+    fn test_entries_list_directories_first() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("src/parser"), root.join("src/alias")).unwrap();
+        let index = FileIndex::build_skipping(root, IgnoreRules::default(), &["node_modules"]).unwrap();
+
+        let entry = |name: &str, is_directory| IndexEntry {
+            name: name.to_string(),
+            is_directory,
+        };
+        let mut expected = vec![entry("parser", true), entry("main.rs", false), entry("my_animation.rs", false)];
+        if cfg!(unix) {
+            // Listed, but its target is not walked again
+            expected.insert(0, entry("alias", true));
+            assert!(index.entries("src/alias").is_none());
+        }
+        assert_eq!(index.entries("src").unwrap(), expected);
+        // Only the given directories are skipped
+        assert_eq!(index.entries("target").unwrap(), vec![entry("debug", true)]);
+        assert!(index.entries("missing").is_none());
+    }
+}
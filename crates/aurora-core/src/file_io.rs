@@ -1,14 +1,21 @@
 //! File I/O utilities for AuroraHeart
 //!
 //! This module provides safe, high-level file I/O operations with proper
-//! error handling and encoding support.
+//! error handling and encoding support. Large files can be read and written
+//! in chunks with progress callbacks; streaming reads stop at a memory cap
+//! instead of loading the whole file.
 
+use crate::paths::WalkGuard;
 use crate::types::{FileContent, FilePath};
+use serde::Serialize;
 use std::fs;
-use std::io;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+/// Size of the chunks used by streaming reads and writes
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
 /// Errors that can occur during file I/O operations
 #[derive(Error, Debug)]
 pub enum FileIoError {
@@ -33,6 +40,36 @@ pub enum FileIoError {
     IsDirectory(PathBuf),
 }
 
+/// Progress of a streaming read or write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Progress {
+    /// Bytes read or written so far
+    pub bytes_done: u64,
+    /// Bytes that will be read or written in total
+    pub total_bytes: u64,
+}
+
+/// Content returned by a capped streaming read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamedRead {
+    /// File content, up to the memory cap
+    pub content: String,
+    /// Size of the whole file in bytes
+    pub total_bytes: u64,
+    /// Whether the content stops before the end of the file
+    pub truncated: bool,
+}
+
+/// Map an IO error to the matching [`FileIoError`] for `path`
+fn io_error(e: io::Error, path: &Path) -> FileIoError {
+    match e.kind() {
+        io::ErrorKind::NotFound => FileIoError::NotFound(path.to_path_buf()),
+        io::ErrorKind::PermissionDenied => FileIoError::PermissionDenied(path.to_path_buf()),
+        io::ErrorKind::InvalidData => FileIoError::InvalidUtf8(path.to_path_buf()),
+        _ => FileIoError::Io(e),
+    }
+}
+
 /// Read a file and return its contents as a UTF-8 string
 pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String, FileIoError> {
     let path = path.as_ref();
@@ -45,17 +82,66 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String, FileIoError> {
         return Err(FileIoError::IsDirectory(path.to_path_buf()));
     }
 
-    match fs::read_to_string(path) {
-        Ok(content) => Ok(content),
-        Err(e) => match e.kind() {
-            io::ErrorKind::NotFound => Err(FileIoError::NotFound(path.to_path_buf())),
-            io::ErrorKind::PermissionDenied => {
-                Err(FileIoError::PermissionDenied(path.to_path_buf()))
-            }
-            io::ErrorKind::InvalidData => Err(FileIoError::InvalidUtf8(path.to_path_buf())),
-            _ => Err(FileIoError::Io(e)),
-        },
+    fs::read_to_string(path).map_err(|e| io_error(e, path))
+}
+
+/// Read at most `max_bytes` of a file in chunks, reporting progress after each chunk
+///
+/// Files larger than the cap are truncated at the last complete UTF-8
+/// character before it, so a huge log can be previewed without loading it
+/// all into memory.
+pub fn read_file_streaming<P, F>(
+    path: P,
+    max_bytes: u64,
+    mut on_progress: F,
+) -> Result<StreamedRead, FileIoError>
+where
+    P: AsRef<Path>,
+    F: FnMut(Progress),
+{
+    let path = path.as_ref();
+
+    if path.is_dir() {
+        return Err(FileIoError::IsDirectory(path.to_path_buf()));
+    }
+
+    let mut file = fs::File::open(path).map_err(|e| io_error(e, path))?;
+    let total_bytes = file.metadata().map_err(|e| io_error(e, path))?.len();
+    let limit = total_bytes.min(max_bytes);
+
+    let mut buffer = Vec::with_capacity(limit as usize);
+    let mut chunk = vec![0; CHUNK_SIZE];
+    while (buffer.len() as u64) < limit {
+        let wanted = CHUNK_SIZE.min((limit - buffer.len() as u64) as usize);
+        let read = file.read(&mut chunk[..wanted]).map_err(|e| io_error(e, path))?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        on_progress(Progress {
+            bytes_done: buffer.len() as u64,
+            total_bytes: limit,
+        });
     }
+
+    let truncated = total_bytes > buffer.len() as u64;
+    let content = match String::from_utf8(buffer) {
+        Ok(content) => content,
+        // The cap may split a multi-byte character; drop the partial bytes
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).map_err(|_| FileIoError::InvalidUtf8(path.to_path_buf()))?
+        }
+        Err(_) => return Err(FileIoError::InvalidUtf8(path.to_path_buf())),
+    };
+
+    Ok(StreamedRead {
+        content,
+        total_bytes,
+        truncated,
+    })
 }
 
 /// Read a file and return it as FileContent
@@ -93,6 +179,42 @@ pub fn write_file<P: AsRef<Path>, C: AsRef<str>>(
     }
 }
 
+/// Write content to a file in chunks, reporting progress after each chunk
+///
+/// Parent directories are created if needed.
+pub fn write_file_streaming<P, F>(
+    path: P,
+    content: &str,
+    mut on_progress: F,
+) -> Result<(), FileIoError>
+where
+    P: AsRef<Path>,
+    F: FnMut(Progress),
+{
+    let path = path.as_ref();
+
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    let file = fs::File::create(path).map_err(|e| io_error(e, path))?;
+    let mut writer = io::BufWriter::with_capacity(CHUNK_SIZE, file);
+    let total_bytes = content.len() as u64;
+    let mut bytes_done = 0;
+    for chunk in content.as_bytes().chunks(CHUNK_SIZE) {
+        writer.write_all(chunk).map_err(|e| io_error(e, path))?;
+        bytes_done += chunk.len() as u64;
+        on_progress(Progress {
+            bytes_done,
+            total_bytes,
+        });
+    }
+    writer.flush().map_err(|e| io_error(e, path))?;
+    Ok(())
+}
+
 /// Write FileContent to disk
 pub fn write_file_content(file_content: &FileContent) -> Result<(), FileIoError> {
     write_file(&file_content.path, &file_content.content)
@@ -135,6 +257,8 @@ pub fn list_files<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, FileIoError> {
 }
 
 /// List all files in a directory recursively
+///
+/// Symbolic links are not followed, so link cycles cannot recurse forever.
 pub fn list_files_recursive<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, FileIoError> {
     let dir = dir.as_ref();
 
@@ -147,18 +271,23 @@ pub fn list_files_recursive<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, File
     }
 
     let mut files = Vec::new();
-    visit_dirs(dir, &mut files)?;
+    let mut guard = WalkGuard::new(dir, false);
+    guard.start(dir);
+    visit_dirs(dir, &mut guard, &mut files)?;
     Ok(files)
 }
 
-fn visit_dirs(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), FileIoError> {
+fn visit_dirs(dir: &Path, guard: &mut WalkGuard, files: &mut Vec<PathBuf>) -> Result<(), FileIoError> {
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
+        if !guard.allows(&path) {
+            continue;
+        }
         if path.is_file() {
             files.push(path);
         } else if path.is_dir() {
-            visit_dirs(&path, files)?;
+            visit_dirs(&path, guard, files)?;
         }
     }
     Ok(())
@@ -224,6 +353,51 @@ mod tests {
         assert_eq!(loaded.content, original.content);
     }
 
+    #[test]
+    fn test_streaming_read_and_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("big.log");
+        let content = "line of log output\n".repeat(CHUNK_SIZE / 10);
+
+        let mut progress = Vec::new();
+        write_file_streaming(&file_path, &content, |p| progress.push(p)).unwrap();
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress.last().unwrap().bytes_done, content.len() as u64);
+
+        let mut progress = Vec::new();
+        let read = read_file_streaming(&file_path, u64::MAX, |p| progress.push(p)).unwrap();
+        assert_eq!(read.content, content);
+        assert!(!read.truncated);
+        assert_eq!(progress.last().unwrap().bytes_done, content.len() as u64);
+
+        let read = read_file_streaming(&file_path, 100, |_| {}).unwrap();
+        assert_eq!(read.content, content[..100]);
+        assert_eq!(read.total_bytes, content.len() as u64);
+        assert!(read.truncated);
+    }
+
+    #[test]
+    fn test_streaming_read_cap_splits_character() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("notes.txt");
+        write_file(&file_path, "aé").unwrap();
+
+        // The cap falls inside the two-byte 'é'
+        let read = read_file_streaming(&file_path, 2, |_| {}).unwrap();
+        assert_eq!(read.content, "a");
+        assert!(read.truncated);
+
+        fs::write(&file_path, [0x61, 0xff, 0x62]).unwrap();
+        assert!(matches!(
+            read_file_streaming(&file_path, 10, |_| {}),
+            Err(FileIoError::InvalidUtf8(_))
+        ));
+        assert!(matches!(
+            read_file_streaming(temp_dir.path().join("missing.txt"), 10, |_| {}),
+            Err(FileIoError::NotFound(_))
+        ));
+    }
+
     #[test]
     fn test_file_exists() {
         let temp_dir = TempDir::new().unwrap();
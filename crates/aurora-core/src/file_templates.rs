@@ -0,0 +1,170 @@
+//! Templates for new files
+//!
+//! A project keeps file templates in `.AuroraHeart/file_templates/`, so
+//! files created by the agent start with the team's license header, module
+//! doc comment, or test scaffolding. The template `license-header` is the
+//! file `license-header.<ext>`, used for new files with that extension, or
+//! `license-header` without an extension, used for any file.
+//!
+//! Templates use the `{{variable}}` placeholders of [`substitute`]:
+//! - `content`: the text being written; a template without `{{content}}` is
+//!   a header and the text follows it
+//! - `file_name` and `file_stem`: the new file's name with and without its
+//!   extension
+//! - `path`: the file's path relative to the project root, with `/`
+//!   separators
+//! - `project_name`: the name of the project directory
+//!
+//! Callers may add more, such as the current date.
+
+use crate::scaffold::substitute;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Directory of file templates inside the project's `.AuroraHeart` directory
+pub const FILE_TEMPLATES_DIR: &str = "file_templates";
+
+/// Errors that can occur while rendering a file template
+#[derive(Error, Debug)]
+pub enum FileTemplateError {
+    /// IO error while reading a template
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// No template with this name applies to the file
+    #[error("Unknown file template '{name}'; available: {}", available_list(.available))]
+    UnknownTemplate { name: String, available: Vec<String> },
+}
+
+pub type Result<T> = std::result::Result<T, FileTemplateError>;
+
+fn available_list(available: &[String]) -> String {
+    if available.is_empty() {
+        format!("none (add templates to .AuroraHeart/{}/)", FILE_TEMPLATES_DIR)
+    } else {
+        available.join(", ")
+    }
+}
+
+/// The file templates of one project
+pub struct FileTemplates {
+    project_root: PathBuf,
+    dir: PathBuf,
+}
+
+impl FileTemplates {
+    /// Templates of the project at `project_root`
+    pub fn for_project(project_root: impl Into<PathBuf>) -> Self {
+        let project_root = project_root.into();
+        let dir = project_root.join(".AuroraHeart").join(FILE_TEMPLATES_DIR);
+        Self { project_root, dir }
+    }
+
+    /// Names of the templates, sorted
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let names: BTreeSet<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| {
+                let path = entry.path();
+                path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+            })
+            .collect();
+        names.into_iter().collect()
+    }
+
+    /// The template file `name` uses for `target`, preferring one for its
+    /// extension over a generic one
+    pub fn resolve(&self, name: &str, target: &Path) -> Result<PathBuf> {
+        let valid_name = !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\']);
+        let extension = target.extension().map(|ext| ext.to_string_lossy().to_string());
+        let candidates = extension
+            .map(|ext| self.dir.join(format!("{}.{}", name, ext)))
+            .into_iter()
+            .chain(std::iter::once(self.dir.join(name)));
+        for candidate in candidates {
+            if valid_name && candidate.is_file() {
+                return Ok(candidate);
+            }
+        }
+        Err(FileTemplateError::UnknownTemplate {
+            name: name.to_string(),
+            available: self.list(),
+        })
+    }
+
+    /// Render template `name` for a new file at `target` holding `content`
+    ///
+    /// `variables` are substituted along with the built-in ones.
+    pub fn render(
+        &self,
+        name: &str,
+        target: &Path,
+        content: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<String> {
+        let template = std::fs::read_to_string(self.resolve(name, target)?)?;
+
+        let mut all = variables.clone();
+        let name_of = |name: Option<&std::ffi::OsStr>| {
+            name.map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+        };
+        all.insert("file_name".to_string(), name_of(target.file_name()));
+        all.insert("file_stem".to_string(), name_of(target.file_stem()));
+        all.insert("project_name".to_string(), name_of(self.project_root.file_name()));
+        let relative = target.strip_prefix(&self.project_root).unwrap_or(target);
+        all.insert("path".to_string(), relative.to_string_lossy().replace('\\', "/"));
+
+        // The content is inserted last so placeholders in it stay as written
+        let rendered = substitute(&template, &all);
+        if rendered.contains("{{content}}") {
+            Ok(rendered.replace("{{content}}", content))
+        } else if rendered.is_empty() || rendered.ends_with('\n') {
+            Ok(format!("{}{}", rendered, content))
+        } else {
+            Ok(format!("{}\n{}", rendered, content))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_header_and_wrapping_templates() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("billing");
+        let dir = root.join(".AuroraHeart").join(FILE_TEMPLATES_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("license"), "# Copyright {{year}} {{project_name}}").unwrap();
+        std::fs::write(dir.join("license.rs"), "// Copyright {{year}} {{project_name}}\n").unwrap();
+        std::fs::write(dir.join("module.rs"), "//! {{file_stem}} ({{path}})\n\n{{content}}\n").unwrap();
+
+        let templates = FileTemplates::for_project(&root);
+        assert_eq!(templates.list(), vec!["license", "module"]);
+
+        let variables = BTreeMap::from([("year".to_string(), "2026".to_string())]);
+        let rust = templates
+            .render("license", &root.join("src/lib.rs"), "pub fn f() {}\n", &variables)
+            .unwrap();
+        assert_eq!(rust, "// Copyright 2026 billing\npub fn f() {}\n");
+        let python = templates
+            .render("license", &root.join("app.py"), "x = '{{content}}'\n", &variables)
+            .unwrap();
+        assert_eq!(python, "# Copyright 2026 billing\nx = '{{content}}'\n");
+        let module = templates
+            .render("module", &root.join("src/invoice.rs"), "pub struct Invoice;", &variables)
+            .unwrap();
+        assert_eq!(module, "//! invoice (src/invoice.rs)\n\npub struct Invoice;\n");
+
+        let err = templates.render("module", &root.join("app.py"), "", &variables).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown file template 'module'; available: license, module");
+        assert!(templates.resolve("../license", &root.join("a.rs")).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! Fuzzy matching in the style of fzf
+//!
+//! A query matches a candidate when its characters appear in the candidate in
+//! order, not necessarily next to each other. Matches are scored the way fzf
+//! scores them: every matched character earns points, gaps between matched
+//! characters cost points, and characters matched at the start of a word
+//! (after `/`, `_`, `-`, `.`, whitespace, or at a camelCase hump) earn a
+//! bonus, as do runs of consecutive characters.
+//!
+//! Whitespace separates terms; every term has to match. Matching ignores
+//! case unless the query contains an uppercase letter.
+
+use serde::{Deserialize, Serialize};
+
+/// Points for each matched character
+const SCORE_MATCH: i64 = 16;
+/// Cost of the first character of a gap
+const SCORE_GAP_START: i64 = -3;
+/// Cost of each further character of a gap
+const SCORE_GAP_EXTENSION: i64 = -1;
+/// Bonus for a match at the start of a word
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+/// Bonus for a match right after whitespace
+const BONUS_BOUNDARY_WHITE: i64 = BONUS_BOUNDARY + 2;
+/// Bonus for a match right after a path or list delimiter
+const BONUS_BOUNDARY_DELIMITER: i64 = BONUS_BOUNDARY + 1;
+/// Bonus for matching a non-word character such as `.` or `/`
+const BONUS_NON_WORD: i64 = SCORE_MATCH / 2;
+/// Bonus for a match at a camelCase hump or the first digit of a number
+const BONUS_CAMEL_123: i64 = BONUS_BOUNDARY - 1;
+/// Minimum bonus for a character continuing a run of matches
+const BONUS_CONSECUTIVE: i64 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+/// The bonus of a term's first character counts this many times
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// A candidate matched by a fuzzy query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuzzyMatch {
+    /// Higher is better
+    pub score: i64,
+    /// Character (not byte) indices of the matched characters, ascending
+    pub positions: Vec<usize>,
+}
+
+/// A parsed fuzzy query, reusable across candidates
+#[derive(Debug, Clone)]
+pub struct FuzzyQuery {
+    terms: Vec<Vec<char>>,
+    case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    White,
+    NonWord,
+    Delimiter,
+    Lower,
+    Upper,
+    Letter,
+    Number,
+}
+
+impl FuzzyQuery {
+    /// Parse `query`
+    pub fn new(query: &str) -> Self {
+        let case_sensitive = query.chars().any(char::is_uppercase);
+        let terms = query
+            .split_whitespace()
+            .map(|term| term.chars().map(|c| fold(c, case_sensitive)).collect())
+            .collect();
+        Self { terms, case_sensitive }
+    }
+
+    /// Whether the query has no terms, which matches everything
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Match `candidate`, returning `None` if some term does not match
+    pub fn matches(&self, candidate: &str) -> Option<FuzzyMatch> {
+        // Cheap check before collecting the candidate's characters
+        if !self.terms.iter().all(|term| self.contains_in_order(term, candidate)) {
+            return None;
+        }
+
+        let text: Vec<char> = candidate.chars().collect();
+        let mut score = 0;
+        let mut positions = Vec::new();
+        for term in &self.terms {
+            let (term_score, term_positions) = self.match_term(term, &text)?;
+            score += term_score;
+            positions.extend(term_positions);
+        }
+        positions.sort_unstable();
+        positions.dedup();
+        Some(FuzzyMatch { score, positions })
+    }
+
+    fn contains_in_order(&self, term: &[char], candidate: &str) -> bool {
+        let mut pending = term.iter().peekable();
+        for c in candidate.chars() {
+            match pending.peek() {
+                Some(&&wanted) if fold(c, self.case_sensitive) == wanted => {
+                    pending.next();
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        pending.peek().is_none()
+    }
+
+    /// Find the shortest window ending at the first complete match, then
+    /// score the characters matched inside it
+    fn match_term(&self, term: &[char], text: &[char]) -> Option<(i64, Vec<usize>)> {
+        let folded = |index: usize| fold(text[index], self.case_sensitive);
+
+        // Forward: where the first complete match ends
+        let mut matched = 0;
+        let mut end = None;
+        for index in 0..text.len() {
+            if folded(index) == term[matched] {
+                matched += 1;
+                if matched == term.len() {
+                    end = Some(index + 1);
+                    break;
+                }
+            }
+        }
+        let end = end?;
+
+        // Backward: the latest start that still matches before `end`
+        let mut remaining = term.len();
+        let mut start = 0;
+        for index in (0..end).rev() {
+            if folded(index) == term[remaining - 1] {
+                remaining -= 1;
+                if remaining == 0 {
+                    start = index;
+                    break;
+                }
+            }
+        }
+
+        Some(self.score(term, text, start, end))
+    }
+
+    fn score(&self, term: &[char], text: &[char], start: usize, end: usize) -> (i64, Vec<usize>) {
+        let mut score = 0;
+        let mut positions = Vec::with_capacity(term.len());
+        let mut pending = 0;
+        let mut in_gap = false;
+        let mut consecutive = 0;
+        let mut first_bonus = 0;
+        let mut prev_class = match start {
+            0 => CharClass::Delimiter,
+            _ => char_class(text[start - 1]),
+        };
+
+        for (index, &c) in text.iter().enumerate().take(end).skip(start) {
+            let class = char_class(c);
+            if pending < term.len() && fold(c, self.case_sensitive) == term[pending] {
+                positions.push(index);
+                score += SCORE_MATCH;
+                let mut bonus = bonus_for(prev_class, class);
+                if consecutive == 0 {
+                    first_bonus = bonus;
+                } else {
+                    // A run keeps the bonus of the boundary it started at
+                    if bonus >= BONUS_BOUNDARY && bonus > first_bonus {
+                        first_bonus = bonus;
+                    }
+                    bonus = bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+                }
+                score += if pending == 0 { bonus * BONUS_FIRST_CHAR_MULTIPLIER } else { bonus };
+                in_gap = false;
+                consecutive += 1;
+                pending += 1;
+            } else {
+                score += if in_gap { SCORE_GAP_EXTENSION } else { SCORE_GAP_START };
+                in_gap = true;
+                consecutive = 0;
+                first_bonus = 0;
+            }
+            prev_class = class;
+        }
+
+        (score, positions)
+    }
+}
+
+/// Match `query` against `candidate`
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    FuzzyQuery::new(query).matches(candidate)
+}
+
+fn fold(c: char, case_sensitive: bool) -> char {
+    if case_sensitive {
+        c
+    } else if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_ascii_lowercase() {
+        CharClass::Lower
+    } else if c.is_ascii_uppercase() {
+        CharClass::Upper
+    } else if c.is_ascii_digit() {
+        CharClass::Number
+    } else if c.is_whitespace() {
+        CharClass::White
+    } else if matches!(c, '/' | '\\' | ',' | ':' | ';' | '|') {
+        CharClass::Delimiter
+    } else if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_alphabetic() {
+        CharClass::Letter
+    } else if c.is_numeric() {
+        CharClass::Number
+    } else {
+        CharClass::NonWord
+    }
+}
+
+/// Bonus for matching a character of class `class` following one of `prev`
+fn bonus_for(prev: CharClass, class: CharClass) -> i64 {
+    let is_word = !matches!(class, CharClass::White | CharClass::NonWord | CharClass::Delimiter);
+    if is_word {
+        match prev {
+            CharClass::White => return BONUS_BOUNDARY_WHITE,
+            CharClass::Delimiter => return BONUS_BOUNDARY_DELIMITER,
+            CharClass::NonWord => return BONUS_BOUNDARY,
+            _ => {}
+        }
+    }
+    match (prev, class) {
+        (CharClass::Lower, CharClass::Upper) => BONUS_CAMEL_123,
+        (prev, CharClass::Number) if prev != CharClass::Number => BONUS_CAMEL_123,
+        (_, CharClass::White) => BONUS_BOUNDARY_WHITE,
+        (_, CharClass::NonWord | CharClass::Delimiter) => BONUS_NON_WORD,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_positions_and_case() {
+        let m = fuzzy_match("mrs", "src/main.rs").unwrap();
+        assert_eq!(m.positions, vec![4, 9, 10]);
+
+        assert!(fuzzy_match("MAIN", "src/main.rs").is_none(), "uppercase makes the query case sensitive");
+        assert!(fuzzy_match("main", "src/Main.rs").is_some());
+        assert!(fuzzy_match("rsm", "src/main.rs").is_none());
+        assert!(fuzzy_match("src rs", "src/main.rs").is_some());
+        assert!(fuzzy_match("src xyz", "src/main.rs").is_none());
+        assert!(FuzzyQuery::new("  ").is_empty());
+    }
+
+    #[test]
+    fn test_scores_prefer_boundaries_and_runs() {
+        let score = |query, candidate| fuzzy_match(query, candidate).map(|m| m.score).unwrap_or(i64::MIN);
+
+        // Consecutive beats scattered
+        assert!(score("main", "src/main.rs") > score("main", "src/my_animation.rs"));
+        // Word starts beat the middle of words
+        assert!(score("fb", "src/foo_bar.rs") > score("fb", "src/sofabed.rs"));
+        // camelCase humps count as word starts
+        assert!(score("fb", "FooBar.ts") > score("fb", "Fabric.ts"));
+        // The shortest window wins over the first occurrence
+        let m = fuzzy_match("ab", "a_x_ab").unwrap();
+        assert_eq!(m.positions, vec![4, 5]);
+    }
+}
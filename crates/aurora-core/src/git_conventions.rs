@@ -0,0 +1,384 @@
+//! Commit message linting and branch name validation
+//!
+//! This module checks commit messages against the rules in the `[git]` section
+//! of `.AuroraHeart/config.toml` (conventional commit format, maximum subject
+//! length, required issue references) and validates new branch names against
+//! git's ref-name rules and an optional project pattern. Failures come with an
+//! auto-fix suggestion where one can be derived.
+
+use crate::config::GitConfig;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors that can occur while applying git conventions
+#[derive(Error, Debug)]
+pub enum GitConventionError {
+    /// A pattern in the configuration is not a valid regex
+    #[error("Invalid pattern '{0}': {1}")]
+    InvalidPattern(String, String),
+}
+
+/// Result type for git convention operations
+pub type Result<T> = std::result::Result<T, GitConventionError>;
+
+/// Result of validating a commit message or branch name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConventionReport {
+    /// True when no rule was violated
+    pub valid: bool,
+    /// Description of each violated rule
+    pub errors: Vec<String>,
+    /// Corrected value that would pass, when one can be derived
+    pub suggestion: Option<String>,
+}
+
+impl ConventionReport {
+    fn from_errors(errors: Vec<String>, suggestion: Option<String>) -> Self {
+        Self {
+            valid: errors.is_empty(),
+            errors,
+            suggestion,
+        }
+    }
+
+    /// Format the report as a helpful error message
+    pub fn format(&self) -> String {
+        let mut output = self
+            .errors
+            .iter()
+            .map(|e| format!("- {}", e))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(suggestion) = &self.suggestion {
+            output.push_str(&format!("\nSuggested: {}", suggestion));
+        }
+        output
+    }
+}
+
+/// Lint a commit message against the configured rules
+pub fn lint_commit_message(message: &str, config: &GitConfig) -> Result<ConventionReport> {
+    let subject = message.lines().next().unwrap_or("").trim();
+    let mut errors = Vec::new();
+
+    if subject.is_empty() {
+        errors.push("Commit subject must not be empty".to_string());
+        return Ok(ConventionReport::from_errors(errors, None));
+    }
+
+    if config.conventional_commits {
+        match parse_conventional(subject) {
+            Some(parsed) if !config.commit_types.iter().any(|t| t == parsed.commit_type) => {
+                errors.push(format!(
+                    "Unknown commit type '{}' (allowed: {})",
+                    parsed.commit_type,
+                    config.commit_types.join(", ")
+                ));
+            }
+            Some(_) => {}
+            None => errors.push(
+                "Subject must follow conventional commits: type(scope): description".to_string(),
+            ),
+        }
+    }
+
+    let subject_length = subject.chars().count();
+    if subject_length > config.max_subject_length {
+        errors.push(format!(
+            "Subject is {} characters (max {})",
+            subject_length, config.max_subject_length
+        ));
+    }
+
+    if let Some(second_line) = message.lines().nth(1) {
+        if !second_line.trim().is_empty() {
+            errors.push("Separate the subject from the body with a blank line".to_string());
+        }
+    }
+
+    if config.require_issue_reference {
+        let pattern = compile(&config.issue_reference_pattern)?;
+        if !pattern.is_match(message) {
+            errors.push("Commit message must reference an issue (e.g. #123 or PROJ-123)".to_string());
+        }
+    }
+
+    let suggestion = if errors.is_empty() {
+        None
+    } else {
+        suggest_subject(subject, config).filter(|s| s != subject)
+    };
+
+    Ok(ConventionReport::from_errors(errors, suggestion))
+}
+
+/// Validate a new branch name against git ref rules and the configured pattern
+pub fn validate_branch_name(name: &str, config: &GitConfig) -> Result<ConventionReport> {
+    let mut errors = Vec::new();
+
+    if name.is_empty() {
+        errors.push("Branch name must not be empty".to_string());
+        return Ok(ConventionReport::from_errors(errors, None));
+    }
+
+    if name.starts_with('-') || name.starts_with('/') || name.ends_with('/') {
+        errors.push("Branch name must not start with '-' or start/end with '/'".to_string());
+    }
+    if name.contains("..") || name.contains("//") || name.contains("@{") {
+        errors.push("Branch name must not contain '..', '//', or '@{'".to_string());
+    }
+    if name.ends_with(".lock") || name.ends_with('.') {
+        errors.push("Branch name must not end with '.lock' or '.'".to_string());
+    }
+    if name
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || "~^:?*[\\".contains(c))
+    {
+        errors.push("Branch name must not contain spaces or any of ~ ^ : ? * [ \\".to_string());
+    }
+
+    if let Some(pattern) = &config.branch_pattern {
+        if !compile(pattern)?.is_match(name) {
+            errors.push(format!("Branch name must match pattern {}", pattern));
+        }
+    }
+
+    let suggestion = if errors.is_empty() {
+        None
+    } else {
+        let sanitized = sanitize_branch_name(name);
+        let passes = match &config.branch_pattern {
+            Some(pattern) => compile(pattern)?.is_match(&sanitized),
+            None => true,
+        };
+        Some(sanitized).filter(|s| passes && !s.is_empty() && s != name)
+    };
+
+    Ok(ConventionReport::from_errors(errors, suggestion))
+}
+
+/// The parts of a conventional commit subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConventionalSubject<'a> {
+    /// Commit type, such as `feat` or `fix`
+    pub commit_type: &'a str,
+    /// Scope in parentheses after the type
+    pub scope: Option<&'a str>,
+    /// Marked as a breaking change with `!`
+    pub breaking: bool,
+    /// Summary of the change after the `: `
+    pub description: &'a str,
+}
+
+/// Split a conventional subject (`type(scope)!: description`) into its parts
+pub fn parse_conventional(subject: &str) -> Option<ConventionalSubject<'_>> {
+    let (header, description) = subject.split_once(": ")?;
+    if description.trim().is_empty() {
+        return None;
+    }
+
+    let breaking = header.ends_with('!');
+    let header = header.strip_suffix('!').unwrap_or(header);
+    let (commit_type, scope) = match header.split_once('(') {
+        Some((commit_type, scope)) => {
+            let scope = scope.strip_suffix(')')?;
+            if scope.is_empty() || scope.contains(['(', ')']) {
+                return None;
+            }
+            (commit_type, Some(scope))
+        }
+        None => (header, None),
+    };
+
+    if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    Some(ConventionalSubject {
+        commit_type,
+        scope,
+        breaking,
+        description: description.trim(),
+    })
+}
+
+/// Build a subject that satisfies the format and length rules
+fn suggest_subject(subject: &str, config: &GitConfig) -> Option<String> {
+    let mut suggestion = subject.trim_end_matches('.').to_string();
+
+    if config.conventional_commits && parse_conventional(&suggestion).is_none() {
+        let commit_type = infer_commit_type(&suggestion);
+        if !config.commit_types.iter().any(|t| t == commit_type) {
+            return None;
+        }
+
+        let mut chars = suggestion.chars();
+        let description = match chars.next() {
+            Some(first) => first.to_lowercase().chain(chars).collect::<String>(),
+            None => return None,
+        };
+        suggestion = format!("{}: {}", commit_type, description);
+    }
+
+    if suggestion.chars().count() > config.max_subject_length {
+        suggestion = suggestion
+            .chars()
+            .take(config.max_subject_length)
+            .collect::<String>();
+        if let Some(last_space) = suggestion.rfind(' ') {
+            suggestion.truncate(last_space);
+        }
+    }
+
+    Some(suggestion)
+}
+
+/// Guess a conventional commit type from the first word of a subject
+fn infer_commit_type(subject: &str) -> &'static str {
+    let first_word = subject
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+
+    match first_word.as_str() {
+        "fix" | "fixed" | "fixes" | "resolve" | "resolved" | "correct" => "fix",
+        "doc" | "docs" | "document" | "documented" => "docs",
+        "refactor" | "refactored" | "restructure" | "simplify" | "rename" | "renamed" => "refactor",
+        "test" | "tests" | "tested" => "test",
+        "speed" | "optimize" | "optimized" => "perf",
+        "revert" | "reverted" => "revert",
+        "format" | "formatted" => "style",
+        "bump" | "update" | "updated" | "upgrade" | "remove" | "removed" | "cleanup" => "chore",
+        _ => "feat",
+    }
+}
+
+/// Replace characters that are invalid in branch names
+fn sanitize_branch_name(name: &str) -> String {
+    let replaced: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c.is_control() || "~^:?*[\\@{".contains(c) {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let mut sanitized = String::with_capacity(replaced.len());
+    for c in replaced.chars() {
+        let previous = sanitized.chars().last();
+        if (c == '-' || c == '.' || c == '/') && previous == Some(c) {
+            continue;
+        }
+        sanitized.push(c);
+    }
+
+    let mut sanitized = sanitized
+        .trim_matches(|c| c == '-' || c == '/' || c == '.')
+        .to_string();
+    while let Some(stripped) = sanitized.strip_suffix(".lock") {
+        sanitized = stripped.to_string();
+    }
+    sanitized
+}
+
+fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern)
+        .map_err(|e| GitConventionError::InvalidPattern(pattern.to_string(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conventional() -> GitConfig {
+        GitConfig {
+            conventional_commits: true,
+            ..GitConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_default_config_accepts_plain_messages() {
+        let report = lint_commit_message("Add parser", &GitConfig::default()).unwrap();
+        assert!(report.valid);
+        assert!(report.suggestion.is_none());
+    }
+
+    #[test]
+    fn test_conventional_commit_validation() {
+        let config = conventional();
+        assert!(lint_commit_message("feat(parser): add tokens", &config).unwrap().valid);
+        assert!(lint_commit_message("fix!: drop legacy API", &config).unwrap().valid);
+
+        let report = lint_commit_message("Fixed the crash on startup.", &config).unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.suggestion.as_deref(), Some("fix: fixed the crash on startup"));
+
+        let report = lint_commit_message("wip: stuff", &config).unwrap();
+        assert!(report.errors[0].contains("Unknown commit type 'wip'"));
+    }
+
+    #[test]
+    fn test_subject_length_and_body_separation() {
+        let config = GitConfig {
+            max_subject_length: 20,
+            ..GitConfig::default()
+        };
+        let report = lint_commit_message("Add a very long subject line here\nbody", &config).unwrap();
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(report.suggestion.as_deref(), Some("Add a very long"));
+        assert!(report.format().contains("Suggested: Add a very long"));
+    }
+
+    #[test]
+    fn test_issue_reference_required() {
+        let config = GitConfig {
+            require_issue_reference: true,
+            ..GitConfig::default()
+        };
+        assert!(!lint_commit_message("Add parser", &config).unwrap().valid);
+        assert!(lint_commit_message("Add parser (#42)", &config).unwrap().valid);
+        assert!(lint_commit_message("Add parser\n\nRefs AUR-7", &config).unwrap().valid);
+    }
+
+    #[test]
+    fn test_branch_name_validation() {
+        let config = GitConfig::default();
+        assert!(validate_branch_name("feature/parser", &config).unwrap().valid);
+
+        let report = validate_branch_name("My New: Branch", &config).unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.suggestion.as_deref(), Some("my-new-branch"));
+
+        assert!(!validate_branch_name("bad..name", &config).unwrap().valid);
+        assert!(!validate_branch_name("topic.lock", &config).unwrap().valid);
+    }
+
+    #[test]
+    fn test_branch_pattern() {
+        let config = GitConfig {
+            branch_pattern: Some(r"^(feat|fix)/[a-z0-9-]+$".to_string()),
+            ..GitConfig::default()
+        };
+        assert!(validate_branch_name("feat/login", &config).unwrap().valid);
+
+        let report = validate_branch_name("Feat/Login Page", &config).unwrap();
+        assert_eq!(report.suggestion.as_deref(), Some("feat/login-page"));
+
+        let report = validate_branch_name("login", &config).unwrap();
+        assert!(!report.valid);
+        assert!(report.suggestion.is_none());
+
+        let bad = GitConfig {
+            branch_pattern: Some("(".to_string()),
+            ..GitConfig::default()
+        };
+        assert!(validate_branch_name("x", &bad).is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! Local file history
+//!
+//! Every saved or agent-modified file gets a timestamped snapshot under
+//! `.AuroraHeart/history/`, independent of git, so untracked files and
+//! uncommitted work can be recovered. Each file has its own directory (named
+//! after a hash of its project-relative path) holding an `index.json` and one
+//! snapshot per version; the oldest versions are pruned beyond the configured
+//! limit.
+
+use crate::config::{Config, HistoryConfig};
+use crate::paths::normalize_path;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors that can occur in file history operations
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    /// IO error while reading or writing history
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Malformed history index
+    #[error("Invalid history index: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The file is not inside the project
+    #[error("File is outside the project: {0}")]
+    OutsideProject(PathBuf),
+
+    /// No version with the given id exists for the file
+    #[error("No version {id} in the history of {path}")]
+    VersionNotFound {
+        /// Project-relative path of the file
+        path: String,
+        /// Requested version id
+        id: String,
+    },
+}
+
+/// Result type for file history operations
+pub type Result<T> = std::result::Result<T, HistoryError>;
+
+/// What produced a version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySource {
+    /// Saved from the editor
+    Save,
+    /// Written by an agent tool
+    Agent,
+    /// Content found on disk before an edit or restore replaced it
+    External,
+    /// Restored from an earlier version
+    Restore,
+}
+
+/// One recorded version of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Version id (unique per file)
+    pub id: String,
+    /// When the version was recorded, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// What produced the version
+    pub source: HistorySource,
+    /// Content size in bytes
+    pub size: usize,
+    /// SHA-256 of the content, used to skip recording unchanged content
+    pub hash: String,
+}
+
+/// Per-file history index
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    /// Project-relative path with `/` separators
+    path: String,
+    /// Versions, oldest first
+    entries: Vec<HistoryEntry>,
+}
+
+fn content_hash(content: &str) -> String {
+    digest(&SHA256, content.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Timestamped local history of a project's files
+#[derive(Debug, Clone)]
+pub struct FileHistory {
+    project_root: PathBuf,
+    dir: PathBuf,
+    config: HistoryConfig,
+}
+
+impl FileHistory {
+    /// Create a history store for the project at `project_root` with default limits
+    pub fn new<P: AsRef<Path>>(project_root: P) -> Self {
+        Self::with_config(project_root, HistoryConfig::default())
+    }
+
+    /// Create a history store using the project's `[history]` settings
+    pub fn for_project<P: AsRef<Path>>(project_root: P, config: &Config) -> Self {
+        Self::with_config(project_root, config.history.clone())
+    }
+
+    fn with_config<P: AsRef<Path>>(project_root: P, config: HistoryConfig) -> Self {
+        let project_root = project_root.as_ref().to_path_buf();
+        Self {
+            dir: project_root.join(".AuroraHeart").join("history"),
+            project_root,
+            config,
+        }
+    }
+
+    /// Project-relative path with `/` separators
+    fn relative_path(&self, path: &Path) -> Result<String> {
+        let normalized = normalize_path(path);
+        let relative = if normalized.is_relative() {
+            normalized
+        } else if let Ok(relative) = normalized.strip_prefix(normalize_path(&self.project_root)) {
+            relative.to_path_buf()
+        } else {
+            // Fall back to resolved paths, e.g. when the root is a symlink
+            let root = std::fs::canonicalize(&self.project_root)?;
+            let resolved = match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => std::fs::canonicalize(parent)
+                    .map(|parent| parent.join(name))
+                    .unwrap_or_else(|_| path.to_path_buf()),
+                _ => path.to_path_buf(),
+            };
+            resolved
+                .strip_prefix(&root)
+                .map(Path::to_path_buf)
+                .map_err(|_| HistoryError::OutsideProject(path.to_path_buf()))?
+        };
+
+        let parts: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if parts.is_empty() || parts.iter().any(|p| p == "..") {
+            return Err(HistoryError::OutsideProject(path.to_path_buf()));
+        }
+        Ok(parts.join("/"))
+    }
+
+    fn file_dir(&self, relative: &str) -> PathBuf {
+        let key: String = content_hash(relative).chars().take(16).collect();
+        self.dir.join(key)
+    }
+
+    fn load_index(&self, relative: &str) -> Result<HistoryIndex> {
+        let path = self.file_dir(relative).join("index.json");
+        if !path.exists() {
+            return Ok(HistoryIndex {
+                path: relative.to_string(),
+                entries: Vec::new(),
+            });
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    fn save_index(&self, index: &HistoryIndex) -> Result<()> {
+        let dir = self.file_dir(&index.path);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("index.json"), serde_json::to_string_pretty(index)?)?;
+        Ok(())
+    }
+
+    /// Record a version of `path` with the given content
+    ///
+    /// Returns `None` when the content matches the latest version or exceeds
+    /// the size limit. Files inside `.AuroraHeart` are never recorded.
+    pub fn record(
+        &self,
+        path: &Path,
+        content: &str,
+        source: HistorySource,
+    ) -> Result<Option<HistoryEntry>> {
+        let relative = self.relative_path(path)?;
+        if relative.starts_with(".AuroraHeart/") || content.len() as u64 > self.config.max_file_size
+        {
+            return Ok(None);
+        }
+
+        let mut index = self.load_index(&relative)?;
+        let hash = content_hash(content);
+        if index
+            .entries
+            .last()
+            .is_some_and(|latest| latest.hash == hash)
+        {
+            return Ok(None);
+        }
+
+        // Keep ids unique and increasing even within the same millisecond
+        let timestamp = match index.entries.last() {
+            Some(latest) => now_millis().max(latest.timestamp + 1),
+            None => now_millis(),
+        };
+        let entry = HistoryEntry {
+            id: timestamp.to_string(),
+            timestamp,
+            source,
+            size: content.len(),
+            hash,
+        };
+
+        let dir = self.file_dir(&relative);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(&entry.id), content)?;
+        index.entries.push(entry.clone());
+
+        let excess = index
+            .entries
+            .len()
+            .saturating_sub(self.config.max_versions.max(1));
+        for pruned in index.entries.drain(..excess) {
+            let _ = std::fs::remove_file(dir.join(&pruned.id));
+        }
+        self.save_index(&index)?;
+        Ok(Some(entry))
+    }
+
+    /// Record the current on-disk content of `path`, if it exists and is text
+    ///
+    /// Used before a file is overwritten or deleted so content that was never
+    /// saved through the IDE can still be recovered.
+    pub fn record_existing(
+        &self,
+        path: &Path,
+        source: HistorySource,
+    ) -> Result<Option<HistoryEntry>> {
+        let absolute = self.project_root.join(path);
+        match std::fs::metadata(&absolute) {
+            Ok(metadata) if metadata.is_file() && metadata.len() <= self.config.max_file_size => {}
+            _ => return Ok(None),
+        }
+        match std::fs::read_to_string(&absolute) {
+            Ok(content) => self.record(path, &content, source),
+            // Binary files are not tracked
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Recorded versions of `path`, newest first
+    pub fn versions(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let relative = self.relative_path(path)?;
+        let mut entries = self.load_index(&relative)?.entries;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Content of one recorded version of `path`
+    pub fn read_version(&self, path: &Path, id: &str) -> Result<String> {
+        let relative = self.relative_path(path)?;
+        let index = self.load_index(&relative)?;
+        if !index.entries.iter().any(|entry| entry.id == id) {
+            return Err(HistoryError::VersionNotFound {
+                path: relative,
+                id: id.to_string(),
+            });
+        }
+        Ok(std::fs::read_to_string(self.file_dir(&relative).join(id))?)
+    }
+
+    /// Restore `path` to a recorded version and return the restored content
+    ///
+    /// The content being replaced is recorded first, so a restore can itself
+    /// be undone. Deleted files are recreated.
+    pub fn restore(&self, path: &Path, id: &str) -> Result<String> {
+        let content = self.read_version(path, id)?;
+        let absolute = self.project_root.join(path);
+
+        self.record_existing(path, HistorySource::External)?;
+        if let Some(parent) = absolute.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&absolute, &content)?;
+        self.record(path, &content, HistorySource::Restore)?;
+        Ok(content)
+    }
+
+    /// Project-relative paths of all files with recorded history, sorted
+    ///
+    /// Includes files that have since been deleted.
+    pub fn files(&self) -> Result<Vec<String>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let index_path = entry?.path().join("index.json");
+            if index_path.is_file() {
+                let index: HistoryIndex =
+                    serde_json::from_str(&std::fs::read_to_string(index_path)?)?;
+                files.push(index.path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_dedupes_and_prunes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.history.max_versions = 3;
+        let history = FileHistory::for_project(temp_dir.path(), &config);
+        let file = temp_dir.path().join("notes.txt");
+
+        assert!(history
+            .record(&file, "one", HistorySource::Save)
+            .unwrap()
+            .is_some());
+        assert!(history
+            .record(&file, "one", HistorySource::Save)
+            .unwrap()
+            .is_none());
+        for content in ["two", "three", "four"] {
+            history
+                .record(&file, content, HistorySource::Agent)
+                .unwrap();
+        }
+
+        let versions = history.versions(&file).unwrap();
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[0].source, HistorySource::Agent);
+        assert!(versions[0].timestamp > versions[1].timestamp);
+        assert_eq!(
+            history.read_version(&file, &versions[0].id).unwrap(),
+            "four"
+        );
+        assert_eq!(history.read_version(&file, &versions[2].id).unwrap(), "two");
+        assert_eq!(history.files().unwrap(), vec!["notes.txt".to_string()]);
+
+        // Relative and absolute paths refer to the same history
+        assert_eq!(history.versions(Path::new("notes.txt")).unwrap(), versions);
+        assert!(matches!(
+            history.record(Path::new("/elsewhere/file.txt"), "x", HistorySource::Save),
+            Err(HistoryError::OutsideProject(_))
+        ));
+        assert!(history
+            .record(
+                &temp_dir.path().join(".AuroraHeart/config.toml"),
+                "x",
+                HistorySource::Save
+            )
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_restore_deleted_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = FileHistory::new(temp_dir.path());
+        let file = temp_dir.path().join("scratch/draft.md");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+
+        std::fs::write(&file, "first draft").unwrap();
+        history
+            .record_existing(&file, HistorySource::External)
+            .unwrap();
+        std::fs::write(&file, "second draft").unwrap();
+        history
+            .record(&file, "second draft", HistorySource::Save)
+            .unwrap();
+        std::fs::remove_dir_all(temp_dir.path().join("scratch")).unwrap();
+
+        let versions = history.versions(&file).unwrap();
+        let first = versions
+            .iter()
+            .find(|v| v.source == HistorySource::External)
+            .unwrap();
+        assert_eq!(history.restore(&file, &first.id).unwrap(), "first draft");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "first draft");
+
+        let versions = history.versions(&file).unwrap();
+        assert_eq!(versions[0].source, HistorySource::Restore);
+        assert!(matches!(
+            history.read_version(&file, "42"),
+            Err(HistoryError::VersionNotFound { .. })
+        ));
+    }
+}
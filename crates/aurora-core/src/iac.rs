@@ -0,0 +1,330 @@
+//! Infrastructure-as-code plans
+//!
+//! Terraform and Pulumi describe what a deployment would change before
+//! changing anything: `terraform show -json` of a saved plan lists its
+//! `resource_changes` with their actions, and `pulumi preview --json` lists
+//! `steps` with their operations. Both are reduced to a [`PlanSummary`] that
+//! counts additions, changes, and destructions the way Terraform's own
+//! "Plan: N to add" line does, with a replacement counted as both an
+//! addition and a destruction.
+//!
+//! Saved Terraform plans are kept in `.AuroraHeart/iac/`, one per
+//! configuration directory, so that what is applied is exactly what was
+//! planned and reviewed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors in reading infrastructure plans
+#[derive(Error, Debug)]
+pub enum IacError {
+    /// The plan output is not JSON
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The plan output is JSON of another shape
+    #[error("Unrecognized plan output: {0}")]
+    InvalidPlan(String),
+
+    /// An unknown engine name
+    #[error("Unknown IaC engine '{0}'; expected 'terraform' or 'pulumi'")]
+    UnknownEngine(String),
+}
+
+pub type Result<T> = std::result::Result<T, IacError>;
+
+/// Infrastructure-as-code tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IacEngine {
+    Terraform,
+    Pulumi,
+}
+
+impl IacEngine {
+    /// The engine's executable
+    pub fn program(&self) -> &'static str {
+        match self {
+            IacEngine::Terraform => "terraform",
+            IacEngine::Pulumi => "pulumi",
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            IacEngine::Terraform => "Terraform",
+            IacEngine::Pulumi => "Pulumi",
+        }
+    }
+
+    /// The engine of the configuration in `dir`: Pulumi for a `Pulumi.yaml`,
+    /// Terraform for `.tf` files
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("Pulumi.yaml").exists() || dir.join("Pulumi.yml").exists() {
+            return Some(IacEngine::Pulumi);
+        }
+        let has_tf = std::fs::read_dir(dir).ok()?.flatten().any(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.ends_with(".tf") || name.ends_with(".tf.json")
+        });
+        has_tf.then_some(IacEngine::Terraform)
+    }
+}
+
+impl FromStr for IacEngine {
+    type Err = IacError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "terraform" => Ok(IacEngine::Terraform),
+            "pulumi" => Ok(IacEngine::Pulumi),
+            _ => Err(IacError::UnknownEngine(s.to_string())),
+        }
+    }
+}
+
+/// What a plan does to a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+    /// Destroy and recreate
+    Replace,
+}
+
+impl ChangeAction {
+    /// Terraform's marker for the action
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ChangeAction::Create => "+",
+            ChangeAction::Update => "~",
+            ChangeAction::Delete => "-",
+            ChangeAction::Replace => "-/+",
+        }
+    }
+}
+
+impl fmt::Display for ChangeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeAction::Create => write!(f, "create"),
+            ChangeAction::Update => write!(f, "update"),
+            ChangeAction::Delete => write!(f, "destroy"),
+            ChangeAction::Replace => write!(f, "replace"),
+        }
+    }
+}
+
+/// A resource a plan changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourceChange {
+    /// Terraform resource address or Pulumi `type::name`
+    pub address: String,
+    pub action: ChangeAction,
+}
+
+/// The changes a plan would make
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PlanSummary {
+    pub changes: Vec<ResourceChange>,
+}
+
+impl PlanSummary {
+    fn count(&self, actions: &[ChangeAction]) -> usize {
+        self.changes.iter().filter(|change| actions.contains(&change.action)).count()
+    }
+
+    /// Resources created, including replacements
+    pub fn to_add(&self) -> usize {
+        self.count(&[ChangeAction::Create, ChangeAction::Replace])
+    }
+
+    /// Resources updated in place
+    pub fn to_change(&self) -> usize {
+        self.count(&[ChangeAction::Update])
+    }
+
+    /// Resources destroyed, including replacements
+    pub fn to_destroy(&self) -> usize {
+        self.count(&[ChangeAction::Delete, ChangeAction::Replace])
+    }
+
+    /// Whether the plan changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The counts line followed by one line per changed resource
+    pub fn format(&self) -> String {
+        if self.is_empty() {
+            return "No changes. The infrastructure matches the configuration.\n".to_string();
+        }
+        let mut text = format!(
+            "Plan: {} to add, {} to change, {} to destroy.\n",
+            self.to_add(),
+            self.to_change(),
+            self.to_destroy()
+        );
+        for change in &self.changes {
+            text.push_str(&format!("  {} {} ({})\n", change.action.symbol(), change.address, change.action));
+        }
+        text
+    }
+}
+
+/// Summarize the output of `terraform show -json <planfile>`
+pub fn parse_terraform_plan(json: &str) -> Result<PlanSummary> {
+    let plan: Value = serde_json::from_str(json)?;
+    if plan["format_version"].is_null() {
+        return Err(IacError::InvalidPlan("missing format_version".to_string()));
+    }
+    let mut summary = PlanSummary::default();
+    for resource in plan["resource_changes"].as_array().into_iter().flatten() {
+        let actions: Vec<&str> = resource["change"]["actions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let action = match actions.as_slice() {
+            ["create"] => ChangeAction::Create,
+            ["update"] => ChangeAction::Update,
+            ["delete"] => ChangeAction::Delete,
+            ["delete", "create"] | ["create", "delete"] => ChangeAction::Replace,
+            // no-op, read, and forget leave the infrastructure alone
+            _ => continue,
+        };
+        let address = resource["address"]
+            .as_str()
+            .ok_or_else(|| IacError::InvalidPlan("resource change without an address".to_string()))?;
+        summary.changes.push(ResourceChange {
+            address: address.to_string(),
+            action,
+        });
+    }
+    Ok(summary)
+}
+
+/// Summarize the output of `pulumi preview --json`
+pub fn parse_pulumi_preview(json: &str) -> Result<PlanSummary> {
+    let preview: Value = serde_json::from_str(json)?;
+    let steps = preview["steps"]
+        .as_array()
+        .ok_or_else(|| IacError::InvalidPlan("missing steps".to_string()))?;
+    let mut summary = PlanSummary::default();
+    for step in steps {
+        let action = match step["op"].as_str() {
+            Some("create" | "import") => ChangeAction::Create,
+            Some("update") => ChangeAction::Update,
+            Some("delete") => ChangeAction::Delete,
+            Some("replace") => ChangeAction::Replace,
+            // same, read, refresh, and the halves of a replacement
+            _ => continue,
+        };
+        // urn:pulumi:<stack>::<project>::<type>::<name>
+        let urn = step["urn"].as_str().unwrap_or_default();
+        let address = urn.splitn(3, "::").nth(2).unwrap_or(urn);
+        summary.changes.push(ResourceChange {
+            address: address.to_string(),
+            action,
+        });
+    }
+    Ok(summary)
+}
+
+/// Where the saved Terraform plan for the configuration in `dir` is kept
+pub fn plan_file(project_root: &Path, dir: &Path) -> PathBuf {
+    let relative = dir.strip_prefix(project_root).unwrap_or(dir);
+    let name: Vec<String> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    let name = if name.is_empty() { "root".to_string() } else { name.join("_") };
+    project_root.join(".AuroraHeart").join("iac").join(format!("{}.tfplan", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terraform_plan() {
+        let summary = parse_terraform_plan(
+            r#"{
+                "format_version": "1.2",
+                "resource_changes": [
+                    { "address": "aws_s3_bucket.logs", "change": { "actions": ["create"] } },
+                    { "address": "aws_instance.web", "change": { "actions": ["delete", "create"] } },
+                    { "address": "aws_iam_role.ci", "change": { "actions": ["update"] } },
+                    { "address": "aws_sqs_queue.old", "change": { "actions": ["delete"] } },
+                    { "address": "data.aws_ami.ubuntu", "change": { "actions": ["read"] } },
+                    { "address": "aws_vpc.main", "change": { "actions": ["no-op"] } }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!((summary.to_add(), summary.to_change(), summary.to_destroy()), (2, 1, 2));
+        assert_eq!(
+            summary.format(),
+            "Plan: 2 to add, 1 to change, 2 to destroy.\n  + aws_s3_bucket.logs (create)\n  -/+ aws_instance.web (replace)\n  ~ aws_iam_role.ci (update)\n  - aws_sqs_queue.old (destroy)\n"
+        );
+        assert!(parse_terraform_plan(r#"{ "format_version": "1.2" }"#).unwrap().is_empty());
+        assert!(parse_terraform_plan("{}").is_err());
+    }
+
+    #[test]
+    fn test_pulumi_preview() {
+        let summary = parse_pulumi_preview(
+            r#"{
+                "steps": [
+                    { "op": "same", "urn": "urn:pulumi:dev::site::pulumi:pulumi:Stack::site-dev" },
+                    { "op": "create", "urn": "urn:pulumi:dev::site::aws:s3/bucket:Bucket::assets" },
+                    { "op": "replace", "urn": "urn:pulumi:dev::site::aws:ec2/instance:Instance::web" }
+                ],
+                "changeSummary": { "create": 1, "replace": 1, "same": 1 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            summary.changes,
+            vec![
+                ResourceChange {
+                    address: "aws:s3/bucket:Bucket::assets".to_string(),
+                    action: ChangeAction::Create,
+                },
+                ResourceChange {
+                    address: "aws:ec2/instance:Instance::web".to_string(),
+                    action: ChangeAction::Replace,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_engine_and_plan_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("infra/prod")).unwrap();
+        std::fs::write(root.join("infra/prod/main.tf"), "").unwrap();
+        std::fs::write(root.join("Pulumi.yaml"), "name: site\n").unwrap();
+
+        assert_eq!(IacEngine::detect(&root.join("infra/prod")), Some(IacEngine::Terraform));
+        assert_eq!(IacEngine::detect(root), Some(IacEngine::Pulumi));
+        assert_eq!(IacEngine::detect(&root.join("infra")), None);
+        assert_eq!(
+            plan_file(root, &root.join("infra/prod")),
+            root.join(".AuroraHeart/iac/infra_prod.tfplan")
+        );
+        assert_eq!(plan_file(root, root), root.join(".AuroraHeart/iac/root.tfplan"));
+    }
+}
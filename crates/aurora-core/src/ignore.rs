@@ -0,0 +1,241 @@
+//! Ignore rules for the agent's view of the project
+//!
+//! Generated code, fixtures, and secrets directories often should not reach
+//! the model. Patterns from the `[project] ignore` config list and the
+//! project's `.auroraignore` file hide matching paths from the search and
+//! listing tools, the repository map, and the file tree.
+//!
+//! The file uses a subset of gitignore syntax, one pattern per line:
+//! - blank lines and lines starting with `#` are skipped
+//! - a leading `!` re-includes paths matched by an earlier pattern
+//! - a trailing `/` matches directories only
+//! - a pattern containing `/` elsewhere is anchored to the project root;
+//!   otherwise it matches a file or directory name at any depth
+//! - `*`, `?`, `[...]` do not match `/`, while `**` matches any number of
+//!   directories
+//!
+//! Everything under an ignored directory is ignored, as with git.
+
+use crate::config::Config;
+use crate::paths::normalize_path;
+use glob::{MatchOptions, Pattern};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the ignore file in the project root
+pub const IGNORE_FILE: &str = ".auroraignore";
+
+/// Errors that can occur while loading ignore rules
+#[derive(Error, Debug)]
+pub enum IgnoreError {
+    /// A pattern is not a valid glob
+    #[error("Invalid ignore pattern '{0}': {1}")]
+    InvalidPattern(String, String),
+
+    /// IO error while reading the ignore file
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result type for ignore operations
+pub type Result<T> = std::result::Result<T, IgnoreError>;
+
+/// One parsed ignore pattern
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Pattern,
+    /// Re-include instead of ignore (`!pattern`)
+    negated: bool,
+    /// Match directories only (`pattern/`)
+    dir_only: bool,
+    /// Match the whole relative path rather than a single name
+    anchored: bool,
+}
+
+/// Compiled ignore rules for one project
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl IgnoreRules {
+    /// Compile `patterns` for paths under `root`
+    pub fn new<S: AsRef<str>>(root: impl Into<PathBuf>, patterns: impl IntoIterator<Item = S>) -> Result<Self> {
+        let mut rules = Vec::new();
+        for line in patterns {
+            if let Some(rule) = parse_rule(line.as_ref())? {
+                rules.push(rule);
+            }
+        }
+        Ok(Self {
+            root: normalize_path(root.into()),
+            rules,
+        })
+    }
+
+    /// Load the configured patterns followed by the project's `.auroraignore`
+    pub fn load(project_root: impl AsRef<Path>, config: &Config) -> Result<Self> {
+        let project_root = project_root.as_ref();
+        let mut patterns = config.project.ignore.clone();
+
+        let ignore_file = project_root.join(IGNORE_FILE);
+        if ignore_file.is_file() {
+            let contents = std::fs::read_to_string(&ignore_file)?;
+            patterns.extend(contents.lines().map(str::to_string));
+        }
+
+        Self::new(project_root, patterns)
+    }
+
+    /// Whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path` is hidden
+    ///
+    /// Relative paths are taken relative to the project root; absolute paths
+    /// outside the root are never ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let path = normalize_path(path);
+        let relative = if path.is_absolute() {
+            match path.strip_prefix(&self.root) {
+                Ok(relative) => relative,
+                Err(_) => return false,
+            }
+        } else {
+            &path
+        };
+
+        let names: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        // A path is hidden when it or any directory above it is ignored
+        let mut prefix = String::new();
+        for (index, name) in names.iter().enumerate() {
+            if index > 0 {
+                prefix.push('/');
+            }
+            prefix.push_str(name);
+            let entry_is_dir = index + 1 < names.len() || is_dir;
+            if self.matches(&prefix, name, entry_is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether the last rule matching one entry ignores it
+    fn matches(&self, relative: &str, name: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let candidate = if rule.anchored { relative } else { name };
+            if rule.pattern.matches_with(candidate, MATCH_OPTIONS) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Parse one line of ignore syntax, or `None` for blanks and comments
+fn parse_rule(line: &str) -> Result<Option<IgnoreRule>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let line = line.strip_prefix('/').unwrap_or(line);
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let pattern =
+        Pattern::new(line).map_err(|e| IgnoreError::InvalidPattern(line.to_string(), e.to_string()))?;
+    Ok(Some(IgnoreRule {
+        pattern,
+        negated,
+        dir_only,
+        anchored,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rules(patterns: &[&str]) -> IgnoreRules {
+        IgnoreRules::new("/project", patterns).unwrap()
+    }
+
+    #[test]
+    fn test_name_and_anchored_patterns() {
+        let rules = rules(&["# generated", "", "*.snap", "secrets/", "/build", "src/gen/**/*.rs"]);
+
+        assert!(rules.is_ignored(Path::new("tests/output.snap"), false));
+        assert!(rules.is_ignored(Path::new("config/secrets"), true));
+        assert!(rules.is_ignored(Path::new("config/secrets/key.pem"), false));
+        assert!(!rules.is_ignored(Path::new("config/secrets"), false), "secrets/ matches directories only");
+        assert!(rules.is_ignored(Path::new("build/out.txt"), false));
+        assert!(!rules.is_ignored(Path::new("tools/build"), true), "/build is anchored");
+        assert!(rules.is_ignored(Path::new("src/gen/api/types.rs"), false));
+        assert!(!rules.is_ignored(Path::new("src/main.rs"), false));
+    }
+
+    #[test]
+    fn test_negation_and_absolute_paths() {
+        let rules = rules(&["fixtures/*", "!fixtures/keep.json"]);
+
+        assert!(rules.is_ignored(Path::new("/project/fixtures/big.json"), false));
+        assert!(!rules.is_ignored(Path::new("/project/fixtures/keep.json"), false));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/fixtures/big.json"), false));
+        assert!(rules.is_ignored(Path::new("fixtures/big.json"), false));
+    }
+
+    #[test]
+    fn test_load_combines_config_and_file() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join(IGNORE_FILE), "*.log\n!keep.log\n").unwrap();
+        let mut config = Config::default();
+        config.project.ignore = vec!["vendor/".to_string(), "keep.log".to_string()];
+
+        let rules = IgnoreRules::load(temp.path(), &config).unwrap();
+        assert!(rules.is_ignored(&temp.path().join("vendor"), true));
+        assert!(rules.is_ignored(&temp.path().join("debug.log"), false));
+        assert!(!rules.is_ignored(&temp.path().join("keep.log"), false), "the file is read last");
+
+        assert!(matches!(
+            IgnoreRules::new("/project", ["[unclosed"]),
+            Err(IgnoreError::InvalidPattern(..))
+        ));
+        assert!(IgnoreRules::load(temp.path().join("missing"), &Config::default()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+//! JSON Schema validation
+//!
+//! Validates JSON values, including YAML and TOML files read into them,
+//! against a JSON Schema with the [`jsonschema`] crate. The schema's draft
+//! is taken from its `$schema`, defaulting to 2020-12; `format` is checked,
+//! `pattern` and `patternProperties` use ECMA-262 regex semantics, and
+//! `$ref` resolves within the schema. References to other documents are
+//! not fetched: a schema using them is reported as invalid.
+//!
+//! Every error carries the JSON pointer of the offending value so callers
+//! can map it back to a line of the file.
+
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::ValidationError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// How a value breaks its schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaErrorKind {
+    /// A required property is missing
+    Missing,
+    /// The value has the wrong type
+    Type,
+    /// The value is not one of the allowed values (`enum` or `const`)
+    Enum,
+    /// A number, length, or count is outside its bounds
+    Range,
+    /// The schema does not allow the property or value at all
+    Unexpected,
+    /// A string does not match its `pattern`
+    Pattern,
+    /// A string is not in its `format`, such as `date-time` or `email`
+    Format,
+    /// An array repeats an item that must be unique
+    Duplicate,
+    /// The value matches none, or several, of the `anyOf`/`oneOf` forms, or its `not` form
+    Form,
+    /// The schema itself is invalid, or a `$ref` in it cannot be resolved
+    Reference,
+}
+
+/// A value that does not satisfy the schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaError {
+    /// JSON pointer of the value, empty for the document itself
+    pub pointer: String,
+    /// How the value breaks the schema
+    pub kind: SchemaErrorKind,
+    /// Human-readable explanation, without the pointer
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "/" } else { &self.pointer };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Validate `instance` against `schema`
+///
+/// Errors are ordered by pointer. A schema that cannot be compiled yields a
+/// single [`SchemaErrorKind::Reference`] error for the document.
+pub fn validate(instance: &Value, schema: &Value) -> Vec<SchemaError> {
+    let validator = match jsonschema::options().should_validate_formats(true).build(schema) {
+        Ok(validator) => validator,
+        Err(e) => {
+            let message = match e.kind() {
+                ValidationErrorKind::Referencing(e) => format!("cannot resolve $ref: {}", e),
+                _ => format!("invalid schema: {}", e),
+            };
+            return vec![SchemaError {
+                pointer: String::new(),
+                kind: SchemaErrorKind::Reference,
+                message,
+            }];
+        }
+    };
+    let mut errors = Vec::new();
+    for error in validator.iter_errors(instance) {
+        convert(&error, schema, &mut errors);
+    }
+    errors.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+    errors
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn child(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, crate::yaml::escape_pointer(key))
+}
+
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > 40 {
+        format!("{}...", text.chars().take(40).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// Add the errors `error`, found validating against `schema`, stands for to
+/// `errors`
+fn convert(error: &ValidationError, schema: &Value, errors: &mut Vec<SchemaError>) {
+    let pointer = error.instance_path().as_str();
+    let instance = error.instance().as_ref();
+    let mut push = |kind, message: String| {
+        errors.push(SchemaError {
+            pointer: pointer.to_string(),
+            kind,
+            message,
+        })
+    };
+
+    match error.kind() {
+        ValidationErrorKind::Required { property } => errors.push(SchemaError {
+            pointer: child(pointer, property.as_str().unwrap_or_default()),
+            kind: SchemaErrorKind::Missing,
+            message: "required property is missing".to_string(),
+        }),
+        ValidationErrorKind::AdditionalProperties { unexpected } | ValidationErrorKind::UnevaluatedProperties { unexpected } => {
+            for key in unexpected {
+                errors.push(SchemaError {
+                    pointer: child(pointer, key),
+                    kind: SchemaErrorKind::Unexpected,
+                    message: format!("unknown property '{}'", key),
+                });
+            }
+        }
+        ValidationErrorKind::Type { kind } => {
+            let types: Vec<&str> = match kind {
+                TypeKind::Single(expected) => vec![expected.as_str()],
+                TypeKind::Multiple(expected) => expected.iter().map(|expected| expected.as_str()).collect(),
+            };
+            push(SchemaErrorKind::Type, format!("expected {}, found {}", types.join(" or "), type_name(instance)))
+        }
+        ValidationErrorKind::Enum { options } => {
+            let allowed: Vec<String> = options.as_array().into_iter().flatten().map(short).collect();
+            push(SchemaErrorKind::Enum, format!("{} is not one of {}", short(instance), allowed.join(", ")))
+        }
+        ValidationErrorKind::Constant { expected_value } => {
+            push(SchemaErrorKind::Enum, format!("must be {}", short(expected_value)))
+        }
+        ValidationErrorKind::Minimum { limit } => {
+            push(SchemaErrorKind::Range, format!("{} is below the minimum of {}", instance, limit))
+        }
+        ValidationErrorKind::Maximum { limit } => {
+            push(SchemaErrorKind::Range, format!("{} is above the maximum of {}", instance, limit))
+        }
+        ValidationErrorKind::ExclusiveMinimum { limit } => {
+            push(SchemaErrorKind::Range, format!("{} must be greater than {}", instance, limit))
+        }
+        ValidationErrorKind::ExclusiveMaximum { limit } => {
+            push(SchemaErrorKind::Range, format!("{} must be less than {}", instance, limit))
+        }
+        ValidationErrorKind::MultipleOf { multiple_of } => {
+            push(SchemaErrorKind::Range, format!("{} is not a multiple of {}", instance, multiple_of))
+        }
+        ValidationErrorKind::MinLength { limit } => {
+            push(SchemaErrorKind::Range, format!("must be at least {} characters", limit))
+        }
+        ValidationErrorKind::MaxLength { limit } => {
+            push(SchemaErrorKind::Range, format!("must be at most {} characters", limit))
+        }
+        ValidationErrorKind::MinItems { limit } => push(SchemaErrorKind::Range, format!("must have at least {} items", limit)),
+        ValidationErrorKind::MaxItems { limit } => push(SchemaErrorKind::Range, format!("must have at most {} items", limit)),
+        ValidationErrorKind::MinProperties { limit } => {
+            push(SchemaErrorKind::Range, format!("must have at least {} properties", limit))
+        }
+        ValidationErrorKind::MaxProperties { limit } => {
+            push(SchemaErrorKind::Range, format!("must have at most {} properties", limit))
+        }
+        ValidationErrorKind::UniqueItems => {
+            let items = instance.as_array().map(Vec::as_slice).unwrap_or_default();
+            let duplicate = (1..items.len()).find(|&i| items[..i].contains(&items[i]));
+            errors.push(SchemaError {
+                pointer: duplicate.map_or_else(|| pointer.to_string(), |i| child(pointer, &i.to_string())),
+                kind: SchemaErrorKind::Duplicate,
+                message: "duplicates an earlier item, but items must be unique".to_string(),
+            })
+        }
+        ValidationErrorKind::Pattern { pattern } => {
+            // Show the pattern as the schema spells it, not as translated
+            let pattern = schema
+                .pointer(error.schema_path().as_str())
+                .and_then(Value::as_str)
+                .unwrap_or(pattern);
+            push(
+                SchemaErrorKind::Pattern,
+                format!("{} does not match the pattern {}", short(instance), pattern),
+            )
+        }
+        ValidationErrorKind::Format { format } => {
+            push(SchemaErrorKind::Format, format!("{} is not a valid {}", short(instance), format))
+        }
+        ValidationErrorKind::AnyOf { context } | ValidationErrorKind::OneOfNotValid { context } => {
+            let results = context
+                .iter()
+                .map(|branch| {
+                    let mut branch_errors = Vec::new();
+                    for error in branch {
+                        convert(error, schema, &mut branch_errors);
+                    }
+                    branch_errors
+                })
+                .collect();
+            errors.extend(closest_failure(results, pointer, "does not match any of the allowed forms"))
+        }
+        ValidationErrorKind::OneOfMultipleValid { context } => {
+            let matches = context.iter().filter(|branch| branch.is_empty()).count();
+            push(
+                SchemaErrorKind::Form,
+                format!("matches {} of the allowed forms, but must match exactly one", matches),
+            )
+        }
+        ValidationErrorKind::Not { .. } => push(SchemaErrorKind::Form, "matches a form that is not allowed".to_string()),
+        ValidationErrorKind::FalseSchema
+        | ValidationErrorKind::AdditionalItems { .. }
+        | ValidationErrorKind::UnevaluatedItems { .. } => push(SchemaErrorKind::Unexpected, "is not allowed".to_string()),
+        ValidationErrorKind::Referencing(e) => push(SchemaErrorKind::Reference, format!("cannot resolve $ref: {}", e)),
+        ValidationErrorKind::PropertyNames { error } => push(SchemaErrorKind::Unexpected, format!("invalid property name: {}", error)),
+        ValidationErrorKind::BacktrackLimitExceeded { .. } => push(SchemaErrorKind::Pattern, error.to_string()),
+        _ => push(SchemaErrorKind::Form, error.to_string()),
+    }
+}
+
+/// The errors to report when no alternative matched: those of the
+/// alternative that got furthest into the value, or a summary when none
+/// got past the value itself
+fn closest_failure(results: Vec<Vec<SchemaError>>, pointer: &str, summary: &str) -> Vec<SchemaError> {
+    let deepest = |errors: &Vec<SchemaError>| errors.iter().map(|error| error.pointer.len()).max().unwrap_or(0);
+    let best = results
+        .into_iter()
+        .filter(|errors| deepest(errors) > pointer.len())
+        .min_by_key(|errors| (errors.len(), usize::MAX - deepest(errors)));
+    best.unwrap_or_else(|| {
+        vec![SchemaError {
+            pointer: pointer.to_string(),
+            kind: SchemaErrorKind::Form,
+            message: summary.to_string(),
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn messages(instance: Value, schema: Value) -> Vec<String> {
+        validate(&instance, &schema).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_object_constraints() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "jobs"],
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "jobs": {
+                    "type": "object",
+                    "patternProperties": { "^[a-z][a-z0-9_-]*$": { "$ref": "#/definitions/job" } },
+                    "additionalProperties": false
+                }
+            },
+            "definitions": {
+                "job": {
+                    "type": "object",
+                    "required": ["runs-on"],
+                    "properties": {
+                        "runs-on": { "type": ["string", "array"] },
+                        "timeout-minutes": { "type": "integer", "minimum": 1, "maximum": 360 }
+                    },
+                    "additionalProperties": false
+                }
+            }
+        });
+        assert!(messages(json!({ "name": "CI", "jobs": { "build": { "runs-on": "ubuntu-latest" } } }), schema.clone()).is_empty());
+        assert_eq!(
+            messages(
+                json!({ "jobs": { "build": { "runs_on": "ubuntu", "timeout-minutes": 0 }, "Bad": {} } }),
+                schema.clone()
+            ),
+            vec![
+                "/jobs/Bad: unknown property 'Bad'",
+                "/jobs/build/runs-on: required property is missing",
+                "/jobs/build/runs_on: unknown property 'runs_on'",
+                "/jobs/build/timeout-minutes: 0 is below the minimum of 1",
+                "/name: required property is missing",
+            ]
+        );
+        let kinds: Vec<SchemaErrorKind> = validate(&json!({ "name": 1, "jobs": {} }), &schema).iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![SchemaErrorKind::Type]);
+        assert_eq!(validate(&json!({}), &schema)[0].kind, SchemaErrorKind::Missing);
+    }
+
+    #[test]
+    fn test_combinators_and_arrays() {
+        let schema = json!({
+            "type": "array",
+            "uniqueItems": true,
+            "items": {
+                "oneOf": [
+                    { "type": "string", "enum": ["push", "pull_request"] },
+                    { "type": "object", "properties": { "cron": { "type": "string", "pattern": "^\\S+ \\S+ \\S+ \\S+ \\S+$" } }, "required": ["cron"] }
+                ]
+            }
+        });
+        assert!(messages(json!(["push", { "cron": "0 * * * *" }]), schema.clone()).is_empty());
+        assert_eq!(
+            messages(json!(["push", "push", "tag", { "cron": "hourly" }, 3]), schema),
+            vec![
+                "/1: duplicates an earlier item, but items must be unique",
+                "/2: does not match any of the allowed forms",
+                "/3/cron: \"hourly\" does not match the pattern ^\\S+ \\S+ \\S+ \\S+ \\S+$",
+                "/4: does not match any of the allowed forms",
+            ]
+        );
+        assert_eq!(messages(json!(5), json!({ "not": { "type": "integer" } })), vec!["/: matches a form that is not allowed"]);
+        let unresolved = messages(json!(1), json!({ "$ref": "#/missing" }));
+        assert!(unresolved[0].starts_with("/: cannot resolve $ref"), "{:?}", unresolved);
+        assert_eq!(
+            validate(&json!(1), &json!({ "$ref": "https://example.com/other.json" }))[0].kind,
+            SchemaErrorKind::Reference
+        );
+    }
+
+    #[test]
+    fn test_formats_and_ecma_patterns() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "updated": { "type": "string", "format": "date-time" },
+                "contact": { "type": "string", "format": "email" }
+            },
+            // \\d matches only ASCII digits in ECMA-262
+            "patternProperties": { "^x-\\d+$": { "type": "string" } }
+        });
+        assert!(messages(json!({ "updated": "2024-05-01T12:00:00Z", "x-1": "ok" }), schema.clone()).is_empty());
+        assert_eq!(
+            messages(json!({ "updated": "yesterday", "contact": "nobody", "x-2": 2, "x-٣": 3 }), schema),
+            vec![
+                "/contact: \"nobody\" is not a valid email",
+                "/updated: \"yesterday\" is not a valid date-time",
+                "/x-2: expected string, found integer",
+            ]
+        );
+    }
+}
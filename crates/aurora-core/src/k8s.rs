@@ -0,0 +1,807 @@
+//! Kubernetes manifest checks
+//!
+//! Manifests are read with the [`yaml`](crate::yaml) reader, and each
+//! resource is then checked the way kubeval does: required fields and
+//! their types, apiVersions the API server still serves, and the mistakes
+//! that make `kubectl apply` fail, such as a selector that does not match
+//! its pod template's labels or a ConfigMap value that is not a string.
+
+use crate::yaml::{self, YamlError};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Errors in reading manifests
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum K8sError {
+    /// The YAML could not be read
+    #[error("YAML error on line {line}: {message}")]
+    Yaml { line: usize, message: String },
+}
+
+impl From<YamlError> for K8sError {
+    fn from(error: YamlError) -> Self {
+        K8sError::Yaml {
+            line: error.line,
+            message: error.message,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, K8sError>;
+
+/// One document of a manifest file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestDocument {
+    /// Line the document starts on, from 1
+    pub line: usize,
+    pub value: Value,
+}
+
+impl ManifestDocument {
+    /// `Kind/name` of the resource, as kubectl prints it
+    pub fn resource(&self) -> String {
+        let kind = self.value["kind"].as_str().unwrap_or("<unknown kind>");
+        let name = self.value["metadata"]["name"]
+            .as_str()
+            .or_else(|| self.value["metadata"]["generateName"].as_str())
+            .unwrap_or("<unnamed>");
+        format!("{}/{}", kind, name)
+    }
+}
+
+/// A resource field that would be rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestIssue {
+    /// Line the resource's document starts on
+    pub line: usize,
+    /// `Kind/name` of the resource
+    pub resource: String,
+    /// Dotted path of the field, empty for the resource itself
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ManifestIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "line {}: {}: {}", self.line, self.resource, self.message)
+        } else {
+            write!(
+                f,
+                "line {}: {} {}: {}",
+                self.line, self.resource, self.field, self.message
+            )
+        }
+    }
+}
+
+/// Outcome of validating manifests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ManifestReport {
+    /// `Kind/name` of every resource checked
+    pub resources: Vec<String>,
+    pub issues: Vec<ManifestIssue>,
+}
+
+/// Whether `text` looks like a Kubernetes manifest rather than other YAML
+pub fn is_manifest(text: &str) -> bool {
+    let mut api_version = false;
+    let mut kind = false;
+    for line in text.lines() {
+        api_version |= line.starts_with("apiVersion:");
+        kind |= line.starts_with("kind:");
+    }
+    api_version && kind
+}
+
+/// Whether `text` is a Helm template, which must be rendered before it is valid YAML
+pub fn is_template(text: &str) -> bool {
+    text.contains("{{")
+}
+
+/// Read the non-empty documents of a manifest
+pub fn parse_manifests(text: &str) -> Result<Vec<ManifestDocument>> {
+    Ok(yaml::parse_documents(text)?
+        .into_iter()
+        .map(|document| ManifestDocument {
+            line: document.line,
+            value: document.value,
+        })
+        .collect())
+}
+
+/// Validate every resource in a manifest
+pub fn validate_manifests(text: &str) -> Result<ManifestReport> {
+    let mut report = ManifestReport::default();
+    for document in parse_manifests(text)? {
+        // A List holds its resources in items
+        if document.value["kind"] == "List" {
+            for item in document.value["items"].as_array().into_iter().flatten() {
+                let item = ManifestDocument {
+                    line: document.line,
+                    value: item.clone(),
+                };
+                check_document(&item, &mut report);
+            }
+        } else {
+            check_document(&document, &mut report);
+        }
+    }
+    Ok(report)
+}
+
+/// apiVersions the API server serves for the built-in kinds
+fn served_api_versions(kind: &str) -> Option<&'static [&'static str]> {
+    Some(match kind {
+        "Pod"
+        | "Service"
+        | "ConfigMap"
+        | "Secret"
+        | "Namespace"
+        | "ServiceAccount"
+        | "PersistentVolume"
+        | "PersistentVolumeClaim" => &["v1"],
+        "Deployment" | "StatefulSet" | "DaemonSet" | "ReplicaSet" => &["apps/v1"],
+        "Job" | "CronJob" => &["batch/v1"],
+        "Ingress" | "NetworkPolicy" => &["networking.k8s.io/v1"],
+        "HorizontalPodAutoscaler" => &["autoscaling/v2", "autoscaling/v1"],
+        "Role" | "RoleBinding" | "ClusterRole" | "ClusterRoleBinding" => {
+            &["rbac.authorization.k8s.io/v1"]
+        }
+        "PodDisruptionBudget" => &["policy/v1"],
+        _ => return None,
+    })
+}
+
+/// Whether `name` is a DNS subdomain, as most resource names must be
+fn is_dns_subdomain(name: &str) -> bool {
+    static SUBDOMAIN: OnceLock<Regex> = OnceLock::new();
+    name.len() <= 253
+        && SUBDOMAIN
+            .get_or_init(|| {
+                Regex::new(r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*$")
+                    .unwrap()
+            })
+            .is_match(name)
+}
+
+/// Whether `name` is a DNS label, as container and namespace names must be
+fn is_dns_label(name: &str) -> bool {
+    name.len() <= 63 && !name.contains('.') && is_dns_subdomain(name)
+}
+
+/// Collects the issues of one resource
+struct Checker<'a> {
+    line: usize,
+    resource: String,
+    issues: &'a mut Vec<ManifestIssue>,
+}
+
+impl Checker<'_> {
+    fn issue(&mut self, field: &str, message: impl Into<String>) {
+        self.issues.push(ManifestIssue {
+            line: self.line,
+            resource: self.resource.clone(),
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// The string at `field`, reporting it when missing or of another type
+    fn string<'v>(&mut self, value: &'v Value, field: &str) -> Option<&'v str> {
+        match value {
+            Value::String(s) => Some(s),
+            Value::Null => {
+                self.issue(field, "is required");
+                None
+            }
+            other => {
+                self.issue(field, format!("must be a string, got {}", type_name(other)));
+                None
+            }
+        }
+    }
+
+    /// The mapping at `field`, reporting it when missing (if `required`) or of another type
+    fn mapping<'v>(
+        &mut self,
+        value: &'v Value,
+        field: &str,
+        required: bool,
+    ) -> Option<&'v Map<String, Value>> {
+        match value {
+            Value::Object(map) => Some(map),
+            Value::Null if !required => None,
+            Value::Null => {
+                self.issue(field, "is required");
+                None
+            }
+            other => {
+                self.issue(
+                    field,
+                    format!("must be a mapping, got {}", type_name(other)),
+                );
+                None
+            }
+        }
+    }
+
+    /// The sequence at `field`, reporting it when present with another type
+    fn sequence<'v>(&mut self, value: &'v Value, field: &str) -> &'v [Value] {
+        match value {
+            Value::Array(items) => items,
+            Value::Null => &[],
+            other => {
+                self.issue(field, format!("must be a list, got {}", type_name(other)));
+                &[]
+            }
+        }
+    }
+
+    /// Report the values of the mapping at `field` that are not strings
+    fn string_values(&mut self, value: &Value, field: &str) {
+        for (key, value) in self.mapping(value, field, false).into_iter().flatten() {
+            if !value.is_string() {
+                self.issue(
+                    &format!("{}.{}", field, key),
+                    format!(
+                        "must be a string; quote the {} ({})",
+                        type_name(value),
+                        quoted(value)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Report `field` unless it is an integer port number
+    fn port(&mut self, value: &Value, field: &str) {
+        match value.as_i64() {
+            Some(1..=65535) => {}
+            Some(port) => self.issue(field, format!("{} is not a port number (1-65535)", port)),
+            None => self.issue(
+                field,
+                format!("must be an integer port number, got {}", type_name(value)),
+            ),
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "mapping",
+    }
+}
+
+/// `value` as it should be written quoted
+fn quoted(value: &Value) -> String {
+    format!("\"{}\"", value.to_string().trim_matches('"'))
+}
+
+fn check_document(document: &ManifestDocument, report: &mut ManifestReport) {
+    let resource = document.resource();
+    report.resources.push(resource.clone());
+    let mut checker = Checker {
+        line: document.line,
+        resource,
+        issues: &mut report.issues,
+    };
+    check_resource(&document.value, &mut checker);
+}
+
+fn check_resource(doc: &Value, c: &mut Checker) {
+    if !doc.is_object() {
+        c.issue(
+            "",
+            format!("a resource must be a mapping, got {}", type_name(doc)),
+        );
+        return;
+    }
+    let api_version = c.string(&doc["apiVersion"], "apiVersion");
+    let kind = c.string(&doc["kind"], "kind");
+
+    if c.mapping(&doc["metadata"], "metadata", true).is_some() {
+        let metadata = &doc["metadata"];
+        if metadata["generateName"].is_null() || !metadata["name"].is_null() {
+            if let Some(name) = c.string(&metadata["name"], "metadata.name") {
+                if !is_dns_subdomain(name) {
+                    c.issue(
+                        "metadata.name",
+                        format!("'{}' must be lowercase letters, digits, '-', and '.'", name),
+                    );
+                }
+            }
+        }
+        if !metadata["namespace"].is_null() {
+            if let Some(namespace) = c.string(&metadata["namespace"], "metadata.namespace") {
+                if !is_dns_label(namespace) {
+                    c.issue(
+                        "metadata.namespace",
+                        format!("'{}' is not a valid namespace name", namespace),
+                    );
+                }
+            }
+        }
+        c.string_values(&metadata["labels"], "metadata.labels");
+        c.string_values(&metadata["annotations"], "metadata.annotations");
+    }
+
+    let (Some(api_version), Some(kind)) = (api_version, kind) else {
+        return;
+    };
+    if let Some(served) = served_api_versions(kind) {
+        if !served.contains(&api_version) {
+            c.issue(
+                "apiVersion",
+                format!(
+                    "{} is not served for {}; use {}",
+                    api_version,
+                    kind,
+                    served.join(" or ")
+                ),
+            );
+        }
+    }
+
+    match kind {
+        "Deployment" | "StatefulSet" | "DaemonSet" | "ReplicaSet" => check_workload(doc, kind, c),
+        "Job" => check_job(&doc["spec"], "spec", c),
+        "CronJob" => check_cron_job(&doc["spec"], c),
+        "Pod" => check_pod(&doc["spec"], c),
+        "Service" => check_service(&doc["spec"], c),
+        "ConfigMap" => {
+            c.string_values(&doc["data"], "data");
+        }
+        "Secret" => {
+            c.string_values(&doc["data"], "data");
+            c.string_values(&doc["stringData"], "stringData");
+        }
+        "Ingress" => check_ingress(&doc["spec"], c),
+        _ => {}
+    }
+}
+
+fn check_workload(doc: &Value, kind: &str, c: &mut Checker) {
+    let spec = &doc["spec"];
+    if c.mapping(spec, "spec", true).is_none() {
+        return;
+    }
+    if kind != "DaemonSet" && !spec["replicas"].is_null() && spec["replicas"].as_u64().is_none() {
+        c.issue("spec.replicas", "must be a non-negative integer");
+    }
+
+    let template_labels = &spec["template"]["metadata"]["labels"];
+    match c.mapping(&spec["selector"], "spec.selector", true) {
+        Some(selector) if selector.is_empty() => {
+            c.issue("spec.selector", "must select the pod template's labels")
+        }
+        Some(_) => {
+            if let Some(match_labels) = c.mapping(
+                &spec["selector"]["matchLabels"],
+                "spec.selector.matchLabels",
+                false,
+            ) {
+                for (key, value) in match_labels {
+                    if template_labels[key.as_str()] != *value {
+                        c.issue(
+                            "spec.selector.matchLabels",
+                            format!(
+                                "{}={} does not match the labels of spec.template.metadata.labels",
+                                key,
+                                value
+                                    .as_str()
+                                    .map_or_else(|| value.to_string(), str::to_string)
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+
+    if c.mapping(&spec["template"], "spec.template", true)
+        .is_some()
+    {
+        c.string_values(template_labels, "spec.template.metadata.labels");
+        if c.mapping(&spec["template"]["spec"], "spec.template.spec", true)
+            .is_some()
+        {
+            check_pod_spec(&spec["template"]["spec"], "spec.template.spec", c);
+        }
+    }
+}
+
+fn check_job(spec: &Value, field: &str, c: &mut Checker) {
+    if c.mapping(spec, field, true).is_none() {
+        return;
+    }
+    let template = format!("{}.template.spec", field);
+    if c.mapping(&spec["template"]["spec"], &template, true)
+        .is_none()
+    {
+        return;
+    }
+    let pod = &spec["template"]["spec"];
+    match pod["restartPolicy"].as_str() {
+        Some("Never" | "OnFailure") => {}
+        Some(policy) => c.issue(
+            &format!("{}.restartPolicy", template),
+            format!(
+                "'{}' is not allowed for jobs; use Never or OnFailure",
+                policy
+            ),
+        ),
+        None => c.issue(
+            &format!("{}.restartPolicy", template),
+            "is required for jobs (Never or OnFailure)",
+        ),
+    }
+    check_pod_spec(pod, &template, c);
+}
+
+fn check_cron_job(spec: &Value, c: &mut Checker) {
+    if c.mapping(spec, "spec", true).is_none() {
+        return;
+    }
+    c.string(&spec["schedule"], "spec.schedule");
+    if c.mapping(&spec["jobTemplate"], "spec.jobTemplate", true)
+        .is_some()
+    {
+        check_job(&spec["jobTemplate"]["spec"], "spec.jobTemplate.spec", c);
+    }
+}
+
+fn check_pod(spec: &Value, c: &mut Checker) {
+    if c.mapping(spec, "spec", true).is_some() {
+        check_pod_spec(spec, "spec", c);
+    }
+}
+
+fn check_pod_spec(spec: &Value, field: &str, c: &mut Checker) {
+    let containers = c.sequence(&spec["containers"], &format!("{}.containers", field));
+    if containers.is_empty() {
+        c.issue(
+            &format!("{}.containers", field),
+            "must list at least one container",
+        );
+    }
+    let mut names = Vec::new();
+    for list in ["initContainers", "containers"] {
+        for (i, container) in c
+            .sequence(&spec[list], &format!("{}.{}", field, list))
+            .iter()
+            .enumerate()
+        {
+            let path = format!("{}.{}[{}]", field, list, i);
+            if c.mapping(container, &path, true).is_none() {
+                continue;
+            }
+            if let Some(name) = c.string(&container["name"], &format!("{}.name", path)) {
+                if !is_dns_label(name) {
+                    c.issue(
+                        &format!("{}.name", path),
+                        format!("'{}' is not a valid container name", name),
+                    );
+                }
+                if names.contains(&name) {
+                    c.issue(
+                        &format!("{}.name", path),
+                        format!("duplicate container name '{}'", name),
+                    );
+                }
+                names.push(name);
+            }
+            c.string(&container["image"], &format!("{}.image", path));
+            for (j, port) in c
+                .sequence(&container["ports"], &format!("{}.ports", path))
+                .iter()
+                .enumerate()
+            {
+                c.port(
+                    &port["containerPort"],
+                    &format!("{}.ports[{}].containerPort", path, j),
+                );
+            }
+            for (j, env) in c
+                .sequence(&container["env"], &format!("{}.env", path))
+                .iter()
+                .enumerate()
+            {
+                let env_path = format!("{}.env[{}]", path, j);
+                c.string(&env["name"], &format!("{}.name", env_path));
+                if !env["value"].is_null() && !env["value"].is_string() {
+                    c.issue(
+                        &format!("{}.value", env_path),
+                        format!(
+                            "must be a string; quote the {} ({})",
+                            type_name(&env["value"]),
+                            quoted(&env["value"])
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn check_service(spec: &Value, c: &mut Checker) {
+    if c.mapping(spec, "spec", true).is_none() {
+        return;
+    }
+    let service_type = spec["type"].as_str().unwrap_or("ClusterIP");
+    if !matches!(
+        service_type,
+        "ClusterIP" | "NodePort" | "LoadBalancer" | "ExternalName"
+    ) {
+        c.issue(
+            "spec.type",
+            format!(
+                "'{}' must be ClusterIP, NodePort, LoadBalancer, or ExternalName",
+                service_type
+            ),
+        );
+    }
+    if service_type == "ExternalName" {
+        c.string(&spec["externalName"], "spec.externalName");
+        return;
+    }
+
+    let ports = c.sequence(&spec["ports"], "spec.ports");
+    if ports.is_empty() {
+        c.issue("spec.ports", "must list at least one port");
+    }
+    for (i, port) in ports.iter().enumerate() {
+        let path = format!("spec.ports[{}]", i);
+        c.port(&port["port"], &format!("{}.port", path));
+        match &port["targetPort"] {
+            Value::Null | Value::String(_) => {}
+            target => c.port(target, &format!("{}.targetPort", path)),
+        }
+        if let Some(protocol) = port["protocol"].as_str() {
+            if !matches!(protocol, "TCP" | "UDP" | "SCTP") {
+                c.issue(
+                    &format!("{}.protocol", path),
+                    format!("'{}' must be TCP, UDP, or SCTP", protocol),
+                );
+            }
+        }
+        if ports.len() > 1 && port["name"].is_null() {
+            c.issue(
+                &format!("{}.name", path),
+                "is required when a Service has more than one port",
+            );
+        }
+    }
+    c.string_values(&spec["selector"], "spec.selector");
+}
+
+fn check_ingress(spec: &Value, c: &mut Checker) {
+    if c.mapping(spec, "spec", true).is_none() {
+        return;
+    }
+    for (i, rule) in c.sequence(&spec["rules"], "spec.rules").iter().enumerate() {
+        for (j, path) in c
+            .sequence(
+                &rule["http"]["paths"],
+                &format!("spec.rules[{}].http.paths", i),
+            )
+            .iter()
+            .enumerate()
+        {
+            let field = format!("spec.rules[{}].http.paths[{}]", i, j);
+            match path["pathType"].as_str() {
+                Some("Exact" | "Prefix" | "ImplementationSpecific") => {}
+                Some(other) => c.issue(
+                    &format!("{}.pathType", field),
+                    format!(
+                        "'{}' must be Exact, Prefix, or ImplementationSpecific",
+                        other
+                    ),
+                ),
+                None => c.issue(&format!("{}.pathType", field), "is required"),
+            }
+            let service = &path["backend"]["service"];
+            if service.is_null() && path["backend"]["resource"].is_null() {
+                c.issue(&format!("{}.backend.service", field), "is required");
+                continue;
+            }
+            if !service.is_null() {
+                c.string(&service["name"], &format!("{}.backend.service.name", field));
+                if service["port"]["number"].is_null() && service["port"]["name"].is_null() {
+                    c.issue(
+                        &format!("{}.backend.service.port", field),
+                        "needs a number or name",
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_yaml() {
+        let documents = parse_manifests(
+            r#"# leading comment
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings   # trailing comment
+  labels: {app: web, tier: "front end"}
+data:
+  enabled: yes
+  ratio: 0.5
+  url: http://example.com/#anchor
+  script: |
+    echo "one"
+
+    echo "two"
+  folded: >-
+    a
+    b
+---
+---
+list:
+- name: a
+  ports:
+  - 80
+  - '8080'
+-
+  - nested
+empty:
+"#,
+        )
+        .unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].line, 1);
+        assert_eq!(documents[0].resource(), "ConfigMap/settings");
+        assert_eq!(
+            documents[0].value["metadata"]["labels"],
+            json!({ "app": "web", "tier": "front end" })
+        );
+        assert_eq!(
+            documents[0].value["data"],
+            json!({
+                "enabled": true,
+                "ratio": 0.5,
+                "url": "http://example.com/#anchor",
+                "script": "echo \"one\"\n\necho \"two\"\n",
+                "folded": "a b",
+            })
+        );
+        assert_eq!(documents[1].line, 20);
+        assert_eq!(
+            documents[1].value,
+            json!({ "list": [{ "name": "a", "ports": [80, "8080"] }, ["nested"]], "empty": null })
+        );
+    }
+
+    #[test]
+    fn test_yaml_errors() {
+        assert_eq!(
+            parse_manifests("a: 1\n   b: 2\n"),
+            Err(K8sError::Yaml {
+                line: 2,
+                message: "mapping values are not allowed in this context".to_string()
+            })
+        );
+        assert!(parse_manifests("a: 1\na: 2\n").is_err());
+        assert!(parse_manifests("args: [\"sh\", \"-c\"\n").is_err());
+        assert!(parse_manifests("name: \"unterminated\n").is_err());
+    }
+
+    #[test]
+    fn test_valid_manifest() {
+        let report = validate_manifests(
+            r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+spec:
+  replicas: 2
+  selector:
+    matchLabels:
+      app: web
+  template:
+    metadata:
+      labels:
+        app: web
+    spec:
+      containers:
+      - name: web
+        image: nginx:1.27
+        ports:
+        - containerPort: 80
+        env:
+        - name: MODE
+          value: "1"
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+spec:
+  selector:
+    app: web
+  ports:
+  - port: 80
+    targetPort: http
+"#,
+        )
+        .unwrap();
+        assert_eq!(report.resources, vec!["Deployment/web", "Service/web"]);
+        assert_eq!(report.issues, vec![]);
+    }
+
+    #[test]
+    fn test_manifest_issues() {
+        let report = validate_manifests(
+            r#"apiVersion: extensions/v1beta1
+kind: Deployment
+metadata:
+  name: Web_App
+  labels:
+    version: 1.0
+spec:
+  selector:
+    matchLabels:
+      app: web
+  template:
+    metadata:
+      labels:
+        app: api
+    spec:
+      containers:
+      - name: web
+        ports:
+        - containerPort: 70000
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: flags
+data:
+  debug: true
+---
+kind: Service
+metadata: {}
+"#,
+        )
+        .unwrap();
+        let issues: Vec<String> = report
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "line 1: Deployment/Web_App metadata.name: 'Web_App' must be lowercase letters, digits, '-', and '.'",
+                "line 1: Deployment/Web_App metadata.labels.version: must be a string; quote the number (\"1.0\")",
+                "line 1: Deployment/Web_App apiVersion: extensions/v1beta1 is not served for Deployment; use apps/v1",
+                "line 1: Deployment/Web_App spec.selector.matchLabels: app=web does not match the labels of spec.template.metadata.labels",
+                "line 1: Deployment/Web_App spec.template.spec.containers[0].image: is required",
+                "line 1: Deployment/Web_App spec.template.spec.containers[0].ports[0].containerPort: 70000 is not a port number (1-65535)",
+                "line 21: ConfigMap/flags data.debug: must be a string; quote the boolean (\"true\")",
+                "line 28: Service/<unnamed> apiVersion: is required",
+                "line 28: Service/<unnamed> metadata.name: is required",
+            ]
+        );
+        assert!(is_manifest("apiVersion: v1\nkind: Pod\n"));
+        assert!(!is_manifest("name: CI\non: push\n"));
+    }
+}
@@ -7,32 +7,245 @@
 //! - Shared types and error handling
 //! - File I/O utilities
 //! - Project detection and analysis
+//! - Secret scanning and pre-commit quality gates
+//! - License header and dependency license policy checks
+//! - CycloneDX and SPDX software bills of materials from lockfiles
+//! - Docker and Podman command lines and starter Dockerfiles
+//! - Kubernetes manifest validation
+//! - Terraform and Pulumi plan summaries
+//! - JSON, YAML, and TOML config file checks against JSON Schemas
+//! - Compact summaries of OpenAPI and GraphQL API contracts
+//! - Database schemas from live introspection or SQL migrations
+//! - Migration commands for SQLx, Diesel, Alembic, Prisma, and Knex
+//! - Markdown tables of contents, heading lint, and intra-repo link checks
+//! - Sampling parameters and provider validation
+//! - PDF and DOCX text extraction
+//! - Parallel project-wide search and replace
+//! - Whole-word and case-preserving replacement of identifiers
+//! - Per-line previews of bulk replacements, applied selectively
+//! - Fuzzy quick-open over an incrementally refreshed index of project paths
+//! - Persistent workspace symbol index for go-to-symbol
+//! - Bundled tree-sitter grammars for parsing source files
+//! - Buffered line scanning for very large files
+//! - Lexical path normalization (separators, drive letters, UNC, long paths)
+//! - Symlink-safe recursive walks (cycle detection, workspace confinement)
+//! - `.auroraignore` rules hiding paths from the agent and the file tree
+//! - Project file listing from git or a directory walk, honoring ignore rules
+//! - Protection of lockfiles, vendored, generated, and conflicted files from bulk replacement
+//! - Persisted workspace UI state (recent files, bookmarks, open tabs)
+//! - Recently opened projects, kept per user
+//! - Project templates and scaffolding
+//! - Per-project file templates (license headers, module docs, test scaffolds)
+//! - `.editorconfig` styles applied to files the agent writes
+//! - Named project tasks with dependency ordering and watch mode
+//! - Changelog sections grouped from conventional commits
+//! - Code owners from CODEOWNERS files and frequent contributors from git history
+//! - Benchmark runs with per-commit results and regression reports
+//! - Sampling profiler integration with hotspot summaries
+//! - Structured compiler diagnostics from build output
+//! - Parse-error and bracket checks for files with no compiler installed
+//! - Unified line diffs for previewing generated files
+//! - Local file history independent of git
+//! - Shell selection and login-shell environment capture
+//! - Discovery of the external programs tools run, with install hints
+//! - Registry of command palette actions, including plugin contributions
+//! - Reply language and locale-aware date and size formatting
+//! - Trying regular expressions on sample text before a bulk replacement
+//! - Scratch runs of Rust, Python, and JavaScript snippets outside the project
+//! - Jupyter notebook cells and their outputs
+//! - Plain, screen-reader-friendly display of tool output
+//! - File tree and tab icons, extensible by plugins and config
 
+pub mod actions;
+pub mod api_spec;
+pub mod bench;
+pub mod changelog;
 pub mod config;
+pub mod config_file;
+pub mod container;
 pub mod crypto;
+pub mod db_schema;
+pub mod diagnostics;
+pub mod diff;
+pub mod documents;
+pub mod editorconfig;
 pub mod error;
+pub mod external;
+pub mod file_icons;
+pub mod file_index;
+pub mod file_templates;
 pub mod file_io;
+pub mod fuzzy;
+pub mod git_conventions;
+pub mod grammar;
+pub mod history;
+pub mod iac;
+pub mod json_schema;
+pub mod k8s;
+pub mod license;
+pub mod ignore;
+pub mod line_scan;
+pub mod locale;
+pub mod markdown;
+pub mod migrate;
+pub mod notebook;
+pub mod output_style;
+pub mod owners;
+pub mod paths;
 pub mod plugin;
+pub mod process_limits;
+pub mod profile;
 pub mod project;
+pub mod project_files;
+pub mod protected;
+pub mod quality_gate;
+pub mod recent;
+pub mod regex_test;
+pub mod replace_preview;
+pub mod sampling;
+pub mod sbom;
+pub mod scaffold;
+pub mod search;
+pub mod shell;
+pub mod snippet;
+pub mod symbols;
+pub mod syntax;
 pub mod types;
 pub mod command;
 pub mod hooks;
+pub mod secrets;
+pub mod tasks;
+pub mod watch;
+pub mod word_replace;
+pub mod workspace;
+pub mod yaml;
 
+pub use actions::{Action, ActionError, ActionKind, ActionRegistry};
+pub use api_spec::{
+    find_api_specs, load_api_spec, ApiSpecError, ApiSpecKind, ApiSummary, Endpoint, Model,
+};
+pub use bench::{
+    BenchCommand, BenchComparison, BenchError, BenchReport, BenchResult, BenchRun, BenchStore,
+    BenchVerdict, Benchmark,
+};
+pub use changelog::{
+    commits_in_range, merge_section, save_changelog, ChangelogCommit, ChangelogError, ChangelogPreview, Release,
+    CHANGELOG_FILE,
+};
 pub use command::{CommandError, CommandExecutor, ParsedCommand, find_commands_in_text};
 pub use hooks::{
     HookError, HookExecutor, HookResult, SessionStartContext, SessionEndContext,
     ToolCallContext, AfterToolCallContext,
 };
-pub use config::{Config, ConfigError};
+pub use config::{
+    ApiGeneratorDefinition, BudgetConfig, Config, ConfigError, ContainerConfig, ContainerEngine, DatabaseConfig, FileIconDefinition, FilterAction, FilterCommandDefinition, FilterRuleDefinition, FormatterDefinition, GitConfig, HistoryConfig, IssueTrackerConfig, IssueTrackerKind, LicenseConfig, OutboundFilterConfig, QualityGateConfig, SttBackend, SyntaxCheckerDefinition, VoiceConfig,
+    ProfileConfig, ProfilerKind, PromptAnswer, QualityGateStep, QualityGateStepKind, ShellConfig,
+    ShellKind, ReplaceConfig, TaskDefinition, TerminalConfig, ToolInputConfig, UpdateChannel, UpdateConfig, UPDATE_CONFIG_FILE, WasmFilterDefinition, WatchConfig, LocaleConfig,
+    user_config_dir,
+};
+pub use container::{
+    build_args, dockerfile_for, dockerignore_for, run_args, ContainerError, PortMapping, RunOptions,
+};
 pub use crypto::{CredentialStore, CredentialStoreError};
+pub use db_schema::{
+    database_url, migration_files, redact_url, DatabaseEngine, DatabaseSchema, DbSchemaError,
+};
+pub use diagnostics::{
+    apply_edits, parse_build_output, Diagnostic, DiagnosticFilter, FixError, QuickFix, Severity,
+    TextEdit, TextRange,
+};
+pub use diff::unified_diff;
+pub use documents::{
+    Document, DocumentError, DocumentKind, extract_document, extract_docx, extract_pdf,
+    parse_page_range,
+};
+pub use editorconfig::{EditorConfigError, EndOfLine, FileStyle, IndentSize, IndentStyle, EDITORCONFIG_FILE};
 pub use error::{AuroraCoreError, Result};
-pub use file_io::{FileIoError, read_file, read_file_content, write_file, write_file_content};
+pub use external::{
+    install_hint, missing_program_message, ExternalProgram, ExternalTools, ProgramStatus, PROGRAMS,
+};
+pub use file_icons::{FileIcon, FileIconError, FileIcons, FILE_ICON, FOLDER_ICON};
+pub use file_index::{FileIndex, FileIndexError, FileMatch, IndexChanges, IndexEntry};
+pub use file_templates::{FileTemplateError, FileTemplates, FILE_TEMPLATES_DIR};
+pub use file_io::{
+    FileIoError, Progress, StreamedRead, read_file, read_file_content, read_file_streaming,
+    write_file, write_file_content, write_file_streaming,
+};
+pub use git_conventions::{
+    ConventionReport, ConventionalSubject, GitConventionError, lint_commit_message, parse_conventional,
+    validate_branch_name,
+};
+pub use fuzzy::{fuzzy_match, FuzzyMatch, FuzzyQuery};
+pub use history::{FileHistory, HistoryEntry, HistoryError, HistorySource};
+pub use iac::{
+    parse_pulumi_preview, parse_terraform_plan, plan_file, ChangeAction, IacEngine, IacError, PlanSummary, ResourceChange,
+};
+pub use ignore::{IgnoreError, IgnoreRules, IGNORE_FILE};
+pub use config_file::{known_schema, parse_config_file, ConfigDocument, ConfigFormat, ConfigSyntaxError};
+pub use json_schema::{validate as validate_json_schema, SchemaError, SchemaErrorKind};
+pub use markdown::{
+    check_markdown, is_markdown, markdown_files, refresh_toc, Heading, MarkdownError, MarkdownIssue, MarkdownIssueKind,
+};
+pub use migrate::{MigrateError, MigrationAction, MigrationTool};
+pub use notebook::{strip_ansi, CellKind, CellOutput, Notebook, NotebookCell, NotebookError, OutputImage};
+pub use k8s::{
+    is_manifest, is_template, parse_manifests, validate_manifests, K8sError, ManifestDocument, ManifestIssue, ManifestReport,
+};
+pub use license::{
+    cargo_dependencies, npm_dependencies, Dependency, Ecosystem, LicenseError, LicensePolicy, LicenseReport,
+    LicenseViolation,
+};
+pub use protected::{has_conflict_markers, ProtectedFiles, SkipReason, SkippedFile};
+pub use line_scan::{LineScanner, ScanStats, LARGE_FILE_BYTES};
+pub use locale::{Locale, UiMessage};
+pub use output_style::{plain_text, OutputStyle};
+pub use owners::{file_owners, relative_path, CodeOwners, Contributor, FileOwners, OwnersError, CODEOWNERS_PATHS};
+pub use paths::{is_symlink, long_path, normalize_path, path_key, resolve_path, WalkGuard};
 pub use plugin::{
-    AgentDefinition, AgentInfo, CommandDefinition, Hook, HookType, Plugin, PluginError,
+    ActionDefinition, AgentDefinition, AgentInfo, CommandDefinition, Hook, HookType, Plugin, PluginError,
     PluginManager, PluginMetadata,
 };
-pub use project::{Language, ProjectError, detect_language, find_project_root, get_project_name};
+pub use profile::{Hotspot, ProfileError, ProfileReport, Profiler};
+pub use project::{
+    Language, ProjectError, detect_language, find_project_root, get_project_name,
+    validate_project_root,
+};
+pub use project_files::{project_files, staged_files, tracked_files, FileListError};
+pub use quality_gate::{QualityGate, QualityGateError, QualityGateReport, StepResult, StepStatus};
+pub use recent::{
+    RecentProject, RecentProjects, RecentProjectsError, RecentProjectsStore, MAX_RECENT_PROJECTS,
+};
+pub use regex_test::{
+    build_regex, test_regex, GroupMatch, RegexMatch, RegexOptions, RegexTestReport, MATCH_TIMEOUT, MAX_PATTERN_LEN,
+};
+pub use replace_preview::{
+    apply_line_changes, replace_by_line, AppliedChanges, FileChanges, LineChange, LineChangeError, Replaced,
+};
+pub use sampling::{ANTHROPIC_LIMITS, ProviderLimits, SamplingError, SamplingParams};
+pub use sbom::{generate_sbom, SbomComponent, SbomError, SbomFormat};
+pub use scaffold::{Scaffolder, ScaffoldError, TemplateInfo, TemplateKind};
+pub use search::{
+    FileMatches, ProjectSearch, ReplaceSummary, SearchError, SearchMatch, SearchQuery,
+    SearchSummary,
+};
+pub use secrets::{SecretBaseline, SecretFinding, SecretScanError, SecretScanner};
+pub use symbols::{
+    extract_symbols, IndexUpdate, Symbol, SymbolIndex, SymbolIndexError, SymbolKind, SymbolMatch,
+};
+pub use syntax::{check_parse, check_structure, Syntax, SyntaxIssue};
+pub use word_replace::{case_variants, preserve_case, split_words, CaseStyle, ReplaceOptions};
+pub use yaml::{YamlDocument, YamlError};
+pub use shell::{Shell, ShellEnvironment, ShellError};
+pub use process_limits::ProcessLimits;
+pub use snippet::{Sandbox, SnippetError, SnippetLanguage, SnippetStep, StepProgram};
+pub use tasks::{
+    OutputStream, TaskError, TaskEvent, TaskReport, TaskResult, TaskRunner, TaskStatus,
+};
 pub use types::*;
+pub use watch::{FileWatcher, TaskWatcher, WatchError, WatchRun};
+pub use workspace::{
+    Bookmark, CursorPosition, WorkspaceState, WorkspaceStateError, WorkspaceStore,
+};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,457 @@
+//! License header and dependency license checks
+//!
+//! The `[license]` config names a header every source file must carry and
+//! the SPDX licenses dependencies may or may not use. Headers are found in
+//! the first [`HEADER_LINES`] lines with comment markers ignored, so one
+//! configured text covers `//`, `#`, and `/* */` comments alike.
+//!
+//! Dependency licenses come from the data the package managers record:
+//! `cargo metadata` (the same data cargo-deny checks) for Rust and the
+//! `license` fields of `package-lock.json` for npm. A license expression
+//! such as `MIT OR Apache-2.0` passes when some choice of its licenses is
+//! allowed and not denied.
+
+use crate::config::LicenseConfig;
+use crate::ignore::IgnoreRules;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Number of leading lines searched for the license header
+pub const HEADER_LINES: usize = 20;
+
+/// Errors that can occur while checking licenses
+#[derive(Error, Debug)]
+pub enum LicenseError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Package manager data could not be parsed
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// An exclude pattern is invalid
+    #[error("Invalid license exclude pattern: {0}")]
+    InvalidPattern(String),
+
+    /// The configured header could not be compiled
+    #[error("Invalid license header: {0}")]
+    InvalidHeader(String),
+}
+
+pub type Result<T> = std::result::Result<T, LicenseError>;
+
+/// Package manager a dependency comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Pypi,
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ecosystem::Cargo => write!(f, "cargo"),
+            Ecosystem::Npm => write!(f, "npm"),
+            Ecosystem::Pypi => write!(f, "pypi"),
+        }
+    }
+}
+
+/// A third-party package and its declared license
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Dependency {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    /// SPDX license expression, if the package declares one
+    pub license: Option<String>,
+}
+
+/// A breach of the license policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LicenseViolation {
+    /// A source file lacks the required header
+    MissingHeader { path: PathBuf },
+    /// A dependency's license is denied or not allowed
+    DisallowedLicense { dependency: Dependency },
+    /// A dependency declares no license while an allow list is set
+    UnknownLicense { dependency: Dependency },
+}
+
+impl fmt::Display for LicenseViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseViolation::MissingHeader { path } => write!(f, "{}: missing license header", path.display()),
+            LicenseViolation::DisallowedLicense { dependency: d } => write!(
+                f,
+                "{} {} {}: license '{}' is not allowed",
+                d.ecosystem,
+                d.name,
+                d.version,
+                d.license.as_deref().unwrap_or_default()
+            ),
+            LicenseViolation::UnknownLicense { dependency: d } => {
+                write!(f, "{} {} {}: no license declared", d.ecosystem, d.name, d.version)
+            }
+        }
+    }
+}
+
+/// Result of a license check
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LicenseReport {
+    pub files_checked: usize,
+    pub dependencies_checked: usize,
+    pub violations: Vec<LicenseViolation>,
+}
+
+/// The license policy of one project
+pub struct LicensePolicy {
+    header: Option<Regex>,
+    extensions: HashSet<String>,
+    exclude: IgnoreRules,
+    allowed: HashSet<String>,
+    denied: HashSet<String>,
+}
+
+impl LicensePolicy {
+    /// The policy `config` sets for the project at `root`
+    pub fn new(root: impl Into<PathBuf>, config: &LicenseConfig) -> Result<Self> {
+        let header = match config.header.as_deref().map(str::trim).filter(|h| !h.is_empty()) {
+            Some(header) => Some(header_regex(header)?),
+            None => None,
+        };
+        let lower = |ids: &[String]| ids.iter().map(|id| id.to_lowercase()).collect();
+        Ok(Self {
+            header,
+            extensions: config.extensions.iter().map(|ext| ext.trim_start_matches('.').to_lowercase()).collect(),
+            exclude: IgnoreRules::new(root, &config.exclude).map_err(|e| LicenseError::InvalidPattern(e.to_string()))?,
+            allowed: lower(&config.allowed),
+            denied: lower(&config.denied),
+        })
+    }
+
+    /// Whether a header is required
+    pub fn requires_header(&self) -> bool {
+        self.header.is_some()
+    }
+
+    /// Whether `path` is a source file that needs the header
+    pub fn needs_header(&self, path: &Path) -> bool {
+        self.header.is_some()
+            && path
+                .extension()
+                .is_some_and(|ext| self.extensions.contains(&ext.to_string_lossy().to_lowercase()))
+            && !self.exclude.is_ignored(path, false)
+    }
+
+    /// Whether `content` starts with the required header
+    pub fn has_header(&self, content: &str) -> bool {
+        let Some(header) = &self.header else {
+            return true;
+        };
+        let text: Vec<&str> = content
+            .lines()
+            .take(HEADER_LINES)
+            .map(strip_comment)
+            .filter(|line| !line.is_empty())
+            .collect();
+        header.is_match(&text.join("\n"))
+    }
+
+    /// Check the headers of `files`, skipping those that need none
+    pub fn check_headers(&self, files: &[PathBuf], report: &mut LicenseReport) -> Result<()> {
+        for path in files.iter().filter(|path| self.needs_header(path)) {
+            let content = match std::fs::read(path) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            report.files_checked += 1;
+            if !self.has_header(&content) {
+                report.violations.push(LicenseViolation::MissingHeader { path: path.clone() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the policy constrains dependency licenses
+    pub fn checks_dependencies(&self) -> bool {
+        !self.allowed.is_empty() || !self.denied.is_empty()
+    }
+
+    /// Whether a license expression can be satisfied by allowed licenses
+    pub fn permits(&self, expression: &str) -> bool {
+        let tokens = tokenize(expression);
+        let mut parser = ExpressionParser { tokens: &tokens, pos: 0 };
+        match parser.or(&|id: &str| self.permits_id(id)) {
+            Some(permitted) => permitted && parser.pos == tokens.len(),
+            None => false,
+        }
+    }
+
+    /// Whether one license identifier is permitted
+    ///
+    /// `+` means "or later", so `GPL-2.0+` is also judged by `GPL-2.0`.
+    fn permits_id(&self, id: &str) -> bool {
+        let id = id.to_lowercase();
+        let ids = [id.as_str(), id.trim_end_matches('+')];
+        !ids.iter().any(|id| self.denied.contains(*id))
+            && (self.allowed.is_empty() || ids.iter().any(|id| self.allowed.contains(*id)))
+    }
+
+    /// Check the licenses of `dependencies`
+    pub fn check_dependencies(&self, dependencies: Vec<Dependency>, report: &mut LicenseReport) {
+        if !self.checks_dependencies() {
+            return;
+        }
+        for dependency in dependencies {
+            report.dependencies_checked += 1;
+            match dependency.license.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+                Some(license) if self.permits(license) => {}
+                Some(_) => report.violations.push(LicenseViolation::DisallowedLicense { dependency }),
+                None if !self.allowed.is_empty() => report.violations.push(LicenseViolation::UnknownLicense { dependency }),
+                None => {}
+            }
+        }
+    }
+}
+
+/// A regex finding `header` in comment-stripped text
+fn header_regex(header: &str) -> Result<Regex> {
+    let lines: Vec<String> = header
+        .lines()
+        .map(strip_comment)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.split("{{year}}")
+                .map(|part| regex::escape(part.trim_end()).replace(' ', r"\s+"))
+                .collect::<Vec<_>>()
+                .join(r"\s*\d{4}(?:\s*[-,]\s*\d{4})*\s*")
+        })
+        .collect();
+    Regex::new(&format!("(?i){}", lines.join(r"\s*\n\s*"))).map_err(|e| LicenseError::InvalidHeader(e.to_string()))
+}
+
+/// A line without comment markers or surrounding whitespace
+fn strip_comment(line: &str) -> &str {
+    let mut line = line.trim();
+    for suffix in ["-->", "*/"] {
+        if let Some(rest) = line.strip_suffix(suffix) {
+            line = rest.trim_end();
+        }
+    }
+    for prefix in ["<!--", "/*", "//!", "///", "//", "--", "#", ";", "*"] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            line = rest.trim_start();
+            break;
+        }
+    }
+    line
+}
+
+/// Tokens of an SPDX expression; the legacy `MIT/Apache-2.0` form is read
+/// as an `OR`
+fn tokenize(expression: &str) -> Vec<String> {
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+struct ExpressionParser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl ExpressionParser<'_> {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.tokens.get(self.pos).is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self, permits: &dyn Fn(&str) -> bool) -> Option<bool> {
+        let mut result = self.and(permits)?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            result |= self.and(permits)?;
+        }
+        Some(result)
+    }
+
+    fn and(&mut self, permits: &dyn Fn(&str) -> bool) -> Option<bool> {
+        let mut result = self.term(permits)?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            result &= self.term(permits)?;
+        }
+        Some(result)
+    }
+
+    fn term(&mut self, permits: &dyn Fn(&str) -> bool) -> Option<bool> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        let result = if token == "(" {
+            let inner = self.or(permits)?;
+            (self.tokens.get(self.pos)? == ")").then_some(())?;
+            self.pos += 1;
+            inner
+        } else {
+            permits(token)
+        };
+        // An exception only widens what the license permits
+        if self.peek_keyword("with") {
+            self.pos += 2;
+        }
+        Some(result)
+    }
+}
+
+/// Third-party packages in `cargo metadata --format-version 1` output
+///
+/// Workspace members, which have no registry or git source, are left out.
+pub fn cargo_dependencies(metadata: &str) -> Result<Vec<Dependency>> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata)?;
+    let packages = metadata["packages"].as_array().map(Vec::as_slice).unwrap_or_default();
+    Ok(packages
+        .iter()
+        .filter(|package| !package["source"].is_null())
+        .map(|package| Dependency {
+            ecosystem: Ecosystem::Cargo,
+            name: package["name"].as_str().unwrap_or_default().to_string(),
+            version: package["version"].as_str().unwrap_or_default().to_string(),
+            license: package["license"].as_str().map(str::to_string),
+        })
+        .collect())
+}
+
+/// Installed packages listed in a `package-lock.json` (lockfile version 2 or 3)
+pub fn npm_dependencies(lockfile: &str) -> Result<Vec<Dependency>> {
+    let lockfile: serde_json::Value = serde_json::from_str(lockfile)?;
+    let Some(packages) = lockfile["packages"].as_object() else {
+        return Ok(Vec::new());
+    };
+    Ok(packages
+        .iter()
+        .filter(|(path, package)| !path.is_empty() && package["link"].as_bool() != Some(true))
+        .map(|(path, package)| {
+            let name = match package["name"].as_str() {
+                Some(name) => name.to_string(),
+                None => path.rsplit("node_modules/").next().unwrap_or(path).to_string(),
+            };
+            let license = match &package["license"] {
+                serde_json::Value::String(license) => Some(license.clone()),
+                // Old packages declare `{ "type": "MIT" }`
+                value => value["type"].as_str().map(str::to_string),
+            };
+            Dependency {
+                ecosystem: Ecosystem::Npm,
+                name,
+                version: package["version"].as_str().unwrap_or_default().to_string(),
+                license,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn policy(config: LicenseConfig) -> LicensePolicy {
+        LicensePolicy::new("/project", &config).unwrap()
+    }
+
+    #[test]
+    fn test_header_check() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let config = LicenseConfig {
+            header: Some("Copyright {{year}} Example Corp.\nSPDX-License-Identifier: MIT".to_string()),
+            exclude: vec!["generated/".to_string()],
+            ..LicenseConfig::default()
+        };
+        let policy = LicensePolicy::new(root, &config).unwrap();
+        assert!(policy.has_header("// Copyright 2019-2026 Example Corp.\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n"));
+        assert!(policy.has_header("#!/usr/bin/env python\n# copyright 2024 example corp.\n# SPDX-License-Identifier: MIT\n"));
+        assert!(policy.has_header("/*\n * Copyright 2025 Example Corp.\n * SPDX-License-Identifier: MIT\n */\n"));
+        assert!(!policy.has_header("// Copyright 2025 Example Corp.\n// SPDX-License-Identifier: Apache-2.0\n"));
+
+        std::fs::create_dir_all(root.join("generated")).unwrap();
+        std::fs::write(root.join("a.rs"), "// Copyright 2026 Example Corp.\n// SPDX-License-Identifier: MIT\n").unwrap();
+        std::fs::write(root.join("b.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("generated/c.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("README.md"), "# Readme\n").unwrap();
+        let files: Vec<PathBuf> = ["a.rs", "b.rs", "generated/c.rs", "README.md"].iter().map(|f| root.join(f)).collect();
+        let mut report = LicenseReport::default();
+        policy.check_headers(&files, &mut report).unwrap();
+        assert_eq!(report.files_checked, 2);
+        assert_eq!(report.violations, vec![LicenseViolation::MissingHeader { path: root.join("b.rs") }]);
+    }
+
+    #[test]
+    fn test_license_expressions() {
+        let denied = policy(LicenseConfig {
+            denied: vec!["GPL-3.0".to_string()],
+            ..LicenseConfig::default()
+        });
+        assert!(denied.permits("MIT OR GPL-3.0"));
+        assert!(!denied.permits("MIT AND GPL-3.0"));
+        assert!(!denied.permits("gpl-3.0+"));
+
+        let allowed = policy(LicenseConfig {
+            allowed: vec!["MIT".to_string(), "Apache-2.0".to_string()],
+            ..LicenseConfig::default()
+        });
+        assert!(allowed.permits("MIT/Apache-2.0"));
+        assert!(allowed.permits("(MIT OR BSD-3-Clause) AND Apache-2.0 WITH LLVM-exception"));
+        assert!(!allowed.permits("BSD-3-Clause"));
+        assert!(!allowed.permits("MIT AND (Unlicense"));
+    }
+
+    #[test]
+    fn test_dependency_check() {
+        let metadata = r#"{"packages": [
+            {"name": "app", "version": "0.1.0", "license": null, "source": null},
+            {"name": "serde", "version": "1.0.0", "license": "MIT OR Apache-2.0", "source": "registry+https://github.com/rust-lang/crates.io-index"},
+            {"name": "gpl-crate", "version": "2.0.0", "license": "GPL-3.0-only", "source": "registry+https://github.com/rust-lang/crates.io-index"}
+        ]}"#;
+        let lockfile = r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "web", "version": "1.0.0"},
+            "node_modules/left-pad": {"version": "1.3.0", "license": "WTFPL"},
+            "node_modules/@scope/pkg": {"version": "2.0.0", "license": {"type": "MIT"}},
+            "node_modules/mystery": {"version": "0.0.1"}
+        }}"#;
+        let mut dependencies = cargo_dependencies(metadata).unwrap();
+        dependencies.extend(npm_dependencies(lockfile).unwrap());
+        assert_eq!(dependencies.len(), 5);
+
+        let policy = policy(LicenseConfig {
+            allowed: vec!["MIT".to_string(), "Apache-2.0".to_string()],
+            ..LicenseConfig::default()
+        });
+        let mut report = LicenseReport::default();
+        policy.check_dependencies(dependencies, &mut report);
+        assert_eq!(report.dependencies_checked, 5);
+        let messages: Vec<String> = report.violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "cargo gpl-crate 2.0.0: license 'GPL-3.0-only' is not allowed",
+                "npm left-pad 1.3.0: license 'WTFPL' is not allowed",
+                "npm mystery 0.0.1: no license declared",
+            ]
+        );
+    }
+}
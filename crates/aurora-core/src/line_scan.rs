@@ -0,0 +1,183 @@
+//! Line scanning for very large files
+//!
+//! Files are read through a buffer and split into lines as they arrive, so
+//! searching a multi-hundred-megabyte log never copies it into a `String`,
+//! and a log rotated or truncated mid-scan just ends early. Lines longer
+//! than the configured limit are skipped without being buffered, which
+//! keeps minified or binary-ish data from producing enormous matches.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// Files larger than this are scanned line by line instead of read whole
+pub const LARGE_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Default limit on the length of a scanned line
+pub const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// Counts from a completed scan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    /// Lines passed to the visitor
+    pub lines: usize,
+    /// Lines skipped for exceeding the length limit or containing NUL bytes
+    pub skipped_lines: usize,
+}
+
+/// Scans a file line by line without loading it into memory
+#[derive(Debug, Clone)]
+pub struct LineScanner {
+    max_line_bytes: usize,
+}
+
+impl Default for LineScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineScanner {
+    /// Create a scanner with the default line length limit
+    pub fn new() -> Self {
+        Self {
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+        }
+    }
+
+    /// Skip lines longer than `max_line_bytes`
+    pub fn max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self
+    }
+
+    /// Call `visit` with each line's number (1-based) and text until it returns false
+    ///
+    /// Line endings are stripped and invalid UTF-8 is replaced. Skipped lines
+    /// still count towards the line numbers of the lines after them.
+    pub fn scan<F>(&self, path: &Path, mut visit: F) -> io::Result<ScanStats>
+    where
+        F: FnMut(usize, &str) -> bool,
+    {
+        let file = File::open(path)?;
+        let mut stats = ScanStats::default();
+        let mut line_number = 0;
+        // Called with `None` for a line too long to buffer
+        let mut on_line = |line: Option<&[u8]>| {
+            line_number += 1;
+            let Some(line) = line
+                .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+                .filter(|line| line.len() <= self.max_line_bytes && !line.contains(&0))
+            else {
+                stats.skipped_lines += 1;
+                return true;
+            };
+            stats.lines += 1;
+            visit(line_number, &String::from_utf8_lossy(line))
+        };
+
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        let mut too_long = false;
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                if !line.is_empty() || too_long {
+                    on_line((!too_long).then_some(line.as_slice()));
+                }
+                break;
+            }
+            let end = buffer.iter().position(|&b| b == b'\n');
+            let consumed = end.map_or(buffer.len(), |i| i + 1);
+            let chunk = &buffer[..end.unwrap_or(buffer.len())];
+            // Keep one byte past the limit for a `\r` before the newline
+            if too_long || line.len() + chunk.len() > self.max_line_bytes.saturating_add(1) {
+                too_long = true;
+                line.clear();
+            } else {
+                line.extend_from_slice(chunk);
+            }
+            reader.consume(consumed);
+            if end.is_some() {
+                let more = on_line((!too_long).then_some(line.as_slice()));
+                line.clear();
+                too_long = false;
+                if !more {
+                    break;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.log");
+        let long_line = "x".repeat(100);
+        std::fs::write(
+            &path,
+            format!("first\r\nsecond\n{}\nbinary\0data\nlast\n", long_line),
+        )
+        .unwrap();
+
+        let mut lines = Vec::new();
+        let stats = LineScanner::new()
+            .max_line_bytes(50)
+            .scan(&path, |number, line| {
+                lines.push((number, line.to_string()));
+                true
+            })
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (1, "first".to_string()),
+                (2, "second".to_string()),
+                (5, "last".to_string())
+            ]
+        );
+        assert_eq!(
+            stats,
+            ScanStats {
+                lines: 3,
+                skipped_lines: 2
+            }
+        );
+
+        let mut count = 0;
+        LineScanner::new()
+            .scan(&path, |_, _| {
+                count += 1;
+                count < 2
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+
+        // Lines longer than the read buffer are skipped without being held
+        let path = temp_dir.path().join("minified.js");
+        std::fs::write(&path, format!("{}\n{}\r\nend", "x".repeat(40_000), "y".repeat(50))).unwrap();
+        let mut lines = Vec::new();
+        let stats = LineScanner::new()
+            .max_line_bytes(50)
+            .scan(&path, |number, line| {
+                lines.push((number, line.len()));
+                true
+            })
+            .unwrap();
+        assert_eq!(lines, vec![(2, 50), (3, 3)]);
+        assert_eq!(stats.skipped_lines, 1);
+
+        let empty = temp_dir.path().join("empty.log");
+        std::fs::write(&empty, "").unwrap();
+        let stats = LineScanner::new().scan(&empty, |_, _| true).unwrap();
+        assert_eq!(stats, ScanStats::default());
+    }
+}
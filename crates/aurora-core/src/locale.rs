@@ -0,0 +1,256 @@
+//! Response language and locale formatting
+//!
+//! The `[locale]` config section has two settings:
+//! - `language`: the language the agent replies in and UI error messages are
+//!   shown in, as a tag (`de`, `pt-BR`) or an English name (`German`)
+//! - `format`: the locale used for dates and sizes in tool output, such as
+//!   `de-DE`; it falls back to `language`
+//!
+//! With neither set, output keeps the locale-neutral forms: ISO 8601 dates
+//! and `.` as the decimal separator. Timestamps are always shown in UTC.
+//! UI messages are translated for the languages in [`UiMessage`]; others fall
+//! back to English.
+
+use crate::config::LocaleConfig;
+
+/// Languages recognized by tag or English name: (tag, English name)
+const LANGUAGES: &[(&str, &str)] = &[
+    ("de", "German"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("zh", "Chinese"),
+];
+
+/// Languages that write `1,5` rather than `1.5`
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &["de", "es", "fr", "it", "nl", "pl", "pt", "ru", "sv"];
+
+/// ISO 8601 date and time, used when no locale is configured
+const ISO_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A parsed locale
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Locale {
+    /// Lowercase language subtag; empty for the neutral locale
+    language: String,
+    /// Uppercase region subtag
+    region: Option<String>,
+}
+
+impl Locale {
+    /// Parse a tag like `de`, `de-DE`, `pt_BR.UTF-8`, or an English name
+    /// like `German`
+    ///
+    /// Unrecognized names are kept as written so they can still be used as a
+    /// reply language.
+    pub fn parse(tag: &str) -> Self {
+        let tag = tag.trim();
+        // Drop POSIX encoding and modifier suffixes (`de_DE.UTF-8@euro`)
+        let tag = tag.split(['.', '@']).next().unwrap_or(tag);
+
+        if let Some((code, _)) = LANGUAGES.iter().find(|(_, name)| name.eq_ignore_ascii_case(tag)) {
+            return Self {
+                language: code.to_string(),
+                region: None,
+            };
+        }
+
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default().to_lowercase();
+        let region = subtags
+            .find(|subtag| subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(str::to_uppercase);
+        Self { language, region }
+    }
+
+    /// Locale for UI messages: `[locale] language`
+    pub fn for_messages(config: &LocaleConfig) -> Self {
+        config.language.as_deref().map(Self::parse).unwrap_or_default()
+    }
+
+    /// Locale for tool output: `[locale] format`, else `[locale] language`
+    pub fn for_formatting(config: &LocaleConfig) -> Self {
+        config
+            .format
+            .as_deref()
+            .or(config.language.as_deref())
+            .map(Self::parse)
+            .unwrap_or_default()
+    }
+
+    /// Language subtag, empty for the neutral locale
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Region subtag, if any
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// English name of the language, if it is a known one
+    pub fn language_name(&self) -> Option<&'static str> {
+        LANGUAGES
+            .iter()
+            .find(|(code, _)| *code == self.language)
+            .map(|(_, name)| *name)
+    }
+
+    /// Decimal separator for numbers
+    pub fn decimal_separator(&self) -> char {
+        if DECIMAL_COMMA_LANGUAGES.contains(&self.language.as_str()) {
+            ','
+        } else {
+            '.'
+        }
+    }
+
+    /// `strftime`-style pattern for a date and time
+    pub fn date_format(&self) -> &'static str {
+        match (self.language.as_str(), self.region()) {
+            ("en", None | Some("US")) => "%m/%d/%Y %H:%M:%S",
+            ("en", Some(_)) | ("es" | "fr" | "it" | "pt", _) => "%d/%m/%Y %H:%M:%S",
+            ("de" | "pl" | "ru", _) => "%d.%m.%Y %H:%M:%S",
+            ("nl", _) => "%d-%m-%Y %H:%M:%S",
+            ("ja" | "zh", _) => "%Y/%m/%d %H:%M:%S",
+            ("ko", _) => "%Y. %m. %d. %H:%M:%S",
+            _ => ISO_DATE_FORMAT,
+        }
+    }
+
+    /// Format a file size with binary units, e.g. `2.93 KB` or `2,93 KB`
+    pub fn format_size(&self, bytes: u64) -> String {
+        const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+        let mut size = bytes as f64;
+        let mut unit_index = 0;
+
+        while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit_index += 1;
+        }
+
+        if unit_index == 0 {
+            format!("{} {}", bytes, UNITS[0])
+        } else {
+            let number = format!("{:.2}", size).replace('.', &self.decimal_separator().to_string());
+            format!("{} {}", number, UNITS[unit_index])
+        }
+    }
+
+    /// Instruction telling the agent which language to reply in, or `None`
+    /// when no language is configured
+    pub fn response_instruction(config: &LocaleConfig) -> Option<String> {
+        let language = config.language.as_deref().map(str::trim).filter(|language| !language.is_empty())?;
+        let locale = Self::parse(language);
+        let name = match (locale.language_name(), locale.region()) {
+            (Some(name), Some(region)) => format!("{} ({})", name, region),
+            (Some(name), None) => name.to_string(),
+            (None, _) => language.to_string(),
+        };
+        Some(format!(
+            "Reply in {} unless the user writes to you in another language. Keep code, identifiers, \
+             commands, file paths, and quoted tool output in their original form.",
+            name
+        ))
+    }
+
+    /// Translated text of a UI message
+    pub fn message(&self, message: UiMessage) -> &'static str {
+        message.text(&self.language)
+    }
+}
+
+/// UI messages shown to the user, translated for the configured language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiMessage {
+    /// No API key is stored for the project
+    NoApiKey,
+    /// Prefix for an error from the agent
+    Error,
+}
+
+impl UiMessage {
+    /// Text in the given language, falling back to English
+    fn text(self, language: &str) -> &'static str {
+        match (self, language) {
+            (UiMessage::NoApiKey, "de") => {
+                "Kein API-Schlüssel konfiguriert. Bitte hinterlegen Sie Ihren API-Schlüssel in den Einstellungen."
+            }
+            (UiMessage::NoApiKey, "es") => {
+                "No hay ninguna clave de API configurada. Configure su clave de API en Ajustes."
+            }
+            (UiMessage::NoApiKey, "fr") => {
+                "Aucune clé API n'est configurée. Veuillez définir votre clé API dans les Paramètres."
+            }
+            (UiMessage::NoApiKey, "ja") => "APIキーが設定されていません。設定でAPIキーを登録してください。",
+            (UiMessage::NoApiKey, _) => "No API key configured. Please set your API key in Settings.",
+            (UiMessage::Error, "de") => "Fehler",
+            (UiMessage::Error, "fr") => "Erreur",
+            (UiMessage::Error, "ja") => "エラー",
+            (UiMessage::Error, _) => "Error",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(language: Option<&str>, format: Option<&str>) -> LocaleConfig {
+        LocaleConfig {
+            language: language.map(str::to_string),
+            format: format.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_tags_and_names() {
+        let locale = Locale::parse("pt_BR.UTF-8");
+        assert_eq!((locale.language(), locale.region()), ("pt", Some("BR")));
+        assert_eq!(Locale::parse("German").language(), "de");
+        assert_eq!(Locale::parse("zh-Hant-TW").region(), Some("TW"));
+        assert_eq!(Locale::parse("Klingon").language_name(), None);
+    }
+
+    #[test]
+    fn test_formatting() {
+        let neutral = Locale::default();
+        assert_eq!(neutral.format_size(3000), "2.93 KB");
+        assert_eq!(neutral.format_size(512), "512 B");
+        assert_eq!(neutral.date_format(), ISO_DATE_FORMAT);
+
+        let german = Locale::for_formatting(&config(Some("en"), Some("de-DE")));
+        assert_eq!(german.format_size(3000), "2,93 KB");
+        assert_eq!(german.date_format(), "%d.%m.%Y %H:%M:%S");
+        assert_eq!(Locale::parse("en-GB").date_format(), "%d/%m/%Y %H:%M:%S");
+        assert_eq!(Locale::parse("en").date_format(), "%m/%d/%Y %H:%M:%S");
+        assert_eq!(Locale::for_formatting(&config(Some("fr"), None)).decimal_separator(), ',');
+    }
+
+    #[test]
+    fn test_response_instruction_and_messages() {
+        assert_eq!(Locale::response_instruction(&config(None, Some("de"))), None);
+        assert!(Locale::response_instruction(&config(Some("pt-BR"), None))
+            .unwrap()
+            .starts_with("Reply in Portuguese (BR) unless"));
+        assert!(Locale::response_instruction(&config(Some("Esperanto"), None))
+            .unwrap()
+            .starts_with("Reply in Esperanto unless"));
+
+        let french = Locale::for_messages(&config(Some("French"), None));
+        assert_eq!(french.message(UiMessage::Error), "Erreur");
+        assert_eq!(Locale::parse("sv").message(UiMessage::Error), "Error");
+        assert_eq!(
+            Locale::default().message(UiMessage::NoApiKey),
+            "No API key configured. Please set your API key in Settings."
+        );
+    }
+}
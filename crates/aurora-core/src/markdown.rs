@@ -0,0 +1,683 @@
+//! Markdown headings, tables of contents, and link checks
+//!
+//! Headings are read the way GitHub renders them: ATX (`## Title`) and
+//! setext (underlined) headings outside fenced code blocks and front
+//! matter, with anchors slugged as GitHub slugs them, so `#usage-1` names
+//! the second "Usage" heading. A table of contents is kept between
+//! `<!-- toc -->` and `<!-- tocstop -->` markers, the convention of
+//! markdown-toc and most editors.
+//!
+//! Link checks cover links within the repository: relative and
+//! root-relative paths must exist, and `#anchor` fragments must name a
+//! heading or an HTML `id`/`name` in the target file. External URLs are
+//! left alone.
+
+use crate::project_files::{project_files, FileListError};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Marker before a generated table of contents
+pub const TOC_START: &str = "<!-- toc -->";
+
+/// Marker after a generated table of contents
+pub const TOC_END: &str = "<!-- tocstop -->";
+
+/// Errors in finding Markdown files
+#[derive(Error, Debug)]
+pub enum MarkdownError {
+    /// The project's files could not be listed
+    #[error("Could not list project files: {0}")]
+    Files(#[from] FileListError),
+}
+
+/// A heading of a Markdown document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Heading {
+    /// 1 for `#` through 6 for `######`
+    pub level: u8,
+    /// Text as rendered, without link syntax or emphasis markers
+    pub text: String,
+    /// Fragment that links to it, without the `#`
+    pub anchor: String,
+    /// 1-based line
+    pub line: usize,
+}
+
+/// What a Markdown problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkdownIssueKind {
+    /// A link to a file that does not exist
+    BrokenLink,
+    /// A link to an anchor the target does not define
+    BrokenAnchor,
+    /// A heading out of structure
+    Heading,
+}
+
+/// A problem in a Markdown file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MarkdownIssue {
+    /// File, relative to the project root
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: usize,
+    pub kind: MarkdownIssueKind,
+    pub message: String,
+}
+
+impl fmt::Display for MarkdownIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file.display(), self.line, self.message)
+    }
+}
+
+/// Whether `path` is a Markdown file
+pub fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .as_deref(),
+        Some("md" | "markdown" | "mdx")
+    )
+}
+
+/// Lines of `text` with whether each is prose rather than code or front matter
+fn prose_lines(text: &str) -> Vec<(&str, bool)> {
+    let mut fence: Option<String> = None;
+    let mut front_matter = text.starts_with("---\n") || text.starts_with("---\r\n");
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let trimmed = line.trim_start();
+            if front_matter {
+                if i > 0 && line.trim_end() == "---" {
+                    front_matter = false;
+                }
+                return (line, false);
+            }
+            if let Some(open) = &fence {
+                if trimmed.starts_with(open.as_str())
+                    && trimmed
+                        .trim_start_matches(open.chars().next().unwrap())
+                        .trim()
+                        .is_empty()
+                {
+                    fence = None;
+                }
+                return (line, false);
+            }
+            if line.len() - trimmed.len() < 4
+                && (trimmed.starts_with("```") || trimmed.starts_with("~~~"))
+            {
+                let marker = trimmed.chars().next().unwrap();
+                let width = trimmed.chars().take_while(|&c| c == marker).count();
+                fence = Some(marker.to_string().repeat(width));
+                return (line, false);
+            }
+            (line, true)
+        })
+        .collect()
+}
+
+/// Heading text as rendered: link text instead of links, no emphasis or code markers
+fn plain_text(text: &str) -> String {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap());
+    link.replace_all(text, "$1")
+        .chars()
+        .filter(|c| !matches!(c, '`' | '*'))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// The anchor GitHub gives a heading with this text
+pub fn slugify(text: &str) -> String {
+    plain_text(text)
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The headings of a Markdown document
+pub fn headings(text: &str) -> Vec<Heading> {
+    static ATX: OnceLock<Regex> = OnceLock::new();
+    let atx = ATX.get_or_init(|| {
+        Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+(.*?))?(?:[ \t]+#+)?[ \t]*$").unwrap()
+    });
+    let lines = prose_lines(text);
+    let mut headings = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut push = |level: u8, raw: &str, line: usize| {
+        let text = plain_text(raw);
+        let slug = slugify(raw);
+        let count = seen.entry(slug.clone()).or_insert(0);
+        let anchor = if *count == 0 {
+            slug
+        } else {
+            format!("{}-{}", slug, count)
+        };
+        *count += 1;
+        headings.push(Heading {
+            level,
+            text,
+            anchor,
+            line,
+        });
+    };
+
+    for (i, (line, prose)) in lines.iter().enumerate() {
+        if !prose {
+            continue;
+        }
+        if let Some(captures) = atx.captures(line) {
+            let level = captures[1].len() as u8;
+            push(
+                level,
+                captures.get(2).map_or("", |text| text.as_str()),
+                i + 1,
+            );
+            continue;
+        }
+        // A setext underline turns the paragraph line above it into a heading
+        let underline = line.trim();
+        let previous = i.checked_sub(1).map(|p| lines[p]);
+        let setext = match previous {
+            Some((text, true))
+                if !text.trim().is_empty()
+                    && !atx.is_match(text)
+                    && line.len() - line.trim_start().len() < 4 =>
+            {
+                if !underline.is_empty() && underline.chars().all(|c| c == '=') {
+                    Some(1)
+                } else if underline.len() >= 2
+                    && underline.chars().all(|c| c == '-')
+                    && !is_list_item(text)
+                {
+                    Some(2)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if let (Some(level), Some((text, _))) = (setext, previous) {
+            push(level, text.trim(), i);
+        }
+    }
+    headings
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ")
+}
+
+/// A nested list linking to the headings from `min_level` to `max_level`
+pub fn table_of_contents(headings: &[Heading], min_level: u8, max_level: u8) -> String {
+    let mut toc = String::new();
+    for heading in headings {
+        if heading.level < min_level || heading.level > max_level {
+            continue;
+        }
+        if matches!(
+            heading.text.to_lowercase().as_str(),
+            "table of contents" | "contents"
+        ) {
+            continue;
+        }
+        let indent = "  ".repeat((heading.level - min_level) as usize);
+        toc.push_str(&format!(
+            "{}- [{}](#{})\n",
+            indent, heading.text, heading.anchor
+        ));
+    }
+    toc
+}
+
+/// `text` with its table of contents regenerated between the markers, or
+/// inserted after the first top-level heading when it has none
+pub fn refresh_toc(text: &str, min_level: u8, max_level: u8) -> String {
+    let toc = table_of_contents(&headings(text), min_level, max_level);
+    let block = format!("{}\n\n{}\n{}", TOC_START, toc, TOC_END);
+
+    if let (Some(start), Some(end)) = (text.find(TOC_START), text.find(TOC_END)) {
+        if start < end {
+            return format!(
+                "{}{}{}",
+                &text[..start],
+                block,
+                &text[end + TOC_END.len()..]
+            );
+        }
+    }
+
+    let title = headings(text)
+        .into_iter()
+        .find(|heading| heading.level == 1);
+    let insert_after = title.map_or(0, |heading| heading.line);
+    let mut lines: Vec<&str> = text.lines().collect();
+    let at = insert_after.min(lines.len());
+    let block_lines: Vec<&str> = if at == 0 {
+        block.lines().chain([""]).collect()
+    } else {
+        [""].into_iter().chain(block.lines()).collect()
+    };
+    lines.splice(at..at, block_lines);
+    let mut result = lines.join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Structural problems with the headings of `text`: skipped levels,
+/// several top-level headings, empty headings, and `#Heading` lines that
+/// are missing the space that makes them headings
+pub fn lint_headings(file: &Path, text: &str) -> Vec<MarkdownIssue> {
+    static NO_SPACE: OnceLock<Regex> = OnceLock::new();
+    let no_space = NO_SPACE.get_or_init(|| Regex::new(r"^ {0,3}#{1,6}[^#\s!]").unwrap());
+    let issue = |line, message: String| MarkdownIssue {
+        file: file.to_path_buf(),
+        line,
+        kind: MarkdownIssueKind::Heading,
+        message,
+    };
+
+    let mut issues = Vec::new();
+    let mut previous: Option<u8> = None;
+    let mut seen_title = false;
+    for heading in headings(text) {
+        if heading.text.is_empty() {
+            issues.push(issue(heading.line, "empty heading".to_string()));
+        }
+        if let Some(previous) = previous.filter(|&previous| heading.level > previous + 1) {
+            issues.push(issue(
+                heading.line,
+                format!(
+                    "heading level jumps from H{} to H{}; use H{}",
+                    previous,
+                    heading.level,
+                    previous + 1
+                ),
+            ));
+        }
+        if heading.level == 1 {
+            if seen_title {
+                issues.push(issue(
+                    heading.line,
+                    format!("second top-level heading '{}'", heading.text),
+                ));
+            }
+            seen_title = true;
+        }
+        previous = Some(heading.level);
+    }
+    for (i, (line, prose)) in prose_lines(text).iter().enumerate() {
+        if *prose && no_space.is_match(line) {
+            issues.push(issue(
+                i + 1,
+                format!(
+                    "'{}' needs a space after the '#' to be a heading",
+                    line.trim()
+                ),
+            ));
+        }
+    }
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// A link target on a line
+struct Link {
+    target: String,
+    line: usize,
+}
+
+/// Links of `text`, outside code blocks and code spans
+fn links(text: &str) -> Vec<Link> {
+    static INLINE: OnceLock<Regex> = OnceLock::new();
+    static DEFINITION: OnceLock<Regex> = OnceLock::new();
+    static CODE_SPAN: OnceLock<Regex> = OnceLock::new();
+    let inline = INLINE.get_or_init(|| {
+        Regex::new(r#"\]\(\s*(?:<([^>]*)>|([^)\s]+))(?:\s+["'(][^)]*)?\)"#).unwrap()
+    });
+    let definition =
+        DEFINITION.get_or_init(|| Regex::new(r"^ {0,3}\[[^\]]+\]:\s*<?([^\s>]+)>?").unwrap());
+    let code_span = CODE_SPAN.get_or_init(|| Regex::new(r"`+[^`]*`+").unwrap());
+
+    let mut links = Vec::new();
+    for (i, (line, prose)) in prose_lines(text).iter().enumerate() {
+        if !prose {
+            continue;
+        }
+        let line_text = code_span.replace_all(line, "");
+        for captures in inline.captures_iter(&line_text) {
+            if let Some(target) = captures.get(1).or_else(|| captures.get(2)) {
+                links.push(Link {
+                    target: target.as_str().to_string(),
+                    line: i + 1,
+                });
+            }
+        }
+        if let Some(captures) = definition.captures(&line_text) {
+            links.push(Link {
+                target: captures[1].to_string(),
+                line: i + 1,
+            });
+        }
+    }
+    links
+}
+
+/// Anchors `text` defines: its headings and HTML `id` and `name` attributes
+fn anchors(text: &str) -> HashSet<String> {
+    static HTML_ANCHOR: OnceLock<Regex> = OnceLock::new();
+    let html = HTML_ANCHOR.get_or_init(|| {
+        Regex::new(r#"<[a-zA-Z][^>]*\s(?:id|name)\s*=\s*["']([^"']+)["']"#).unwrap()
+    });
+    let mut anchors: HashSet<String> = headings(text)
+        .into_iter()
+        .map(|heading| heading.anchor)
+        .collect();
+    anchors.extend(
+        html.captures_iter(text)
+            .map(|captures| captures[1].to_string()),
+    );
+    anchors
+}
+
+/// Decode `%XX` escapes in a link path
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Checks links across the Markdown files of a project
+pub struct LinkChecker {
+    root: PathBuf,
+    /// Anchors of the files read so far, by path relative to the root
+    anchors: HashMap<PathBuf, HashSet<String>>,
+}
+
+impl LinkChecker {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            anchors: HashMap::new(),
+        }
+    }
+
+    fn anchors_of(&mut self, file: &Path) -> &HashSet<String> {
+        let root = &self.root;
+        self.anchors.entry(file.to_path_buf()).or_insert_with(|| {
+            std::fs::read_to_string(root.join(file))
+                .map(|text| anchors(&text))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Broken links in `file` (relative to the root), whose contents are `text`
+    pub fn check(&mut self, file: &Path, text: &str) -> Vec<MarkdownIssue> {
+        self.anchors.insert(file.to_path_buf(), anchors(text));
+        let mut issues = Vec::new();
+        for link in links(text) {
+            let target = link.target.as_str();
+            // URLs with a scheme, and protocol-relative ones, leave the repository
+            let scheme = target.split_once(':').is_some_and(|(scheme, _)| {
+                !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            });
+            if scheme || target.starts_with("//") {
+                continue;
+            }
+            let (path, anchor) = match target.split_once('#') {
+                Some((path, anchor)) => (path, Some(anchor)),
+                None => (target, None),
+            };
+            let path = path.split('?').next().unwrap_or_default();
+            let path = percent_decode(path);
+
+            let linked = if path.is_empty() {
+                file.to_path_buf()
+            } else {
+                let base = if path.starts_with('/') {
+                    PathBuf::new()
+                } else {
+                    file.parent().map(Path::to_path_buf).unwrap_or_default()
+                };
+                match normalize_relative(&base.join(path.trim_start_matches('/'))) {
+                    Some(linked) => linked,
+                    None => {
+                        issues.push(MarkdownIssue {
+                            file: file.to_path_buf(),
+                            line: link.line,
+                            kind: MarkdownIssueKind::BrokenLink,
+                            message: format!("{} points outside the repository", target),
+                        });
+                        continue;
+                    }
+                }
+            };
+            if !self.root.join(&linked).exists() {
+                issues.push(MarkdownIssue {
+                    file: file.to_path_buf(),
+                    line: link.line,
+                    kind: MarkdownIssueKind::BrokenLink,
+                    message: format!("{} does not exist", target),
+                });
+                continue;
+            }
+            let Some(anchor) = anchor.filter(|anchor| !anchor.is_empty()) else {
+                continue;
+            };
+            if !is_markdown(&linked) {
+                continue;
+            }
+            let anchor = percent_decode(anchor);
+            // GitHub line anchors such as #L10 name lines, not headings
+            let line_anchor = anchor.starts_with('L')
+                && anchor[1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_digit());
+            if !line_anchor
+                && !self.anchors_of(&linked).contains(&anchor.to_lowercase())
+                && !self.anchors_of(&linked).contains(&anchor)
+            {
+                issues.push(MarkdownIssue {
+                    file: file.to_path_buf(),
+                    line: link.line,
+                    kind: MarkdownIssueKind::BrokenAnchor,
+                    message: format!(
+                        "{} has no heading or anchor '#{}'",
+                        linked.display(),
+                        anchor
+                    ),
+                });
+            }
+        }
+        issues
+    }
+}
+
+/// `path` with `.` and `..` resolved, or `None` if it climbs above its root
+fn normalize_relative(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            // Climbing above the root ends the walk
+            Component::ParentDir => normalized.pop().then_some(())?,
+            _ => {}
+        }
+    }
+    Some(normalized)
+}
+
+/// Tracked Markdown files under `paths` (relative to `root`), or by
+/// default those in `docs/` and at the root, less those the project ignores
+pub fn markdown_files(root: &Path, paths: &[PathBuf]) -> Result<Vec<PathBuf>, MarkdownError> {
+    let markdown = project_files(root)?
+        .into_iter()
+        .filter(|file| is_markdown(file));
+    Ok(if paths.is_empty() {
+        markdown
+            .filter(|file| file.starts_with("docs") || file.parent() == Some(Path::new("")))
+            .collect()
+    } else {
+        markdown
+            .filter(|file| {
+                paths
+                    .iter()
+                    .any(|path| path.as_os_str().is_empty() || file.starts_with(path))
+            })
+            .collect()
+    })
+}
+
+/// Broken links and heading problems in Markdown `files` relative to `root`
+pub fn check_markdown(root: &Path, files: &[PathBuf]) -> Vec<MarkdownIssue> {
+    let mut checker = LinkChecker::new(root);
+    let mut issues = Vec::new();
+    for file in files {
+        let Ok(text) = std::fs::read_to_string(root.join(file)) else {
+            continue;
+        };
+        issues.extend(lint_headings(file, &text));
+        issues.extend(checker.check(file, &text));
+    }
+    issues.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const GUIDE: &str = "---\ntitle: Guide\n---\n# Guide\n\n## Install `cli`\n\n```sh\n# not a heading\n```\n\n## Usage\n\n### [Flags](flags.md) & Options\n\nSetup\n-----\n\n## Usage\n";
+
+    #[test]
+    fn test_headings() {
+        let headings = headings(GUIDE);
+        let anchors: Vec<(u8, &str, usize)> = headings
+            .iter()
+            .map(|h| (h.level, h.anchor.as_str(), h.line))
+            .collect();
+        assert_eq!(
+            anchors,
+            vec![
+                (1, "guide", 4),
+                (2, "install-cli", 6),
+                (2, "usage", 12),
+                (3, "flags--options", 14),
+                (2, "setup", 16),
+                (2, "usage-1", 19),
+            ]
+        );
+        assert_eq!(headings[3].text, "Flags & Options");
+    }
+
+    #[test]
+    fn test_refresh_toc() {
+        let text = "# Guide\n\nIntro.\n\n## One\n\n### Nested\n\n## Two\n";
+        let refreshed = refresh_toc(text, 2, 3);
+        assert_eq!(
+            refreshed,
+            "# Guide\n\n<!-- toc -->\n\n- [One](#one)\n  - [Nested](#nested)\n- [Two](#two)\n\n<!-- tocstop -->\n\nIntro.\n\n## One\n\n### Nested\n\n## Two\n"
+        );
+        let renamed = refreshed.replace("## Two", "## Three");
+        assert!(
+            refresh_toc(&renamed, 2, 3).contains("- [Three](#three)\n\n<!-- tocstop -->\n\nIntro.")
+        );
+        assert_eq!(
+            refresh_toc(&refresh_toc(&renamed, 2, 3), 2, 3),
+            refresh_toc(&renamed, 2, 3)
+        );
+    }
+
+    #[test]
+    fn test_lint_headings() {
+        let issues: Vec<String> =
+            lint_headings(Path::new("a.md"), "# A\n\n### Deep\n\n#Tag\n\n# B\n")
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "a.md:3: heading level jumps from H1 to H3; use H2",
+                "a.md:5: '#Tag' needs a space after the '#' to be a heading",
+                "a.md:7: second top-level heading 'B'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/guide.md"), GUIDE).unwrap();
+        std::fs::write(
+            root.join("docs/flags.md"),
+            "# Flags\n\n<a id=\"verbose\"></a>\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("README.md"),
+            "# Project\n\nSee [the guide](docs/guide.md#usage-1), [flags](</docs/flags.md#verbose>),\n\
+             [install](docs/guide.md#installation), [missing](docs/missing.md), `[code](nowhere.md)`,\n\
+             [site](https://example.com/x.md), [top](#project), [up](../outside.md).\n\n[ref]: docs/flags.md#nope\n",
+        )
+        .unwrap();
+
+        let issues: Vec<String> = check_markdown(
+            root,
+            &[PathBuf::from("README.md"), PathBuf::from("docs/guide.md")],
+        )
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "README.md:4: docs/guide.md has no heading or anchor '#installation'",
+                "README.md:4: docs/missing.md does not exist",
+                "README.md:5: ../outside.md points outside the repository",
+                "README.md:7: docs/flags.md has no heading or anchor '#nope'",
+            ]
+        );
+    }
+}
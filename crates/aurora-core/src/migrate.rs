@@ -0,0 +1,218 @@
+//! Database migration runners
+//!
+//! Projects run their migrations with their framework's own command:
+//! `sqlx migrate`, `diesel migration`, `alembic`, `prisma migrate`, or
+//! `knex migrate:*`. [`MigrationTool`] detects which one a project uses
+//! from the files the framework keeps, and turns status, up, and down
+//! requests into its command lines, so the agent never needs to remember
+//! which spelling each framework uses.
+//!
+//! Some frameworks can print the SQL a migration would run without running
+//! it ([`MigrationTool::preview_args`]); for the rest a dry run lists the
+//! pending migrations instead.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors in building migration commands
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MigrateError {
+    /// An unknown framework name
+    #[error("Unknown migration tool '{0}'; expected 'sqlx', 'diesel', 'alembic', 'prisma', or 'knex'")]
+    UnknownTool(String),
+
+    /// An unknown action name
+    #[error("Unknown migration action '{0}'; expected 'status', 'up', or 'down'")]
+    UnknownAction(String),
+
+    /// The framework cannot do what was asked
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+pub type Result<T> = std::result::Result<T, MigrateError>;
+
+/// Migration framework
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationTool {
+    Sqlx,
+    Diesel,
+    Alembic,
+    Prisma,
+    Knex,
+}
+
+/// What to do with the migrations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationAction {
+    /// Show which migrations have run and which are pending
+    Status,
+    /// Apply every pending migration
+    Up,
+    /// Revert the most recent migrations
+    Down,
+}
+
+impl FromStr for MigrationAction {
+    type Err = MigrateError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "status" => Ok(MigrationAction::Status),
+            "up" => Ok(MigrationAction::Up),
+            "down" => Ok(MigrationAction::Down),
+            _ => Err(MigrateError::UnknownAction(s.to_string())),
+        }
+    }
+}
+
+impl MigrationTool {
+    /// The framework's executable
+    pub fn program(&self) -> &'static str {
+        match self {
+            MigrationTool::Sqlx => "sqlx",
+            MigrationTool::Diesel => "diesel",
+            MigrationTool::Alembic => "alembic",
+            MigrationTool::Prisma => "prisma",
+            MigrationTool::Knex => "knex",
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            MigrationTool::Sqlx => "SQLx",
+            MigrationTool::Diesel => "Diesel",
+            MigrationTool::Alembic => "Alembic",
+            MigrationTool::Prisma => "Prisma Migrate",
+            MigrationTool::Knex => "Knex",
+        }
+    }
+
+    /// The framework the project in `dir` uses: a `prisma/schema.prisma`, a
+    /// knexfile, an `alembic.ini`, a `diesel.toml`, or a Cargo manifest
+    /// depending on sqlx next to a `migrations` directory
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("prisma/schema.prisma").exists() || dir.join("schema.prisma").exists() {
+            return Some(MigrationTool::Prisma);
+        }
+        let knexfile = ["js", "ts", "cjs", "mjs"]
+            .iter()
+            .any(|ext| dir.join(format!("knexfile.{}", ext)).exists());
+        if knexfile {
+            return Some(MigrationTool::Knex);
+        }
+        if dir.join("alembic.ini").exists() {
+            return Some(MigrationTool::Alembic);
+        }
+        if dir.join("diesel.toml").exists() {
+            return Some(MigrationTool::Diesel);
+        }
+        let uses_sqlx = std::fs::read_to_string(dir.join("Cargo.toml")).is_ok_and(|manifest| manifest.contains("sqlx"));
+        (uses_sqlx && dir.join("migrations").is_dir()).then_some(MigrationTool::Sqlx)
+    }
+
+    /// Command lines, run in order, that carry out `action`; `steps` is how
+    /// many migrations `Down` reverts
+    pub fn commands(&self, action: MigrationAction, steps: u32) -> Result<Vec<Vec<String>>> {
+        let steps = steps.max(1);
+        let line = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let repeat = |args: &[&str]| vec![line(args); steps as usize];
+        Ok(match (self, action) {
+            (MigrationTool::Sqlx, MigrationAction::Status) => vec![line(&["migrate", "info"])],
+            (MigrationTool::Sqlx, MigrationAction::Up) => vec![line(&["migrate", "run"])],
+            (MigrationTool::Sqlx, MigrationAction::Down) => repeat(&["migrate", "revert"]),
+            (MigrationTool::Diesel, MigrationAction::Status) => vec![line(&["migration", "list"])],
+            (MigrationTool::Diesel, MigrationAction::Up) => vec![line(&["migration", "run"])],
+            (MigrationTool::Diesel, MigrationAction::Down) => repeat(&["migration", "revert"]),
+            (MigrationTool::Alembic, MigrationAction::Status) => {
+                vec![line(&["history", "-r", "current:", "--indicate-current"])]
+            }
+            (MigrationTool::Alembic, MigrationAction::Up) => vec![line(&["upgrade", "head"])],
+            (MigrationTool::Alembic, MigrationAction::Down) => {
+                vec![vec!["downgrade".to_string(), format!("-{}", steps)]]
+            }
+            (MigrationTool::Prisma, MigrationAction::Status) => vec![line(&["migrate", "status"])],
+            (MigrationTool::Prisma, MigrationAction::Up) => vec![line(&["migrate", "deploy"])],
+            (MigrationTool::Prisma, MigrationAction::Down) => {
+                return Err(MigrateError::Unsupported(
+                    "Prisma Migrate has no down migrations. Create a new migration that undoes the change, or mark a failed migration with `prisma migrate resolve --rolled-back <name>`.".to_string(),
+                ));
+            }
+            (MigrationTool::Knex, MigrationAction::Status) => vec![line(&["migrate:list"])],
+            (MigrationTool::Knex, MigrationAction::Up) => vec![line(&["migrate:latest"])],
+            (MigrationTool::Knex, MigrationAction::Down) => repeat(&["migrate:down"]),
+        })
+    }
+
+    /// Arguments that print what `action` would do without doing it, when
+    /// the framework can; `Down` previews only a single step
+    pub fn preview_args(&self, action: MigrationAction) -> Option<Vec<String>> {
+        let args: &[&str] = match (self, action) {
+            (MigrationTool::Sqlx, MigrationAction::Up) => &["migrate", "run", "--dry-run"],
+            (MigrationTool::Sqlx, MigrationAction::Down) => &["migrate", "revert", "--dry-run"],
+            (MigrationTool::Alembic, MigrationAction::Up) => &["upgrade", "head", "--sql"],
+            _ => return None,
+        };
+        Some(args.iter().map(|arg| arg.to_string()).collect())
+    }
+}
+
+impl FromStr for MigrationTool {
+    type Err = MigrateError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "sqlx" => Ok(MigrationTool::Sqlx),
+            "diesel" => Ok(MigrationTool::Diesel),
+            "alembic" => Ok(MigrationTool::Alembic),
+            "prisma" => Ok(MigrationTool::Prisma),
+            "knex" => Ok(MigrationTool::Knex),
+            _ => Err(MigrateError::UnknownTool(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(MigrationTool::detect(dir.path()), None);
+
+        std::fs::write(dir.path().join("Cargo.toml"), "[dependencies]\nsqlx = \"0.8\"\n").unwrap();
+        assert_eq!(MigrationTool::detect(dir.path()), None, "sqlx needs a migrations directory");
+        std::fs::create_dir(dir.path().join("migrations")).unwrap();
+        assert_eq!(MigrationTool::detect(dir.path()), Some(MigrationTool::Sqlx));
+
+        std::fs::write(dir.path().join("diesel.toml"), "").unwrap();
+        assert_eq!(MigrationTool::detect(dir.path()), Some(MigrationTool::Diesel));
+
+        std::fs::write(dir.path().join("knexfile.ts"), "").unwrap();
+        assert_eq!(MigrationTool::detect(dir.path()), Some(MigrationTool::Knex));
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            MigrationTool::Alembic.commands(MigrationAction::Down, 2).unwrap(),
+            vec![vec!["downgrade".to_string(), "-2".to_string()]]
+        );
+        assert_eq!(MigrationTool::Knex.commands(MigrationAction::Down, 3).unwrap().len(), 3);
+        assert_eq!(MigrationTool::Diesel.commands(MigrationAction::Down, 0).unwrap().len(), 1);
+        assert!(matches!(
+            MigrationTool::Prisma.commands(MigrationAction::Down, 1),
+            Err(MigrateError::Unsupported(_))
+        ));
+        assert!(MigrationTool::Sqlx.preview_args(MigrationAction::Up).unwrap().contains(&"--dry-run".to_string()));
+        assert_eq!(MigrationTool::Prisma.preview_args(MigrationAction::Up), None);
+        assert_eq!("Knex".parse::<MigrationTool>(), Ok(MigrationTool::Knex));
+        assert_eq!("sideways".parse::<MigrationAction>(), Err(MigrateError::UnknownAction("sideways".to_string())));
+    }
+}
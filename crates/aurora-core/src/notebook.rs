@@ -0,0 +1,336 @@
+//! Jupyter notebook files and cell outputs
+//!
+//! A [`Notebook`] keeps the parsed `.ipynb` JSON as it is and only reads
+//! and replaces what cell execution touches: a code cell's source, its
+//! outputs, and its execution count. Everything else, including metadata
+//! this module knows nothing about, is written back unchanged.
+//!
+//! [`CellOutput`] mirrors the nbformat 4 output types, which are also the
+//! shapes kernels send results in, so outputs go from the kernel into the
+//! notebook without conversion.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Kernel started for notebooks that name none
+pub const DEFAULT_KERNEL: &str = "python3";
+
+/// Errors in reading or updating a notebook
+#[derive(Error, Debug)]
+pub enum NotebookError {
+    /// The file is not JSON
+    #[error("Invalid notebook JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The JSON is not an nbformat 4 notebook
+    #[error("Not a Jupyter notebook: {0}")]
+    NotANotebook(String),
+
+    /// No cell has the index
+    #[error("Cell {index} does not exist; the notebook has {count} cells")]
+    CellOutOfRange { index: usize, count: usize },
+
+    /// The cell is Markdown or raw text
+    #[error("Cell {0} is not a code cell")]
+    NotCode(usize),
+}
+
+pub type Result<T> = std::result::Result<T, NotebookError>;
+
+/// Kind of notebook cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellKind {
+    Code,
+    Markdown,
+    Raw,
+}
+
+/// A cell of a notebook, for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NotebookCell {
+    pub index: usize,
+    pub kind: CellKind,
+    pub source: String,
+    pub execution_count: Option<u32>,
+}
+
+/// One output of a code cell, in nbformat 4 form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "output_type", rename_all = "snake_case")]
+pub enum CellOutput {
+    /// Text written to stdout or stderr
+    Stream {
+        name: String,
+        #[serde(deserialize_with = "multiline")]
+        text: String,
+    },
+    /// The value of the cell's last expression, by MIME type
+    ExecuteResult {
+        execution_count: Option<u32>,
+        data: BTreeMap<String, Value>,
+        #[serde(default)]
+        metadata: Map<String, Value>,
+    },
+    /// Rich output displayed while the cell ran, e.g. a plot, by MIME type
+    DisplayData {
+        data: BTreeMap<String, Value>,
+        #[serde(default)]
+        metadata: Map<String, Value>,
+    },
+    /// The exception the cell raised
+    Error {
+        ename: String,
+        evalue: String,
+        traceback: Vec<String>,
+    },
+}
+
+/// An image from a cell's rich output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputImage {
+    pub mime: String,
+    /// File extension for saving it, without the dot
+    pub extension: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// Image types with their file extensions; SVG is text, the rest base64
+const IMAGE_TYPES: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/svg+xml", "svg"),
+];
+
+impl CellOutput {
+    /// The output as plain text: stream text, the `text/plain` form of rich
+    /// output, or an error's name, message, and traceback without terminal
+    /// colors. `None` for rich output with no text form, such as a plot.
+    pub fn text(&self) -> Option<String> {
+        match self {
+            CellOutput::Stream { text, .. } => Some(text.clone()),
+            CellOutput::ExecuteResult { data, .. } | CellOutput::DisplayData { data, .. } => {
+                data.get("text/plain").map(joined)
+            }
+            CellOutput::Error { ename, evalue, traceback } => {
+                let mut text = format!("{}: {}\n", ename, evalue);
+                for line in traceback {
+                    text.push_str(&strip_ansi(line));
+                    text.push('\n');
+                }
+                Some(text)
+            }
+        }
+    }
+
+    /// Images in the output, decoded; undecodable ones are skipped
+    pub fn images(&self) -> Vec<OutputImage> {
+        let (CellOutput::ExecuteResult { data, .. } | CellOutput::DisplayData { data, .. }) = self else {
+            return Vec::new();
+        };
+        IMAGE_TYPES
+            .iter()
+            .filter_map(|(mime, extension)| {
+                let content = joined(data.get(*mime)?);
+                let bytes = if *mime == "image/svg+xml" {
+                    content.into_bytes()
+                } else {
+                    let compact: String = content.split_whitespace().collect();
+                    BASE64.decode(compact).ok()?
+                };
+                Some(OutputImage {
+                    mime: mime.to_string(),
+                    extension,
+                    bytes,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Text stored as a string or, as notebooks often store it, a list of lines
+fn joined(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        other => other.to_string(),
+    }
+}
+
+fn multiline<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    Ok(joined(&Value::deserialize(deserializer)?))
+}
+
+/// `text` without ANSI color and cursor sequences, which tracebacks are full of
+pub fn strip_ansi(text: &str) -> String {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").expect("valid regex"))
+        .replace_all(text, "")
+        .into_owned()
+}
+
+/// A parsed `.ipynb` notebook
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notebook {
+    json: Value,
+}
+
+impl Notebook {
+    /// Parse a notebook file's contents
+    pub fn parse(text: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(text)?;
+        if !json["cells"].is_array() {
+            return Err(NotebookError::NotANotebook("no cells list".to_string()));
+        }
+        match json["nbformat"].as_u64() {
+            Some(4) => Ok(Self { json }),
+            Some(version) => Err(NotebookError::NotANotebook(format!(
+                "nbformat {} is not supported; save it as nbformat 4",
+                version
+            ))),
+            None => Err(NotebookError::NotANotebook("no nbformat version".to_string())),
+        }
+    }
+
+    /// The kernel the notebook was written for, from its kernelspec
+    pub fn kernel_name(&self) -> Option<&str> {
+        self.json["metadata"]["kernelspec"]["name"].as_str()
+    }
+
+    /// Every cell, in order
+    pub fn cells(&self) -> Vec<NotebookCell> {
+        self.cell_values()
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| NotebookCell {
+                index,
+                kind: match cell["cell_type"].as_str() {
+                    Some("code") => CellKind::Code,
+                    Some("markdown") => CellKind::Markdown,
+                    _ => CellKind::Raw,
+                },
+                source: joined(&cell["source"]),
+                execution_count: cell["execution_count"].as_u64().map(|count| count as u32),
+            })
+            .collect()
+    }
+
+    /// Source of the code cell at `index`
+    pub fn code(&self, index: usize) -> Result<String> {
+        let cell = self.code_cell(index)?;
+        Ok(joined(&cell["source"]))
+    }
+
+    /// Replace the outputs and execution count of the code cell at `index`
+    pub fn set_outputs(&mut self, index: usize, outputs: &[CellOutput], execution_count: Option<u32>) -> Result<()> {
+        self.code_cell(index)?;
+        let outputs = serde_json::to_value(outputs)?;
+        let cell = &mut self.json["cells"][index];
+        cell["outputs"] = outputs;
+        cell["execution_count"] = execution_count.map_or(Value::Null, Value::from);
+        Ok(())
+    }
+
+    /// The notebook as Jupyter writes it: one-space indentation and a
+    /// trailing newline
+    pub fn to_json(&self) -> String {
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+        self.json
+            .serialize(&mut serializer)
+            .expect("a JSON value always serializes");
+        let mut text = String::from_utf8(out).expect("serde_json writes UTF-8");
+        text.push('\n');
+        text
+    }
+
+    fn cell_values(&self) -> &[Value] {
+        self.json["cells"].as_array().map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn code_cell(&self, index: usize) -> Result<&Value> {
+        let cells = self.cell_values();
+        let cell = cells.get(index).ok_or(NotebookError::CellOutOfRange {
+            index,
+            count: cells.len(),
+        })?;
+        if cell["cell_type"] != "code" {
+            return Err(NotebookError::NotCode(index));
+        }
+        Ok(cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  { "cell_type": "markdown", "metadata": {}, "source": ["# Title\n"] },
+  { "cell_type": "code", "execution_count": null, "metadata": {"tags": ["keep"]}, "outputs": [], "source": ["x = 1\n", "x + 1"] }
+ ],
+ "metadata": { "kernelspec": { "name": "python3", "display_name": "Python 3" } },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_cells_and_outputs() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook.kernel_name(), Some("python3"));
+        assert_eq!(notebook.cells()[0].kind, CellKind::Markdown);
+        assert_eq!(notebook.code(1).unwrap(), "x = 1\nx + 1");
+        assert!(matches!(notebook.code(0), Err(NotebookError::NotCode(0))));
+        assert!(matches!(notebook.code(5), Err(NotebookError::CellOutOfRange { index: 5, count: 2 })));
+
+        let outputs: Vec<CellOutput> = serde_json::from_str(
+            r#"[
+                {"output_type": "stream", "name": "stdout", "text": ["a\n", "b\n"]},
+                {"output_type": "execute_result", "execution_count": 1, "data": {"text/plain": "2"}}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(outputs[0].text().unwrap(), "a\nb\n");
+        notebook.set_outputs(1, &outputs, Some(1)).unwrap();
+
+        let saved = notebook.to_json();
+        assert!(saved.starts_with("{\n \"cells\": [\n  {"));
+        let reparsed = Notebook::parse(&saved).unwrap();
+        assert_eq!(reparsed.cells()[1].execution_count, Some(1));
+        assert!(saved.contains("\"keep\""), "cell metadata is preserved");
+        assert!(saved.contains("\"metadata\": {}"), "outputs get an empty metadata object");
+    }
+
+    #[test]
+    fn test_errors_and_images() {
+        let error = CellOutput::Error {
+            ename: "ValueError".to_string(),
+            evalue: "bad".to_string(),
+            traceback: vec!["\u{1b}[0;31mValueError\u{1b}[0m: bad".to_string()],
+        };
+        assert_eq!(error.text().unwrap(), "ValueError: bad\nValueError: bad\n");
+
+        let plot = CellOutput::DisplayData {
+            data: BTreeMap::from([
+                ("image/png".to_string(), Value::from("iVBO\nRw==")),
+                ("text/plain".to_string(), Value::from("<Figure>")),
+            ]),
+            metadata: Map::new(),
+        };
+        let images = plot.images();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].extension, "png");
+        assert_eq!(images[0].bytes, BASE64.decode("iVBORw==").unwrap());
+        assert_eq!(plot.text().unwrap(), "<Figure>");
+
+        assert!(matches!(Notebook::parse("{\"cells\": [], \"nbformat\": 3}"), Err(NotebookError::NotANotebook(_))));
+    }
+}
@@ -0,0 +1,199 @@
+//! Rich or plain display of tool output
+//!
+//! Tool results are decorated with emoji status markers and drawn with
+//! box-drawing trees, which screen readers read out as symbol names or skip
+//! mid-sentence. [`OutputStyle::Plain`] rewrites them for display: status
+//! markers become words (`✅` → `OK:`, `❌` → `Error:`), decorative emoji are
+//! dropped, and tree lines become ASCII.
+//!
+//! Only what is shown changes. The model sees tool results as the tools
+//! wrote them, so file contents it reads are never altered.
+//!
+//! The style is a per-user setting, kept in `output_style` under
+//! [`user_config_dir`].
+
+use crate::config::user_config_dir;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name of the output style setting in the user config directory
+const OUTPUT_STYLE_FILE: &str = "output_style";
+
+/// Symbols that carry meaning, with the words that replace them
+const SYMBOL_LABELS: &[(char, &str)] = &[
+    ('✅', "OK"),
+    ('✓', "OK"),
+    ('✔', "OK"),
+    ('❌', "Error"),
+    ('✗', "Error"),
+    ('✘', "Error"),
+    ('⚠', "Warning"),
+    ('⏭', "Skipped"),
+    ('⏳', "Pending"),
+    ('⏸', "Paused"),
+    ('🔒', "Security"),
+    ('🆕', "New"),
+];
+
+/// Variation selector that asks for the emoji form of the symbol before it
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+/// How tool output is displayed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStyle {
+    /// As the tools write it, with emoji and box-drawing
+    #[default]
+    Rich,
+    /// Words and ASCII only, for screen readers
+    Plain,
+}
+
+impl OutputStyle {
+    /// `text` in this style
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            OutputStyle::Rich => Cow::Borrowed(text),
+            OutputStyle::Plain => Cow::Owned(plain_text(text)),
+        }
+    }
+
+    /// The style saved at `path`; rich when none is saved
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| contents.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the style at `path`
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, format!("{}\n", self))
+    }
+
+    /// Where the user's style is saved
+    pub fn user_path() -> Option<PathBuf> {
+        user_config_dir().map(|dir| dir.join(OUTPUT_STYLE_FILE))
+    }
+}
+
+impl fmt::Display for OutputStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputStyle::Rich => "rich",
+            OutputStyle::Plain => "plain",
+        })
+    }
+}
+
+impl FromStr for OutputStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rich" => Ok(OutputStyle::Rich),
+            "plain" => Ok(OutputStyle::Plain),
+            other => Err(format!("Unknown output style '{}'; expected 'rich' or 'plain'", other)),
+        }
+    }
+}
+
+/// `text` with status symbols as words, other emoji dropped, and
+/// box-drawing and arrows as ASCII
+pub fn plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == EMOJI_PRESENTATION {
+            continue;
+        }
+        if let Some(ascii) = ascii_line(c) {
+            plain.push_str(ascii);
+            continue;
+        }
+        let label = SYMBOL_LABELS.iter().find(|(symbol, _)| *symbol == c).map(|(_, label)| *label);
+        if label.is_none() && !is_pictograph(c) {
+            plain.push(c);
+            continue;
+        }
+
+        // The spaces that separated the symbol from its text
+        while chars.next_if(|next| *next == ' ' || *next == EMOJI_PRESENTATION).is_some() {}
+        let Some(label) = label else { continue };
+        let rest: String = chars.clone().take(label.len()).collect();
+        // `❌ Error` reads as `Error`, not `Error: Error`
+        if rest.eq_ignore_ascii_case(label) {
+            continue;
+        }
+        plain.push_str(label);
+        match chars.peek() {
+            None | Some('\n') => {}
+            Some(_) => plain.push_str(": "),
+        }
+    }
+    plain
+}
+
+/// ASCII for box-drawing characters and arrows
+fn ascii_line(c: char) -> Option<&'static str> {
+    Some(match c {
+        '→' | '⟶' => "->",
+        '←' | '⟵' => "<-",
+        '─' | '━' | '═' => "-",
+        '│' | '┃' | '║' => "|",
+        '├' | '┣' | '╠' => "|",
+        '└' | '┗' | '╚' => "`",
+        '┌' | '┐' | '┘' | '┬' | '┴' | '┼' | '╔' | '╗' | '╝' | '╦' | '╩' | '╬' => "+",
+        _ => return None,
+    })
+}
+
+/// Whether `c` is an emoji or pictographic symbol
+fn is_pictograph(c: char) -> bool {
+    matches!(c,
+        '\u{2300}'..='\u{23FF}'      // technical symbols: ⏱ ⏳
+        | '\u{25A0}'..='\u{25FF}'    // geometric shapes: ▶ ●
+        | '\u{2600}'..='\u{27BF}'    // miscellaneous symbols and dingbats: ☸ ➖
+        | '\u{2B00}'..='\u{2BFF}'    // arrows and stars: ⭐
+        | '\u{1F000}'..='\u{1FAFF}'  // emoji
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            plain_text("🔒 Security Analysis (Rust)\n✅ No known vulnerabilities found."),
+            "Security Analysis (Rust)\nOK: No known vulnerabilities found."
+        );
+        assert_eq!(plain_text("⚠️  cargo-audit not installed"), "Warning: cargo-audit not installed");
+        assert_eq!(plain_text("[❌ Error: not found]"), "[Error: not found]");
+        assert_eq!(plain_text("☸️  Rendered 3 resource(s)"), "Rendered 3 resource(s)");
+        assert_eq!(plain_text("src/\n├── main.rs\n└── lib.rs"), "src/\n|-- main.rs\n`-- lib.rs");
+        assert_eq!(plain_text("org_id: integer → organizations.id"), "org_id: integer -> organizations.id");
+        assert_eq!(plain_text("passed ✅\nnext"), "passed OK\nnext");
+        assert_eq!(plain_text("naïve café, 日本語"), "naïve café, 日本語");
+    }
+
+    #[test]
+    fn test_style_setting() {
+        assert_eq!(OutputStyle::Rich.apply("✅ done"), "✅ done");
+        assert_eq!(OutputStyle::Plain.apply("✅ done"), "OK: done");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config").join(OUTPUT_STYLE_FILE);
+        assert_eq!(OutputStyle::load(&path), OutputStyle::Rich);
+        OutputStyle::Plain.save(&path).unwrap();
+        assert_eq!(OutputStyle::load(&path), OutputStyle::Plain);
+        assert!("loud".parse::<OutputStyle>().is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Code ownership from CODEOWNERS and git history
+//!
+//! The owners of a file are the teams or people its project's CODEOWNERS
+//! file assigns to it, looked up in the same places GitHub and GitLab look:
+//! `.github/CODEOWNERS`, `CODEOWNERS`, `docs/CODEOWNERS`, and
+//! `.gitlab/CODEOWNERS`. Its frequent contributors come from git history,
+//! for projects with no CODEOWNERS file or files it does not cover.
+//!
+//! CODEOWNERS patterns follow gitignore syntax, and the last matching line
+//! wins:
+//! - a pattern containing `/` other than at its end is anchored to the
+//!   project root; otherwise it matches a file or directory name at any depth
+//! - a pattern matching a directory covers everything under it, except one
+//!   ending in `/*`, which covers only the directory's own files
+//! - a pattern with no owners leaves matching files unowned
+//! - GitLab section headers (`[Docs]`) and `#` comments are skipped
+
+use crate::paths::normalize_path;
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path};
+use std::process::Command;
+use thiserror::Error;
+
+/// Where CODEOWNERS files are looked for, in order
+pub const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+
+/// Commits of a file's history read to find its frequent contributors
+const HISTORY_COMMITS: usize = 200;
+
+/// Contributors listed for a file
+const MAX_CONTRIBUTORS: usize = 3;
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Errors that can occur while reading code owners
+#[derive(Error, Debug)]
+pub enum OwnersError {
+    /// IO error while reading the CODEOWNERS file
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A pattern is not a valid glob
+    #[error("Invalid CODEOWNERS pattern '{pattern}' on line {line}: {message}")]
+    InvalidPattern { line: usize, pattern: String, message: String },
+}
+
+/// Result type for ownership operations
+pub type Result<T> = std::result::Result<T, OwnersError>;
+
+/// One line of a CODEOWNERS file
+#[derive(Debug, Clone)]
+struct OwnerRule {
+    /// The pattern as written
+    source: String,
+    pattern: Pattern,
+    /// Match the whole relative path rather than a single name
+    anchored: bool,
+    /// Match directories only (`pattern/`)
+    dir_only: bool,
+    /// Ends in `/*`, so it does not cover subdirectories
+    shallow: bool,
+    owners: Vec<String>,
+}
+
+/// A parsed CODEOWNERS file
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    /// Path of the file relative to the project root
+    pub file: String,
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    /// Parse the text of a CODEOWNERS file found at `file`
+    pub fn parse(file: impl Into<String>, text: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') || line.starts_with("^[") {
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            let Some(raw) = tokens.next() else {
+                continue;
+            };
+            let owners: Vec<String> = tokens.take_while(|token| !token.starts_with('#')).map(str::to_string).collect();
+
+            let source = raw.strip_prefix('\\').unwrap_or(raw).to_string();
+            let (dir_only, pattern) = match source.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, source.as_str()),
+            };
+            let anchored = pattern.contains('/');
+            let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+            let shallow = pattern.ends_with("/*");
+            let pattern = if pattern.is_empty() { "**" } else { pattern };
+            let pattern = Pattern::new(pattern).map_err(|e| OwnersError::InvalidPattern {
+                line: index + 1,
+                pattern: source.clone(),
+                message: e.to_string(),
+            })?;
+            rules.push(OwnerRule {
+                source,
+                pattern,
+                anchored,
+                dir_only,
+                shallow,
+                owners,
+            });
+        }
+        Ok(Self {
+            file: file.into(),
+            rules,
+        })
+    }
+
+    /// The project's CODEOWNERS file, if it has one
+    pub fn load(project_root: &Path) -> Result<Option<Self>> {
+        for file in CODEOWNERS_PATHS {
+            let path = project_root.join(file);
+            if path.is_file() {
+                let text = std::fs::read_to_string(&path)?;
+                return Self::parse(*file, &text).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The pattern and owners of the last line matching `relative`, a path
+    /// relative to the project root; `None` when no line assigns owners
+    pub fn owners_of(&self, relative: &str) -> Option<(&str, &[String])> {
+        let names: Vec<&str> = relative.split('/').filter(|name| !name.is_empty()).collect();
+        let rule = self.rules.iter().rev().find(|rule| {
+            (0..names.len()).any(|index| {
+                let is_dir = index + 1 < names.len();
+                if rule.dir_only && !is_dir || rule.shallow && index + 1 != names.len() {
+                    return false;
+                }
+                let candidate = if rule.anchored { names[..=index].join("/") } else { names[index].to_string() };
+                rule.pattern.matches_with(&candidate, MATCH_OPTIONS)
+            })
+        })?;
+        (!rule.owners.is_empty()).then_some((rule.source.as_str(), rule.owners.as_slice()))
+    }
+}
+
+/// Someone who committed to a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    /// Commits touching the file among the most recent ones
+    pub commits: usize,
+}
+
+/// Who owns a file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileOwners {
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// Owners assigned by CODEOWNERS
+    pub owners: Vec<String>,
+    /// The CODEOWNERS pattern that assigned them
+    pub rule: Option<String>,
+    /// The CODEOWNERS file the rule is in
+    pub codeowners: Option<String>,
+    /// Frequent recent committers, most commits first
+    pub contributors: Vec<Contributor>,
+}
+
+impl fmt::Display for FileOwners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.rule, &self.codeowners) {
+            (Some(rule), Some(file)) => {
+                write!(f, "{}: owned by {} ({} in {})", self.path, self.owners.join(", "), rule, file)?
+            }
+            _ => write!(f, "{}: no CODEOWNERS entry", self.path)?,
+        }
+        if !self.contributors.is_empty() {
+            let contributors: Vec<String> = self
+                .contributors
+                .iter()
+                .map(|c| format!("{} <{}> ({} commits)", c.name, c.email, c.commits))
+                .collect();
+            write!(f, "\nFrequent contributors: {}", contributors.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The owners of `path` (relative to `project_root` or absolute), from the
+/// project's CODEOWNERS file and, when `history` is set, its git history
+pub fn file_owners(project_root: &Path, path: &Path, history: bool) -> Result<FileOwners> {
+    let relative = relative_path(project_root, path);
+    let mut owners = FileOwners {
+        path: relative.clone(),
+        ..Default::default()
+    };
+    if let Some(codeowners) = CodeOwners::load(project_root)? {
+        if let Some((rule, names)) = codeowners.owners_of(&relative) {
+            owners.owners = names.to_vec();
+            owners.rule = Some(rule.to_string());
+            owners.codeowners = Some(codeowners.file.clone());
+        }
+    }
+    if history {
+        owners.contributors = recent_contributors(project_root, &relative);
+    }
+    Ok(owners)
+}
+
+/// Most frequent authors of the latest commits touching `relative`; empty
+/// outside a git repository
+pub fn recent_contributors(project_root: &Path, relative: &str) -> Vec<Contributor> {
+    let limit = format!("-n{}", HISTORY_COMMITS);
+    let output = Command::new("git")
+        .args(["log", "--no-merges", &limit, "--format=%aN%x09%aE", "--", relative])
+        .current_dir(project_root)
+        .output();
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some((name, email)) = line.split_once('\t') {
+            *counts.entry((name.to_string(), email.to_string())).or_default() += 1;
+        }
+    }
+    let mut contributors: Vec<Contributor> = counts
+        .into_iter()
+        .map(|((name, email), commits)| Contributor { name, email, commits })
+        .collect();
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+    contributors.truncate(MAX_CONTRIBUTORS);
+    contributors
+}
+
+/// `path` relative to `project_root` with `/` separators, as CODEOWNERS
+/// patterns match it
+pub fn relative_path(project_root: &Path, path: &Path) -> String {
+    let path = normalize_path(path);
+    let relative = path.strip_prefix(normalize_path(project_root)).unwrap_or(&path);
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_codeowners_patterns() {
+        let codeowners = CodeOwners::parse(
+            "CODEOWNERS",
+            "# Default owners\n\
+             *       @org/everyone\n\
+             *.rs    @org/rust # inline comment\n\
+             /docs/  @org/docs\n\
+             apps/   @org/apps\n\
+             /build/* @org/build\n\
+             [Generated]\n\
+             /src/generated/\n\
+             \\#notes @org/notes\n",
+        )
+        .unwrap();
+        let owner = |path: &str| codeowners.owners_of(path).map(|(_, owners)| owners.join(" "));
+
+        assert_eq!(owner("README.md").as_deref(), Some("@org/everyone"));
+        assert_eq!(owner("src/lib.rs").as_deref(), Some("@org/rust"));
+        assert_eq!(owner("docs/guide/intro.md").as_deref(), Some("@org/docs"));
+        assert_eq!(owner("web/apps/main.ts").as_deref(), Some("@org/apps"));
+        assert_eq!(owner("build/out.txt").as_deref(), Some("@org/build"));
+        assert_eq!(owner("build/nested/out.txt").as_deref(), Some("@org/everyone"));
+        assert_eq!(owner("src/generated/api.rs"), None);
+        assert_eq!(owner("#notes").as_deref(), Some("@org/notes"));
+        assert_eq!(codeowners.owners_of("src/main.rs").unwrap().0, "*.rs");
+    }
+
+    #[test]
+    fn test_file_owners() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(".github")).unwrap();
+        std::fs::write(root.join(".github/CODEOWNERS"), "/src/ @org/core\n").unwrap();
+
+        let owners = file_owners(root, &root.join("src/lib.rs"), true).unwrap();
+        assert_eq!(owners.path, "src/lib.rs");
+        assert_eq!(owners.owners, vec!["@org/core"]);
+        assert_eq!(owners.codeowners.as_deref(), Some(".github/CODEOWNERS"));
+        assert!(owners.contributors.is_empty());
+        assert_eq!(owners.to_string(), "src/lib.rs: owned by @org/core (/src/ in .github/CODEOWNERS)");
+
+        let unowned = file_owners(root, Path::new("README.md"), false).unwrap();
+        assert!(unowned.owners.is_empty());
+        assert_eq!(unowned.to_string(), "README.md: no CODEOWNERS entry");
+    }
+}
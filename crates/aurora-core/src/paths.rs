@@ -0,0 +1,344 @@
+//! Path normalization
+//!
+//! Paths reach the IDE from the model, the frontend, and the filesystem with
+//! mixed `/` and `\` separators, `.` and `..` components, and differently
+//! cased drive letters, so the same file can show up under several names.
+//! Every tool and Tauri command resolves paths through [`resolve_path`] so a
+//! file has one spelling. Normalization is lexical: it never touches the
+//! filesystem or follows links.
+//!
+//! On Windows, UNC paths (`\\server\share\...`) keep their share prefix,
+//! verbatim prefixes (`\\?\C:\...`) are removed for comparison and display,
+//! and [`long_path`] adds one back for paths too long for the legacy API.
+//!
+//! Recursive walks consult a [`WalkGuard`] before entering an entry, so
+//! symbolic links are skipped unless following them is requested, and even
+//! then a link cannot lead outside the workspace or back into a directory
+//! the walk has already entered.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Longest path the legacy Windows file API accepts without a `\\?\` prefix
+const MAX_LEGACY_PATH: usize = 260;
+
+/// Normalize a path lexically
+///
+/// Separators are unified (to `\` on Windows), `.` components and repeated
+/// separators are dropped, `..` is applied to the preceding component, and
+/// drive letters are upper-cased. A `..` that would climb above the root is
+/// dropped; leading `..` of a relative path is kept.
+pub fn normalize_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref().to_string_lossy();
+    PathBuf::from(normalize_str(&path, cfg!(target_os = "windows")))
+}
+
+/// Resolve `path` against `base` when it is relative, then normalize it
+pub fn resolve_path(base: impl AsRef<Path>, path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if is_absolute_str(&path.to_string_lossy(), cfg!(target_os = "windows")) {
+        normalize_path(path)
+    } else {
+        normalize_path(base.as_ref().join(path))
+    }
+}
+
+/// A key identifying a path for comparison and deduplication
+///
+/// The normalized path, lower-cased on Windows where file names are
+/// case-insensitive.
+pub fn path_key(path: impl AsRef<Path>) -> String {
+    let normalized = normalize_path(path).to_string_lossy().into_owned();
+    if cfg!(target_os = "windows") {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
+
+/// The path to hand to filesystem calls
+///
+/// On Windows, absolute paths longer than the legacy limit get a `\\?\`
+/// (or `\\?\UNC\`) prefix; elsewhere the path is returned unchanged.
+pub fn long_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if !cfg!(target_os = "windows") {
+        return path.to_path_buf();
+    }
+    let normalized = normalize_path(path).to_string_lossy().into_owned();
+    PathBuf::from(long_path_str(&normalized))
+}
+
+fn long_path_str(normalized: &str) -> String {
+    if normalized.len() < MAX_LEGACY_PATH || !is_absolute_str(normalized, true) {
+        normalized.to_string()
+    } else if let Some(unc) = normalized.strip_prefix(r"\\") {
+        format!(r"\\?\UNC\{}", unc)
+    } else {
+        format!(r"\\?\{}", normalized)
+    }
+}
+
+/// Whether `path` is absolute under Windows or POSIX rules
+fn is_absolute_str(path: &str, windows: bool) -> bool {
+    if !windows {
+        return path.starts_with('/');
+    }
+    let bytes = path.as_bytes();
+    let is_sep = |b: Option<&u8>| matches!(b, Some(b'/') | Some(b'\\'));
+    (is_sep(bytes.first()) && is_sep(bytes.get(1)))
+        || (bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && is_sep(bytes.get(2)))
+}
+
+/// Split a Windows path into its prefix (drive or UNC share), whether it is
+/// rooted, and the rest
+fn split_windows_prefix(path: &str) -> (String, bool, &str) {
+    // Verbatim paths: \\?\UNC\server\share\... and \\?\C:\...
+    let path = match path.strip_prefix(r"\\?\") {
+        Some(rest) => match rest.strip_prefix(r"UNC\") {
+            Some(unc) => return split_unc(unc),
+            None => rest,
+        },
+        None => path,
+    };
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return split_unc(unc);
+    }
+
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let drive = format!("{}:", (bytes[0] as char).to_ascii_uppercase());
+        let rest = &path[2..];
+        return (drive, rest.starts_with('\\'), rest);
+    }
+    (String::new(), path.starts_with('\\'), path)
+}
+
+/// Split `server\share\rest` into the `\\server\share` prefix and the rest
+fn split_unc(unc: &str) -> (String, bool, &str) {
+    let mut parts = unc.splitn(3, '\\');
+    let server = parts.next().unwrap_or_default();
+    let share = parts.next().unwrap_or_default();
+    let rest = parts.next().unwrap_or_default();
+    (format!(r"\\{}\{}", server, share), true, rest)
+}
+
+/// Normalize a path string under Windows or POSIX rules
+fn normalize_str(path: &str, windows: bool) -> String {
+    let (prefix, rooted, rest, sep) = if windows {
+        let unified = path.replace('/', "\\");
+        let (prefix, rooted, rest) = split_windows_prefix(&unified);
+        (prefix, rooted, rest.to_string(), '\\')
+    } else {
+        (String::new(), path.starts_with('/'), path.to_string(), '/')
+    };
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in rest.split(sep) {
+        match component {
+            "" | "." => {}
+            ".." => match components.last() {
+                Some(&last) if last != ".." => {
+                    components.pop();
+                }
+                _ if rooted => {}
+                _ => components.push(".."),
+            },
+            _ => components.push(component),
+        }
+    }
+
+    let mut normalized = prefix;
+    if rooted {
+        normalized.push(sep);
+    }
+    normalized.push_str(&components.join(&sep.to_string()));
+    if normalized.is_empty() {
+        normalized.push('.');
+    }
+    normalized
+}
+
+/// Decides which entries a recursive walk may visit
+///
+/// Symbolic links are skipped unless the guard follows them. A followed link
+/// must resolve inside the root, and every directory is entered at most once
+/// by its canonical path, which breaks link cycles. Hard links to files need
+/// no special handling; directories cannot be hard-linked.
+#[derive(Debug, Clone)]
+pub struct WalkGuard {
+    root: PathBuf,
+    follow_symlinks: bool,
+    visited: HashSet<PathBuf>,
+    skipped_links: usize,
+}
+
+impl WalkGuard {
+    /// Create a guard for walks confined to `root`
+    pub fn new(root: impl AsRef<Path>, follow_symlinks: bool) -> Self {
+        let root = root.as_ref();
+        Self {
+            root: std::fs::canonicalize(root).unwrap_or_else(|_| normalize_path(root)),
+            follow_symlinks,
+            visited: HashSet::new(),
+            skipped_links: 0,
+        }
+    }
+
+    /// Whether symbolic links are followed
+    pub fn follows_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// Number of links refused so far
+    pub fn skipped_links(&self) -> usize {
+        self.skipped_links
+    }
+
+    /// Record the directory a walk starts from
+    ///
+    /// The starting point was named explicitly, so it is entered even when it
+    /// is a link; recording it lets links that lead back to it be detected.
+    pub fn start(&mut self, path: &Path) {
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| normalize_path(path));
+        self.visited.insert(canonical);
+    }
+
+    /// Whether the symbolic link at `path` may be followed, without
+    /// recording anything
+    pub fn permits_link(&self, path: &Path) -> bool {
+        self.link_target(path).is_some()
+    }
+
+    /// The canonical target of a link the guard may follow
+    fn link_target(&self, path: &Path) -> Option<PathBuf> {
+        if !self.follow_symlinks {
+            return None;
+        }
+        std::fs::canonicalize(path)
+            .ok()
+            .filter(|target| target.starts_with(&self.root))
+    }
+
+    /// Whether the walk may visit `path`
+    ///
+    /// Directories are recorded as visited, so asking again for the same
+    /// directory (under any name) returns false. Missing paths and dangling
+    /// links are refused.
+    pub fn allows(&mut self, path: &Path) -> bool {
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return false;
+        };
+
+        if metadata.file_type().is_symlink() {
+            let Some(target) = self.link_target(path) else {
+                self.skipped_links += 1;
+                return false;
+            };
+            if target.is_dir() && !self.visited.insert(target) {
+                self.skipped_links += 1;
+                return false;
+            }
+            true
+        } else if metadata.is_dir() {
+            let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| normalize_path(path));
+            self.visited.insert(canonical)
+        } else {
+            true
+        }
+    }
+}
+
+/// Whether `path` is itself a symbolic link (without following it)
+pub fn is_symlink(path: impl AsRef<Path>) -> bool {
+    std::fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_posix() {
+        assert_eq!(normalize_str("/home/me/./project//src/../lib.rs", false), "/home/me/project/lib.rs");
+        assert_eq!(normalize_str("src/", false), "src");
+        assert_eq!(normalize_str("../a/./b/..", false), "../a");
+        assert_eq!(normalize_str("/../etc", false), "/etc");
+        assert_eq!(normalize_str("./", false), ".");
+        assert_eq!(normalize_str(r"dir\file", false), r"dir\file");
+    }
+
+    #[test]
+    fn test_normalize_windows() {
+        assert_eq!(normalize_str("c:/Users/me/project/src/../main.rs", true), r"C:\Users\me\project\main.rs");
+        assert_eq!(normalize_str(r"C:\Users\me\\project\", true), r"C:\Users\me\project");
+        assert_eq!(normalize_str(r"\\?\c:\Users\me", true), r"C:\Users\me");
+        assert_eq!(normalize_str("//server/share/dir/../file.txt", true), r"\\server\share\file.txt");
+        assert_eq!(normalize_str(r"\\?\UNC\server\share\dir", true), r"\\server\share\dir");
+        assert_eq!(normalize_str("src/lib.rs", true), r"src\lib.rs");
+        assert_eq!(normalize_str(r"C:\..\Windows", true), r"C:\Windows");
+    }
+
+    #[test]
+    fn test_absolute_and_long_paths() {
+        assert!(is_absolute_str("C:/Users", true));
+        assert!(is_absolute_str(r"\\server\share", true));
+        assert!(!is_absolute_str("C:relative", true));
+        assert!(!is_absolute_str("/usr", true));
+        assert!(is_absolute_str("/usr", false));
+
+        let long = format!(r"C:\{}", "a".repeat(300));
+        assert_eq!(long_path_str(&long), format!(r"\\?\{}", long));
+        let long_unc = format!(r"\\server\share\{}", "a".repeat(300));
+        assert_eq!(long_path_str(&long_unc), format!(r"\\?\UNC\server\share\{}", "a".repeat(300)));
+        assert_eq!(long_path_str(r"C:\short"), r"C:\short");
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let base = if cfg!(target_os = "windows") { r"C:\project" } else { "/project" };
+        assert_eq!(resolve_path(base, "src/./main.rs"), normalize_path(Path::new(base).join("src").join("main.rs")));
+        assert_eq!(resolve_path(base, "../other"), normalize_path(Path::new(base).parent().unwrap().join("other")));
+        assert_eq!(path_key(resolve_path(base, "a/../b")), path_key(Path::new(base).join("b")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_guard_links() {
+        use std::os::unix::fs::symlink;
+
+        let outside = tempfile::TempDir::new().unwrap();
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        symlink(root.join("src"), root.join("src/nested/loop")).unwrap();
+        symlink(outside.path(), root.join("escape")).unwrap();
+        symlink(root.join("src/lib.rs"), root.join("lib_link.rs")).unwrap();
+        symlink(root.join("missing"), root.join("dangling")).unwrap();
+
+        let mut guard = WalkGuard::new(root, false);
+        guard.start(root);
+        assert!(!guard.allows(root), "the start is already entered");
+        assert!(guard.allows(&root.join("src")));
+        assert!(guard.allows(&root.join("src/lib.rs")));
+        assert!(!guard.allows(&root.join("src/nested/loop")));
+        assert!(!guard.allows(&root.join("lib_link.rs")));
+        assert!(!guard.allows(&root.join("src")), "directories are entered once");
+        assert_eq!(guard.skipped_links(), 2);
+
+        let mut guard = WalkGuard::new(root, true);
+        assert!(guard.follows_symlinks());
+        assert!(guard.allows(&root.join("src")));
+        assert!(!guard.allows(&root.join("src/nested/loop")), "cycle back into src");
+        assert!(guard.allows(&root.join("lib_link.rs")));
+        assert!(!guard.allows(&root.join("escape")), "target outside the root");
+        assert!(!guard.allows(&root.join("dangling")));
+        assert!(guard.permits_link(&root.join("lib_link.rs")));
+        assert!(!guard.permits_link(&root.join("escape")));
+        assert!(is_symlink(root.join("escape")));
+        assert!(!is_symlink(root.join("src")));
+    }
+}
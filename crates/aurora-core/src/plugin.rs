@@ -3,8 +3,9 @@
 //! This module provides the plugin architecture that allows extending AuroraHeart
 //! with custom agents, commands, and session hooks through `.AuroraHeart/plugins/`.
 
+use crate::config::FileIconDefinition;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -42,6 +43,34 @@ pub type Result<T> = std::result::Result<T, PluginError>;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginMetadata {
     pub plugin: PluginInfo,
+
+    /// Command palette actions contributed by the plugin (`[[actions]]`)
+    #[serde(default)]
+    pub actions: Vec<ActionDefinition>,
+
+    /// File tree and tab icons contributed by the plugin
+    /// (`[file_icons.<icon>]`)
+    #[serde(default)]
+    pub file_icons: BTreeMap<String, FileIconDefinition>,
+}
+
+/// A command palette action contributed by a plugin
+///
+/// Running the action sends the plugin's slash command `command`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionDefinition {
+    /// Identifier, unique within the plugin
+    pub id: String,
+    /// Title shown in the command palette
+    pub title: String,
+    /// Palette category; defaults to the plugin name
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Slash command to run (without the `/`)
+    pub command: String,
+    /// Suggested key binding, e.g. `ctrl+alt+r`
+    #[serde(default)]
+    pub keybinding: Option<String>,
 }
 
 /// Core plugin information
@@ -397,6 +426,12 @@ author = "Test Author"
 
 [plugin.dependencies]
 required_tools = ["read", "write"]
+
+[[actions]]
+id = "run-test-command"
+title = "Run Test Command"
+command = "test-command"
+keybinding = "ctrl+alt+t"
 "#;
         fs::write(plugin_dir.join("plugin.toml"), plugin_toml)?;
 
@@ -448,6 +483,8 @@ stop_on_error = true
         assert_eq!(plugin.commands.len(), 1);
         assert!(plugin.agents.contains_key("test-agent"));
         assert!(plugin.commands.contains_key("test-command"));
+        assert_eq!(plugin.metadata.actions.len(), 1);
+        assert_eq!(plugin.metadata.actions[0].command, "test-command");
 
         // Cleanup
         let _ = fs::remove_dir_all(&temp_dir);
@@ -0,0 +1,163 @@
+//! CPU and memory limits for child processes
+//!
+//! Callers set the limits up with [`ProcessLimits::apply`] before spawning
+//! and finish with [`ProcessLimits::attach`] once the child is running.
+//!
+//! On Unix the limits are set with `setrlimit` in the child before it runs
+//! its program, and carry over to the commands it starts. Memory is capped
+//! by the data segment rather than the address space, which V8's large
+//! virtual reservations exceed at startup.
+//!
+//! On Windows the child is assigned to a job object limiting each of its
+//! processes' user-mode CPU time and committed memory; processes it starts
+//! join the job too. The child runs briefly before it is assigned, so a
+//! process it starts at once may escape the limits.
+//!
+//! Other platforms have no equivalent here: [`ProcessLimits::ENFORCED`] is
+//! false there and both calls do nothing, leaving callers only their
+//! timeouts.
+
+/// Limits applied to a child process; `None` leaves a resource unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessLimits {
+    /// CPU time the process may use, in seconds
+    pub cpu_seconds: Option<u64>,
+    /// Heap and other private data the process may allocate, in bytes
+    pub memory_bytes: Option<u64>,
+}
+
+impl ProcessLimits {
+    /// Whether the limits are enforced on this platform
+    pub const ENFORCED: bool = cfg!(any(unix, windows));
+
+    /// Limit only memory, to `bytes`
+    pub fn memory(bytes: u64) -> Self {
+        Self {
+            cpu_seconds: None,
+            memory_bytes: Some(bytes),
+        }
+    }
+
+    /// Apply the limits to the process `command` starts, where the platform
+    /// can before it runs
+    pub fn apply(&self, command: &mut tokio::process::Command) {
+        #[cfg(unix)]
+        {
+            let limits: Vec<_> = [(libc::RLIMIT_CPU, self.cpu_seconds), (libc::RLIMIT_DATA, self.memory_bytes)]
+                .into_iter()
+                .filter_map(|(resource, limit)| {
+                    let limit = limit? as libc::rlim_t;
+                    Some((
+                        resource,
+                        libc::rlimit {
+                            rlim_cur: limit,
+                            rlim_max: limit,
+                        },
+                    ))
+                })
+                .collect();
+            if limits.is_empty() {
+                return;
+            }
+            // SAFETY: only async-signal-safe setrlimit calls run between fork
+            // and exec, and they touch only the child
+            unsafe {
+                command.pre_exec(move || {
+                    for (resource, limit) in &limits {
+                        if libc::setrlimit(*resource, limit) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = command;
+        }
+    }
+
+    /// Apply the limits to `child`, started from a command they were
+    /// [applied](Self::apply) to
+    ///
+    /// Only Windows needs this step; an error means the child runs without
+    /// its limits.
+    pub fn attach(&self, child: &tokio::process::Child) -> std::io::Result<()> {
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::Foundation::CloseHandle;
+            use windows_sys::Win32::System::JobObjects::{
+                AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+                SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_PROCESS_MEMORY,
+                JOB_OBJECT_LIMIT_PROCESS_TIME,
+            };
+
+            // SAFETY: the structure is plain data, valid when zeroed
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+            if let Some(seconds) = self.cpu_seconds {
+                // In 100-nanosecond ticks
+                info.BasicLimitInformation.PerProcessUserTimeLimit =
+                    i64::try_from(seconds.saturating_mul(10_000_000)).unwrap_or(i64::MAX);
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+            }
+            if let Some(bytes) = self.memory_bytes {
+                info.ProcessMemoryLimit = usize::try_from(bytes).unwrap_or(usize::MAX);
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+            }
+            // A child that already exited has no handle and nothing to limit
+            let Some(process) = child.raw_handle() else {
+                return Ok(());
+            };
+            if info.BasicLimitInformation.LimitFlags == 0 {
+                return Ok(());
+            }
+            // SAFETY: the job handle is checked before use and closed once;
+            // the process handle stays valid while `child` lives
+            unsafe {
+                let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if job.is_null() {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let assigned = SetInformationJobObject(
+                    job,
+                    JobObjectExtendedLimitInformation,
+                    std::ptr::from_ref(&info).cast(),
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                ) != 0
+                    && AssignProcessToJobObject(job, process) != 0;
+                let error = std::io::Error::last_os_error();
+                // The job, and its limits, last while the child is in it
+                CloseHandle(job);
+                if !assigned {
+                    return Err(error);
+                }
+            }
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = child;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_apply_to_child() {
+        let limits = ProcessLimits {
+            cpu_seconds: Some(30),
+            memory_bytes: Some(512 * 1024 * 1024),
+        };
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg("ulimit -t; ulimit -d");
+        limits.apply(&mut command);
+
+        let output = command.output().await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "30\n524288\n");
+    }
+}
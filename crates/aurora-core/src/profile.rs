@@ -0,0 +1,585 @@
+//! Sampling profiler integration
+//!
+//! A command is run under `perf`, DTrace, or `py-spy`, chosen per project
+//! language through the `[profile]` section of `.AuroraHeart/config.toml`.
+//! The sampled call stacks are normalized into folded stacks
+//! (`root;caller;leaf count`), written to `.AuroraHeart/profiles/` for the UI
+//! to render as a flamegraph, and summarized as the top hotspots.
+
+use crate::config::{Config, ProfilerKind};
+use crate::project::{detect_language, Language};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Name of the folded stacks artifact in each profile directory
+pub const FOLDED_STACKS_FILE: &str = "stacks.folded";
+
+/// Number of output lines kept when a profiler fails
+const MAX_FAILURE_LINES: usize = 40;
+
+/// Errors that can occur while profiling
+#[derive(Error, Debug)]
+pub enum ProfileError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The profiler could not be started
+    #[error("Failed to start {profiler}: {message} (is it installed and on PATH?)")]
+    ProfilerUnavailable {
+        /// Profiler binary
+        profiler: String,
+        /// Launch error
+        message: String,
+    },
+
+    /// The profiler ran but collected no samples
+    #[error("No samples were collected by {profiler}\n{output}")]
+    NoSamples {
+        /// Profiler binary
+        profiler: String,
+        /// Tail of the profiler output
+        output: String,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, ProfileError>;
+
+impl ProfilerKind {
+    /// Name of the profiler binary
+    pub fn binary(&self) -> &'static str {
+        match self {
+            ProfilerKind::Perf => "perf",
+            ProfilerKind::Dtrace => "dtrace",
+            ProfilerKind::PySpy => "py-spy",
+        }
+    }
+
+    /// Default profiler for a language on the current platform
+    pub fn default_for(language: Option<Language>) -> Self {
+        match language {
+            Some(Language::Python) => ProfilerKind::PySpy,
+            _ if cfg!(target_os = "linux") => ProfilerKind::Perf,
+            _ => ProfilerKind::Dtrace,
+        }
+    }
+}
+
+/// One sampled call stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack {
+    /// Frames from the outermost caller to the sampled function
+    pub frames: Vec<String>,
+    /// Number of samples with this stack
+    pub samples: u64,
+}
+
+/// A function ranked by the samples spent in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hotspot {
+    /// Function (frame) name
+    pub function: String,
+    /// Samples where the function was executing
+    pub self_samples: u64,
+    /// Samples where the function was on the stack
+    pub total_samples: u64,
+    /// Self samples as a percentage of all samples
+    pub self_percent: f64,
+    /// Total samples as a percentage of all samples
+    pub total_percent: f64,
+}
+
+/// Summary of a profiling run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileReport {
+    /// Profiler used
+    pub profiler: ProfilerKind,
+    /// Profiled command
+    pub command: String,
+    /// Exit code of the profiler (and usually the command), if it exited normally
+    pub exit_code: Option<i32>,
+    /// Number of samples collected
+    pub total_samples: u64,
+    /// Hottest functions by self samples
+    pub hotspots: Vec<Hotspot>,
+    /// Folded stacks file for flamegraph rendering
+    pub artifact: PathBuf,
+}
+
+impl ProfileReport {
+    /// Format the report for display
+    pub fn format(&self) -> String {
+        let mut output = format!(
+            "Profiled `{}` with {} ({} samples)\n",
+            self.command,
+            self.profiler.binary(),
+            self.total_samples
+        );
+        if let Some(code) = self.exit_code.filter(|code| *code != 0) {
+            output.push_str(&format!("⚠️ Command exited with code {}\n", code));
+        }
+
+        output.push_str("\n  self%  total%  function\n");
+        for hotspot in &self.hotspots {
+            output.push_str(&format!(
+                "{:>6.1}% {:>6.1}%  {}\n",
+                hotspot.self_percent, hotspot.total_percent, hotspot.function
+            ));
+        }
+
+        output.push_str(&format!("\nFlamegraph data: {}\n", self.artifact.display()));
+        output
+    }
+}
+
+/// Strip an address offset such as `+0x1f` from a symbol
+fn strip_offset(symbol: &str) -> &str {
+    match symbol.rfind("+0x") {
+        Some(index) if index > 0 => &symbol[..index],
+        _ => symbol,
+    }
+}
+
+/// Parse `perf script` output
+pub fn parse_perf_script(output: &str) -> Vec<Stack> {
+    let mut stacks = Vec::new();
+    let mut frames: Vec<String> = Vec::new();
+    let mut in_sample = false;
+
+    let mut finish = |frames: &mut Vec<String>, in_sample: &mut bool| {
+        if *in_sample && !frames.is_empty() {
+            let mut stack = std::mem::take(frames);
+            stack.reverse();
+            stacks.push(Stack {
+                frames: stack,
+                samples: 1,
+            });
+        }
+        frames.clear();
+        *in_sample = false;
+    };
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            finish(&mut frames, &mut in_sample);
+        } else if line.starts_with(char::is_whitespace) {
+            // "<address> <symbol>+<offset> (<dso>)"
+            let frame = line.trim();
+            let frame = frame.split_once(' ').map(|(_, rest)| rest).unwrap_or(frame);
+            let symbol = match frame.rfind(" (") {
+                Some(index) => &frame[..index],
+                None => frame,
+            };
+            frames.push(strip_offset(symbol).to_string());
+        } else {
+            finish(&mut frames, &mut in_sample);
+            in_sample = true;
+        }
+    }
+    finish(&mut frames, &mut in_sample);
+
+    merge(stacks)
+}
+
+/// Parse the `@[ustack()] = count()` aggregation printed by DTrace
+pub fn parse_dtrace_stacks(output: &str) -> Vec<Stack> {
+    let mut stacks = Vec::new();
+    let mut frames: Vec<String> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            frames.clear();
+        } else if let Ok(samples) = line.parse::<u64>() {
+            if !frames.is_empty() {
+                let mut stack = std::mem::take(&mut frames);
+                stack.reverse();
+                stacks.push(Stack {
+                    frames: stack,
+                    samples,
+                });
+            }
+        } else if line.starts_with("CPU") && line.contains("FUNCTION") {
+            frames.clear();
+        } else {
+            // "module`function+0x1f"
+            let symbol = line.split_once('`').map(|(_, f)| f).unwrap_or(line);
+            frames.push(strip_offset(symbol).to_string());
+        }
+    }
+
+    merge(stacks)
+}
+
+/// Parse folded stacks (`root;caller;leaf count`), as written by `py-spy --format raw`
+///
+/// Line numbers in `py-spy` frames (`func (file.py:12)`) are dropped so all
+/// samples of a function are attributed to one frame.
+pub fn parse_folded(output: &str) -> Vec<Stack> {
+    let stacks = output
+        .lines()
+        .filter_map(|line| {
+            let (stack, samples) = line.trim().rsplit_once(' ')?;
+            let samples = samples.parse().ok()?;
+            let frames = stack.split(';').map(strip_line_number).collect();
+            Some(Stack { frames, samples })
+        })
+        .collect();
+    merge(stacks)
+}
+
+fn strip_line_number(frame: &str) -> String {
+    if let Some(body) = frame.strip_suffix(')') {
+        if let Some((location, line)) = body.rsplit_once(':') {
+            if !line.is_empty() && line.chars().all(|c| c.is_ascii_digit()) {
+                return format!("{})", location);
+            }
+        }
+    }
+    frame.to_string()
+}
+
+/// Combine identical stacks
+fn merge(stacks: Vec<Stack>) -> Vec<Stack> {
+    let mut merged: BTreeMap<Vec<String>, u64> = BTreeMap::new();
+    for stack in stacks {
+        *merged.entry(stack.frames).or_default() += stack.samples;
+    }
+    merged
+        .into_iter()
+        .map(|(frames, samples)| Stack { frames, samples })
+        .collect()
+}
+
+/// Render stacks in the folded format used by flamegraph tools
+pub fn to_folded(stacks: &[Stack]) -> String {
+    stacks
+        .iter()
+        .map(|stack| {
+            let frames: Vec<String> = stack.frames.iter().map(|f| f.replace(';', ":")).collect();
+            format!("{} {}\n", frames.join(";"), stack.samples)
+        })
+        .collect()
+}
+
+/// Rank functions by self samples, returning the total sample count and the top `top_n`
+pub fn hotspots(stacks: &[Stack], top_n: usize) -> (u64, Vec<Hotspot>) {
+    let total: u64 = stacks.iter().map(|s| s.samples).sum();
+    let mut self_samples: BTreeMap<&str, u64> = BTreeMap::new();
+    let mut total_samples: BTreeMap<&str, u64> = BTreeMap::new();
+
+    for stack in stacks {
+        if let Some(leaf) = stack.frames.last() {
+            *self_samples.entry(leaf).or_default() += stack.samples;
+        }
+        // Recursive functions count once per stack
+        let unique: BTreeSet<&str> = stack.frames.iter().map(String::as_str).collect();
+        for function in unique {
+            *total_samples.entry(function).or_default() += stack.samples;
+        }
+    }
+
+    let percent = |samples: u64| {
+        if total == 0 {
+            0.0
+        } else {
+            samples as f64 * 100.0 / total as f64
+        }
+    };
+    let mut ranked: Vec<Hotspot> = self_samples
+        .into_iter()
+        .map(|(function, samples)| {
+            let inclusive = total_samples.get(function).copied().unwrap_or(samples);
+            Hotspot {
+                function: function.to_string(),
+                self_samples: samples,
+                total_samples: inclusive,
+                self_percent: percent(samples),
+                total_percent: percent(inclusive),
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.self_samples
+            .cmp(&a.self_samples)
+            .then_with(|| a.function.cmp(&b.function))
+    });
+    ranked.truncate(top_n);
+
+    (total, ranked)
+}
+
+/// Runs commands under a sampling profiler
+pub struct Profiler {
+    project_root: PathBuf,
+    kind: ProfilerKind,
+    frequency: u32,
+    top_n: usize,
+}
+
+impl Profiler {
+    /// Create a profiler with default sampling settings
+    pub fn new<P: AsRef<Path>>(project_root: P, kind: ProfilerKind) -> Self {
+        let defaults = crate::config::ProfileConfig::default();
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            kind,
+            frequency: defaults.frequency,
+            top_n: defaults.top_n,
+        }
+    }
+
+    /// Create a profiler for a project, choosing the profiler by its language
+    pub fn for_project<P: AsRef<Path>>(project_root: P, config: &Config) -> Self {
+        let project_root = project_root.as_ref();
+        let language = detect_language(project_root).ok();
+        let kind = language
+            .and_then(|language| config.profile.profilers.get(language.as_str()))
+            .copied()
+            .unwrap_or_else(|| ProfilerKind::default_for(language));
+
+        Self {
+            frequency: config.profile.frequency,
+            top_n: config.profile.top_n,
+            ..Self::new(project_root, kind)
+        }
+    }
+
+    /// Use a different profiler
+    pub fn with_kind(mut self, kind: ProfilerKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Set the number of hotspots in the summary
+    pub fn top_n(mut self, top_n: usize) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    /// The profiler that will be used
+    pub fn kind(&self) -> ProfilerKind {
+        self.kind
+    }
+
+    /// Profile a shell command run from the project root
+    pub fn run(&self, command: &str) -> Result<ProfileReport> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let dir = self
+            .project_root
+            .join(".AuroraHeart")
+            .join("profiles")
+            .join(format!("{}-{}", stamp, self.kind.binary()));
+        std::fs::create_dir_all(&dir)?;
+
+        let (output, stacks) = match self.kind {
+            ProfilerKind::Perf => {
+                let data = dir.join("perf.data");
+                let output = self.spawn(
+                    Command::new("perf")
+                        .arg("record")
+                        .arg("-F")
+                        .arg(self.frequency.to_string())
+                        .arg("-g")
+                        .arg("-o")
+                        .arg(&data)
+                        .args(["--", "sh", "-c", command]),
+                )?;
+                let script = self.spawn(Command::new("perf").arg("script").arg("-i").arg(&data))?;
+                let stacks = parse_perf_script(&String::from_utf8_lossy(&script.stdout));
+                (output, stacks)
+            }
+            ProfilerKind::Dtrace => {
+                let raw = dir.join("dtrace.out");
+                let probe = format!(
+                    "profile-{} /pid == $target/ {{ @[ustack()] = count(); }}",
+                    self.frequency
+                );
+                let output = self.spawn(
+                    Command::new("dtrace")
+                        .args(["-x", "ustackframes=100", "-n", &probe, "-o"])
+                        .arg(&raw)
+                        .arg("-c")
+                        .arg(command),
+                )?;
+                let stacks =
+                    parse_dtrace_stacks(&std::fs::read_to_string(&raw).unwrap_or_default());
+                (output, stacks)
+            }
+            ProfilerKind::PySpy => {
+                let raw = dir.join("py-spy.folded");
+                let output = self.spawn(
+                    Command::new("py-spy")
+                        .args(["record", "--subprocesses", "--format", "raw", "--rate"])
+                        .arg(self.frequency.to_string())
+                        .arg("-o")
+                        .arg(&raw)
+                        .args(["--", "sh", "-c", command]),
+                )?;
+                let stacks = parse_folded(&std::fs::read_to_string(&raw).unwrap_or_default());
+                (output, stacks)
+            }
+        };
+
+        if stacks.is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let lines: Vec<&str> = stderr.lines().collect();
+            return Err(ProfileError::NoSamples {
+                profiler: self.kind.binary().to_string(),
+                output: lines[lines.len().saturating_sub(MAX_FAILURE_LINES)..].join("\n"),
+            });
+        }
+
+        let artifact = dir.join(FOLDED_STACKS_FILE);
+        std::fs::write(&artifact, to_folded(&stacks))?;
+        let (total_samples, hotspots) = hotspots(&stacks, self.top_n);
+
+        Ok(ProfileReport {
+            profiler: self.kind,
+            command: command.to_string(),
+            exit_code: output.status.code(),
+            total_samples,
+            hotspots,
+            artifact,
+        })
+    }
+
+    /// Run a profiler command in the project root
+    fn spawn(&self, command: &mut Command) -> Result<Output> {
+        command
+            .current_dir(&self.project_root)
+            .output()
+            .map_err(|e| ProfileError::ProfilerUnavailable {
+                profiler: self.kind.binary().to_string(),
+                message: e.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_perf_script() {
+        let output = "\
+myprog 1234 100.000001:     1010101 cycles:u:
+\t    55d4c1a2b3c4 parse_line+0x34 (/tmp/myprog)
+\t    55d4c1a2b000 main+0x10 (/tmp/myprog)
+\t    7f0000000000 __libc_start_main+0xf3 (/usr/lib/libc.so.6)
+
+myprog 1234 100.000002:     1010101 cycles:u:
+\t    55d4c1a2b3c4 parse_line+0x38 (/tmp/myprog)
+\t    55d4c1a2b000 main+0x10 (/tmp/myprog)
+\t    7f0000000000 __libc_start_main+0xf3 (/usr/lib/libc.so.6)
+
+myprog 1234 100.000003:     1010101 cycles:u:
+\t    55d4c1a2b000 main+0x20 (/tmp/myprog)
+\t    7f0000000000 __libc_start_main+0xf3 (/usr/lib/libc.so.6)
+";
+        let stacks = parse_perf_script(output);
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(stacks[0].frames, vec!["__libc_start_main", "main"]);
+        assert_eq!(
+            stacks[1].frames,
+            vec!["__libc_start_main", "main", "parse_line"]
+        );
+        assert_eq!(stacks[1].samples, 2);
+    }
+
+    #[test]
+    fn test_parse_dtrace_stacks() {
+        let output = "\
+CPU     ID                    FUNCTION:NAME
+
+              libsystem_c.dylib`strlen+0x12
+              myprog`main+0x20
+               7
+
+              myprog`main+0x30
+               3
+";
+        let stacks = parse_dtrace_stacks(output);
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(stacks[0].frames, vec!["main"]);
+        assert_eq!(stacks[0].samples, 3);
+        assert_eq!(stacks[1].frames, vec!["main", "strlen"]);
+        assert_eq!(stacks[1].samples, 7);
+    }
+
+    #[test]
+    fn test_parse_folded_and_hotspots() {
+        let output = "\
+<module> (app.py:1);main (app.py:10);parse (app.py:20) 6
+<module> (app.py:1);main (app.py:12);parse (app.py:21) 2
+<module> (app.py:1);main (app.py:14) 2
+";
+        let stacks = parse_folded(output);
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(
+            to_folded(&stacks),
+            "<module> (app.py);main (app.py) 2\n<module> (app.py);main (app.py);parse (app.py) 8\n"
+        );
+
+        let (total, hotspots) = hotspots(&stacks, 5);
+        assert_eq!(total, 10);
+        assert_eq!(hotspots.len(), 2);
+        assert_eq!(hotspots[0].function, "parse (app.py)");
+        assert_eq!(hotspots[0].self_percent, 80.0);
+        assert_eq!(hotspots[1].function, "main (app.py)");
+        assert_eq!(hotspots[1].self_samples, 2);
+        assert_eq!(hotspots[1].total_percent, 100.0);
+    }
+
+    #[test]
+    fn test_profiler_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("requirements.txt"), "").unwrap();
+
+        let mut config = Config::default();
+        let profiler = Profiler::for_project(temp_dir.path(), &config);
+        assert_eq!(profiler.kind(), ProfilerKind::PySpy);
+
+        config
+            .profile
+            .profilers
+            .insert("python".to_string(), ProfilerKind::Dtrace);
+        let profiler = Profiler::for_project(temp_dir.path(), &config);
+        assert_eq!(profiler.kind(), ProfilerKind::Dtrace);
+        assert_eq!(
+            profiler.with_kind(ProfilerKind::Perf).kind(),
+            ProfilerKind::Perf
+        );
+    }
+
+    #[test]
+    fn test_report_format() {
+        let report = ProfileReport {
+            profiler: ProfilerKind::Perf,
+            command: "./target/release/app".to_string(),
+            exit_code: Some(0),
+            total_samples: 10,
+            hotspots: vec![Hotspot {
+                function: "parse".to_string(),
+                self_samples: 8,
+                total_samples: 9,
+                self_percent: 80.0,
+                total_percent: 90.0,
+            }],
+            artifact: PathBuf::from(".AuroraHeart/profiles/1-perf/stacks.folded"),
+        };
+
+        let text = report.format();
+        assert!(text.starts_with("Profiled `./target/release/app` with perf (10 samples)"));
+        assert!(text.contains("  80.0%   90.0%  parse\n"));
+        assert!(text.contains("Flamegraph data: .AuroraHeart/profiles/1-perf/stacks.folded"));
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides utilities for detecting project types, languages,
 //! and finding project roots.
 
+use crate::paths::normalize_path;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -20,6 +21,14 @@ pub enum ProjectError {
     /// Language could not be detected
     #[error("Could not detect project language")]
     LanguageUnknown,
+
+    /// The folder to open does not exist
+    #[error("Folder not found: {0}")]
+    NotFound(PathBuf),
+
+    /// The path to open is not a folder
+    #[error("Not a folder: {0}")]
+    NotADirectory(PathBuf),
 }
 
 /// Programming languages supported by AuroraHeart
@@ -226,6 +235,21 @@ pub fn find_project_root<P: AsRef<Path>>(start_path: P) -> Result<PathBuf, Proje
     }
 }
 
+/// Check that `path` can be opened as a project
+///
+/// The folder has to exist and be listable. Returns the normalized path.
+pub fn validate_project_root<P: AsRef<Path>>(path: P) -> Result<PathBuf, ProjectError> {
+    let path = normalize_path(path);
+    if !path.exists() {
+        return Err(ProjectError::NotFound(path));
+    }
+    if !path.is_dir() {
+        return Err(ProjectError::NotADirectory(path));
+    }
+    std::fs::read_dir(&path)?;
+    Ok(path)
+}
+
 /// Get the project name from the directory name
 pub fn get_project_name<P: AsRef<Path>>(project_root: P) -> Option<String> {
     project_root
@@ -346,6 +370,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_project_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "notes").unwrap();
+
+        assert_eq!(
+            validate_project_root(temp_dir.path().join(".")).unwrap(),
+            normalize_path(temp_dir.path())
+        );
+        assert!(matches!(validate_project_root(&file), Err(ProjectError::NotADirectory(_))));
+        assert!(matches!(
+            validate_project_root(temp_dir.path().join("missing")),
+            Err(ProjectError::NotFound(_))
+        ));
+    }
+
     #[test]
     fn test_get_project_name() {
         let path = PathBuf::from("/home/user/my-project");
@@ -0,0 +1,169 @@
+//! Listing the files of a project
+//!
+//! [`tracked_files`] lists the files git tracks under a directory, or every
+//! file under it outside a git repository, skipping `.git`, `target`,
+//! `node_modules`, and build output. [`project_files`] also leaves out the
+//! paths hidden by the project's ignore rules (`[project] ignore` and
+//! `.auroraignore`), and is what the tools looking for files of one kind,
+//! such as Markdown documents, API specs, or migrations, start from.
+//!
+//! Secret scanning lists with [`tracked_files`] instead, so ignore rules
+//! meant to keep files from the model cannot hide a committed secret.
+
+use crate::config::{Config, ConfigError};
+use crate::ignore::{IgnoreError, IgnoreRules};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Directories that are never walked when listing outside of git
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules", "dist", "build"];
+
+/// Errors that can occur while listing project files
+#[derive(Error, Debug)]
+pub enum FileListError {
+    /// IO error while walking the directory tree
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Git command failed
+    #[error("Git error: {0}")]
+    Git(String),
+
+    /// The project configuration could not be loaded
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+
+    /// The ignore rules could not be loaded
+    #[error("Ignore rules error: {0}")]
+    Ignore(#[from] IgnoreError),
+}
+
+/// Result type for file listing
+pub type Result<T> = std::result::Result<T, FileListError>;
+
+/// Files of the project at `root` not hidden by its ignore rules, relative
+/// to the root
+pub fn project_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let config = Config::load(root)?;
+    let ignore = IgnoreRules::load(root, &config)?;
+    Ok(tracked_files(root)?
+        .into_iter()
+        .filter(|file| !ignore.is_ignored(file, false))
+        .collect())
+}
+
+/// List files tracked by git, relative to the root
+///
+/// If the root is not inside a git repository, all files under the root are
+/// returned instead (skipping `.git`, `target`, `node_modules`, and build output).
+pub fn tracked_files<P: AsRef<Path>>(root: P) -> Result<Vec<PathBuf>> {
+    let root = root.as_ref();
+    match git_file_list(root, &["ls-files", "-z"]) {
+        Ok(files) => Ok(files),
+        Err(FileListError::Git(e)) => {
+            tracing::debug!("git ls-files unavailable ({}), walking directory instead", e);
+            let mut files = Vec::new();
+            walk_files(root, root, &mut files)?;
+            files.sort();
+            Ok(files)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// List files staged for commit, relative to the root
+pub fn staged_files<P: AsRef<Path>>(root: P) -> Result<Vec<PathBuf>> {
+    git_file_list(
+        root.as_ref(),
+        &["diff", "--cached", "--name-only", "--relative", "--diff-filter=ACMR", "-z"],
+    )
+}
+
+fn git_file_list(root: &Path, args: &[&str]) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| FileListError::Git(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(FileListError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+fn walk_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            if SKIPPED_DIRS.iter().any(|skip| name == *skip) {
+                continue;
+            }
+            walk_files(root, &path, files)?;
+        } else if file_type.is_file() {
+            if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_files_honor_ignore_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for file in ["README.md", "docs/guide.md", "generated/api.md", "node_modules/dep/README.md"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "# Title\n").unwrap();
+        }
+        fs::write(root.join(".auroraignore"), "generated/\n").unwrap();
+
+        let mut tracked = tracked_files(root).unwrap();
+        tracked.retain(|file| file.extension().is_some_and(|ext| ext == "md"));
+        assert_eq!(tracked.len(), 3);
+
+        let files = project_files(root).unwrap();
+        assert!(files.contains(&PathBuf::from("README.md")));
+        assert!(files.contains(&Path::new("docs").join("guide.md")));
+        assert!(!files.iter().any(|file| file.starts_with("generated")));
+    }
+
+    #[test]
+    fn test_staged_files_relative_to_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git").args(args).current_dir(temp_dir.path()).output().unwrap().status;
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        fs::create_dir_all(temp_dir.path().join("app/src")).unwrap();
+        fs::write(temp_dir.path().join("app/src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(temp_dir.path().join("README.md"), "# Title\n").unwrap();
+        git(&["add", "."]);
+
+        let root = temp_dir.path().join("app");
+        let expected = vec![Path::new("src").join("main.rs")];
+        assert_eq!(staged_files(&root).unwrap(), expected);
+        assert_eq!(tracked_files(&root).unwrap(), expected);
+    }
+}
@@ -0,0 +1,191 @@
+//! Files bulk replacement leaves alone
+//!
+//! A project-wide search and replace should not rewrite lockfiles, minified
+//! bundles, vendored dependencies, or generated code: the next install or
+//! codegen run would undo the edit, or the edit would corrupt a file a tool
+//! owns. Files are protected by path, using [`DEFAULT_PROTECTED_PATTERNS`]
+//! plus the `[replace] exclude` patterns (in `.auroraignore` syntax, so
+//! `!Cargo.lock` re-includes a default), or by content, when a generated
+//! marker appears near the top of the file or the file holds unresolved
+//! merge conflicts in either the two-way or diff3 style.
+
+use crate::config::ReplaceConfig;
+use crate::ignore::{IgnoreRules, Result};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Paths protected unless `[replace] default_exclusions` is off
+pub const DEFAULT_PROTECTED_PATTERNS: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Cargo.lock",
+    "Gemfile.lock",
+    "poetry.lock",
+    "composer.lock",
+    "go.sum",
+    "*.min.js",
+    "*.min.css",
+    "*.map",
+    "vendor/",
+    "node_modules/",
+    "third_party/",
+];
+
+/// Markers that identify generated files, matched case-insensitively
+pub const DEFAULT_GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "do not edit",
+    "code generated by",
+    "auto-generated",
+    "autogenerated",
+];
+
+/// Number of leading lines searched for a generated marker
+const MARKER_LINES: usize = 5;
+
+/// Why a file was left alone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", content = "detail", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The path matches a protected pattern
+    Protected,
+    /// The file carries a generated marker
+    Generated(String),
+    /// The file has unresolved merge conflicts
+    MergeConflict,
+    /// Matching the pattern in the file took too long
+    MatchTimeout,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Protected => write!(f, "protected path"),
+            SkipReason::Generated(marker) => write!(f, "generated file ({})", marker),
+            SkipReason::MergeConflict => write!(f, "unresolved merge conflict"),
+            SkipReason::MatchTimeout => write!(f, "pattern matching timed out"),
+        }
+    }
+}
+
+/// A file left alone and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+/// Decides which files under a project root bulk replacement skips
+#[derive(Debug, Clone)]
+pub struct ProtectedFiles {
+    rules: IgnoreRules,
+    markers: Vec<String>,
+}
+
+impl ProtectedFiles {
+    /// Protection for files under `root` configured by `config`
+    pub fn new(root: impl Into<PathBuf>, config: &ReplaceConfig) -> Result<Self> {
+        let defaults = if config.default_exclusions {
+            DEFAULT_PROTECTED_PATTERNS
+        } else {
+            &[]
+        };
+        let patterns = defaults.iter().map(|p| p.to_string()).chain(config.exclude.iter().cloned());
+        let markers = if config.skip_generated {
+            DEFAULT_GENERATED_MARKERS
+                .iter()
+                .map(|m| m.to_string())
+                .chain(config.generated_markers.iter().map(|m| m.to_lowercase()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            rules: IgnoreRules::new(root, patterns)?,
+            markers,
+        })
+    }
+
+    /// Whether `path` is protected by a pattern
+    pub fn is_protected(&self, path: &Path, is_dir: bool) -> bool {
+        self.rules.is_ignored(path, is_dir)
+    }
+
+    /// Why a file with `content` should be left alone, if it should
+    pub fn content_skip_reason(&self, content: &str) -> Option<SkipReason> {
+        let header = content.lines().take(MARKER_LINES).collect::<Vec<_>>().join("\n").to_lowercase();
+        if let Some(marker) = self.markers.iter().find(|marker| header.contains(marker.as_str())) {
+            return Some(SkipReason::Generated(marker.clone()));
+        }
+        has_conflict_markers(content).then_some(SkipReason::MergeConflict)
+    }
+}
+
+/// Whether `content` holds an unresolved merge conflict
+///
+/// Recognizes two-way conflicts (`<<<<<<<`, `=======`, `>>>>>>>`) and diff3
+/// conflicts, which add a `|||||||` section with the common ancestor.
+pub fn has_conflict_markers(content: &str) -> bool {
+    #[derive(PartialEq)]
+    enum State {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut state = State::Outside;
+    for line in content.lines() {
+        state = match state {
+            State::Outside if line.starts_with("<<<<<<< ") || line == "<<<<<<<" => State::Ours,
+            State::Ours if line.starts_with("|||||||") => State::Base,
+            State::Ours | State::Base if line == "=======" => State::Theirs,
+            State::Theirs if line.starts_with(">>>>>>> ") || line == ">>>>>>>" => return true,
+            state => state,
+        };
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protected_paths() {
+        let root = Path::new("/project");
+        let protected = ProtectedFiles::new(root, &ReplaceConfig::default()).unwrap();
+        assert!(protected.is_protected(&root.join("package-lock.json"), false));
+        assert!(protected.is_protected(&root.join("web/dist/app.min.js"), false));
+        assert!(protected.is_protected(&root.join("vendor"), true));
+        assert!(!protected.is_protected(&root.join("src/main.rs"), false));
+
+        let config = ReplaceConfig {
+            exclude: vec!["!Cargo.lock".to_string(), "fixtures/".to_string()],
+            ..ReplaceConfig::default()
+        };
+        let protected = ProtectedFiles::new(root, &config).unwrap();
+        assert!(!protected.is_protected(&root.join("Cargo.lock"), false));
+        assert!(protected.is_protected(&root.join("tests/fixtures"), true));
+    }
+
+    #[test]
+    fn test_content_skip_reason() {
+        let protected = ProtectedFiles::new("/project", &ReplaceConfig::default()).unwrap();
+        assert_eq!(
+            protected.content_skip_reason("// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"),
+            Some(SkipReason::Generated("do not edit".to_string()))
+        );
+        assert_eq!(protected.content_skip_reason("fn main() {}\n"), None);
+
+        let two_way = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\nb\n";
+        let diff3 = "<<<<<<< HEAD\nours\n||||||| base\noriginal\n=======\ntheirs\n>>>>>>> feature\n";
+        assert_eq!(protected.content_skip_reason(two_way), Some(SkipReason::MergeConflict));
+        assert_eq!(protected.content_skip_reason(diff3), Some(SkipReason::MergeConflict));
+        assert!(!has_conflict_markers("=======\nMarkdown heading underline\n"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Recently opened projects
+//!
+//! The list is kept per user in `recent_projects.json` under
+//! [`user_config_dir`], most recent first, so any project can be reopened
+//! from the start screen or the File menu. Paths are compared with
+//! [`path_key`], so the same directory reached through different spellings
+//! is listed once.
+
+use crate::config::user_config_dir;
+use crate::paths::{normalize_path, path_key};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Maximum number of recent projects remembered
+pub const MAX_RECENT_PROJECTS: usize = 10;
+
+/// File name of the recent projects list in the user config directory
+const RECENT_PROJECTS_FILE: &str = "recent_projects.json";
+
+/// Errors that can occur while loading or saving the recent projects list
+#[derive(Error, Debug)]
+pub enum RecentProjectsError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// List could not be parsed or written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, RecentProjectsError>;
+
+/// A project in the recent list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentProject {
+    /// Project root
+    pub path: PathBuf,
+    /// Display name (the directory name)
+    pub name: String,
+    /// When the project was last opened, in milliseconds since the Unix epoch
+    pub opened_at: u64,
+}
+
+/// Recently opened projects, most recent first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentProjects {
+    #[serde(default)]
+    pub projects: Vec<RecentProject>,
+}
+
+impl RecentProjects {
+    /// Move `path` to the front of the list, adding it if it is new
+    pub fn record(&mut self, path: impl AsRef<Path>) {
+        let path = normalize_path(path);
+        self.remove(&path);
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
+        self.projects.insert(
+            0,
+            RecentProject {
+                path,
+                name,
+                opened_at: now_millis(),
+            },
+        );
+        self.projects.truncate(MAX_RECENT_PROJECTS);
+    }
+
+    /// Remove `path` from the list
+    ///
+    /// Returns true if it was listed.
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> bool {
+        let key = path_key(path);
+        let before = self.projects.len();
+        self.projects.retain(|project| path_key(&project.path) != key);
+        self.projects.len() < before
+    }
+
+    /// Drop projects whose directory no longer exists
+    pub fn prune_missing(&mut self) {
+        self.projects.retain(|project| project.path.is_dir());
+    }
+}
+
+/// Loads and saves the recent projects list
+pub struct RecentProjectsStore {
+    path: PathBuf,
+}
+
+impl RecentProjectsStore {
+    /// The current user's list, or `None` if there is no config directory
+    pub fn for_user() -> Option<Self> {
+        user_config_dir().map(|dir| Self::at(dir.join(RECENT_PROJECTS_FILE)))
+    }
+
+    /// A list stored at `path`
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the list file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the list, or an empty list if none has been saved
+    pub fn load(&self) -> Result<RecentProjects> {
+        if !self.path.exists() {
+            return Ok(RecentProjects::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(&self.path)?)?)
+    }
+
+    /// Save the list
+    pub fn save(&self, recent: &RecentProjects) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(recent)?)?;
+        Ok(())
+    }
+
+    /// Load, modify, and save the list
+    pub fn update(&self, f: impl FnOnce(&mut RecentProjects)) -> Result<RecentProjects> {
+        let mut recent = self.load()?;
+        f(&mut recent);
+        self.save(&recent)?;
+        Ok(recent)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_moves_to_front_and_caps() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut recent = RecentProjects::default();
+        for i in 0..MAX_RECENT_PROJECTS + 2 {
+            recent.record(temp_dir.path().join(format!("project-{}", i)));
+        }
+        assert_eq!(recent.projects.len(), MAX_RECENT_PROJECTS);
+        assert_eq!(recent.projects[0].name, format!("project-{}", MAX_RECENT_PROJECTS + 1));
+
+        let again = temp_dir.path().join("project-5").join(".");
+        recent.record(&again);
+        assert_eq!(recent.projects.len(), MAX_RECENT_PROJECTS);
+        assert_eq!(recent.projects[0].name, "project-5");
+        assert_eq!(
+            recent.projects.iter().filter(|p| p.name == "project-5").count(),
+            1,
+            "the same directory is listed once"
+        );
+
+        assert!(recent.remove(temp_dir.path().join("project-5")));
+        assert!(!recent.remove(temp_dir.path().join("project-5")));
+    }
+
+    #[test]
+    fn test_store_round_trip_and_prune() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RecentProjectsStore::at(temp_dir.path().join("config").join(RECENT_PROJECTS_FILE));
+        assert!(store.load().unwrap().projects.is_empty());
+
+        let existing = temp_dir.path().join("existing");
+        std::fs::create_dir(&existing).unwrap();
+        let saved = store
+            .update(|recent| {
+                recent.record(temp_dir.path().join("deleted"));
+                recent.record(&existing);
+            })
+            .unwrap();
+        assert_eq!(store.load().unwrap(), saved);
+
+        let mut loaded = store.load().unwrap();
+        loaded.prune_missing();
+        assert_eq!(loaded.projects.len(), 1);
+        assert_eq!(loaded.projects[0].path, normalize_path(&existing));
+    }
+}
@@ -0,0 +1,333 @@
+//! Trying out a regular expression on sample text
+//!
+//! A pattern that matches more or less than intended does the most damage
+//! in a bulk replacement. [`test_regex`] runs a pattern over sample text
+//! the way `grep` and `multi_replace` do, with the same engine, and reports
+//! each match with its position and capture groups, what a replacement
+//! would turn it into, and the mistakes that are easy to miss: a pattern
+//! that also matches empty text, a replacement naming a group the pattern
+//! does not have, and `$1x`, which reads as the group named `1x`.
+//!
+//! Patterns come from the model, so [`build_regex`] also bounds how large
+//! and deeply nested they may be, and callers give up on a file once
+//! matching a line of it takes longer than [`MATCH_TIMEOUT`].
+
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+use std::time::Duration;
+
+/// Longest pattern accepted, in bytes
+pub const MAX_PATTERN_LEN: usize = 4096;
+
+/// Largest compiled program a pattern may produce, in bytes
+pub const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Largest cache of lazily built matching states, in bytes
+pub const REGEX_DFA_SIZE_LIMIT: usize = 2 << 20;
+
+/// Deepest nesting of groups and repetitions
+pub const REGEX_NEST_LIMIT: u32 = 64;
+
+/// How long matching one line may take before its file is skipped
+pub const MATCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Flags a pattern is compiled with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegexOptions {
+    pub case_insensitive: bool,
+    /// `^` and `$` match at line boundaries
+    pub multiline: bool,
+    /// `.` also matches `\n`
+    pub dot_matches_newline: bool,
+}
+
+/// Compile `pattern` with `options`, within the size and nesting limits
+pub fn build_regex(pattern: &str, options: RegexOptions) -> Result<Regex, regex::Error> {
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(regex::Error::Syntax(format!(
+            "pattern is {} bytes long; the limit is {} bytes",
+            pattern.len(),
+            MAX_PATTERN_LEN
+        )));
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(options.case_insensitive)
+        .multi_line(options.multiline)
+        .dot_matches_new_line(options.dot_matches_newline)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+        .nest_limit(REGEX_NEST_LIMIT)
+        .build()
+}
+
+/// One capture group of a match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupMatch {
+    /// Group number, from 1
+    pub index: usize,
+    pub name: Option<String>,
+    /// Captured text; `None` when the group did not take part in the match
+    pub text: Option<String>,
+}
+
+/// One match of the pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexMatch {
+    /// Line of the match's start, from 1
+    pub line: usize,
+    /// Column of the match's start, from 1, in characters
+    pub column: usize,
+    pub text: String,
+    pub groups: Vec<GroupMatch>,
+    /// The match after replacement, when a replacement was given
+    pub replaced: Option<String>,
+}
+
+/// Matches of a pattern in sample text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexTestReport {
+    /// The first matches, up to the limit
+    pub matches: Vec<RegexMatch>,
+    /// How many matches there are in all
+    pub total: usize,
+    /// Lines with a match, in all
+    pub lines: usize,
+    pub warnings: Vec<String>,
+}
+
+/// Run `pattern` over `text`, keeping the first `limit` matches and
+/// expanding `replacement` for each as `multi_replace` would
+pub fn test_regex(
+    pattern: &str,
+    options: RegexOptions,
+    text: &str,
+    replacement: Option<&str>,
+    limit: usize,
+) -> Result<RegexTestReport, regex::Error> {
+    let regex = build_regex(pattern, options)?;
+    let names: Vec<Option<&str>> = regex.capture_names().collect();
+    let mut matches = Vec::new();
+    let mut total = 0;
+    let mut empty = 0;
+    let mut lines = Vec::new();
+
+    for captures in regex.captures_iter(text) {
+        let whole = captures.get(0).expect("group 0 always matches");
+        total += 1;
+        if whole.as_str().is_empty() {
+            empty += 1;
+        }
+        let before = &text[..whole.start()];
+        let line = before.matches('\n').count() + 1;
+        if lines.last() != Some(&line) {
+            lines.push(line);
+        }
+        if matches.len() >= limit {
+            continue;
+        }
+
+        let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        let groups = (1..captures.len())
+            .map(|index| GroupMatch {
+                index,
+                name: names[index].map(str::to_string),
+                text: captures.get(index).map(|group| group.as_str().to_string()),
+            })
+            .collect();
+        let replaced = replacement.map(|replacement| {
+            let mut replaced = String::new();
+            captures.expand(replacement, &mut replaced);
+            replaced
+        });
+        matches.push(RegexMatch {
+            line,
+            column,
+            text: whole.as_str().to_string(),
+            groups,
+            replaced,
+        });
+    }
+
+    let mut warnings = Vec::new();
+    if empty > 0 {
+        warnings.push(format!(
+            "The pattern matches empty text ({} of {} matches); a replacement would insert at each of those positions. Use + instead of * or anchor the pattern if that is not intended.",
+            empty, total
+        ));
+    }
+    if let Some(replacement) = replacement {
+        warnings.extend(replacement_warnings(&regex, replacement));
+    }
+    let anchored = pattern.starts_with('^') || (pattern.ends_with('$') && !pattern.ends_with("\\$"));
+    if total == 0 && anchored && !options.multiline && text.contains('\n') {
+        warnings.push(
+            "^ and $ match only at the start and end of the whole text; set multiline to match at each line.".to_string(),
+        );
+    }
+
+    Ok(RegexTestReport {
+        matches,
+        total,
+        lines: lines.len(),
+        warnings,
+    })
+}
+
+/// Groups a replacement refers to that the pattern does not have
+fn replacement_warnings(regex: &Regex, replacement: &str) -> Vec<String> {
+    let names: Vec<&str> = regex.capture_names().flatten().collect();
+    let groups = regex.captures_len();
+    let mut warnings = Vec::new();
+    let mut rest = replacement;
+    while let Some(dollar) = rest.find('$') {
+        rest = &rest[dollar + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+            continue;
+        }
+        let (reference, braced) = match rest.strip_prefix('{') {
+            Some(inner) => match inner.find('}') {
+                Some(end) => (&inner[..end], true),
+                None => continue,
+            },
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], false)
+            }
+        };
+        if reference.is_empty() {
+            continue;
+        }
+        let digits = reference.chars().take_while(char::is_ascii_digit).count();
+        if digits == reference.len() {
+            if reference.parse::<usize>().is_ok_and(|index| index >= groups) {
+                warnings.push(format!(
+                    "The replacement refers to group ${} but the pattern has only {} group(s); it will be replaced with nothing.",
+                    reference,
+                    groups - 1
+                ));
+            }
+        } else if digits > 0 && !braced {
+            warnings.push(format!(
+                "${} is read as a group named '{}', which replaces with nothing; write ${{{}}}{} to follow group {} with '{}'.",
+                reference,
+                reference,
+                &reference[..digits],
+                &reference[digits..],
+                &reference[..digits],
+                &reference[digits..]
+            ));
+        } else if !names.contains(&reference) {
+            warnings.push(format!(
+                "The replacement refers to a group named '{}' that the pattern does not have; it will be replaced with nothing.",
+                reference
+            ));
+        }
+    }
+    warnings
+}
+
+impl fmt::Display for RegexTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total == 0 {
+            writeln!(f, "No matches.")?;
+        } else {
+            writeln!(f, "{} match(es) on {} line(s):", self.total, self.lines)?;
+        }
+        for (number, m) in self.matches.iter().enumerate() {
+            writeln!(f, "\n{}. line {}, column {}: {:?}", number + 1, m.line, m.column, m.text)?;
+            for group in &m.groups {
+                let label = match &group.name {
+                    Some(name) => format!("${} ({})", group.index, name),
+                    None => format!("${}", group.index),
+                };
+                match &group.text {
+                    Some(text) => writeln!(f, "   {} = {:?}", label, text)?,
+                    None => writeln!(f, "   {} did not participate", label)?,
+                }
+            }
+            if let Some(replaced) = &m.replaced {
+                writeln!(f, "   → {:?}", replaced)?;
+            }
+        }
+        if self.matches.len() < self.total {
+            writeln!(f, "\n... and {} more match(es)", self.total - self.matches.len())?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "\n⚠️  {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_and_groups() {
+        let pattern = r"(?P<user>\w+)@(\w+)(\.org)?";
+        let options = RegexOptions::default();
+        let report = test_regex(pattern, options, "mail bob@example\nand ann@site.org", Some("$2/${user}"), 10).unwrap();
+        assert_eq!(report.total, 2);
+        assert_eq!(report.lines, 2);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        assert_eq!(
+            report.to_string(),
+            "2 match(es) on 2 line(s):
+
+1. line 1, column 6: \"bob@example\"
+   $1 (user) = \"bob\"
+   $2 = \"example\"
+   $3 did not participate
+   → \"example/bob\"
+
+2. line 2, column 5: \"ann@site.org\"
+   $1 (user) = \"ann\"
+   $2 = \"site\"
+   $3 = \".org\"
+   → \"site/ann\"
+"
+        );
+
+        let limited = test_regex(pattern, options, "a@b c@d e@f", None, 1).unwrap();
+        assert_eq!(limited.matches.len(), 1);
+        assert!(limited.to_string().contains("... and 2 more match(es)"));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let options = RegexOptions::default();
+        let report = test_regex(r"(\d*)", options, "a1", Some("$1x $3 $name $$1 ${1}x"), 10).unwrap();
+        assert_eq!(report.warnings.len(), 4, "{:?}", report.warnings);
+        assert!(report.warnings[0].starts_with("The pattern matches empty text (1 of 2 matches)"));
+        assert!(report.warnings[1].contains("write ${1}x to follow group 1 with 'x'"));
+        assert!(report.warnings[2].contains("group $3 but the pattern has only 1 group(s)"));
+        assert!(report.warnings[3].contains("group named 'name'"));
+
+        let report = test_regex("^b$", options, "a\nb\n", None, 10).unwrap();
+        assert!(report.warnings[0].contains("set multiline"));
+        let multiline = RegexOptions {
+            multiline: true,
+            ..options
+        };
+        let report = test_regex("^b$", multiline, "a\nb\n", None, 10).unwrap();
+        assert_eq!(report.total, 1);
+        assert!(report.warnings.is_empty());
+
+        assert!(test_regex("(unclosed", options, "", None, 10).is_err());
+    }
+
+    #[test]
+    fn test_pattern_limits() {
+        let options = RegexOptions::default();
+        let long = "a".repeat(MAX_PATTERN_LEN + 1);
+        assert!(build_regex(&long, options).unwrap_err().to_string().contains("the limit is 4096 bytes"));
+        assert!(matches!(build_regex(r"(\w{100}){100}", options), Err(regex::Error::CompiledTooBig(_))));
+        let nested = format!("{}a{}", "(".repeat(100), ")".repeat(100));
+        assert!(build_regex(&nested, options).is_err());
+        assert!(build_regex(r"(\w+\s*)+$", options).is_ok());
+    }
+}
@@ -0,0 +1,209 @@
+//! Per-line previews of bulk replacements, applied selectively
+//!
+//! [`replace_by_line`] replaces every match of a pattern and groups what
+//! changed by the lines it touched, so a preview can be shown as a tree of
+//! files and changed lines. The changes a user keeps selected are written
+//! back with [`apply_line_changes`], which refuses a file whose lines no
+//! longer read as they did in the preview.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A change to a run of lines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineChange {
+    /// First line changed, from 1
+    pub line: usize,
+    /// The lines before the change, without the final line break
+    pub before: String,
+    /// The lines after the change
+    pub after: String,
+}
+
+/// The changes to one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChanges {
+    /// Path of the file, relative to the project root when inside it
+    pub path: String,
+    /// Matches replaced in the file
+    pub replacements: usize,
+    /// Changed lines in file order
+    pub changes: Vec<LineChange>,
+}
+
+/// Outcome of applying a selection of changes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedChanges {
+    /// Changed files with the number of changes applied, in the order given
+    pub files: Vec<(String, usize)>,
+    /// Total changes applied
+    pub total_changes: usize,
+    /// Files left alone, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Outcome of replacing the matches in one text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replaced {
+    /// The text with every match replaced
+    pub content: String,
+    /// Matches replaced
+    pub replacements: usize,
+    /// Changed lines in order
+    pub changes: Vec<LineChange>,
+}
+
+/// Errors applying changes to a file
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LineChangeError {
+    /// The lines at a change no longer read as they did in the preview
+    #[error("line {0} has changed since the preview")]
+    Stale(usize),
+
+    /// Two changes touch the same line
+    #[error("changes at lines {0} and {1} overlap")]
+    Overlap(usize, usize),
+}
+
+/// Replace every match of `regex` in `content` with `replace`, grouping the
+/// changes by the lines they touch; `None` when the text stays the same or
+/// `replace` gives up by returning `None`
+pub fn replace_by_line(
+    content: &str,
+    regex: &Regex,
+    mut replace: impl FnMut(&Captures) -> Option<String>,
+) -> Option<Replaced> {
+    // Byte ranges of whole lines with a match, merged where they share a
+    // line, with the (start, end, replacement) of each match in them
+    let mut groups: Vec<(usize, usize, Vec<(usize, usize, String)>)> = Vec::new();
+    for caps in regex.captures_iter(content) {
+        let found = caps.get(0).expect("group 0 always matches");
+        let start = content[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+        let end = content[found.end()..].find('\n').map_or(content.len(), |i| found.end() + i);
+        let replacement = (found.start(), found.end(), replace(&caps)?);
+        match groups.last_mut() {
+            Some(group) if start <= group.1 => {
+                group.1 = group.1.max(end);
+                group.2.push(replacement);
+            }
+            _ => groups.push((start, end, vec![replacement])),
+        }
+    }
+
+    let mut new_content = String::with_capacity(content.len());
+    let mut changes = Vec::new();
+    let mut replacements = 0;
+    let mut copied = 0;
+    for (start, end, matches) in groups {
+        let mut after = String::new();
+        let mut position = start;
+        for (match_start, match_end, replacement) in &matches {
+            after.push_str(&content[position..*match_start]);
+            after.push_str(replacement);
+            position = *match_end;
+        }
+        after.push_str(&content[position..end]);
+        replacements += matches.len();
+
+        new_content.push_str(&content[copied..start]);
+        new_content.push_str(&after);
+        copied = end;
+        let before = &content[start..end];
+        if before != after {
+            changes.push(LineChange {
+                line: content[..start].matches('\n').count() + 1,
+                before: before.to_string(),
+                after,
+            });
+        }
+    }
+    new_content.push_str(&content[copied..]);
+
+    (!changes.is_empty()).then_some(Replaced {
+        content: new_content,
+        replacements,
+        changes,
+    })
+}
+
+/// `content` with `changes` applied
+///
+/// Every change must still find its `before` lines where the preview saw
+/// them; otherwise nothing is applied.
+pub fn apply_line_changes(content: &str, changes: &[LineChange]) -> Result<String, LineChangeError> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut sorted: Vec<&LineChange> = changes.iter().collect();
+    sorted.sort_by_key(|change| change.line);
+
+    let mut result = String::with_capacity(content.len());
+    let mut copied = 0;
+    let mut previous: Option<(usize, usize)> = None;
+    for change in sorted {
+        let last_line = change.line + change.before.matches('\n').count();
+        if let Some((line, end)) = previous {
+            if change.line <= end {
+                return Err(LineChangeError::Overlap(line, change.line));
+            }
+        }
+        previous = Some((change.line, last_line));
+
+        let start = *line_starts.get(change.line.wrapping_sub(1)).ok_or(LineChangeError::Stale(change.line))?;
+        let end = start + change.before.len();
+        let at_line_end = content.get(end..).is_some_and(|rest| rest.is_empty() || rest.starts_with('\n'));
+        if content.get(start..end) != Some(change.before.as_str()) || !at_line_end {
+            return Err(LineChangeError::Stale(change.line));
+        }
+        result.push_str(&content[copied..start]);
+        result.push_str(&change.after);
+        copied = end;
+    }
+    result.push_str(&content[copied..]);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_by_line() {
+        let content = "let foo = 1;\nkeep\nfoo(foo);\nfoo\nbar\n";
+        let regex = Regex::new("foo").unwrap();
+        let replaced = replace_by_line(content, &regex, |_| Some("baz".to_string())).unwrap();
+        assert_eq!(replaced.content, "let baz = 1;\nkeep\nbaz(baz);\nbaz\nbar\n");
+        assert_eq!(replaced.replacements, 4);
+        let lines: Vec<(usize, &str, &str)> = replaced
+            .changes
+            .iter()
+            .map(|change| (change.line, change.before.as_str(), change.after.as_str()))
+            .collect();
+        assert_eq!(lines, vec![(1, "let foo = 1;", "let baz = 1;"), (3, "foo(foo);", "baz(baz);"), (4, "foo", "baz")]);
+
+        // A match across lines is one change to all of them
+        let regex = Regex::new(r"foo\(foo\);\nfoo").unwrap();
+        let replaced = replace_by_line(content, &regex, |_| Some("x".to_string())).unwrap();
+        assert_eq!(replaced.changes[0].line, 3);
+        assert_eq!(replaced.changes[0].before, "foo(foo);\nfoo");
+        assert!(replace_by_line(content, &Regex::new("nothing").unwrap(), |_| Some(String::new())).is_none());
+    }
+
+    #[test]
+    fn test_apply_selected_changes() {
+        let content = "let foo = 1;\nkeep\nfoo(foo);\nfoo\n";
+        let regex = Regex::new("foo").unwrap();
+        let replaced = replace_by_line(content, &regex, |_| Some("baz".to_string())).unwrap();
+
+        // Deselecting line 3 leaves it alone
+        let selected = vec![replaced.changes[0].clone(), replaced.changes[2].clone()];
+        assert_eq!(apply_line_changes(content, &selected).unwrap(), "let baz = 1;\nkeep\nfoo(foo);\nbaz\n");
+        assert_eq!(apply_line_changes(content, &replaced.changes).unwrap(), replaced.content);
+
+        let edited = "let foo = 2;\nkeep\nfoo(foo);\nfoo\n";
+        assert_eq!(apply_line_changes(edited, &selected), Err(LineChangeError::Stale(1)));
+        let twice = vec![replaced.changes[1].clone(), replaced.changes[1].clone()];
+        assert_eq!(apply_line_changes(content, &twice), Err(LineChangeError::Overlap(3, 3)));
+    }
+}
@@ -0,0 +1,275 @@
+//! Sampling parameters for model requests
+//!
+//! This module holds the generation controls (max output tokens, temperature,
+//! top-p, top-k, stop sequences) that can be set project-wide in the
+//! `[agent.sampling]` section of `.AuroraHeart/config.toml` or overridden per
+//! conversation, along with validation against each provider's limits.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors that can occur while validating sampling parameters
+#[derive(Error, Debug, PartialEq)]
+pub enum SamplingError {
+    /// A numeric parameter is outside the provider's allowed range
+    #[error("{param} must be between {min} and {max} for {provider} (got {value})")]
+    OutOfRange {
+        provider: String,
+        param: String,
+        value: String,
+        min: String,
+        max: String,
+    },
+
+    /// The provider does not support a parameter
+    #[error("{provider} does not support {param}")]
+    Unsupported { provider: String, param: String },
+
+    /// A stop sequence is empty or only whitespace
+    #[error("Stop sequences must contain non-whitespace characters")]
+    InvalidStopSequence,
+
+    /// More stop sequences than the provider accepts
+    #[error("{provider} accepts at most {max} stop sequences (got {count})")]
+    TooManyStopSequences {
+        provider: String,
+        count: usize,
+        max: usize,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, SamplingError>;
+
+/// Limits a provider places on sampling parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderLimits {
+    /// Provider name used in error messages
+    pub name: &'static str,
+    /// Highest accepted temperature
+    pub max_temperature: f32,
+    /// Highest accepted max output tokens
+    pub max_output_tokens: usize,
+    /// Maximum number of stop sequences
+    pub max_stop_sequences: usize,
+    /// Whether top-k sampling is supported
+    pub supports_top_k: bool,
+}
+
+/// Limits for the Anthropic Messages API
+pub const ANTHROPIC_LIMITS: ProviderLimits = ProviderLimits {
+    name: "Anthropic",
+    max_temperature: 1.0,
+    max_output_tokens: 128_000,
+    max_stop_sequences: 16,
+    supports_top_k: true,
+};
+
+/// Generation controls for a model request
+///
+/// Unset values fall back to the next level (conversation → project →
+/// provider default).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingParams {
+    /// Maximum tokens to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+
+    /// Sampling temperature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling probability mass
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Only sample from the top K tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+
+    /// Sequences that stop generation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+}
+
+impl SamplingParams {
+    /// Check whether no parameter is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Layer `overrides` on top of these parameters
+    ///
+    /// Values set in `overrides` win; a non-empty stop sequence list replaces
+    /// the base list rather than extending it.
+    pub fn merge(&self, overrides: &SamplingParams) -> SamplingParams {
+        SamplingParams {
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            top_k: overrides.top_k.or(self.top_k),
+            stop_sequences: if overrides.stop_sequences.is_empty() {
+                self.stop_sequences.clone()
+            } else {
+                overrides.stop_sequences.clone()
+            },
+        }
+    }
+
+    /// Validate the parameters against a provider's limits
+    pub fn validate(&self, limits: &ProviderLimits) -> Result<()> {
+        let out_of_range = |param: &str, value: String, min: &str, max: String| {
+            SamplingError::OutOfRange {
+                provider: limits.name.to_string(),
+                param: param.to_string(),
+                value,
+                min: min.to_string(),
+                max,
+            }
+        };
+
+        if let Some(max_tokens) = self.max_tokens {
+            if max_tokens == 0 || max_tokens > limits.max_output_tokens {
+                return Err(out_of_range(
+                    "max_tokens",
+                    max_tokens.to_string(),
+                    "1",
+                    limits.max_output_tokens.to_string(),
+                ));
+            }
+        }
+
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=limits.max_temperature).contains(&temperature) {
+                return Err(out_of_range(
+                    "temperature",
+                    temperature.to_string(),
+                    "0",
+                    limits.max_temperature.to_string(),
+                ));
+            }
+        }
+
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err(out_of_range("top_p", top_p.to_string(), "0", "1".to_string()));
+            }
+        }
+
+        if let Some(top_k) = self.top_k {
+            if !limits.supports_top_k {
+                return Err(SamplingError::Unsupported {
+                    provider: limits.name.to_string(),
+                    param: "top_k".to_string(),
+                });
+            }
+            if top_k == 0 {
+                return Err(out_of_range(
+                    "top_k",
+                    top_k.to_string(),
+                    "1",
+                    usize::MAX.to_string(),
+                ));
+            }
+        }
+
+        if self.stop_sequences.len() > limits.max_stop_sequences {
+            return Err(SamplingError::TooManyStopSequences {
+                provider: limits.name.to_string(),
+                count: self.stop_sequences.len(),
+                max: limits.max_stop_sequences,
+            });
+        }
+        if self.stop_sequences.iter().any(|s| s.trim().is_empty()) {
+            return Err(SamplingError::InvalidStopSequence);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_prefers_overrides() {
+        let project = SamplingParams {
+            max_tokens: Some(2048),
+            temperature: Some(0.2),
+            stop_sequences: vec!["END".to_string()],
+            ..SamplingParams::default()
+        };
+        let conversation = SamplingParams {
+            temperature: Some(0.9),
+            top_k: Some(40),
+            ..SamplingParams::default()
+        };
+
+        let merged = project.merge(&conversation);
+        assert_eq!(merged.max_tokens, Some(2048));
+        assert_eq!(merged.temperature, Some(0.9));
+        assert_eq!(merged.top_k, Some(40));
+        assert_eq!(merged.stop_sequences, vec!["END".to_string()]);
+        assert!(SamplingParams::default().is_empty());
+    }
+
+    #[test]
+    fn test_validate_ranges() {
+        let valid = SamplingParams {
+            max_tokens: Some(4096),
+            temperature: Some(1.0),
+            top_p: Some(0.95),
+            top_k: Some(50),
+            stop_sequences: vec!["\n\nHuman:".to_string()],
+        };
+        assert!(valid.validate(&ANTHROPIC_LIMITS).is_ok());
+
+        let too_hot = SamplingParams {
+            temperature: Some(1.5),
+            ..SamplingParams::default()
+        };
+        let err = too_hot.validate(&ANTHROPIC_LIMITS).unwrap_err();
+        assert!(err.to_string().contains("temperature must be between 0 and 1"));
+
+        let zero_tokens = SamplingParams {
+            max_tokens: Some(0),
+            ..SamplingParams::default()
+        };
+        assert!(zero_tokens.validate(&ANTHROPIC_LIMITS).is_err());
+
+        let no_top_k = ProviderLimits {
+            supports_top_k: false,
+            ..ANTHROPIC_LIMITS
+        };
+        let top_k = SamplingParams {
+            top_k: Some(10),
+            ..SamplingParams::default()
+        };
+        assert!(matches!(
+            top_k.validate(&no_top_k),
+            Err(SamplingError::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_stop_sequences() {
+        let blank = SamplingParams {
+            stop_sequences: vec!["  ".to_string()],
+            ..SamplingParams::default()
+        };
+        assert_eq!(
+            blank.validate(&ANTHROPIC_LIMITS),
+            Err(SamplingError::InvalidStopSequence)
+        );
+
+        let many = SamplingParams {
+            stop_sequences: (0..17).map(|i| format!("stop{}", i)).collect(),
+            ..SamplingParams::default()
+        };
+        assert!(matches!(
+            many.validate(&ANTHROPIC_LIMITS),
+            Err(SamplingError::TooManyStopSequences { count: 17, .. })
+        ));
+    }
+}
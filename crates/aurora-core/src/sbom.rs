@@ -0,0 +1,405 @@
+//! Software bill of materials
+//!
+//! Builds a CycloneDX 1.5 or SPDX 2.3 JSON document listing the project's
+//! third-party packages, read from the lockfiles in the project root:
+//! `Cargo.lock` (with SHA-256 checksums), `package-lock.json` (version 2 or
+//! 3, with declared licenses), and `requirements.txt`. Every package gets a
+//! package URL (purl) so scanners can match it against advisories.
+//!
+//! Only pinned requirements (`name==version`) carry a version; other
+//! requirement lines are listed by name.
+
+use crate::license::{npm_dependencies, Ecosystem, LicenseError};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors that can occur while generating an SBOM
+#[derive(Error, Debug)]
+pub enum SbomError {
+    /// IO error while reading a lockfile
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A JSON lockfile could not be parsed, or the document written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// `Cargo.lock` could not be parsed
+    #[error("Invalid Cargo.lock: {0}")]
+    CargoLock(#[from] toml::de::Error),
+
+    /// `package-lock.json` could not be read
+    #[error("Invalid package-lock.json: {0}")]
+    PackageLock(#[from] LicenseError),
+
+    /// No supported lockfile was found
+    #[error("No Cargo.lock, package-lock.json, or requirements.txt in {0}")]
+    NoLockfiles(String),
+
+    /// Unknown document format
+    #[error("Unknown SBOM format '{0}'; expected 'cyclonedx' or 'spdx'")]
+    UnknownFormat(String),
+}
+
+pub type Result<T> = std::result::Result<T, SbomError>;
+
+/// Document format of an SBOM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    #[default]
+    CycloneDx,
+    Spdx,
+}
+
+impl FromStr for SbomFormat {
+    type Err = SbomError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "cyclonedx" => Ok(SbomFormat::CycloneDx),
+            "spdx" => Ok(SbomFormat::Spdx),
+            _ => Err(SbomError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// A package listed in the SBOM
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SbomComponent {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    /// Empty when the lockfile does not pin a version
+    pub version: String,
+    /// Declared SPDX license expression
+    pub license: Option<String>,
+    /// Hex SHA-256 of the package archive
+    pub sha256: Option<String>,
+}
+
+impl SbomComponent {
+    /// Package URL identifying the component
+    pub fn purl(&self) -> String {
+        let (kind, name) = match self.ecosystem {
+            Ecosystem::Cargo => ("cargo", self.name.clone()),
+            Ecosystem::Npm => ("npm", self.name.replace('@', "%40")),
+            // PyPI names are case-insensitive and normalized to dashes
+            Ecosystem::Pypi => ("pypi", self.name.to_lowercase().replace(['_', '.'], "-")),
+        };
+        if self.version.is_empty() {
+            format!("pkg:{}/{}", kind, name)
+        } else {
+            format!("pkg:{}/{}@{}", kind, name, self.version)
+        }
+    }
+}
+
+/// The third-party packages in the lockfiles in `root`
+pub fn collect_components(root: &Path) -> Result<Vec<SbomComponent>> {
+    let mut components = Vec::new();
+    let mut found = false;
+
+    let cargo_lock = root.join("Cargo.lock");
+    if cargo_lock.is_file() {
+        found = true;
+        components.extend(cargo_lock_components(&std::fs::read_to_string(cargo_lock)?)?);
+    }
+    let package_lock = root.join("package-lock.json");
+    if package_lock.is_file() {
+        found = true;
+        components.extend(npm_dependencies(&std::fs::read_to_string(package_lock)?)?.into_iter().map(|d| {
+            SbomComponent {
+                ecosystem: d.ecosystem,
+                name: d.name,
+                version: d.version,
+                license: d.license,
+                sha256: None,
+            }
+        }));
+    }
+    let requirements = root.join("requirements.txt");
+    if requirements.is_file() {
+        found = true;
+        components.extend(requirements_components(&std::fs::read_to_string(requirements)?));
+    }
+
+    if !found {
+        return Err(SbomError::NoLockfiles(root.display().to_string()));
+    }
+    Ok(components)
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+}
+
+/// Packages in a `Cargo.lock`, leaving out workspace members
+pub fn cargo_lock_components(lockfile: &str) -> Result<Vec<SbomComponent>> {
+    let lock: CargoLock = toml::from_str(lockfile)?;
+    Ok(lock
+        .package
+        .into_iter()
+        .filter(|package| package.source.is_some())
+        .map(|package| SbomComponent {
+            ecosystem: Ecosystem::Cargo,
+            name: package.name,
+            version: package.version,
+            license: None,
+            sha256: package.checksum,
+        })
+        .collect())
+}
+
+/// Packages named in a `requirements.txt`
+///
+/// Options, includes, editable installs, and URLs are skipped.
+pub fn requirements_components(requirements: &str) -> Vec<SbomComponent> {
+    requirements
+        .lines()
+        .map(|line| line.split(" #").next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty() && !line.starts_with(['#', '-']) && !line.contains("://"))
+        .filter_map(|line| {
+            // Drop environment markers, then split the name from the specifier
+            let requirement = line.split(';').next().unwrap_or_default().trim();
+            let name_end = requirement
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+                .unwrap_or(requirement.len());
+            let name = &requirement[..name_end];
+            if name.is_empty() {
+                return None;
+            }
+            let specifier = requirement[name_end..].trim_start();
+            let specifier = match specifier.strip_prefix('[') {
+                Some(rest) => rest.split_once(']').map(|(_, rest)| rest.trim_start()).unwrap_or_default(),
+                None => specifier,
+            };
+            let version = specifier
+                .strip_prefix("==")
+                .filter(|v| !v.contains([',', '*']))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default();
+            Some(SbomComponent {
+                ecosystem: Ecosystem::Pypi,
+                name: name.to_string(),
+                version,
+                license: None,
+                sha256: None,
+            })
+        })
+        .collect()
+}
+
+/// SBOM of the project at `root` in `format`, as pretty-printed JSON
+pub fn generate_sbom(root: &Path, format: SbomFormat) -> Result<String> {
+    let components = collect_components(root)?;
+    let project = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    render_sbom(format, &project, &components, now.as_secs())
+}
+
+/// SBOM document for `components` of `project`, created at `created`
+/// (seconds since the Unix epoch)
+pub fn render_sbom(format: SbomFormat, project: &str, components: &[SbomComponent], created: u64) -> Result<String> {
+    let timestamp = rfc3339(created);
+    let tool_version = env!("CARGO_PKG_VERSION");
+    let document = match format {
+        SbomFormat::CycloneDx => json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "timestamp": timestamp,
+                "tools": {
+                    "components": [{ "type": "application", "name": "AuroraHeart", "version": tool_version }]
+                },
+                "component": { "type": "application", "bom-ref": project, "name": project }
+            },
+            "components": components.iter().map(|component| {
+                let mut entry = json!({
+                    "type": "library",
+                    "bom-ref": component.purl(),
+                    "name": component.name,
+                    "purl": component.purl(),
+                });
+                if !component.version.is_empty() {
+                    entry["version"] = json!(component.version);
+                }
+                if let Some(license) = &component.license {
+                    entry["licenses"] = json!([{ "expression": license }]);
+                }
+                if let Some(sha256) = &component.sha256 {
+                    entry["hashes"] = json!([{ "alg": "SHA-256", "content": sha256 }]);
+                }
+                entry
+            }).collect::<Vec<_>>(),
+        }),
+        SbomFormat::Spdx => {
+            let root_id = "SPDXRef-Package-root";
+            let mut packages = vec![json!({
+                "name": project,
+                "SPDXID": root_id,
+                "downloadLocation": "NOASSERTION",
+                "filesAnalyzed": false,
+            })];
+            let mut relationships = vec![json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": root_id,
+            })];
+            for (i, component) in components.iter().enumerate() {
+                let id = format!("SPDXRef-Package-{}", i + 1);
+                let mut package = json!({
+                    "name": component.name,
+                    "SPDXID": id,
+                    "downloadLocation": "NOASSERTION",
+                    "filesAnalyzed": false,
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": component.license.as_deref().unwrap_or("NOASSERTION"),
+                    "externalRefs": [{
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": component.purl(),
+                    }],
+                });
+                if !component.version.is_empty() {
+                    package["versionInfo"] = json!(component.version);
+                }
+                if let Some(sha256) = &component.sha256 {
+                    package["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": sha256 }]);
+                }
+                packages.push(package);
+                relationships.push(json!({
+                    "spdxElementId": root_id,
+                    "relationshipType": "DEPENDS_ON",
+                    "relatedSpdxElement": id,
+                }));
+            }
+            json!({
+                "spdxVersion": "SPDX-2.3",
+                "dataLicense": "CC0-1.0",
+                "SPDXID": "SPDXRef-DOCUMENT",
+                "name": format!("{}-sbom", project),
+                "documentNamespace": format!("https://spdx.org/spdxdocs/{}-{}", project.replace(' ', "-"), created),
+                "creationInfo": {
+                    "created": timestamp,
+                    "creators": [format!("Tool: AuroraHeart-{}", tool_version)],
+                },
+                "packages": packages,
+                "relationships": relationships,
+            })
+        }
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// `seconds` since the Unix epoch as an RFC 3339 UTC timestamp
+fn rfc3339(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let time = seconds % 86_400;
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_components_from_lockfiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        assert!(matches!(collect_components(root), Err(SbomError::NoLockfiles(_))));
+
+        std::fs::write(
+            root.join("Cargo.lock"),
+            "version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\nchecksum = \"abc123\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("package-lock.json"),
+            r#"{"lockfileVersion": 3, "packages": {"": {"name": "web"}, "node_modules/@types/node": {"version": "20.1.0", "license": "MIT"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("requirements.txt"),
+            "# deps\n-r base.txt\nRequests[socks]==2.31.0  # http\nzope.interface>=6\nnumpy==1.26.4; python_version >= \"3.9\"\n",
+        )
+        .unwrap();
+
+        let purls: Vec<String> = collect_components(root).unwrap().iter().map(SbomComponent::purl).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:cargo/serde@1.0.210",
+                "pkg:npm/%40types/node@20.1.0",
+                "pkg:pypi/requests@2.31.0",
+                "pkg:pypi/zope-interface",
+                "pkg:pypi/numpy@1.26.4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let components = vec![SbomComponent {
+            ecosystem: Ecosystem::Cargo,
+            name: "serde".to_string(),
+            version: "1.0.210".to_string(),
+            license: Some("MIT OR Apache-2.0".to_string()),
+            sha256: Some("abc123".to_string()),
+        }];
+
+        let cyclonedx: serde_json::Value =
+            serde_json::from_str(&render_sbom(SbomFormat::CycloneDx, "billing", &components, 1_760_486_400).unwrap()).unwrap();
+        assert_eq!(cyclonedx["bomFormat"], "CycloneDX");
+        assert_eq!(cyclonedx["metadata"]["timestamp"], "2025-10-15T00:00:00Z");
+        assert_eq!(cyclonedx["components"][0]["purl"], "pkg:cargo/serde@1.0.210");
+        assert_eq!(cyclonedx["components"][0]["licenses"][0]["expression"], "MIT OR Apache-2.0");
+        assert_eq!(cyclonedx["components"][0]["hashes"][0]["content"], "abc123");
+
+        let spdx: serde_json::Value =
+            serde_json::from_str(&render_sbom(SbomFormat::Spdx, "billing", &components, 951_825_599).unwrap()).unwrap();
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(spdx["creationInfo"]["created"], "2000-02-29T11:59:59Z");
+        assert_eq!(spdx["packages"][1]["licenseDeclared"], "MIT OR Apache-2.0");
+        assert_eq!(spdx["packages"][1]["checksums"][0]["algorithm"], "SHA256");
+        assert_eq!(spdx["relationships"][1]["relationshipType"], "DEPENDS_ON");
+        assert_eq!("SPDX".parse::<SbomFormat>().unwrap(), SbomFormat::Spdx);
+        assert!("xml".parse::<SbomFormat>().is_err());
+    }
+}
@@ -0,0 +1,467 @@
+//! Project templates and scaffolding
+//!
+//! New projects are created either from a built-in template, which runs the
+//! ecosystem's own generator (`cargo new`, `npm create vite`, `poetry new`),
+//! or from a user-defined template directory that is copied with `{{variable}}`
+//! placeholders substituted in file names and contents.
+//!
+//! User templates live in `<config dir>/AuroraHeart/templates/<name>/`. A
+//! template may include a `template.toml` with a `description` and default
+//! `[variables]`; that file is not copied.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Metadata file inside a user template directory
+const TEMPLATE_MANIFEST: &str = "template.toml";
+
+/// Errors that can occur while scaffolding a project
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// No template with this name
+    #[error("Unknown template: {0}")]
+    UnknownTemplate(String),
+
+    /// The project name cannot be used as a directory name
+    #[error("Invalid project name: {0}")]
+    InvalidName(String),
+
+    /// The destination directory already exists and is not empty
+    #[error("Destination already exists: {0}")]
+    DestinationExists(String),
+
+    /// The template's generator command failed
+    #[error("Template command failed: {0}")]
+    CommandFailed(String),
+
+    /// template.toml could not be parsed
+    #[error("Invalid template manifest: {0}")]
+    InvalidManifest(String),
+}
+
+pub type Result<T> = std::result::Result<T, ScaffoldError>;
+
+/// Where a template comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateKind {
+    /// Runs an external generator
+    Builtin,
+    /// Copies a user-defined directory
+    Directory,
+}
+
+/// A template available for scaffolding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    /// Template name used to select it
+    pub name: String,
+    /// Short description
+    pub description: String,
+    /// Built-in generator or template directory
+    pub kind: TemplateKind,
+}
+
+/// A built-in template backed by a generator command
+struct BuiltinTemplate {
+    name: &'static str,
+    description: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+}
+
+const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "rust-bin",
+        description: "Rust binary crate (cargo new)",
+        program: "cargo",
+        args: &["new", "{{project_name}}"],
+    },
+    BuiltinTemplate {
+        name: "rust-lib",
+        description: "Rust library crate (cargo new --lib)",
+        program: "cargo",
+        args: &["new", "--lib", "{{project_name}}"],
+    },
+    BuiltinTemplate {
+        name: "vite-react",
+        description: "Vite + React app (npm create vite)",
+        program: "npm",
+        args: &[
+            "create",
+            "vite@latest",
+            "{{project_name}}",
+            "--",
+            "--template",
+            "react",
+        ],
+    },
+    BuiltinTemplate {
+        name: "vite-vanilla",
+        description: "Vite app without a framework (npm create vite)",
+        program: "npm",
+        args: &[
+            "create",
+            "vite@latest",
+            "{{project_name}}",
+            "--",
+            "--template",
+            "vanilla",
+        ],
+    },
+    BuiltinTemplate {
+        name: "poetry",
+        description: "Python package (poetry new)",
+        program: "poetry",
+        args: &["new", "{{project_name}}"],
+    },
+];
+
+/// Optional manifest of a user template
+#[derive(Debug, Default, Deserialize)]
+struct TemplateManifest {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+}
+
+/// Replace `{{name}}` placeholders with variable values
+///
+/// Unknown placeholders are left untouched.
+pub fn substitute(text: &str, variables: &BTreeMap<String, String>) -> String {
+    let mut output = text.to_string();
+    for (name, value) in variables {
+        output = output.replace(&format!("{{{{{}}}}}", name), value);
+    }
+    output
+}
+
+/// Creates new projects from templates
+pub struct Scaffolder {
+    template_dirs: Vec<PathBuf>,
+}
+
+impl Default for Scaffolder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scaffolder {
+    /// Create a scaffolder using the user template directory
+    pub fn new() -> Self {
+        Self {
+            template_dirs: dirs::config_dir()
+                .map(|dir| dir.join("AuroraHeart").join("templates"))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Also look for user templates in `dir`
+    pub fn with_template_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.template_dirs.push(dir.into());
+        self
+    }
+
+    /// List built-in and user templates
+    pub fn list_templates(&self) -> Vec<TemplateInfo> {
+        let mut templates: Vec<TemplateInfo> = BUILTIN_TEMPLATES
+            .iter()
+            .map(|t| TemplateInfo {
+                name: t.name.to_string(),
+                description: t.description.to_string(),
+                kind: TemplateKind::Builtin,
+            })
+            .collect();
+
+        let mut user_templates = Vec::new();
+        for dir in &self.template_dirs {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+                let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                let description = read_manifest(&path)
+                    .ok()
+                    .and_then(|m| m.description)
+                    .unwrap_or_else(|| format!("Template directory {}", path.display()));
+                user_templates.push(TemplateInfo {
+                    name: name.to_string(),
+                    description,
+                    kind: TemplateKind::Directory,
+                });
+            }
+        }
+        user_templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates.extend(user_templates);
+        templates
+    }
+
+    /// Create `parent_dir/name` from a template and return its path
+    ///
+    /// `project_name` and `crate_name` (the name with `-` replaced by `_`)
+    /// are always available as variables; `variables` override template
+    /// defaults.
+    pub fn scaffold(
+        &self,
+        template: &str,
+        name: &str,
+        parent_dir: &Path,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<PathBuf> {
+        validate_name(name)?;
+        let destination = parent_dir.join(name);
+        if destination.exists() && std::fs::read_dir(&destination)?.next().is_some() {
+            return Err(ScaffoldError::DestinationExists(
+                destination.display().to_string(),
+            ));
+        }
+
+        let mut all_variables = BTreeMap::new();
+        all_variables.insert("project_name".to_string(), name.to_string());
+        all_variables.insert("crate_name".to_string(), name.replace('-', "_"));
+
+        if let Some(builtin) = BUILTIN_TEMPLATES.iter().find(|t| t.name == template) {
+            all_variables.extend(variables.clone());
+            std::fs::create_dir_all(parent_dir)?;
+            run_generator(builtin, parent_dir, &all_variables)?;
+        } else {
+            let source = self
+                .find_template_dir(template)
+                .ok_or_else(|| ScaffoldError::UnknownTemplate(template.to_string()))?;
+            all_variables.extend(read_manifest(&source)?.variables);
+            all_variables.extend(variables.clone());
+            std::fs::create_dir_all(&destination)?;
+            copy_template(&source, &destination, &all_variables, true)?;
+        }
+
+        tracing::info!(
+            "Scaffolded '{}' project at {}",
+            template,
+            destination.display()
+        );
+        Ok(destination)
+    }
+
+    fn find_template_dir(&self, template: &str) -> Option<PathBuf> {
+        if validate_name(template).is_err() {
+            return None;
+        }
+        self.template_dirs
+            .iter()
+            .map(|dir| dir.join(template))
+            .find(|path| path.is_dir())
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\', ':'])
+        && !name.starts_with('.');
+    if valid {
+        Ok(())
+    } else {
+        Err(ScaffoldError::InvalidName(name.to_string()))
+    }
+}
+
+fn read_manifest(template_dir: &Path) -> Result<TemplateManifest> {
+    let path = template_dir.join(TEMPLATE_MANIFEST);
+    if !path.exists() {
+        return Ok(TemplateManifest::default());
+    }
+    toml::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| ScaffoldError::InvalidManifest(e.to_string()))
+}
+
+fn run_generator(
+    template: &BuiltinTemplate,
+    working_dir: &Path,
+    variables: &BTreeMap<String, String>,
+) -> Result<()> {
+    let args: Vec<String> = template
+        .args
+        .iter()
+        .map(|a| substitute(a, variables))
+        .collect();
+    tracing::debug!(
+        "Running template generator: {} {}",
+        template.program,
+        args.join(" ")
+    );
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(template.program);
+        command
+    } else {
+        Command::new(template.program)
+    };
+
+    let output = command
+        .args(&args)
+        .current_dir(working_dir)
+        .output()
+        .map_err(|e| ScaffoldError::CommandFailed(format!("{}: {}", template.program, e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(ScaffoldError::CommandFailed(format!(
+            "{} {} exited with {}: {}",
+            template.program,
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Copy a template directory, substituting variables in names and text files
+fn copy_template(
+    source: &Path,
+    destination: &Path,
+    variables: &BTreeMap<String, String>,
+    is_root: bool,
+) -> Result<()> {
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" || (is_root && name == TEMPLATE_MANIFEST) {
+            continue;
+        }
+
+        let target = destination.join(substitute(&name, variables));
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            copy_template(&entry.path(), &target, variables, false)?;
+        } else {
+            let bytes = std::fs::read(entry.path())?;
+            match String::from_utf8(bytes) {
+                Ok(text) if !text.contains('\0') => {
+                    std::fs::write(&target, substitute(&text, variables))?
+                }
+                Ok(text) => std::fs::write(&target, text)?,
+                Err(e) => std::fs::write(&target, e.into_bytes())?,
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn user_template(templates: &Path) {
+        let template = templates.join("service");
+        std::fs::create_dir_all(template.join("src/{{crate_name}}")).unwrap();
+        std::fs::write(
+            template.join(TEMPLATE_MANIFEST),
+            "description = \"HTTP service\"\n[variables]\nport = \"8080\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            template.join("README.md"),
+            "# {{project_name}}\nListens on {{port}} as {{unknown}}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            template.join("src/{{crate_name}}/mod.rs"),
+            "// {{crate_name}}\n",
+        )
+        .unwrap();
+        std::fs::write(template.join("logo.bin"), b"\0{{project_name}}").unwrap();
+    }
+
+    #[test]
+    fn test_list_templates() {
+        let temp_dir = TempDir::new().unwrap();
+        user_template(temp_dir.path());
+
+        let scaffolder = Scaffolder {
+            template_dirs: vec![temp_dir.path().to_path_buf()],
+        };
+        let templates = scaffolder.list_templates();
+        assert!(templates
+            .iter()
+            .any(|t| t.name == "rust-bin" && t.kind == TemplateKind::Builtin));
+        let service = templates.iter().find(|t| t.name == "service").unwrap();
+        assert_eq!(service.description, "HTTP service");
+        assert_eq!(service.kind, TemplateKind::Directory);
+    }
+
+    #[test]
+    fn test_scaffold_from_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = temp_dir.path().join("templates");
+        user_template(&templates);
+        let projects = temp_dir.path().join("projects");
+
+        let scaffolder = Scaffolder {
+            template_dirs: vec![templates],
+        };
+        let mut variables = BTreeMap::new();
+        variables.insert("port".to_string(), "3000".to_string());
+        let project = scaffolder
+            .scaffold("service", "billing-api", &projects, &variables)
+            .unwrap();
+
+        assert_eq!(project, projects.join("billing-api"));
+        assert_eq!(
+            std::fs::read_to_string(project.join("README.md")).unwrap(),
+            "# billing-api\nListens on 3000 as {{unknown}}\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(project.join("src/billing_api/mod.rs")).unwrap(),
+            "// billing_api\n"
+        );
+        assert_eq!(
+            std::fs::read(project.join("logo.bin")).unwrap(),
+            b"\0{{project_name}}"
+        );
+        assert!(!project.join(TEMPLATE_MANIFEST).exists());
+
+        assert!(matches!(
+            scaffolder.scaffold("service", "billing-api", &projects, &BTreeMap::new()),
+            Err(ScaffoldError::DestinationExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_scaffold_rejects_bad_input() {
+        let temp_dir = TempDir::new().unwrap();
+        let scaffolder = Scaffolder {
+            template_dirs: vec![temp_dir.path().to_path_buf()],
+        };
+
+        for name in ["", "..", "a/b", ".hidden"] {
+            assert!(matches!(
+                scaffolder.scaffold("rust-bin", name, temp_dir.path(), &BTreeMap::new()),
+                Err(ScaffoldError::InvalidName(_))
+            ));
+        }
+        assert!(matches!(
+            scaffolder.scaffold("missing", "app", temp_dir.path(), &BTreeMap::new()),
+            Err(ScaffoldError::UnknownTemplate(_))
+        ));
+        assert!(matches!(
+            scaffolder.scaffold("../escape", "app", temp_dir.path(), &BTreeMap::new()),
+            Err(ScaffoldError::UnknownTemplate(_))
+        ));
+    }
+}
@@ -0,0 +1,705 @@
+//! Parallel project-wide search and replace
+//!
+//! Files are collected up front (skipping hidden entries, build output, and
+//! binary files) and then scanned by a pool of worker threads. Results are
+//! delivered per file as workers finish, so callers can stream them to the
+//! editor while a search over a large repository is still running.
+//!
+//! Files above [`LARGE_FILE_BYTES`] are searched with a [`LineScanner`]
+//! instead of being read into memory, and overlong lines in them are skipped.
+//! Replace still only touches files below that size.
+
+use crate::line_scan::{LineScanner, LARGE_FILE_BYTES};
+use crate::paths::WalkGuard;
+use crate::word_replace::ReplaceOptions;
+use glob::Pattern;
+use regex::{Captures, NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use thiserror::Error;
+
+/// Directories never searched
+pub(crate) const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "dist", "build"];
+
+/// Errors that can occur while searching or replacing
+#[derive(Error, Debug)]
+pub enum SearchError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The search pattern is not a valid regex
+    #[error("Invalid search pattern: {0}")]
+    InvalidPattern(String),
+
+    /// An include or exclude glob is invalid
+    #[error("Invalid glob '{0}': {1}")]
+    InvalidGlob(String, String),
+}
+
+pub type Result<T> = std::result::Result<T, SearchError>;
+
+/// What to search for and where
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Text or regex to find
+    pub query: String,
+    /// Treat `query` as a regular expression
+    #[serde(default)]
+    pub regex: bool,
+    /// Match case exactly
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Only match whole words
+    #[serde(default)]
+    pub match_whole_word: bool,
+    /// Also match the other spellings of an identifier query (`FooBar`,
+    /// `FOO_BAR`) and replace each in its own style
+    #[serde(default)]
+    pub preserve_case: bool,
+    /// Only search paths matching one of these globs (relative to the root)
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Skip paths matching any of these globs
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Lines of context before and after each match
+    #[serde(default = "default_context_lines")]
+    pub context_lines: usize,
+    /// Stop after this many matches
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    /// Follow symbolic links that stay inside the root
+    #[serde(default)]
+    pub follow_symlinks: bool,
+}
+
+fn default_context_lines() -> usize {
+    2
+}
+
+fn default_max_results() -> usize {
+    2000
+}
+
+impl SearchQuery {
+    /// Whole-word and case-preserving options of the query
+    pub fn replace_options(&self) -> ReplaceOptions {
+        ReplaceOptions {
+            match_whole_word: self.match_whole_word,
+            preserve_case: self.preserve_case,
+        }
+    }
+
+    /// Create a case-insensitive literal search with default limits
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            regex: false,
+            case_sensitive: false,
+            match_whole_word: false,
+            preserve_case: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            context_lines: default_context_lines(),
+            max_results: default_max_results(),
+            follow_symlinks: false,
+        }
+    }
+}
+
+/// A single match with surrounding lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchMatch {
+    /// Line number (1-based)
+    pub line_number: usize,
+    /// Column of the match start in characters (1-based)
+    pub column: usize,
+    /// Byte range of the match within `line`
+    pub match_start: usize,
+    /// End of the byte range of the match within `line`
+    pub match_end: usize,
+    /// The matching line
+    pub line: String,
+    /// Lines immediately before the match
+    pub context_before: Vec<String>,
+    /// Lines immediately after the match
+    pub context_after: Vec<String>,
+}
+
+/// All matches in one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileMatches {
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// Matches in file order
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Outcome of a project search
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchSummary {
+    /// Files with matches, sorted by path (empty when results were streamed)
+    pub files: Vec<FileMatches>,
+    /// Total matches found
+    pub total_matches: usize,
+    /// Number of files scanned
+    pub files_searched: usize,
+    /// True when the search stopped at `max_results`
+    pub truncated: bool,
+}
+
+/// Outcome of a project-wide replace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplaceSummary {
+    /// Changed files with their replacement counts, sorted by path
+    pub files: Vec<(String, usize)>,
+    /// Total replacements made
+    pub total_replacements: usize,
+}
+
+/// A compiled search over one project
+pub struct ProjectSearch {
+    root: PathBuf,
+    query: SearchQuery,
+    matcher: Regex,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+fn compile_globs(globs: &[String]) -> Result<Vec<Pattern>> {
+    globs
+        .iter()
+        .map(|g| Pattern::new(g).map_err(|e| SearchError::InvalidGlob(g.clone(), e.to_string())))
+        .collect()
+}
+
+impl ProjectSearch {
+    /// Compile a search rooted at `root`
+    pub fn new(root: impl Into<PathBuf>, query: SearchQuery) -> Result<Self> {
+        if query.query.is_empty() {
+            return Err(SearchError::InvalidPattern("query is empty".to_string()));
+        }
+
+        let pattern = query.replace_options().pattern(&query.query, !query.regex);
+        let matcher = RegexBuilder::new(&pattern)
+            .case_insensitive(!query.case_sensitive)
+            .build()
+            .map_err(|e| SearchError::InvalidPattern(e.to_string()))?;
+
+        Ok(Self {
+            root: root.into(),
+            include: compile_globs(&query.include)?,
+            exclude: compile_globs(&query.exclude)?,
+            matcher,
+            query,
+        })
+    }
+
+    /// Run the search and collect all results sorted by path
+    pub fn run(&self) -> Result<SearchSummary> {
+        let mut files = Vec::new();
+        let mut summary = self.run_streaming(|file| files.push(file))?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        summary.files = files;
+        Ok(summary)
+    }
+
+    /// Run the search, passing each file's matches to `on_file` as soon as it
+    /// has been scanned
+    ///
+    /// The returned summary has no `files`; they were all given to `on_file`.
+    pub fn run_streaming(&self, mut on_file: impl FnMut(FileMatches)) -> Result<SearchSummary> {
+        let paths = self.collect_files(None)?;
+        let total_matches = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let max_results = self.query.max_results;
+
+        let files_searched = self.parallel(
+            &paths,
+            &stop,
+            |path| {
+                let mut file = self.search_file(path)?;
+                let previous = total_matches.fetch_add(file.matches.len(), Ordering::SeqCst);
+                if previous + file.matches.len() >= max_results {
+                    stop.store(true, Ordering::SeqCst);
+                    file.matches.truncate(max_results.saturating_sub(previous));
+                }
+                (!file.matches.is_empty()).then_some(file)
+            },
+            &mut on_file,
+        );
+
+        let total = total_matches.load(Ordering::SeqCst);
+        Ok(SearchSummary {
+            files: Vec::new(),
+            total_matches: total.min(max_results),
+            files_searched,
+            truncated: total >= max_results,
+        })
+    }
+
+    /// Replace every match in the project
+    ///
+    /// With a regex query, `$1`/`${name}` in `replacement` expand to capture
+    /// groups; literal queries insert the replacement text verbatim. With
+    /// `preserve_case`, each replacement follows the style of its match.
+    pub fn replace(&self, replacement: &str) -> Result<ReplaceSummary> {
+        let options = self.query.replace_options();
+        let paths = self.collect_files(Some(LARGE_FILE_BYTES))?;
+        let stop = AtomicBool::new(false);
+        let mut results = Vec::new();
+        let mut first_error = None;
+
+        self.parallel(
+            &paths,
+            &stop,
+            |path| {
+                let content = std::fs::read_to_string(path).ok()?;
+                let count = self.matcher.find_iter(&content).count();
+                if count == 0 {
+                    return None;
+                }
+                let replaced = if options.preserve_case {
+                    self.matcher.replace_all(&content, |caps: &Captures| {
+                        let mut expanded = String::new();
+                        if self.query.regex {
+                            caps.expand(replacement, &mut expanded);
+                        } else {
+                            expanded.push_str(replacement);
+                        }
+                        options.replacement_for(&caps[0], &expanded).into_owned()
+                    })
+                } else if self.query.regex {
+                    self.matcher.replace_all(&content, replacement)
+                } else {
+                    self.matcher.replace_all(&content, NoExpand(replacement))
+                };
+                Some(
+                    std::fs::write(path, replaced.as_bytes())
+                        .map(|_| (self.relative(path), count))
+                        .map_err(SearchError::from),
+                )
+            },
+            &mut |result| match result {
+                Ok(changed) => results.push(changed),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            },
+        );
+
+        if let Some(e) = first_error {
+            return Err(e);
+        }
+        results.sort();
+        Ok(ReplaceSummary {
+            total_replacements: results.iter().map(|(_, count)| count).sum(),
+            files: results,
+        })
+    }
+
+    /// Apply `work` to every path on a pool of worker threads, handing
+    /// non-empty results to `on_result` on the calling thread
+    ///
+    /// Returns the number of paths processed before `stop` was set.
+    fn parallel<T: Send>(
+        &self,
+        paths: &[PathBuf],
+        stop: &AtomicBool,
+        work: impl Fn(&Path) -> Option<T> + Sync,
+        on_result: &mut impl FnMut(T),
+    ) -> usize {
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+            .min(paths.len().max(1));
+        let next = AtomicUsize::new(0);
+        let processed = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                let sender = sender.clone();
+                let (next, processed, work) = (&next, &processed, &work);
+                scope.spawn(move || {
+                    while !stop.load(Ordering::SeqCst) {
+                        let Some(path) = paths.get(next.fetch_add(1, Ordering::SeqCst)) else {
+                            break;
+                        };
+                        processed.fetch_add(1, Ordering::SeqCst);
+                        if let Some(result) = work(path) {
+                            if sender.send(result).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for result in receiver {
+                on_result(result);
+            }
+        });
+
+        processed.load(Ordering::SeqCst)
+    }
+
+    fn search_file(&self, path: &Path) -> Option<FileMatches> {
+        if std::fs::metadata(path).ok()?.len() > LARGE_FILE_BYTES {
+            return self.search_large_file(path);
+        }
+
+        let content = std::fs::read_to_string(path).ok()?;
+        let lines: Vec<&str> = content.lines().collect();
+        let context = self.query.context_lines;
+
+        let mut matches = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            for found in self.matcher.find_iter(line) {
+                matches.push(SearchMatch {
+                    line_number: index + 1,
+                    column: line[..found.start()].chars().count() + 1,
+                    match_start: found.start(),
+                    match_end: found.end(),
+                    line: line.to_string(),
+                    context_before: lines[index.saturating_sub(context)..index]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    context_after: lines[index + 1..(index + 1 + context).min(lines.len())]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                });
+            }
+        }
+
+        Some(FileMatches {
+            path: self.relative(path),
+            matches,
+        })
+    }
+
+    /// Search a file line by line, keeping only the context lines in memory
+    fn search_large_file(&self, path: &Path) -> Option<FileMatches> {
+        let context = self.query.context_lines;
+        let max_results = self.query.max_results;
+        let mut before: VecDeque<String> = VecDeque::with_capacity(context);
+        let mut matches: Vec<SearchMatch> = Vec::new();
+        // Matches still collecting their trailing context
+        let mut awaiting: Vec<usize> = Vec::new();
+
+        LineScanner::new()
+            .scan(path, |line_number, line| {
+                for &index in &awaiting {
+                    matches[index].context_after.push(line.to_string());
+                }
+                awaiting.retain(|&index| matches[index].context_after.len() < context);
+
+                let first_new = matches.len();
+                for found in self.matcher.find_iter(line) {
+                    if matches.len() >= max_results {
+                        break;
+                    }
+                    matches.push(SearchMatch {
+                        line_number,
+                        column: line[..found.start()].chars().count() + 1,
+                        match_start: found.start(),
+                        match_end: found.end(),
+                        line: line.to_string(),
+                        context_before: before.iter().cloned().collect(),
+                        context_after: Vec::new(),
+                    });
+                }
+
+                if context > 0 {
+                    awaiting.extend(first_new..matches.len());
+                    if before.len() == context {
+                        before.pop_front();
+                    }
+                    before.push_back(line.to_string());
+                }
+                matches.len() < max_results || !awaiting.is_empty()
+            })
+            .ok()?;
+
+        Some(FileMatches {
+            path: self.relative(path),
+            matches,
+        })
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn is_excluded(&self, relative: &str) -> bool {
+        self.exclude.iter().any(|p| p.matches(relative))
+    }
+
+    fn is_included(&self, relative: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|p| p.matches(relative))
+    }
+
+    /// Collect searchable files under the root, optionally skipping files
+    /// larger than `max_bytes`
+    fn collect_files(&self, max_bytes: Option<u64>) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut guard = WalkGuard::new(&self.root, self.query.follow_symlinks);
+        guard.start(&self.root);
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.starts_with('.') {
+                    continue;
+                }
+
+                let relative = self.relative(&path);
+                if self.is_excluded(&relative) {
+                    continue;
+                }
+
+                if !guard.allows(&path) {
+                    continue;
+                }
+
+                // The guard only lets through links that may be followed
+                let metadata = std::fs::metadata(&path)?;
+                if metadata.is_dir() {
+                    if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                        pending.push(path);
+                    }
+                } else if metadata.is_file()
+                    && self.is_included(&relative)
+                    && match max_bytes {
+                        Some(max) => metadata.len() <= max,
+                        None => true,
+                    }
+                    && !is_binary(&path)
+                {
+                    files.push(path);
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Check the start of a file for NUL bytes
+fn is_binary(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut buffer = [0u8; 8192];
+    match std::fs::File::open(path).and_then(|mut f| f.read(&mut buffer)) {
+        Ok(read) => buffer[..read].contains(&0),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "// intro\nfn parse_config() {}\nfn parse_args() {}\n// end\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/nested/util.rs"), "pub fn Parse() {}\n").unwrap();
+        std::fs::write(root.join("target/generated.rs"), "fn parse() {}\n").unwrap();
+        std::fs::write(root.join("docs/guide.md"), "How to parse input\n").unwrap();
+        std::fs::write(root.join("image.bin"), b"parse\0\x01\x02").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_literal_search_with_context() {
+        let temp_dir = project();
+        let search = ProjectSearch::new(temp_dir.path(), SearchQuery::new("parse")).unwrap();
+        let summary = search.run().unwrap();
+
+        let paths: Vec<&str> = summary.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["docs/guide.md", "src/lib.rs", "src/nested/util.rs"]
+        );
+        assert_eq!(summary.total_matches, 4);
+        assert!(!summary.truncated);
+
+        let lib = &summary.files[1];
+        let first = &lib.matches[0];
+        assert_eq!(first.line_number, 2);
+        assert_eq!(first.column, 4);
+        assert_eq!(&first.line[first.match_start..first.match_end], "parse");
+        assert_eq!(first.context_before, vec!["// intro"]);
+        assert_eq!(first.context_after, vec!["fn parse_args() {}", "// end"]);
+    }
+
+    #[test]
+    fn test_regex_case_and_globs() {
+        let temp_dir = project();
+        let mut query = SearchQuery::new(r"fn (P\w+)");
+        query.regex = true;
+        query.case_sensitive = true;
+        let summary = ProjectSearch::new(temp_dir.path(), query.clone())
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(summary.total_matches, 1);
+        assert_eq!(summary.files[0].path, "src/nested/util.rs");
+
+        let mut query = SearchQuery::new("parse");
+        query.include = vec!["src/**/*.rs".to_string()];
+        query.exclude = vec!["src/nested".to_string()];
+        let summary = ProjectSearch::new(temp_dir.path(), query)
+            .unwrap()
+            .run()
+            .unwrap();
+        let paths: Vec<&str> = summary.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_streaming_and_max_results() {
+        let temp_dir = project();
+        let mut query = SearchQuery::new("parse");
+        query.max_results = 2;
+
+        let mut streamed = Vec::new();
+        let summary = ProjectSearch::new(temp_dir.path(), query)
+            .unwrap()
+            .run_streaming(|file| streamed.push(file))
+            .unwrap();
+
+        assert!(summary.files.is_empty());
+        assert!(summary.truncated);
+        assert_eq!(summary.total_matches, 2);
+        assert_eq!(streamed.iter().map(|f| f.matches.len()).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn test_replace_in_project() {
+        let temp_dir = project();
+        let mut query = SearchQuery::new(r"parse_(\w+)");
+        query.regex = true;
+        query.case_sensitive = true;
+        let summary = ProjectSearch::new(temp_dir.path(), query)
+            .unwrap()
+            .replace("read_$1")
+            .unwrap();
+
+        assert_eq!(summary.files, vec![("src/lib.rs".to_string(), 2)]);
+        assert_eq!(summary.total_replacements, 2);
+        let content = std::fs::read_to_string(temp_dir.path().join("src/lib.rs")).unwrap();
+        assert!(content.contains("fn read_config() {}\nfn read_args() {}"));
+
+        let summary = ProjectSearch::new(temp_dir.path(), SearchQuery::new("How to"))
+            .unwrap()
+            .replace("$1 Ways to")
+            .unwrap();
+        assert_eq!(summary.total_replacements, 1);
+        let guide = std::fs::read_to_string(temp_dir.path().join("docs/guide.md")).unwrap();
+        assert_eq!(guide, "$1 Ways to parse input\n");
+    }
+
+    #[test]
+    fn test_replace_whole_word_preserving_case() {
+        let temp_dir = project();
+        std::fs::write(
+            temp_dir.path().join("src/config.rs"),
+            "struct ParseConfig;\nconst PARSE_CONFIG: u8 = 0;\nfn parse_config() {}\nfn parse_configs() {}\n",
+        )
+        .unwrap();
+        let mut query = SearchQuery::new("parseConfig");
+        query.case_sensitive = true;
+        query.match_whole_word = true;
+        query.preserve_case = true;
+        query.include = vec!["src/config.rs".to_string()];
+        let summary = ProjectSearch::new(temp_dir.path(), query).unwrap().replace("loadSettings").unwrap();
+
+        assert_eq!(summary.total_replacements, 3);
+        let content = std::fs::read_to_string(temp_dir.path().join("src/config.rs")).unwrap();
+        assert_eq!(
+            content,
+            "struct LoadSettings;\nconst LOAD_SETTINGS: u8 = 0;\nfn load_settings() {}\nfn parse_configs() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_large_file_search() {
+        let temp_dir = project();
+        let mut log = String::new();
+        let mut number = 0;
+        while log.len() as u64 <= LARGE_FILE_BYTES {
+            number += 1;
+            log.push_str(&format!("line {} ok\n", number));
+        }
+        log.push_str(&format!("{}\n", "parse ".repeat(4000)));
+        log.push_str("before\nparse error here\nafter one\nafter two\nafter three\n");
+        std::fs::write(temp_dir.path().join("app.log"), &log).unwrap();
+
+        let mut query = SearchQuery::new("parse");
+        query.include = vec!["*.log".to_string()];
+        let summary = ProjectSearch::new(temp_dir.path(), query.clone())
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(summary.total_matches, 1);
+        let found = &summary.files[0].matches[0];
+        assert_eq!(found.line_number, number + 3);
+        assert_eq!(found.line, "parse error here");
+        assert_eq!(
+            found.context_before,
+            vec![format!("line {} ok", number), "before".to_string()]
+        );
+        assert_eq!(found.context_after, vec!["after one", "after two"]);
+
+        let summary = ProjectSearch::new(temp_dir.path(), query)
+            .unwrap()
+            .replace("read")
+            .unwrap();
+        assert_eq!(summary.total_replacements, 0);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        let temp_dir = project();
+        let mut query = SearchQuery::new("(unclosed");
+        query.regex = true;
+        assert!(matches!(
+            ProjectSearch::new(temp_dir.path(), query),
+            Err(SearchError::InvalidPattern(_))
+        ));
+
+        let mut query = SearchQuery::new("x");
+        query.include = vec!["[".to_string()];
+        assert!(matches!(
+            ProjectSearch::new(temp_dir.path(), query),
+            Err(SearchError::InvalidGlob(_, _))
+        ));
+
+        assert!(ProjectSearch::new(temp_dir.path(), SearchQuery::new("")).is_err());
+    }
+}
@@ -0,0 +1,524 @@
+//! Shell selection and login-shell environment
+//!
+//! The bash tool runs commands through the shell chosen in the `[shell]`
+//! section of `.AuroraHeart/config.toml`, optionally as a login or
+//! interactive shell. An IDE started from a desktop launcher does not inherit
+//! the `PATH` that `.zshrc` or `.bashrc` set up for nvm, pyenv, or cargo, so
+//! the user's login shell is run once at startup and its environment is
+//! captured as a [`ShellEnvironment`] for tool commands to use.
+//!
+//! On Windows the default shell is PowerShell. Commands are passed to it
+//! base64-encoded, which sidesteps Windows argument quoting and keeps
+//! non-ASCII text intact, and the POSIX-isms agents commonly write (`&&`,
+//! `||`, `export`, `/dev/null`) are translated for Windows PowerShell 5.
+
+use crate::config::{ShellConfig, ShellKind};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use thiserror::Error;
+
+/// How long the login shell may take to print its environment
+pub const DEFAULT_CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Markers around the captured environment, so rc-file output can be ignored
+const ENV_START: &str = "__AURORAHEART_ENV_START__";
+const ENV_END: &str = "__AURORAHEART_ENV_END__";
+
+/// Variables describing the capturing shell itself rather than the user's setup
+const SHELL_LOCAL_VARS: &[&str] = &["_", "OLDPWD", "PWD", "SHLVL"];
+
+/// Run before every PowerShell command so output is UTF-8
+const POWERSHELL_PRELUDE: &str = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+                                  $OutputEncoding = [System.Text.Encoding]::UTF8; ";
+
+/// Errors that can occur while capturing a shell environment
+#[derive(Error, Debug)]
+pub enum ShellError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The shell cannot print its environment this way
+    #[error("Capturing the environment is not supported for {0:?}")]
+    Unsupported(ShellKind),
+
+    /// The shell did not finish in time
+    #[error("Shell did not print its environment within {0:?}")]
+    Timeout(Duration),
+
+    /// The shell's output held no environment
+    #[error("Shell output did not contain an environment")]
+    NoEnvironment,
+}
+
+pub type Result<T> = std::result::Result<T, ShellError>;
+
+impl ShellKind {
+    /// Shell used when none is configured
+    ///
+    /// `sh` on Unix. On Windows, PowerShell 7 when `pwsh` is on PATH and
+    /// Windows PowerShell otherwise.
+    pub fn platform_default() -> Self {
+        if !cfg!(target_os = "windows") {
+            ShellKind::Sh
+        } else if find_on_path(ShellKind::Pwsh.program()).is_some() {
+            ShellKind::Pwsh
+        } else {
+            ShellKind::Powershell
+        }
+    }
+
+    /// Display name for messages and tool descriptions
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ShellKind::Sh => "sh",
+            ShellKind::Bash => "bash",
+            ShellKind::Zsh => "zsh",
+            ShellKind::Fish => "fish",
+            ShellKind::Pwsh => "PowerShell 7 (pwsh)",
+            ShellKind::Powershell => "Windows PowerShell 5",
+            ShellKind::Cmd => "cmd.exe",
+        }
+    }
+
+    /// Whether this is PowerShell 7 or Windows PowerShell
+    pub fn is_powershell(&self) -> bool {
+        matches!(self, ShellKind::Pwsh | ShellKind::Powershell)
+    }
+
+    /// Usual executable name
+    pub fn program(&self) -> &'static str {
+        match self {
+            ShellKind::Sh => "sh",
+            ShellKind::Bash => "bash",
+            ShellKind::Zsh => "zsh",
+            ShellKind::Fish => "fish",
+            ShellKind::Pwsh => "pwsh",
+            ShellKind::Powershell => "powershell",
+            ShellKind::Cmd => "cmd",
+        }
+    }
+
+    /// Recognize a shell from its executable path (e.g. `/usr/bin/zsh`)
+    pub fn from_program(path: &Path) -> Option<Self> {
+        let name = path.file_stem()?.to_str()?.to_lowercase();
+        [
+            ShellKind::Sh,
+            ShellKind::Bash,
+            ShellKind::Zsh,
+            ShellKind::Fish,
+            ShellKind::Pwsh,
+            ShellKind::Powershell,
+            ShellKind::Cmd,
+        ]
+        .into_iter()
+        .find(|kind| kind.program() == name)
+    }
+
+    /// Whether the shell understands `-l`, `-i`, and `-c` like `sh`
+    fn is_posix_like(&self) -> bool {
+        matches!(
+            self,
+            ShellKind::Sh | ShellKind::Bash | ShellKind::Zsh | ShellKind::Fish
+        )
+    }
+}
+
+/// A shell and the way commands are passed to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    kind: ShellKind,
+    program: PathBuf,
+    login: bool,
+    interactive: bool,
+}
+
+impl Shell {
+    /// A non-login, non-interactive shell of `kind` found on PATH
+    pub fn new(kind: ShellKind) -> Self {
+        Self {
+            kind,
+            program: PathBuf::from(kind.program()),
+            login: false,
+            interactive: false,
+        }
+    }
+
+    /// The shell described by a `[shell]` config section
+    ///
+    /// Without a configured kind, the kind is recognized from the configured
+    /// path, falling back to the platform default.
+    pub fn from_config(config: &ShellConfig) -> Self {
+        let kind = config
+            .kind
+            .or_else(|| config.path.as_deref().and_then(ShellKind::from_program))
+            .unwrap_or_else(ShellKind::platform_default);
+        let mut shell = Self::new(kind)
+            .login(config.login)
+            .interactive(config.interactive);
+        if let Some(path) = &config.path {
+            shell.program = path.clone();
+        }
+        shell
+    }
+
+    /// The user's login shell from `$SHELL`, run as an interactive login shell
+    ///
+    /// Returns `None` when `$SHELL` is unset or names an unknown shell.
+    pub fn user_login_shell() -> Option<Self> {
+        let program = PathBuf::from(std::env::var_os("SHELL")?);
+        let kind = ShellKind::from_program(&program)?;
+        Some(Self {
+            kind,
+            program,
+            login: true,
+            interactive: true,
+        })
+    }
+
+    /// Run commands in a login shell
+    pub fn login(mut self, login: bool) -> Self {
+        self.login = login;
+        self
+    }
+
+    /// Run commands in an interactive shell
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Kind of shell
+    pub fn kind(&self) -> ShellKind {
+        self.kind
+    }
+
+    /// Shell executable
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Arguments that make the shell run `command` and exit
+    ///
+    /// PowerShell receives the command as `-EncodedCommand`, translated by
+    /// [`translate_posix`] for Windows PowerShell.
+    pub fn args(&self, command: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        match self.kind {
+            ShellKind::Cmd => args.push("/C".to_string()),
+            ShellKind::Pwsh | ShellKind::Powershell => {
+                // `-Login` is only accepted first, and only by pwsh on Unix
+                if self.login && self.kind == ShellKind::Pwsh && !cfg!(target_os = "windows") {
+                    args.push("-Login".to_string());
+                }
+                args.push("-NoLogo".to_string());
+                if !self.interactive {
+                    args.extend(["-NoProfile".to_string(), "-NonInteractive".to_string()]);
+                }
+                let command = match self.kind {
+                    ShellKind::Powershell => translate_posix(command),
+                    _ => command.to_string(),
+                };
+                args.push("-EncodedCommand".to_string());
+                args.push(encode_powershell(&format!("{}{}", POWERSHELL_PRELUDE, command)));
+                return args;
+            }
+            _ => {
+                if self.login {
+                    args.push("-l".to_string());
+                }
+                if self.interactive {
+                    args.push("-i".to_string());
+                }
+                args.push("-c".to_string());
+            }
+        }
+        args.push(command.to_string());
+        args
+    }
+
+    /// A command that runs `command` in this shell
+    pub fn command(&self, command: &str) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(self.args(command));
+        cmd
+    }
+}
+
+/// Encode a script for `-EncodedCommand`: base64 of its UTF-16LE bytes
+fn encode_powershell(script: &str) -> String {
+    let bytes: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    BASE64.encode(bytes)
+}
+
+/// Translate common POSIX shell constructs into Windows PowerShell
+///
+/// `a && b` and `a || c` become explicit checks of `$?` (PowerShell 5 has
+/// neither operator), `export NAME=value` sets `$env:NAME`, and `/dev/null`
+/// becomes `$null`. Quoted text is left alone.
+pub fn translate_posix(command: &str) -> String {
+    let segments = split_top_level(command);
+    if segments.len() == 1 && segments[0].0.is_none() {
+        return translate_segment(segments[0].1);
+    }
+
+    let mut script = String::new();
+    let mut chained = false;
+    for (operator, segment) in segments {
+        let segment = translate_segment(segment);
+        match operator {
+            None => script.push_str(&segment),
+            Some(";") => {
+                script.push_str("; ");
+                script.push_str(&segment);
+            }
+            Some(operator) => {
+                if !chained {
+                    script.push_str("; $aurora_ok = $?");
+                    chained = true;
+                }
+                let condition = if operator == "&&" { "$aurora_ok" } else { "-not $aurora_ok" };
+                script.push_str(&format!("; if ({}) {{ {}; $aurora_ok = $? }}", condition, segment));
+            }
+        }
+    }
+    script
+}
+
+/// Split a command at `&&`, `||`, and `;` outside quotes, keeping the operator
+/// that precedes each part
+fn split_top_level(command: &str) -> Vec<(Option<&'static str>, &str)> {
+    let mut parts = Vec::new();
+    let mut operator = None;
+    let mut start = 0;
+    let mut quote = None;
+    let bytes = command.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if b == b'\'' || b == b'"' => quote = Some(b),
+            None => {
+                let next = match (b, bytes.get(i + 1)) {
+                    (b'&', Some(b'&')) => Some(("&&", 2)),
+                    (b'|', Some(b'|')) => Some(("||", 2)),
+                    (b';', _) => Some((";", 1)),
+                    _ => None,
+                };
+                if let Some((op, len)) = next {
+                    parts.push((operator, command[start..i].trim()));
+                    operator = Some(op);
+                    i += len;
+                    start = i;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    parts.push((operator, command[start..].trim()));
+    parts
+}
+
+/// Translate one simple command
+fn translate_segment(segment: &str) -> String {
+    if let Some((name, value)) = segment
+        .strip_prefix("export ")
+        .and_then(|assignment| assignment.trim().split_once('='))
+    {
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        return format!("$env:{} = '{}'", name.trim(), value.replace('\'', "''"));
+    }
+    segment.replace("/dev/null", "$null")
+}
+
+/// Find an executable on PATH, trying Windows executable extensions
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    find_in_path(program, &std::env::var_os("PATH")?)
+}
+
+/// Find an executable in the directories of a `PATH`-style list, trying
+/// Windows executable extensions
+pub fn find_in_path(program: &str, path: &std::ffi::OsStr) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(target_os = "windows") {
+        &[".exe", ".cmd", ".bat", ""]
+    } else {
+        &[""]
+    };
+    std::env::split_paths(path)
+        .flat_map(|dir| {
+            extensions
+                .iter()
+                .map(move |ext| dir.join(format!("{}{}", program, ext)))
+        })
+        .find(|path| path.is_file())
+}
+
+/// Environment variables captured from a shell
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellEnvironment {
+    vars: BTreeMap<String, String>,
+}
+
+impl ShellEnvironment {
+    /// Run `shell` once and capture the environment it sets up
+    ///
+    /// The shell's stdin is empty and anything its startup files print is
+    /// ignored. A shell that takes longer than `timeout` is killed.
+    pub fn capture(shell: &Shell, timeout: Duration) -> Result<Self> {
+        if !shell.kind().is_posix_like() {
+            return Err(ShellError::Unsupported(shell.kind()));
+        }
+
+        let script = format!("printf '%s' {}; env -0; printf '%s' {}", ENV_START, ENV_END);
+        let mut child = shell
+            .command(&script)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut stdout = child.stdout.take().ok_or(ShellError::NoEnvironment)?;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = sender.send(stdout.read_to_end(&mut output).map(|_| output));
+        });
+
+        match receiver.recv_timeout(timeout) {
+            Ok(output) => {
+                let output = output?;
+                child.wait()?;
+                Self::parse(&output).ok_or(ShellError::NoEnvironment)
+            }
+            Err(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                Err(ShellError::Timeout(timeout))
+            }
+        }
+    }
+
+    /// Extract the NUL-separated `env -0` output between the capture markers
+    fn parse(output: &[u8]) -> Option<Self> {
+        let output = String::from_utf8_lossy(output);
+        let start = output.find(ENV_START)? + ENV_START.len();
+        let end = output[start..].find(ENV_END)? + start;
+
+        let vars: BTreeMap<String, String> = output[start..end]
+            .split('\0')
+            .filter_map(|entry| entry.split_once('='))
+            .filter(|(name, _)| !name.is_empty() && !SHELL_LOCAL_VARS.contains(name))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        (!vars.is_empty()).then_some(Self { vars })
+    }
+
+    /// All captured variables
+    pub fn vars(&self) -> &BTreeMap<String, String> {
+        &self.vars
+    }
+
+    /// Value of one variable
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    /// Whether nothing was captured
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+}
+
+impl FromIterator<(String, String)> for ShellEnvironment {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            vars: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_args() {
+        assert_eq!(Shell::new(ShellKind::Sh).args("ls"), vec!["-c", "ls"]);
+        assert_eq!(
+            Shell::new(ShellKind::Zsh).login(true).interactive(true).args("ls"),
+            vec!["-l", "-i", "-c", "ls"]
+        );
+        assert_eq!(Shell::new(ShellKind::Cmd).login(true).args("dir"), vec!["/C", "dir"]);
+        let args = Shell::new(ShellKind::Pwsh).args("echo 'héllo' && dir");
+        assert_eq!(&args[..4], ["-NoLogo", "-NoProfile", "-NonInteractive", "-EncodedCommand"]);
+        let utf16: Vec<u16> = BASE64
+            .decode(&args[4])
+            .unwrap()
+            .chunks(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        let script = String::from_utf16(&utf16).unwrap();
+        assert!(script.starts_with(POWERSHELL_PRELUDE));
+        assert!(script.ends_with("echo 'héllo' && dir"));
+
+        let config = ShellConfig {
+            path: Some(PathBuf::from("/opt/homebrew/bin/fish")),
+            login: true,
+            ..ShellConfig::default()
+        };
+        let shell = Shell::from_config(&config);
+        assert_eq!(shell.kind(), ShellKind::Fish);
+        assert_eq!(shell.program(), Path::new("/opt/homebrew/bin/fish"));
+        assert_eq!(shell.args("ls"), vec!["-l", "-c", "ls"]);
+        assert_eq!(
+            Shell::from_config(&ShellConfig::default()).kind(),
+            ShellKind::platform_default()
+        );
+    }
+
+    #[test]
+    fn test_translate_posix() {
+        assert_eq!(translate_posix("cargo build"), "cargo build");
+        assert_eq!(
+            translate_posix("cd app && npm test || echo 'a && b'"),
+            "cd app; $aurora_ok = $?; if ($aurora_ok) { npm test; $aurora_ok = $? }; \
+             if (-not $aurora_ok) { echo 'a && b'; $aurora_ok = $? }"
+        );
+        assert_eq!(
+            translate_posix("export RUST_LOG=\"debug\"; cargo run 2>/dev/null"),
+            "$env:RUST_LOG = 'debug'; cargo run 2>$null"
+        );
+    }
+
+    #[test]
+    fn test_parse_environment() {
+        let output = format!(
+            "Welcome!\n{}PATH=/usr/bin:/home/me/.cargo/bin\0PWD=/tmp\0EMPTY=\0MULTI=a\nb\0{}",
+            ENV_START, ENV_END
+        );
+        let env = ShellEnvironment::parse(output.as_bytes()).unwrap();
+        assert_eq!(env.get("PATH"), Some("/usr/bin:/home/me/.cargo/bin"));
+        assert_eq!(env.get("EMPTY"), Some(""));
+        assert_eq!(env.get("MULTI"), Some("a\nb"));
+        assert_eq!(env.get("PWD"), None);
+        assert!(ShellEnvironment::parse(b"no markers").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_capture_environment() {
+        let env = ShellEnvironment::capture(&Shell::new(ShellKind::Sh), DEFAULT_CAPTURE_TIMEOUT).unwrap();
+        assert!(env.get("PATH").is_some());
+        assert!(matches!(
+            ShellEnvironment::capture(&Shell::new(ShellKind::Cmd), DEFAULT_CAPTURE_TIMEOUT),
+            Err(ShellError::Unsupported(ShellKind::Cmd))
+        ));
+    }
+}
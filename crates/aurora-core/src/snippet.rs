@@ -0,0 +1,245 @@
+//! Scratch runs of short code snippets
+//!
+//! A snippet is written to a fresh directory under the system temp
+//! directory, away from the project, and run there: Python and JavaScript
+//! directly, Rust as a throwaway Cargo crate that is built first and then
+//! run. [`Sandbox`] owns the directory and removes it when dropped.
+//!
+//! This is a scratch space, not a security boundary: the snippet runs as
+//! the user, with their file system and network. What it does bound is
+//! time (the caller's timeout) and memory
+//! ([`ProcessLimits`](crate::ProcessLimits), on Unix and Windows).
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors in preparing a snippet
+#[derive(Error, Debug)]
+pub enum SnippetError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// An unknown language name
+    #[error("Unknown snippet language '{0}'; expected 'rust', 'python', or 'javascript'")]
+    UnknownLanguage(String),
+}
+
+pub type Result<T> = std::result::Result<T, SnippetError>;
+
+/// Language of a snippet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetLanguage {
+    Rust,
+    Python,
+    JavaScript,
+}
+
+impl SnippetLanguage {
+    /// The external program that builds or runs snippets
+    pub fn program(&self) -> &'static str {
+        match self {
+            SnippetLanguage::Rust => "cargo",
+            SnippetLanguage::Python => "python",
+            SnippetLanguage::JavaScript => "node",
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            SnippetLanguage::Rust => "Rust",
+            SnippetLanguage::Python => "Python",
+            SnippetLanguage::JavaScript => "JavaScript",
+        }
+    }
+}
+
+impl FromStr for SnippetLanguage {
+    type Err = SnippetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "rust" | "rs" => Ok(SnippetLanguage::Rust),
+            "python" | "py" => Ok(SnippetLanguage::Python),
+            "javascript" | "js" | "node" => Ok(SnippetLanguage::JavaScript),
+            _ => Err(SnippetError::UnknownLanguage(s.to_string())),
+        }
+    }
+}
+
+/// What a step runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepProgram {
+    /// One of the external programs, by name
+    External(&'static str),
+    /// A binary the previous step built
+    Built(PathBuf),
+}
+
+/// One command of a snippet run, in the sandbox directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetStep {
+    pub program: StepProgram,
+    pub args: Vec<String>,
+    /// Whether this step runs the snippet itself, and so gets the memory
+    /// limit; compiling does not
+    pub runs_snippet: bool,
+}
+
+/// A snippet written to its own temporary directory
+#[derive(Debug)]
+pub struct Sandbox {
+    language: SnippetLanguage,
+    dir: tempfile::TempDir,
+}
+
+impl Sandbox {
+    /// Write `code` into a new directory under the system temp directory
+    ///
+    /// The directory gets a random name and is created fresh, never reusing
+    /// one that already exists.
+    pub fn create(language: SnippetLanguage, code: &str) -> Result<Self> {
+        let dir = tempfile::Builder::new().prefix("auroraheart-snippet-").tempdir()?;
+        // Dropping removes the directory, including on a failed write below
+        let sandbox = Self { language, dir };
+        let root = sandbox.dir();
+
+        match language {
+            SnippetLanguage::Rust => {
+                std::fs::create_dir_all(root.join("src"))?;
+                std::fs::write(
+                    root.join("Cargo.toml"),
+                    "[package]\nname = \"snippet\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[workspace]\n",
+                )?;
+                std::fs::write(root.join("src/main.rs"), rust_main(code))?;
+            }
+            SnippetLanguage::Python => std::fs::write(root.join("snippet.py"), code)?,
+            SnippetLanguage::JavaScript => std::fs::write(root.join(javascript_file(code)), code)?,
+        }
+        Ok(sandbox)
+    }
+
+    /// The sandbox directory
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Commands that run the snippet, in order; `memory_mb` is passed to
+    /// runtimes that have their own heap limit
+    pub fn steps(&self, memory_mb: u64) -> Vec<SnippetStep> {
+        match self.language {
+            SnippetLanguage::Rust => {
+                let target = self.dir().join("target");
+                let binary = target
+                    .join("debug")
+                    .join(format!("snippet{}", std::env::consts::EXE_SUFFIX));
+                vec![
+                    SnippetStep {
+                        program: StepProgram::External("cargo"),
+                        args: vec![
+                            "build".to_string(),
+                            "--quiet".to_string(),
+                            "--offline".to_string(),
+                            "--target-dir".to_string(),
+                            target.to_string_lossy().into_owned(),
+                        ],
+                        runs_snippet: false,
+                    },
+                    SnippetStep {
+                        program: StepProgram::Built(binary),
+                        args: Vec::new(),
+                        runs_snippet: true,
+                    },
+                ]
+            }
+            // -I keeps PYTHONPATH and the user's site-packages out
+            SnippetLanguage::Python => vec![SnippetStep {
+                program: StepProgram::External("python"),
+                args: vec!["-I".to_string(), "snippet.py".to_string()],
+                runs_snippet: true,
+            }],
+            SnippetLanguage::JavaScript => {
+                let file = if self.dir().join("snippet.mjs").exists() {
+                    "snippet.mjs"
+                } else {
+                    "snippet.js"
+                };
+                vec![SnippetStep {
+                    program: StepProgram::External("node"),
+                    args: vec![format!("--max-old-space-size={}", memory_mb), file.to_string()],
+                    runs_snippet: true,
+                }]
+            }
+        }
+    }
+}
+
+/// `code` as a crate's `main.rs`: as it is when it has a `main`, otherwise
+/// as the body of one, with its leading `use` lines kept at the top
+fn rust_main(code: &str) -> String {
+    if code.contains("fn main(") {
+        return code.to_string();
+    }
+    let mut uses = String::new();
+    let mut lines = code.lines().peekable();
+    while let Some(line) = lines.next_if(|line| {
+        let trimmed = line.trim();
+        trimmed.is_empty() || trimmed.starts_with("use ") || trimmed.starts_with("//")
+    }) {
+        uses.push_str(line);
+        uses.push('\n');
+    }
+    let body: Vec<&str> = lines.collect();
+    format!("{}fn main() {{\n{}\n}}\n", uses, body.join("\n"))
+}
+
+/// The file name for a JavaScript snippet: `.mjs` when it uses ES module
+/// `import` or `export` statements, so Node loads it as a module
+fn javascript_file(code: &str) -> &'static str {
+    let module = code.lines().any(|line| {
+        let line = line.trim_start();
+        line.starts_with("import ") || line.starts_with("import{") || line.starts_with("export ")
+    });
+    if module {
+        "snippet.mjs"
+    } else {
+        "snippet.js"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_main() {
+        assert_eq!(rust_main("fn main() {}\n"), "fn main() {}\n");
+        assert_eq!(
+            rust_main("use std::collections::HashMap;\n\nlet m: HashMap<u8, u8> = HashMap::new();\nprintln!(\"{}\", m.len());"),
+            "use std::collections::HashMap;\n\nfn main() {\nlet m: HashMap<u8, u8> = HashMap::new();\nprintln!(\"{}\", m.len());\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let sandbox = Sandbox::create(SnippetLanguage::JavaScript, "import fs from 'node:fs';\n").unwrap();
+        let dir = sandbox.dir().to_path_buf();
+        assert!(dir.join("snippet.mjs").exists());
+        let steps = sandbox.steps(256);
+        assert_eq!(steps[0].args, vec!["--max-old-space-size=256", "snippet.mjs"]);
+        drop(sandbox);
+        assert!(!dir.exists());
+
+        let sandbox = Sandbox::create(SnippetLanguage::Rust, "println!(\"hi\");").unwrap();
+        assert!(sandbox.dir().join("Cargo.toml").exists());
+        let steps = sandbox.steps(256);
+        assert_eq!(steps[0].program, StepProgram::External("cargo"));
+        assert!(!steps[0].runs_snippet);
+        assert!(matches!(&steps[1].program, StepProgram::Built(path) if path.starts_with(sandbox.dir())));
+
+        assert_eq!("py".parse::<SnippetLanguage>().unwrap(), SnippetLanguage::Python);
+        assert!("cobol".parse::<SnippetLanguage>().is_err());
+    }
+}
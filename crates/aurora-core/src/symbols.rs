@@ -0,0 +1,708 @@
+//! Workspace symbol index for go-to-symbol
+//!
+//! Definitions (functions, methods, types, traits, classes, constants, …)
+//! are extracted from Rust, Python, JavaScript/TypeScript, Go, and Java
+//! sources with their tree-sitter grammars and kept in
+//! `.AuroraHeart/index/symbols.json`. Each file's entry records the
+//! modification time and size it was extracted from, so
+//! [`SymbolIndex::update`] only re-reads files that changed since the index
+//! was last saved, including across restarts.
+//!
+//! A definition inside a class, trait, interface, or `impl` block is
+//! attributed to it, as is a Go method to its receiver type.
+
+use crate::fuzzy::FuzzyQuery;
+use crate::grammar::Grammar;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+use tree_sitter::Node;
+
+/// Bump when extraction changes so stored indexes are rebuilt
+const INDEX_VERSION: u32 = 2;
+
+/// Files larger than this are assumed to be generated and skipped
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Bonus for a symbol named exactly like the query, ignoring case
+const EXACT_NAME_BONUS: i64 = 32;
+
+/// Errors that can occur while saving the symbol index
+#[derive(Error, Debug)]
+pub enum SymbolIndexError {
+    /// IO error while writing the index
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The index could not be serialized
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Result type for symbol index operations
+pub type Result<T> = std::result::Result<T, SymbolIndexError>;
+
+/// What kind of definition a symbol is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Interface,
+    Class,
+    Module,
+    Type,
+    Constant,
+    Macro,
+}
+
+impl SymbolKind {
+    /// Lowercase name, as used in tool output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Method => "method",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Class => "class",
+            SymbolKind::Module => "module",
+            SymbolKind::Type => "type",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Macro => "macro",
+        }
+    }
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A definition found in a source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    /// Name of the definition
+    pub name: String,
+    /// What kind of definition it is
+    pub kind: SymbolKind,
+    /// 1-based line of the definition
+    pub line: usize,
+    /// Type, trait, class, or interface the definition belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+}
+
+impl Symbol {
+    /// Name qualified with its container, e.g. `Parser::parse`
+    pub fn qualified_name(&self) -> String {
+        match &self.container {
+            Some(container) => format!("{}::{}", container, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A symbol matched by a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolMatch {
+    /// File defining the symbol, relative to the project root
+    pub path: String,
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    /// Higher is better
+    pub score: i64,
+    /// Character indices of the matched characters in the symbol's name
+    pub positions: Vec<usize>,
+}
+
+impl Ord for SymbolMatch {
+    /// Better matches sort first: higher score, then shorter name, then by
+    /// location
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .cmp(&self.score)
+            .then_with(|| self.symbol.name.len().cmp(&other.symbol.name.len()))
+            .then_with(|| self.path.cmp(&other.path))
+            .then_with(|| self.symbol.line.cmp(&other.symbol.line))
+            .then_with(|| self.symbol.name.cmp(&other.symbol.name))
+    }
+}
+
+impl PartialOrd for SymbolMatch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// What a [`SymbolIndex::update`] did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexUpdate {
+    /// Files (re-)extracted because they are new or changed
+    pub indexed: usize,
+    /// Files dropped because they no longer exist
+    pub removed: usize,
+}
+
+/// The symbols of one file and the file state they were extracted from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    size: u64,
+    symbols: Vec<Symbol>,
+}
+
+/// The stored form of the index
+#[derive(Serialize, Deserialize)]
+struct StoredIndex {
+    version: u32,
+    files: BTreeMap<String, IndexedFile>,
+}
+
+/// Symbols of every source file in a project
+#[derive(Debug)]
+pub struct SymbolIndex {
+    root: PathBuf,
+    files: BTreeMap<String, IndexedFile>,
+    /// Changed since it was loaded or last saved
+    dirty: bool,
+}
+
+impl SymbolIndex {
+    /// The stored index of the project at `project_root`, or an empty index
+    /// if none was saved or it cannot be read
+    pub fn load(project_root: impl Into<PathBuf>) -> Self {
+        let mut index = Self {
+            root: project_root.into(),
+            files: BTreeMap::new(),
+            dirty: false,
+        };
+        let path = index.path();
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<StoredIndex>(&contents) {
+                Ok(stored) if stored.version == INDEX_VERSION => index.files = stored.files,
+                Ok(_) => tracing::info!("Rebuilding symbol index from an older version"),
+                Err(e) => tracing::warn!("Ignoring unreadable symbol index {}: {}", path.display(), e),
+            }
+        }
+        index
+    }
+
+    /// Path of the stored index
+    pub fn path(&self) -> PathBuf {
+        self.root.join(".AuroraHeart").join("index").join("symbols.json")
+    }
+
+    /// Number of indexed symbols
+    pub fn len(&self) -> usize {
+        self.files.values().map(|file| file.symbols.len()).sum()
+    }
+
+    /// Whether no symbol is indexed
+    pub fn is_empty(&self) -> bool {
+        self.files.values().all(|file| file.symbols.is_empty())
+    }
+
+    /// Bring the index in line with the project's files
+    ///
+    /// `paths` lists every file of the project relative to its root, e.g.
+    /// from a [`FileIndex`](crate::FileIndex). Source files that are new or
+    /// whose modification time or size changed are re-extracted; indexed
+    /// files missing from `paths` are dropped.
+    pub fn update<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) -> IndexUpdate {
+        let mut update = IndexUpdate::default();
+        let mut seen = BTreeSet::new();
+
+        for relative in paths {
+            if indexed_grammar(Path::new(relative)).is_none() {
+                continue;
+            }
+            seen.insert(relative);
+            let Ok(metadata) = std::fs::metadata(self.root.join(relative)) else {
+                continue;
+            };
+            let modified = metadata.modified().ok();
+            let size = metadata.len();
+            if self
+                .files
+                .get(relative)
+                .is_some_and(|file| file.modified == modified && file.size == size)
+            {
+                continue;
+            }
+
+            let symbols = if size > MAX_FILE_BYTES {
+                Vec::new()
+            } else {
+                match std::fs::read_to_string(self.root.join(relative)) {
+                    Ok(text) => extract_symbols(Path::new(relative), &text),
+                    // Not UTF-8 or unreadable: remember it as having none
+                    Err(_) => Vec::new(),
+                }
+            };
+            self.files.insert(
+                relative.to_string(),
+                IndexedFile {
+                    modified,
+                    size,
+                    symbols,
+                },
+            );
+            update.indexed += 1;
+        }
+
+        let before = self.files.len();
+        self.files.retain(|path, _| seen.contains(path.as_str()));
+        update.removed = before - self.files.len();
+
+        if update.indexed > 0 || update.removed > 0 {
+            self.dirty = true;
+        }
+        update
+    }
+
+    /// Write the index to [`Self::path`] if it changed
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let path = self.path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let stored = StoredIndex {
+            version: INDEX_VERSION,
+            files: std::mem::take(&mut self.files),
+        };
+        let serialized = serde_json::to_string(&stored);
+        self.files = stored.files;
+
+        // Write then rename, so a crash never leaves a truncated index
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serialized?)?;
+        std::fs::rename(&temp, &path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Symbols defined in `path` (relative to the root), in line order
+    pub fn symbols_in(&self, path: &str) -> &[Symbol] {
+        self.files
+            .get(path)
+            .map(|file| file.symbols.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The `limit` symbols whose names best match `query`, best first,
+    /// optionally only those of `kind`
+    pub fn find(&self, query: &str, kind: Option<SymbolKind>, limit: usize) -> Vec<SymbolMatch> {
+        let parsed = FuzzyQuery::new(query);
+        if limit == 0 || parsed.is_empty() {
+            return Vec::new();
+        }
+        let exact = query.trim().to_lowercase();
+        let mut best: BinaryHeap<SymbolMatch> = BinaryHeap::with_capacity(limit + 1);
+
+        for (path, file) in &self.files {
+            for symbol in &file.symbols {
+                if kind.is_some_and(|kind| kind != symbol.kind) {
+                    continue;
+                }
+                let Some(matched) = parsed.matches(&symbol.name) else {
+                    continue;
+                };
+                let bonus = if symbol.name.to_lowercase() == exact { EXACT_NAME_BONUS } else { 0 };
+                let candidate = SymbolMatch {
+                    path: path.clone(),
+                    symbol: symbol.clone(),
+                    score: matched.score + bonus,
+                    positions: matched.positions,
+                };
+                if best.len() < limit {
+                    best.push(candidate);
+                } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                    best.pop();
+                    best.push(candidate);
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+    }
+}
+
+/// The grammar of a file in a language the index covers
+fn indexed_grammar(path: &Path) -> Option<Grammar> {
+    Grammar::for_path(path).filter(|grammar| {
+        matches!(
+            grammar,
+            Grammar::Rust
+                | Grammar::Python
+                | Grammar::JavaScript
+                | Grammar::TypeScript
+                | Grammar::Tsx
+                | Grammar::Go
+                | Grammar::Java
+        )
+    })
+}
+
+/// A definition, or a block whose definitions belong to a type
+enum Found<'tree> {
+    Definition {
+        name: Node<'tree>,
+        kind: SymbolKind,
+        /// Set when the definition names its container itself, like a Go
+        /// method's receiver
+        container: Option<String>,
+        /// Definitions inside this one belong to it
+        opens: bool,
+    },
+    /// A Rust `impl` block for the named type
+    Container(String),
+}
+
+/// Extract the definitions in `text`, the contents of the file at `path`
+///
+/// Returns nothing for files in languages the index does not cover.
+pub fn extract_symbols(path: &Path, text: &str) -> Vec<Symbol> {
+    let Some(grammar) = indexed_grammar(path) else {
+        return Vec::new();
+    };
+    let Some(tree) = grammar.parse(text) else {
+        return Vec::new();
+    };
+    let source = text.as_bytes();
+    let mut symbols = Vec::new();
+    // Containers being walked, as (node id, name)
+    let mut containers: Vec<(usize, String)> = Vec::new();
+    let mut cursor = tree.walk();
+
+    loop {
+        let node = cursor.node();
+        match definition(grammar, node, source) {
+            Some(Found::Definition {
+                name,
+                kind,
+                container,
+                opens,
+            }) => {
+                if let Ok(name_text) = name.utf8_text(source) {
+                    let container = container.or_else(|| containers.last().map(|(_, name)| name.clone()));
+                    let kind = match kind {
+                        SymbolKind::Function if container.is_some() => SymbolKind::Method,
+                        kind => kind,
+                    };
+                    if opens {
+                        containers.push((node.id(), name_text.to_string()));
+                    }
+                    symbols.push(Symbol {
+                        name: name_text.to_string(),
+                        kind,
+                        line: name.start_position().row + 1,
+                        container,
+                    });
+                }
+            }
+            Some(Found::Container(name)) => containers.push((node.id(), name)),
+            None => {}
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+        // Leave finished nodes until one has a next sibling
+        loop {
+            if containers.last().is_some_and(|(id, _)| *id == cursor.node().id()) {
+                containers.pop();
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return symbols;
+            }
+        }
+    }
+}
+
+/// What `node` defines, if anything
+fn definition<'tree>(grammar: Grammar, node: Node<'tree>, source: &[u8]) -> Option<Found<'tree>> {
+    let named = |kind, opens| {
+        Some(Found::Definition {
+            name: node.child_by_field_name("name")?,
+            kind,
+            container: None,
+            opens,
+        })
+    };
+
+    match grammar {
+        Grammar::Rust => match node.kind() {
+            "function_item" | "function_signature_item" => named(SymbolKind::Function, false),
+            "struct_item" | "union_item" => named(SymbolKind::Struct, false),
+            "enum_item" => named(SymbolKind::Enum, false),
+            "trait_item" => named(SymbolKind::Trait, true),
+            "type_item" => named(SymbolKind::Type, false),
+            "mod_item" => named(SymbolKind::Module, false),
+            "const_item" | "static_item" => named(SymbolKind::Constant, false),
+            "macro_definition" => named(SymbolKind::Macro, false),
+            "impl_item" => rust_type_name(node.child_by_field_name("type")?, source).map(Found::Container),
+            _ => None,
+        },
+        Grammar::Python => match node.kind() {
+            "function_definition" => named(SymbolKind::Function, false),
+            "class_definition" => named(SymbolKind::Class, true),
+            _ => None,
+        },
+        Grammar::JavaScript | Grammar::TypeScript | Grammar::Tsx => match node.kind() {
+            "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                named(SymbolKind::Function, false)
+            }
+            "class_declaration" | "abstract_class_declaration" => named(SymbolKind::Class, true),
+            "interface_declaration" => named(SymbolKind::Interface, true),
+            "enum_declaration" => named(SymbolKind::Enum, false),
+            "type_alias_declaration" => named(SymbolKind::Type, false),
+            // Methods of object literals are not definitions worth indexing
+            "method_definition" if node.parent().is_some_and(|parent| parent.kind() == "class_body") => {
+                named(SymbolKind::Method, false)
+            }
+            "method_signature" | "abstract_method_signature" => named(SymbolKind::Method, false),
+            "variable_declarator" => {
+                let value = node.child_by_field_name("value")?;
+                let is_function = matches!(
+                    value.kind(),
+                    "arrow_function" | "function_expression" | "function" | "generator_function"
+                );
+                let name = node.child_by_field_name("name")?;
+                (is_function && name.kind() == "identifier").then_some(Found::Definition {
+                    name,
+                    kind: SymbolKind::Function,
+                    container: None,
+                    opens: false,
+                })
+            }
+            _ => None,
+        },
+        Grammar::Go => match node.kind() {
+            "function_declaration" => named(SymbolKind::Function, false),
+            "method_declaration" => {
+                let receiver = node.child_by_field_name("receiver")?;
+                Some(Found::Definition {
+                    name: node.child_by_field_name("name")?,
+                    kind: SymbolKind::Method,
+                    container: first_of_kind(receiver, "type_identifier")
+                        .and_then(|node| node.utf8_text(source).ok())
+                        .map(str::to_string),
+                    opens: false,
+                })
+            }
+            "type_spec" => match node.child_by_field_name("type")?.kind() {
+                "struct_type" => named(SymbolKind::Struct, false),
+                "interface_type" => named(SymbolKind::Interface, false),
+                _ => named(SymbolKind::Type, false),
+            },
+            "type_alias" => named(SymbolKind::Type, false),
+            "const_spec" => named(SymbolKind::Constant, false),
+            _ => None,
+        },
+        Grammar::Java => match node.kind() {
+            "class_declaration" | "record_declaration" => named(SymbolKind::Class, true),
+            "interface_declaration" | "annotation_type_declaration" => named(SymbolKind::Interface, true),
+            "enum_declaration" => named(SymbolKind::Enum, true),
+            "method_declaration" | "constructor_declaration" => named(SymbolKind::Method, false),
+            _ => None,
+        },
+        Grammar::C | Grammar::Cpp | Grammar::Ruby | Grammar::Json => None,
+    }
+}
+
+/// The type a Rust `impl` is for: `Parser` for `Parser<'a>`, `fmt::Parser`,
+/// or `&Parser`
+fn rust_type_name(node: Node, source: &[u8]) -> Option<String> {
+    match node.kind() {
+        "type_identifier" => node.utf8_text(source).ok().map(str::to_string),
+        "generic_type" | "reference_type" => rust_type_name(node.child_by_field_name("type")?, source),
+        "scoped_type_identifier" => rust_type_name(node.child_by_field_name("name")?, source),
+        _ => None,
+    }
+}
+
+/// The first node of `kind` in `node`'s subtree, in document order
+fn first_of_kind<'tree>(node: Node<'tree>, kind: &str) -> Option<Node<'tree>> {
+    if node.kind() == kind {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).find_map(|child| first_of_kind(child, kind));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(symbols: &[Symbol]) -> Vec<(String, SymbolKind, usize)> {
+        symbols
+            .iter()
+            .map(|s| (s.qualified_name(), s.kind, s.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_rust_symbols() {
+        let source = r#"//! Parser
+pub struct Parser<'a> {
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    pub(crate) async fn parse(&self) -> Ast {
+        if ready() {
+        }
+    }
+}
+
+impl fmt::Display for Parser<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+pub trait Visit {
+    fn visit(&self);
+}
+
+const MAX_DEPTH: usize = 64;
+macro_rules! bail { () => {} }
+fn helper() {}
+"#;
+        assert_eq!(
+            names(&extract_symbols(Path::new("src/parser.rs"), source)),
+            vec![
+                ("Parser".to_string(), SymbolKind::Struct, 2),
+                ("Parser::new".to_string(), SymbolKind::Method, 7),
+                ("Parser::parse".to_string(), SymbolKind::Method, 11),
+                ("Parser::fmt".to_string(), SymbolKind::Method, 18),
+                ("Visit".to_string(), SymbolKind::Trait, 23),
+                ("Visit::visit".to_string(), SymbolKind::Method, 24),
+                ("MAX_DEPTH".to_string(), SymbolKind::Constant, 27),
+                ("bail".to_string(), SymbolKind::Macro, 28),
+                ("helper".to_string(), SymbolKind::Function, 29),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_other_languages() {
+        let python = "class Shape:\n    def area(self):\n        return 0\n\nasync def main():\n    pass\n";
+        assert_eq!(
+            names(&extract_symbols(Path::new("shapes.py"), python)),
+            vec![
+                ("Shape".to_string(), SymbolKind::Class, 1),
+                ("Shape::area".to_string(), SymbolKind::Method, 2),
+                ("main".to_string(), SymbolKind::Function, 5),
+            ]
+        );
+
+        let typescript = "export class Store {\n  async load(id: string): Promise<void> {\n    if (id) {\n    }\n  }\n}\nexport const useStore = () => {};\nexport interface Options {}\n";
+        assert_eq!(
+            names(&extract_symbols(Path::new("store.ts"), typescript)),
+            vec![
+                ("Store".to_string(), SymbolKind::Class, 1),
+                ("Store::load".to_string(), SymbolKind::Method, 2),
+                ("useStore".to_string(), SymbolKind::Function, 7),
+                ("Options".to_string(), SymbolKind::Interface, 8),
+            ]
+        );
+
+        let go = "type Server struct {\n}\n\nfunc (s *Server) Start() error {\n}\n\nfunc main() {\n}\n";
+        assert_eq!(
+            names(&extract_symbols(Path::new("main.go"), go)),
+            vec![
+                ("Server".to_string(), SymbolKind::Struct, 1),
+                ("Server::Start".to_string(), SymbolKind::Method, 4),
+                ("main".to_string(), SymbolKind::Function, 7),
+            ]
+        );
+
+        assert!(extract_symbols(Path::new("notes.txt"), "fn main() {}").is_empty());
+    }
+
+    #[test]
+    fn test_extract_definitions_split_across_lines() {
+        let rust = "impl<T>\n    Display\n    for Wrapper<T>\n{\n    fn\n    fmt(&self) {}\n}\n";
+        assert_eq!(
+            names(&extract_symbols(Path::new("wrapper.rs"), rust)),
+            vec![("Wrapper::fmt".to_string(), SymbolKind::Method, 6)]
+        );
+
+        let java = "public class Cache {\n    @Override\n    public String\n        toString() { return \"\"; }\n}\n";
+        assert_eq!(
+            names(&extract_symbols(Path::new("Cache.java"), java)),
+            vec![
+                ("Cache".to_string(), SymbolKind::Class, 1),
+                ("Cache::toString".to_string(), SymbolKind::Method, 4),
+            ]
+        );
+
+        let go = "const (\n\tMaxSize = 10\n\tMinSize = 1\n)\n";
+        assert_eq!(
+            names(&extract_symbols(Path::new("limits.go"), go)),
+            vec![
+                ("MaxSize".to_string(), SymbolKind::Constant, 2),
+                ("MinSize".to_string(), SymbolKind::Constant, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_updates_incrementally_and_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub fn parse_config() {}\n").unwrap();
+        std::fs::write(root.join("src/util.rs"), "pub struct ConfigParser;\n").unwrap();
+        std::fs::write(root.join("README.md"), "# fn not_code() {}\n").unwrap();
+        let paths = ["README.md", "src/lib.rs", "src/util.rs"];
+
+        let mut index = SymbolIndex::load(root);
+        assert_eq!(index.update(paths), IndexUpdate { indexed: 2, removed: 0 });
+        assert_eq!(index.len(), 2);
+        index.save().unwrap();
+        assert!(root.join(".AuroraHeart/index/symbols.json").exists());
+
+        let found = index.find("config", None, 10);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].symbol.name, "ConfigParser");
+        assert_eq!(index.find("config", Some(SymbolKind::Function), 10)[0].path, "src/lib.rs");
+        assert_eq!(index.find("pc", None, 10)[0].symbol.name, "parse_config");
+
+        // A reloaded index only re-reads what changed
+        let mut index = SymbolIndex::load(root);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.update(paths), IndexUpdate::default());
+        std::fs::write(root.join("src/lib.rs"), "pub fn parse_config() {}\npub fn load() {}\n").unwrap();
+        assert_eq!(
+            index.update(["README.md", "src/lib.rs"]),
+            IndexUpdate { indexed: 1, removed: 1 }
+        );
+        assert_eq!(index.symbols_in("src/lib.rs").len(), 2);
+        assert!(index.symbols_in("src/util.rs").is_empty());
+    }
+}
@@ -0,0 +1,455 @@
+//! Syntax checks without a compiler
+//!
+//! When no compiler or interpreter for a language is installed, the agent
+//! can still learn whether a file is broken. Languages with a bundled
+//! tree-sitter grammar are parsed, and [`check_parse`] reports the places
+//! the parser had to skip or make up code. Other languages get
+//! [`check_structure`], which scans the file with the language's comment and
+//! string rules, skipping their contents, and matches `()`, `[]`, and `{}`:
+//! it catches the ways edits most often break files, an unbalanced bracket
+//! or a string or block comment left open, but not a malformed statement.
+//!
+//! Neither replaces the compiler: a file that passes may still fail to
+//! build, for example on an undefined name or a type error.
+
+use crate::grammar::Grammar;
+use std::fmt;
+use tree_sitter::Node;
+
+/// Comment and string rules of a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syntax {
+    /// Markers that start a comment running to the end of the line
+    pub line_comments: &'static [&'static str],
+    /// Block comment start and end markers
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// Whether block comments nest
+    pub nested_comments: bool,
+    /// Quotes that delimit strings
+    pub quotes: &'static [char],
+    /// Quotes whose strings may span lines
+    pub multiline_quotes: &'static [char],
+    /// Whether tripled quotes (`"""`) delimit multi-line strings
+    pub triple_quotes: bool,
+    /// Whether `'` may also start a Rust lifetime or label
+    pub lifetimes: bool,
+    /// Whether `r"..."` and `r#"..."#` raw strings exist
+    pub raw_strings: bool,
+}
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    nested_comments: false,
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+    triple_quotes: false,
+    lifetimes: false,
+    raw_strings: false,
+};
+
+impl Syntax {
+    /// The rules for a language name, as `syntax_check` names languages
+    pub fn for_language(language: &str) -> Option<Self> {
+        let syntax = match language {
+            "rust" => Syntax {
+                nested_comments: true,
+                multiline_quotes: &['"'],
+                lifetimes: true,
+                raw_strings: true,
+                ..C_LIKE
+            },
+            "javascript" | "typescript" => Syntax {
+                quotes: &['"', '\'', '`'],
+                multiline_quotes: &['`'],
+                ..C_LIKE
+            },
+            "go" => Syntax {
+                quotes: &['"', '\'', '`'],
+                multiline_quotes: &['`'],
+                ..C_LIKE
+            },
+            "kotlin" | "swift" | "scala" => Syntax {
+                nested_comments: true,
+                triple_quotes: true,
+                ..C_LIKE
+            },
+            "java" | "csharp" | "dart" => Syntax {
+                triple_quotes: true,
+                ..C_LIKE
+            },
+            "c" | "cpp" | "zig" => C_LIKE,
+            "php" => Syntax {
+                line_comments: &["//", "#"],
+                multiline_quotes: &['"', '\''],
+                ..C_LIKE
+            },
+            "python" => Syntax {
+                line_comments: &["#"],
+                block_comment: None,
+                triple_quotes: true,
+                ..C_LIKE
+            },
+            "ruby" => Syntax {
+                line_comments: &["#"],
+                block_comment: None,
+                multiline_quotes: &['"', '\''],
+                ..C_LIKE
+            },
+            "lua" => Syntax {
+                line_comments: &["--"],
+                block_comment: Some(("--[[", "]]")),
+                ..C_LIKE
+            },
+            "json" => Syntax {
+                line_comments: &[],
+                block_comment: None,
+                quotes: &['"'],
+                ..C_LIKE
+            },
+            _ => return None,
+        };
+        Some(syntax)
+    }
+}
+
+/// A structural error in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxIssue {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column, in characters
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+struct Scanner<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    syntax: &'a Syntax,
+}
+
+impl Scanner<'_> {
+    fn starts_with(&self, marker: &str) -> bool {
+        marker.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn bump(&mut self) {
+        if self.chars[self.pos] == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.pos += 1;
+    }
+
+    fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.pos < self.chars.len() {
+                self.bump();
+            }
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn current(&self) -> char {
+        self.chars[self.pos]
+    }
+
+    fn skip_line(&mut self) {
+        while !self.at_end() && self.current() != '\n' {
+            self.bump();
+        }
+    }
+
+    /// Skip a block comment whose start marker is at the current position;
+    /// `false` if it never ends
+    fn skip_block_comment(&mut self, start: &str, end: &str) -> bool {
+        self.skip(start.chars().count());
+        let mut depth = 1;
+        while !self.at_end() {
+            if self.starts_with(end) {
+                self.skip(end.chars().count());
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            } else if self.syntax.nested_comments && self.starts_with(start) {
+                self.skip(start.chars().count());
+                depth += 1;
+            } else {
+                self.bump();
+            }
+        }
+        false
+    }
+
+    /// Skip a string whose opening delimiter is at the current position;
+    /// `false` if it never ends
+    fn skip_string(&mut self, quote: char, multiline: bool) -> bool {
+        self.bump();
+        while !self.at_end() {
+            match self.current() {
+                '\\' => self.skip(2),
+                '\n' if !multiline => return false,
+                c if c == quote => {
+                    self.bump();
+                    return true;
+                }
+                _ => self.bump(),
+            }
+        }
+        false
+    }
+
+    fn skip_delimited(&mut self, open: &str, close: &str) -> bool {
+        self.skip(open.chars().count());
+        while !self.at_end() {
+            if self.starts_with(close) {
+                self.skip(close.chars().count());
+                return true;
+            }
+            self.bump();
+        }
+        false
+    }
+
+    /// The `#`s of a raw string starting at the current position
+    fn raw_string_hashes(&self) -> Option<usize> {
+        if self.current() != 'r' || self.pos > 0 && is_ident(self.chars[self.pos - 1]) {
+            return None;
+        }
+        let hashes = self.chars[self.pos + 1..].iter().take_while(|&&c| c == '#').count();
+        (self.chars.get(self.pos + 1 + hashes) == Some(&'"')).then_some(hashes)
+    }
+
+    /// Whether the `'` at the current position starts a lifetime or label
+    /// rather than a character literal
+    fn is_lifetime(&self) -> bool {
+        let next = self.chars.get(self.pos + 1).copied();
+        let after = self.chars.get(self.pos + 2).copied();
+        matches!(next, Some(c) if is_ident(c)) && after != Some('\'')
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn closer(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+/// Syntax errors in `source`, from the parse tree of `grammar`
+///
+/// Each `ERROR` node, code the parser could not fit into the grammar, and
+/// each missing node, code it assumed, is reported once. `None` if the
+/// grammar cannot be loaded.
+pub fn check_parse(source: &str, grammar: Grammar) -> Option<Vec<SyntaxIssue>> {
+    let tree = grammar.parse(source)?;
+    let mut issues = Vec::new();
+    let mut pending = vec![tree.root_node()];
+
+    while let Some(node) = pending.pop() {
+        if node.is_missing() {
+            issues.push(issue_at(source, node, format!("missing '{}'", node.kind())));
+        } else if node.is_error() {
+            let text = node.utf8_text(source.as_bytes()).unwrap_or_default();
+            let first_line = text.lines().map(str::trim).find(|line| !line.is_empty());
+            let message = match first_line {
+                Some(line) if line.chars().count() > 40 => {
+                    format!("unexpected '{}…'", line.chars().take(40).collect::<String>())
+                }
+                Some(line) => format!("unexpected '{}'", line),
+                None => "syntax error".to_string(),
+            };
+            issues.push(issue_at(source, node, message));
+        } else if node.has_error() {
+            let mut cursor = node.walk();
+            pending.extend(node.children(&mut cursor));
+        }
+    }
+
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+    Some(issues)
+}
+
+/// An issue at the start of `node`
+fn issue_at(source: &str, node: Node, message: String) -> SyntaxIssue {
+    let start = node.start_byte();
+    let line_start = start - node.start_position().column;
+    SyntaxIssue {
+        line: node.start_position().row + 1,
+        column: source.get(line_start..start).map_or(0, |prefix| prefix.chars().count()) + 1,
+        message,
+    }
+}
+
+/// Unbalanced brackets and unterminated strings and comments in `source`
+pub fn check_structure(source: &str, syntax: &Syntax) -> Vec<SyntaxIssue> {
+    let mut scanner = Scanner {
+        chars: source.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        syntax,
+    };
+    let mut issues = Vec::new();
+    let mut open: Vec<(char, usize, usize)> = Vec::new();
+    let issue = |line, column, message: String| SyntaxIssue { line, column, message };
+
+    while !scanner.at_end() {
+        let (line, column) = (scanner.line, scanner.column);
+        let c = scanner.current();
+
+        if let Some((start, end)) = syntax.block_comment.filter(|(start, _)| scanner.starts_with(start)) {
+            if !scanner.skip_block_comment(start, end) {
+                issues.push(issue(line, column, "unterminated block comment".to_string()));
+            }
+            continue;
+        }
+        if syntax.line_comments.iter().any(|marker| scanner.starts_with(marker)) {
+            scanner.skip_line();
+            continue;
+        }
+        if syntax.raw_strings {
+            if let Some(hashes) = scanner.raw_string_hashes() {
+                let open_marker = format!("r{}\"", "#".repeat(hashes));
+                let close_marker = format!("\"{}", "#".repeat(hashes));
+                if !scanner.skip_delimited(&open_marker, &close_marker) {
+                    issues.push(issue(line, column, "unterminated raw string".to_string()));
+                }
+                continue;
+            }
+        }
+        if syntax.quotes.contains(&c) {
+            if syntax.triple_quotes {
+                let triple: String = [c; 3].iter().collect();
+                if scanner.starts_with(&triple) {
+                    if !scanner.skip_delimited(&triple, &triple) {
+                        issues.push(issue(line, column, format!("unterminated {} string", triple)));
+                    }
+                    continue;
+                }
+            }
+            if c == '\'' && syntax.lifetimes && scanner.is_lifetime() {
+                scanner.bump();
+                continue;
+            }
+            if !scanner.skip_string(c, syntax.multiline_quotes.contains(&c)) {
+                issues.push(issue(line, column, format!("unterminated string starting with {}", c)));
+            }
+            continue;
+        }
+
+        match c {
+            '(' | '[' | '{' => open.push((c, line, column)),
+            ')' | ']' | '}' => match open.pop() {
+                Some((opener, ..)) if closer(opener) == c => {}
+                Some((opener, open_line, open_column)) => issues.push(issue(
+                    line,
+                    column,
+                    format!(
+                        "'{}' does not match '{}' opened at line {}, column {}",
+                        c, opener, open_line, open_column
+                    ),
+                )),
+                None => issues.push(issue(line, column, format!("unexpected '{}'", c))),
+            },
+            _ => {}
+        }
+        scanner.bump();
+    }
+
+    for (opener, line, column) in open {
+        issues.push(issue(line, column, format!("'{}' is never closed", opener)));
+    }
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(language: &str, source: &str) -> Vec<String> {
+        check_structure(source, &Syntax::for_language(language).unwrap())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn parse(language: &str, source: &str) -> Vec<String> {
+        check_parse(source, Grammar::for_language(language).unwrap())
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("java", "class A {\n    void f() { g(1); }\n}\n").is_empty());
+        assert!(parse("ruby", "def f(x)\n  [x, '{']\nend\n").is_empty());
+
+        // Balanced, but not valid code
+        assert_eq!(
+            parse("java", "class A {\n    void f() { int = 1; }\n}\n"),
+            vec!["line 2, column 19: missing 'identifier'".to_string()]
+        );
+        assert_eq!(
+            parse("rust", "fn main() {\n    let x = 1\n}\n"),
+            vec!["line 2, column 14: missing ';'".to_string()]
+        );
+        assert_eq!(parse("python", "x = )\n"), vec!["line 1, column 1: unexpected 'x = )'".to_string()]);
+        assert!(!parse("json", "{\"a\": 1,}").is_empty());
+        assert!(Grammar::for_language("kotlin").is_none());
+    }
+
+    #[test]
+    fn test_balanced_sources() {
+        let rust = "fn f<'a>(s: &'a str) -> char {\n    /* { /* nested } */ */\n    let _ = r#\"}\"#;\n    let _ = \"a\n    ]\";\n    '}'\n}\n";
+        assert!(check("rust", rust).is_empty(), "{:?}", check("rust", rust));
+        let python = "def f(x):  # (\n    \"\"\"Doc ]\n    \"\"\"\n    return [x, '{']\n";
+        assert!(check("python", python).is_empty());
+        let js = "const s = `line (\n${x}`;\nf({ a: [1] }); // }\n";
+        assert!(check("javascript", js).is_empty());
+    }
+
+    #[test]
+    fn test_structural_errors() {
+        assert_eq!(
+            check("java", "class A {\n    void f() {\n        g(];\n    }\n"),
+            vec![
+                "line 1, column 9: '{' is never closed".to_string(),
+                "line 3, column 11: ']' does not match '(' opened at line 3, column 10".to_string(),
+            ]
+        );
+        assert_eq!(check("c", "int x; }\n"), vec!["line 1, column 8: unexpected '}'".to_string()]);
+        assert_eq!(
+            check("python", "s = 'open\nt = 1\n"),
+            vec!["line 1, column 5: unterminated string starting with '".to_string()]
+        );
+        assert_eq!(
+            check("go", "/* never\nclosed"),
+            vec!["line 1, column 1: unterminated block comment".to_string()]
+        );
+        assert!(Syntax::for_language("brainfuck").is_none());
+    }
+}
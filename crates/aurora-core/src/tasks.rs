@@ -0,0 +1,564 @@
+//! Named project tasks
+//!
+//! Tasks are defined in the `[tasks]` section of `.AuroraHeart/config.toml`.
+//! Running a task first runs its dependencies: the dependency graph is split
+//! into groups whose tasks only depend on earlier groups, each group runs in
+//! parallel, and every output line is reported as it is produced. A failing
+//! group stops the run and the remaining tasks are reported as skipped.
+
+use crate::config::{Config, TaskDefinition};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Instant;
+use thiserror::Error;
+
+/// Maximum number of output lines kept per task in the report
+pub const MAX_OUTPUT_LINES: usize = 200;
+
+/// Errors that can occur while planning a task run
+#[derive(Error, Debug)]
+pub enum TaskError {
+    /// No task with this name is defined
+    #[error("Unknown task: {0}")]
+    UnknownTask(String),
+
+    /// A task depends on a task that is not defined
+    #[error("Task '{task}' depends on unknown task '{dependency}'")]
+    UnknownDependency {
+        /// Task declaring the dependency
+        task: String,
+        /// Missing dependency
+        dependency: String,
+    },
+
+    /// Task dependencies form a cycle
+    #[error("Dependency cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+pub type Result<T> = std::result::Result<T, TaskError>;
+
+/// Outcome of a single task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// The command exited successfully
+    Passed,
+    /// The command failed or could not be started
+    Failed,
+    /// The task did not run because an earlier group failed
+    Skipped,
+}
+
+/// Output stream a log line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    /// Standard output
+    Stdout,
+    /// Standard error
+    Stderr,
+}
+
+/// Progress reported while tasks run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskEvent {
+    /// A task started
+    Started {
+        /// Task name
+        task: String,
+        /// Command being run
+        command: String,
+    },
+    /// A task produced a line of output
+    Output {
+        /// Task name
+        task: String,
+        /// Stream the line was written to
+        stream: OutputStream,
+        /// The line, without its terminator
+        line: String,
+    },
+    /// A task finished or was skipped
+    Finished {
+        /// Task name
+        task: String,
+        /// Outcome
+        status: TaskStatus,
+        /// Exit code, if the process exited normally
+        exit_code: Option<i32>,
+        /// Wall-clock duration in milliseconds
+        duration_ms: u64,
+    },
+}
+
+/// Result of a single task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResult {
+    /// Task name
+    pub name: String,
+    /// Command that was run
+    pub command: String,
+    /// Outcome
+    pub status: TaskStatus,
+    /// Exit code, if the process exited normally
+    pub exit_code: Option<i32>,
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: u64,
+    /// Last [`MAX_OUTPUT_LINES`] lines of combined output
+    pub output: Vec<String>,
+}
+
+/// Report produced by running a task and its dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskReport {
+    /// The requested task
+    pub task: String,
+    /// True when every task passed
+    pub passed: bool,
+    /// Results in execution order
+    pub results: Vec<TaskResult>,
+}
+
+impl TaskReport {
+    /// Format the report for display
+    pub fn format(&self) -> String {
+        let mut output = if self.passed {
+            format!("✅ Task '{}' passed\n", self.task)
+        } else {
+            format!("❌ Task '{}' failed\n", self.task)
+        };
+
+        for result in &self.results {
+            let marker = match result.status {
+                TaskStatus::Passed => "✅",
+                TaskStatus::Failed => "❌",
+                TaskStatus::Skipped => "⏭️",
+            };
+            let exit = result
+                .exit_code
+                .map(|code| format!(", exit code {}", code))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "\n{} {} ({}ms{})\n   $ {}\n",
+                marker, result.name, result.duration_ms, exit, result.command
+            ));
+            for line in &result.output {
+                output.push_str(&format!("   {}\n", line));
+            }
+        }
+
+        output
+    }
+}
+
+/// Runs named tasks for a project
+pub struct TaskRunner {
+    project_root: PathBuf,
+    tasks: BTreeMap<String, TaskDefinition>,
+}
+
+impl TaskRunner {
+    /// Create a runner for the given task definitions
+    pub fn new<P: AsRef<Path>>(project_root: P, tasks: BTreeMap<String, TaskDefinition>) -> Self {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            tasks,
+        }
+    }
+
+    /// Create a runner for the tasks in a project's configuration
+    pub fn from_config<P: AsRef<Path>>(project_root: P, config: &Config) -> Self {
+        Self::new(project_root, config.tasks.clone())
+    }
+
+    /// Defined tasks by name
+    pub fn tasks(&self) -> &BTreeMap<String, TaskDefinition> {
+        &self.tasks
+    }
+
+    /// Resolve the groups needed to run `name`
+    ///
+    /// Tasks in a group depend only on tasks in earlier groups and can run in
+    /// parallel. The requested task is always alone in the last group.
+    pub fn plan(&self, name: &str) -> Result<Vec<Vec<String>>> {
+        if !self.tasks.contains_key(name) {
+            return Err(TaskError::UnknownTask(name.to_string()));
+        }
+
+        let mut levels = BTreeMap::new();
+        self.resolve_level(name, &mut Vec::new(), &mut levels)?;
+
+        let depth = levels.values().copied().max().unwrap_or(0);
+        let mut groups = vec![Vec::new(); depth + 1];
+        for (task, level) in levels {
+            groups[level].push(task);
+        }
+        Ok(groups)
+    }
+
+    /// Compute the level of `name` (one more than its deepest dependency)
+    fn resolve_level(
+        &self,
+        name: &str,
+        stack: &mut Vec<String>,
+        levels: &mut BTreeMap<String, usize>,
+    ) -> Result<usize> {
+        if let Some(level) = levels.get(name) {
+            return Ok(*level);
+        }
+        if let Some(start) = stack.iter().position(|t| t == name) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(TaskError::Cycle(cycle));
+        }
+
+        let definition = self
+            .tasks
+            .get(name)
+            .ok_or_else(|| TaskError::UnknownTask(name.to_string()))?;
+
+        stack.push(name.to_string());
+        let mut level = 0;
+        for dependency in &definition.depends_on {
+            if !self.tasks.contains_key(dependency) {
+                return Err(TaskError::UnknownDependency {
+                    task: name.to_string(),
+                    dependency: dependency.clone(),
+                });
+            }
+            level = level.max(self.resolve_level(dependency, stack, levels)? + 1);
+        }
+        stack.pop();
+
+        levels.insert(name.to_string(), level);
+        Ok(level)
+    }
+
+    /// Run `name` after its dependencies, reporting progress through `on_event`
+    pub fn run<F>(&self, name: &str, on_event: F) -> Result<TaskReport>
+    where
+        F: Fn(TaskEvent) + Sync,
+    {
+        let groups = self.plan(name)?;
+        let mut results = Vec::new();
+        let mut failed = false;
+
+        for group in groups {
+            if failed {
+                for task in group {
+                    let command = self.tasks[&task].command.clone();
+                    on_event(TaskEvent::Finished {
+                        task: task.clone(),
+                        status: TaskStatus::Skipped,
+                        exit_code: None,
+                        duration_ms: 0,
+                    });
+                    results.push(TaskResult {
+                        name: task,
+                        command,
+                        status: TaskStatus::Skipped,
+                        exit_code: None,
+                        duration_ms: 0,
+                        output: Vec::new(),
+                    });
+                }
+                continue;
+            }
+
+            let group_results: Vec<TaskResult> = std::thread::scope(|scope| {
+                let handles: Vec<_> = group
+                    .iter()
+                    .map(|task| scope.spawn(|| self.run_one(task, &self.tasks[task], &on_event)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .zip(&group)
+                    .map(|(handle, task)| {
+                        handle.join().unwrap_or_else(|_| TaskResult {
+                            name: task.clone(),
+                            command: self.tasks[task].command.clone(),
+                            status: TaskStatus::Failed,
+                            exit_code: None,
+                            duration_ms: 0,
+                            output: vec!["task runner thread panicked".to_string()],
+                        })
+                    })
+                    .collect()
+            });
+
+            failed = group_results.iter().any(|r| r.status == TaskStatus::Failed);
+            results.extend(group_results);
+        }
+
+        Ok(TaskReport {
+            task: name.to_string(),
+            passed: !failed,
+            results,
+        })
+    }
+
+    /// Run a single task, streaming its output
+    fn run_one<F>(&self, name: &str, definition: &TaskDefinition, on_event: &F) -> TaskResult
+    where
+        F: Fn(TaskEvent) + Sync,
+    {
+        let start = Instant::now();
+        on_event(TaskEvent::Started {
+            task: name.to_string(),
+            command: definition.command.clone(),
+        });
+
+        let mut command = if cfg!(target_os = "windows") {
+            let mut command = Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        let cwd = match &definition.cwd {
+            Some(cwd) => self.project_root.join(cwd),
+            None => self.project_root.clone(),
+        };
+        command
+            .arg(&definition.command)
+            .current_dir(cwd)
+            .envs(&definition.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let output = Mutex::new(Vec::new());
+        let record = |stream: OutputStream, line: String| {
+            if let Ok(mut output) = output.lock() {
+                output.push(line.clone());
+                if output.len() > MAX_OUTPUT_LINES {
+                    output.remove(0);
+                }
+            }
+            on_event(TaskEvent::Output {
+                task: name.to_string(),
+                stream,
+                line,
+            });
+        };
+
+        let exit_status = match command.spawn() {
+            Ok(mut child) => {
+                let stdout = child.stdout.take();
+                let stderr = child.stderr.take();
+                std::thread::scope(|scope| {
+                    if let Some(stdout) = stdout {
+                        scope.spawn(|| {
+                            stream_lines(stdout, |line| record(OutputStream::Stdout, line))
+                        });
+                    }
+                    if let Some(stderr) = stderr {
+                        scope.spawn(|| {
+                            stream_lines(stderr, |line| record(OutputStream::Stderr, line))
+                        });
+                    }
+                });
+                child.wait()
+            }
+            Err(e) => Err(e),
+        };
+
+        let (status, exit_code) = match exit_status {
+            Ok(exit) if exit.success() => (TaskStatus::Passed, exit.code()),
+            Ok(exit) => (TaskStatus::Failed, exit.code()),
+            Err(e) => {
+                record(
+                    OutputStream::Stderr,
+                    format!("Failed to run command: {}", e),
+                );
+                (TaskStatus::Failed, None)
+            }
+        };
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        on_event(TaskEvent::Finished {
+            task: name.to_string(),
+            status,
+            exit_code,
+            duration_ms,
+        });
+
+        TaskResult {
+            name: name.to_string(),
+            command: definition.command.clone(),
+            status,
+            exit_code,
+            duration_ms,
+            output: output.into_inner().unwrap_or_default(),
+        }
+    }
+}
+
+/// Call `on_line` for each line read from `reader`
+fn stream_lines(reader: impl Read, mut on_line: impl FnMut(String)) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                on_line(line.trim_end_matches(['\n', '\r']).to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn task(command: &str, depends_on: &[&str]) -> TaskDefinition {
+        TaskDefinition {
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            ..TaskDefinition::new(command)
+        }
+    }
+
+    fn runner(root: &Path, tasks: &[(&str, TaskDefinition)]) -> TaskRunner {
+        TaskRunner::new(
+            root,
+            tasks
+                .iter()
+                .map(|(name, definition)| (name.to_string(), definition.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_plan_groups_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let runner = runner(
+            temp_dir.path(),
+            &[
+                ("lint", task("true", &[])),
+                ("build", task("true", &[])),
+                ("test", task("true", &["build"])),
+                ("ci", task("true", &["lint", "test"])),
+                ("unrelated", task("true", &[])),
+            ],
+        );
+
+        assert_eq!(
+            runner.plan("ci").unwrap(),
+            vec![
+                vec!["build".to_string(), "lint".to_string()],
+                vec!["test".to_string()],
+                vec!["ci".to_string()],
+            ]
+        );
+        assert!(matches!(
+            runner.plan("deploy"),
+            Err(TaskError::UnknownTask(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_rejects_cycles_and_missing_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let runner = runner(
+            temp_dir.path(),
+            &[
+                ("a", task("true", &["b"])),
+                ("b", task("true", &["a"])),
+                ("c", task("true", &["missing"])),
+            ],
+        );
+
+        match runner.plan("a") {
+            Err(TaskError::Cycle(cycle)) => assert_eq!(cycle, vec!["a", "b", "a"]),
+            other => panic!("expected cycle, got {:?}", other),
+        }
+        assert!(matches!(
+            runner.plan("c"),
+            Err(TaskError::UnknownDependency { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_streams_output_and_skips_after_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let greet = TaskDefinition {
+            env: [("GREETING".to_string(), "hello".to_string())].into(),
+            ..task("echo $GREETING; echo oops >&2", &[])
+        };
+        let runner = runner(
+            temp_dir.path(),
+            &[
+                ("greet", greet),
+                ("fail", task("exit 3", &["greet"])),
+                ("after", task("echo never", &["fail"])),
+            ],
+        );
+
+        let events = Mutex::new(Vec::new());
+        let report = runner
+            .run("after", |event| events.lock().unwrap().push(event))
+            .unwrap();
+
+        assert!(!report.passed);
+        let statuses: Vec<_> = report
+            .results
+            .iter()
+            .map(|r| (r.name.as_str(), r.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("greet", TaskStatus::Passed),
+                ("fail", TaskStatus::Failed),
+                ("after", TaskStatus::Skipped),
+            ]
+        );
+        assert_eq!(report.results[1].exit_code, Some(3));
+        assert!(report.results[0].output.contains(&"hello".to_string()));
+
+        let events = events.into_inner().unwrap();
+        assert!(events.contains(&TaskEvent::Output {
+            task: "greet".to_string(),
+            stream: OutputStream::Stderr,
+            line: "oops".to_string(),
+        }));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, TaskEvent::Started { task, .. } if task == "after")));
+        assert!(report.format().contains("❌ Task 'after' failed"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_uses_task_cwd() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("web")).unwrap();
+        let runner = runner(
+            temp_dir.path(),
+            &[(
+                "where",
+                TaskDefinition {
+                    cwd: Some("web".to_string()),
+                    ..task("basename \"$PWD\"", &[])
+                },
+            )],
+        );
+
+        let report = runner.run("where", |_| {}).unwrap();
+        assert!(report.passed);
+        assert_eq!(report.results[0].output, vec!["web".to_string()]);
+    }
+}
@@ -0,0 +1,423 @@
+//! Watch mode for project tasks
+//!
+//! A task whose definition (or whose dependencies' definitions) lists `watch`
+//! globs can be run in watch mode: it runs once, then again every time a
+//! matching file is added, modified, or removed. Changes are detected by
+//! polling modification times, and a burst of changes (such as a save-all or
+//! a formatter run) is collected into a single rerun.
+
+use crate::config::Config;
+use crate::search::SKIPPED_DIRS;
+use crate::tasks::{TaskError, TaskEvent, TaskReport, TaskRunner, TaskStatus};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Maximum number of polls spent waiting for a burst of changes to settle
+const MAX_SETTLE_POLLS: usize = 10;
+
+/// Errors that can occur while setting up or running watch mode
+#[derive(Error, Debug)]
+pub enum WatchError {
+    /// A watch pattern is not a valid glob
+    #[error("Invalid watch pattern '{0}': {1}")]
+    InvalidGlob(String, String),
+
+    /// Neither the task nor its dependencies define watch patterns
+    #[error("Task '{0}' has no watch patterns")]
+    NoWatchPatterns(String),
+
+    /// The task could not be planned
+    #[error(transparent)]
+    Task(#[from] TaskError),
+
+    /// IO error while scanning the project
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, WatchError>;
+
+/// Detects changes to files matching a set of globs
+pub struct FileWatcher {
+    root: PathBuf,
+    patterns: Vec<Pattern>,
+    snapshot: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl FileWatcher {
+    /// Watch files under `root` whose relative paths match any of `globs`
+    pub fn new(root: impl Into<PathBuf>, globs: &[String]) -> Result<Self> {
+        let patterns = globs
+            .iter()
+            .map(|g| Pattern::new(g).map_err(|e| WatchError::InvalidGlob(g.clone(), e.to_string())))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut watcher = Self {
+            root: root.into(),
+            patterns,
+            snapshot: BTreeMap::new(),
+        };
+        watcher.snapshot = watcher.scan()?;
+        Ok(watcher)
+    }
+
+    /// Relative paths of files added, modified, or removed since the last poll
+    pub fn poll(&mut self) -> Result<Vec<String>> {
+        let current = self.scan()?;
+        let mut changed = BTreeSet::new();
+
+        for (path, modified) in &current {
+            if self.snapshot.get(path) != Some(modified) {
+                changed.insert(self.relative(path));
+            }
+        }
+        for path in self.snapshot.keys() {
+            if !current.contains_key(path) {
+                changed.insert(self.relative(path));
+            }
+        }
+
+        self.snapshot = current;
+        Ok(changed.into_iter().collect())
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Modification times of all watched files
+    fn scan(&self) -> Result<BTreeMap<PathBuf, SystemTime>> {
+        let mut files = BTreeMap::new();
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.starts_with('.') {
+                    continue;
+                }
+
+                let path = entry.path();
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                        pending.push(path);
+                    }
+                } else if file_type.is_file() {
+                    let relative = self.relative(&path);
+                    if self.patterns.iter().any(|p| p.matches(&relative)) {
+                        // The file may disappear between listing and stat
+                        if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                            files.insert(path, modified);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+/// One run of a watched task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchRun {
+    /// The watched task
+    pub task: String,
+    /// Files whose changes triggered the run (empty for the initial run)
+    pub changed_files: Vec<String>,
+    /// Result of the run
+    pub report: TaskReport,
+}
+
+impl WatchRun {
+    /// Whether every task in the run passed
+    pub fn passed(&self) -> bool {
+        self.report.passed
+    }
+
+    /// Output of the failing tasks, formatted for the agent
+    ///
+    /// Returns `None` when the run passed.
+    pub fn failure_summary(&self) -> Option<String> {
+        if self.passed() {
+            return None;
+        }
+
+        let mut summary = if self.changed_files.is_empty() {
+            format!("Watch task '{}' failed.\n", self.task)
+        } else {
+            format!(
+                "Watch task '{}' failed after changes to: {}\n",
+                self.task,
+                self.changed_files.join(", ")
+            )
+        };
+
+        for result in self
+            .report
+            .results
+            .iter()
+            .filter(|r| r.status == TaskStatus::Failed)
+        {
+            let exit = result
+                .exit_code
+                .map(|code| format!(" (exit code {})", code))
+                .unwrap_or_default();
+            summary.push_str(&format!("\n$ {}{}\n", result.command, exit));
+            for line in &result.output {
+                summary.push_str(line);
+                summary.push('\n');
+            }
+        }
+
+        Some(summary)
+    }
+}
+
+/// Reruns a task whenever its watched files change
+pub struct TaskWatcher {
+    runner: TaskRunner,
+    task: String,
+    files: FileWatcher,
+    poll_interval: Duration,
+}
+
+impl TaskWatcher {
+    /// Watch `task` using the project's task and watch configuration
+    ///
+    /// The watched globs are those of the task and all of its dependencies.
+    pub fn new<P: AsRef<Path>>(project_root: P, config: &Config, task: &str) -> Result<Self> {
+        let project_root = project_root.as_ref();
+        let runner = TaskRunner::from_config(project_root, config);
+
+        let mut globs = BTreeSet::new();
+        for name in runner.plan(task)?.iter().flatten() {
+            globs.extend(runner.tasks()[name].watch.iter().cloned());
+        }
+        if globs.is_empty() {
+            return Err(WatchError::NoWatchPatterns(task.to_string()));
+        }
+
+        let globs: Vec<String> = globs.into_iter().collect();
+
+        Ok(Self {
+            runner,
+            task: task.to_string(),
+            files: FileWatcher::new(project_root, &globs)?,
+            poll_interval: Duration::from_millis(config.watch.poll_interval_ms.max(1)),
+        })
+    }
+
+    /// The watched task
+    pub fn task(&self) -> &str {
+        &self.task
+    }
+
+    /// Run the task now and after every change, until `stop` is set
+    ///
+    /// Task progress is reported through `on_event` and each completed run
+    /// through `on_run`.
+    pub fn run<E, R>(&mut self, stop: &AtomicBool, on_event: E, mut on_run: R) -> Result<()>
+    where
+        E: Fn(TaskEvent) + Sync,
+        R: FnMut(&WatchRun),
+    {
+        let mut changed_files = Vec::new();
+
+        loop {
+            let report = self.runner.run(&self.task, &on_event)?;
+            on_run(&WatchRun {
+                task: self.task.clone(),
+                changed_files: std::mem::take(&mut changed_files),
+                report,
+            });
+            // Ignore changes made by the task itself (build outputs, formatters)
+            self.files.poll()?;
+
+            while changed_files.is_empty() {
+                if !self.sleep(stop) {
+                    return Ok(());
+                }
+                changed_files = self.files.poll()?;
+            }
+            self.settle(stop, &mut changed_files)?;
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Keep collecting changes until a poll comes back empty
+    fn settle(&mut self, stop: &AtomicBool, changed_files: &mut Vec<String>) -> Result<()> {
+        for _ in 0..MAX_SETTLE_POLLS {
+            if !self.sleep(stop) {
+                break;
+            }
+            let more = self.files.poll()?;
+            if more.is_empty() {
+                break;
+            }
+            changed_files.extend(more);
+        }
+        changed_files.sort();
+        changed_files.dedup();
+        Ok(())
+    }
+
+    /// Sleep for one poll interval; returns false if `stop` was set
+    fn sleep(&self, stop: &AtomicBool) -> bool {
+        if stop.load(Ordering::Relaxed) {
+            return false;
+        }
+        std::thread::sleep(self.poll_interval);
+        !stop.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TaskDefinition;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    fn touch(path: &Path, content: &str) {
+        std::fs::write(path, content).unwrap();
+        // Make sure the modification time differs from the previous write
+        let later = SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_file_watcher_reports_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("src/old.rs"), "").unwrap();
+
+        let mut watcher =
+            FileWatcher::new(root, &["src/*.rs".to_string(), "target/*".to_string()]).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+
+        touch(&root.join("src/lib.rs"), "fn changed() {}");
+        std::fs::write(root.join("src/new.rs"), "").unwrap();
+        std::fs::remove_file(root.join("src/old.rs")).unwrap();
+        std::fs::write(root.join("src/notes.txt"), "").unwrap();
+        std::fs::write(root.join("target/out.rs"), "").unwrap();
+
+        assert_eq!(
+            watcher.poll().unwrap(),
+            vec!["src/lib.rs", "src/new.rs", "src/old.rs"]
+        );
+        assert!(watcher.poll().unwrap().is_empty());
+
+        assert!(matches!(
+            FileWatcher::new(root, &["[".to_string()]),
+            Err(WatchError::InvalidGlob(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_task_watcher_requires_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config
+            .tasks
+            .insert("build".to_string(), TaskDefinition::new("true"));
+        config.tasks.insert(
+            "test".to_string(),
+            TaskDefinition {
+                depends_on: vec!["build".to_string()],
+                ..TaskDefinition::new("true")
+            },
+        );
+
+        assert!(matches!(
+            TaskWatcher::new(temp_dir.path(), &config, "test"),
+            Err(WatchError::NoWatchPatterns(_))
+        ));
+        assert!(matches!(
+            TaskWatcher::new(temp_dir.path(), &config, "missing"),
+            Err(WatchError::Task(TaskError::UnknownTask(_)))
+        ));
+
+        // Patterns on a dependency are enough
+        config.tasks.get_mut("build").unwrap().watch = vec!["**/*.rs".to_string()];
+        let watcher = TaskWatcher::new(temp_dir.path(), &config, "test").unwrap();
+        assert_eq!(watcher.task(), "test");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_task_watcher_reruns_on_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        std::fs::write(root.join("input.txt"), "ok").unwrap();
+
+        let mut config = Config::default();
+        config.watch.poll_interval_ms = 10;
+        config.tasks.insert(
+            "check".to_string(),
+            TaskDefinition {
+                watch: vec!["*.txt".to_string()],
+                ..TaskDefinition::new("grep -q ok input.txt || { echo broken; exit 1; }")
+            },
+        );
+
+        let mut watcher = TaskWatcher::new(&root, &config, "check").unwrap();
+        let stop = AtomicBool::new(false);
+        let runs = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                watcher
+                    .run(&stop, |_| {}, |run| runs.lock().unwrap().push(run.clone()))
+                    .unwrap();
+            });
+
+            let wait_for_runs = |count: usize| {
+                for _ in 0..500 {
+                    if runs.lock().unwrap().len() >= count {
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                panic!("watcher did not complete {} runs", count);
+            };
+
+            wait_for_runs(1);
+            touch(&root.join("input.txt"), "fail");
+            wait_for_runs(2);
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        let runs = runs.into_inner().unwrap();
+        assert!(runs[0].passed());
+        assert!(runs[0].changed_files.is_empty());
+        assert!(!runs[1].passed());
+        assert_eq!(runs[1].changed_files, vec!["input.txt"]);
+
+        let summary = runs[1].failure_summary().unwrap();
+        assert!(summary.starts_with("Watch task 'check' failed after changes to: input.txt"));
+        assert!(summary.contains("(exit code 1)\nbroken\n"));
+        assert!(runs[0].failure_summary().is_none());
+    }
+}
@@ -0,0 +1,229 @@
+//! Whole-word and case-preserving replacement
+//!
+//! Renaming an identifier usually means renaming every spelling of it.
+//! With [`ReplaceOptions::preserve_case`], a plain identifier pattern such
+//! as `fooBar` also matches `FooBar`, `foo_bar`, and `FOO_BAR`, and each
+//! match is replaced in its own style: replacing with `bazQux` gives
+//! `BazQux`, `baz_qux`, and `BAZ_QUX`. A match spelled entirely in lowercase
+//! gets the replacement as typed. [`ReplaceOptions::match_whole_word`]
+//! keeps a pattern from matching inside a longer word, so `foo` leaves
+//! `food` and `foo_bar` alone.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// How matches are found and replaced beyond the pattern itself
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaceOptions {
+    /// Only match whole words
+    #[serde(default)]
+    pub match_whole_word: bool,
+    /// Match the identifier's other spellings and replace each in its style
+    #[serde(default)]
+    pub preserve_case: bool,
+}
+
+impl ReplaceOptions {
+    /// Regex source for `pattern`, which is literal text when `literal` and
+    /// a regex otherwise
+    pub fn pattern(&self, pattern: &str, literal: bool) -> String {
+        let mut source = if self.preserve_case && is_identifier(pattern) {
+            let mut variants = case_variants(pattern);
+            // Longest first, so a variant is not cut short by its own prefix
+            variants.sort_by_key(|variant| std::cmp::Reverse(variant.len()));
+            variants.iter().map(|variant| regex::escape(variant)).collect::<Vec<_>>().join("|")
+        } else if literal {
+            regex::escape(pattern)
+        } else {
+            pattern.to_string()
+        };
+        if self.match_whole_word {
+            source = format!(r"\b(?:{})\b", source);
+        }
+        source
+    }
+
+    /// `replacement` for the text `matched`, in the style of `matched` when
+    /// preserving case
+    pub fn replacement_for<'a>(&self, matched: &str, replacement: &'a str) -> Cow<'a, str> {
+        if self.preserve_case {
+            Cow::Owned(preserve_case(matched, replacement))
+        } else {
+            Cow::Borrowed(replacement)
+        }
+    }
+}
+
+/// How an identifier joins and capitalizes its words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStyle {
+    /// `foobar`
+    Lower,
+    /// `fooBar`
+    Camel,
+    /// `FooBar`
+    Pascal,
+    /// `foo_bar`
+    Snake,
+    /// `FOO_BAR`, or `FOO` for one word
+    ScreamingSnake,
+}
+
+impl CaseStyle {
+    /// The style `text` is written in, if it is an identifier
+    pub fn detect(text: &str) -> Option<Self> {
+        if !is_identifier(text) || !text.chars().any(char::is_alphabetic) {
+            return None;
+        }
+        let has_upper = text.chars().any(char::is_uppercase);
+        let has_lower = text.chars().any(char::is_lowercase);
+        Some(match (has_upper, has_lower) {
+            (true, false) => CaseStyle::ScreamingSnake,
+            (false, _) if text.contains('_') => CaseStyle::Snake,
+            (false, _) => CaseStyle::Lower,
+            _ if text.chars().next().is_some_and(char::is_uppercase) => CaseStyle::Pascal,
+            _ => CaseStyle::Camel,
+        })
+    }
+
+    /// `words` written in this style
+    pub fn render(&self, words: &[String]) -> String {
+        match self {
+            CaseStyle::Lower => words.concat(),
+            CaseStyle::Camel => match words.split_first() {
+                Some((first, rest)) => {
+                    first.clone() + &rest.iter().map(|word| capitalize(word)).collect::<String>()
+                }
+                None => String::new(),
+            },
+            CaseStyle::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            CaseStyle::Snake => words.join("_"),
+            CaseStyle::ScreamingSnake => words.join("_").to_uppercase(),
+        }
+    }
+}
+
+/// Lowercase words of an identifier, split at underscores and case changes
+/// (`parseHTTPRequest` is `parse`, `http`, `request`)
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in text.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let starts_word = i > 0
+                && c.is_uppercase()
+                && (chars[i - 1].is_lowercase()
+                    || chars[i - 1].is_numeric()
+                    || (chars[i - 1].is_uppercase() && chars.get(i + 1).is_some_and(|next| next.is_lowercase())));
+            if starts_word && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.extend(c.to_lowercase());
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// The spellings of the identifier `text`: itself, and its camel, Pascal,
+/// snake, and screaming snake forms (lowercase, capitalized, and uppercase
+/// for a single word)
+pub fn case_variants(text: &str) -> Vec<String> {
+    let words = split_words(text);
+    let styles: &[CaseStyle] = if words.len() > 1 {
+        &[CaseStyle::Camel, CaseStyle::Pascal, CaseStyle::Snake, CaseStyle::ScreamingSnake]
+    } else {
+        &[CaseStyle::Lower, CaseStyle::Pascal, CaseStyle::ScreamingSnake]
+    };
+    let mut variants = vec![text.to_string()];
+    for style in styles {
+        let variant = style.render(&words);
+        if !variant.is_empty() && !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+    variants
+}
+
+/// `replacement` written in the style of `matched`
+///
+/// A match in all lowercase, or one that is not an identifier, gets the
+/// replacement as typed. A replacement that is not an identifier is only
+/// uppercased or capitalized to follow the match.
+pub fn preserve_case(matched: &str, replacement: &str) -> String {
+    let Some(style) = CaseStyle::detect(matched) else {
+        return replacement.to_string();
+    };
+    if !is_identifier(replacement) {
+        return match style {
+            CaseStyle::ScreamingSnake => replacement.to_uppercase(),
+            CaseStyle::Pascal => capitalize(replacement),
+            _ => replacement.to_string(),
+        };
+    }
+    match style {
+        CaseStyle::Lower => replacement.to_string(),
+        style => style.render(&split_words(replacement)),
+    }
+}
+
+/// Whether `text` is a non-empty run of letters, digits, and underscores
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// `word` with its first letter uppercased
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants_and_styles() {
+        assert_eq!(split_words("parseHTTPRequest"), vec!["parse", "http", "request"]);
+        assert_eq!(split_words("FOO_BAR2"), vec!["foo", "bar2"]);
+        assert_eq!(case_variants("fooBar"), vec!["fooBar", "FooBar", "foo_bar", "FOO_BAR"]);
+        assert_eq!(case_variants("foo"), vec!["foo", "Foo", "FOO"]);
+
+        for (matched, expected) in [
+            ("fooBar", "bazQux"),
+            ("FooBar", "BazQux"),
+            ("foo_bar", "baz_qux"),
+            ("FOO_BAR", "BAZ_QUX"),
+            ("foobar", "bazQux"),
+        ] {
+            assert_eq!(preserve_case(matched, "bazQux"), expected, "{}", matched);
+        }
+        assert_eq!(preserve_case("FOO", "baz qux"), "BAZ QUX");
+        assert_eq!(preserve_case("a.b", "bazQux"), "bazQux");
+    }
+
+    #[test]
+    fn test_replace_options() {
+        let options = ReplaceOptions {
+            match_whole_word: true,
+            preserve_case: true,
+        };
+        let regex = regex::Regex::new(&options.pattern("fooBar", true)).unwrap();
+        let text = "let fooBar = FooBar::new(FOO_BAR, foo_bar, fooBarBaz);";
+        let replaced = regex.replace_all(text, |caps: &regex::Captures| {
+            options.replacement_for(&caps[0], "bazQux").into_owned()
+        });
+        assert_eq!(replaced, "let bazQux = BazQux::new(BAZ_QUX, baz_qux, fooBarBaz);");
+
+        let plain = ReplaceOptions::default();
+        assert_eq!(plain.pattern("a.b", true), r"a\.b");
+        assert_eq!(plain.pattern("a.b", false), "a.b");
+        assert_eq!(plain.replacement_for("FOO", "bar"), "bar");
+    }
+}
@@ -0,0 +1,288 @@
+//! Persisted workspace UI state
+//!
+//! Recently opened files, cursor positions, bookmarks, and open tabs are kept
+//! in `.AuroraHeart/state.json` so a project reopens the way it was left.
+//! Paths inside the project are stored relative to its root, which keeps the
+//! file valid if the project directory moves; [`WorkspaceStore::restore`]
+//! resolves them back to absolute paths and drops files that no longer exist.
+
+use crate::paths::normalize_path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Maximum number of recent files remembered
+pub const MAX_RECENT_FILES: usize = 20;
+
+/// Errors that can occur while loading or saving workspace state
+#[derive(Error, Debug)]
+pub enum WorkspaceStateError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// State file could not be parsed or written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, WorkspaceStateError>;
+
+/// A cursor position in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorPosition {
+    /// Line (1-based)
+    pub line: usize,
+    /// Column (1-based)
+    pub column: usize,
+}
+
+/// A bookmarked line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// File path
+    pub path: String,
+    /// Line (1-based)
+    pub line: usize,
+    /// Optional label shown in the bookmark list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// UI state saved per project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceState {
+    /// Recently opened files, most recent first
+    #[serde(default)]
+    pub recent_files: Vec<String>,
+    /// Last cursor position per file
+    #[serde(default)]
+    pub cursors: BTreeMap<String, CursorPosition>,
+    /// Bookmarked lines
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// Open tabs in display order
+    #[serde(default)]
+    pub open_tabs: Vec<String>,
+    /// The focused tab
+    #[serde(default)]
+    pub active_tab: Option<String>,
+}
+
+impl WorkspaceState {
+    /// Move a file to the front of the recent list
+    pub fn record_recent_file(&mut self, path: &str) {
+        self.recent_files.retain(|p| p != path);
+        self.recent_files.insert(0, path.to_string());
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Remember the cursor position for a file
+    pub fn set_cursor(&mut self, path: &str, cursor: CursorPosition) {
+        self.cursors.insert(path.to_string(), cursor);
+    }
+
+    /// Add a bookmark, or remove it if the line is already bookmarked
+    ///
+    /// Returns true if the bookmark was added.
+    pub fn toggle_bookmark(&mut self, path: &str, line: usize, label: Option<String>) -> bool {
+        let before = self.bookmarks.len();
+        self.bookmarks
+            .retain(|b| !(b.path == path && b.line == line));
+        if self.bookmarks.len() < before {
+            return false;
+        }
+
+        self.bookmarks.push(Bookmark {
+            path: path.to_string(),
+            line,
+            label,
+        });
+        self.bookmarks
+            .sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        true
+    }
+
+    /// Replace the open tabs and the focused tab
+    pub fn set_open_tabs(&mut self, tabs: Vec<String>, active_tab: Option<String>) {
+        self.active_tab = active_tab.filter(|active| tabs.contains(active));
+        self.open_tabs = tabs;
+    }
+
+    /// Apply `f` to every stored path, dropping entries for which it returns `None`
+    fn map_paths(&mut self, f: impl Fn(&str) -> Option<String>) {
+        self.recent_files = self.recent_files.iter().filter_map(|p| f(p)).collect();
+        self.cursors = std::mem::take(&mut self.cursors)
+            .into_iter()
+            .filter_map(|(p, cursor)| Some((f(&p)?, cursor)))
+            .collect();
+        self.bookmarks = std::mem::take(&mut self.bookmarks)
+            .into_iter()
+            .filter_map(|b| {
+                Some(Bookmark {
+                    path: f(&b.path)?,
+                    ..b
+                })
+            })
+            .collect();
+        self.open_tabs = self.open_tabs.iter().filter_map(|p| f(p)).collect();
+        self.active_tab = self.active_tab.as_deref().and_then(f);
+    }
+}
+
+/// Loads and saves workspace state for one project
+pub struct WorkspaceStore {
+    project_root: PathBuf,
+}
+
+impl WorkspaceStore {
+    /// Create a store for the project at `project_root`
+    pub fn for_project<P: AsRef<Path>>(project_root: P) -> Self {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Path of the state file
+    pub fn path(&self) -> PathBuf {
+        self.project_root.join(".AuroraHeart").join("state.json")
+    }
+
+    /// Path as stored in the state file (relative when inside the project)
+    pub fn stored_path(&self, path: &str) -> String {
+        normalize_path(path)
+            .strip_prefix(normalize_path(&self.project_root))
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| path.to_string())
+    }
+
+    /// Load the stored state, or an empty state if none has been saved
+    pub fn load(&self) -> Result<WorkspaceState> {
+        let path = self.path();
+        if !path.exists() {
+            return Ok(WorkspaceState::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Save the state
+    pub fn save(&self, state: &WorkspaceState) -> Result<()> {
+        let path = self.path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+
+    /// Load, modify, and save the state
+    ///
+    /// Paths passed to `f` should be converted with [`Self::stored_path`].
+    pub fn update(&self, f: impl FnOnce(&mut WorkspaceState)) -> Result<WorkspaceState> {
+        let mut state = self.load()?;
+        f(&mut state);
+        self.save(&state)?;
+        Ok(state)
+    }
+
+    /// Load the state for reopening the project
+    ///
+    /// Paths are resolved to absolute paths and entries for files that no
+    /// longer exist are dropped.
+    pub fn restore(&self) -> Result<WorkspaceState> {
+        let mut state = self.load()?;
+        state.map_paths(|stored| {
+            let path = self.project_root.join(stored);
+            path.is_file().then(|| path.to_string_lossy().to_string())
+        });
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_recent_files_are_deduplicated_and_capped() {
+        let mut state = WorkspaceState::default();
+        for i in 0..25 {
+            state.record_recent_file(&format!("file{}.rs", i));
+        }
+        state.record_recent_file("file20.rs");
+
+        assert_eq!(state.recent_files.len(), MAX_RECENT_FILES);
+        assert_eq!(state.recent_files[0], "file20.rs");
+        assert_eq!(state.recent_files[1], "file24.rs");
+        assert_eq!(
+            state
+                .recent_files
+                .iter()
+                .filter(|p| *p == "file20.rs")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_toggle_bookmark_and_tabs() {
+        let mut state = WorkspaceState::default();
+        assert!(state.toggle_bookmark("src/b.rs", 10, None));
+        assert!(state.toggle_bookmark("src/a.rs", 3, Some("entry point".to_string())));
+        assert_eq!(state.bookmarks[0].path, "src/a.rs");
+
+        assert!(!state.toggle_bookmark("src/b.rs", 10, None));
+        assert_eq!(state.bookmarks.len(), 1);
+
+        state.set_open_tabs(
+            vec!["src/a.rs".to_string()],
+            Some("src/gone.rs".to_string()),
+        );
+        assert_eq!(state.active_tab, None);
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let store = WorkspaceStore::for_project(root);
+        assert_eq!(store.load().unwrap(), WorkspaceState::default());
+
+        let main = root.join("src/main.rs").to_string_lossy().to_string();
+        let stored = store.stored_path(&main);
+        assert_eq!(stored, "src/main.rs");
+        let unnormalized = root.join("src/../src/./main.rs").to_string_lossy().to_string();
+        assert_eq!(store.stored_path(&unnormalized), "src/main.rs");
+
+        store
+            .update(|state| {
+                state.record_recent_file(&stored);
+                state.record_recent_file("src/deleted.rs");
+                state.set_cursor(&stored, CursorPosition { line: 4, column: 2 });
+                state.toggle_bookmark(&stored, 1, None);
+                state.set_open_tabs(
+                    vec![stored.clone(), "src/deleted.rs".to_string()],
+                    Some(stored.clone()),
+                );
+            })
+            .unwrap();
+
+        let raw = std::fs::read_to_string(root.join(".AuroraHeart/state.json")).unwrap();
+        assert!(raw.contains("\"src/main.rs\""));
+
+        let restored = store.restore().unwrap();
+        assert_eq!(restored.recent_files, vec![main.clone()]);
+        assert_eq!(restored.open_tabs, vec![main.clone()]);
+        assert_eq!(restored.active_tab, Some(main.clone()));
+        assert_eq!(
+            restored.cursors.get(&main),
+            Some(&CursorPosition { line: 4, column: 2 })
+        );
+        assert_eq!(restored.bookmarks[0].path, main);
+    }
+}
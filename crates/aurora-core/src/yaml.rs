@@ -0,0 +1,361 @@
+//! YAML reading for configuration files and manifests
+//!
+//! Documents are parsed with [`yaml_rust2`] and read into JSON values, along
+//! with the line each mapping entry and sequence item starts on so errors
+//! about a value can point at it. Aliases are expanded and `<<` merge keys
+//! are applied. Plain scalars resolve the way YAML 1.1 readers such as
+//! Kubernetes read them, so `yes` and `off` are booleans, unless a `!!str`
+//! tag says otherwise; other tags are ignored. Mapping keys must be scalars.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use thiserror::Error;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+/// A YAML syntax error
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("YAML error on line {line}: {message}")]
+pub struct YamlError {
+    /// Line of the error, from 1
+    pub line: usize,
+    pub message: String,
+}
+
+pub type Result<T> = std::result::Result<T, YamlError>;
+
+/// One document of a YAML file
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlDocument {
+    /// Line the document starts on, from 1
+    pub line: usize,
+    pub value: Value,
+    /// Line each mapping entry and sequence item starts on, by JSON pointer
+    pub positions: BTreeMap<String, usize>,
+}
+
+/// Values aliases may expand to in one document, so a few nested aliases
+/// cannot build an enormous value
+const MAX_ALIASED_VALUES: usize = 100_000;
+
+/// Read the non-empty documents of a YAML file
+pub fn parse_documents(text: &str) -> Result<Vec<YamlDocument>> {
+    let mut events = Events::default();
+    Parser::new_from_str(text)
+        .load(&mut events, true)
+        .map_err(|e| YamlError {
+            line: e.marker().line(),
+            message: e.info().to_string(),
+        })?;
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut documents = Vec::new();
+    let mut events = events.0.into_iter().peekable();
+    let mut first = true;
+    while let Some((event, mark)) = events.next() {
+        if event != Event::DocumentStart {
+            continue;
+        }
+        // An explicit document starts after its `---`
+        let explicit = lines.get(mark.line() - 1).is_some_and(|line| line.starts_with("---"));
+        let line = match (explicit, first) {
+            (true, _) => mark.line() + 1,
+            (false, true) => 1,
+            (false, false) => mark.line(),
+        };
+        first = false;
+        let mut builder = Builder::default();
+        for (event, mark) in events.by_ref() {
+            if event == Event::DocumentEnd {
+                break;
+            }
+            builder.event(event, mark)?;
+        }
+        if let Some(value) = builder.root.filter(|value| !value.is_null()) {
+            documents.push(YamlDocument {
+                line,
+                value,
+                positions: builder.positions,
+            });
+        }
+    }
+    Ok(documents)
+}
+
+/// Escape a key for use in a JSON pointer
+pub fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Parser events with their positions
+#[derive(Default)]
+struct Events(Vec<(Event, Marker)>);
+
+impl MarkedEventReceiver for Events {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        self.0.push((event, mark));
+    }
+}
+
+/// A collection being read
+enum Frame {
+    Sequence {
+        pointer: String,
+        anchor: usize,
+        items: Vec<Value>,
+    },
+    Mapping {
+        pointer: String,
+        anchor: usize,
+        map: Map<String, Value>,
+        /// Mappings merged in with `<<`, whose keys the mapping's own override
+        merged: Vec<Map<String, Value>>,
+        /// The key whose value is being read, and the line it is on
+        key: Option<(String, usize)>,
+    },
+}
+
+/// Builds one document's JSON value from its parser events
+#[derive(Default)]
+struct Builder {
+    stack: Vec<Frame>,
+    root: Option<Value>,
+    anchors: HashMap<usize, Value>,
+    aliased: usize,
+    positions: BTreeMap<String, usize>,
+}
+
+impl Builder {
+    fn error(mark: Marker, message: impl Into<String>) -> YamlError {
+        YamlError {
+            line: mark.line(),
+            message: message.into(),
+        }
+    }
+
+    fn event(&mut self, event: Event, mark: Marker) -> Result<()> {
+        // A scalar where a mapping expects a key is the key
+        if let Some(Frame::Mapping { pointer, key: key @ None, .. }) = self.stack.last_mut() {
+            let Event::Scalar(text, ..) = event else {
+                return match event {
+                    Event::MappingEnd => self.end(),
+                    _ => Err(Self::error(mark, "unsupported YAML construct: mapping keys must be scalars")),
+                };
+            };
+            if text != "<<" {
+                self.positions.insert(format!("{}/{}", pointer, escape_pointer(&text)), mark.line());
+            }
+            *key = Some((text, mark.line()));
+            return Ok(());
+        }
+
+        match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                self.node_position(mark);
+                let value = scalar(text, style, tag.as_ref());
+                self.complete(anchor, value)
+            }
+            Event::Alias(anchor) => {
+                self.node_position(mark);
+                let value = self
+                    .anchors
+                    .get(&anchor)
+                    .cloned()
+                    .ok_or_else(|| Self::error(mark, "alias to an unknown anchor"))?;
+                self.aliased += value_count(&value);
+                if self.aliased > MAX_ALIASED_VALUES {
+                    return Err(Self::error(mark, "aliases expand to too many values"));
+                }
+                self.complete(0, value)
+            }
+            Event::SequenceStart(anchor, _) => {
+                let pointer = self.node_position(mark);
+                self.stack.push(Frame::Sequence {
+                    pointer,
+                    anchor,
+                    items: Vec::new(),
+                });
+                Ok(())
+            }
+            Event::MappingStart(anchor, _) => {
+                let pointer = self.node_position(mark);
+                self.stack.push(Frame::Mapping {
+                    pointer,
+                    anchor,
+                    map: Map::new(),
+                    merged: Vec::new(),
+                    key: None,
+                });
+                Ok(())
+            }
+            Event::SequenceEnd | Event::MappingEnd => self.end(),
+            _ => Ok(()),
+        }
+    }
+
+    /// The pointer of the node starting at `mark`, recording the line of a
+    /// sequence item
+    fn node_position(&mut self, mark: Marker) -> String {
+        match self.stack.last() {
+            Some(Frame::Sequence { pointer, items, .. }) => {
+                let pointer = format!("{}/{}", pointer, items.len());
+                self.positions.insert(pointer.clone(), mark.line());
+                pointer
+            }
+            Some(Frame::Mapping {
+                pointer,
+                key: Some((key, _)),
+                ..
+            }) => format!("{}/{}", pointer, escape_pointer(key)),
+            _ => String::new(),
+        }
+    }
+
+    /// Finish the innermost collection
+    fn end(&mut self) -> Result<()> {
+        match self.stack.pop() {
+            Some(Frame::Sequence { anchor, items, .. }) => self.complete(anchor, Value::Array(items)),
+            Some(Frame::Mapping {
+                anchor, mut map, merged, ..
+            }) => {
+                for merged in merged {
+                    for (key, value) in merged {
+                        map.entry(key).or_insert(value);
+                    }
+                }
+                self.complete(anchor, Value::Object(map))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Place a finished node in its parent, remembering it under its anchor
+    fn complete(&mut self, anchor: usize, value: Value) -> Result<()> {
+        if anchor > 0 {
+            self.anchors.insert(anchor, value.clone());
+        }
+        match self.stack.last_mut() {
+            Some(Frame::Sequence { items, .. }) => items.push(value),
+            Some(Frame::Mapping { map, merged, key, .. }) => {
+                let (key, line) = key.take().expect("a mapping value follows its key");
+                if key == "<<" {
+                    let sources = match value {
+                        Value::Array(items) => items,
+                        value => vec![value],
+                    };
+                    for source in sources {
+                        match source {
+                            Value::Object(source) => merged.push(source),
+                            _ => {
+                                return Err(YamlError {
+                                    line,
+                                    message: "merge key '<<' must be given mappings".to_string(),
+                                })
+                            }
+                        }
+                    }
+                } else if map.insert(key.clone(), value).is_some() {
+                    return Err(YamlError {
+                        line,
+                        message: format!("duplicate key '{}'", key),
+                    });
+                }
+            }
+            None => self.root = Some(value),
+        }
+        Ok(())
+    }
+}
+
+/// The value of a scalar written with `style`
+fn scalar(text: String, style: TScalarStyle, tag: Option<&Tag>) -> Value {
+    match tag.map(|tag| tag.suffix.as_str()) {
+        Some("str") => Value::String(text),
+        _ if style == TScalarStyle::Plain => resolve_plain(&text),
+        _ => Value::String(text),
+    }
+}
+
+/// Number of values in `value`, counting itself
+fn value_count(value: &Value) -> usize {
+    1 + match value {
+        Value::Array(items) => items.iter().map(value_count).sum(),
+        Value::Object(map) => map.values().map(value_count).sum(),
+        _ => 0,
+    }
+}
+
+/// The value of an unquoted scalar
+fn resolve_plain(text: &str) -> Value {
+    static INTEGER: OnceLock<Regex> = OnceLock::new();
+    static FLOAT: OnceLock<Regex> = OnceLock::new();
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" | "y" | "Y" => return Value::Bool(true),
+        "false" | "False" | "FALSE" | "no" | "No" | "NO" | "off" | "Off" | "OFF" | "n" | "N" => {
+            return Value::Bool(false)
+        }
+        _ => {}
+    }
+    let integer = INTEGER.get_or_init(|| Regex::new(r"^[-+]?[0-9]+$").unwrap());
+    if integer.is_match(text) {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::from(n);
+        }
+    }
+    let float = FLOAT.get_or_init(|| Regex::new(r"^[-+]?([0-9]+\.[0-9]*|\.[0-9]+)([eE][-+]?[0-9]+)?$").unwrap());
+    if float.is_match(text) {
+        if let Some(n) = text.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let documents = parse_documents(
+            "name: CI\non:\n  push:\n    branches: [main]\njobs:\n  build/test:\n    steps:\n    - run: make\n    -\n      uses: actions/checkout@v4\n",
+        )
+        .unwrap();
+        let positions = &documents[0].positions;
+        assert_eq!(positions["/name"], 1);
+        assert_eq!(positions["/on/push/branches"], 4);
+        assert_eq!(positions["/jobs/build~1test/steps/0/run"], 8);
+        assert_eq!(positions["/jobs/build~1test/steps/1"], 10);
+        assert_eq!(positions["/jobs/build~1test/steps/1/uses"], 10);
+        assert_eq!(positions["/on/push/branches/0"], 4);
+    }
+
+    #[test]
+    fn test_anchors_and_merge_keys() {
+        let documents = parse_documents(
+            "x-common: &common\n  restart: always\n  environment: [\"A=1\"]\nservices:\n  web:\n    <<: *common\n    restart: \"no\"\n    image: web\n  db:\n    <<: [*common]\n    image: !!str 16\n",
+        )
+        .unwrap();
+        assert_eq!(
+            documents[0].value["services"],
+            serde_json::json!({
+                "web": { "restart": "no", "environment": ["A=1"], "image": "web" },
+                "db": { "restart": "always", "environment": ["A=1"], "image": "16" },
+            })
+        );
+        assert_eq!(documents[0].positions["/services/web/image"], 8);
+
+        let error = parse_documents("a: &a [*a]\n").unwrap_err();
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn test_multi_line_flow_collections() {
+        let documents = parse_documents("a: {b: 1,\n  c: [2,\n    3]}\n").unwrap();
+        assert_eq!(documents[0].value, serde_json::json!({ "a": { "b": 1, "c": [2, 3] } }));
+        assert_eq!(documents[0].positions["/a/c/1"], 3);
+    }
+}
@@ -0,0 +1,125 @@
+//! Approval of tool actions that need the user's consent
+//!
+//! Some tool actions, such as reading from a Kubernetes cluster or applying
+//! infrastructure changes, never run without the user's explicit approval.
+//! [`ApprovalBroker`] asks for it by emitting a `tool-approval-requested`
+//! event and waits until the frontend answers with the `answer_tool_approval`
+//! command. Cancelling the tool call stops the wait, and answering it
+//! afterwards does nothing.
+
+use aurora_agent::{ToolApproval, ToolUse};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+/// Event emitted when a tool action waits for the user's approval
+pub const TOOL_APPROVAL_REQUESTED: &str = "tool-approval-requested";
+
+/// Payload of the `tool-approval-requested` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    /// Pass back to `answer_tool_approval`
+    pub id: String,
+    /// Tool asking for approval
+    pub tool: String,
+    /// What the tool is about to do
+    pub action: serde_json::Value,
+}
+
+type Notifier = Box<dyn Fn(&ApprovalRequest) + Send + Sync>;
+
+/// Approval requests waiting for the user's answer
+pub struct ApprovalBroker {
+    pending: Mutex<HashMap<String, oneshot::Sender<bool>>>,
+    next_id: AtomicU64,
+    notify: Notifier,
+}
+
+impl ApprovalBroker {
+    /// Create a broker that asks the frontend of `app_handle`
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self::with_notifier(move |request| {
+            if let Err(e) = app_handle.emit(TOOL_APPROVAL_REQUESTED, request) {
+                tracing::error!("Failed to emit {}: {}", TOOL_APPROVAL_REQUESTED, e);
+            }
+        })
+    }
+
+    fn with_notifier(notify: impl Fn(&ApprovalRequest) + Send + Sync + 'static) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            notify: Box::new(notify),
+        }
+    }
+
+    /// Answer the request with `id`
+    ///
+    /// Returns false if there is no such request, or its call stopped waiting.
+    pub fn answer(&self, id: &str, approved: bool) -> bool {
+        match self.pending.lock().unwrap().remove(id) {
+            Some(sender) => sender.send(approved).is_ok(),
+            None => false,
+        }
+    }
+}
+
+impl ToolApproval for ApprovalBroker {
+    fn approve<'a>(&'a self, tool_use: &'a ToolUse) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+        Box::pin(async move {
+            let id = format!("approval-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+            let (tx, rx) = oneshot::channel();
+            self.pending.lock().unwrap().insert(id.clone(), tx);
+            (self.notify)(&ApprovalRequest {
+                id,
+                tool: tool_use.name.clone(),
+                action: tool_use.input.clone(),
+            });
+            rx.await.unwrap_or(false)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_approval_answered_through_broker() {
+        let (requests_tx, mut requests) = tokio::sync::mpsc::unbounded_channel();
+        let broker = Arc::new(ApprovalBroker::with_notifier(move |request| {
+            let _ = requests_tx.send(request.clone());
+        }));
+        let action = ToolUse {
+            id: "k8s_cluster_read".to_string(),
+            name: "k8s".to_string(),
+            input: serde_json::json!({ "action": "get", "context": "staging" }),
+        };
+
+        let waiting = tokio::spawn({
+            let broker = Arc::clone(&broker);
+            let action = action.clone();
+            async move { broker.approve(&action).await }
+        });
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request.tool, "k8s");
+        assert_eq!(request.action["context"], "staging");
+        assert!(broker.answer(&request.id, true));
+        assert!(waiting.await.unwrap());
+        assert!(!broker.answer(&request.id, true), "each request is answered once");
+
+        let waiting = tokio::spawn({
+            let broker = Arc::clone(&broker);
+            async move { broker.approve(&action).await }
+        });
+        let request = requests.recv().await.unwrap();
+        assert!(broker.answer(&request.id, false));
+        assert!(!waiting.await.unwrap());
+    }
+}
@@ -0,0 +1,161 @@
+//! Editor buffer registry
+//!
+//! The frontend reports which files are open and the content of tabs with
+//! unsaved changes. Agent file tools read those dirty buffers instead of the
+//! stale copy on disk, and every agent write emits a `file-externally-modified`
+//! event so the frontend can reload or warn about conflicting edits. Files
+//! opened as truncated previews are tracked so they are never saved back over
+//! the full file.
+
+use aurora_agent::{BufferSync, UnsavedBuffer};
+use aurora_core::normalize_path;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted after the agent writes a file
+pub const FILE_EXTERNALLY_MODIFIED: &str = "file-externally-modified";
+
+/// Payload of the `file-externally-modified` event
+#[derive(Debug, Clone, Serialize)]
+pub struct FileModifiedEvent {
+    /// Path of the written file
+    pub path: String,
+    /// Whether the file is open in an editor tab
+    pub is_open: bool,
+    /// Whether the editor held unsaved changes that the write was based on
+    pub had_unsaved_changes: bool,
+}
+
+/// Open editor buffers keyed by normalized path
+pub struct BufferRegistry {
+    /// Open files mapped to their unsaved content (`None` when clean)
+    buffers: Mutex<HashMap<PathBuf, Option<String>>>,
+    /// Open files that only hold the start of a file too large to load
+    previews: Mutex<HashSet<PathBuf>>,
+    app_handle: AppHandle,
+}
+
+/// Normalize a path so frontend and tool paths compare equal
+///
+/// Canonicalizing resolves links; normalizing afterwards removes the
+/// verbatim prefix Windows adds and unifies separators for unsaved files.
+fn buffer_key(path: &Path) -> PathBuf {
+    normalize_path(std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+}
+
+impl BufferRegistry {
+    /// Create an empty registry
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            buffers: Mutex::new(HashMap::new()),
+            previews: Mutex::new(HashSet::new()),
+            app_handle,
+        }
+    }
+
+    /// Record an open file and its unsaved content (`None` when it matches disk)
+    pub fn update(&self, path: &Path, dirty_content: Option<String>) {
+        if let Ok(mut buffers) = self.buffers.lock() {
+            buffers.insert(buffer_key(path), dirty_content);
+        }
+    }
+
+    /// Forget a closed file
+    pub fn close(&self, path: &Path) {
+        if let Ok(mut buffers) = self.buffers.lock() {
+            buffers.remove(&buffer_key(path));
+        }
+        self.set_preview(path, false);
+    }
+
+    /// Forget all buffers, e.g. when switching projects
+    pub fn clear(&self) {
+        if let Ok(mut buffers) = self.buffers.lock() {
+            buffers.clear();
+        }
+        if let Ok(mut previews) = self.previews.lock() {
+            previews.clear();
+        }
+    }
+
+    /// Content of every buffer with unsaved changes, ordered by path
+    pub fn unsaved(&self) -> Vec<UnsavedBuffer> {
+        let Ok(buffers) = self.buffers.lock() else {
+            return Vec::new();
+        };
+        let mut unsaved: Vec<UnsavedBuffer> = buffers
+            .iter()
+            .filter_map(|(path, content)| {
+                content.as_ref().map(|content| UnsavedBuffer {
+                    path: path.to_string_lossy().to_string(),
+                    content: content.clone(),
+                })
+            })
+            .collect();
+        unsaved.sort_by(|a, b| a.path.cmp(&b.path));
+        unsaved
+    }
+
+    /// Record whether a file was opened as a truncated preview
+    pub fn set_preview(&self, path: &Path, is_preview: bool) {
+        if let Ok(mut previews) = self.previews.lock() {
+            if is_preview {
+                previews.insert(buffer_key(path));
+            } else {
+                previews.remove(&buffer_key(path));
+            }
+        }
+    }
+
+    /// Whether the file is open as a truncated preview
+    pub fn is_preview(&self, path: &Path) -> bool {
+        self.previews
+            .lock()
+            .map(|previews| previews.contains(&buffer_key(path)))
+            .unwrap_or(false)
+    }
+}
+
+impl BufferSync for BufferRegistry {
+    fn dirty_content(&self, path: &Path) -> Option<String> {
+        let buffers = self.buffers.lock().ok()?;
+        buffers.get(&buffer_key(path)).cloned().flatten()
+    }
+
+    fn dirty_paths(&self) -> Vec<PathBuf> {
+        match self.buffers.lock() {
+            Ok(buffers) => buffers
+                .iter()
+                .filter(|(_, content)| content.is_some())
+                .map(|(path, _)| path.clone())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn file_written(&self, path: &Path) {
+        let key = buffer_key(path);
+        let (is_open, had_unsaved_changes) = match self.buffers.lock() {
+            Ok(mut buffers) => match buffers.get_mut(&key) {
+                // Disk now holds the agent's version; the frontend decides
+                // whether to reload it into the tab
+                Some(content) => (true, content.take().is_some()),
+                None => (false, false),
+            },
+            Err(_) => (false, false),
+        };
+
+        let event = FileModifiedEvent {
+            path: key.to_string_lossy().to_string(),
+            is_open,
+            had_unsaved_changes,
+        };
+        tracing::debug!("Emitting {} for {}", FILE_EXTERNALLY_MODIFIED, event.path);
+        if let Err(e) = self.app_handle.emit(FILE_EXTERNALLY_MODIFIED, event) {
+            tracing::error!("Failed to emit {}: {}", FILE_EXTERNALLY_MODIFIED, e);
+        }
+    }
+}
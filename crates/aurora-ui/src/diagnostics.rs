@@ -0,0 +1,218 @@
+//! Diagnostics hub
+//!
+//! Language server diagnostics, build errors, lint findings, and analysis
+//! results are merged into one problem list. Each origin publishes its own
+//! diagnostics, for the whole project or a single file, replacing what it
+//! published there before. Every change emits a `diagnostics-changed` event
+//! with the new counts, and the list backs the agent's `get_diagnostics` and
+//! `apply_quick_fix` tools. Applying a fix removes its diagnostic until the
+//! origin reports again.
+
+use aurora_agent::DiagnosticsProvider;
+use aurora_core::{Diagnostic, DiagnosticFilter, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted whenever the problem list changes
+pub const DIAGNOSTICS_CHANGED: &str = "diagnostics-changed";
+
+/// Where a diagnostic came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticOrigin {
+    /// A language server
+    Lsp,
+    /// Compiler output from a build
+    Build,
+    /// A linter
+    Lint,
+    /// Static analysis
+    Analysis,
+}
+
+impl DiagnosticOrigin {
+    /// Origin of diagnostics attached to an agent tool result
+    pub fn for_tool(name: &str) -> Self {
+        match name {
+            "build" => DiagnosticOrigin::Build,
+            "lint" => DiagnosticOrigin::Lint,
+            _ => DiagnosticOrigin::Analysis,
+        }
+    }
+}
+
+/// A diagnostic in the problem list
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticEntry {
+    /// Id used to apply the diagnostic's quick fixes
+    pub id: String,
+    /// Where it came from
+    pub origin: DiagnosticOrigin,
+    /// The diagnostic itself
+    #[serde(flatten)]
+    pub diagnostic: Diagnostic,
+}
+
+/// Number of diagnostics per severity
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiagnosticCounts {
+    /// Number of errors
+    pub errors: usize,
+    /// Number of warnings
+    pub warnings: usize,
+    /// Number of informational notes
+    pub infos: usize,
+    /// Number of hints
+    pub hints: usize,
+}
+
+/// Merged problem list from all diagnostic origins
+pub struct DiagnosticsHub {
+    diagnostics: Mutex<BTreeMap<DiagnosticOrigin, Vec<Diagnostic>>>,
+    app_handle: AppHandle,
+}
+
+impl DiagnosticsHub {
+    /// Create an empty hub
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            diagnostics: Mutex::new(BTreeMap::new()),
+            app_handle,
+        }
+    }
+
+    /// Replace the diagnostics an origin reported for `file`, or for the
+    /// whole project when `file` is `None`
+    pub fn publish(
+        &self,
+        origin: DiagnosticOrigin,
+        file: Option<&str>,
+        diagnostics: Vec<Diagnostic>,
+    ) {
+        let changed = match self.diagnostics.lock() {
+            Ok(mut all) => {
+                let current = all.entry(origin).or_default();
+                let mut next: Vec<Diagnostic> = match file {
+                    Some(file) => current.iter().filter(|d| d.file != file).cloned().collect(),
+                    None => Vec::new(),
+                };
+                next.extend(diagnostics);
+
+                if next == *current {
+                    false
+                } else {
+                    *current = next;
+                    true
+                }
+            }
+            Err(_) => false,
+        };
+
+        if changed {
+            self.notify();
+        }
+    }
+
+    /// Forget all diagnostics, e.g. when switching projects
+    pub fn clear(&self) {
+        let changed = match self.diagnostics.lock() {
+            Ok(mut all) => {
+                let had_any = all.values().any(|d| !d.is_empty());
+                all.clear();
+                had_any
+            }
+            Err(_) => false,
+        };
+
+        if changed {
+            self.notify();
+        }
+    }
+
+    /// Diagnostics matching the filter, most severe first, then by location
+    pub fn query(
+        &self,
+        filter: &DiagnosticFilter,
+        origin: Option<DiagnosticOrigin>,
+    ) -> Vec<DiagnosticEntry> {
+        let Ok(all) = self.diagnostics.lock() else {
+            return Vec::new();
+        };
+
+        let mut entries: Vec<DiagnosticEntry> = all
+            .iter()
+            .filter(|(o, _)| origin.is_none_or(|origin| **o == origin))
+            .flat_map(|(o, diagnostics)| {
+                diagnostics
+                    .iter()
+                    .filter(|d| filter.matches(d))
+                    .map(|d| DiagnosticEntry {
+                        id: d.id(),
+                        origin: *o,
+                        diagnostic: d.clone(),
+                    })
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            let (a, b) = (&a.diagnostic, &b.diagnostic);
+            a.severity
+                .cmp(&b.severity)
+                .then_with(|| a.file.cmp(&b.file))
+                .then(a.line.cmp(&b.line))
+                .then(a.column.cmp(&b.column))
+        });
+        entries
+    }
+
+    /// Number of diagnostics per severity across all origins
+    pub fn counts(&self) -> DiagnosticCounts {
+        let mut counts = DiagnosticCounts::default();
+        if let Ok(all) = self.diagnostics.lock() {
+            for diagnostic in all.values().flatten() {
+                match diagnostic.severity {
+                    Severity::Error => counts.errors += 1,
+                    Severity::Warning => counts.warnings += 1,
+                    Severity::Info => counts.infos += 1,
+                    Severity::Hint => counts.hints += 1,
+                }
+            }
+        }
+        counts
+    }
+
+    fn notify(&self) {
+        let counts = self.counts();
+        tracing::debug!("Emitting {}: {:?}", DIAGNOSTICS_CHANGED, counts);
+        if let Err(e) = self.app_handle.emit(DIAGNOSTICS_CHANGED, counts) {
+            tracing::error!("Failed to emit {}: {}", DIAGNOSTICS_CHANGED, e);
+        }
+    }
+}
+
+impl DiagnosticsProvider for DiagnosticsHub {
+    fn diagnostics(&self, filter: &DiagnosticFilter) -> Vec<Diagnostic> {
+        self.query(filter, None)
+            .into_iter()
+            .map(|entry| entry.diagnostic)
+            .collect()
+    }
+
+    fn fixed(&self, id: &str) {
+        let changed = match self.diagnostics.lock() {
+            Ok(mut all) => {
+                let before: usize = all.values().map(Vec::len).sum();
+                for diagnostics in all.values_mut() {
+                    diagnostics.retain(|d| d.id() != id);
+                }
+                all.values().map(Vec::len).sum::<usize>() != before
+            }
+            Err(_) => false,
+        };
+
+        if changed {
+            self.notify();
+        }
+    }
+}
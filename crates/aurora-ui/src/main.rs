@@ -5,18 +5,59 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod approvals;
+mod buffers;
+mod diagnostics;
+mod progress;
+mod project_index;
 mod terminal;
-
-use aurora_agent::{AgenticEvent, AnthropicClient, Conversation, ToolExecutor};
+mod watchers;
+
+use aurora_agent::attachments::{self, Attachment, AttachmentLimits};
+use aurora_agent::changelog::polish_changelog;
+use aurora_agent::ci::{self, CiClient, CiStatus, JobLog};
+use aurora_agent::issues::{self, Issue, IssueClient};
+use aurora_agent::onboarding::{self, OnboardingPlan, ProjectAnalysis, ProposedFile};
+use aurora_agent::sessions::{self, SavedSession, SessionMetadata, SessionSearchResult, SessionStore};
+use aurora_agent::voice::{self, Transcriber};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use aurora_agent::updates::{AvailableUpdate, StagedUpdate, UpdateClient, UpdateStager, CURRENT_VERSION};
+use aurora_agent::{
+    AgenticEvent, AnthropicClient, BufferSync, ClientError, FilterAuditEntry, FilterAuditLog, OutboundFilter,
+    OutboundFilterError, Conversation, ConversationUsage, EditorState, TranscriptError,
+    TranscriptStore, UsageRange,
+    UsageRecord, UsageReport, UsageStore,
+    AgentMode, FilteredToolExecutor, SharedEditorState, SystemPrompt, SystemPromptBuilder, ToolApproval, ToolCache,
+    ToolExecutor, ToolMetricsReport, ToolPolicy, project_directives, repo_map, ReplEntry, ReplLanguage, ReplSessionInfo,
+    CellExecution, KernelInfo, Persona, PromptOverride,
+    InFlightTurn, RecoverySnapshot, RecoveryStore,
+};
 use aurora_core::{
-    detect_language, find_project_root, get_project_name, read_file, write_file, Config,
-    ConfigError, CredentialStore,
+    Action, ActionKind, ActionRegistry, PluginManager, CellOutput, Notebook, NotebookCell, OutputStyle,
+    FileIcon, FileIcons, OutboundFilterConfig, AppliedChanges, FileChanges, FileOwners, file_owners,
+    ChangelogPreview, Release, commits_in_range, save_changelog,
+    BudgetConfig, detect_language, find_project_root, get_project_name, lint_commit_message,
+    read_file_streaming, validate_branch_name, write_file_streaming, Config, ConfigError,
+    ConventionReport, CredentialStore, CursorPosition, Diagnostic, DiagnosticFilter, FileHistory, FileMatch,
+    HistoryEntry, HistorySource, IgnoreRules, Locale, Progress, ProjectSearch, QualityGate, QualityGateReport,
+    RecentProject, RecentProjects, RecentProjectsStore, ReplaceOptions, ReplaceSummary, SamplingParams, SbomFormat, Scaffolder, SearchQuery, SearchSummary, SecretBaseline,
+    SecretFinding, SecretScanner, Shell, ShellEnvironment, SymbolKind, SymbolMatch, TaskDefinition, normalize_path, path_key,
+    resolve_path, TaskReport, TaskRunner, TaskWatcher,
+    TemplateInfo, UiMessage, UpdateConfig, validate_project_root, WatchRun, WorkspaceState, WorkspaceStore, ANTHROPIC_LIMITS,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::{Manager, State};
+use approvals::ApprovalBroker;
+use buffers::BufferRegistry;
+use diagnostics::{DiagnosticEntry, DiagnosticOrigin, DiagnosticsHub};
+use progress::{ProgressInfo, ProgressRegistry};
+use project_index::ProjectIndex;
 use terminal::{ShellType, TerminalId, TerminalManager};
+use watchers::WatchRegistry;
 
 /// Initialize tracing for logging
 fn init_tracing() {
@@ -37,6 +78,8 @@ pub struct FileTreeItem {
     pub name: String,
     pub path: String,
     pub is_directory: bool,
+    /// Icon drawn for the entry, the same one its editor tab shows
+    pub icon: FileIcon,
 }
 
 /// File open result
@@ -44,17 +87,174 @@ pub struct FileTreeItem {
 pub struct FileOpenResult {
     pub path: String,
     pub content: String,
+    /// Whether the content is a read-only preview of a file over the size cap
+    pub truncated: bool,
+    /// Size of the whole file in bytes
+    pub total_bytes: u64,
+    /// Icon for the file's tab
+    pub icon: FileIcon,
+}
+
+/// Event emitted as large files are read or written
+const FILE_PROGRESS: &str = "file-progress";
+
+/// Direction of a file transfer reported by `file-progress`
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum FileOperation {
+    Read,
+    Write,
+}
+
+/// Payload of the `file-progress` event
+#[derive(Debug, Clone, Serialize)]
+struct FileProgress {
+    /// Path of the file being transferred
+    path: String,
+    /// Whether the file is being read or written
+    operation: FileOperation,
+    #[serde(flatten)]
+    progress: Progress,
+}
+
+/// Emit `file-progress` for one chunk of a transfer
+fn emit_file_progress(app: &tauri::AppHandle, path: &str, operation: FileOperation, progress: Progress) {
+    use tauri::Emitter;
+
+    let event = FileProgress {
+        path: path.to_string(),
+        operation,
+        progress,
+    };
+    if let Err(e) = app.emit(FILE_PROGRESS, event) {
+        tracing::error!("Failed to emit {}: {}", FILE_PROGRESS, e);
+    }
+}
+
+/// Read a file for the editor in chunks, capped at the configured size
+///
+/// Files over the cap open as a truncated preview that cannot be saved.
+async fn open_for_editing(
+    path: String,
+    app: tauri::AppHandle,
+    state: &State<'_, AppState>,
+) -> Result<FileOpenResult, String> {
+    let max_bytes = {
+        let project_root = state.project_root.lock().unwrap();
+        Config::load(&*project_root).unwrap_or_default().editor.max_open_file_size
+    };
+
+    let read_path = path.clone();
+    let read = tokio::task::spawn_blocking(move || {
+        read_file_streaming(&read_path, max_bytes, |progress| {
+            emit_file_progress(&app, &read_path, FileOperation::Read, progress)
+        })
+    })
+    .await
+    .map_err(|e| format!("File read failed: {}", e))?
+    .map_err(|e| {
+        let error_msg = format!("Error reading file: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+
+    if read.truncated {
+        tracing::info!(
+            "Opened {} as a preview: {} of {} bytes",
+            path,
+            read.content.len(),
+            read.total_bytes
+        );
+    }
+    state.buffers.set_preview(Path::new(&path), read.truncated);
+    let icon = state.file_icons.lock().unwrap().icon(Path::new(&path), false);
+
+    Ok(FileOpenResult {
+        icon,
+        path,
+        content: read.content,
+        truncated: read.truncated,
+        total_bytes: read.total_bytes,
+    })
 }
 
 /// Application state shared across Tauri commands
 pub struct AppState {
     pub project_root: Arc<Mutex<PathBuf>>,
     pub conversation: Arc<Mutex<Conversation>>,
+    pub session: Arc<Mutex<SessionMetadata>>,
+    pub editor_state: SharedEditorState,
+    pub buffers: Arc<BufferRegistry>,
+    pub diagnostics: Arc<DiagnosticsHub>,
+    pub tool_executor: Arc<ToolExecutor>,
+    /// Plan mode, or the approved plan being carried out
+    pub agent_mode: Arc<Mutex<AgentMode>>,
+    /// Run tool calls within the policy's auto-approve limits
+    pub auto_approve: Arc<Mutex<bool>>,
+    /// Tool actions waiting for the user's explicit approval
+    pub approvals: Arc<ApprovalBroker>,
+    pub watchers: Arc<WatchRegistry>,
+    /// File and symbol indexes of the open project for quick-open and
+    /// go-to-symbol
+    pub project_index: Arc<ProjectIndex>,
+    /// Long-running operations reported to the frontend
+    pub progress: Arc<ProgressRegistry>,
+    /// The agent turn running, kept for crash recovery snapshots
+    pub turn: Arc<Mutex<Option<InFlightTurn>>>,
+    /// State left by a run that crashed, until restored or discarded
+    pub recovery: Mutex<Option<RecoverySnapshot>>,
     pub terminal_manager: TerminalManager,
+    /// How tool output is displayed, rich or plain for screen readers
+    pub output_style: Mutex<OutputStyle>,
+    /// Icons of the open project's files, from the built-in, plugin, and
+    /// config definitions
+    pub file_icons: Mutex<FileIcons>,
+    /// The open project's outbound filter and the config it was built from,
+    /// kept across turns so each hit is audited once
+    pub outbound_filter: Mutex<Option<(PathBuf, OutboundFilterConfig, Arc<OutboundFilter>)>>,
+    /// Bumped on every project switch, so a turn started in the old project
+    /// does not write its results into the new one
+    pub project_generation: AtomicU64,
+}
+
+/// Load the project's ignore rules, hiding nothing if they cannot be read
+fn project_ignore_rules(project_root: &Path) -> IgnoreRules {
+    let config = Config::load(project_root).unwrap_or_default();
+    IgnoreRules::load(project_root, &config).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load ignore rules: {}", e);
+        IgnoreRules::default()
+    })
+}
+
+/// The open project's file icons
+fn project_file_icons(project_root: &Path) -> FileIcons {
+    FileIcons::for_project(project_root, &Config::load(project_root).unwrap_or_default())
+}
+
+/// The open project's outbound filter, rebuilt when the project or its
+/// `[outbound_filter]` config changes
+fn project_outbound_filter(state: &AppState, project_root: &Path, config: &OutboundFilterConfig) -> Result<Arc<OutboundFilter>, String> {
+    let mut cached = state.outbound_filter.lock().unwrap();
+    if let Some((root, built_from, filter)) = cached.as_ref() {
+        if root == project_root && built_from == config {
+            return Ok(Arc::clone(filter));
+        }
+    }
+    let filter = Arc::new(OutboundFilter::for_project(project_root, config).map_err(|e| format!("⚠ {}", e))?);
+    *cached = Some((project_root.to_path_buf(), config.clone(), Arc::clone(&filter)));
+    Ok(filter)
+}
+
+/// Load the project's tool policy, using the default limits if it is invalid
+fn project_tool_policy(project_root: &Path) -> ToolPolicy {
+    ToolPolicy::load(project_root).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load tool policy: {}", e);
+        ToolPolicy::default()
+    })
 }
 
-/// Load files from current directory into file tree
-fn load_file_tree_internal<P: AsRef<Path>>(dir: P) -> Vec<FileTreeItem> {
+/// Load files from current directory into file tree, leaving out ignored paths
+fn load_file_tree_internal<P: AsRef<Path>>(dir: P, ignore: &IgnoreRules, icons: &FileIcons) -> Vec<FileTreeItem> {
     let mut items = Vec::new();
 
     if let Ok(entries) = std::fs::read_dir(dir.as_ref()) {
@@ -72,9 +272,13 @@ fn load_file_tree_internal<P: AsRef<Path>>(dir: P) -> Vec<FileTreeItem> {
             }
 
             let is_directory = path.is_dir();
-            let path_str = path.to_string_lossy().to_string();
+            if ignore.is_ignored(&path, is_directory) {
+                continue;
+            }
+            let path_str = normalize_path(&path).to_string_lossy().to_string();
 
             items.push(FileTreeItem {
+                icon: icons.icon_for_name(&file_name, is_directory),
                 name: file_name,
                 path: path_str,
                 is_directory,
@@ -82,14 +286,49 @@ fn load_file_tree_internal<P: AsRef<Path>>(dir: P) -> Vec<FileTreeItem> {
         }
     }
 
-    // Sort: directories first, then files, both alphabetically
+    sort_tree_items(&mut items);
+    items
+}
+
+/// Sort tree items: directories first, then files, both alphabetically
+fn sort_tree_items(items: &mut [FileTreeItem]) {
     items.sort_by(|a, b| match (a.is_directory, b.is_directory) {
         (true, false) => std::cmp::Ordering::Less,
         (false, true) => std::cmp::Ordering::Greater,
         _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
     });
+}
 
-    items
+/// Contents of the project directory `dir`, from the project index when it
+/// holds the directory and from disk otherwise
+fn directory_contents(state: &AppState, project_root: &Path, dir: &Path) -> Vec<FileTreeItem> {
+    let relative = normalize_path(dir)
+        .strip_prefix(normalize_path(project_root))
+        .ok()
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"));
+    let entries = relative
+        .as_deref()
+        .and_then(|relative| state.project_index.entries(relative).map(|entries| (relative, entries)));
+
+    let icons = state.file_icons.lock().unwrap();
+    match entries {
+        Some((relative, entries)) => {
+            let mut items: Vec<FileTreeItem> = entries
+                .into_iter()
+                .map(|entry| FileTreeItem {
+                    path: normalize_path(project_root.join(relative).join(&entry.name))
+                        .to_string_lossy()
+                        .to_string(),
+                    icon: icons.icon_for_name(&entry.name, entry.is_directory),
+                    name: entry.name,
+                    is_directory: entry.is_directory,
+                })
+                .collect();
+            sort_tree_items(&mut items);
+            items
+        }
+        None => load_file_tree_internal(dir, &project_ignore_rules(project_root), &icons),
+    }
 }
 
 /// Mask API key for display (show first 7 chars and last 4 chars)
@@ -103,28 +342,108 @@ fn mask_api_key(api_key: &str) -> String {
     format!("{}...{}", prefix, suffix)
 }
 
+/// Assemble the system prompt for a project
+/// Text of a UI message in the project's configured language
+fn ui_message(project_root: &Path, message: UiMessage) -> &'static str {
+    Locale::for_messages(&Config::load(project_root).unwrap_or_default().locale).message(message)
+}
+
+fn build_system_prompt(
+    project_root: &Path,
+    config: &Config,
+    mode: &AgentMode,
+    prompt_override: &PromptOverride,
+) -> SystemPrompt {
+    SystemPromptBuilder::for_project(project_root)
+        .response_language(&config.locale)
+        .agent_mode(mode)
+        .directives(project_directives(project_root, config))
+        .prompt_override(prompt_override)
+        .repo_map(repo_map(project_root, &project_ignore_rules(project_root)))
+        .build()
+}
+
 // ============================================================================
 // TAURI COMMANDS
 // ============================================================================
 
 /// Get file tree for the project root
+///
+/// Served from the project index, which pushes `tree-updated` events as
+/// files and directories appear and disappear.
 #[tauri::command]
 async fn get_file_tree(state: State<'_, AppState>) -> Result<Vec<FileTreeItem>, String> {
     tracing::info!("get_file_tree command called");
-    let project_root = state.project_root.lock().unwrap();
-    Ok(load_file_tree_internal(&*project_root))
+    let project_root = state.project_root.lock().unwrap().clone();
+    Ok(directory_contents(&state, &project_root, &project_root))
+}
+
+/// Default number of results of the quick-open and go-to-symbol finders
+const DEFAULT_FUZZY_LIMIT: usize = 50;
+
+/// Find project files whose paths fuzzily match `query`, best first
+///
+/// Served from the in-memory project index, which is kept up to date as
+/// files are added and removed. Paths are relative to the project root.
+#[tauri::command]
+async fn fuzzy_find_files(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FileMatch>, String> {
+    let project_index = Arc::clone(&state.project_index);
+    let limit = limit.unwrap_or(DEFAULT_FUZZY_LIMIT);
+    // Waits while the index is still being built
+    tokio::task::spawn_blocking(move || project_index.find(&query, limit))
+        .await
+        .map_err(|e| format!("File search failed: {}", e))
+}
+
+/// Find symbols defined anywhere in the project whose names fuzzily match
+/// `query`, best first, optionally only those of `kind`
+///
+/// Served from the project's symbol index, which is stored in
+/// `.AuroraHeart/index/` and re-extracts files as they change.
+#[tauri::command]
+async fn find_workspace_symbol(
+    query: String,
+    kind: Option<SymbolKind>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SymbolMatch>, String> {
+    let project_index = Arc::clone(&state.project_index);
+    let limit = limit.unwrap_or(DEFAULT_FUZZY_LIMIT);
+    // Waits while the index is still being built
+    tokio::task::spawn_blocking(move || project_index.find_symbols(&query, kind, limit))
+        .await
+        .map_err(|e| format!("Symbol search failed: {}", e))
 }
 
 /// Get directory contents
 #[tauri::command]
-async fn get_directory_contents(path: String) -> Result<Vec<FileTreeItem>, String> {
+async fn get_directory_contents(path: String, state: State<'_, AppState>) -> Result<Vec<FileTreeItem>, String> {
     tracing::info!("get_directory_contents command called for: {}", path);
-    Ok(load_file_tree_internal(path))
+    let project_root = state.project_root.lock().unwrap().clone();
+    Ok(directory_contents(&state, &project_root, &project_path(&state, &path)))
+}
+
+/// Icons of the files at `paths`, for tabs restored without opening their
+/// files
+#[tauri::command]
+async fn get_file_icons(paths: Vec<String>, state: State<'_, AppState>) -> Result<Vec<FileIcon>, String> {
+    let icons = state.file_icons.lock().unwrap();
+    Ok(paths
+        .iter()
+        .map(|path| {
+            let path = Path::new(path);
+            icons.icon(path, path.is_dir())
+        })
+        .collect())
 }
 
 /// Open a file using native file dialog
 #[tauri::command]
-async fn open_file(app: tauri::AppHandle) -> Result<Option<FileOpenResult>, String> {
+async fn open_file(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Option<FileOpenResult>, String> {
     use tauri_plugin_dialog::DialogExt;
 
     tracing::info!("open_file command called");
@@ -139,20 +458,9 @@ async fn open_file(app: tauri::AppHandle) -> Result<Option<FileOpenResult>, Stri
         // FilePath::as_path() returns Option<&Path>, so we need to handle it
         if let Some(p) = path.as_path() {
             let path_str = p.to_string_lossy().to_string();
-            match read_file(&path_str) {
-                Ok(content) => {
-                    tracing::info!("Opened file: {}", path_str);
-                    Ok(Some(FileOpenResult {
-                        path: path_str,
-                        content,
-                    }))
-                }
-                Err(e) => {
-                    let error_msg = format!("Error reading file: {}", e);
-                    tracing::error!("{}", error_msg);
-                    Err(error_msg)
-                }
-            }
+            let result = open_for_editing(path_str, app.clone(), &state).await?;
+            tracing::info!("Opened file: {}", result.path);
+            Ok(Some(result))
         } else {
             Err("Invalid file path".to_string())
         }
@@ -164,31 +472,121 @@ async fn open_file(app: tauri::AppHandle) -> Result<Option<FileOpenResult>, Stri
 
 /// Read a file by path
 #[tauri::command]
-async fn read_file_by_path(path: String) -> Result<String, String> {
+async fn read_file_by_path(
+    path: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     tracing::info!("read_file_by_path command called for: {}", path);
+    let path = project_path(&state, &path).to_string_lossy().to_string();
 
-    read_file(&path).map_err(|e| {
-        let error_msg = format!("Error reading file: {}", e);
-        tracing::error!("{}", error_msg);
-        error_msg
-    })
+    Ok(open_for_editing(path, app, &state).await?.content)
+}
+
+/// Resolve a path from the frontend against the project root and normalize it
+fn project_path(state: &State<'_, AppState>, path: &str) -> PathBuf {
+    resolve_path(&*state.project_root.lock().unwrap(), path)
+}
+
+/// Local file history of the current project
+fn project_file_history(state: &State<'_, AppState>) -> FileHistory {
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).unwrap_or_default();
+    FileHistory::for_project(&project_root, &config)
 }
 
 /// Save file content to disk
 #[tauri::command]
-async fn save_file(path: String, content: String) -> Result<(), String> {
+async fn save_file(
+    path: String,
+    content: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
     tracing::info!("save_file command called for: {}", path);
+    let path = project_path(&state, &path).to_string_lossy().to_string();
+
+    if state.buffers.is_preview(Path::new(&path)) {
+        let error_msg = format!(
+            "{} is open as a truncated preview; saving it would discard the rest of the file",
+            path
+        );
+        tracing::error!("{}", error_msg);
+        return Err(error_msg);
+    }
 
-    write_file(&path, &content).map_err(|e| {
+    // Files outside the project have no history; recording never blocks a save
+    let history = project_file_history(&state);
+    if let Err(e) = history.record_existing(Path::new(&path), HistorySource::External) {
+        tracing::debug!("Not recording history of {}: {}", path, e);
+    }
+    let (write_path, write_content) = (path.clone(), content.clone());
+    tokio::task::spawn_blocking(move || {
+        write_file_streaming(&write_path, &write_content, |progress| {
+            emit_file_progress(&app, &write_path, FileOperation::Write, progress)
+        })
+    })
+    .await
+    .map_err(|e| format!("File write failed: {}", e))?
+    .map_err(|e| {
         let error_msg = format!("Error saving file: {}", e);
         tracing::error!("{}", error_msg);
         error_msg
     })?;
+    if let Err(e) = history.record(Path::new(&path), &content, HistorySource::Save) {
+        tracing::debug!("Not recording history of {}: {}", path, e);
+    }
+    state.buffers.update(Path::new(&path), None);
 
     tracing::info!("File saved successfully: {}", path);
     Ok(())
 }
 
+/// Recorded versions of a file, newest first
+#[tauri::command]
+async fn get_file_history(path: String, state: State<'_, AppState>) -> Result<Vec<HistoryEntry>, String> {
+    tracing::info!("get_file_history command called for: {}", path);
+    project_file_history(&state)
+        .versions(Path::new(&path))
+        .map_err(|e| {
+            let error_msg = format!("Failed to load file history: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Content of one recorded version of a file
+#[tauri::command]
+async fn get_file_version(path: String, id: String, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("get_file_version command called for: {} ({})", path, id);
+    project_file_history(&state)
+        .read_version(Path::new(&path), &id)
+        .map_err(|e| {
+            let error_msg = format!("Failed to read file version: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Restore a file to a recorded version and return the restored content
+///
+/// The replaced content is recorded first, so the restore can be undone.
+#[tauri::command]
+async fn restore_file_version(path: String, id: String, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("restore_file_version command called for: {} ({})", path, id);
+    let content = project_file_history(&state)
+        .restore(Path::new(&path), &id)
+        .map_err(|e| {
+            let error_msg = format!("Failed to restore file version: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+
+    // Let open tabs reload the restored content
+    state.buffers.file_written(&project_path(&state, &path));
+    Ok(content)
+}
+
 /// Save API key to encrypted credential store
 #[tauri::command]
 async fn save_api_key(key: String, state: State<'_, AppState>) -> Result<(), String> {
@@ -219,27 +617,62 @@ async fn load_api_key(state: State<'_, AppState>) -> Result<String, String> {
         .retrieve("anthropic_api_key", "auroraheart")
         .map_err(|e| {
             tracing::debug!("No API key found: {}", e);
-            "No API key configured".to_string()
+            ui_message(&project_root, UiMessage::NoApiKey).to_string()
+        })
+}
+
+/// Event emitted with the structured diagnostics of each build tool result
+const BUILD_DIAGNOSTICS: &str = "build-diagnostics";
+
+/// Event carrying output of a running tool command
+const TOOL_OUTPUT: &str = "tool-output";
+
+/// Event emitted when a running tool command appears to be prompting for input
+const TOOL_AWAITING_INPUT: &str = "tool-awaiting-input";
+
+/// Event emitted when the conversation reaches its budget and the agent pauses
+const BUDGET_EXCEEDED: &str = "budget-exceeded";
+
+/// Event emitted when possible prompt injections are removed from a tool
+/// result
+const INJECTION_DETECTED: &str = "injection-detected";
+
+/// Event emitted with each evaluation the repl tool finishes
+const REPL_UPDATE: &str = "repl-update";
+
+/// Event carrying an output of a notebook cell the notebook panel is running
+const NOTEBOOK_OUTPUT: &str = "notebook-output";
+
+/// Payload of a `notebook-output` event
+#[derive(Clone, Serialize)]
+struct NotebookOutputEvent {
+    path: String,
+    cell: usize,
+    output: CellOutput,
+}
+
+/// Load the API key for an agent turn
+fn turn_api_key(state: &AppState) -> Result<String, String> {
+    let project_root = state.project_root.lock().unwrap();
+    let store = CredentialStore::for_project(&*project_root);
+    store
+        .retrieve("anthropic_api_key", "auroraheart")
+        .map_err(|e| {
+            tracing::error!("Failed to load API key: {}", e);
+            format!("⚠ {}", ui_message(&project_root, UiMessage::NoApiKey))
         })
 }
 
 /// Send a message to Claude and run the agentic loop
 #[tauri::command]
-async fn send_message(message: String, state: State<'_, AppState>) -> Result<String, String> {
+async fn send_message(
+    message: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     tracing::info!("send_message command called: {}", message);
 
-    // Load API key
-    let api_key = {
-        let project_root = state.project_root.lock().unwrap();
-        let store = CredentialStore::for_project(&*project_root);
-        store
-            .retrieve("anthropic_api_key", "auroraheart")
-            .map_err(|e| {
-                let error_msg = "⚠ No API key configured. Please set your API key in Settings.";
-                tracing::error!("Failed to load API key: {}", e);
-                error_msg.to_string()
-            })?
-    };
+    let api_key = turn_api_key(&state)?;
 
     // Add user message to conversation and truncate if needed
     {
@@ -252,58 +685,200 @@ async fn send_message(message: String, state: State<'_, AppState>) -> Result<Str
         }
     }
 
-    // Create client
-    let client = AnthropicClient::new(api_key);
+    run_agent_turn(api_key, Some(&message), &app, &state).await
+}
+
+/// Continue a conversation paused at its budget, allowing the same spend again
+#[tauri::command]
+async fn continue_after_budget(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("continue_after_budget command called");
+
+    let api_key = turn_api_key(&state)?;
+    let project_budget = {
+        let project_root = state.project_root.lock().unwrap();
+        Config::load(&*project_root).unwrap_or_default().agent.budget
+    };
+    {
+        let mut conv = state.conversation.lock().unwrap();
+        let budget = if conv.budget.is_unlimited() { project_budget } else { conv.budget };
+        conv.budget = conv.usage.extend(&budget);
+    }
+
+    run_agent_turn(api_key, None, &app, &state).await
+}
 
-    // Create tool executor
-    let project_root_path = {
-        let guard = state.project_root.lock().unwrap();
-        guard.clone()
+/// Log a failure to write the transcript; the turn goes on without it
+fn note_transcript_error(result: Result<(), TranscriptError>) {
+    if let Err(e) = result {
+        tracing::warn!("Failed to write transcript: {}", e);
+    }
+}
+
+/// Run the agentic loop on the current conversation and format its events
+///
+/// `prompt` is the user message that started the turn, if any, for the
+/// transcript; it has already been added to the conversation.
+async fn run_agent_turn(
+    api_key: String,
+    prompt: Option<&str>,
+    app: &tauri::AppHandle,
+    state: &AppState,
+) -> Result<String, String> {
+    use tauri::Emitter;
+
+    // The turn's results belong to the project it started in
+    let generation = state.project_generation.load(Ordering::SeqCst);
+    let same_project = || state.project_generation.load(Ordering::SeqCst) == generation;
+
+    // Attribute this session's tool calls to it in the tool metrics
+    let turn_session = state.session.lock().unwrap().clone();
+    let session_id = turn_session.id.clone();
+    let mode = state.agent_mode.lock().unwrap().clone();
+    let executor = FilteredToolExecutor::new(Arc::clone(&state.tool_executor), Vec::new(), Vec::new())
+        .with_session(session_id.clone())
+        .with_mode(mode);
+    // Actions that need consent, such as cluster reads, ask the user through
+    // the frontend; auto-approved calls never get to ask
+    let executor = if *state.auto_approve.lock().unwrap() {
+        executor.with_auto_approve()
+    } else {
+        executor.with_consent(Arc::clone(&state.approvals) as Arc<dyn ToolApproval>)
     };
-    let executor = ToolExecutor::with_working_directory(project_root_path);
 
-    // Clone conversation for agentic loop
+    // Clone conversation for agentic loop, applying project sampling defaults
+    // underneath the conversation's own overrides, and the project budget
+    // unless the conversation has its own
+    let (project_sampling, project_budget, title_model, locale, outbound_filter) = {
+        let project_root = state.project_root.lock().unwrap().clone();
+        let config = Config::load(&project_root).unwrap_or_default();
+        let outbound_filter = project_outbound_filter(state, &project_root, &config.outbound_filter)?;
+        (
+            config.agent.sampling,
+            config.agent.budget,
+            config.agent.title_model,
+            Locale::for_messages(&config.locale),
+            outbound_filter,
+        )
+    };
+    let client = AnthropicClient::new(api_key).with_outbound_filter(outbound_filter);
     let mut conv = {
         let guard = state.conversation.lock().unwrap();
         guard.clone()
     };
+    let sampling_overrides = conv.sampling.clone();
+    conv.sampling = project_sampling.merge(&sampling_overrides);
+    let usage_before = conv.usage.clone();
+    let budget_override = conv.budget;
+    if budget_override.is_unlimited() {
+        conv.budget = project_budget;
+    }
 
-    // Run agentic loop
-    let events = client
-        .run_agentic_loop(&mut conv, &executor, None)
-        .await
-        .map_err(|e| {
-            let error_msg = format!("⚠ Error: {}", e);
-            tracing::error!("Agentic loop error: {:?}", e);
-            error_msg
-        })?;
+    // Append the turn to the session's transcript as it happens
+    let project_root_path = state.project_root.lock().unwrap().clone();
+    let mut transcript = TranscriptStore::for_project(&project_root_path)
+        .start_turn(&session_id, AnthropicClient::default_model())
+        .map_err(|e| tracing::warn!("Failed to start transcript: {}", e))
+        .ok();
+    if let (Some(transcript), Some(prompt)) = (transcript.as_mut(), prompt) {
+        note_transcript_error(transcript.user_message(prompt));
+    }
 
-    // Update conversation with the modified version
+    // Run agentic loop, keeping the partial response for crash recovery
+    *state.turn.lock().unwrap() = Some(InFlightTurn::new());
+    let style = *state.output_style.lock().unwrap();
+    let mut events = Vec::new();
+    let result = client
+        .run_agentic_loop_with(&mut conv, &executor, AnthropicClient::default_model(), None, |event| {
+            if let Some(transcript) = transcript.as_mut() {
+                note_transcript_error(transcript.agentic(event));
+            }
+            if same_project() {
+                if let Some(turn) = state.turn.lock().unwrap().as_mut() {
+                    turn.record(event);
+                }
+            }
+            // Command output and prompts are shown live; the final tool
+            // result repeats the output
+            let live_event = match event {
+                AgenticEvent::ToolOutputChunk { .. } => Some(TOOL_OUTPUT),
+                AgenticEvent::ToolAwaitingInput { .. } => Some(TOOL_AWAITING_INPUT),
+                _ => None,
+            };
+            let event = event.clone().styled(style);
+            match live_event {
+                Some(name) => {
+                    if let Err(e) = app.emit(name, &event) {
+                        tracing::error!("Failed to emit {}: {}", name, e);
+                    }
+                }
+                None => events.push(event),
+            }
+        })
+        .await;
+    if let (Err(e), Some(transcript)) = (&result, transcript.take()) {
+        note_transcript_error(transcript.finish(Some(e.to_string())));
+    }
+    if result.is_err() && same_project() {
+        *state.turn.lock().unwrap() = None;
+    }
+    // A blocked prompt stays visible but is no longer sent with the
+    // conversation, so the next message is not blocked by it too
+    if let (Err(ClientError::OutboundFilter(OutboundFilterError::Blocked { .. })), Some(_), true) =
+        (&result, prompt, same_project())
     {
-        let mut conversation_lock = state.conversation.lock().unwrap();
-        *conversation_lock = conv;
+        let mut conv = state.conversation.lock().unwrap();
+        if let Some(last) = conv.messages().len().checked_sub(1) {
+            conv.exclude_message(last, true);
+        }
     }
+    result.map_err(|e| {
+        let error_msg = style
+            .apply(&format!("⚠ {}: {}", locale.message(UiMessage::Error), e))
+            .into_owned();
+        tracing::error!("Agentic loop error: {:?}", e);
+        error_msg
+    })?;
 
     // Format events into response text
     let mut output = String::new();
     let mut final_text = String::new();
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
 
     for event in &events {
         match event {
             AgenticEvent::ToolCall { id, name, input } => {
+                tool_names.insert(id, name);
                 let tool_info = format!("\n[🔧 Tool: {} (id: {})]\n", name, id);
-                output.push_str(&tool_info);
+                output.push_str(&style.apply(&tool_info));
                 tracing::info!("Tool call: {} with input: {:?}", name, input);
             }
             AgenticEvent::ToolResult {
                 tool_use_id,
                 content,
                 is_error,
+                data,
             } => {
-                let result_prefix = if is_error == &Some(true) {
-                    "❌ Error"
-                } else {
-                    "✓ Result"
+                if let Some(diagnostics) = data
+                    .as_ref()
+                    .and_then(|data| data.get("diagnostics"))
+                    .filter(|_| same_project())
+                {
+                    if let Err(e) = app.emit(BUILD_DIAGNOSTICS, diagnostics) {
+                        tracing::error!("Failed to emit {}: {}", BUILD_DIAGNOSTICS, e);
+                    }
+                    let tool = tool_names.get(tool_use_id.as_str()).copied().unwrap_or_default();
+                    match serde_json::from_value(diagnostics.clone()) {
+                        Ok(diagnostics) => {
+                            state.diagnostics.publish(DiagnosticOrigin::for_tool(tool), None, diagnostics)
+                        }
+                        Err(e) => tracing::error!("Invalid diagnostics from {}: {}", tool, e),
+                    }
+                }
+                let result_prefix = match (is_error == &Some(true), style) {
+                    (true, OutputStyle::Rich) => "❌ Error",
+                    (true, OutputStyle::Plain) => "Error",
+                    (false, OutputStyle::Rich) => "✓ Result",
+                    (false, OutputStyle::Plain) => "Result",
                 };
                 // Truncate long tool results for display
                 let display_content = if content.len() > 200 {
@@ -319,14 +894,76 @@ async fn send_message(message: String, state: State<'_, AppState>) -> Result<Str
                     content.len()
                 );
             }
+            AgenticEvent::InjectionDetected { tool, findings, .. } => {
+                if let Err(e) = app.emit(INJECTION_DETECTED, event) {
+                    tracing::error!("Failed to emit {}: {}", INJECTION_DETECTED, e);
+                }
+                let kinds: Vec<&str> = findings.iter().map(|finding| finding.kind.as_str()).collect();
+                let notice = format!(
+                    "[⚠ Possible prompt injection removed from the {} result: {}]\n",
+                    tool,
+                    kinds.join(", ")
+                );
+                output.push_str(&style.apply(&notice));
+            }
             AgenticEvent::TextResponse { text } => {
                 final_text.push_str(text);
             }
+            AgenticEvent::BudgetExceeded { tokens, cost_usd, .. } => {
+                if let Err(e) = app.emit(BUDGET_EXCEEDED, event) {
+                    tracing::error!("Failed to emit {}: {}", BUDGET_EXCEEDED, e);
+                }
+                let notice = format!(
+                    "\n[⏸ Budget reached: {} tokens, ${:.2} spent. Continue to keep going.]\n",
+                    tokens, cost_usd
+                );
+                output.push_str(&style.apply(&notice));
+            }
+            AgenticEvent::ToolOutputChunk { .. } | AgenticEvent::ToolAwaitingInput { .. } => {}
         }
     }
 
     // Add final text response
     output.push_str(&final_text);
+    if !final_text.is_empty() {
+        conv.add_assistant_message(final_text.clone());
+    }
+
+    // Record this turn's spend for the usage dashboard
+    let mut session = if same_project() {
+        state.session.lock().unwrap().clone()
+    } else {
+        turn_session
+    };
+    let records = UsageRecord::since(&usage_before, &conv.usage, &session.id);
+    if let Err(e) = UsageStore::for_project(&project_root_path).append(&records) {
+        tracing::warn!("Failed to record usage: {}", e);
+    }
+    if let Some(mut transcript) = transcript {
+        note_transcript_error(transcript.usage(&records));
+        note_transcript_error(transcript.finish(None));
+    }
+
+    // Save the session, titling it once the first exchange is complete
+    if session.title.is_none() && sessions::ready_for_title(&conv) {
+        let title_model = title_model.as_deref().unwrap_or(AnthropicClient::default_model());
+        session.title = Some(sessions::title_or_fallback(&client, &conv, title_model).await);
+    }
+    if let Err(e) = SessionStore::for_project(&project_root_path).save(&mut session, &conv) {
+        tracing::warn!("Failed to save session: {}", e);
+    }
+
+    // Update conversation with the modified version, unless the project was
+    // switched while the turn ran; the turn is still saved in its own project
+    if same_project() {
+        *state.session.lock().unwrap() = session;
+        conv.sampling = sampling_overrides;
+        conv.budget = budget_override;
+        *state.conversation.lock().unwrap() = conv;
+        *state.turn.lock().unwrap() = None;
+    } else {
+        tracing::info!("Project switched during the turn; not restoring its conversation");
+    }
 
     tracing::info!("Agentic loop completed with {} events", events.len());
     Ok(output)
@@ -338,63 +975,402 @@ async fn clear_chat(state: State<'_, AppState>) -> Result<(), String> {
     tracing::info!("clear_chat command called");
 
     let mut conv = state.conversation.lock().unwrap();
+    let had_override = !conv.prompt_override.is_empty();
     conv.clear();
+    drop(conv);
+    if had_override {
+        let project_root = state.project_root.lock().unwrap().clone();
+        let mode = state.agent_mode.lock().unwrap().clone();
+        reload_system_prompt(&state, &project_root, &mode);
+    }
+    let old_session = std::mem::replace(&mut *state.session.lock().unwrap(), SessionMetadata::new());
+    state.tool_executor.clear_cache();
+    for session in state.tool_executor.repl_sessions() {
+        if session.conversation.as_deref() == Some(old_session.id.as_str()) {
+            state.tool_executor.close_repl_session(session.conversation.as_deref(), session.language);
+        }
+    }
+    state.tool_executor.shutdown_scratch_kernels(Some(&old_session.id));
 
     tracing::info!("Conversation cleared");
     Ok(())
 }
 
-// ============================================================================
-// TERMINAL COMMANDS
-// ============================================================================
+/// Answer a running tool command's prompt, or close its stdin when `input` is absent
+#[tauri::command]
+async fn send_tool_input(
+    tool_use_id: String,
+    input: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    tracing::info!("send_tool_input command called for {}", tool_use_id);
+    if state.tool_executor.send_input(&tool_use_id, input) {
+        Ok(())
+    } else {
+        Err(format!("Tool call {} is not waiting for input", tool_use_id))
+    }
+}
 
-/// Get list of available shells on the system
+/// Answer a tool action waiting for the user's approval
 #[tauri::command]
-async fn get_available_shells() -> Result<Vec<ShellType>, String> {
-    tracing::info!("get_available_shells command called");
-    Ok(TerminalManager::detect_available_shells())
+async fn answer_tool_approval(id: String, approved: bool, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("answer_tool_approval command called for {}: {}", id, approved);
+    if state.approvals.answer(&id, approved) {
+        Ok(())
+    } else {
+        Err(format!("No tool action {} is waiting for approval", id))
+    }
 }
 
-/// Get the default shell for the platform
+/// Cancel a running tool call; the agent sees the call fail as cancelled
 #[tauri::command]
-async fn get_default_shell() -> Result<ShellType, String> {
-    tracing::info!("get_default_shell command called");
-    Ok(TerminalManager::default_shell())
+async fn cancel_tool(tool_use_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("cancel_tool command called for {}", tool_use_id);
+    if state.tool_executor.cancel(&tool_use_id) {
+        Ok(())
+    } else {
+        Err(format!("Tool call {} is not running", tool_use_id))
+    }
 }
 
-/// Spawn a new terminal session
+/// Get tool call metrics for the current session, or for every call since startup
 #[tauri::command]
-async fn spawn_terminal(
-    shell_type: ShellType,
-    cols: u16,
-    rows: u16,
+async fn get_tool_metrics(
+    current_session: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<TerminalId, String> {
-    tracing::info!(
-        "spawn_terminal command called: {:?} ({}x{})",
-        shell_type,
-        cols,
-        rows
-    );
+) -> Result<ToolMetricsReport, String> {
+    let session = current_session
+        .unwrap_or(false)
+        .then(|| state.session.lock().unwrap().id.clone());
+    Ok(state.tool_executor.metrics().report(session.as_deref()))
+}
 
-    // Use project root as working directory
-    let project_root = state.project_root.lock().unwrap();
-    let working_dir = project_root.to_string_lossy().to_string();
+/// REPL sessions the agent has open
+#[tauri::command]
+async fn list_repl_sessions(state: State<'_, AppState>) -> Result<Vec<ReplSessionInfo>, String> {
+    Ok(state.tool_executor.repl_sessions())
+}
 
+/// Evaluations so far in the current conversation's REPL session for `language`
+#[tauri::command]
+async fn get_repl_transcript(language: String, state: State<'_, AppState>) -> Result<Vec<ReplEntry>, String> {
+    let language: ReplLanguage = language.parse()?;
+    let session_id = state.session.lock().unwrap().id.clone();
     state
-        .terminal_manager
-        .spawn_terminal(shell_type, cols, rows, Some(working_dir))
-        .map_err(|e| {
-            let error_msg = format!("Failed to spawn terminal: {}", e);
-            tracing::error!("{}", error_msg);
-            error_msg
-        })
+        .tool_executor
+        .repl_transcript(Some(&session_id), language)
+        .ok_or_else(|| format!("No {} session is open", language.program()))
 }
 
-/// Write data to terminal
+/// Stop the current conversation's REPL session for `language`
 #[tauri::command]
-async fn write_terminal(
-    id: TerminalId,
+async fn close_repl_session(language: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("close_repl_session command called for {}", language);
+    let language: ReplLanguage = language.parse()?;
+    let session_id = state.session.lock().unwrap().id.clone();
+    if state.tool_executor.close_repl_session(Some(&session_id), language) {
+        Ok(())
+    } else {
+        Err(format!("No {} session is open", language.program()))
+    }
+}
+
+/// Cells of a notebook, for the notebook panel
+#[tauri::command]
+async fn get_notebook_cells(path: String, state: State<'_, AppState>) -> Result<Vec<NotebookCell>, String> {
+    let absolute = state.tool_executor.resolve(&path);
+    let contents = tokio::fs::read_to_string(&absolute)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let notebook = Notebook::parse(&contents).map_err(|e| e.to_string())?;
+    Ok(notebook.cells())
+}
+
+/// Run a notebook cell in the notebook's kernel and save its outputs,
+/// emitting each output as it arrives
+#[tauri::command]
+async fn execute_notebook_cell(
+    path: String,
+    cell: usize,
+    timeout_secs: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CellExecution, String> {
+    tracing::info!("execute_notebook_cell command called for cell {} of {}", cell, path);
+    let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(600));
+    state
+        .tool_executor
+        .run_notebook_cell(&path, cell, timeout, true, |output| {
+            let event = NotebookOutputEvent {
+                path: path.clone(),
+                cell,
+                output: output.clone(),
+            };
+            if let Err(e) = app.emit(NOTEBOOK_OUTPUT, event) {
+                tracing::error!("Failed to emit {}: {}", NOTEBOOK_OUTPUT, e);
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Jupyter kernels running for notebooks and the agent's scratch cells
+#[tauri::command]
+async fn list_kernels(state: State<'_, AppState>) -> Result<Vec<KernelInfo>, String> {
+    Ok(state.tool_executor.kernels().list())
+}
+
+/// Interrupt the cell a notebook's kernel is running
+#[tauri::command]
+async fn interrupt_kernel(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("interrupt_kernel command called for {}", path);
+    let id = state.tool_executor.resolve(&path).to_string_lossy().into_owned();
+    state.tool_executor.kernels().interrupt(&id).await.map_err(|e| e.to_string())
+}
+
+/// Shut a notebook's kernel down, losing its state
+#[tauri::command]
+async fn shutdown_kernel(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("shutdown_kernel command called for {}", path);
+    let id = state.tool_executor.resolve(&path).to_string_lossy().into_owned();
+    if state.tool_executor.kernels().shutdown(&id) {
+        Ok(())
+    } else {
+        Err(format!("No kernel is running for {}", path))
+    }
+}
+
+/// External programs the agent's tools run, and whether each is installed
+#[tauri::command]
+async fn get_external_tools(state: State<'_, AppState>) -> Result<Vec<aurora_core::ProgramStatus>, String> {
+    Ok(state.tool_executor.external_tools().statuses())
+}
+
+/// Look for the external programs again, e.g. after installing one
+#[tauri::command]
+async fn refresh_external_tools(state: State<'_, AppState>) -> Result<Vec<aurora_core::ProgramStatus>, String> {
+    tracing::info!("refresh_external_tools command called");
+    let executor = Arc::clone(&state.tool_executor);
+    tokio::task::spawn_blocking(move || {
+        executor.refresh_external_tools();
+        executor.external_tools().statuses()
+    })
+    .await
+    .map_err(|e| format!("Tool discovery failed: {}", e))
+}
+
+/// Attach files and folders as context for the next message
+#[tauri::command]
+async fn attach_context(paths: Vec<String>, state: State<'_, AppState>) -> Result<Vec<Attachment>, String> {
+    tracing::info!("attach_context command called: {:?}", paths);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let attachments = attachments::load_attachments(&project_root, &paths, &AttachmentLimits::default())
+        .map_err(|e| {
+            let error_msg = format!("Failed to attach context: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+
+    state.conversation.lock().unwrap().attach(attachments.clone());
+    Ok(attachments)
+}
+
+/// Update the open editors and selection visible to the agent
+#[tauri::command]
+async fn update_editor_state(editor_state: EditorState, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::debug!(
+        "update_editor_state command called: {} open editors",
+        editor_state.open_editors.len()
+    );
+    let mut shared = state.editor_state.write().map_err(|_| {
+        let error_msg = "Editor state lock poisoned".to_string();
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+    *shared = editor_state;
+    Ok(())
+}
+
+/// Report an open editor buffer and its unsaved content
+///
+/// `content` is the buffer text when it has unsaved changes, or `None` once it
+/// matches the file on disk.
+#[tauri::command]
+async fn update_buffer(path: String, content: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::debug!("update_buffer command called for: {} (dirty: {})", path, content.is_some());
+    state.buffers.update(Path::new(&path), content);
+    Ok(())
+}
+
+/// Forget an editor buffer when its tab closes
+#[tauri::command]
+async fn close_buffer(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::debug!("close_buffer command called for: {}", path);
+    state.buffers.close(Path::new(&path));
+    Ok(())
+}
+
+/// Replace the diagnostics one origin reports, e.g. a language server's
+/// diagnostics for `file` or a linter's findings for the whole project
+#[tauri::command]
+async fn publish_diagnostics(
+    origin: DiagnosticOrigin,
+    file: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    tracing::debug!(
+        "publish_diagnostics command called: {} from {:?}",
+        diagnostics.len(),
+        origin
+    );
+    state.diagnostics.publish(origin, file.as_deref(), diagnostics);
+    Ok(())
+}
+
+/// Apply one of a diagnostic's quick fixes
+///
+/// Edits go through the editor buffers like agent edits, so open tabs are
+/// notified of the change.
+#[tauri::command]
+async fn apply_quick_fix(diagnostic_id: String, fix: Option<usize>, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("apply_quick_fix command called: {}", diagnostic_id);
+    state
+        .tool_executor
+        .apply_quick_fix(&diagnostic_id, fix.unwrap_or(0))
+        .await
+        .map_err(|e| {
+            let error_msg = format!("Failed to apply quick fix: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Apply the changes of a multi_replace preview the user kept selected
+///
+/// `files` is the preview's `files` data with deselected files and lines
+/// removed. Edits go through the editor buffers like agent edits; files
+/// changed since the preview are left alone and reported.
+#[tauri::command]
+async fn apply_selected_changes(files: Vec<FileChanges>, state: State<'_, AppState>) -> Result<AppliedChanges, String> {
+    tracing::info!("apply_selected_changes command called for {} files", files.len());
+    state.tool_executor.apply_selected_changes(&files).await.map_err(|e| {
+        let error_msg = format!("Failed to apply changes: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Who owns a file: its CODEOWNERS entry and frequent recent committers
+#[tauri::command]
+async fn get_file_owners(path: String, state: State<'_, AppState>) -> Result<FileOwners, String> {
+    tracing::info!("get_file_owners command called: {}", path);
+    let project_root = state.project_root.lock().unwrap().clone();
+    tokio::task::spawn_blocking(move || file_owners(&project_root, Path::new(&path), true))
+        .await
+        .map_err(|e| format!("Failed to get file owners: {}", e))?
+        .map_err(|e| {
+            let error_msg = format!("Failed to get file owners: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Query the merged problem list, most severe first
+#[tauri::command]
+async fn get_diagnostics(
+    filter: Option<DiagnosticFilter>,
+    origin: Option<DiagnosticOrigin>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DiagnosticEntry>, String> {
+    tracing::info!("get_diagnostics command called");
+    Ok(state.diagnostics.query(&filter.unwrap_or_default(), origin))
+}
+
+/// Update a message flag and persist the session
+fn update_message_flag(
+    state: &State<'_, AppState>,
+    index: usize,
+    update: impl FnOnce(&mut Conversation, usize) -> bool,
+) -> Result<(), String> {
+    let mut conv = state.conversation.lock().unwrap();
+    if !update(&mut conv, index) {
+        return Err(format!("No message at index {}", index));
+    }
+
+    let project_root = state.project_root.lock().unwrap().clone();
+    let mut session = state.session.lock().unwrap();
+    if let Err(e) = SessionStore::for_project(&project_root).save(&mut session, &conv) {
+        tracing::warn!("Failed to save session: {}", e);
+    }
+    Ok(())
+}
+
+/// Pin a message so it is always kept when the conversation is truncated
+#[tauri::command]
+async fn pin_message(index: usize, pinned: bool, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("pin_message command called: {} (pinned={})", index, pinned);
+    update_message_flag(&state, index, |conv, i| conv.pin_message(i, pinned))
+}
+
+/// Exclude a message from the context sent to the API (it stays displayed)
+#[tauri::command]
+async fn exclude_message(index: usize, excluded: bool, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("exclude_message command called: {} (excluded={})", index, excluded);
+    update_message_flag(&state, index, |conv, i| conv.exclude_message(i, excluded))
+}
+
+// ============================================================================
+// TERMINAL COMMANDS
+// ============================================================================
+
+/// Get list of available shells on the system
+#[tauri::command]
+async fn get_available_shells() -> Result<Vec<ShellType>, String> {
+    tracing::info!("get_available_shells command called");
+    Ok(TerminalManager::detect_available_shells())
+}
+
+/// Get the default shell for the platform
+#[tauri::command]
+async fn get_default_shell() -> Result<ShellType, String> {
+    tracing::info!("get_default_shell command called");
+    Ok(TerminalManager::default_shell())
+}
+
+/// Spawn a new terminal session
+#[tauri::command]
+async fn spawn_terminal(
+    shell_type: ShellType,
+    cols: u16,
+    rows: u16,
+    state: State<'_, AppState>,
+) -> Result<TerminalId, String> {
+    tracing::info!(
+        "spawn_terminal command called: {:?} ({}x{})",
+        shell_type,
+        cols,
+        rows
+    );
+
+    // Use project root as working directory
+    let project_root = state.project_root.lock().unwrap();
+    let working_dir = project_root.to_string_lossy().to_string();
+
+    state
+        .terminal_manager
+        .spawn_terminal(shell_type, cols, rows, Some(working_dir))
+        .map_err(|e| {
+            let error_msg = format!("Failed to spawn terminal: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Write data to terminal
+#[tauri::command]
+async fn write_terminal(
+    id: TerminalId,
     data: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
@@ -446,6 +1422,17 @@ async fn list_terminals(state: State<'_, AppState>) -> Result<Vec<TerminalId>, S
     Ok(state.terminal_manager.list_terminals())
 }
 
+/// How often terminals are checked for being idle
+const TERMINAL_IDLE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Shut down the shells of idle terminals until they are used again
+fn spawn_idle_terminal_monitor(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TERMINAL_IDLE_CHECK_INTERVAL);
+        app_handle.state::<AppState>().terminal_manager.suspend_idle();
+    });
+}
+
 // ============================================================================
 // GIT COMMANDS
 // ============================================================================
@@ -611,6 +1598,35 @@ async fn git_commit(message: String, amend: bool, state: State<'_, AppState>) ->
     tracing::info!("git_commit command called: amend={}", amend);
     let project_root = state.project_root.lock().unwrap().clone();
 
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+
+    // Enforce commit message conventions
+    let lint = lint_commit_message(&message, &config.git).map_err(|e| e.to_string())?;
+    if !lint.valid {
+        let error_msg = format!("Commit message does not follow project conventions:\n{}", lint.format());
+        tracing::error!("{}", error_msg);
+        return Err(error_msg);
+    }
+
+    // Run the pre-commit quality gate (includes the secret scan by default)
+    if config.quality_gate.run_on_commit {
+        let gate = QualityGate::new(&project_root, config.quality_gate)
+            .map_err(|e| e.to_string())?
+            .staged_only(true)
+            .shell(Shell::from_config(&config.shell))
+            .environment(state.tool_executor.shell_environment().map(|environment| (*environment).clone()));
+        let report = tokio::task::spawn_blocking(move || gate.run()).await.map_err(|e| {
+            let error_msg = format!("Quality gate task failed: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+        if !report.passed {
+            let error_msg = format!("Commit blocked by quality gate:\n{}", report.format());
+            tracing::error!("{}", error_msg);
+            return Err(error_msg);
+        }
+    }
+
     let mut args = vec!["commit", "-m", &message];
     if amend {
         args.push("--amend");
@@ -631,6 +1647,48 @@ async fn git_commit(message: String, amend: bool, state: State<'_, AppState>) ->
     Ok(result)
 }
 
+/// Check a commit message against the project's conventions without committing
+#[tauri::command]
+async fn check_commit_message(message: String, state: State<'_, AppState>) -> Result<ConventionReport, String> {
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    lint_commit_message(&message, &config.git).map_err(|e| e.to_string())
+}
+
+/// Create a new git branch, validating its name against the project's conventions
+#[tauri::command]
+async fn git_create_branch(name: String, checkout: bool, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("git_create_branch command called: {} (checkout={})", name, checkout);
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    let report = validate_branch_name(&name, &config.git).map_err(|e| e.to_string())?;
+    if !report.valid {
+        let error_msg = format!("Invalid branch name '{}':\n{}", name, report.format());
+        tracing::error!("{}", error_msg);
+        return Err(error_msg);
+    }
+
+    let args = if checkout {
+        vec!["checkout", "-b", &name]
+    } else {
+        vec!["branch", &name]
+    };
+
+    let output = std::process::Command::new("git")
+        .args(&args)
+        .current_dir(project_root)
+        .output()
+        .map_err(|e| format!("Failed to create branch: {}", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Branch creation failed: {}", error));
+    }
+
+    Ok(format!("Created branch {}", name))
+}
+
 /// Push commits to remote
 #[tauri::command]
 async fn git_push(remote: Option<String>, branch: Option<String>, state: State<'_, AppState>) -> Result<String, String> {
@@ -681,60 +1739,1558 @@ async fn git_pull(state: State<'_, AppState>) -> Result<String, String> {
     Ok(result)
 }
 
-/// Get current project root path
-#[tauri::command]
-async fn get_project_root(state: State<'_, AppState>) -> Result<String, String> {
-    let project_root = state.project_root.lock().unwrap();
-    Ok(project_root.to_string_lossy().to_string())
-}
-
-/// Open folder picker and set new project root
+/// Run the pre-commit quality gate pipeline
 #[tauri::command]
-async fn open_folder(
-    app: tauri::AppHandle,
+async fn run_quality_gate(
+    staged_only: bool,
+    steps: Option<Vec<String>>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    use tauri::Emitter;
-    use tauri_plugin_dialog::DialogExt;
-
-    tracing::info!("open_folder command called");
-
-    // Show folder picker dialog
-    let folder_path = app
-        .dialog()
-        .file()
-        .set_title("Select Project Folder")
-        .blocking_pick_folder();
+) -> Result<QualityGateReport, String> {
+    tracing::info!("run_quality_gate command called: staged_only={}", staged_only);
+    let project_root = state.project_root.lock().unwrap().clone();
 
-    if let Some(folder) = folder_path {
-        let path = folder.into_path().map_err(|e| format!("Failed to convert path: {}", e))?;
-        tracing::info!("Selected folder: {:?}", path);
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    let mut gate = QualityGate::new(&project_root, config.quality_gate)
+        .map_err(|e| e.to_string())?
+        .staged_only(staged_only)
+        .shell(Shell::from_config(&config.shell))
+        .environment(state.tool_executor.shell_environment().map(|environment| (*environment).clone()));
+    if let Some(steps) = steps {
+        gate = gate.only_steps(&steps).map_err(|e| e.to_string())?;
+    }
 
-        // Update project root
-        {
-            let mut project_root = state.project_root.lock().unwrap();
-            *project_root = path.clone();
-        }
+    tokio::task::spawn_blocking(move || gate.run())
+        .await
+        .map_err(|e| {
+            let error_msg = format!("Quality gate task failed: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
 
-        // Emit event to refresh frontend
-        app.emit("project-folder-changed", path.to_string_lossy().to_string())
-            .map_err(|e| format!("Failed to emit event: {}", e))?;
+/// Scan the project for committed secrets
+#[tauri::command]
+async fn scan_secrets(staged_only: bool, state: State<'_, AppState>) -> Result<Vec<SecretFinding>, String> {
+    tracing::info!("scan_secrets command called: staged_only={}", staged_only);
+    let project_root = state.project_root.lock().unwrap().clone();
 
-        Ok(path.to_string_lossy().to_string())
+    let scanner = SecretScanner::for_project(&project_root).map_err(|e| e.to_string())?;
+    let findings = if staged_only {
+        scanner.scan_staged(&project_root)
     } else {
-        Err("No folder selected".to_string())
-    }
+        scanner.scan_tracked(&project_root)
+    };
+
+    findings.map_err(|e| {
+        let error_msg = format!("Secret scan failed: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
 }
 
-// ============================================================================
-// MAIN
-// ============================================================================
+/// Check the project's Markdown for broken links and heading problems
+///
+/// Checks `paths` (relative to the project root) when given, otherwise
+/// `docs/` and the root's Markdown files.
+#[tauri::command]
+async fn check_markdown(
+    paths: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<aurora_core::MarkdownIssue>, String> {
+    tracing::info!("check_markdown command called: {:?}", paths);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let paths: Vec<PathBuf> = paths.unwrap_or_default().into_iter().map(PathBuf::from).collect();
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    init_tracing();
-    tracing::info!("Starting AuroraHeart IDE (Tauri)");
+    tokio::task::spawn_blocking(move || {
+        let files = aurora_core::markdown_files(&project_root, &paths)?;
+        Ok(aurora_core::check_markdown(&project_root, &files))
+    })
+    .await
+    .map_err(|e| format!("Markdown check task failed: {}", e))?
+    .map_err(|e: aurora_core::MarkdownError| {
+        let error_msg = format!("Markdown check failed: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Generate a software bill of materials for the project
+///
+/// Writes the document to `output_path` when given and returns it either way.
+#[tauri::command]
+async fn generate_sbom(
+    format: Option<SbomFormat>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let format = format.unwrap_or_default();
+    tracing::info!("generate_sbom command called: {:?}", format);
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let document = tokio::task::spawn_blocking(move || aurora_core::generate_sbom(&project_root, format))
+        .await
+        .map_err(|e| format!("SBOM task failed: {}", e))?
+        .map_err(|e| {
+            let error_msg = format!("Failed to generate SBOM: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+    if let Some(output_path) = output_path {
+        std::fs::write(&output_path, &document).map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+    }
+    Ok(document)
+}
+
+/// Add secret finding fingerprints to the baseline as known false positives
+#[tauri::command]
+async fn add_secrets_to_baseline(fingerprints: Vec<String>, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("add_secrets_to_baseline command called: {} fingerprint(s)", fingerprints.len());
+    let project_root = state.project_root.lock().unwrap().clone();
+    let baseline_path = SecretBaseline::default_path(&project_root);
+
+    let mut baseline = SecretBaseline::load(&baseline_path).map_err(|e| e.to_string())?;
+    baseline.fingerprints.extend(fingerprints);
+    baseline.save(&baseline_path).map_err(|e| {
+        let error_msg = format!("Failed to save secrets baseline: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Transcribe audio recorded for voice input with the project's `[voice]`
+/// backend; the frontend puts the text in the message box
+///
+/// `audio` is the recording, base64-encoded, and `mime_type` its type as the
+/// recorder reports it (e.g. `audio/webm;codecs=opus`).
+#[tauri::command]
+async fn transcribe_audio(audio: String, mime_type: String, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("transcribe_audio command called: {} ({} base64 bytes)", mime_type, audio.len());
+    let project_root = state.project_root.lock().unwrap().clone();
+    let audio = BASE64
+        .decode(audio.trim())
+        .map_err(|e| format!("Invalid audio data: {}", e))?;
+
+    let transcriber = Transcriber::for_project(&project_root).map_err(|e| e.to_string())?;
+    transcriber.transcribe(&audio, &mime_type).await.map_err(|e| {
+        let error_msg = format!("Failed to transcribe audio: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Store an access token for an external integration (CI providers, issue trackers)
+#[tauri::command]
+async fn save_integration_token(key: String, token: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("save_integration_token command called: {}", key);
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let allowed = [
+        ci::GITHUB_TOKEN_KEY,
+        ci::GITLAB_TOKEN_KEY,
+        issues::JIRA_TOKEN_KEY,
+        issues::LINEAR_TOKEN_KEY,
+        voice::STT_API_KEY,
+    ];
+    if !allowed.contains(&key.as_str()) {
+        return Err(format!("Unknown integration token: {}", key));
+    }
+
+    let credential_store = CredentialStore::for_project(&project_root);
+    credential_store
+        .store(&key, &token, "auroraheart")
+        .map_err(|e| {
+            let error_msg = format!("Failed to save token: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Resolve the branch to inspect for CI commands
+fn ci_branch(project_root: &Path, branch: Option<String>) -> Result<String, String> {
+    match branch {
+        Some(branch) => Ok(branch),
+        None => ci::current_branch(project_root).map_err(|e| e.to_string()),
+    }
+}
+
+/// Get CI status for a branch (defaults to the current branch)
+#[tauri::command]
+async fn get_ci_status(branch: Option<String>, state: State<'_, AppState>) -> Result<CiStatus, String> {
+    tracing::info!("get_ci_status command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let branch = ci_branch(&project_root, branch)?;
+
+    let client = CiClient::for_project(&project_root).map_err(|e| e.to_string())?;
+    client.get_status(&branch).await.map_err(|e| {
+        let error_msg = format!("Failed to get CI status: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Get logs of failed CI jobs (defaults to the latest failed run on the current branch)
+#[tauri::command]
+async fn get_failed_job_logs(
+    run_id: Option<u64>,
+    branch: Option<String>,
+    max_lines: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<JobLog>, String> {
+    tracing::info!("get_failed_job_logs command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let branch = ci_branch(&project_root, branch)?;
+
+    let client = CiClient::for_project(&project_root).map_err(|e| e.to_string())?;
+    client
+        .get_failed_job_logs(&branch, run_id, max_lines.unwrap_or(ci::DEFAULT_LOG_LINES))
+        .await
+        .map_err(|e| {
+            let error_msg = format!("Failed to get job logs: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Fetch an issue from the configured issue tracker
+#[tauri::command]
+async fn get_issue(id: String, state: State<'_, AppState>) -> Result<Issue, String> {
+    tracing::info!("get_issue command called: {}", id);
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let client = IssueClient::for_project(&project_root, &id, None).map_err(|e| e.to_string())?;
+    client.get_issue(&id).await.map_err(|e| {
+        let error_msg = format!("Failed to fetch issue {}: {}", id, e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Sampling parameters at each level, as shown in Settings
+#[derive(Debug, Serialize)]
+pub struct SamplingSettings {
+    /// Project defaults from `[agent.sampling]`
+    pub project: SamplingParams,
+    /// Overrides for the current conversation
+    pub conversation: SamplingParams,
+    /// What the next request will use
+    pub effective: SamplingParams,
+}
+
+/// Get the project and conversation sampling parameters
+#[tauri::command]
+async fn get_sampling_settings(state: State<'_, AppState>) -> Result<SamplingSettings, String> {
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    let conversation = state.conversation.lock().unwrap().sampling.clone();
+
+    Ok(SamplingSettings {
+        effective: config.agent.sampling.merge(&conversation),
+        project: config.agent.sampling,
+        conversation,
+    })
+}
+
+/// Save the project's default sampling parameters to the config file
+#[tauri::command]
+async fn save_sampling_settings(params: SamplingParams, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("save_sampling_settings command called: {:?}", params);
+    params.validate(&ANTHROPIC_LIMITS).map_err(|e| e.to_string())?;
+
+    let project_root = state.project_root.lock().unwrap().clone();
+    let mut config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    config.agent.sampling = params;
+    config.save(&project_root).map_err(|e| {
+        let error_msg = format!("Failed to save config: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Override sampling parameters for the current conversation
+#[tauri::command]
+async fn set_conversation_sampling(params: SamplingParams, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("set_conversation_sampling command called: {:?}", params);
+    params.validate(&ANTHROPIC_LIMITS).map_err(|e| e.to_string())?;
+    state.conversation.lock().unwrap().sampling = params;
+    Ok(())
+}
+
+/// A persona offered for conversations
+#[derive(Debug, Serialize)]
+pub struct PersonaInfo {
+    pub persona: Persona,
+    pub title: &'static str,
+    /// What the persona adds to the system prompt
+    pub instructions: &'static str,
+}
+
+/// The personas a conversation can take on
+#[tauri::command]
+async fn list_personas() -> Result<Vec<PersonaInfo>, String> {
+    Ok(Persona::ALL
+        .into_iter()
+        .map(|persona| PersonaInfo {
+            persona,
+            title: persona.title(),
+            instructions: persona.instructions(),
+        })
+        .collect())
+}
+
+/// The current conversation's persona and custom system prompt
+#[tauri::command]
+async fn get_conversation_prompt(state: State<'_, AppState>) -> Result<PromptOverride, String> {
+    Ok(state.conversation.lock().unwrap().prompt_override.clone())
+}
+
+/// Set the current conversation's persona and custom system prompt
+///
+/// Both are layered on the project directives rather than replacing them;
+/// `None` for both goes back to the directives alone. Returns the prompt
+/// the model now receives.
+#[tauri::command]
+async fn set_conversation_prompt(
+    persona: Option<Persona>,
+    custom_prompt: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SystemPrompt, String> {
+    tracing::info!("set_conversation_prompt command called: {:?}", persona);
+    let custom_prompt = custom_prompt.filter(|prompt| !prompt.trim().is_empty());
+    state.conversation.lock().unwrap().prompt_override = PromptOverride { persona, custom_prompt };
+
+    let project_root = state.project_root.lock().unwrap().clone();
+    let mode = state.agent_mode.lock().unwrap().clone();
+    Ok(reload_system_prompt(&state, &project_root, &mode))
+}
+
+/// Spend of the current conversation and the budget it runs under
+#[derive(Debug, Serialize)]
+pub struct BudgetStatus {
+    /// Tokens and cost spent so far
+    pub usage: ConversationUsage,
+    /// Limit set for this conversation, if any
+    pub conversation: BudgetConfig,
+    /// Project default from `[agent.budget]`
+    pub project: BudgetConfig,
+    /// Whether the agent is paused until the user continues
+    pub exceeded: bool,
+}
+
+/// Get the current conversation's spend and budget
+#[tauri::command]
+async fn get_budget_status(state: State<'_, AppState>) -> Result<BudgetStatus, String> {
+    let project_root = state.project_root.lock().unwrap().clone();
+    let project = Config::load(&project_root)
+        .map_err(|e| format!("Failed to load config: {}", e))?
+        .agent
+        .budget;
+    let conv = state.conversation.lock().unwrap();
+    let effective = if conv.budget.is_unlimited() { project } else { conv.budget };
+
+    Ok(BudgetStatus {
+        usage: conv.usage.clone(),
+        conversation: conv.budget,
+        project,
+        exceeded: conv.usage.exceeds(&effective),
+    })
+}
+
+/// Set a budget for the current conversation, replacing the project default
+#[tauri::command]
+async fn set_conversation_budget(budget: BudgetConfig, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("set_conversation_budget command called: {:?}", budget);
+    state.conversation.lock().unwrap().budget = budget;
+    Ok(())
+}
+
+/// Outbound filter audit entries, newest first, at most `limit` of them
+#[tauri::command]
+async fn get_outbound_audit_log(limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<FilterAuditEntry>, String> {
+    tracing::info!("get_outbound_audit_log command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let mut entries = FilterAuditLog::for_project(&project_root).entries().map_err(|e| {
+        let error_msg = format!("Failed to read outbound audit log: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(usize::MAX));
+    Ok(entries)
+}
+
+/// Aggregate recorded token usage and cost by day, model, and conversation
+#[tauri::command]
+async fn get_usage_report(range: UsageRange, state: State<'_, AppState>) -> Result<UsageReport, String> {
+    tracing::info!("get_usage_report command called: {:?}", range);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let mut report = UsageStore::for_project(&project_root).report(range).map_err(|e| {
+        let error_msg = format!("Failed to read usage: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+
+    // Label conversations with their session titles
+    match SessionStore::for_project(&project_root).list() {
+        Ok(sessions) => {
+            let titles: HashMap<String, Option<String>> =
+                sessions.into_iter().map(|session| (session.id, session.title)).collect();
+            for conversation in &mut report.conversations {
+                conversation.title = titles.get(&conversation.conversation).cloned().flatten();
+            }
+        }
+        Err(e) => tracing::warn!("Failed to list sessions for usage report: {}", e),
+    }
+    Ok(report)
+}
+
+/// List saved chat sessions, newest first
+#[tauri::command]
+async fn list_sessions(state: State<'_, AppState>) -> Result<Vec<SessionMetadata>, String> {
+    let project_root = state.project_root.lock().unwrap().clone();
+    SessionStore::for_project(&project_root).list().map_err(|e| {
+        let error_msg = format!("Failed to list sessions: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Search session titles and message content across saved sessions
+#[tauri::command]
+async fn search_sessions(query: String, state: State<'_, AppState>) -> Result<Vec<SessionSearchResult>, String> {
+    tracing::info!("search_sessions command called: {}", query);
+    let project_root = state.project_root.lock().unwrap().clone();
+    SessionStore::for_project(&project_root).search(&query).map_err(|e| {
+        let error_msg = format!("Failed to search sessions: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Load a saved session into the current conversation
+#[tauri::command]
+async fn load_session(id: String, state: State<'_, AppState>) -> Result<SavedSession, String> {
+    tracing::info!("load_session command called: {}", id);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let saved = SessionStore::for_project(&project_root).load(&id).map_err(|e| {
+        let error_msg = format!("Failed to load session: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+
+    {
+        let mut conversation = state.conversation.lock().unwrap();
+        conversation.messages = saved.messages.clone();
+        conversation.attachments = saved.attachments.clone();
+        conversation.usage = saved.usage.clone();
+        conversation.budget = saved.budget;
+        conversation.prompt_override = saved.prompt_override.clone();
+        conversation.pending_attachments.clear();
+    }
+    let mode = state.agent_mode.lock().unwrap().clone();
+    reload_system_prompt(&state, &project_root, &mode);
+    *state.session.lock().unwrap() = saved.metadata.clone();
+    state.tool_executor.clear_cache();
+    Ok(saved)
+}
+
+/// Event emitted for each file while a streamed project search runs
+const PROJECT_SEARCH_RESULT: &str = "project-search-result";
+
+/// Compile a project search from command arguments
+fn project_search(
+    project_root: PathBuf,
+    query: String,
+    regex: bool,
+    case_sensitive: bool,
+    options: ReplaceOptions,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+) -> Result<ProjectSearch, String> {
+    let mut search_query = SearchQuery::new(query);
+    search_query.regex = regex;
+    search_query.case_sensitive = case_sensitive;
+    search_query.match_whole_word = options.match_whole_word;
+    search_query.preserve_case = options.preserve_case;
+    search_query.include = include.unwrap_or_default();
+    search_query.exclude = exclude.unwrap_or_default();
+
+    ProjectSearch::new(project_root, search_query).map_err(|e| {
+        let error_msg = format!("Invalid search: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Search file contents across the project
+///
+/// With `stream` set, each file's matches are emitted as a
+/// `project-search-result` event as soon as it is scanned and the returned
+/// summary carries only the totals.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn search_in_project(
+    query: String,
+    regex: bool,
+    case_sensitive: bool,
+    match_whole_word: Option<bool>,
+    preserve_case: Option<bool>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    stream: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SearchSummary, String> {
+    use tauri::Emitter;
+
+    tracing::info!("search_in_project command called: {:?} (regex: {})", query, regex);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let options = ReplaceOptions {
+        match_whole_word: match_whole_word.unwrap_or(false),
+        preserve_case: preserve_case.unwrap_or(false),
+    };
+    let search = project_search(project_root, query, regex, case_sensitive, options, include, exclude)?;
+
+    let result = tokio::task::spawn_blocking(move || {
+        if stream.unwrap_or(false) {
+            search.run_streaming(|file| {
+                if let Err(e) = app.emit(PROJECT_SEARCH_RESULT, file) {
+                    tracing::error!("Failed to emit {}: {}", PROJECT_SEARCH_RESULT, e);
+                }
+            })
+        } else {
+            search.run()
+        }
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?;
+
+    result.map_err(|e| {
+        let error_msg = format!("Search failed: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Replace every match of a search across the project
+///
+/// With `preserve_case`, an identifier query also replaces its other
+/// spellings, each in its own style. Open editors are notified through
+/// `file-externally-modified` events.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn replace_in_project(
+    query: String,
+    replacement: String,
+    regex: bool,
+    case_sensitive: bool,
+    match_whole_word: Option<bool>,
+    preserve_case: Option<bool>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<ReplaceSummary, String> {
+    tracing::info!("replace_in_project command called: {:?} -> {:?}", query, replacement);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let options = ReplaceOptions {
+        match_whole_word: match_whole_word.unwrap_or(false),
+        preserve_case: preserve_case.unwrap_or(false),
+    };
+    let search = project_search(project_root.clone(), query, regex, case_sensitive, options, include, exclude)?;
+
+    let summary = tokio::task::spawn_blocking(move || search.replace(&replacement))
+        .await
+        .map_err(|e| format!("Replace task failed: {}", e))?
+        .map_err(|e| {
+            let error_msg = format!("Replace failed: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+
+    for (path, _) in &summary.files {
+        state.buffers.file_written(&project_root.join(path));
+    }
+    tracing::info!(
+        "Replaced {} matches in {} files",
+        summary.total_replacements,
+        summary.files.len()
+    );
+    Ok(summary)
+}
+
+/// Load, modify, and save the project's workspace state
+fn update_workspace_state(
+    state: &State<'_, AppState>,
+    update: impl FnOnce(&WorkspaceStore, &mut WorkspaceState),
+) -> Result<WorkspaceState, String> {
+    let project_root = state.project_root.lock().unwrap().clone();
+    let store = WorkspaceStore::for_project(&project_root);
+    store.update(|workspace| update(&store, workspace)).map_err(|e| {
+        let error_msg = format!("Failed to save workspace state: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Restore recent files, cursor positions, bookmarks, and open tabs
+#[tauri::command]
+async fn get_workspace_state(state: State<'_, AppState>) -> Result<WorkspaceState, String> {
+    tracing::info!("get_workspace_state command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    WorkspaceStore::for_project(&project_root).restore().map_err(|e| {
+        let error_msg = format!("Failed to load workspace state: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Record a file as recently opened
+#[tauri::command]
+async fn record_recent_file(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::debug!("record_recent_file command called for: {}", path);
+    update_workspace_state(&state, |store, workspace| {
+        workspace.record_recent_file(&store.stored_path(&path))
+    })?;
+    Ok(())
+}
+
+/// Remember the cursor position in a file
+#[tauri::command]
+async fn save_cursor_position(
+    path: String,
+    line: usize,
+    column: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    tracing::debug!("save_cursor_position command called for: {}:{}:{}", path, line, column);
+    update_workspace_state(&state, |store, workspace| {
+        workspace.set_cursor(&store.stored_path(&path), CursorPosition { line, column })
+    })?;
+    Ok(())
+}
+
+/// Add or remove a bookmark; returns true if it was added
+#[tauri::command]
+async fn toggle_bookmark(
+    path: String,
+    line: usize,
+    label: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    tracing::info!("toggle_bookmark command called for: {}:{}", path, line);
+    let mut added = false;
+    update_workspace_state(&state, |store, workspace| {
+        added = workspace.toggle_bookmark(&store.stored_path(&path), line, label)
+    })?;
+    Ok(added)
+}
+
+/// Remember the open tabs and the focused tab
+#[tauri::command]
+async fn save_open_tabs(
+    tabs: Vec<String>,
+    active_tab: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    tracing::debug!("save_open_tabs command called: {} tabs", tabs.len());
+    update_workspace_state(&state, |store, workspace| {
+        let tabs = tabs.iter().map(|t| store.stored_path(t)).collect();
+        workspace.set_open_tabs(tabs, active_tab.map(|t| store.stored_path(&t)))
+    })?;
+    Ok(())
+}
+
+/// Get current project root path
+#[tauri::command]
+async fn get_project_root(state: State<'_, AppState>) -> Result<String, String> {
+    let project_root = state.project_root.lock().unwrap();
+    Ok(project_root.to_string_lossy().to_string())
+}
+
+/// Load, modify, and save the user's recent projects list
+fn update_recent_projects(f: impl FnOnce(&mut RecentProjects)) -> Result<RecentProjects, String> {
+    let store = RecentProjectsStore::for_user().ok_or("No user config directory")?;
+    store
+        .update(f)
+        .map_err(|e| format!("Failed to update recent projects: {}", e))
+}
+
+/// Make `path` the project root and reset project-scoped state
+///
+/// The folder is checked first, so a failed switch leaves the open project
+/// as it was. Everything tied to the old project is then torn down: running
+/// tool calls are cancelled, terminals closed, task watchers stopped, and the
+/// conversation, session, buffers, diagnostics, and tool cache cleared.
+/// Returns the normalized project root.
+fn switch_project(app: &tauri::AppHandle, state: &State<'_, AppState>, path: &Path) -> Result<PathBuf, String> {
+    use tauri::Emitter;
+
+    let path = validate_project_root(path).map_err(|e| {
+        let error_msg = format!("Cannot open project: {}", e);
+        tracing::warn!("{}", error_msg);
+        error_msg
+    })?;
+    tracing::info!("Switching project to {}", path.display());
+
+    // Tear down the old project
+    for tool in state.tool_executor.running_tools() {
+        state.tool_executor.cancel(&tool.tool_use_id);
+    }
+    state.tool_executor.clear_cache();
+    state.tool_executor.close_all_repl_sessions();
+    state.tool_executor.kernels().shutdown_all();
+    state.terminal_manager.close_all();
+    state.watchers.stop_all();
+
+    // Update project root; a turn still running for the old project sees the
+    // new generation and leaves the new project's state alone
+    {
+        let mut project_root = state.project_root.lock().unwrap();
+        *project_root = path.clone();
+        state.project_generation.fetch_add(1, Ordering::SeqCst);
+    }
+    *state.turn.lock().unwrap() = None;
+
+    // Rebuild the system prompt for the new project; a plan for the old
+    // project no longer applies
+    let config = Config::load(&path).unwrap_or_default();
+    let mode = {
+        let mut mode = state.agent_mode.lock().unwrap();
+        if matches!(*mode, AgentMode::Approved(_)) {
+            *mode = AgentMode::Execute;
+        }
+        mode.clone()
+    };
+    let system_prompt = build_system_prompt(&path, &config, &mode, &PromptOverride::default());
+    state.terminal_manager.set_idle_timeout(config.terminal.idle_timeout());
+    {
+        let mut conversation = state.conversation.lock().unwrap();
+        conversation.system_prompt = Some(system_prompt.text());
+        conversation.system_blocks = Some(system_prompt.to_blocks());
+        conversation.clear();
+    }
+    *state.session.lock().unwrap() = SessionMetadata::new();
+    state.buffers.clear();
+    state.diagnostics.clear();
+    state.tool_executor.set_working_directory(&path);
+    state.tool_executor.set_policy(project_tool_policy(&path));
+    state
+        .tool_executor
+        .set_file_history(Some(FileHistory::for_project(&path, &config)));
+    let icons = project_file_icons(&path);
+    *state.file_icons.lock().unwrap() = icons.clone();
+    state
+        .project_index
+        .open(path.clone(), project_ignore_rules(&path), icons);
+
+    if let Err(e) = update_recent_projects(|recent| recent.record(&path)) {
+        tracing::warn!("{}", e);
+    }
+
+    // Emit event to refresh frontend
+    app.emit("project-folder-changed", path.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    Ok(path)
+}
+
+/// Recently opened projects, most recent first
+///
+/// Projects whose folder no longer exists are dropped from the list.
+#[tauri::command]
+async fn get_recent_projects() -> Result<Vec<RecentProject>, String> {
+    tracing::info!("get_recent_projects command called");
+    let recent = update_recent_projects(RecentProjects::prune_missing)?;
+    Ok(recent.projects)
+}
+
+/// Open a project from the recent projects list
+///
+/// A project that can no longer be opened is removed from the list.
+#[tauri::command]
+async fn open_recent_project(
+    path: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    tracing::info!("open_recent_project command called: {}", path);
+
+    match switch_project(&app, &state, Path::new(&path)) {
+        Ok(path) => Ok(path.to_string_lossy().to_string()),
+        Err(e) => {
+            if let Err(update_err) = update_recent_projects(|recent| {
+                recent.remove(&path);
+            }) {
+                tracing::warn!("{}", update_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Analyze the project and propose AURORA.md and config changes for review
+///
+/// With `use_agent`, the model drafts AURORA.md from the analysis; otherwise
+/// it is rendered from the analysis directly. Nothing is written.
+#[tauri::command]
+async fn init_project_directives(use_agent: Option<bool>, state: State<'_, AppState>) -> Result<OnboardingPlan, String> {
+    tracing::info!("init_project_directives command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let analysis = {
+        let root = project_root.clone();
+        tokio::task::spawn_blocking(move || ProjectAnalysis::analyze(&root))
+            .await
+            .map_err(|e| format!("Analysis task failed: {}", e))?
+    };
+
+    let directives = if use_agent.unwrap_or(false) {
+        let api_key = CredentialStore::for_project(&project_root)
+            .retrieve("anthropic_api_key", "auroraheart")
+            .map_err(|_| format!("⚠ {}", ui_message(&project_root, UiMessage::NoApiKey)))?;
+        let config = Config::load(&project_root).unwrap_or_default();
+        let outbound_filter = project_outbound_filter(&state, &project_root, &config.outbound_filter)?;
+        onboarding::draft_directives(&AnthropicClient::new(api_key).with_outbound_filter(outbound_filter), &analysis)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        analysis.render_directives()
+    };
+
+    OnboardingPlan::new(&project_root, analysis, directives).map_err(|e| e.to_string())
+}
+
+/// Save reviewed onboarding files and reload the system prompt
+#[tauri::command]
+async fn save_project_directives(files: Vec<ProposedFile>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    tracing::info!("save_project_directives command called for {} files", files.len());
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let written = onboarding::save_files(&project_root, &files).map_err(|e| e.to_string())?;
+
+    let mode = state.agent_mode.lock().unwrap().clone();
+    reload_system_prompt(&state, &project_root, &mode);
+
+    Ok(written
+        .iter()
+        .map(|path| normalize_path(path).to_string_lossy().to_string())
+        .collect())
+}
+
+/// Group the commits in `range` by conventional commit type into a
+/// CHANGELOG.md section and propose the updated file for review
+///
+/// `version` titles the section (default `Unreleased`); a section with the
+/// same title is replaced. With `polish`, the model rewords the entries.
+/// Nothing is written.
+#[tauri::command]
+async fn generate_changelog(
+    range: String,
+    version: Option<String>,
+    date: Option<String>,
+    polish: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ChangelogPreview, String> {
+    tracing::info!("generate_changelog command called: {}", range);
+    let project_root = state.project_root.lock().unwrap().clone();
+
+    let commits = {
+        let root = project_root.clone();
+        tokio::task::spawn_blocking(move || commits_in_range(&root, &range))
+            .await
+            .map_err(|e| format!("Changelog task failed: {}", e))?
+            .map_err(|e| {
+                let error_msg = format!("Failed to read commits: {}", e);
+                tracing::error!("{}", error_msg);
+                error_msg
+            })?
+    };
+    let release = Release {
+        title: version.unwrap_or_else(|| "Unreleased".to_string()),
+        date,
+        commits,
+    };
+
+    let mut section = release.render();
+    if polish.unwrap_or(false) && !release.commits.is_empty() {
+        let api_key = CredentialStore::for_project(&project_root)
+            .retrieve("anthropic_api_key", "auroraheart")
+            .map_err(|_| format!("⚠ {}", ui_message(&project_root, UiMessage::NoApiKey)))?;
+        let config = Config::load(&project_root).unwrap_or_default();
+        let outbound_filter = project_outbound_filter(&state, &project_root, &config.outbound_filter)?;
+        section = polish_changelog(&AnthropicClient::new(api_key).with_outbound_filter(outbound_filter), &section)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    ChangelogPreview::new(&project_root, &section).map_err(|e| e.to_string())
+}
+
+/// Write a reviewed changelog preview to CHANGELOG.md
+#[tauri::command]
+async fn save_changelog_preview(preview: ChangelogPreview, state: State<'_, AppState>) -> Result<String, String> {
+    tracing::info!("save_changelog_preview command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let path = save_changelog(&project_root, &preview.content).map_err(|e| {
+        let error_msg = format!("Failed to write changelog: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+    Ok(normalize_path(&path).to_string_lossy().to_string())
+}
+
+/// Preview the system prompt assembled for the project
+///
+/// Shows the final order of project directives, directive modules, and the
+/// user's global AURORA.md exactly as the model receives them.
+#[tauri::command]
+async fn preview_system_prompt(state: State<'_, AppState>) -> Result<SystemPrompt, String> {
+    tracing::info!("preview_system_prompt command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).unwrap_or_default();
+    let mode = state.agent_mode.lock().unwrap().clone();
+    let prompt_override = state.conversation.lock().unwrap().prompt_override.clone();
+    Ok(build_system_prompt(&project_root, &config, &mode, &prompt_override))
+}
+
+/// Rebuild the conversation's system prompt for `mode`, keeping the
+/// conversation's persona and custom instructions, and return it
+fn reload_system_prompt(state: &AppState, project_root: &Path, mode: &AgentMode) -> SystemPrompt {
+    let config = Config::load(project_root).unwrap_or_default();
+    let prompt_override = state.conversation.lock().unwrap().prompt_override.clone();
+    let system_prompt = build_system_prompt(project_root, &config, mode, &prompt_override);
+    let mut conversation = state.conversation.lock().unwrap();
+    conversation.system_prompt = Some(system_prompt.text());
+    conversation.system_blocks = Some(system_prompt.to_blocks());
+    system_prompt
+}
+
+/// Rebuild the conversation's system prompt for a new agent mode
+fn set_mode(state: &State<'_, AppState>, mode: AgentMode) {
+    let project_root = state.project_root.lock().unwrap().clone();
+    reload_system_prompt(state, &project_root, &mode);
+    *state.agent_mode.lock().unwrap() = mode;
+}
+
+/// Turn auto-approve mode on or off
+///
+/// While it is on, tool calls run within the limits of the project's
+/// `policy.toml`: files modified per turn, no removals outside the workspace,
+/// and no push or publish commands.
+#[tauri::command]
+async fn set_auto_approve(enabled: bool, state: State<'_, AppState>) -> Result<bool, String> {
+    tracing::info!("set_auto_approve command called: {}", enabled);
+    *state.auto_approve.lock().unwrap() = enabled;
+    Ok(enabled)
+}
+
+/// Whether auto-approve mode is on
+#[tauri::command]
+async fn get_auto_approve(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(*state.auto_approve.lock().unwrap())
+}
+
+/// Set how tool output is displayed, `rich` or `plain`, for every project
+///
+/// Plain output replaces emoji and box-drawing with words and ASCII, which
+/// screen readers read cleanly. It applies from the next message on.
+#[tauri::command]
+async fn set_output_style(style: String, state: State<'_, AppState>) -> Result<OutputStyle, String> {
+    tracing::info!("set_output_style command called: {}", style);
+    let style: OutputStyle = style.parse()?;
+    if let Some(path) = OutputStyle::user_path() {
+        style.save(&path).map_err(|e| {
+            let error_msg = format!("Failed to save output style: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+    }
+    *state.output_style.lock().unwrap() = style;
+    Ok(style)
+}
+
+/// How tool output is displayed
+#[tauri::command]
+async fn get_output_style(state: State<'_, AppState>) -> Result<OutputStyle, String> {
+    Ok(*state.output_style.lock().unwrap())
+}
+
+/// Get the current agent mode
+#[tauri::command]
+async fn get_agent_mode(state: State<'_, AppState>) -> Result<AgentMode, String> {
+    Ok(state.agent_mode.lock().unwrap().clone())
+}
+
+/// Enter read-only plan mode, or return to execution without a plan
+#[tauri::command]
+async fn set_plan_mode(enabled: bool, state: State<'_, AppState>) -> Result<AgentMode, String> {
+    tracing::info!("set_plan_mode command called: {}", enabled);
+    let mode = if enabled { AgentMode::Plan } else { AgentMode::Execute };
+    set_mode(&state, mode.clone());
+    Ok(mode)
+}
+
+/// Approve a plan and switch to execution mode carrying it as context
+///
+/// Without `plan`, the agent's latest reply is taken as the plan.
+#[tauri::command]
+async fn approve_plan(plan: Option<String>, state: State<'_, AppState>) -> Result<AgentMode, String> {
+    tracing::info!("approve_plan command called");
+    let plan = match plan.filter(|plan| !plan.trim().is_empty()) {
+        Some(plan) => plan,
+        None => state
+            .conversation
+            .lock()
+            .unwrap()
+            .last_assistant_text()
+            .ok_or_else(|| "There is no plan to approve yet".to_string())?,
+    };
+    let mode = AgentMode::Approved(plan);
+    set_mode(&state, mode.clone());
+    Ok(mode)
+}
+
+/// List built-in and user-defined project templates
+#[tauri::command]
+async fn list_project_templates() -> Result<Vec<TemplateInfo>, String> {
+    tracing::info!("list_project_templates command called");
+    Ok(Scaffolder::new().list_templates())
+}
+
+/// Create a project from a template and open it as the project root
+#[tauri::command]
+async fn create_project(
+    template: String,
+    name: String,
+    parent_dir: String,
+    variables: Option<BTreeMap<String, String>>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    tracing::info!("create_project command called: {} from '{}' in {}", name, template, parent_dir);
+
+    let variables = variables.unwrap_or_default();
+    let project = tokio::task::spawn_blocking(move || {
+        Scaffolder::new().scaffold(&template, &name, Path::new(&parent_dir), &variables)
+    })
+    .await
+    .map_err(|e| format!("Scaffold task failed: {}", e))?
+    .map_err(|e| {
+        let error_msg = format!("Failed to create project: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+
+    let project = switch_project(&app, &state, &project)?;
+    Ok(project.to_string_lossy().to_string())
+}
+
+/// Event emitted for each task start, output line, and finish while a task runs
+const TASK_EVENT: &str = "task-event";
+
+/// Backend commands offered in the command palette: command, title, category
+const PALETTE_COMMANDS: &[(&str, &str, &str)] = &[
+    ("open_folder", "Open Folder…", "File"),
+    ("get_recent_projects", "Open Recent Project…", "File"),
+    ("create_project", "New Project from Template…", "File"),
+    ("fuzzy_find_files", "Go to File…", "Go"),
+    ("find_workspace_symbol", "Go to Symbol in Workspace…", "Go"),
+    ("search_in_project", "Find in Files…", "Search"),
+    ("replace_in_project", "Replace in Files…", "Search"),
+    ("clear_chat", "Clear Chat", "Agent"),
+    ("set_plan_mode", "Toggle Plan Mode", "Agent"),
+    ("set_auto_approve", "Toggle Auto-Approve", "Agent"),
+    ("list_sessions", "Open Saved Session…", "Agent"),
+    ("search_sessions", "Search Sessions…", "Agent"),
+    ("preview_system_prompt", "Preview System Prompt", "Agent"),
+    ("init_project_directives", "Generate AURORA.md", "Agent"),
+    ("get_usage_report", "Show Usage Report", "Agent"),
+    ("get_tool_metrics", "Show Tool Metrics", "Agent"),
+    ("refresh_external_tools", "Refresh Installed Tools", "Agent"),
+    ("save_api_key", "Set API Key…", "Agent"),
+    ("get_git_status", "Refresh Git Status", "Git"),
+    ("git_commit", "Commit…", "Git"),
+    ("git_create_branch", "Create Branch…", "Git"),
+    ("git_push", "Push", "Git"),
+    ("git_pull", "Pull", "Git"),
+    ("run_quality_gate", "Run Quality Gate", "Git"),
+    ("scan_secrets", "Scan for Secrets", "Git"),
+    ("check_markdown", "Check Markdown Links", "Git"),
+    ("generate_sbom", "Generate SBOM…", "Git"),
+    ("get_ci_status", "Show CI Status", "Git"),
+    ("get_failed_job_logs", "Show Failed CI Logs", "Git"),
+    ("get_issue", "Open Issue…", "Git"),
+    ("spawn_terminal", "New Terminal", "Terminal"),
+    ("list_tasks", "Run Task…", "Tasks"),
+    ("list_watches", "Show Watched Tasks", "Tasks"),
+    ("get_diagnostics", "Show Problems", "Diagnostics"),
+    ("check_for_updates", "Check for Updates", "Application"),
+    ("restart_to_update", "Restart to Update", "Application"),
+];
+
+/// Everything the command palette can run, including plugin contributions
+///
+/// Built per call, since tasks and plugins come from the open project.
+#[tauri::command]
+async fn list_actions(state: State<'_, AppState>) -> Result<Vec<Action>, String> {
+    tracing::info!("list_actions command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).unwrap_or_default();
+
+    let mut registry = ActionRegistry::new();
+    let tools = state.tool_executor.tools().into_iter().map(|tool| {
+        let summary = tool.description.split(['.', '\n']).next().unwrap_or_default().trim().to_string();
+        Action::new(ActionKind::Tool, &tool.name, format!("Tool: {}", tool.name), "Agent Tools")
+            .with_description(summary)
+    });
+    let commands = PALETTE_COMMANDS
+        .iter()
+        .map(|(command, title, category)| Action::new(ActionKind::Command, command, *title, *category));
+    for action in commands.chain(tools) {
+        registry.register(action).map_err(|e| e.to_string())?;
+    }
+    registry.add_tasks(&config.tasks).map_err(|e| e.to_string())?;
+
+    // Discovery creates the plugins directory when it is missing, which
+    // listing actions should not do
+    let mut plugins = PluginManager::new(&project_root);
+    if plugins.plugins_dir.is_dir() {
+        if let Err(e) = plugins.discover_plugins() {
+            tracing::warn!("Failed to load plugins: {}", e);
+        }
+        registry.add_plugins(&plugins);
+    }
+
+    Ok(registry.list())
+}
+
+/// List the tasks defined in the project configuration
+#[tauri::command]
+async fn list_tasks(state: State<'_, AppState>) -> Result<BTreeMap<String, TaskDefinition>, String> {
+    tracing::info!("list_tasks command called");
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok(config.tasks)
+}
+
+/// Run a named task and its dependencies
+///
+/// Progress and output lines are streamed as `task-event` events; the
+/// returned report holds the final status of every task.
+#[tauri::command]
+async fn run_task(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<TaskReport, String> {
+    use tauri::Emitter;
+
+    tracing::info!("run_task command called: {}", name);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    let runner = TaskRunner::from_config(&project_root, &config);
+
+    let result = tokio::task::spawn_blocking(move || {
+        runner.run(&name, |event| {
+            if let Err(e) = app.emit(TASK_EVENT, event) {
+                tracing::error!("Failed to emit {}: {}", TASK_EVENT, e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("Task runner failed: {}", e))?;
+
+    result.map_err(|e| {
+        let error_msg = format!("Failed to run task: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })
+}
+
+/// Event emitted after each run of a watched task
+const WATCH_RESULT: &str = "watch-result";
+
+/// Event emitted when a watcher exits
+const WATCH_STOPPED: &str = "watch-stopped";
+
+/// Payload of the `watch-stopped` event
+#[derive(Debug, Clone, Serialize)]
+struct WatchStopped {
+    /// The task that was being watched
+    task: String,
+    /// Why the watcher exited, if it was not stopped by request
+    error: Option<String>,
+}
+
+/// Keep the conversation's pending watch output in sync with the latest run
+///
+/// A failing run replaces any earlier output for the same task; a passing run
+/// removes it, so the agent only sees regressions that still exist.
+fn inject_watch_result(conversation: &Mutex<Conversation>, run: &WatchRun) {
+    let label = format!("watch: {}", run.task);
+    let Ok(mut conversation) = conversation.lock() else {
+        return;
+    };
+    conversation.pending_attachments.retain(|a| a.path != label);
+    if let Some(summary) = run.failure_summary() {
+        tracing::info!("Attaching failing watch output for '{}' to the next message", run.task);
+        conversation.attach(vec![Attachment::output(label, summary)]);
+    }
+}
+
+/// Run a task now and again whenever its watched files change
+///
+/// Output is streamed as `task-event` events and each run's report as a
+/// `watch-result` event. When `[watch] inject_failures` is enabled, failing
+/// output is attached to the next message sent to the agent.
+#[tauri::command]
+async fn start_watch(task: String, app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    use tauri::Emitter;
+
+    tracing::info!("start_watch command called: {}", task);
+    let project_root = state.project_root.lock().unwrap().clone();
+    let config = Config::load(&project_root).map_err(|e| format!("Failed to load config: {}", e))?;
+    let mut watcher = TaskWatcher::new(&project_root, &config, &task).map_err(|e| {
+        let error_msg = format!("Failed to watch task: {}", e);
+        tracing::error!("{}", error_msg);
+        error_msg
+    })?;
+
+    let inject_failures = config.watch.inject_failures;
+    let stop = state.watchers.start(&task);
+    let watchers = state.watchers.clone();
+    let conversation = state.conversation.clone();
+
+    std::thread::spawn(move || {
+        let result = watcher.run(
+            &stop,
+            |event| {
+                if let Err(e) = app.emit(TASK_EVENT, event) {
+                    tracing::error!("Failed to emit {}: {}", TASK_EVENT, e);
+                }
+            },
+            |run| {
+                if inject_failures {
+                    inject_watch_result(&conversation, run);
+                }
+                if let Err(e) = app.emit(WATCH_RESULT, run) {
+                    tracing::error!("Failed to emit {}: {}", WATCH_RESULT, e);
+                }
+            },
+        );
+
+        let error = result.err().map(|e| e.to_string());
+        match &error {
+            Some(e) => tracing::error!("Watcher for '{}' stopped: {}", task, e),
+            None => tracing::info!("Watcher for '{}' stopped", task),
+        }
+        watchers.finished(&task, &stop);
+        if let Err(e) = app.emit(WATCH_STOPPED, WatchStopped { task, error }) {
+            tracing::error!("Failed to emit {}: {}", WATCH_STOPPED, e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Stop watching a task
+#[tauri::command]
+async fn stop_watch(task: String, state: State<'_, AppState>) -> Result<bool, String> {
+    tracing::info!("stop_watch command called: {}", task);
+    Ok(state.watchers.stop(&task))
+}
+
+/// List the tasks currently being watched
+#[tauri::command]
+async fn list_watches(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.watchers.active())
+}
+
+/// Open folder picker and set new project root
+#[tauri::command]
+async fn open_folder(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    tracing::info!("open_folder command called");
+
+    // Show folder picker dialog
+    let folder_path = app
+        .dialog()
+        .file()
+        .set_title("Select Project Folder")
+        .blocking_pick_folder();
+
+    if let Some(folder) = folder_path {
+        let path = folder.into_path().map_err(|e| format!("Failed to convert path: {}", e))?;
+        tracing::info!("Selected folder: {:?}", path);
+
+        let path = switch_project(&app, &state, &path)?;
+        Ok(path.to_string_lossy().to_string())
+    } else {
+        Err("No folder selected".to_string())
+    }
+}
+
+/// Operations in progress, oldest first
+#[tauri::command]
+async fn list_progress(state: State<'_, AppState>) -> Result<Vec<ProgressInfo>, String> {
+    Ok(state.progress.active())
+}
+
+/// Ask a cancellable operation to stop
+#[tauri::command]
+async fn cancel_progress(token: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("cancel_progress command called for {}", token);
+    if state.progress.cancel(&token) {
+        Ok(())
+    } else {
+        Err(format!("No cancellable operation {}", token))
+    }
+}
+
+// ============================================================================
+// CRASH RECOVERY
+// ============================================================================
+
+/// How often the recovery snapshot is refreshed
+const RECOVERY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Capture the conversation, the turn in flight, and unsaved buffers
+fn capture_recovery_snapshot(state: &AppState) -> RecoverySnapshot {
+    let project_root = state.project_root.lock().unwrap().clone();
+    let session = state.session.lock().unwrap().clone();
+    let turn = state.turn.lock().unwrap().clone();
+    let conversation = state.conversation.lock().unwrap();
+    RecoverySnapshot::capture(&project_root, &session, &conversation, turn.as_ref(), state.buffers.unsaved())
+}
+
+/// Refresh the recovery snapshot whenever the state changes
+///
+/// While a crashed run's snapshot awaits restoring or discarding it is left
+/// in place, so a second crash does not lose it.
+fn spawn_recovery_writer(app_handle: tauri::AppHandle, store: RecoveryStore) {
+    std::thread::spawn(move || {
+        let mut last_saved = None;
+        loop {
+            std::thread::sleep(RECOVERY_INTERVAL);
+            let state = app_handle.state::<AppState>();
+            if state.recovery.lock().unwrap().is_some() {
+                continue;
+            }
+            let snapshot = capture_recovery_snapshot(&state);
+            let state_only = (&snapshot.project_root, &snapshot.session, &snapshot.turn, &snapshot.buffers);
+            let contents = serde_json::to_string(&state_only).ok();
+            if contents.is_some() && contents == last_saved {
+                continue;
+            }
+            match store.save(&snapshot) {
+                Ok(()) => last_saved = contents,
+                Err(e) => tracing::warn!("Failed to save recovery snapshot: {}", e),
+            }
+        }
+    });
+}
+
+fn recovery_store() -> Result<RecoveryStore, String> {
+    RecoveryStore::for_user().ok_or_else(|| "No user config directory for recovery snapshots".to_string())
+}
+
+/// State left by a run that crashed, to offer restoring it
+#[tauri::command]
+async fn get_recovery_snapshot(state: State<'_, AppState>) -> Result<Option<RecoverySnapshot>, String> {
+    Ok(state.recovery.lock().unwrap().clone())
+}
+
+/// Restore the conversation and unsaved buffers of a run that crashed
+///
+/// Switches to the crashed run's project if another one is open. An
+/// interrupted agent turn is restored as a partial assistant message. The
+/// snapshot is returned so the frontend can reopen the unsaved buffers.
+#[tauri::command]
+async fn restore_previous_session(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RecoverySnapshot, String> {
+    tracing::info!("restore_previous_session command called");
+    let snapshot = state
+        .recovery
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "No previous session to restore".to_string())?;
+
+    let current_root = state.project_root.lock().unwrap().clone();
+    if path_key(&current_root) != path_key(&snapshot.project_root) {
+        switch_project(&app, &state, &snapshot.project_root)?;
+    }
+
+    let conversation = {
+        let mut conversation = state.conversation.lock().unwrap();
+        conversation.messages = snapshot.messages();
+        conversation.attachments = snapshot.session.attachments.clone();
+        conversation.usage = snapshot.session.usage.clone();
+        conversation.budget = snapshot.session.budget;
+        conversation.prompt_override = snapshot.session.prompt_override.clone();
+        conversation.pending_attachments.clear();
+        conversation.clone()
+    };
+    let mode = state.agent_mode.lock().unwrap().clone();
+    reload_system_prompt(&state, &snapshot.project_root, &mode);
+    let mut session = snapshot.session.metadata.clone();
+    if !conversation.messages.is_empty() {
+        if let Err(e) = SessionStore::for_project(&snapshot.project_root).save(&mut session, &conversation) {
+            tracing::warn!("Failed to save restored session: {}", e);
+        }
+    }
+    *state.session.lock().unwrap() = session;
+    state.tool_executor.clear_cache();
+    for buffer in &snapshot.buffers {
+        state.buffers.update(Path::new(&buffer.path), Some(buffer.content.clone()));
+    }
+
+    *state.recovery.lock().unwrap() = None;
+    tracing::info!(
+        "Restored {} messages and {} unsaved buffers",
+        conversation.messages.len(),
+        snapshot.buffers.len()
+    );
+    Ok(snapshot)
+}
+
+/// Drop the state of a run that crashed without restoring it
+#[tauri::command]
+async fn discard_recovery(state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("discard_recovery command called");
+    *state.recovery.lock().unwrap() = None;
+    recovery_store()?
+        .clear()
+        .map_err(|e| format!("Failed to discard recovery snapshot: {}", e))
+}
+
+// ============================================================================
+// UPDATE COMMANDS
+// ============================================================================
+
+/// Event emitted when the startup check finds a newer release
+const UPDATE_AVAILABLE: &str = "update-available";
+
+/// Updater for the user's update settings; projects have no say in where
+/// updates come from
+fn update_client() -> Result<UpdateClient, String> {
+    let config = UpdateConfig::load_user().map_err(|e| format!("Failed to read update settings: {}", e))?;
+    UpdateClient::new(&config).map_err(|e| e.to_string())
+}
+
+fn update_stager() -> Result<UpdateStager, String> {
+    UpdateStager::for_user().ok_or_else(|| "No user config directory for staging updates".to_string())
+}
+
+/// Check the configured channel for a release newer than this build
+#[tauri::command]
+async fn check_for_updates() -> Result<Option<AvailableUpdate>, String> {
+    tracing::info!("check_for_updates command called");
+    update_client()?
+        .check(CURRENT_VERSION)
+        .await
+        .map_err(|e| {
+            let error_msg = format!("Failed to check for updates: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })
+}
+
+/// Download and verify an update, staging it to be applied on restart
+#[tauri::command]
+async fn download_update(update: AvailableUpdate, state: State<'_, AppState>) -> Result<StagedUpdate, String> {
+    tracing::info!("download_update command called: {}", update.version);
+    let client = update_client()?;
+    let stager = update_stager()?;
+    let progress = state
+        .progress
+        .begin(format!("Downloading AuroraHeart {}", update.version), true);
+    let staged = client
+        .download(&update, &stager, |done, total| {
+            progress.report_fraction(done, total);
+            !progress.is_cancelled()
+        })
+        .await
+        .map_err(|e| {
+            let error_msg = format!("Failed to download update: {}", e);
+            tracing::error!("{}", error_msg);
+            error_msg
+        })?;
+    tracing::info!("Staged update {}", staged.version);
+    Ok(staged)
+}
+
+/// The update waiting to be applied, if any
+#[tauri::command]
+async fn get_staged_update() -> Result<Option<StagedUpdate>, String> {
+    update_stager()?
+        .pending()
+        .map_err(|e| format!("Failed to read staged update: {}", e))
+}
+
+/// Apply the staged update and restart into it
+#[tauri::command]
+async fn restart_to_update(app: tauri::AppHandle) -> Result<(), String> {
+    tracing::info!("restart_to_update command called");
+    let executable = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+    match update_stager()?.apply(&executable) {
+        Ok(Some(version)) => tracing::info!("Applied update {}, restarting", version),
+        Ok(None) => return Err("No update is staged".to_string()),
+        Err(e) => {
+            let error_msg = format!("Failed to apply update: {}", e);
+            tracing::error!("{}", error_msg);
+            return Err(error_msg);
+        }
+    }
+    app.restart()
+}
+
+/// Remove the recovery snapshot on a clean shutdown
+///
+/// A crashed run's snapshot that was neither restored nor discarded is kept
+/// so it is offered again on the next launch.
+fn clear_recovery_snapshot(app: &tauri::AppHandle) {
+    let pending = app
+        .try_state::<AppState>()
+        .is_some_and(|state| state.recovery.lock().unwrap().is_some());
+    if pending {
+        return;
+    }
+    if let Some(Err(e)) = RecoveryStore::for_user().map(|store| store.clear()) {
+        tracing::warn!("Failed to clear recovery snapshot: {}", e);
+    }
+}
+
+/// Swap a staged update in for the executable as the app exits
+fn apply_staged_update() {
+    let (Some(stager), Ok(executable)) = (UpdateStager::for_user(), std::env::current_exe()) else {
+        return;
+    };
+    match stager.apply(&executable) {
+        Ok(Some(version)) => tracing::info!("Applied update {}; it runs from the next launch", version),
+        Ok(None) => {}
+        Err(e) => tracing::error!("Failed to apply update: {}", e),
+    }
+}
+
+// ============================================================================
+// MAIN
+// ============================================================================
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Serve an isolated tool call when started as a tool worker
+    if aurora_agent::tools::is_worker_invocation() {
+        std::process::exit(aurora_agent::tools::run_worker().await);
+    }
+
+    // Initialize logging
+    init_tracing();
+    tracing::info!("Starting AuroraHeart IDE (Tauri)");
 
     // Get current directory
     let current_dir = std::env::current_dir()?;
@@ -747,6 +3303,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     tracing::info!("Project root: {:?}", project_root);
+    if let Err(e) = update_recent_projects(|recent| recent.record(&project_root)) {
+        tracing::warn!("{}", e);
+    }
 
     // Get project name
     let project_name = get_project_name(&project_root).unwrap_or_else(|| "Unknown".to_string());
@@ -792,11 +3351,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::debug!("Configuration: {:?}", config);
 
-    // Create persistent conversation with system prompt
-    let conversation = Arc::new(Mutex::new(Conversation::with_system_prompt(
-        "You are Claude, a helpful AI assistant integrated into AuroraHeart IDE. \
-         You help developers with coding tasks, explaining code, debugging, and general programming questions.",
-    )));
+    // Create persistent conversation with a system prompt assembled from
+    // the project's directives and environment
+    let system_prompt = build_system_prompt(&project_root, &config, &AgentMode::Execute, &PromptOverride::default());
+    let conversation = Arc::new(Mutex::new(Conversation::with_sectioned_prompt(&system_prompt)));
 
     // Build and run Tauri application
     tauri::Builder::default()
@@ -804,26 +3362,170 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .setup(move |app| {
             // Create terminal manager with app handle
             let terminal_manager = TerminalManager::new(app.handle().clone());
+            terminal_manager.set_idle_timeout(config.terminal.idle_timeout());
+
+            // One tool executor serves every conversation; project switches
+            // update it in place
+            let editor_state = EditorState::shared();
+            let buffers = Arc::new(BufferRegistry::new(app.handle().clone()));
+            let diagnostics = Arc::new(DiagnosticsHub::new(app.handle().clone()));
+            let tool_executor = Arc::new(ToolExecutor::with_working_directory(&project_root)
+                .with_editor_state(editor_state.clone())
+                .with_buffer_sync(buffers.clone())
+                .with_diagnostics(diagnostics.clone())
+                .with_file_history(FileHistory::for_project(&project_root, &config))
+                .with_tool_cache(Arc::new(ToolCache::new()))
+                .with_policy(project_tool_policy(&project_root))
+                .with_interactive_input(true));
+
+            // Show REPL evaluations live in the session view
+            let mut repl_updates = tool_executor.subscribe_repl();
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tauri::Emitter;
+                use tokio::sync::broadcast::error::RecvError;
+
+                loop {
+                    match repl_updates.recv().await {
+                        Ok(update) => {
+                            if let Err(e) = app_handle.emit(REPL_UPDATE, update) {
+                                tracing::warn!("Failed to emit {}: {}", REPL_UPDATE, e);
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Skipped {} REPL updates", skipped);
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            // Give tool commands the PATH the user's shell startup files set
+            // up, which a desktop launch does not inherit
+            if !config.shell.skip_environment_capture {
+                if let Some(shell) = Shell::user_login_shell() {
+                    let tool_executor = Arc::clone(&tool_executor);
+                    std::thread::spawn(move || {
+                        match ShellEnvironment::capture(&shell, aurora_core::shell::DEFAULT_CAPTURE_TIMEOUT) {
+                            Ok(environment) => tool_executor.set_shell_environment(Some(environment)),
+                            Err(e) => tracing::warn!("Failed to capture shell environment: {}", e),
+                        }
+                    });
+                }
+            }
+
+            // A snapshot left behind means the last run did not shut down
+            // cleanly
+            let recovery = RecoveryStore::for_user().and_then(|store| {
+                store
+                    .load()
+                    .map_err(|e| tracing::warn!("Failed to load recovery snapshot: {}", e))
+                    .ok()
+                    .flatten()
+            });
+            if let Some(snapshot) = &recovery {
+                tracing::info!("Found recovery snapshot from {}", snapshot.saved_at);
+            }
+
+            let progress = Arc::new(ProgressRegistry::new(app.handle().clone()));
+            let project_index = Arc::new(ProjectIndex::new(app.handle().clone(), Arc::clone(&progress)));
+            let file_icons = project_file_icons(&project_root);
+            project_index.open(project_root.clone(), project_ignore_rules(&project_root), file_icons.clone());
 
             // Create application state
             let app_state = AppState {
                 project_root: Arc::new(Mutex::new(project_root)),
                 conversation,
+                session: Arc::new(Mutex::new(SessionMetadata::new())),
+                editor_state,
+                buffers,
+                diagnostics,
+                tool_executor,
+                agent_mode: Arc::new(Mutex::new(AgentMode::Execute)),
+                auto_approve: Arc::new(Mutex::new(false)),
+                approvals: Arc::new(ApprovalBroker::new(app.handle().clone())),
+                watchers: Arc::new(WatchRegistry::default()),
+                project_index,
+                progress,
+                turn: Arc::new(Mutex::new(None)),
+                recovery: Mutex::new(recovery),
                 terminal_manager,
+                output_style: Mutex::new(OutputStyle::user_path().map(|path| OutputStyle::load(&path)).unwrap_or_default()),
+                file_icons: Mutex::new(file_icons),
+                outbound_filter: Mutex::new(None),
+                project_generation: AtomicU64::new(0),
             };
 
             // Manage the state
             app.manage(app_state);
+            if let Some(store) = RecoveryStore::for_user() {
+                spawn_recovery_writer(app.handle().clone(), store);
+            }
+            spawn_idle_terminal_monitor(app.handle().clone());
+
+            let update_config = UpdateConfig::load_user().unwrap_or_else(|e| {
+                tracing::warn!("Failed to read update settings: {}", e);
+                UpdateConfig::default()
+            });
+            if update_config.check_on_startup && update_config.endpoint.is_some() {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    use tauri::Emitter;
+
+                    let client = match UpdateClient::new(&update_config) {
+                        Ok(client) => client,
+                        Err(e) => {
+                            tracing::warn!("Skipping update check: {}", e);
+                            return;
+                        }
+                    };
+                    match client.check(CURRENT_VERSION).await {
+                        Ok(Some(update)) => {
+                            tracing::info!("Update {} is available", update.version);
+                            if let Err(e) = app_handle.emit(UPDATE_AVAILABLE, update) {
+                                tracing::warn!("Failed to emit {}: {}", UPDATE_AVAILABLE, e);
+                            }
+                        }
+                        Ok(None) => tracing::debug!("No update available"),
+                        Err(e) => tracing::warn!("Failed to check for updates: {}", e),
+                    }
+                });
+            }
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_file_tree,
             get_directory_contents,
+            get_file_icons,
+            fuzzy_find_files,
+            find_workspace_symbol,
             open_file,
             read_file_by_path,
             save_file,
+            get_file_history,
+            get_file_version,
+            restore_file_version,
             send_message,
+            continue_after_budget,
+            get_budget_status,
+            set_conversation_budget,
+            get_usage_report,
+            get_outbound_audit_log,
+            send_tool_input,
+            answer_tool_approval,
+            cancel_tool,
+            list_repl_sessions,
+            get_repl_transcript,
+            close_repl_session,
+            get_notebook_cells,
+            execute_notebook_cell,
+            list_kernels,
+            interrupt_kernel,
+            shutdown_kernel,
+            get_tool_metrics,
+            get_external_tools,
+            refresh_external_tools,
             save_api_key,
             load_api_key,
             clear_chat,
@@ -839,13 +3541,89 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             git_stage,
             git_unstage,
             git_commit,
+            check_commit_message,
+            git_create_branch,
             git_push,
             git_pull,
+            run_quality_gate,
+            scan_secrets,
+            check_markdown,
+            save_integration_token,
+            transcribe_audio,
+            get_ci_status,
+            get_failed_job_logs,
+            get_issue,
+            get_sampling_settings,
+            save_sampling_settings,
+            set_conversation_sampling,
+            list_personas,
+            get_conversation_prompt,
+            set_conversation_prompt,
+            list_sessions,
+            search_sessions,
+            load_session,
+            pin_message,
+            exclude_message,
+            attach_context,
+            update_editor_state,
+            publish_diagnostics,
+            get_diagnostics,
+            apply_quick_fix,
+            apply_selected_changes,
+            get_file_owners,
+            update_buffer,
+            close_buffer,
+            search_in_project,
+            replace_in_project,
+            get_workspace_state,
+            record_recent_file,
+            save_cursor_position,
+            toggle_bookmark,
+            save_open_tabs,
+            add_secrets_to_baseline,
             get_project_root,
+            init_project_directives,
+            save_project_directives,
+            generate_changelog,
+            save_changelog_preview,
+            preview_system_prompt,
+            get_agent_mode,
+            set_plan_mode,
+            approve_plan,
+            set_auto_approve,
+            set_output_style,
+            get_output_style,
+            get_auto_approve,
+            list_project_templates,
+            create_project,
+            list_actions,
+            list_tasks,
+            run_task,
+            start_watch,
+            stop_watch,
+            list_watches,
             open_folder,
+            get_recent_projects,
+            open_recent_project,
+            list_progress,
+            cancel_progress,
+            check_for_updates,
+            download_update,
+            get_staged_update,
+            restart_to_update,
+            get_recovery_snapshot,
+            restore_previous_session,
+            discard_recovery,
+            generate_sbom,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                clear_recovery_snapshot(app);
+                apply_staged_update();
+            }
+        });
 
     tracing::info!("AuroraHeart IDE shutting down");
     Ok(())
@@ -0,0 +1,213 @@
+//! Progress reporting for long-running operations
+//!
+//! An operation such as indexing a project or downloading an update calls
+//! [`ProgressRegistry::begin`] and reports through the returned
+//! [`ProgressHandle`]. Each handle has a token, and every change is emitted
+//! as a `progress` event tagged `start`, `update`, or `finish`, so the
+//! frontend can show one progress entry per token. Cancellable operations
+//! poll [`ProgressHandle::is_cancelled`]; the frontend cancels them with the
+//! `cancel_progress` command. Dropping a handle finishes its operation.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted when an operation starts, advances, or finishes
+pub const PROGRESS: &str = "progress";
+
+/// Payload of the `progress` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// An operation started
+    Start {
+        token: String,
+        title: String,
+        cancellable: bool,
+    },
+    /// An operation advanced
+    Update {
+        token: String,
+        /// Percent done (0-100), or `None` when the total is unknown
+        percentage: Option<u8>,
+        message: Option<String>,
+    },
+    /// An operation finished, completed or cancelled
+    Finish {
+        token: String,
+        message: Option<String>,
+        cancelled: bool,
+    },
+}
+
+/// An operation in progress
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressInfo {
+    pub token: String,
+    pub title: String,
+    pub cancellable: bool,
+    pub percentage: Option<u8>,
+    pub message: Option<String>,
+}
+
+struct Operation {
+    /// Start order, for listing
+    sequence: u64,
+    info: ProgressInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Operations in progress, keyed by token
+pub struct ProgressRegistry {
+    operations: Mutex<BTreeMap<String, Operation>>,
+    next_id: AtomicU64,
+    app_handle: AppHandle,
+}
+
+impl ProgressRegistry {
+    /// Create a registry with no operations
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            operations: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(0),
+            app_handle,
+        }
+    }
+
+    /// Start reporting an operation
+    pub fn begin(self: &Arc<Self>, title: impl Into<String>, cancellable: bool) -> ProgressHandle {
+        let sequence = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let token = format!("progress-{}", sequence);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let info = ProgressInfo {
+            token: token.clone(),
+            title: title.into(),
+            cancellable,
+            percentage: None,
+            message: None,
+        };
+        self.emit(ProgressEvent::Start {
+            token: token.clone(),
+            title: info.title.clone(),
+            cancellable,
+        });
+        self.operations.lock().unwrap().insert(
+            token.clone(),
+            Operation {
+                sequence,
+                info,
+                cancelled: Arc::clone(&cancelled),
+            },
+        );
+
+        ProgressHandle {
+            token,
+            cancelled,
+            registry: Arc::clone(self),
+            finished: false,
+        }
+    }
+
+    /// Ask the operation with `token` to stop
+    ///
+    /// Returns false if there is no such operation or it cannot be cancelled.
+    pub fn cancel(&self, token: &str) -> bool {
+        let operations = self.operations.lock().unwrap();
+        match operations.get(token) {
+            Some(operation) if operation.info.cancellable => {
+                operation.cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Operations in progress, oldest first
+    pub fn active(&self) -> Vec<ProgressInfo> {
+        let operations = self.operations.lock().unwrap();
+        let mut active: Vec<&Operation> = operations.values().collect();
+        active.sort_by_key(|operation| operation.sequence);
+        active.into_iter().map(|operation| operation.info.clone()).collect()
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Err(e) = self.app_handle.emit(PROGRESS, event) {
+            tracing::warn!("Failed to emit {}: {}", PROGRESS, e);
+        }
+    }
+}
+
+/// Reports the progress of one operation
+pub struct ProgressHandle {
+    token: String,
+    cancelled: Arc<AtomicBool>,
+    registry: Arc<ProgressRegistry>,
+    finished: bool,
+}
+
+impl ProgressHandle {
+    /// Whether the user asked the operation to stop
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Report `percentage` (clamped to 100) and an optional status message
+    ///
+    /// Nothing is emitted when neither changed since the last report, so
+    /// callers can report on every step of a loop.
+    pub fn report(&self, percentage: Option<u8>, message: Option<String>) {
+        let percentage = percentage.map(|percentage| percentage.min(100));
+        {
+            let mut operations = self.registry.operations.lock().unwrap();
+            let Some(operation) = operations.get_mut(&self.token) else {
+                return;
+            };
+            if operation.info.percentage == percentage && (message.is_none() || operation.info.message == message) {
+                return;
+            }
+            operation.info.percentage = percentage;
+            if message.is_some() {
+                operation.info.message.clone_from(&message);
+            }
+        }
+        self.registry.emit(ProgressEvent::Update {
+            token: self.token.clone(),
+            percentage,
+            message,
+        });
+    }
+
+    /// Report `done` of `total` steps as a percentage
+    pub fn report_fraction(&self, done: u64, total: Option<u64>) {
+        let percentage = total
+            .filter(|&total| total > 0)
+            .map(|total| (done.min(total) * 100 / total) as u8);
+        self.report(percentage, None);
+    }
+
+    /// End the operation with an optional closing message
+    pub fn finish(mut self, message: Option<String>) {
+        self.end(message);
+    }
+
+    fn end(&mut self, message: Option<String>) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.registry.operations.lock().unwrap().remove(&self.token);
+        self.registry.emit(ProgressEvent::Finish {
+            token: self.token.clone(),
+            message,
+            cancelled: self.is_cancelled(),
+        });
+    }
+}
+
+impl Drop for ProgressHandle {
+    fn drop(&mut self) {
+        self.end(None);
+    }
+}
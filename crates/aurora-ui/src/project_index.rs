@@ -0,0 +1,285 @@
+//! Indexes of the open project behind the file tree, quick-open, and
+//! go-to-symbol
+//!
+//! The file index is built, and the stored symbol index brought up to date,
+//! on a background thread when a project is opened. The same thread then
+//! polls both every [`POLL_INTERVAL`]: the file index for added and removed
+//! files and directories, which it reports to the frontend as a
+//! `tree-updated` event, and the symbol index for source files that changed.
+//! Opening another project stops the old thread.
+
+use crate::progress::ProgressRegistry;
+use crate::FileTreeItem;
+use aurora_core::{
+    normalize_path, FileIcons, FileIndex, FileMatch, IgnoreRules, IndexChanges, IndexEntry, SymbolIndex, SymbolKind,
+    SymbolMatch,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often the indexes look for changed files
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Directories the file tree does not show, and so are not indexed
+const TREE_SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// Event emitted when files or directories appear or disappear
+pub const TREE_UPDATED: &str = "tree-updated";
+
+/// Payload of the `tree-updated` event
+///
+/// Only the topmost changed entries are listed: a new directory arrives as
+/// one item whose contents can be loaded when it is expanded, and removing
+/// a directory removes everything under it.
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeUpdate {
+    /// New entries, each under a directory that was already in the tree
+    pub added: Vec<FileTreeItem>,
+    /// Paths of entries that no longer exist
+    pub removed: Vec<String>,
+}
+
+impl TreeUpdate {
+    /// The tree changes of `changes` to the project at `root`
+    fn from_changes(root: &Path, changes: &IndexChanges, icons: &FileIcons) -> Self {
+        let added_dirs: BTreeSet<&str> = changes.added_dirs.iter().map(String::as_str).collect();
+        let removed_dirs: BTreeSet<&str> = changes.removed_dirs.iter().map(String::as_str).collect();
+
+        let added_files = changes.added.iter().map(|path| (path, false));
+        let added = changes
+            .added_dirs
+            .iter()
+            .map(|path| (path, true))
+            .chain(added_files)
+            .filter(|(path, _)| !added_dirs.contains(parent(path)))
+            .map(|(path, is_directory)| {
+                let name = path.rsplit('/').next().unwrap_or(path);
+                FileTreeItem {
+                    name: name.to_string(),
+                    path: tree_path(root, path),
+                    is_directory,
+                    icon: icons.icon_for_name(name, is_directory),
+                }
+            })
+            .collect();
+        let removed = changes
+            .removed_dirs
+            .iter()
+            .chain(&changes.removed)
+            .filter(|path| !removed_dirs.contains(parent(path)))
+            .map(|path| tree_path(root, path))
+            .collect();
+
+        Self { added, removed }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Directory containing the relative path `path` ("" for the root)
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// The path the file tree shows for `relative`
+fn tree_path(root: &Path, relative: &str) -> String {
+    normalize_path(root.join(relative)).to_string_lossy().to_string()
+}
+
+/// The indexes of the open project
+#[derive(Default)]
+struct IndexState {
+    files: Option<FileIndex>,
+    symbols: Option<SymbolIndex>,
+    /// Set while the indexes of the open project are being built
+    building: bool,
+}
+
+/// The open project's indexes and the thread keeping them fresh
+pub struct ProjectIndex {
+    state: Arc<(Mutex<IndexState>, Condvar)>,
+    /// Stop flag of the thread maintaining the current indexes
+    stop: Mutex<Arc<AtomicBool>>,
+    app_handle: AppHandle,
+    progress: Arc<ProgressRegistry>,
+}
+
+/// Bring `symbols` up to date with the files in `files` and save it
+fn update_symbols(files: &FileIndex, symbols: &mut SymbolIndex) {
+    let update = symbols.update(files.paths());
+    if update.indexed > 0 || update.removed > 0 {
+        tracing::debug!(
+            "Symbol index: {} files indexed, {} removed",
+            update.indexed,
+            update.removed
+        );
+    }
+    if let Err(e) = symbols.save() {
+        tracing::warn!("Failed to save the symbol index: {}", e);
+    }
+}
+
+impl ProjectIndex {
+    /// Create an empty index; nothing is indexed until a project is opened
+    pub fn new(app_handle: AppHandle, progress: Arc<ProgressRegistry>) -> Self {
+        Self {
+            state: Arc::default(),
+            stop: Mutex::default(),
+            app_handle,
+            progress,
+        }
+    }
+
+    /// Index `root`, replacing the indexes of the previous project
+    ///
+    /// Entries reported by `tree-updated` get their icons from `icons`.
+    pub fn open(&self, root: PathBuf, ignore: IgnoreRules, icons: FileIcons) {
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let mut current = self.stop.lock().unwrap();
+            current.store(true, Ordering::Relaxed);
+            *current = stop.clone();
+        }
+        {
+            let (lock, _) = &*self.state;
+            let mut state = lock.lock().unwrap();
+            state.files = None;
+            state.symbols = None;
+            state.building = true;
+        }
+
+        let shared = Arc::clone(&self.state);
+        let app_handle = self.app_handle.clone();
+        let name = root.file_name().map_or_else(|| root.display().to_string(), |name| name.to_string_lossy().to_string());
+        let progress = self.progress.begin(format!("Indexing {}", name), false);
+        std::thread::spawn(move || {
+            let (lock, ready) = &*shared;
+            let started = std::time::Instant::now();
+            progress.report(None, Some("Scanning files".to_string()));
+            let built = FileIndex::build_skipping(&root, ignore, TREE_SKIPPED_DIRS).map(|files| {
+                progress.report(None, Some(format!("Indexing symbols in {} files", files.len())));
+                let mut symbols = SymbolIndex::load(&root);
+                update_symbols(&files, &mut symbols);
+                (files, symbols)
+            });
+            progress.finish(None);
+            {
+                // Checked under the lock, so a project opened meanwhile
+                // cannot be handed this project's indexes
+                let mut state = lock.lock().unwrap();
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                state.building = false;
+                match built {
+                    Ok((files, symbols)) => {
+                        tracing::info!(
+                            "Indexed {} files and {} symbols in {:?}",
+                            files.len(),
+                            symbols.len(),
+                            started.elapsed()
+                        );
+                        state.files = Some(files);
+                        state.symbols = Some(symbols);
+                    }
+                    Err(e) => tracing::warn!("Failed to index {}: {}", root.display(), e),
+                }
+            }
+            ready.notify_all();
+
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let mut state = lock.lock().unwrap();
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let IndexState {
+                    files: Some(files),
+                    symbols: Some(symbols),
+                    ..
+                } = &mut *state
+                else {
+                    break;
+                };
+                let update = match files.refresh() {
+                    Ok(changes) if !changes.is_empty() => {
+                        tracing::debug!(
+                            "File index: {} added, {} removed",
+                            changes.added.len(),
+                            changes.removed.len()
+                        );
+                        Some(TreeUpdate::from_changes(&root, &changes, &icons))
+                    }
+                    Ok(_) => None,
+                    Err(e) => {
+                        tracing::warn!("Failed to refresh the file index: {}", e);
+                        None
+                    }
+                };
+                update_symbols(files, symbols);
+                drop(state);
+
+                if let Some(update) = update.filter(|update| !update.is_empty()) {
+                    if let Err(e) = app_handle.emit(TREE_UPDATED, update) {
+                        tracing::warn!("Failed to emit {}: {}", TREE_UPDATED, e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Run `f` on the indexes once they are built
+    fn with_state<T>(&self, f: impl FnOnce(&IndexState) -> T) -> T {
+        let (lock, ready) = &*self.state;
+        let state = ready
+            .wait_while(lock.lock().unwrap(), |state| state.building)
+            .unwrap();
+        f(&state)
+    }
+
+    /// Entries of the directory `relative` ("" for the project root)
+    ///
+    /// Does not wait for the index: returns `None` while it is being built,
+    /// and for directories it does not hold, so the caller can read the
+    /// directory from disk instead.
+    pub fn entries(&self, relative: &str) -> Option<Vec<IndexEntry>> {
+        let (lock, _) = &*self.state;
+        let state = lock.lock().unwrap();
+        state.files.as_ref()?.entries(relative)
+    }
+
+    /// The `limit` files best matching `query`
+    ///
+    /// Waits for the index while it is being built; returns nothing if no
+    /// project is indexed.
+    pub fn find(&self, query: &str, limit: usize) -> Vec<FileMatch> {
+        self.with_state(|state| {
+            state
+                .files
+                .as_ref()
+                .map(|files| files.find(query, limit))
+                .unwrap_or_default()
+        })
+    }
+
+    /// The `limit` symbols best matching `query`, optionally only of `kind`
+    ///
+    /// Waits for the index while it is being built; returns nothing if no
+    /// project is indexed.
+    pub fn find_symbols(&self, query: &str, kind: Option<SymbolKind>, limit: usize) -> Vec<SymbolMatch> {
+        self.with_state(|state| {
+            state
+                .symbols
+                .as_ref()
+                .map(|symbols| symbols.find(query, kind, limit))
+                .unwrap_or_default()
+        })
+    }
+}
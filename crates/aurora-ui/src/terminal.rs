@@ -2,13 +2,23 @@
 //!
 //! Provides terminal emulation with support for PowerShell, WSL, and CMD on Windows.
 //! Uses portable-pty for cross-platform PTY support with event-based output.
+//!
+//! When an idle timeout is configured, a terminal left without input or
+//! output for that long, with nothing running in the foreground, has its
+//! shell shut down to free memory and emits `terminal-<id>-suspended`.
+//! Terminals whose foreground process cannot be detected (on Windows) are
+//! never suspended. Resizing it only records the new size; writing to it
+//! respawns the shell under the same ID, at the last size, in the directory
+//! the shell was last in, and emits `terminal-<id>-resumed`.
 
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use thiserror::Error;
 
@@ -31,16 +41,68 @@ pub struct TerminalSession {
     master: Box<dyn portable_pty::MasterPty + Send>,
     /// PTY writer (cloned from master)
     writer: Box<dyn Write + Send>,
+    /// Shell process
+    child: Box<dyn portable_pty::Child + Send + Sync>,
     /// Shell type for this terminal
     shell_type: ShellType,
     /// Terminal size
     size: PtySize,
+    /// Directory the shell was started in
+    working_dir: Option<String>,
+    /// Time of the last input or output
+    last_active: Arc<Mutex<Instant>>,
+    /// Set when the shell is shut down for being idle, so its reader thread
+    /// does not report the terminal as closed
+    suspended: Arc<AtomicBool>,
+}
+
+impl TerminalSession {
+    /// Whether a command other than the shell runs in the foreground, taken
+    /// to be the case when it cannot be told
+    #[cfg(unix)]
+    fn is_busy(&self) -> bool {
+        match (self.master.process_group_leader(), self.child.process_id()) {
+            (Some(leader), Some(shell)) => u32::try_from(leader).ok() != Some(shell),
+            _ => true,
+        }
+    }
+
+    /// The foreground process cannot be told apart from the shell here, so
+    /// a terminal is never taken to be idle
+    #[cfg(not(unix))]
+    fn is_busy(&self) -> bool {
+        true
+    }
+
+    /// The shell's current directory, falling back to the one it started in
+    /// where it cannot be read
+    fn current_dir(&self) -> Option<String> {
+        #[cfg(target_os = "linux")]
+        if let Some(dir) = self
+            .child
+            .process_id()
+            .and_then(|pid| std::fs::read_link(format!("/proc/{}/cwd", pid)).ok())
+            .and_then(|dir| dir.into_os_string().into_string().ok())
+        {
+            return Some(dir);
+        }
+        self.working_dir.clone()
+    }
+}
+
+/// A terminal whose shell was shut down for being idle
+struct SuspendedTerminal {
+    shell_type: ShellType,
+    size: PtySize,
+    working_dir: Option<String>,
 }
 
 /// Terminal manager state
 pub struct TerminalManager {
     sessions: Arc<Mutex<HashMap<TerminalId, TerminalSession>>>,
+    suspended: Arc<Mutex<HashMap<TerminalId, SuspendedTerminal>>>,
     next_id: Arc<Mutex<usize>>,
+    idle_timeout: Mutex<Option<Duration>>,
     app_handle: AppHandle,
 }
 
@@ -71,7 +133,9 @@ impl TerminalManager {
     pub fn new(app_handle: AppHandle) -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            suspended: Arc::new(Mutex::new(HashMap::new())),
             next_id: Arc::new(Mutex::new(0)),
+            idle_timeout: Mutex::new(None),
             app_handle,
         }
     }
@@ -143,16 +207,29 @@ impl TerminalManager {
         rows: u16,
         working_dir: Option<String>,
     ) -> Result<TerminalId, TerminalError> {
-        let pty_system = native_pty_system();
-
-        // Create PTY with specified size
         let size = PtySize {
             rows,
             cols,
             pixel_width: 0,
             pixel_height: 0,
         };
+        let id = self.next_id();
+        self.spawn_session(&id, shell_type, size, working_dir)?;
+        tracing::info!("Spawned terminal {} with shell {:?}", id, shell_type);
+        Ok(id)
+    }
+
+    /// Start a shell in a new PTY and store it as terminal `id`
+    fn spawn_session(
+        &self,
+        id: &TerminalId,
+        shell_type: ShellType,
+        size: PtySize,
+        working_dir: Option<String>,
+    ) -> Result<(), TerminalError> {
+        let pty_system = native_pty_system();
 
+        // Create PTY with specified size
         let pair = pty_system
             .openpty(size)
             .map_err(|e| TerminalError::SpawnFailed(format!("Failed to create PTY: {}", e)))?;
@@ -161,12 +238,12 @@ impl TerminalManager {
         let mut cmd = self.build_shell_command(&shell_type)?;
 
         // Set working directory if provided
-        if let Some(dir) = working_dir {
+        if let Some(dir) = &working_dir {
             cmd.cwd(dir);
         }
 
         // Spawn the shell process
-        let _child = pair
+        let child = pair
             .slave
             .spawn_command(cmd)
             .map_err(|e| TerminalError::SpawnFailed(format!("Failed to spawn shell: {}", e)))?;
@@ -186,15 +263,18 @@ impl TerminalManager {
             .try_clone_reader()
             .map_err(|e| TerminalError::SpawnFailed(format!("Failed to get reader: {}", e)))?;
 
-        // Generate ID
-        let id = self.next_id();
-
         // Create session
+        let last_active = Arc::new(Mutex::new(Instant::now()));
+        let suspended = Arc::new(AtomicBool::new(false));
         let session = TerminalSession {
             master: pair.master,
             writer,
+            child,
             shell_type,
             size,
+            working_dir,
+            last_active: Arc::clone(&last_active),
+            suspended: Arc::clone(&suspended),
         };
 
         // Store session
@@ -211,6 +291,7 @@ impl TerminalManager {
             loop {
                 // Use the raw reader directly without BufReader
                 match reader.read(&mut buffer) {
+                    Ok(0) if suspended.load(Ordering::Relaxed) => break,
                     Ok(0) => {
                         // EOF - terminal closed
                         tracing::info!("Terminal {} closed (EOF)", terminal_id);
@@ -222,6 +303,7 @@ impl TerminalManager {
                     Ok(n) => {
                         // Got data - emit it to frontend
                         tracing::debug!("Terminal {} read {} bytes", terminal_id, n);
+                        *last_active.lock().unwrap() = Instant::now();
 
                         // Convert to string, replacing invalid UTF-8 with replacement character
                         let data = String::from_utf8_lossy(&buffer[..n]).to_string();
@@ -238,6 +320,7 @@ impl TerminalManager {
                         std::thread::sleep(std::time::Duration::from_millis(10));
                         continue;
                     }
+                    Err(_) if suspended.load(Ordering::Relaxed) => break,
                     Err(e) => {
                         tracing::error!("Failed to read from terminal {}: {} (kind: {:?})", terminal_id, e, e.kind());
                         if let Err(emit_err) = app_handle.emit_to(
@@ -255,8 +338,76 @@ impl TerminalManager {
             tracing::info!("Terminal {} reader thread exiting", terminal_id);
         });
 
-        tracing::info!("Spawned terminal {} with shell {:?}", id, shell_type);
-        Ok(id)
+        Ok(())
+    }
+
+    /// Set how long a terminal may stay idle before its shell is shut down
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        *self.idle_timeout.lock().unwrap() = timeout;
+    }
+
+    /// Shut down the shells of terminals idle longer than the idle timeout
+    ///
+    /// Terminals running a foreground command are left alone however long
+    /// they are quiet. Returns the IDs of the suspended terminals.
+    pub fn suspend_idle(&self) -> Vec<TerminalId> {
+        let Some(timeout) = *self.idle_timeout.lock().unwrap() else {
+            return Vec::new();
+        };
+        let idle: Vec<(TerminalId, TerminalSession)> = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let ids: Vec<TerminalId> = sessions
+                .iter()
+                .filter(|(_, session)| session.last_active.lock().unwrap().elapsed() >= timeout && !session.is_busy())
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.into_iter()
+                .filter_map(|id| sessions.remove(&id).map(|session| (id, session)))
+                .collect()
+        };
+
+        let mut suspended = Vec::new();
+        for (id, mut session) in idle {
+            // Read the directory before the shell exits and takes it along
+            let terminal = SuspendedTerminal {
+                shell_type: session.shell_type,
+                size: session.size,
+                working_dir: session.current_dir(),
+            };
+            session.suspended.store(true, Ordering::Relaxed);
+            if let Err(e) = session.child.kill() {
+                tracing::warn!("Failed to stop idle terminal {}: {}", id, e);
+            }
+            // Reap the shell; it may take a moment to exit after the hangup
+            if let Ok(None) = session.child.try_wait() {
+                thread::spawn(move || {
+                    let _ = session.child.wait();
+                });
+            }
+            self.suspended.lock().unwrap().insert(id.clone(), terminal);
+            if let Err(e) = self.app_handle.emit_to("main", &format!("terminal-{}-suspended", id), ()) {
+                tracing::error!("Failed to emit suspend event: {}", e);
+            }
+            tracing::info!("Suspended idle terminal {}", id);
+            suspended.push(id);
+        }
+        suspended
+    }
+
+    /// Respawn the shell of a terminal suspended for being idle
+    fn resume_if_suspended(&self, id: &TerminalId) -> Result<(), TerminalError> {
+        let Some(terminal) = self.suspended.lock().unwrap().remove(id) else {
+            return Ok(());
+        };
+        if let Err(e) = self.spawn_session(id, terminal.shell_type, terminal.size, terminal.working_dir.clone()) {
+            self.suspended.lock().unwrap().insert(id.clone(), terminal);
+            return Err(e);
+        }
+        if let Err(e) = self.app_handle.emit_to("main", &format!("terminal-{}-resumed", id), ()) {
+            tracing::error!("Failed to emit resume event: {}", e);
+        }
+        tracing::info!("Resumed terminal {}", id);
+        Ok(())
     }
 
     /// Build shell command based on shell type
@@ -304,10 +455,12 @@ impl TerminalManager {
 
     /// Write data to terminal
     pub fn write_terminal(&self, id: &TerminalId, data: &str) -> Result<(), TerminalError> {
+        self.resume_if_suspended(id)?;
         let mut sessions = self.sessions.lock().unwrap();
         let session = sessions
             .get_mut(id)
             .ok_or_else(|| TerminalError::NotFound(id.clone()))?;
+        *session.last_active.lock().unwrap() = Instant::now();
 
         session
             .writer
@@ -329,11 +482,6 @@ impl TerminalManager {
         cols: u16,
         rows: u16,
     ) -> Result<(), TerminalError> {
-        let mut sessions = self.sessions.lock().unwrap();
-        let session = sessions
-            .get_mut(id)
-            .ok_or_else(|| TerminalError::NotFound(id.clone()))?;
-
         let new_size = PtySize {
             rows,
             cols,
@@ -341,6 +489,18 @@ impl TerminalManager {
             pixel_height: 0,
         };
 
+        // A suspended terminal keeps the size for when its shell is respawned
+        if let Some(terminal) = self.suspended.lock().unwrap().get_mut(id) {
+            terminal.size = new_size;
+            return Ok(());
+        }
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| TerminalError::NotFound(id.clone()))?;
+        *session.last_active.lock().unwrap() = Instant::now();
+
         session
             .master
             .resize(new_size)
@@ -352,23 +512,39 @@ impl TerminalManager {
 
     /// Close terminal session
     pub fn close_terminal(&self, id: &TerminalId) -> Result<(), TerminalError> {
-        let mut sessions = self.sessions.lock().unwrap();
-        sessions
-            .remove(id)
-            .ok_or_else(|| TerminalError::NotFound(id.clone()))?;
+        let was_open = self.sessions.lock().unwrap().remove(id).is_some();
+        let was_suspended = self.suspended.lock().unwrap().remove(id).is_some();
+        if !was_open && !was_suspended {
+            return Err(TerminalError::NotFound(id.clone()));
+        }
 
         tracing::info!("Closed terminal {}", id);
         Ok(())
     }
 
-    /// Get list of active terminal IDs
-    pub fn list_terminals(&self) -> Vec<TerminalId> {
-        self.sessions
+    /// Close every terminal session, e.g. when switching projects
+    ///
+    /// Returns the IDs of the closed terminals.
+    pub fn close_all(&self) -> Vec<TerminalId> {
+        let mut closed: Vec<TerminalId> = self
+            .sessions
             .lock()
             .unwrap()
-            .keys()
-            .cloned()
-            .collect()
+            .drain()
+            .map(|(id, _)| id)
+            .collect();
+        closed.extend(self.suspended.lock().unwrap().drain().map(|(id, _)| id));
+        if !closed.is_empty() {
+            tracing::info!("Closed {} terminals", closed.len());
+        }
+        closed
+    }
+
+    /// Get list of open terminal IDs, including suspended ones
+    pub fn list_terminals(&self) -> Vec<TerminalId> {
+        let mut ids: Vec<TerminalId> = self.sessions.lock().unwrap().keys().cloned().collect();
+        ids.extend(self.suspended.lock().unwrap().keys().cloned());
+        ids
     }
 }
 
@@ -0,0 +1,76 @@
+//! Running task watchers
+//!
+//! Each watched task runs on its own thread until its stop flag is set. The
+//! registry keeps those flags so watchers can be stopped individually, listed,
+//! and all stopped when the project changes.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Stop flags of running watchers keyed by task name
+#[derive(Default)]
+pub struct WatchRegistry {
+    watchers: Mutex<BTreeMap<String, Arc<AtomicBool>>>,
+}
+
+impl WatchRegistry {
+    /// Register a watcher for `task`, stopping any watcher already running it
+    ///
+    /// Returns the stop flag for the new watcher.
+    pub fn start(&self, task: &str) -> Arc<AtomicBool> {
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Ok(mut watchers) = self.watchers.lock() {
+            if let Some(previous) = watchers.insert(task.to_string(), stop.clone()) {
+                previous.store(true, Ordering::Relaxed);
+            }
+        }
+        stop
+    }
+
+    /// Stop the watcher for `task`; returns false if none was running
+    pub fn stop(&self, task: &str) -> bool {
+        let Ok(mut watchers) = self.watchers.lock() else {
+            return false;
+        };
+        match watchers.remove(task) {
+            Some(stop) => {
+                stop.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a watcher that exited on its own
+    ///
+    /// Only removes the entry if it still belongs to the exiting watcher.
+    pub fn finished(&self, task: &str, stop: &Arc<AtomicBool>) {
+        if let Ok(mut watchers) = self.watchers.lock() {
+            if watchers
+                .get(task)
+                .is_some_and(|current| Arc::ptr_eq(current, stop))
+            {
+                watchers.remove(task);
+            }
+        }
+    }
+
+    /// Stop every watcher, e.g. when switching projects
+    pub fn stop_all(&self) {
+        if let Ok(mut watchers) = self.watchers.lock() {
+            for stop in watchers.values() {
+                stop.store(true, Ordering::Relaxed);
+            }
+            watchers.clear();
+        }
+    }
+
+    /// Names of the watched tasks
+    pub fn active(&self) -> Vec<String> {
+        self.watchers
+            .lock()
+            .map(|watchers| watchers.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}